@@ -0,0 +1,616 @@
+//! Token and dollar accounting for agent runs.
+//!
+//! When an agent run ends, its token usage is read from what the executor
+//! left behind and appended to `.workgraph/accounting/runs.jsonl`, one
+//! [`RunUsage`] per run and model. Two sources are understood:
+//!
+//! - a `usage.json` sidecar in the agent directory (its path is passed to
+//!   the agent as `$WG_USAGE_FILE`), which any executor can write;
+//! - Claude's `stream-json`/`json` output in `output.log`: the final
+//!   `result` message, or the usage of each assistant message if the run
+//!   was cut short.
+//!
+//! Tokens are priced with the [`ModelRegistry`]. A cost reported by the
+//! executor is only used for models the registry does not know.
+
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::fs::{self, OpenOptions};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+use crate::graph::WorkGraph;
+use crate::models::ModelRegistry;
+use crate::service::registry::AgentRegistry;
+
+/// Token counts for one model.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TokenCounts {
+    #[serde(default)]
+    pub input_tokens: u64,
+    #[serde(default)]
+    pub output_tokens: u64,
+    #[serde(default, alias = "cache_read_input_tokens")]
+    pub cache_read_tokens: u64,
+    #[serde(default, alias = "cache_creation_input_tokens")]
+    pub cache_write_tokens: u64,
+}
+
+impl TokenCounts {
+    pub fn add(&mut self, other: &TokenCounts) {
+        self.input_tokens += other.input_tokens;
+        self.output_tokens += other.output_tokens;
+        self.cache_read_tokens += other.cache_read_tokens;
+        self.cache_write_tokens += other.cache_write_tokens;
+    }
+
+    pub fn total(&self) -> u64 {
+        self.input_tokens + self.output_tokens + self.cache_read_tokens + self.cache_write_tokens
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.total() == 0
+    }
+}
+
+/// Usage of one model, as parsed from executor output.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ModelUsage {
+    pub model: Option<String>,
+    pub tokens: TokenCounts,
+    /// Cost the executor reported, if any
+    pub reported_cost: Option<f64>,
+}
+
+/// One ledger line: the usage of one model in one agent run.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RunUsage {
+    /// Agent run (registry ID, e.g. "agent-7")
+    pub run: String,
+    pub task_id: String,
+    /// Agency agent identity the task was assigned to
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub agent: Option<String>,
+    /// Role of that agent identity
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub role: Option<String>,
+    /// Registry model ID when known, else the model name as reported
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub model: Option<String>,
+    #[serde(flatten)]
+    pub tokens: TokenCounts,
+    /// Priced cost in USD, if the model or the executor provided a price
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub cost_usd: Option<f64>,
+    /// Where the usage came from: "usage.json" or "claude"
+    pub source: String,
+    pub recorded_at: String,
+}
+
+/// Dimension to roll usage up by.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum GroupBy {
+    #[default]
+    Task,
+    Role,
+    Agent,
+    Model,
+}
+
+impl std::str::FromStr for GroupBy {
+    type Err = anyhow::Error;
+    fn from_str(s: &str) -> Result<Self> {
+        match s.to_lowercase().as_str() {
+            "task" => Ok(GroupBy::Task),
+            "role" => Ok(GroupBy::Role),
+            "agent" => Ok(GroupBy::Agent),
+            "model" => Ok(GroupBy::Model),
+            other => anyhow::bail!(
+                "Unknown grouping '{}'. Must be: task, role, agent, model",
+                other
+            ),
+        }
+    }
+}
+
+/// Usage summed over a group of runs.
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct Rollup {
+    /// Group key; "(none)" for runs without one
+    pub key: String,
+    pub runs: usize,
+    #[serde(flatten)]
+    pub tokens: TokenCounts,
+    pub cost_usd: f64,
+    /// Runs whose cost could not be priced
+    pub unpriced_runs: usize,
+}
+
+/// Path of the usage ledger.
+pub fn ledger_path(dir: &Path) -> PathBuf {
+    dir.join("accounting").join("runs.jsonl")
+}
+
+/// Path of the usage sidecar an executor may write for an agent run.
+pub fn sidecar_path(dir: &Path, agent_id: &str) -> PathBuf {
+    dir.join("agents").join(agent_id).join("usage.json")
+}
+
+/// Load all ledger records. A missing ledger is empty; unreadable lines are
+/// skipped.
+pub fn load(dir: &Path) -> Result<Vec<RunUsage>> {
+    let path = ledger_path(dir);
+    if !path.exists() {
+        return Ok(Vec::new());
+    }
+    let content =
+        fs::read_to_string(&path).with_context(|| format!("Failed to read {}", path.display()))?;
+    Ok(content
+        .lines()
+        .filter(|l| !l.trim().is_empty())
+        .filter_map(|l| serde_json::from_str(l).ok())
+        .collect())
+}
+
+/// Append records to the ledger.
+pub fn append(dir: &Path, records: &[RunUsage]) -> Result<()> {
+    if records.is_empty() {
+        return Ok(());
+    }
+    let path = ledger_path(dir);
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    let mut file = OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(&path)
+        .with_context(|| format!("Failed to open {}", path.display()))?;
+    let mut buf = String::new();
+    for record in records {
+        buf.push_str(&serde_json::to_string(record)?);
+        buf.push('\n');
+    }
+    file.write_all(buf.as_bytes())?;
+    Ok(())
+}
+
+#[derive(Deserialize)]
+struct SidecarEntry {
+    #[serde(default)]
+    model: Option<String>,
+    #[serde(flatten)]
+    tokens: TokenCounts,
+    #[serde(default)]
+    cost_usd: Option<f64>,
+}
+
+/// Parse a `usage.json` sidecar: one object or a list of objects with
+/// `model`, `input_tokens`, `output_tokens`, `cache_read_tokens`,
+/// `cache_write_tokens` and `cost_usd`, all optional.
+pub fn parse_sidecar(content: &str) -> Result<Vec<ModelUsage>, String> {
+    let value: serde_json::Value =
+        serde_json::from_str(content).map_err(|e| format!("invalid usage.json: {}", e))?;
+    let entries: Vec<SidecarEntry> = match value {
+        serde_json::Value::Array(_) => serde_json::from_value(value),
+        _ => serde_json::from_value(value).map(|e| vec![e]),
+    }
+    .map_err(|e| format!("invalid usage.json: {}", e))?;
+    Ok(entries
+        .into_iter()
+        .map(|e| ModelUsage {
+            model: e.model,
+            tokens: e.tokens,
+            reported_cost: e.cost_usd,
+        })
+        .collect())
+}
+
+/// Parse Claude `stream-json` (or single-object `json`) output.
+///
+/// `result` messages carry the run's totals, per model when `modelUsage` is
+/// present. Without one, the usage of each distinct assistant message is
+/// summed per model.
+pub fn parse_claude_output(content: &str) -> Vec<ModelUsage> {
+    let mut results: Vec<ModelUsage> = Vec::new();
+    let mut messages: BTreeMap<String, (Option<String>, TokenCounts)> = BTreeMap::new();
+    let mut last_model: Option<String> = None;
+
+    for (i, line) in content.lines().enumerate() {
+        let Ok(val) = serde_json::from_str::<serde_json::Value>(line.trim()) else {
+            continue;
+        };
+        match val.get("type").and_then(|t| t.as_str()) {
+            Some("assistant") => {
+                let Some(message) = val.get("message") else {
+                    continue;
+                };
+                let model = message
+                    .get("model")
+                    .and_then(|m| m.as_str())
+                    .map(String::from);
+                if model.is_some() {
+                    last_model = model.clone();
+                }
+                let Some(usage) = message.get("usage") else {
+                    continue;
+                };
+                let tokens: TokenCounts = serde_json::from_value(usage.clone()).unwrap_or_default();
+                // Partial messages repeat the same id; the last one counts
+                let id = message
+                    .get("id")
+                    .and_then(|m| m.as_str())
+                    .map(String::from)
+                    .unwrap_or_else(|| format!("line-{}", i));
+                messages.insert(id, (model, tokens));
+            }
+            Some("result") => {
+                let total_cost = val.get("total_cost_usd").and_then(|c| c.as_f64());
+                if let Some(per_model) = val.get("modelUsage").and_then(|m| m.as_object())
+                    && !per_model.is_empty()
+                {
+                    for (model, usage) in per_model {
+                        let field = |name: &str| usage.get(name).and_then(|v| v.as_u64());
+                        results.push(ModelUsage {
+                            model: Some(model.clone()),
+                            tokens: TokenCounts {
+                                input_tokens: field("inputTokens").unwrap_or(0),
+                                output_tokens: field("outputTokens").unwrap_or(0),
+                                cache_read_tokens: field("cacheReadInputTokens").unwrap_or(0),
+                                cache_write_tokens: field("cacheCreationInputTokens").unwrap_or(0),
+                            },
+                            reported_cost: usage.get("costUSD").and_then(|c| c.as_f64()),
+                        });
+                    }
+                } else if let Some(usage) = val.get("usage") {
+                    results.push(ModelUsage {
+                        model: last_model.clone(),
+                        tokens: serde_json::from_value(usage.clone()).unwrap_or_default(),
+                        reported_cost: total_cost,
+                    });
+                }
+            }
+            _ => {}
+        }
+    }
+
+    if !results.is_empty() {
+        return merge_by_model(results);
+    }
+    merge_by_model(
+        messages
+            .into_values()
+            .map(|(model, tokens)| ModelUsage {
+                model,
+                tokens,
+                reported_cost: None,
+            })
+            .collect(),
+    )
+}
+
+fn merge_by_model(usages: Vec<ModelUsage>) -> Vec<ModelUsage> {
+    let mut merged: Vec<ModelUsage> = Vec::new();
+    for usage in usages {
+        match merged.iter_mut().find(|m| m.model == usage.model) {
+            Some(existing) => {
+                existing.tokens.add(&usage.tokens);
+                existing.reported_cost = match (existing.reported_cost, usage.reported_cost) {
+                    (Some(a), Some(b)) => Some(a + b),
+                    (a, b) => a.or(b),
+                };
+            }
+            None => merged.push(usage),
+        }
+    }
+    merged.retain(|m| !m.tokens.is_empty() || m.reported_cost.is_some());
+    merged
+}
+
+/// Read the usage of a finished agent run from its directory. The sidecar
+/// wins over parsed output. Returns the usage and its source, or `None` if
+/// the run left no usage behind.
+pub fn read_run(agent_dir: &Path) -> Option<(Vec<ModelUsage>, &'static str)> {
+    if let Ok(content) = fs::read_to_string(agent_dir.join("usage.json")) {
+        match parse_sidecar(&content) {
+            Ok(usages) if !usages.is_empty() => return Some((usages, "usage.json")),
+            Ok(_) => {}
+            Err(e) => eprintln!("Warning: {}: {}", agent_dir.display(), e),
+        }
+    }
+    let output = fs::read_to_string(agent_dir.join("output.log")).ok()?;
+    let usages = parse_claude_output(&output);
+    (!usages.is_empty()).then_some((usages, "claude"))
+}
+
+/// Check if a process with the given PID is alive.
+#[cfg(unix)]
+fn is_pid_alive(pid: u32) -> bool {
+    unsafe { libc::kill(pid as i32, 0) == 0 }
+}
+
+#[cfg(not(unix))]
+fn is_pid_alive(_pid: u32) -> bool {
+    true
+}
+
+/// Record every finished agent run that is not in the ledger yet: runs the
+/// registry no longer counts as alive, or whose process has exited. Runs
+/// are attributed to the task's agent identity and its role. Returns the new
+/// records. The coordinator and reporting commands may sync at the same
+/// time, so the ledger is locked from reading it to appending.
+pub fn sync(dir: &Path, graph: &WorkGraph) -> Result<Vec<RunUsage>> {
+    let _lock = crate::parser::FileLock::acquire(dir.join("accounting").join("runs.lock"))?;
+    let registry = AgentRegistry::load(dir)?;
+    let recorded: HashSet<String> = load(dir)?.into_iter().map(|r| r.run).collect();
+    let models = ModelRegistry::load(dir).unwrap_or_default();
+    let agents_dir = dir.join("agency").join("agents");
+    let now = chrono::Utc::now().to_rfc3339();
+
+    let mut entries: Vec<_> = registry
+        .agents
+        .values()
+        .filter(|a| !recorded.contains(&a.id) && (!a.is_alive() || !is_pid_alive(a.pid)))
+        .collect();
+    entries.sort_by_key(|a| (a.started_at.clone(), a.id.clone()));
+
+    let mut records = Vec::new();
+    for entry in entries {
+        let Some((usages, source)) = read_run(&dir.join("agents").join(&entry.id)) else {
+            continue;
+        };
+        let agent = graph.get_task(&entry.task_id).and_then(|t| t.agent.clone());
+        let role = agent.as_ref().and_then(|hash| {
+            crate::agency::find_agent_by_prefix(&agents_dir, hash)
+                .ok()
+                .map(|a| a.role_id)
+        });
+        for usage in usages {
+            let model_name = usage.model.clone().or_else(|| entry.model.clone());
+            let priced = model_name.as_deref().and_then(|m| models.resolve(m));
+            records.push(RunUsage {
+                run: entry.id.clone(),
+                task_id: entry.task_id.clone(),
+                agent: agent.clone(),
+                role: role.clone(),
+                model: priced.map(|m| m.id.clone()).or(model_name),
+                tokens: usage.tokens,
+                cost_usd: priced
+                    .map(|m| m.price(&usage.tokens))
+                    .or(usage.reported_cost),
+                source: source.to_string(),
+                recorded_at: now.clone(),
+            });
+        }
+    }
+    append(dir, &records)?;
+    Ok(records)
+}
+
+/// The group a record belongs to under `by`; "(none)" if it has no value.
+pub fn group_key(record: &RunUsage, by: GroupBy) -> String {
+    match by {
+        GroupBy::Task => Some(&record.task_id),
+        GroupBy::Role => record.role.as_ref(),
+        GroupBy::Agent => record.agent.as_ref(),
+        GroupBy::Model => record.model.as_ref(),
+    }
+    .cloned()
+    .unwrap_or_else(|| "(none)".to_string())
+}
+
+/// Sum records by `by`, most expensive first.
+pub fn rollup(records: &[RunUsage], by: GroupBy) -> Vec<Rollup> {
+    let mut groups: BTreeMap<String, (Rollup, HashSet<&str>, HashSet<&str>)> = BTreeMap::new();
+    for record in records {
+        let key = group_key(record, by);
+        let (rollup, runs, unpriced) = groups.entry(key.clone()).or_insert_with(|| {
+            (
+                Rollup {
+                    key,
+                    ..Rollup::default()
+                },
+                HashSet::new(),
+                HashSet::new(),
+            )
+        });
+        rollup.tokens.add(&record.tokens);
+        rollup.cost_usd += record.cost_usd.unwrap_or(0.0);
+        runs.insert(&record.run);
+        if record.cost_usd.is_none() {
+            unpriced.insert(&record.run);
+        }
+    }
+    let mut rollups: Vec<Rollup> = groups
+        .into_values()
+        .map(|(mut rollup, runs, unpriced)| {
+            rollup.runs = runs.len();
+            rollup.unpriced_runs = unpriced.len();
+            rollup
+        })
+        .collect();
+    rollups.sort_by(|a, b| {
+        b.cost_usd
+            .partial_cmp(&a.cost_usd)
+            .unwrap_or(std::cmp::Ordering::Equal)
+            .then_with(|| a.key.cmp(&b.key))
+    });
+    rollups
+}
+
+/// Actual cost per task.
+pub fn cost_by_task(records: &[RunUsage]) -> HashMap<String, f64> {
+    let mut costs = HashMap::new();
+    for record in records {
+        *costs.entry(record.task_id.clone()).or_insert(0.0) += record.cost_usd.unwrap_or(0.0);
+    }
+    costs
+}
+
+/// Format a token count compactly (e.g. "12.3k", "1.2M").
+pub fn format_tokens(n: u64) -> String {
+    if n >= 1_000_000 {
+        format!("{:.1}M", n as f64 / 1_000_000.0)
+    } else if n >= 1_000 {
+        format!("{:.1}k", n as f64 / 1_000.0)
+    } else {
+        n.to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::graph::{Node, Task};
+    use tempfile::TempDir;
+
+    const STREAM: &str = r#"{"type":"system","subtype":"init"}
+{"type":"assistant","message":{"id":"m1","model":"claude-opus-4-6","usage":{"input_tokens":10,"cache_read_input_tokens":1000,"output_tokens":5}}}
+{"type":"assistant","message":{"id":"m1","model":"claude-opus-4-6","usage":{"input_tokens":10,"cache_read_input_tokens":1000,"output_tokens":50}}}
+{"type":"assistant","message":{"id":"m2","model":"claude-opus-4-6","usage":{"input_tokens":20,"output_tokens":100}}}
+[wrapper] Agent exited successfully, marking task done
+"#;
+
+    #[test]
+    fn test_parse_claude_output() {
+        // Cut short: assistant messages, deduplicated by id
+        let usages = parse_claude_output(STREAM);
+        assert_eq!(usages.len(), 1);
+        assert_eq!(usages[0].model.as_deref(), Some("claude-opus-4-6"));
+        assert_eq!(
+            usages[0].tokens,
+            TokenCounts {
+                input_tokens: 30,
+                output_tokens: 150,
+                cache_read_tokens: 1000,
+                cache_write_tokens: 0,
+            }
+        );
+
+        // A result message wins, split per model
+        let with_result = format!(
+            "{}{}\n",
+            STREAM,
+            r#"{"type":"result","total_cost_usd":0.5,"usage":{"input_tokens":1},"modelUsage":{"claude-opus-4-6":{"inputTokens":40,"outputTokens":200,"cacheReadInputTokens":2000,"cacheCreationInputTokens":300,"costUSD":0.4},"claude-haiku-4-5":{"inputTokens":7,"outputTokens":3,"costUSD":0.1}}}"#
+        );
+        let usages = parse_claude_output(&with_result);
+        assert_eq!(usages.len(), 2);
+        let opus = usages
+            .iter()
+            .find(|u| u.model.as_deref() == Some("claude-opus-4-6"))
+            .unwrap();
+        assert_eq!(opus.tokens.cache_write_tokens, 300);
+        assert_eq!(opus.reported_cost, Some(0.4));
+
+        assert!(parse_claude_output("plain text\n").is_empty());
+    }
+
+    #[test]
+    fn test_parse_sidecar() {
+        let one =
+            parse_sidecar(r#"{"model":"gpt-4o","input_tokens":100,"output_tokens":10}"#).unwrap();
+        assert_eq!(one.len(), 1);
+        assert_eq!(one[0].tokens.input_tokens, 100);
+        let many = parse_sidecar(
+            r#"[{"model":"a","output_tokens":1,"cost_usd":0.2},{"cache_read_input_tokens":5}]"#,
+        )
+        .unwrap();
+        assert_eq!(many[0].reported_cost, Some(0.2));
+        assert_eq!(many[1].tokens.cache_read_tokens, 5);
+        assert!(parse_sidecar("nope").is_err());
+    }
+
+    #[test]
+    fn test_sync_prices_and_rolls_up_finished_runs() {
+        let tmp = TempDir::new().unwrap();
+        let dir = tmp.path();
+        let mut registry = AgentRegistry::new();
+        for (task, model) in [("t1", "opus"), ("t2", "custom-model"), ("t3", "opus")] {
+            let id = registry.register_agent_with_model(1, task, "claude", "out", Some(model));
+            registry.set_status(&id, crate::service::registry::AgentStatus::Done);
+        }
+        registry.save(dir).unwrap();
+        // agent-3 is still running and must not be recorded
+        registry.set_status("agent-3", crate::service::registry::AgentStatus::Working);
+        registry.save(dir).unwrap();
+
+        let agent_dir = |id: &str| {
+            let d = dir.join("agents").join(id);
+            fs::create_dir_all(&d).unwrap();
+            d
+        };
+        fs::write(agent_dir("agent-1").join("output.log"), STREAM).unwrap();
+        fs::write(
+            agent_dir("agent-2").join("usage.json"),
+            r#"{"input_tokens":1000,"output_tokens":1000,"cost_usd":0.25}"#,
+        )
+        .unwrap();
+        fs::write(agent_dir("agent-3").join("output.log"), STREAM).unwrap();
+
+        let mut graph = WorkGraph::new();
+        for id in ["t1", "t2", "t3"] {
+            graph.add_node(Node::Task(Task {
+                id: id.to_string(),
+                ..Task::default()
+            }));
+        }
+
+        let records = sync(dir, &graph).unwrap();
+        assert_eq!(records.len(), 2);
+        let opus = &records[0];
+        assert_eq!(opus.model.as_deref(), Some("anthropic/claude-opus-4-6"));
+        // 30 in * $5 + 150 out * $25 + 1000 cache reads * $0.50 per 1M
+        let expected = (30.0 * 5.0 + 150.0 * 25.0 + 1000.0 * 0.5) / 1_000_000.0;
+        assert!((opus.cost_usd.unwrap() - expected).abs() < 1e-12);
+        // Unknown model: the reported cost is used
+        assert_eq!(records[1].model.as_deref(), Some("custom-model"));
+        assert_eq!(records[1].cost_usd, Some(0.25));
+
+        // Already recorded runs are not recorded again
+        assert!(sync(dir, &graph).unwrap().is_empty());
+        let all = load(dir).unwrap();
+        assert_eq!(all.len(), 2);
+
+        let by_model = rollup(&all, GroupBy::Model);
+        assert_eq!(by_model[0].key, "custom-model");
+        assert_eq!(by_model[1].tokens.output_tokens, 150);
+        let by_role = rollup(&all, GroupBy::Role);
+        assert_eq!(by_role.len(), 1);
+        assert_eq!(by_role[0].key, "(none)");
+        assert_eq!(by_role[0].runs, 2);
+        assert_eq!(cost_by_task(&all)["t2"], 0.25);
+    }
+
+    #[test]
+    fn test_concurrent_syncs_record_each_run_once() {
+        let tmp = TempDir::new().unwrap();
+        let dir = tmp.path().to_path_buf();
+        let mut registry = AgentRegistry::new();
+        let mut graph = WorkGraph::new();
+        for n in 0..5 {
+            let task = format!("t{}", n);
+            let id = registry.register_agent_with_model(1, &task, "claude", "out", Some("opus"));
+            registry.set_status(&id, crate::service::registry::AgentStatus::Done);
+            let agent_dir = dir.join("agents").join(&id);
+            fs::create_dir_all(&agent_dir).unwrap();
+            fs::write(agent_dir.join("output.log"), STREAM).unwrap();
+            graph.add_node(Node::Task(Task {
+                id: task,
+                ..Task::default()
+            }));
+        }
+        registry.save(&dir).unwrap();
+
+        let graph = std::sync::Arc::new(graph);
+        let syncs: Vec<_> = (0..8)
+            .map(|_| {
+                let (dir, graph) = (dir.clone(), std::sync::Arc::clone(&graph));
+                std::thread::spawn(move || sync(&dir, &graph).unwrap().len())
+            })
+            .collect();
+        let recorded: usize = syncs.into_iter().map(|s| s.join().unwrap()).sum();
+        assert_eq!(recorded, 5);
+        assert_eq!(load(&dir).unwrap().len(), 5);
+    }
+}
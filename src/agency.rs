@@ -1587,7 +1587,7 @@ pub fn capture_task_output(
     fs::create_dir_all(&output_dir)?;
 
     // 1. Git diff capture
-    capture_git_diff(&output_dir, task);
+    capture_git_diff(wg_dir, &output_dir, task);
 
     // 2. Artifact manifest
     capture_artifact_manifest(&output_dir, task);
@@ -1602,9 +1602,33 @@ pub fn capture_task_output(
 ///
 /// Uses `started_at` as the since-timestamp for `git diff`. If the project
 /// is not a git repo or the diff fails, writes an empty patch with a comment.
-fn capture_git_diff(output_dir: &Path, task: &crate::graph::Task) {
+/// A task that ran in its own worktree is diffed against the point its
+/// branch left the integration branch, so other agents' changes stay out.
+fn capture_git_diff(wg_dir: &Path, output_dir: &Path, task: &crate::graph::Task) {
     let patch_path = output_dir.join("changes.patch");
 
+    if crate::worktree::existing(wg_dir, &task.id).is_some() {
+        let config = crate::config::Config::load_or_default(wg_dir).worktree;
+        match crate::worktree::diff(wg_dir, &task.id, config.integration_branch.as_deref()) {
+            Ok(diff) => {
+                let content = if diff.is_empty() {
+                    "# No changes detected in git diff\n".to_string()
+                } else {
+                    diff
+                };
+                if let Err(e) = fs::write(&patch_path, content) {
+                    eprintln!(
+                        "Warning: failed to write patch file {}: {}",
+                        patch_path.display(),
+                        e
+                    );
+                }
+                return;
+            }
+            Err(e) => eprintln!("Warning: worktree diff failed, using project diff: {}", e),
+        }
+    }
+
     // Find the project root by walking up from the .workgraph dir
     let project_root = output_dir.ancestors().find(|p| p.join(".git").exists());
 
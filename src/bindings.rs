@@ -0,0 +1,452 @@
+//! Input bindings: values a task takes from the outputs of its dependencies
+//!
+//! A task publishes named outputs with `wg output set`; they are stored on
+//! the task in the graph. A task after it refers to one with
+//! `{{deps.<task-id>.outputs.<key>}}` in its description or `exec` command,
+//! and the placeholder is replaced with the value when an agent is spawned
+//! (see `TemplateVars`) or the command is run by `wg exec`.
+//!
+//! Only direct dependencies can be bound. A string output is substituted as
+//! is; any other JSON value is substituted as compact JSON. A shell command
+//! never contains the values themselves: each placeholder becomes a quoted
+//! reference to an environment variable (`WG_DEP_<TASK>_<KEY>`) set for the
+//! command, whether the placeholder sits bare or inside quotes, so a value is
+//! always one word and never run as shell syntax. Substitution is a single
+//! pass: a value that itself looks like a placeholder is left as written.
+
+use std::collections::BTreeMap;
+use std::fmt;
+
+use regex::Regex;
+use serde_json::Value;
+
+use crate::graph::{Status, Task, WorkGraph};
+
+/// A `{{deps.<task>.outputs.<key>}}` placeholder.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Binding {
+    /// The placeholder exactly as written
+    pub placeholder: String,
+    pub task_id: String,
+    pub key: String,
+}
+
+/// Why a binding has no value.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Problem {
+    /// The task it names is not in this task's `after` list
+    NotADependency,
+    /// The task it names does not exist
+    NoSuchTask,
+    /// The dependency hasn't published the key yet and is still running
+    Pending(Status),
+    /// The dependency finished without publishing the key
+    MissingKey(Status),
+}
+
+/// A binding that could not be resolved.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Unresolved {
+    pub binding: Binding,
+    pub problem: Problem,
+}
+
+impl Unresolved {
+    /// Whether the binding may still resolve without anyone changing the
+    /// graph by hand.
+    pub fn is_pending(&self) -> bool {
+        matches!(self.problem, Problem::Pending(_))
+    }
+}
+
+impl fmt::Display for Unresolved {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let Binding {
+            placeholder,
+            task_id,
+            key,
+        } = &self.binding;
+        match self.problem {
+            Problem::NotADependency => {
+                write!(f, "{}: '{}' is not a dependency", placeholder, task_id)
+            }
+            Problem::NoSuchTask => write!(f, "{}: no task '{}'", placeholder, task_id),
+            Problem::Pending(status) => write!(
+                f,
+                "{}: '{}' has not published '{}' yet (status: {})",
+                placeholder, task_id, key, status
+            ),
+            Problem::MissingKey(status) => write!(
+                f,
+                "{}: '{}' is {} but never published '{}'",
+                placeholder, task_id, status, key
+            ),
+        }
+    }
+}
+
+fn pattern() -> Regex {
+    Regex::new(r"\{\{\s*deps\.([^{}\s]+?)\.outputs\.([A-Za-z0-9_\-]+)\s*\}\}")
+        .expect("binding pattern is valid")
+}
+
+/// Every distinct binding in `text`, in order of first appearance.
+pub fn find(text: &str) -> Vec<Binding> {
+    let mut bindings: Vec<Binding> = Vec::new();
+    for caps in pattern().captures_iter(text) {
+        let placeholder = caps[0].to_string();
+        if bindings.iter().any(|b| b.placeholder == placeholder) {
+            continue;
+        }
+        bindings.push(Binding {
+            placeholder,
+            task_id: caps[1].to_string(),
+            key: caps[2].to_string(),
+        });
+    }
+    bindings
+}
+
+/// The bindings a task declares in its description and `exec` command.
+pub fn of_task(task: &Task) -> Vec<Binding> {
+    let mut bindings = Vec::new();
+    for text in [task.description.as_deref(), task.exec.as_deref()]
+        .into_iter()
+        .flatten()
+    {
+        for binding in find(text) {
+            if !bindings.contains(&binding) {
+                bindings.push(binding);
+            }
+        }
+    }
+    bindings
+}
+
+/// Resolve `task`'s bindings against the graph: the value of each one that
+/// resolves, keyed by placeholder, and the ones that don't.
+pub fn resolve(graph: &WorkGraph, task: &Task) -> (BTreeMap<String, String>, Vec<Unresolved>) {
+    let mut values = BTreeMap::new();
+    let mut unresolved = Vec::new();
+    for binding in of_task(task) {
+        let problem = if !task.after.contains(&binding.task_id) {
+            Problem::NotADependency
+        } else {
+            match graph.get_task(&binding.task_id) {
+                None => Problem::NoSuchTask,
+                Some(dep) => match dep.outputs.get(&binding.key) {
+                    Some(value) => {
+                        values.insert(binding.placeholder.clone(), render(value));
+                        continue;
+                    }
+                    None if dep.status.is_terminal() => Problem::MissingKey(dep.status),
+                    None => Problem::Pending(dep.status),
+                },
+            }
+        };
+        unresolved.push(Unresolved { binding, problem });
+    }
+    (values, unresolved)
+}
+
+/// Replace every resolved placeholder in `text`, e.g. a prompt.
+pub fn apply(text: &str, values: &BTreeMap<String, String>) -> String {
+    substitute(text, values, |value| value.to_string())
+}
+
+/// A shell command whose bindings refer to environment variables.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BoundCommand {
+    pub command: String,
+    /// The variables to set when running `command`
+    pub env: Vec<(String, String)>,
+}
+
+/// Replace every resolved placeholder in a shell command with a reference
+/// to an environment variable holding its value.
+pub fn apply_to_command(command: &str, values: &BTreeMap<String, String>) -> BoundCommand {
+    let env = env_vars(values);
+    let mut out = String::new();
+    let mut quote = Quote::None;
+    let mut last = 0;
+    for caps in pattern().captures_iter(command) {
+        let m = caps.get(0).expect("whole match");
+        quote = quote.after(&command[last..m.start()]);
+        out.push_str(&command[last..m.start()]);
+        last = m.end();
+        match env
+            .iter()
+            .find(|(placeholder, _, _)| placeholder == m.as_str())
+        {
+            Some((_, name, _)) => out.push_str(&match quote {
+                Quote::None => format!("\"${{{}}}\"", name),
+                Quote::Double => format!("${{{}}}", name),
+                Quote::Single => format!("'\"${{{}}}\"'", name),
+            }),
+            None => out.push_str(m.as_str()),
+        }
+    }
+    out.push_str(&command[last..]);
+    BoundCommand {
+        command: out,
+        env: env
+            .into_iter()
+            .map(|(_, name, value)| (name, value))
+            .collect(),
+    }
+}
+
+/// The environment variable for each resolved placeholder, as
+/// `(placeholder, name, value)`.
+fn env_vars(values: &BTreeMap<String, String>) -> Vec<(String, String, String)> {
+    let mut vars: Vec<(String, String, String)> = Vec::new();
+    for binding in values.keys().flat_map(|placeholder| find(placeholder)) {
+        let base = format!(
+            "WG_DEP_{}_{}",
+            env_name_part(&binding.task_id),
+            env_name_part(&binding.key)
+        );
+        // Different ids can map to the same name, e.g. `a-b` and `a_b`
+        let mut name = base.clone();
+        let mut n = 1;
+        while vars.iter().any(|(_, taken, _)| *taken == name) {
+            n += 1;
+            name = format!("{}_{}", base, n);
+        }
+        let value = values[&binding.placeholder].clone();
+        vars.push((binding.placeholder, name, value));
+    }
+    vars
+}
+
+fn env_name_part(s: &str) -> String {
+    s.chars()
+        .map(|c| {
+            if c.is_ascii_alphanumeric() {
+                c.to_ascii_uppercase()
+            } else {
+                '_'
+            }
+        })
+        .collect()
+}
+
+/// Shell quoting in effect at a point in a command.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Quote {
+    None,
+    Single,
+    Double,
+}
+
+impl Quote {
+    /// The quoting in effect after `text`.
+    fn after(self, text: &str) -> Quote {
+        let mut quote = self;
+        let mut chars = text.chars();
+        while let Some(c) = chars.next() {
+            quote = match (quote, c) {
+                (Quote::None | Quote::Double, '\\') => {
+                    chars.next();
+                    quote
+                }
+                (Quote::None, '\'') => Quote::Single,
+                (Quote::None, '"') => Quote::Double,
+                (Quote::Single, '\'') | (Quote::Double, '"') => Quote::None,
+                _ => quote,
+            };
+        }
+        quote
+    }
+}
+
+fn substitute(
+    text: &str,
+    values: &BTreeMap<String, String>,
+    render: impl Fn(&str) -> String,
+) -> String {
+    pattern()
+        .replace_all(text, |caps: &regex::Captures| match values.get(&caps[0]) {
+            Some(value) => render(value),
+            None => caps[0].to_string(),
+        })
+        .into_owned()
+}
+
+/// An output value as it is substituted into text.
+pub fn render(value: &Value) -> String {
+    match value {
+        Value::String(s) => s.clone(),
+        other => other.to_string(),
+    }
+}
+
+/// Check that `key` can be used in a binding.
+pub fn check_key(key: &str) -> Result<(), String> {
+    if key.is_empty()
+        || !key
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '-')
+    {
+        return Err(format!(
+            "invalid output key '{}': use letters, digits, '_' and '-'",
+            key
+        ));
+    }
+    Ok(())
+}
+
+/// Parse a `key=value` output assignment. The value is taken as JSON if it
+/// parses as JSON, else as a string, so `n=3` is a number and `n="3"` a
+/// string.
+pub fn parse_assignment(assignment: &str) -> Result<(String, Value), String> {
+    let (key, raw) = assignment
+        .split_once('=')
+        .ok_or_else(|| format!("expected key=value, got '{}'", assignment))?;
+    let key = key.trim();
+    check_key(key)?;
+    let value = serde_json::from_str(raw).unwrap_or_else(|_| Value::String(raw.to_string()));
+    Ok((key.to_string(), value))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::graph::Node;
+
+    fn task(id: &str) -> Task {
+        Task {
+            id: id.into(),
+            title: id.into(),
+            ..Task::default()
+        }
+    }
+
+    #[test]
+    fn test_find_bindings() {
+        let text = "Ship {{deps.build.outputs.binary_path}} ({{ deps.build.outputs.size }}), \
+                    again {{deps.build.outputs.binary_path}}; not {{task_id}}";
+        let found = find(text);
+        let keys: Vec<(&str, &str)> = found
+            .iter()
+            .map(|b| (b.task_id.as_str(), b.key.as_str()))
+            .collect();
+        assert_eq!(keys, [("build", "binary_path"), ("build", "size")]);
+        assert_eq!(found[1].placeholder, "{{ deps.build.outputs.size }}");
+    }
+
+    #[test]
+    fn test_resolve_and_apply() {
+        let mut build = task("build");
+        build.status = Status::Done;
+        build
+            .outputs
+            .insert("binary_path".into(), Value::String("target/wg".into()));
+        build.outputs.insert("size".into(), serde_json::json!(1024));
+        let mut lint = task("lint");
+        lint.status = Status::InProgress;
+        let mut deploy = task("deploy");
+        deploy.after = vec!["build".into(), "lint".into()];
+        deploy.description = Some(
+            "{{deps.build.outputs.binary_path}} {{deps.build.outputs.size}} \
+             {{deps.build.outputs.sha}} {{deps.lint.outputs.report}} {{deps.docs.outputs.url}}"
+                .into(),
+        );
+        deploy.exec = Some("scp {{deps.build.outputs.binary_path}} host:".into());
+
+        let mut graph = WorkGraph::new();
+        for t in [build, lint, deploy.clone()] {
+            graph.add_node(Node::Task(t));
+        }
+        let (values, unresolved) = resolve(&graph, &deploy);
+        let bound = apply_to_command(deploy.exec.as_deref().unwrap(), &values);
+        assert_eq!(bound.command, "scp \"${WG_DEP_BUILD_BINARY_PATH}\" host:");
+        assert!(
+            bound
+                .env
+                .contains(&("WG_DEP_BUILD_BINARY_PATH".into(), "target/wg".into()))
+        );
+        assert!(
+            apply(deploy.description.as_deref().unwrap(), &values).starts_with("target/wg 1024 ")
+        );
+        let problems: Vec<&Problem> = unresolved.iter().map(|u| &u.problem).collect();
+        assert_eq!(
+            problems,
+            [
+                &Problem::MissingKey(Status::Done),
+                &Problem::Pending(Status::InProgress),
+                &Problem::NotADependency,
+            ]
+        );
+        assert_eq!(
+            unresolved[0].to_string(),
+            "{{deps.build.outputs.sha}}: 'build' is done but never published 'sha'"
+        );
+    }
+
+    #[test]
+    fn test_command_values_are_never_shell_syntax() {
+        let evil = "x; rm -rf ~ $(touch pwned) `id` 'q' \"d\" ';touch pwned;'";
+        let values = BTreeMap::from([
+            ("{{deps.a.outputs.x}}".to_string(), evil.to_string()),
+            (
+                "{{deps.a.outputs.y}}".to_string(),
+                "{{deps.a.outputs.x}}".to_string(),
+            ),
+        ]);
+        // Bare, single-quoted and double-quoted placeholders
+        let bound = apply_to_command(
+            "printf '%s|' {{deps.a.outputs.x}} '{{deps.a.outputs.x}}' \
+             \"<{{deps.a.outputs.x}}>\" 'it''s {{deps.a.outputs.y}}'",
+            &values,
+        );
+        assert!(!bound.command.contains("rm -rf"));
+
+        let dir = tempfile::tempdir().unwrap();
+        let output = std::process::Command::new("sh")
+            .arg("-c")
+            .arg(&bound.command)
+            .envs(bound.env.iter().map(|(k, v)| (k, v)))
+            .current_dir(dir.path())
+            .output()
+            .unwrap();
+        assert_eq!(
+            String::from_utf8_lossy(&output.stdout),
+            format!("{0}|{0}|<{0}>|its {{{{deps.a.outputs.x}}}}|", evil)
+        );
+        assert!(!dir.path().join("pwned").exists());
+        assert_eq!(
+            apply("{{deps.a.outputs.y}}", &values),
+            "{{deps.a.outputs.x}}"
+        );
+    }
+
+    #[test]
+    fn test_env_names_do_not_collide() {
+        let values = BTreeMap::from([
+            ("{{deps.a-b.outputs.k}}".to_string(), "1".to_string()),
+            ("{{deps.a_b.outputs.k}}".to_string(), "2".to_string()),
+        ]);
+        let bound = apply_to_command("{{deps.a-b.outputs.k}} {{deps.a_b.outputs.k}}", &values);
+        assert_eq!(bound.command, "\"${WG_DEP_A_B_K}\" \"${WG_DEP_A_B_K_2}\"");
+        assert_eq!(bound.env.len(), 2);
+    }
+
+    #[test]
+    fn test_parse_assignment() {
+        assert_eq!(
+            parse_assignment("path=out/bin").unwrap(),
+            ("path".to_string(), Value::String("out/bin".into()))
+        );
+        assert_eq!(
+            parse_assignment("count=3").unwrap(),
+            ("count".to_string(), serde_json::json!(3))
+        );
+        assert_eq!(
+            parse_assignment("quoted=\"3\"").unwrap().1,
+            Value::String("3".into())
+        );
+        assert!(parse_assignment("novalue").is_err());
+        assert!(parse_assignment("bad key=1").is_err());
+    }
+}
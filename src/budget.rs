@@ -0,0 +1,409 @@
+//! Budget guardrails on agent spend.
+//!
+//! A resource with a [`Budget`] caps the dollar cost or tokens of agent runs
+//! as recorded in the usage ledger (see [`crate::accounting`]). Before the
+//! coordinator dispatches a task, every budget covering it must admit it.
+//! Spend only reaches the ledger once a run ends, so a task in progress holds
+//! its `requires_amounts` entry for the resource as a reservation; tasks
+//! that would push spend plus reservations over the cap wait.
+
+use anyhow::{Context, Result};
+use chrono::{DateTime, Datelike, NaiveTime, Utc};
+use serde::Serialize;
+use std::collections::{BTreeMap, BTreeSet};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::accounting::{RunUsage, format_tokens};
+use crate::graph::{Budget, BudgetWindow, Meter, Status, Task, WorkGraph};
+
+/// Current spend against one budget.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct BudgetUsage {
+    pub id: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+    #[serde(flatten)]
+    pub budget: Budget,
+    /// The cap (`available` on the resource)
+    pub available: f64,
+    /// Start of the current window (RFC 3339), if the budget resets
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub since: Option<String>,
+    /// Spend recorded in the window by all covered tasks
+    pub spent: f64,
+    /// Amounts held by covered tasks in progress
+    pub reserved: f64,
+    /// Spend per covered task in the window
+    #[serde(skip_serializing_if = "BTreeMap::is_empty")]
+    pub by_task: BTreeMap<String, f64>,
+}
+
+impl BudgetUsage {
+    /// Whether spend of `task` counts against this budget.
+    pub fn covers(&self, task: &Task) -> bool {
+        task.requires.contains(&self.id)
+            || self
+                .budget
+                .tag
+                .as_ref()
+                .is_none_or(|tag| task.tags.contains(tag))
+    }
+
+    /// Amount of this budget `task` holds while it runs.
+    pub fn held_by(&self, task: &Task) -> f64 {
+        task.requires_amounts.get(&self.id).copied().unwrap_or(0.0)
+    }
+
+    /// Spend counted against the cap when deciding on `task_id`: the task's
+    /// own for per-task budgets, otherwise the total plus reservations.
+    pub fn used_for(&self, task_id: &str) -> f64 {
+        if self.budget.per_task {
+            self.by_task.get(task_id).copied().unwrap_or(0.0)
+        } else {
+            self.spent + self.reserved
+        }
+    }
+
+    /// Whether `task` may be dispatched without going over the cap.
+    pub fn admits(&self, task: &Task) -> bool {
+        if !self.covers(task) {
+            return true;
+        }
+        let used = self.used_for(&task.id);
+        used < self.available && used + self.held_by(task) <= self.available
+    }
+
+    /// Count `task`'s holding as reserved, for a task just dispatched.
+    pub fn reserve(&mut self, task: &Task) {
+        if self.covers(task) && !self.budget.per_task {
+            self.reserved += self.held_by(task);
+        }
+    }
+
+    /// Whether recorded spend has reached a shared cap.
+    pub fn is_exhausted(&self) -> bool {
+        !self.budget.per_task && self.spent >= self.available
+    }
+
+    /// Tasks whose own spend has reached a per-task cap.
+    pub fn exhausted_tasks(&self) -> Vec<&str> {
+        if !self.budget.per_task {
+            return Vec::new();
+        }
+        self.by_task
+            .iter()
+            .filter(|(_, spent)| **spent >= self.available)
+            .map(|(id, _)| id.as_str())
+            .collect()
+    }
+
+    /// Share of the cap in use, in percent. For per-task budgets, the
+    /// largest share any one task has used.
+    pub fn percent(&self) -> f64 {
+        if self.available <= 0.0 {
+            return 100.0;
+        }
+        let used = if self.budget.per_task {
+            self.by_task.values().copied().fold(0.0, f64::max)
+        } else {
+            self.spent + self.reserved
+        };
+        used / self.available * 100.0
+    }
+
+    /// Format an amount in this budget's meter ("$1.25", "12.5k tokens").
+    pub fn format(&self, amount: f64) -> String {
+        match self.budget.meter {
+            Meter::Usd => format!("${:.2}", amount),
+            Meter::Tokens => format!("{} tokens", format_tokens(amount.max(0.0) as u64)),
+        }
+    }
+
+    /// One-line description of the budget's scope, e.g. "daily, tag ci, per task".
+    pub fn scope(&self) -> String {
+        let mut parts = vec![
+            self.budget
+                .window
+                .map(|w| w.to_string())
+                .unwrap_or_else(|| "total".to_string()),
+        ];
+        if let Some(tag) = &self.budget.tag {
+            parts.push(format!("tag {}", tag));
+        }
+        if self.budget.per_task {
+            parts.push("per task".to_string());
+        }
+        parts.join(", ")
+    }
+}
+
+/// Start of the window containing `now`, in UTC. Weeks start on Monday.
+pub fn window_start(window: BudgetWindow, now: DateTime<Utc>) -> DateTime<Utc> {
+    let today = now.date_naive();
+    let day = match window {
+        BudgetWindow::Daily => today,
+        BudgetWindow::Weekly => {
+            today - chrono::Duration::days(today.weekday().num_days_from_monday() as i64)
+        }
+        BudgetWindow::Monthly => today.with_day(1).unwrap_or(today),
+    };
+    day.and_time(NaiveTime::MIN).and_utc()
+}
+
+/// Spend of one ledger record in `meter`.
+fn metered(record: &RunUsage, meter: Meter) -> f64 {
+    match meter {
+        Meter::Usd => record.cost_usd.unwrap_or(0.0),
+        Meter::Tokens => record.tokens.total() as f64,
+    }
+}
+
+/// Usage of every budget in the graph as of `now`.
+pub fn usage(graph: &WorkGraph, runs: &[RunUsage], now: DateTime<Utc>) -> Vec<BudgetUsage> {
+    let mut usages: Vec<BudgetUsage> = graph
+        .resources()
+        .filter_map(|r| {
+            let budget = r.budget.clone()?;
+            let since = budget.window.map(|w| window_start(w, now));
+            Some(BudgetUsage {
+                id: r.id.clone(),
+                name: r.name.clone(),
+                budget,
+                available: r.available.unwrap_or(0.0),
+                since: since.map(|t| t.to_rfc3339()),
+                spent: 0.0,
+                reserved: 0.0,
+                by_task: BTreeMap::new(),
+            })
+        })
+        .collect();
+
+    for usage in &mut usages {
+        let since = usage
+            .since
+            .as_deref()
+            .and_then(|s| DateTime::parse_from_rfc3339(s).ok());
+        for record in runs {
+            if let Some(since) = since {
+                let recorded = DateTime::parse_from_rfc3339(&record.recorded_at);
+                if recorded.is_ok_and(|t| t < since) {
+                    continue;
+                }
+            }
+            // Runs of tasks no longer in the graph only count towards
+            // budgets that cover every task.
+            let covered = match graph.get_task(&record.task_id) {
+                Some(task) => usage.covers(task),
+                None => usage.budget.tag.is_none(),
+            };
+            if !covered {
+                continue;
+            }
+            let amount = metered(record, usage.budget.meter);
+            usage.spent += amount;
+            *usage.by_task.entry(record.task_id.clone()).or_default() += amount;
+        }
+        usage.reserved = graph
+            .tasks()
+            .filter(|t| t.status == Status::InProgress && usage.covers(t))
+            .map(|t| usage.held_by(t))
+            .sum();
+    }
+    usages.sort_by(|a, b| a.id.cmp(&b.id));
+    usages
+}
+
+/// The first budget that does not admit `task`, if any.
+pub fn blocking<'a>(usages: &'a [BudgetUsage], task: &Task) -> Option<&'a BudgetUsage> {
+    usages.iter().find(|u| !u.admits(task))
+}
+
+/// Keys of the exhausted budgets: the resource ID for a shared cap, or
+/// `<resource>/<task>` for a task that used up a per-task cap.
+pub fn exhausted_keys(usages: &[BudgetUsage]) -> BTreeSet<String> {
+    let mut keys = BTreeSet::new();
+    for usage in usages {
+        if usage.is_exhausted() {
+            keys.insert(usage.id.clone());
+        }
+        for task in usage.exhausted_tasks() {
+            keys.insert(format!("{}/{}", usage.id, task));
+        }
+    }
+    keys
+}
+
+/// Where the coordinator remembers which budgets it last saw exhausted.
+pub fn state_path(dir: &Path) -> PathBuf {
+    dir.join("service").join("budgets.json")
+}
+
+/// Budgets the coordinator last saw exhausted (see [`exhausted_keys`]).
+pub fn load_exhausted(dir: &Path) -> BTreeSet<String> {
+    fs::read_to_string(state_path(dir))
+        .ok()
+        .and_then(|content| serde_json::from_str(&content).ok())
+        .unwrap_or_default()
+}
+
+pub fn save_exhausted(dir: &Path, keys: &BTreeSet<String>) -> Result<()> {
+    let path = state_path(dir);
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent).context("Failed to create service directory")?;
+    }
+    let content = serde_json::to_string_pretty(keys)?;
+    fs::write(&path, content).with_context(|| format!("Failed to write {}", path.display()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::accounting::TokenCounts;
+    use crate::graph::{Node, Resource};
+
+    fn run(task_id: &str, cost: f64, tokens: u64, recorded_at: &str) -> RunUsage {
+        RunUsage {
+            run: format!("agent-{}", task_id),
+            task_id: task_id.to_string(),
+            agent: None,
+            role: None,
+            model: None,
+            tokens: TokenCounts {
+                input_tokens: tokens,
+                ..TokenCounts::default()
+            },
+            cost_usd: Some(cost),
+            source: "usage.json".to_string(),
+            recorded_at: recorded_at.to_string(),
+        }
+    }
+
+    fn budget_resource(id: &str, available: f64, budget: Budget) -> Node {
+        Node::Resource(Resource {
+            id: id.to_string(),
+            name: None,
+            resource_type: None,
+            available: Some(available),
+            unit: None,
+            budget: Some(budget),
+        })
+    }
+
+    fn task(id: &str, status: Status, tags: &[&str]) -> Task {
+        Task {
+            id: id.to_string(),
+            title: id.to_string(),
+            status,
+            tags: tags.iter().map(|t| t.to_string()).collect(),
+            ..Task::default()
+        }
+    }
+
+    #[test]
+    fn test_window_start() {
+        let now: DateTime<Utc> = "2026-03-12T15:30:00Z".parse().unwrap();
+        let start = |w| window_start(w, now).to_rfc3339();
+        assert_eq!(start(BudgetWindow::Daily), "2026-03-12T00:00:00+00:00");
+        // 2026-03-12 is a Thursday
+        assert_eq!(start(BudgetWindow::Weekly), "2026-03-09T00:00:00+00:00");
+        assert_eq!(start(BudgetWindow::Monthly), "2026-03-01T00:00:00+00:00");
+    }
+
+    #[test]
+    fn test_daily_cap_counts_window_and_reservations() {
+        let now: DateTime<Utc> = "2026-03-12T15:30:00Z".parse().unwrap();
+        let mut graph = WorkGraph::new();
+        graph.add_node(budget_resource(
+            "daily",
+            10.0,
+            Budget {
+                window: Some(BudgetWindow::Daily),
+                ..Budget::default()
+            },
+        ));
+        let mut running = task("running", Status::InProgress, &[]);
+        running.requires = vec!["daily".to_string()];
+        running.requires_amounts.insert("daily".to_string(), 3.0);
+        graph.add_node(Node::Task(running));
+        let mut next = task("next", Status::Open, &[]);
+        next.requires = vec!["daily".to_string()];
+        next.requires_amounts.insert("daily".to_string(), 2.0);
+        graph.add_node(Node::Task(next.clone()));
+        graph.add_node(Node::Task(task("plain", Status::Open, &[])));
+
+        let runs = vec![
+            run("old", 50.0, 0, "2026-03-11T23:59:00Z"),
+            run("plain", 4.0, 0, "2026-03-12T09:00:00Z"),
+        ];
+        let mut usages = usage(&graph, &runs, now);
+        assert_eq!(usages[0].spent, 4.0);
+        assert_eq!(usages[0].reserved, 3.0);
+        assert!(!usages[0].is_exhausted());
+        // 4 spent + 3 reserved + 2 held = 9 <= 10
+        assert!(usages[0].admits(&next));
+        usages[0].reserve(&next);
+        assert_eq!(usages[0].reserved, 5.0);
+        // 4 + 5 + 2 > 10
+        assert!(!usages[0].admits(&next));
+        assert!(usages[0].admits(graph.get_task("plain").unwrap()));
+
+        let runs = vec![run("plain", 10.0, 0, "2026-03-12T09:00:00Z")];
+        let usages = usage(&graph, &runs, now);
+        assert!(usages[0].is_exhausted());
+        assert!(blocking(&usages, graph.get_task("plain").unwrap()).is_some());
+        assert_eq!(
+            exhausted_keys(&usages),
+            BTreeSet::from(["daily".to_string()])
+        );
+    }
+
+    #[test]
+    fn test_tag_and_per_task_budgets() {
+        let now: DateTime<Utc> = "2026-03-12T15:30:00Z".parse().unwrap();
+        let mut graph = WorkGraph::new();
+        graph.add_node(budget_resource(
+            "ci-usd",
+            5.0,
+            Budget {
+                tag: Some("ci".to_string()),
+                ..Budget::default()
+            },
+        ));
+        graph.add_node(budget_resource(
+            "task-tokens",
+            1000.0,
+            Budget {
+                meter: Meter::Tokens,
+                per_task: true,
+                ..Budget::default()
+            },
+        ));
+        graph.add_node(Node::Task(task("lint", Status::Open, &["ci"])));
+        graph.add_node(Node::Task(task("docs", Status::Open, &[])));
+
+        let runs = vec![
+            run("lint", 6.0, 200, "2026-03-12T09:00:00Z"),
+            run("docs", 9.0, 1500, "2026-03-12T09:00:00Z"),
+            run("gone", 1.0, 10, "2026-03-12T09:00:00Z"),
+        ];
+        let usages = usage(&graph, &runs, now);
+        let (ci, tokens) = (&usages[0], &usages[1]);
+        assert_eq!(ci.spent, 6.0);
+        assert!(ci.is_exhausted());
+        assert!(!ci.admits(graph.get_task("lint").unwrap()));
+        assert!(ci.admits(graph.get_task("docs").unwrap()));
+
+        assert_eq!(tokens.spent, 1710.0);
+        assert!(!tokens.is_exhausted());
+        assert_eq!(tokens.exhausted_tasks(), vec!["docs"]);
+        assert!(tokens.admits(graph.get_task("lint").unwrap()));
+        assert!(!tokens.admits(graph.get_task("docs").unwrap()));
+        assert_eq!(tokens.percent(), 150.0);
+
+        assert_eq!(
+            exhausted_keys(&usages),
+            BTreeSet::from(["ci-usd".to_string(), "task-tokens/docs".to_string()])
+        );
+    }
+}
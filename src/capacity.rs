@@ -0,0 +1,381 @@
+//! Resources as counting semaphores.
+//!
+//! A resource with `available` capacity and no budget is a semaphore. A task
+//! that lists it in `requires` holds its `requires_amounts` entry for it, or
+//! one unit, while the task is in progress. Capacity is taken when the task
+//! is claimed and freed when it leaves in-progress, whether it finishes,
+//! fails, or its agent dies and the task is unclaimed. A task is only
+//! dispatched when every resource it requires has room for it.
+//!
+//! Whoever claims such a task holds [`lock_claim`] from a last check against
+//! the stored graph until the claim is saved, so two claims on different
+//! tasks sharing a resource can't both see room for themselves.
+
+use anyhow::Context;
+use serde::Serialize;
+use std::collections::BTreeMap;
+use std::path::Path;
+
+use crate::graph::{Resource, Status, Task, WorkGraph};
+use crate::parser::{FileLock, load_graph};
+
+/// Whether a resource limits how many tasks may hold it at once.
+pub fn is_semaphore(resource: &Resource) -> bool {
+    resource.available.is_some() && resource.budget.is_none()
+}
+
+/// Amount of a semaphore resource `task` holds while it runs.
+pub fn demand(task: &Task, resource_id: &str) -> f64 {
+    task.requires_amounts
+        .get(resource_id)
+        .copied()
+        .unwrap_or(1.0)
+}
+
+/// Capacity of one semaphore resource and who holds it.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Slot {
+    pub available: f64,
+    pub in_use: f64,
+    /// Tasks in progress holding the resource
+    pub holders: Vec<String>,
+}
+
+/// A resource a task is waiting on.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct ResourceWait {
+    pub resource: String,
+    /// Amount the task needs
+    pub needed: f64,
+    pub in_use: f64,
+    pub available: f64,
+    pub holders: Vec<String>,
+}
+
+impl ResourceWait {
+    /// Whether the task needs more than the resource has at all.
+    pub fn is_impossible(&self) -> bool {
+        self.needed > self.available
+    }
+}
+
+impl std::fmt::Display for ResourceWait {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if self.is_impossible() {
+            return write!(
+                f,
+                "{}: needs {}, but only {} exist",
+                self.resource, self.needed, self.available
+            );
+        }
+        write!(
+            f,
+            "{}: needs {}, {} of {} in use",
+            self.resource, self.needed, self.in_use, self.available
+        )?;
+        if !self.holders.is_empty() {
+            write!(f, " by {}", self.holders.join(", "))?;
+        }
+        Ok(())
+    }
+}
+
+/// Usage of every semaphore resource in a graph.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Capacity {
+    slots: BTreeMap<String, Slot>,
+}
+
+impl Capacity {
+    /// Capacity held by the graph's in-progress tasks.
+    pub fn of(graph: &WorkGraph) -> Self {
+        let slots = graph
+            .resources()
+            .filter(|r| is_semaphore(r))
+            .map(|r| {
+                let slot = Slot {
+                    available: r.available.unwrap_or(0.0),
+                    in_use: 0.0,
+                    holders: Vec::new(),
+                };
+                (r.id.clone(), slot)
+            })
+            .collect();
+        let mut capacity = Capacity { slots };
+        for task in graph.tasks().filter(|t| t.status == Status::InProgress) {
+            capacity.acquire(task);
+        }
+        for slot in capacity.slots.values_mut() {
+            slot.holders.sort();
+        }
+        capacity
+    }
+
+    pub fn get(&self, resource_id: &str) -> Option<&Slot> {
+        self.slots.get(resource_id)
+    }
+
+    /// Resources without room for `task`.
+    pub fn waits(&self, task: &Task) -> Vec<ResourceWait> {
+        task.requires
+            .iter()
+            .filter_map(|id| {
+                let slot = self.slots.get(id)?;
+                let needed = demand(task, id);
+                if slot.holders.contains(&task.id) || slot.in_use + needed <= slot.available {
+                    return None;
+                }
+                Some(ResourceWait {
+                    resource: id.clone(),
+                    needed,
+                    in_use: slot.in_use,
+                    available: slot.available,
+                    holders: slot.holders.clone(),
+                })
+            })
+            .collect()
+    }
+
+    /// Whether every resource `task` requires has room for it.
+    pub fn admits(&self, task: &Task) -> bool {
+        self.waits(task).is_empty()
+    }
+
+    /// Take `task`'s share of the resources it requires.
+    pub fn acquire(&mut self, task: &Task) {
+        for id in &task.requires {
+            if let Some(slot) = self.slots.get_mut(id)
+                && !slot.holders.contains(&task.id)
+            {
+                slot.in_use += demand(task, id);
+                slot.holders.push(task.id.clone());
+            }
+        }
+    }
+}
+
+/// A task that can't be claimed until resources it requires free up.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Unavailable {
+    pub task_id: String,
+    pub waits: Vec<ResourceWait>,
+}
+
+impl std::fmt::Display for Unavailable {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let reasons: Vec<String> = self.waits.iter().map(ToString::to_string).collect();
+        write!(
+            f,
+            "Task '{}' is waiting for resources: {}",
+            self.task_id,
+            reasons.join("; ")
+        )
+    }
+}
+
+impl std::error::Error for Unavailable {}
+
+/// Fail unless every resource `task` requires has room for it in `graph`.
+pub fn check(graph: &WorkGraph, task: &Task) -> Result<(), Unavailable> {
+    let waits = Capacity::of(graph).waits(task);
+    if waits.is_empty() {
+        Ok(())
+    } else {
+        Err(Unavailable {
+            task_id: task.id.clone(),
+            waits,
+        })
+    }
+}
+
+/// Held from the last capacity check until a claim is saved.
+pub struct ClaimGuard {
+    _lock: Option<FileLock>,
+}
+
+/// Before saving a claim of `task`: take the claim lock if the task requires
+/// any resources, and check them against the graph as stored now, which
+/// includes every claim saved by an earlier holder of the lock. Keep the
+/// guard until the claim is saved.
+pub fn lock_claim(workgraph_dir: &Path, task: &Task) -> anyhow::Result<ClaimGuard> {
+    if task.requires.is_empty() {
+        return Ok(ClaimGuard { _lock: None });
+    }
+    let lock = FileLock::acquire(workgraph_dir.join("claim.lock"))?;
+    let stored = load_graph(workgraph_dir.join("graph.jsonl")).context("Failed to load graph")?;
+    check(&stored, stored.get_task(&task.id).unwrap_or(task))?;
+    Ok(ClaimGuard { _lock: Some(lock) })
+}
+
+/// How long the unfinished work on a scarce resource takes at least, since
+/// at most `capacity` units of it can be held at once.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct ResourceBound {
+    pub resource: String,
+    pub capacity: f64,
+    /// Unfinished tasks requiring the resource
+    pub tasks: Vec<String>,
+    /// Estimated hours of those tasks, weighted by the amount each holds
+    pub unit_hours: f64,
+    /// `unit_hours / capacity`
+    pub bound_hours: f64,
+}
+
+/// Semaphore resources that unfinished tasks demand more of than exists,
+/// so those tasks cannot all run at once. Sorted by bound, longest first.
+pub fn resource_bounds(graph: &WorkGraph) -> Vec<ResourceBound> {
+    let mut bounds: Vec<ResourceBound> = graph
+        .resources()
+        .filter(|r| is_semaphore(r))
+        .filter_map(|resource| {
+            let capacity = resource.available.unwrap_or(0.0);
+            let mut tasks: Vec<&Task> = graph
+                .tasks()
+                .filter(|t| !t.status.is_terminal() && t.requires.contains(&resource.id))
+                .collect();
+            tasks.sort_by(|a, b| a.id.cmp(&b.id));
+            let total_demand: f64 = tasks.iter().map(|t| demand(t, &resource.id)).sum();
+            if capacity <= 0.0 || total_demand <= capacity {
+                return None;
+            }
+            let unit_hours: f64 = tasks
+                .iter()
+                .map(|t| {
+                    let hours = t.estimate.as_ref().and_then(|e| e.hours).unwrap_or(0.0);
+                    hours * demand(t, &resource.id)
+                })
+                .sum();
+            Some(ResourceBound {
+                resource: resource.id.clone(),
+                capacity,
+                tasks: tasks.iter().map(|t| t.id.clone()).collect(),
+                unit_hours,
+                bound_hours: unit_hours / capacity,
+            })
+        })
+        .collect();
+    bounds.sort_by(|a, b| {
+        b.bound_hours
+            .total_cmp(&a.bound_hours)
+            .then_with(|| a.resource.cmp(&b.resource))
+    });
+    bounds
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::graph::{Estimate, Node};
+
+    fn resource(id: &str, available: f64) -> Node {
+        Node::Resource(Resource {
+            id: id.to_string(),
+            name: None,
+            resource_type: None,
+            available: Some(available),
+            unit: None,
+            budget: None,
+        })
+    }
+
+    fn task(id: &str, status: Status, requires: &[&str], hours: f64) -> Task {
+        Task {
+            id: id.to_string(),
+            title: id.to_string(),
+            status,
+            requires: requires.iter().map(|r| r.to_string()).collect(),
+            estimate: Some(Estimate {
+                hours: Some(hours),
+                cost: None,
+            }),
+            ..Task::default()
+        }
+    }
+
+    #[test]
+    fn test_claims_see_each_others_saves() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("graph.jsonl");
+        let mut graph = WorkGraph::new();
+        graph.add_node(resource("gpu", 1.0));
+        graph.add_node(Node::Task(task("a", Status::Open, &["gpu"], 1.0)));
+        graph.add_node(Node::Task(task("b", Status::Open, &["gpu"], 1.0)));
+        crate::parser::save_graph(&graph, &path).unwrap();
+
+        // Both claimers loaded the graph before either saved
+        let mut first = load_graph(&path).unwrap();
+        let mut second = load_graph(&path).unwrap();
+        for (graph, id) in [(&first, "a"), (&second, "b")] {
+            assert!(check(graph, graph.get_task(id).unwrap()).is_ok());
+        }
+
+        first.get_task_mut("a").unwrap().status = Status::InProgress;
+        let claim = lock_claim(dir.path(), first.get_task("a").unwrap()).unwrap();
+        crate::parser::save_graph(&first, &path).unwrap();
+        drop(claim);
+
+        second.get_task_mut("b").unwrap().status = Status::InProgress;
+        let err = lock_claim(dir.path(), second.get_task("b").unwrap())
+            .err()
+            .unwrap();
+        assert_eq!(
+            err.to_string(),
+            "Task 'b' is waiting for resources: gpu: needs 1, 1 of 1 in use by a"
+        );
+    }
+
+    #[test]
+    fn test_capacity_counts_in_progress_holders() {
+        let mut graph = WorkGraph::new();
+        graph.add_node(resource("gpu", 2.0));
+        graph.add_node(resource("lock", 1.0));
+        graph.add_node(Node::Task(task("a", Status::InProgress, &["gpu"], 1.0)));
+        graph.add_node(Node::Task(task(
+            "done",
+            Status::Done,
+            &["gpu", "lock"],
+            1.0,
+        )));
+        let mut big = task("big", Status::Open, &["gpu"], 1.0);
+        big.requires_amounts.insert("gpu".to_string(), 2.0);
+        graph.add_node(Node::Task(big.clone()));
+        let b = task("b", Status::Open, &["gpu", "lock"], 1.0);
+        graph.add_node(Node::Task(b.clone()));
+
+        let mut capacity = Capacity::of(&graph);
+        assert_eq!(capacity.get("gpu").unwrap().in_use, 1.0);
+        assert_eq!(capacity.get("lock").unwrap().in_use, 0.0);
+
+        let waits = capacity.waits(&big);
+        assert_eq!(waits.len(), 1);
+        assert_eq!(waits[0].to_string(), "gpu: needs 2, 1 of 2 in use by a");
+        assert!(capacity.admits(&b));
+
+        capacity.acquire(&b);
+        capacity.acquire(&b);
+        assert_eq!(capacity.get("gpu").unwrap().in_use, 2.0);
+        assert!(!capacity.admits(&task("c", Status::Open, &["lock"], 1.0)));
+        // A holder is never waiting on its own resource
+        assert!(capacity.admits(&b));
+
+        big.requires_amounts.insert("gpu".to_string(), 3.0);
+        assert!(capacity.waits(&big)[0].is_impossible());
+    }
+
+    #[test]
+    fn test_resource_bounds_only_for_scarce_resources() {
+        let mut graph = WorkGraph::new();
+        graph.add_node(resource("lock", 1.0));
+        graph.add_node(resource("pool", 4.0));
+        for (id, hours) in [("x", 3.0), ("y", 2.0), ("z", 4.0)] {
+            graph.add_node(Node::Task(task(id, Status::Open, &["lock", "pool"], hours)));
+        }
+        graph.add_node(Node::Task(task("old", Status::Done, &["lock"], 50.0)));
+
+        let bounds = resource_bounds(&graph);
+        assert_eq!(bounds.len(), 1);
+        assert_eq!(bounds[0].resource, "lock");
+        assert_eq!(bounds[0].tasks, vec!["x", "y", "z"]);
+        assert_eq!(bounds[0].bound_hours, 9.0);
+    }
+}
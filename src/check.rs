@@ -393,6 +393,7 @@ mod tests {
             resource_type: Some("compute".to_string()),
             available: Some(4.0),
             unit: Some("GPUs".to_string()),
+            budget: None,
         };
 
         graph.add_node(Node::Task(task));
@@ -480,6 +481,7 @@ mod tests {
             resource_type: Some("budget".to_string()),
             available: Some(1000.0),
             unit: Some("USD".to_string()),
+            budget: None,
         };
 
         graph.add_node(Node::Task(task));
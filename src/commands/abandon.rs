@@ -37,6 +37,7 @@ pub fn run(dir: &Path, id: &str, reason: Option<&str>) -> Result<()> {
         message: log_message,
     });
 
+    let propagation = super::propagate(&mut graph, dir);
     save_graph(&graph, &path).context("Failed to save graph")?;
     super::notify_graph_changed(dir);
 
@@ -53,6 +54,7 @@ pub fn run(dir: &Path, id: &str, reason: Option<&str>) -> Result<()> {
 
     let reason_msg = reason.map(|r| format!(" ({})", r)).unwrap_or_default();
     println!("Marked '{}' as abandoned{}", id, reason_msg);
+    propagation.print();
 
     Ok(())
 }
@@ -1,13 +1,19 @@
 use anyhow::{Context, Result};
 use chrono::Utc;
+use std::collections::BTreeMap;
 use std::path::Path;
-use workgraph::graph::{CycleConfig, Estimate, Node, Status, Task, parse_delay};
+use workgraph::graph::{
+    CatchUp, CycleConfig, EdgeCondition, Estimate, Gate, MapSpec, Node, OutputContract, Schedule,
+    Status, Task, generate_id, parse_delay,
+};
 use workgraph::parser::{load_graph, save_graph};
+use workgraph::trace_function::TaskTemplate;
 
 use super::graph_path;
 
 /// Parse a guard expression string into a LoopGuard.
-/// Formats: 'task:<id>=<status>' or 'always'
+/// Formats: 'task:<id>=<status>', 'always', or a guard expression such as
+/// 'score(review) < 0.8 && iteration < 5' (see `workgraph::guard`)
 pub fn parse_guard_expr(expr: &str) -> Result<workgraph::graph::LoopGuard> {
     let expr = expr.trim();
     if expr.eq_ignore_ascii_case("always") {
@@ -35,34 +41,179 @@ pub fn parse_guard_expr(expr: &str) -> Result<workgraph::graph::LoopGuard> {
             expr
         );
     }
-    anyhow::bail!(
-        "Invalid guard expression '{}'. Expected 'task:<id>=<status>' or 'always'",
-        expr
-    );
+    match workgraph::guard::parse(expr) {
+        Ok(_) => Ok(workgraph::graph::LoopGuard::Expr(expr.to_string())),
+        Err(e) => anyhow::bail!("Invalid guard expression '{}': {}", expr, e),
+    }
 }
 
-#[allow(clippy::too_many_arguments)]
-pub fn run(
-    dir: &Path,
-    title: &str,
-    id: Option<&str>,
-    description: Option<&str>,
-    after: &[String],
-    assign: Option<&str>,
-    hours: Option<f64>,
-    cost: Option<f64>,
-    tags: &[String],
-    skills: &[String],
-    inputs: &[String],
-    deliverables: &[String],
-    max_retries: Option<u32>,
-    model: Option<&str>,
-    verify: Option<&str>,
-    max_iterations: Option<u32>,
-    cycle_guard: Option<&str>,
-    cycle_delay: Option<&str>,
-    visibility: &str,
-) -> Result<()> {
+/// Build and validate a schedule from `--schedule`, `--schedule-tz` and
+/// `--catch-up`.
+pub fn parse_schedule(
+    cron: &str,
+    timezone: Option<&str>,
+    catch_up: Option<&str>,
+) -> Result<Schedule> {
+    let schedule = Schedule {
+        cron: cron.trim().to_string(),
+        timezone: timezone.map(String::from),
+        catch_up: match catch_up {
+            Some(policy) => policy.parse::<CatchUp>().map_err(anyhow::Error::msg)?,
+            None => CatchUp::default(),
+        },
+        last_window: None,
+    };
+    workgraph::schedule::validate(&schedule)
+        .map_err(|e| anyhow::anyhow!("Invalid schedule '{}': {}", cron, e))?;
+    Ok(schedule)
+}
+
+/// Build and validate an approval gate from `--approvers` and `--quorum`.
+/// Without `--quorum`, every approver must approve.
+pub fn parse_gate(approvers: &[String], quorum: Option<u32>) -> Result<Gate> {
+    let approvers: Vec<String> = approvers
+        .iter()
+        .map(|a| a.trim().to_string())
+        .filter(|a| !a.is_empty())
+        .collect();
+    let gate = Gate {
+        quorum: quorum.unwrap_or(approvers.len().max(1) as u32),
+        approvers,
+        votes: vec![],
+        requested_at: None,
+    };
+    workgraph::gate::validate(&gate).map_err(anyhow::Error::msg)?;
+    Ok(gate)
+}
+
+/// Build an output contract from `--expect-file`, `--result-schema`,
+/// `--result-path`, `--check` and `--check-status`, or `None` if none of
+/// them were given. The schema is inline JSON or the path of a file holding it.
+pub fn parse_contract(
+    files: &[String],
+    result_schema: Option<&str>,
+    result_path: Option<&str>,
+    check: Option<&str>,
+    check_status: Option<i32>,
+) -> Result<Option<OutputContract>> {
+    let result_schema = result_schema
+        .map(|schema| {
+            let text = if schema.trim_start().starts_with(['{', '[']) || schema.trim() == "true" {
+                schema.to_string()
+            } else {
+                std::fs::read_to_string(schema)
+                    .with_context(|| format!("Failed to read schema file '{}'", schema))?
+            };
+            let schema: serde_json::Value =
+                serde_json::from_str(&text).context("Invalid --result-schema JSON")?;
+            workgraph::contract::check_schema(&schema)
+                .map_err(|e| anyhow::anyhow!("Invalid --result-schema: {}", e))?;
+            Ok::<_, anyhow::Error>(schema)
+        })
+        .transpose()?;
+    let contract = OutputContract {
+        files: files
+            .iter()
+            .map(|f| f.trim().to_string())
+            .filter(|f| !f.is_empty())
+            .collect(),
+        result_schema,
+        result_path: result_path.map(String::from),
+        check: check.map(String::from),
+        check_status: check_status.unwrap_or(0),
+    };
+    Ok((contract != OutputContract::default()).then_some(contract))
+}
+
+/// Parse a `--requires` value: a resource ID, optionally followed by
+/// `:AMOUNT` to hold while the task runs.
+pub fn parse_requirement(spec: &str) -> Result<(String, Option<f64>)> {
+    match spec.rsplit_once(':') {
+        Some((id, amount)) => {
+            let amount: f64 = amount
+                .trim()
+                .parse()
+                .ok()
+                .filter(|a: &f64| a.is_finite() && *a >= 0.0)
+                .ok_or_else(|| {
+                    anyhow::anyhow!(
+                        "Invalid amount in '{}'. Expected RESOURCE or RESOURCE:AMOUNT",
+                        spec
+                    )
+                })?;
+            Ok((id.trim().to_string(), Some(amount)))
+        }
+        None => Ok((spec.trim().to_string(), None)),
+    }
+}
+
+/// What `wg add` sets on a new task besides its title. Unset options leave
+/// the task's defaults.
+#[derive(Debug, Default)]
+pub struct AddOptions<'a> {
+    pub id: Option<&'a str>,
+    pub description: Option<&'a str>,
+    pub after: &'a [String],
+    pub conditional_after: &'a [(String, EdgeCondition)],
+    pub requires: &'a [(String, Option<f64>)],
+    pub assign: Option<&'a str>,
+    pub priority: Option<i32>,
+    pub requester: Option<&'a str>,
+    pub hours: Option<f64>,
+    pub cost: Option<f64>,
+    pub tags: &'a [String],
+    pub skills: &'a [String],
+    pub inputs: &'a [String],
+    pub deliverables: &'a [String],
+    pub max_retries: Option<u32>,
+    pub timeout: Option<&'a str>,
+    pub model: Option<&'a str>,
+    pub verify: Option<&'a str>,
+    pub max_iterations: Option<u32>,
+    pub cycle_guard: Option<&'a str>,
+    pub cycle_delay: Option<&'a str>,
+    pub map_over: Option<&'a str>,
+    pub map_from: Option<&'a str>,
+    pub parent: Option<&'a str>,
+    pub schedule: Option<Schedule>,
+    pub gate: Option<Gate>,
+    pub contract: Option<OutputContract>,
+    /// `internal` (the default), `public` or `peer`
+    pub visibility: Option<&'a str>,
+}
+
+pub fn run(dir: &Path, title: &str, options: AddOptions) -> Result<()> {
+    let AddOptions {
+        id,
+        description,
+        after,
+        conditional_after,
+        requires,
+        assign,
+        priority,
+        requester,
+        hours,
+        cost,
+        tags,
+        skills,
+        inputs,
+        deliverables,
+        max_retries,
+        timeout,
+        model,
+        verify,
+        max_iterations,
+        cycle_guard,
+        cycle_delay,
+        map_over,
+        map_from,
+        parent,
+        schedule,
+        gate,
+        contract,
+        visibility,
+    } = options;
+    let visibility = visibility.unwrap_or("internal");
     if title.trim().is_empty() {
         anyhow::bail!("Task title cannot be empty");
     }
@@ -96,8 +247,22 @@ pub fn run(
         None => generate_id(title, &graph),
     };
 
+    // Conditional edges are ordinary after edges plus a recorded condition.
+    // A map task waits for its source to succeed.
+    let mut after = after.to_vec();
+    let mut after_conditions = BTreeMap::new();
+    let map_source = map_over.map(|source| (source.to_string(), EdgeCondition::Success));
+    for (blocker_id, condition) in conditional_after.iter().chain(map_source.iter()) {
+        if !after.contains(blocker_id) {
+            after.push(blocker_id.clone());
+        }
+        if *condition != EdgeCondition::Any {
+            after_conditions.insert(blocker_id.clone(), *condition);
+        }
+    }
+
     // Validate after references (supports cross-repo peer:task-id syntax)
-    for blocker_id in after {
+    for blocker_id in &after {
         if blocker_id == &task_id {
             anyhow::bail!("Task '{}' cannot block itself", task_id);
         }
@@ -111,12 +276,42 @@ pub fn run(
         }
     }
 
+    // Required resources, with the amount each holds while the task runs
+    let mut requires_amounts = BTreeMap::new();
+    for (resource_id, amount) in requires {
+        if graph.get_resource(resource_id).is_none() {
+            anyhow::bail!("Resource '{}' does not exist", resource_id);
+        }
+        if let Some(amount) = amount {
+            requires_amounts.insert(resource_id.clone(), *amount);
+        }
+    }
+    let requires: Vec<String> = requires.iter().map(|(id, _)| id.clone()).collect();
+
+    if let Some(parent_id) = parent {
+        let Some(parent_task) = graph.get_task(parent_id) else {
+            anyhow::bail!("Parent task '{}' does not exist", parent_id);
+        };
+        if parent_task.status.is_terminal() {
+            eprintln!(
+                "Warning: parent '{}' is already {}",
+                parent_id, parent_task.status
+            );
+        }
+    }
+
     let estimate = if hours.is_some() || cost.is_some() {
         Some(Estimate { hours, cost })
     } else {
         None
     };
 
+    if let Some(t) = timeout {
+        parse_delay(t).ok_or_else(|| {
+            anyhow::anyhow!("Invalid timeout '{}'. Use format: 30s, 5m, 1h, 24h, 7d", t)
+        })?;
+    }
+
     // Build cycle config if --max-iterations specified
     let cycle_config = if let Some(max_iter) = max_iterations {
         let guard = match cycle_guard {
@@ -144,38 +339,65 @@ pub fn run(
         None
     };
 
+    // Build map spec if --map-over specified: this task's own fields are the
+    // template for each child
+    let map = match (map_over, map_from) {
+        (Some(source), Some(from)) => {
+            if graph.get_task(source).is_none() {
+                anyhow::bail!("Map source task '{}' does not exist", source);
+            }
+            Some(MapSpec {
+                source: source.to_string(),
+                from: from.to_string(),
+                template: TaskTemplate {
+                    template_id: task_id.clone(),
+                    title: title.to_string(),
+                    description: description.unwrap_or_default().to_string(),
+                    skills: skills.to_vec(),
+                    after: vec![],
+                    loops_to: vec![],
+                    role_hint: None,
+                    deliverables: deliverables.to_vec(),
+                    verify: verify.map(String::from),
+                    tags: tags.to_vec(),
+                },
+                children: vec![],
+                join: None,
+            })
+        }
+        (None, None) => None,
+        _ => anyhow::bail!("--map-over and --map-from must be used together"),
+    };
+
     let task = Task {
         id: task_id.clone(),
         title: title.to_string(),
         description: description.map(String::from),
-        status: Status::Open,
         assigned: assign.map(String::from),
+        priority: priority.unwrap_or(0),
+        requester: requester.map(String::from),
         estimate,
-        before: vec![],
-        after: after.to_vec(),
-        requires: vec![],
+        after: after.clone(),
+        after_conditions,
+        requires,
+        requires_amounts,
         tags: tags.to_vec(),
         skills: skills.to_vec(),
         inputs: inputs.to_vec(),
         deliverables: deliverables.to_vec(),
-        artifacts: vec![],
-        exec: None,
-        not_before: None,
         created_at: Some(Utc::now().to_rfc3339()),
-        started_at: None,
-        completed_at: None,
-        log: vec![],
-        retry_count: 0,
         max_retries,
-        failure_reason: None,
         model: model.map(String::from),
         verify: verify.map(String::from),
-        agent: None,
-        loop_iteration: 0,
         cycle_config,
-        ready_after: None,
-        paused: false,
         visibility: visibility.to_string(),
+        map,
+        parent: parent.map(String::from),
+        schedule,
+        timeout: timeout.map(String::from),
+        gate,
+        contract,
+        ..Task::default()
     };
 
     // Add task to graph
@@ -183,7 +405,7 @@ pub fn run(
 
     // Maintain bidirectional consistency: update `blocks` on referenced blocker tasks
     // (skip cross-repo refs — those live in a different graph)
-    for dep in after {
+    for dep in &after {
         if workgraph::federation::parse_remote_ref(dep).is_some() {
             continue; // Cross-repo dep; can't update remote graph's blocks field
         }
@@ -210,6 +432,28 @@ pub fn run(
     );
 
     println!("Added task: {} ({})", title, task_id);
+    if let Some(task) = graph.get_task(&task_id)
+        && let Some(ref schedule) = task.schedule
+        && let Ok(next) = workgraph::schedule::upcoming(schedule, Utc::now(), 1)
+        && let Some(next) = next.first()
+    {
+        println!(
+            "Scheduled: {} (next run {})",
+            schedule.cron,
+            workgraph::schedule::format_time(schedule, *next)
+        );
+    }
+    if let Some(gate) = graph.get_task(&task_id).and_then(|t| t.gate.as_ref()) {
+        if gate.approvers.is_empty() {
+            println!("Approval gate: anyone may approve");
+        } else {
+            println!(
+                "Approval gate: {} of {} must approve",
+                gate.quorum,
+                gate.approvers.join(", ")
+            );
+        }
+    }
     super::print_service_hint(dir);
     Ok(())
 }
@@ -266,6 +510,7 @@ pub fn run_remote(
             model: model.map(String::from),
             verify: verify.map(String::from),
             origin: Some(origin),
+            expected_revision: None,
         };
 
         let response = super::service::send_request(&resolved.workgraph_dir, &request)?;
@@ -324,7 +569,7 @@ fn add_task_directly(
     verify: Option<&str>,
     origin: &str,
 ) -> Result<String> {
-    use workgraph::graph::{Node, Status, Task};
+    use workgraph::graph::{Node, Task};
     use workgraph::parser::{load_graph, save_graph};
 
     let graph_path = super::graph_path(peer_workgraph_dir);
@@ -351,34 +596,15 @@ fn add_task_directly(
         id: task_id.clone(),
         title: title.to_string(),
         description: description.map(String::from),
-        status: Status::Open,
-        assigned: None,
-        estimate: None,
-        before: vec![],
         after: after.to_vec(),
-        requires: vec![],
         tags: tags.to_vec(),
         skills: skills.to_vec(),
-        inputs: vec![],
         deliverables: deliverables.to_vec(),
-        artifacts: vec![],
-        exec: None,
-        not_before: None,
         created_at: Some(chrono::Utc::now().to_rfc3339()),
-        started_at: None,
-        completed_at: None,
-        log: vec![],
-        retry_count: 0,
-        max_retries: None,
-        failure_reason: None,
         model: model.map(String::from),
         verify: verify.map(String::from),
-        agent: None,
-        loop_iteration: 0,
-        ready_after: None,
-        paused: false,
         visibility: "internal".to_string(),
-        cycle_config: None,
+        ..Task::default()
     };
 
     graph.add_node(Node::Task(task));
@@ -408,47 +634,6 @@ fn add_task_directly(
     Ok(task_id)
 }
 
-fn generate_id(title: &str, graph: &workgraph::WorkGraph) -> String {
-    // Generate a slug from the title
-    let slug: String = title
-        .to_lowercase()
-        .chars()
-        .map(|c| if c.is_alphanumeric() { c } else { '-' })
-        .collect::<String>()
-        .split('-')
-        .filter(|s| !s.is_empty())
-        .take(3)
-        .collect::<Vec<_>>()
-        .join("-");
-
-    let base_id = if slug.is_empty() {
-        "task".to_string()
-    } else {
-        slug
-    };
-
-    // Ensure uniqueness
-    if graph.get_node(&base_id).is_none() {
-        return base_id;
-    }
-
-    for i in 2..1000 {
-        let candidate = format!("{}-{}", base_id, i);
-        if graph.get_node(&candidate).is_none() {
-            return candidate;
-        }
-    }
-
-    // Fallback to timestamp
-    format!(
-        "task-{}",
-        std::time::SystemTime::now()
-            .duration_since(std::time::UNIX_EPOCH)
-            .map(|d| d.as_secs())
-            .unwrap_or(0)
-    )
-}
-
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -634,6 +819,22 @@ mod tests {
         assert!(msg.contains("Invalid guard expression"), "got: {msg}");
     }
 
+    #[test]
+    fn guard_expression_is_validated_and_kept() {
+        let g = parse_guard_expr(" score(review) < 0.8 && !exists(\"LGTM\") ").unwrap();
+        assert_eq!(
+            g,
+            workgraph::graph::LoopGuard::Expr(
+                "score(review) < 0.8 && !exists(\"LGTM\")".to_string()
+            )
+        );
+        let msg = parse_guard_expr("score(review) < high")
+            .unwrap_err()
+            .to_string();
+        assert!(msg.contains("Invalid guard expression"), "got: {msg}");
+        assert!(msg.contains("unknown name 'high'"), "got: {msg}");
+    }
+
     #[test]
     fn guard_empty_string_errors() {
         let result = parse_guard_expr("");
@@ -728,27 +929,7 @@ mod tests {
         let graph = WorkGraph::new();
         workgraph::parser::save_graph(&graph, &path).unwrap();
 
-        let result = run(
-            dir_path,
-            "",
-            None,
-            None,
-            &[],
-            None,
-            None,
-            None,
-            &[],
-            &[],
-            &[],
-            &[],
-            None,
-            None,
-            None,
-            None,
-            None,
-            None,
-            "internal",
-        );
+        let result = run(dir_path, "", AddOptions::default());
         assert!(result.is_err());
         assert!(result.unwrap_err().to_string().contains("cannot be empty"));
     }
@@ -762,27 +943,7 @@ mod tests {
         let graph = WorkGraph::new();
         workgraph::parser::save_graph(&graph, &path).unwrap();
 
-        let result = run(
-            dir_path,
-            "   ",
-            None,
-            None,
-            &[],
-            None,
-            None,
-            None,
-            &[],
-            &[],
-            &[],
-            &[],
-            None,
-            None,
-            None,
-            None,
-            None,
-            None,
-            "internal",
-        );
+        let result = run(dir_path, "   ", AddOptions::default());
         assert!(result.is_err());
         assert!(result.unwrap_err().to_string().contains("cannot be empty"));
     }
@@ -799,23 +960,13 @@ mod tests {
         let result = run(
             dir_path,
             "My task",
-            Some("my-task"),
-            None,
-            &["my-task".to_string()], // self-reference
-            None,
-            None,
-            None,
-            &[],
-            &[],
-            &[],
-            &[],
-            None,
-            None,
-            None,
-            None,
+            AddOptions {
+                id: Some("my-task"),
+                after: &["my-task".to_string()],
+                assign: // self-reference
             None,
-            None,
-            "internal",
+                ..Default::default()
+            },
         );
         assert!(result.is_err());
         assert!(
@@ -840,23 +991,10 @@ mod tests {
         let result = run(
             dir_path,
             "My task",
-            None,
-            None,
-            &["nonexistent".to_string()],
-            None,
-            None,
-            None,
-            &[],
-            &[],
-            &[],
-            &[],
-            None,
-            None,
-            None,
-            None,
-            None,
-            None,
-            "internal",
+            AddOptions {
+                after: &["nonexistent".to_string()],
+                ..Default::default()
+            },
         );
         assert!(result.is_ok());
     }
@@ -878,23 +1016,11 @@ mod tests {
         let result = run(
             dir_path,
             "Dependent task",
-            Some("dep-task"),
-            None,
-            &["blocker-a".to_string(), "blocker-b".to_string()],
-            None,
-            None,
-            None,
-            &[],
-            &[],
-            &[],
-            &[],
-            None,
-            None,
-            None,
-            None,
-            None,
-            None,
-            "internal",
+            AddOptions {
+                id: Some("dep-task"),
+                after: &["blocker-a".to_string(), "blocker-b".to_string()],
+                ..Default::default()
+            },
         );
         assert!(result.is_ok());
 
@@ -921,4 +1047,207 @@ mod tests {
             b.before
         );
     }
+
+    #[test]
+    fn conditional_after_records_conditions() {
+        let dir = tempfile::tempdir().unwrap();
+        let dir_path = dir.path();
+        let path = super::graph_path(dir_path);
+
+        let mut graph = WorkGraph::new();
+        graph.add_node(Node::Task(stub_task("build")));
+        graph.add_node(Node::Task(stub_task("lint")));
+        workgraph::parser::save_graph(&graph, &path).unwrap();
+
+        run(
+            dir_path,
+            "Rollback",
+            AddOptions {
+                id: Some("rollback"),
+                after: &["lint".to_string()],
+                conditional_after: &[
+                    ("build".to_string(), EdgeCondition::Failure),
+                    ("lint".to_string(), EdgeCondition::Any),
+                ],
+                ..Default::default()
+            },
+        )
+        .unwrap();
+
+        let graph = load_graph(&path).unwrap();
+        let task = graph.get_task("rollback").unwrap();
+        assert_eq!(task.after, vec!["lint", "build"]);
+        assert_eq!(task.after_condition("build"), EdgeCondition::Failure);
+        assert!(!task.after_conditions.contains_key("lint"));
+        assert!(
+            graph
+                .get_task("build")
+                .unwrap()
+                .before
+                .contains(&"rollback".to_string())
+        );
+    }
+
+    #[test]
+    fn requires_records_resources_and_amounts() {
+        let dir = tempfile::tempdir().unwrap();
+        let dir_path = dir.path();
+        let path = super::graph_path(dir_path);
+
+        let mut graph = WorkGraph::new();
+        for id in ["daily-usd", "gpu"] {
+            graph.add_node(Node::Resource(workgraph::graph::Resource {
+                id: id.to_string(),
+                name: None,
+                resource_type: None,
+                available: Some(10.0),
+                unit: None,
+                budget: None,
+            }));
+        }
+        workgraph::parser::save_graph(&graph, &path).unwrap();
+
+        assert_eq!(
+            parse_requirement("daily-usd:2.5").unwrap(),
+            ("daily-usd".to_string(), Some(2.5))
+        );
+        assert_eq!(parse_requirement("gpu").unwrap(), ("gpu".to_string(), None));
+        assert!(parse_requirement("gpu:lots").is_err());
+        assert!(parse_requirement("gpu:-1").is_err());
+
+        let requires = vec![
+            parse_requirement("daily-usd:2.5").unwrap(),
+            parse_requirement("gpu").unwrap(),
+        ];
+        let add = |id: &str, requires: &[(String, Option<f64>)]| {
+            run(
+                dir_path,
+                "Train",
+                AddOptions {
+                    id: Some(id),
+                    requires,
+                    ..Default::default()
+                },
+            )
+        };
+        add("train", &requires).unwrap();
+
+        let graph = load_graph(&path).unwrap();
+        let task = graph.get_task("train").unwrap();
+        assert_eq!(task.requires, vec!["daily-usd", "gpu"]);
+        assert_eq!(task.requires_amounts.get("daily-usd"), Some(&2.5));
+        assert!(!task.requires_amounts.contains_key("gpu"));
+
+        let missing = vec![("nope".to_string(), None)];
+        let err = add("train-2", &missing).unwrap_err();
+        assert!(err.to_string().contains("Resource 'nope' does not exist"));
+    }
+
+    #[test]
+    fn map_over_records_template_and_source_edge() {
+        let dir = tempfile::tempdir().unwrap();
+        let dir_path = dir.path();
+        let path = super::graph_path(dir_path);
+
+        let mut graph = WorkGraph::new();
+        graph.add_node(Node::Task(stub_task("discover")));
+        workgraph::parser::save_graph(&graph, &path).unwrap();
+
+        run(
+            dir_path,
+            "Port {{item}}",
+            AddOptions {
+                id: Some("port"),
+                description: Some("Port module {{item}} to the new API"),
+                skills: &["rust".to_string()],
+                map_over: Some("discover"),
+                map_from: Some("modules.json"),
+                ..Default::default()
+            },
+        )
+        .unwrap();
+
+        let graph = load_graph(&path).unwrap();
+        let task = graph.get_task("port").unwrap();
+        assert_eq!(task.after, vec!["discover"]);
+        assert_eq!(task.after_condition("discover"), EdgeCondition::Success);
+        let map = task.map.as_ref().unwrap();
+        assert_eq!(map.source, "discover");
+        assert_eq!(map.from, "modules.json");
+        assert_eq!(
+            map.template.description,
+            "Port module {{item}} to the new API"
+        );
+        assert_eq!(map.template.skills, vec!["rust"]);
+        assert!(!map.is_expanded());
+    }
+
+    #[test]
+    fn parent_links_task_to_epic() {
+        let dir = tempfile::tempdir().unwrap();
+        let dir_path = dir.path();
+        let path = super::graph_path(dir_path);
+
+        let mut graph = WorkGraph::new();
+        graph.add_node(Node::Task(stub_task("auth")));
+        workgraph::parser::save_graph(&graph, &path).unwrap();
+
+        let add_child = |id: &str, parent: &str| {
+            run(
+                dir_path,
+                "Child",
+                AddOptions {
+                    id: Some(id),
+                    parent: Some(parent),
+                    ..Default::default()
+                },
+            )
+        };
+        add_child("login", "auth").unwrap();
+        let err = add_child("logout", "missing").unwrap_err();
+        assert!(err.to_string().contains("does not exist"));
+
+        let graph = load_graph(&path).unwrap();
+        assert_eq!(
+            graph.get_task("login").unwrap().parent.as_deref(),
+            Some("auth")
+        );
+        assert!(graph.get_task("logout").is_none());
+        // The epic waits on its children rather than being dispatched
+        let ready: Vec<&str> = workgraph::query::ready_tasks(&graph)
+            .iter()
+            .map(|t| t.id.as_str())
+            .collect();
+        assert_eq!(ready, vec!["login"]);
+    }
+
+    #[test]
+    fn schedule_is_validated_and_keeps_template_out_of_ready() {
+        let schedule =
+            parse_schedule("0 9 * * mon-fri", Some("Europe/Berlin"), Some("skip")).unwrap();
+        assert_eq!(schedule.catch_up, CatchUp::Skip);
+        assert!(parse_schedule("0 9 * *", None, None).is_err());
+        assert!(parse_schedule("@daily", Some("Nowhere/City"), None).is_err());
+        let err = parse_schedule("@daily", None, Some("sometimes")).unwrap_err();
+        assert!(err.to_string().contains("Invalid catch-up policy"));
+
+        let dir = tempfile::tempdir().unwrap();
+        let path = super::graph_path(dir.path());
+        workgraph::parser::save_graph(&WorkGraph::new(), &path).unwrap();
+        run(
+            dir.path(),
+            "Standup notes",
+            AddOptions {
+                id: Some("standup"),
+                schedule: Some(schedule),
+                ..Default::default()
+            },
+        )
+        .unwrap();
+
+        let graph = load_graph(&path).unwrap();
+        let task = graph.get_task("standup").unwrap();
+        assert_eq!(task.schedule.as_ref().unwrap().cron, "0 9 * * mon-fri");
+        assert!(workgraph::query::ready_tasks(&graph).is_empty());
+    }
 }
@@ -1,7 +1,8 @@
 use anyhow::{Context, Result};
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use std::path::Path;
 
+use workgraph::accounting::{self, GroupBy, Rollup, RunUsage, format_tokens};
 use workgraph::agency::{self, Evaluation, Motivation, Role};
 use workgraph::parser::load_graph;
 
@@ -42,6 +43,21 @@ struct ModelStats {
     scores: Vec<f64>,
 }
 
+/// Actual spend of one role, agent or model next to the estimated cost of
+/// the tasks it ran on.
+struct SpendRow {
+    rollup: Rollup,
+    estimated: f64,
+}
+
+/// Spend rollups from the usage ledger.
+#[derive(Default)]
+struct Spend {
+    by_role: Vec<SpendRow>,
+    by_agent: Vec<SpendRow>,
+    by_model: Vec<SpendRow>,
+}
+
 /// Compute a simple trend indicator from recent scores.
 /// Returns "up", "down", "flat", or "-" if insufficient data.
 fn trend(scores: &[f64]) -> &'static str {
@@ -74,18 +90,33 @@ pub fn run(dir: &Path, json: bool, min_evals: u32, by_model: bool) -> Result<()>
     let evaluations =
         agency::load_all_evaluations(&evals_dir).context("Failed to load evaluations")?;
 
-    // Try to load graph for tag-based breakdown (non-fatal if missing)
+    // Try to load graph for tag-based breakdown and spend (non-fatal if missing)
     let graph_path = super::graph_path(dir);
-    let task_tags: HashMap<String, Vec<String>> = if graph_path.exists() {
-        match load_graph(&graph_path) {
-            Ok(graph) => graph
-                .tasks()
-                .map(|t| (t.id.clone(), t.tags.clone()))
-                .collect(),
-            Err(_) => HashMap::new(),
-        }
+    let graph = if graph_path.exists() {
+        load_graph(&graph_path).ok()
     } else {
-        HashMap::new()
+        None
+    };
+    let task_tags: HashMap<String, Vec<String>> = graph
+        .iter()
+        .flat_map(|g| g.tasks())
+        .map(|t| (t.id.clone(), t.tags.clone()))
+        .collect();
+    let estimates: HashMap<String, f64> = graph
+        .iter()
+        .flat_map(|g| g.tasks())
+        .filter_map(|t| Some((t.id.clone(), t.estimate.as_ref()?.cost?)))
+        .collect();
+    if let Some(ref graph) = graph
+        && let Err(e) = accounting::sync(dir, graph)
+    {
+        eprintln!("Warning: usage accounting failed: {}", e);
+    }
+    let records = accounting::load(dir).unwrap_or_default();
+    let spend = Spend {
+        by_role: build_spend(&records, GroupBy::Role, &estimates),
+        by_agent: build_spend(&records, GroupBy::Agent, &estimates),
+        by_model: build_spend(&records, GroupBy::Model, &estimates),
     };
 
     if json {
@@ -94,6 +125,7 @@ pub fn run(dir: &Path, json: bool, min_evals: u32, by_model: bool) -> Result<()>
             &motivations,
             &evaluations,
             &task_tags,
+            &spend,
             min_evals,
             by_model,
         )
@@ -103,6 +135,7 @@ pub fn run(dir: &Path, json: bool, min_evals: u32, by_model: bool) -> Result<()>
             &motivations,
             &evaluations,
             &task_tags,
+            &spend,
             min_evals,
             by_model,
         );
@@ -208,6 +241,25 @@ fn build_tag_breakdown(
     cells
 }
 
+fn build_spend(
+    records: &[RunUsage],
+    by: GroupBy,
+    estimates: &HashMap<String, f64>,
+) -> Vec<SpendRow> {
+    accounting::rollup(records, by)
+        .into_iter()
+        .map(|rollup| {
+            let tasks: HashSet<&str> = records
+                .iter()
+                .filter(|r| accounting::group_key(r, by) == rollup.key)
+                .map(|r| r.task_id.as_str())
+                .collect();
+            let estimated = tasks.iter().filter_map(|t| estimates.get(*t)).sum();
+            SpendRow { rollup, estimated }
+        })
+        .collect()
+}
+
 fn build_model_stats(evaluations: &[Evaluation]) -> Vec<ModelStats> {
     let mut map: HashMap<String, Vec<f64>> = HashMap::new();
     for eval in evaluations {
@@ -273,6 +325,7 @@ fn output_text(
     motivations: &[Motivation],
     evaluations: &[Evaluation],
     task_tags: &HashMap<String, Vec<String>>,
+    spend: &Spend,
     min_evals: u32,
     by_model: bool,
 ) {
@@ -299,6 +352,7 @@ fn output_text(
 
     if evaluations.is_empty() {
         println!("\nNo evaluations recorded yet. Run 'wg evaluate <task-id>' to generate data.");
+        print_spend(spend, by_model);
         return;
     }
 
@@ -460,6 +514,69 @@ fn output_text(
             );
         }
     }
+
+    // 8. Spend
+    print_spend(spend, by_model);
+}
+
+fn print_spend(spend: &Spend, by_model: bool) {
+    let mut sections = vec![("Role", &spend.by_role), ("Agent", &spend.by_agent)];
+    if by_model {
+        sections.push(("Model", &spend.by_model));
+    }
+    for (label, rows) in sections {
+        if rows.is_empty() {
+            continue;
+        }
+        let width = if label == "Model" { 40 } else { 20 };
+        println!("\n--- Spend by {} ---\n", label);
+        println!(
+            "  {:<width$} {:>5} {:>8} {:>10} {:>10}",
+            label,
+            "Runs",
+            "Tokens",
+            "Actual",
+            "Estimated",
+            width = width
+        );
+        println!("  {}", "-".repeat(width + 37));
+        for row in rows {
+            let key = if label == "Model" || row.rollup.key == "(none)" {
+                row.rollup.key.as_str()
+            } else {
+                agency::short_hash(&row.rollup.key)
+            };
+            let estimated = if row.estimated > 0.0 {
+                format!("${:.2}", row.estimated)
+            } else {
+                "-".to_string()
+            };
+            println!(
+                "  {:<width$} {:>5} {:>8} {:>10} {:>10}",
+                key,
+                row.rollup.runs,
+                format_tokens(row.rollup.tokens.total()),
+                format!("${:.2}", row.rollup.cost_usd),
+                estimated,
+                width = width
+            );
+        }
+    }
+}
+
+fn spend_json(rows: &[SpendRow]) -> Vec<serde_json::Value> {
+    rows.iter()
+        .map(|row| {
+            serde_json::json!({
+                "id": row.rollup.key,
+                "runs": row.rollup.runs,
+                "tokens": row.rollup.tokens,
+                "actual_cost": row.rollup.cost_usd,
+                "estimated_cost": row.estimated,
+                "unpriced_runs": row.rollup.unpriced_runs,
+            })
+        })
+        .collect()
 }
 
 // ---------------------------------------------------------------------------
@@ -471,6 +588,7 @@ fn output_json(
     motivations: &[Motivation],
     evaluations: &[Evaluation],
     task_tags: &HashMap<String, Vec<String>>,
+    spend: &Spend,
     min_evals: u32,
     by_model: bool,
 ) -> Result<()> {
@@ -600,6 +718,11 @@ fn output_json(
             "by_motivation": mot_tags_json,
         },
         "underexplored": under_json,
+        "spend": {
+            "by_role": spend_json(&spend.by_role),
+            "by_agent": spend_json(&spend.by_agent),
+            "by_model": spend_json(&spend.by_model),
+        },
     });
 
     if by_model {
@@ -30,6 +30,7 @@ fn effective_status(agent: &AgentEntry) -> String {
             AgentStatus::Done => "done".to_string(),
             AgentStatus::Failed => "failed".to_string(),
             AgentStatus::Dead => "dead".to_string(),
+            AgentStatus::TimedOut => "timed-out".to_string(),
         }
     }
 }
@@ -317,6 +318,7 @@ mod tests {
             status: AgentStatus::Working,
             output_file: "/tmp/test.log".to_string(),
             model: None,
+            timeout_secs: None,
         };
 
         let status = effective_status(&agent);
@@ -339,6 +341,7 @@ mod tests {
             status: AgentStatus::Dead,
             output_file: "/tmp/test.log".to_string(),
             model: None,
+            timeout_secs: None,
         };
 
         let status = effective_status(&agent);
@@ -360,6 +363,7 @@ mod tests {
             status: AgentStatus::Working,
             output_file: "/tmp/test.log".to_string(),
             model: None,
+            timeout_secs: None,
         };
 
         let status = effective_status(&agent);
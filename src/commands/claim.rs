@@ -13,8 +13,22 @@ use workgraph::parser::load_graph;
 pub fn claim(dir: &Path, id: &str, actor: Option<&str>) -> Result<()> {
     let (mut graph, path) = super::load_workgraph_mut(dir)?;
 
+    if matches!(
+        graph.get_task_or_err(id)?.status,
+        Status::Open | Status::Blocked
+    ) {
+        super::ensure_capacity(&graph, id)?;
+    }
+
     let task = graph.get_task_mut_or_err(id)?;
 
+    if task.gate.is_some() {
+        anyhow::bail!(
+            "Task '{}' is an approval gate and cannot be claimed. Use 'wg approve' or 'wg reject'.",
+            id
+        );
+    }
+
     // Only allow claiming tasks that are Open or Blocked
     match task.status {
         Status::Open | Status::Blocked => {}
@@ -72,7 +86,9 @@ pub fn claim(dir: &Path, id: &str, actor: Option<&str>) -> Result<()> {
         message: log_message,
     });
 
+    let claim = workgraph::capacity::lock_claim(dir, graph.get_task_or_err(id)?)?;
     save_graph(&graph, &path).context("Failed to save graph")?;
+    drop(claim);
     super::notify_graph_changed(dir);
 
     // Record operation
@@ -218,6 +234,38 @@ mod tests {
         assert_eq!(task.status, Status::InProgress);
     }
 
+    #[test]
+    fn test_claim_waits_for_resource_capacity() {
+        let dir = tempdir().unwrap();
+        let dir_path = dir.path();
+        let mut holder = make_task("t1", "Holder", Status::Open);
+        holder.requires = vec!["lock".to_string()];
+        let mut waiter = make_task("t2", "Waiter", Status::Open);
+        waiter.requires = vec!["lock".to_string()];
+        let path = setup_workgraph(dir_path, vec![holder, waiter]);
+        let mut graph = load_graph(&path).unwrap();
+        graph.add_node(Node::Resource(workgraph::graph::Resource {
+            id: "lock".to_string(),
+            name: None,
+            resource_type: None,
+            available: Some(1.0),
+            unit: None,
+            budget: None,
+        }));
+        save_graph(&graph, &path).unwrap();
+
+        claim(dir_path, "t1", None).unwrap();
+        let err = claim(dir_path, "t2", None).unwrap_err();
+        assert!(
+            err.to_string()
+                .contains("lock: needs 1, 1 of 1 in use by t1"),
+            "{err}"
+        );
+
+        unclaim(dir_path, "t1").unwrap();
+        claim(dir_path, "t2", None).unwrap();
+    }
+
     #[test]
     fn test_claim_inprogress_task_fails() {
         let dir = tempdir().unwrap();
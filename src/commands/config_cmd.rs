@@ -3,6 +3,7 @@
 use anyhow::Result;
 use std::path::Path;
 use workgraph::config::{Config, ConfigSource, MatrixConfig};
+use workgraph::dispatch::DispatchPolicy;
 
 /// Scope for config operations
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
@@ -40,6 +41,7 @@ pub fn show(dir: &Path, scope: Option<ConfigScope>, json: bool) -> Result<()> {
         println!("  interval = {}", config.coordinator.interval);
         println!("  poll_interval = {}", config.coordinator.poll_interval);
         println!("  executor = \"{}\"", config.coordinator.executor);
+        println!("  dispatch = \"{}\"", config.coordinator.dispatch);
         println!();
         println!("[agency]");
         println!("  auto_evaluate = {}", config.agency.auto_evaluate);
@@ -76,6 +78,11 @@ pub fn show(dir: &Path, scope: Option<ConfigScope>, json: bool) -> Result<()> {
             println!("  triage_max_log_bytes = {}", max_bytes);
         }
         println!();
+        println!("[storage]");
+        println!("  backend = \"{}\"", config.storage.backend);
+        println!("  journal = {}", config.storage.journal);
+        println!("  compact_threshold = {}", config.storage.compact_threshold);
+        println!();
         if config.project.name.is_some() || config.project.description.is_some() {
             println!("[project]");
             if let Some(ref name) = config.project.name {
@@ -120,6 +127,7 @@ pub fn update(
     coordinator_interval: Option<u64>,
     poll_interval: Option<u64>,
     coordinator_executor: Option<&str>,
+    dispatch: Option<DispatchPolicy>,
     auto_evaluate: Option<bool>,
     auto_assign: Option<bool>,
     assigner_model: Option<&str>,
@@ -184,6 +192,12 @@ pub fn update(
         changed = true;
     }
 
+    if let Some(policy) = dispatch {
+        config.coordinator.dispatch = policy;
+        println!("Set coordinator.dispatch = \"{}\"", policy);
+        changed = true;
+    }
+
     // Agency settings
     if let Some(v) = auto_evaluate {
         config.agency.auto_evaluate = v;
@@ -573,6 +587,7 @@ mod tests {
             None,
             None,
             None,
+            None,
         );
         assert!(result.is_ok());
 
@@ -610,6 +625,7 @@ mod tests {
             None,
             None,
             None,
+            None,
         );
         assert!(result.is_ok());
 
@@ -647,6 +663,7 @@ mod tests {
             None,
             None,
             None,
+            None,
         );
         assert!(result.is_ok());
 
@@ -669,6 +686,7 @@ mod tests {
             None,
             None,
             None,
+            None,
             Some(true),
             Some(true),
             Some("sonnet"),
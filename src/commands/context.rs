@@ -11,6 +11,9 @@ struct ContextSource {
     task_title: String,
     status: Status,
     artifacts: Vec<String>,
+    /// Validated result document, if the dependency's output contract has one
+    #[serde(skip_serializing_if = "Option::is_none")]
+    result: Option<serde_json::Value>,
 }
 
 /// Context available for a task
@@ -38,12 +41,20 @@ pub fn run(dir: &Path, task_id: &str, json: bool) -> Result<()> {
             for artifact in &dep_task.artifacts {
                 all_artifacts.insert(artifact.clone());
             }
-            if !dep_task.artifacts.is_empty() {
+            let result = (dep_task.status == Status::Done
+                && dep_task
+                    .contract
+                    .as_ref()
+                    .is_some_and(|c| c.expects_result()))
+            .then(|| workgraph::contract::stored_result(dir, dep_id))
+            .flatten();
+            if !dep_task.artifacts.is_empty() || result.is_some() {
                 available_context.push(ContextSource {
                     task_id: dep_task.id.clone(),
                     task_title: dep_task.title.clone(),
                     status: dep_task.status,
                     artifacts: dep_task.artifacts.clone(),
+                    result,
                 });
             }
         }
@@ -96,6 +107,13 @@ pub fn run(dir: &Path, task_id: &str, json: bool) -> Result<()> {
                 for artifact in &source.artifacts {
                     println!("    - {}", artifact);
                 }
+                if let Some(ref result) = source.result {
+                    println!("    result:");
+                    let pretty = serde_json::to_string_pretty(result)?;
+                    for line in pretty.lines() {
+                        println!("      {}", line);
+                    }
+                }
             }
         } else {
             println!("No artifacts available from dependencies yet.");
@@ -83,7 +83,7 @@ pub fn get_coordination_status(graph: &workgraph::graph::WorkGraph) -> Coordinat
                 && t.after.iter().any(|blocker_id| {
                     graph
                         .get_task(blocker_id)
-                        .map(|b| !b.status.is_terminal())
+                        .map(|b| !t.is_after_satisfied(blocker_id, b.status))
                         .unwrap_or(true) // Missing blocker = unresolved (consistent with query.rs)
                 })
         })
@@ -1,34 +1,138 @@
 use anyhow::Result;
+use std::collections::HashSet;
 use std::path::Path;
+use workgraph::accounting::{self, GroupBy, RunUsage, TokenCounts, format_tokens};
+use workgraph::graph::WorkGraph;
 use workgraph::query::cost_of;
 
-pub fn run(dir: &Path, id: &str, json: bool) -> Result<()> {
+/// Run `wg cost [ID] [--by task|role|agent|model]`.
+///
+/// With a task ID, compares the estimated cost of the task and its
+/// dependencies with what their agent runs actually spent. Without one,
+/// reports the whole project.
+pub fn run(dir: &Path, id: Option<&str>, by: Option<&str>, json: bool) -> Result<()> {
     let (graph, _path) = super::load_workgraph(dir)?;
+    let group_by = by.map(str::parse::<GroupBy>).transpose()?;
 
-    if graph.get_task(id).is_none() {
+    if let Some(id) = id
+        && graph.get_task(id).is_none()
+    {
         anyhow::bail!("Task '{}' not found", id);
     }
 
-    let total_cost = cost_of(&graph, id);
+    if let Err(e) = accounting::sync(dir, &graph) {
+        eprintln!("Warning: usage accounting failed: {}", e);
+    }
+    let records = accounting::load(dir)?;
+
+    let (estimated, records): (f64, Vec<RunUsage>) = match id {
+        Some(id) => {
+            let tasks = with_dependencies(&graph, id);
+            let records = records
+                .into_iter()
+                .filter(|r| tasks.contains(&r.task_id))
+                .collect();
+            (cost_of(&graph, id), records)
+        }
+        None => (
+            graph
+                .tasks()
+                .filter_map(|t| t.estimate.as_ref().and_then(|e| e.cost))
+                .sum(),
+            records,
+        ),
+    };
+    let actual: f64 = records.iter().filter_map(|r| r.cost_usd).sum();
+    let runs: HashSet<&str> = records.iter().map(|r| r.run.as_str()).collect();
+    let mut tokens = TokenCounts::default();
+    for record in &records {
+        tokens.add(&record.tokens);
+    }
+    let breakdown = group_by.map(|by| accounting::rollup(&records, by));
 
     if json {
-        println!(
-            "{}",
-            serde_json::to_string_pretty(&serde_json::json!({
-                "task_id": id,
-                "total_cost": total_cost
-            }))?
-        );
-    } else {
-        println!(
-            "Total cost for '{}' (including dependencies): ${:.2}",
-            id, total_cost
-        );
+        let mut value = serde_json::json!({
+            "total_cost": estimated,
+            "estimated_cost": estimated,
+            "actual_cost": actual,
+            "runs": runs.len(),
+            "tokens": tokens,
+        });
+        if let Some(id) = id {
+            value["task_id"] = serde_json::json!(id);
+        }
+        if let Some(ref breakdown) = breakdown {
+            value["breakdown"] = serde_json::to_value(breakdown)?;
+        }
+        println!("{}", serde_json::to_string_pretty(&value)?);
+        return Ok(());
+    }
+
+    match id {
+        Some(id) => println!("Cost for '{}' (including dependencies):", id),
+        None => println!("Project cost:"),
+    }
+    println!("  Estimated: ${:.2}", estimated);
+    println!(
+        "  Actual:    ${:.2} ({} run(s), {} tokens: {} in, {} out, {} cached)",
+        actual,
+        runs.len(),
+        format_tokens(tokens.total()),
+        format_tokens(tokens.input_tokens),
+        format_tokens(tokens.output_tokens),
+        format_tokens(tokens.cache_read_tokens + tokens.cache_write_tokens)
+    );
+
+    if let (Some(by), Some(breakdown)) = (group_by, breakdown) {
+        println!();
+        if breakdown.is_empty() {
+            println!("No recorded agent runs.");
+        }
+        for row in &breakdown {
+            let estimate = match by {
+                GroupBy::Task => graph
+                    .get_task(&row.key)
+                    .and_then(|t| t.estimate.as_ref())
+                    .and_then(|e| e.cost)
+                    .map(|c| format!("  (est. ${:.2})", c))
+                    .unwrap_or_default(),
+                _ => String::new(),
+            };
+            let unpriced = if row.unpriced_runs > 0 {
+                format!("  [{} unpriced]", row.unpriced_runs)
+            } else {
+                String::new()
+            };
+            println!(
+                "  {:<32} {:>3} run(s) {:>8} tokens  ${:>8.2}{}{}",
+                row.key,
+                row.runs,
+                format_tokens(row.tokens.total()),
+                row.cost_usd,
+                estimate,
+                unpriced
+            );
+        }
     }
 
     Ok(())
 }
 
+/// A task and everything it transitively comes after.
+fn with_dependencies(graph: &WorkGraph, id: &str) -> HashSet<String> {
+    let mut seen = HashSet::new();
+    let mut stack = vec![id.to_string()];
+    while let Some(current) = stack.pop() {
+        if !seen.insert(current.clone()) {
+            continue;
+        }
+        if let Some(task) = graph.get_task(&current) {
+            stack.extend(task.after.iter().cloned());
+        }
+    }
+    seen
+}
+
 #[cfg(test)]
 mod tests {
     use super::super::graph_path;
@@ -60,7 +164,7 @@ mod tests {
     #[test]
     fn test_run_uninitialized() {
         let dir = tempdir().unwrap();
-        let result = run(dir.path(), "t1", false);
+        let result = run(dir.path(), Some("t1"), None, false);
         assert!(result.is_err());
         assert!(result.unwrap_err().to_string().contains("not initialized"));
     }
@@ -69,7 +173,7 @@ mod tests {
     fn test_run_nonexistent_task() {
         let dir = tempdir().unwrap();
         setup_workgraph(dir.path(), vec![make_task("t1", "Task 1")]);
-        let result = run(dir.path(), "nonexistent", false);
+        let result = run(dir.path(), Some("nonexistent"), None, false);
         assert!(result.is_err());
         assert!(result.unwrap_err().to_string().contains("not found"));
     }
@@ -84,7 +188,7 @@ mod tests {
         });
         setup_workgraph(dir.path(), vec![task]);
 
-        let result = run(dir.path(), "t1", false);
+        let result = run(dir.path(), Some("t1"), None, false);
         assert!(result.is_ok());
     }
 
@@ -277,7 +381,53 @@ mod tests {
         let dir = tempdir().unwrap();
         setup_workgraph(dir.path(), vec![make_task("t1", "Task")]);
 
-        let result = run(dir.path(), "t1", false);
+        let result = run(dir.path(), Some("t1"), None, false);
         assert!(result.is_ok());
     }
+
+    #[test]
+    fn test_run_reports_actual_spend() {
+        let dir = tempdir().unwrap();
+        let mut dep = make_task("dep", "Dep");
+        dep.estimate = Some(Estimate {
+            hours: None,
+            cost: Some(2.0),
+        });
+        let mut main = make_task("main", "Main");
+        main.after = vec!["dep".to_string()];
+        setup_workgraph(dir.path(), vec![dep, main, make_task("other", "Other")]);
+
+        let record = |run: &str, task: &str, cost: f64| RunUsage {
+            run: run.to_string(),
+            task_id: task.to_string(),
+            agent: None,
+            role: None,
+            model: Some("anthropic/claude-opus-4-6".to_string()),
+            tokens: TokenCounts {
+                input_tokens: 100,
+                ..TokenCounts::default()
+            },
+            cost_usd: Some(cost),
+            source: "claude".to_string(),
+            recorded_at: "2026-03-01T00:00:00Z".to_string(),
+        };
+        accounting::append(
+            dir.path(),
+            &[
+                record("agent-1", "dep", 1.5),
+                record("agent-2", "main", 0.5),
+                record("agent-3", "other", 4.0),
+            ],
+        )
+        .unwrap();
+
+        assert!(run(dir.path(), Some("main"), Some("model"), false).is_ok());
+        assert!(run(dir.path(), None, Some("task"), true).is_ok());
+        assert!(run(dir.path(), None, Some("color"), false).is_err());
+
+        let graph = load_graph(graph_path(dir.path())).unwrap();
+        let tasks = with_dependencies(&graph, "main");
+        assert_eq!(tasks.len(), 2);
+        assert!(!tasks.contains("other"));
+    }
 }
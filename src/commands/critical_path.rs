@@ -2,6 +2,7 @@ use anyhow::Result;
 use serde::Serialize;
 use std::collections::{HashMap, HashSet};
 use std::path::Path;
+use workgraph::capacity::{self, ResourceBound};
 use workgraph::format_hours;
 use workgraph::graph::{Status, WorkGraph};
 
@@ -30,6 +31,11 @@ struct CriticalPathOutput {
     critical_path: Vec<CriticalTask>,
     task_count: usize,
     total_hours: f64,
+    /// Scarce resources that serialize the tasks needing them
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    resource_bounds: Vec<ResourceBound>,
+    /// The longer of the critical path and the longest resource bound
+    bound_hours: f64,
     slack_analysis: Vec<SlackInfo>,
     cycles_skipped: Vec<Vec<String>>,
 }
@@ -46,6 +52,8 @@ pub fn run(dir: &Path, json: bool) -> Result<()> {
                 critical_path: vec![],
                 task_count: 0,
                 total_hours: 0.0,
+                resource_bounds: vec![],
+                bound_hours: 0.0,
                 slack_analysis: vec![],
                 cycles_skipped: vec![],
             };
@@ -150,11 +158,21 @@ pub fn run(dir: &Path, json: bool) -> Result<()> {
         })
         .collect();
 
+    // Tasks sharing a scarce resource run one after another, however the
+    // dependencies allow them to overlap
+    let resource_bounds = capacity::resource_bounds(&graph);
+    let bound_hours = resource_bounds
+        .iter()
+        .map(|b| b.bound_hours)
+        .fold(total_hours, f64::max);
+
     if json {
         let output = CriticalPathOutput {
             critical_path: critical_tasks,
             task_count: critical_path.len(),
             total_hours,
+            resource_bounds,
+            bound_hours,
             slack_analysis,
             cycles_skipped: cycles,
         };
@@ -208,6 +226,26 @@ pub fn run(dir: &Path, json: bool) -> Result<()> {
             }
         }
 
+        if !resource_bounds.is_empty() {
+            println!("\nResource constraints:");
+            for bound in &resource_bounds {
+                println!(
+                    "  {}: {} task(s), {}h of work at capacity {} -> at least {}h",
+                    bound.resource,
+                    bound.tasks.len(),
+                    format_hours(bound.unit_hours),
+                    bound.capacity,
+                    format_hours(bound.bound_hours)
+                );
+            }
+            if bound_hours > total_hours {
+                println!(
+                    "  Resource contention outlasts the dependency chain: at least {}h overall.",
+                    format_hours(bound_hours)
+                );
+            }
+        }
+
         if !slack_analysis.is_empty() {
             println!("\nSlack analysis:");
             for slack in &slack_analysis {
@@ -231,6 +269,27 @@ pub fn run(dir: &Path, json: bool) -> Result<()> {
     Ok(())
 }
 
+/// Hours of the longest chain of active tasks starting at each active task,
+/// the task itself included. Tasks in cycles are left out.
+pub fn path_hours(graph: &WorkGraph) -> HashMap<String, f64> {
+    let active_ids: HashSet<&str> = graph
+        .tasks()
+        .filter(|t| !t.status.is_terminal())
+        .map(|t| t.id.as_str())
+        .collect();
+    let cycles = detect_cycles_among_active(graph, &active_ids);
+    let cycle_nodes: HashSet<&str> = cycles.iter().flatten().map(String::as_str).collect();
+    let forward_index = build_forward_index(graph, &active_ids, &cycle_nodes);
+
+    let mut memo: HashMap<&str, (f64, Vec<String>)> = HashMap::new();
+    for id in &active_ids {
+        calculate_longest_path(id, graph, &forward_index, &mut memo, &cycle_nodes);
+    }
+    memo.into_iter()
+        .map(|(id, (hours, _))| (id.to_string(), hours))
+        .collect()
+}
+
 /// Build forward index: task_id -> tasks that it blocks (among active non-cycle tasks)
 fn build_forward_index<'a>(
     graph: &'a WorkGraph,
@@ -389,38 +448,12 @@ mod tests {
         Task {
             id: id.to_string(),
             title: title.to_string(),
-            description: None,
-            status: Status::Open,
-            assigned: None,
             estimate: Some(Estimate {
                 hours: Some(hours),
                 cost: None,
             }),
-            before: vec![],
-            after: vec![],
-            requires: vec![],
-            tags: vec![],
-            skills: vec![],
-            inputs: vec![],
-            deliverables: vec![],
-            artifacts: vec![],
-            exec: None,
-            not_before: None,
-            created_at: None,
-            started_at: None,
-            completed_at: None,
-            log: vec![],
-            retry_count: 0,
-            max_retries: None,
-            failure_reason: None,
-            model: None,
-            verify: None,
-            agent: None,
-            loop_iteration: 0,
-            ready_after: None,
-            paused: false,
             visibility: "internal".to_string(),
-            cycle_config: None,
+            ..Task::default()
         }
     }
 
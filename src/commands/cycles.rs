@@ -1,22 +1,62 @@
 use anyhow::Result;
+use std::collections::HashMap;
 use std::path::Path;
-use workgraph::graph::CycleAnalysis;
+use workgraph::graph::{CycleAnalysis, CycleDecision};
+
+/// A recorded cycle decision and when it was made.
+struct LastDecision {
+    timestamp: String,
+    decision: CycleDecision,
+}
 
 pub fn run(dir: &Path, json: bool) -> Result<()> {
     let (graph, _path) = super::load_workgraph(dir)?;
     let analysis = graph.compute_cycle_analysis();
+    let decisions = last_decisions(dir);
 
     if json {
-        print_json(&analysis, &graph)?;
+        print_json(&analysis, &graph, &decisions)?;
     } else {
-        print_human(&analysis, &graph);
+        print_human(&analysis, &graph, &decisions);
     }
 
     Ok(())
 }
 
-fn print_json(analysis: &CycleAnalysis, graph: &workgraph::graph::WorkGraph) -> Result<()> {
-    let cycles_output: Vec<_> = analysis
+/// Latest `cycle_iteration` decision from the provenance log, keyed by the
+/// task that owns the cycle config.
+fn last_decisions(dir: &Path) -> HashMap<String, LastDecision> {
+    let mut latest = HashMap::new();
+    for entry in workgraph::provenance::read_all_operations(dir).unwrap_or_default() {
+        if entry.op != "cycle_iteration" {
+            continue;
+        }
+        if let Ok(decision) = serde_json::from_value::<CycleDecision>(entry.detail) {
+            latest.insert(
+                decision.cycle.clone(),
+                LastDecision {
+                    timestamp: entry.timestamp,
+                    decision,
+                },
+            );
+        }
+    }
+    latest
+}
+
+fn decision_for<'a>(
+    members: &[String],
+    decisions: &'a HashMap<String, LastDecision>,
+) -> Option<&'a LastDecision> {
+    members.iter().find_map(|m| decisions.get(m))
+}
+
+fn print_json(
+    analysis: &CycleAnalysis,
+    graph: &workgraph::graph::WorkGraph,
+    decisions: &HashMap<String, LastDecision>,
+) -> Result<()> {
+    let cycles_output: Vec<serde_json::Value> = analysis
         .cycles
         .iter()
         .map(|c| {
@@ -32,15 +72,21 @@ fn print_json(analysis: &CycleAnalysis, graph: &workgraph::graph::WorkGraph) ->
                 })
                 .collect();
 
-            serde_json::json!({
+            let mut cycle = serde_json::json!({
                 "header": c.header,
                 "members": c.members,
                 "member_count": c.members.len(),
                 "reducible": c.reducible,
                 "member_statuses": statuses,
-            })
+            });
+            if let Some(last) = decision_for(&c.members, decisions) {
+                let mut decision = serde_json::to_value(&last.decision)?;
+                decision["timestamp"] = serde_json::json!(last.timestamp);
+                cycle["last_decision"] = decision;
+            }
+            Ok(cycle)
         })
-        .collect();
+        .collect::<Result<_>>()?;
 
     let back_edges: Vec<_> = analysis
         .back_edges
@@ -58,7 +104,11 @@ fn print_json(analysis: &CycleAnalysis, graph: &workgraph::graph::WorkGraph) ->
     Ok(())
 }
 
-fn print_human(analysis: &CycleAnalysis, graph: &workgraph::graph::WorkGraph) {
+fn print_human(
+    analysis: &CycleAnalysis,
+    graph: &workgraph::graph::WorkGraph,
+    decisions: &HashMap<String, LastDecision>,
+) {
     if analysis.cycles.is_empty() {
         println!("No cycles detected in after edges.");
         return;
@@ -121,6 +171,15 @@ fn print_human(analysis: &CycleAnalysis, graph: &workgraph::graph::WorkGraph) {
             }
         }
 
+        if let Some(last) = decision_for(&cycle.members, decisions) {
+            let d = &last.decision;
+            let outcome = if d.iterated { "iterated" } else { "stopped" };
+            println!(
+                "     Last check: {} after iteration {}/{} ({}): {}",
+                outcome, d.iteration, d.max_iterations, last.timestamp, d.reason
+            );
+        }
+
         if !cycle.reducible {
             println!("     WARNING: Irreducible cycle has multiple entry points.");
         }
@@ -234,4 +293,34 @@ mod tests {
         assert!(!analysis.task_to_cycle.contains_key("c"));
         assert_eq!(analysis.task_to_cycle["a"], analysis.task_to_cycle["b"]);
     }
+
+    #[test]
+    fn test_last_decisions_keeps_latest_per_cycle() {
+        let tmp = TempDir::new().unwrap();
+        let dir = tmp.path().join(".workgraph");
+        std::fs::create_dir_all(&dir).unwrap();
+        for (iteration, iterated) in [(0, true), (1, false)] {
+            let decision = CycleDecision {
+                cycle: "write".to_string(),
+                trigger: "review".to_string(),
+                iteration,
+                max_iterations: 3,
+                iterated,
+                reason: format!("check {}", iteration),
+                reactivated: vec![],
+            };
+            super::super::record_cycle_decision(
+                &dir,
+                &decision,
+                workgraph::provenance::DEFAULT_ROTATION_THRESHOLD,
+            );
+        }
+        let decisions = last_decisions(&dir);
+        assert_eq!(decisions.len(), 1);
+        let last = &decisions["write"].decision;
+        assert_eq!(last.iteration, 1);
+        assert!(!last.iterated);
+        let members = vec!["review".to_string(), "write".to_string()];
+        assert!(decision_for(&members, &decisions).is_some());
+    }
 }
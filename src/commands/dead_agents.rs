@@ -275,7 +275,7 @@ pub fn run_purge(dir: &Path, delete_dirs: bool, json: bool) -> Result<PurgeResul
         .filter(|a| {
             matches!(
                 a.status,
-                AgentStatus::Dead | AgentStatus::Done | AgentStatus::Failed
+                AgentStatus::Dead | AgentStatus::Done | AgentStatus::Failed | AgentStatus::TimedOut
             )
         })
         .map(|a| (a.id.clone(), a.task_id.clone()))
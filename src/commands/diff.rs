@@ -0,0 +1,313 @@
+use anyhow::Result;
+use chrono::{DateTime, Local, Utc};
+use serde::Serialize;
+use serde_json::Value;
+use std::collections::BTreeSet;
+use std::path::Path;
+use workgraph::graph::WorkGraph;
+use workgraph::history::{self, Change, HistoryEntry};
+
+/// Longest field value shown in human-readable output.
+const MAX_VALUE_LEN: usize = 80;
+
+/// Fields left out of the diff: they change on every save.
+const IGNORED_FIELDS: &[&str] = &["revision"];
+
+#[derive(Debug, Serialize)]
+struct FieldChange {
+    field: String,
+    before: Value,
+    after: Value,
+}
+
+#[derive(Debug, Serialize)]
+struct AppendedItems {
+    field: String,
+    items: Vec<Value>,
+}
+
+/// A recorded save that touched the task between the two times.
+#[derive(Debug, Serialize)]
+struct Edit {
+    timestamp: String,
+    change: &'static str,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    actor: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    command: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+struct NodeDiff {
+    id: String,
+    change: &'static str,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    title: Option<String>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    fields: Vec<FieldChange>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    appended: Vec<AppendedItems>,
+    edits: Vec<Edit>,
+}
+
+/// Compare the graph at `from` with the graph at `to` (default: now), using
+/// the graph history log. `task` restricts the diff to one task.
+pub fn run(dir: &Path, from: &str, to: Option<&str>, task: Option<&str>, json: bool) -> Result<()> {
+    if !super::graph_path(dir).exists() {
+        anyhow::bail!("Workgraph not initialized. Run 'wg init' first.");
+    }
+    let from = history::parse_time(from)?;
+    let to = match to {
+        Some(to) => history::parse_time(to)?,
+        None => Utc::now(),
+    };
+    if from > to {
+        anyhow::bail!("The first time must not be later than the second");
+    }
+
+    let entries = history::read_all(dir)?;
+    let before = history::graph_from_entries(&entries, from)?;
+    let after = history::graph_from_entries(&entries, to)?;
+    let diffs = diff_graphs(&before, &after, &entries, from, to, task)?;
+
+    if json {
+        println!(
+            "{}",
+            serde_json::to_string_pretty(&serde_json::json!({
+                "from": from.to_rfc3339(),
+                "to": to.to_rfc3339(),
+                "changes": diffs,
+                "total_changes": diffs.len(),
+            }))?
+        );
+        return Ok(());
+    }
+
+    println!(
+        "Graph changes from {} to {}: {}",
+        format_time(from),
+        format_time(to),
+        match diffs.len() {
+            0 => "none".to_string(),
+            1 => "1 task".to_string(),
+            n => format!("{} tasks", n),
+        }
+    );
+    for diff in &diffs {
+        let marker = match diff.change {
+            "created" => "+",
+            "removed" => "-",
+            _ => "~",
+        };
+        let suffix = match diff.change {
+            "created" | "removed" => format!(" ({})", diff.change),
+            _ => String::new(),
+        };
+        println!();
+        println!(
+            "{} {}  {}{}",
+            marker,
+            diff.id,
+            diff.title.as_deref().unwrap_or(""),
+            suffix
+        );
+        for field in &diff.fields {
+            println!(
+                "    {}: {} -> {}",
+                field.field,
+                format_value(&field.before),
+                format_value(&field.after)
+            );
+        }
+        for appended in &diff.appended {
+            println!("    {}: +{}", appended.field, appended.items.len());
+        }
+        if !diff.edits.is_empty() {
+            println!("    changed by:");
+            for edit in &diff.edits {
+                let when = DateTime::parse_from_rfc3339(&edit.timestamp)
+                    .map(|t| format_time(t.with_timezone(&Utc)))
+                    .unwrap_or_else(|_| edit.timestamp.clone());
+                let who = edit.actor.as_deref().unwrap_or("-");
+                println!(
+                    "      {}  {}  {}",
+                    when,
+                    who,
+                    edit.command.as_deref().unwrap_or("")
+                );
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Field-level differences between two graphs, sorted by node ID.
+fn diff_graphs(
+    before: &WorkGraph,
+    after: &WorkGraph,
+    entries: &[HistoryEntry],
+    from: DateTime<Utc>,
+    to: DateTime<Utc>,
+    task: Option<&str>,
+) -> Result<Vec<NodeDiff>> {
+    let ids: BTreeSet<&str> = before
+        .nodes()
+        .chain(after.nodes())
+        .map(|n| n.id())
+        .filter(|id| task.is_none_or(|t| t == *id))
+        .collect();
+
+    let mut diffs = Vec::new();
+    for id in ids {
+        let old = before.get_node(id).map(serde_json::to_value).transpose()?;
+        let new = after.get_node(id).map(serde_json::to_value).transpose()?;
+        let (change, fields, appended) = match (&old, &new) {
+            (None, Some(_)) => ("created", Vec::new(), Vec::new()),
+            (Some(_), None) => ("removed", Vec::new(), Vec::new()),
+            (Some(old), Some(new)) => {
+                let (old_fields, new_fields, appended) = history::diff_fields(old, new);
+                let fields: Vec<FieldChange> = old_fields
+                    .into_iter()
+                    .filter(|(field, _)| !IGNORED_FIELDS.contains(&field.as_str()))
+                    .map(|(field, before)| FieldChange {
+                        after: new_fields.get(&field).cloned().unwrap_or(Value::Null),
+                        field,
+                        before,
+                    })
+                    .collect();
+                let appended: Vec<AppendedItems> = appended
+                    .into_iter()
+                    .map(|(field, items)| AppendedItems {
+                        field,
+                        items: match items {
+                            Value::Array(items) => items,
+                            other => vec![other],
+                        },
+                    })
+                    .collect();
+                if fields.is_empty() && appended.is_empty() {
+                    continue;
+                }
+                ("changed", fields, appended)
+            }
+            (None, None) => continue,
+        };
+
+        let title = new
+            .as_ref()
+            .or(old.as_ref())
+            .and_then(|n| n.get("title"))
+            .and_then(Value::as_str)
+            .map(String::from);
+        diffs.push(NodeDiff {
+            id: id.to_string(),
+            change,
+            title,
+            fields,
+            appended,
+            edits: edits_between(entries, id, from, to),
+        });
+    }
+    Ok(diffs)
+}
+
+/// History entries touching `id` after `from`, up to and including `to`.
+fn edits_between(
+    entries: &[HistoryEntry],
+    id: &str,
+    from: DateTime<Utc>,
+    to: DateTime<Utc>,
+) -> Vec<Edit> {
+    entries
+        .iter()
+        .filter(|e| e.change.node_id() == Some(id))
+        .filter(|e| {
+            history::entry_time(e)
+                .map(|t| t > from && t <= to)
+                .unwrap_or(false)
+        })
+        .map(|e| Edit {
+            timestamp: e.timestamp.clone(),
+            change: match e.change {
+                Change::Created { .. } => "created",
+                Change::Removed { .. } => "removed",
+                _ => "updated",
+            },
+            actor: e.actor.clone(),
+            command: e.command.clone(),
+        })
+        .collect()
+}
+
+fn format_time(t: DateTime<Utc>) -> String {
+    t.with_timezone(&Local)
+        .format("%Y-%m-%d %H:%M:%S")
+        .to_string()
+}
+
+/// Render a field value on one line: strings bare, absent values as
+/// `(none)`, anything else as compact JSON.
+fn format_value(value: &Value) -> String {
+    let text = match value {
+        Value::Null => return "(none)".to_string(),
+        Value::String(s) => s.replace('\n', " "),
+        other => other.to_string(),
+    };
+    if text.chars().count() > MAX_VALUE_LEN {
+        let short: String = text.chars().take(MAX_VALUE_LEN - 1).collect();
+        format!("{}…", short)
+    } else {
+        text
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+    use tempfile::tempdir;
+    use workgraph::graph::{Node, Status, Task};
+    use workgraph::parser::{load_graph, save_graph};
+
+    #[test]
+    fn test_diff_reports_field_changes_and_edits() {
+        let dir = tempdir().unwrap();
+        fs::create_dir_all(dir.path()).unwrap();
+        let path = super::super::graph_path(dir.path());
+
+        let mut graph = WorkGraph::new();
+        graph.add_node(Node::Task(Task {
+            id: "t1".into(),
+            title: "First".into(),
+            ..Task::default()
+        }));
+        save_graph(&graph, &path).unwrap();
+        std::thread::sleep(std::time::Duration::from_millis(5));
+        let from = Utc::now();
+        std::thread::sleep(std::time::Duration::from_millis(5));
+
+        let mut graph = load_graph(&path).unwrap();
+        let task = graph.get_task_mut("t1").unwrap();
+        task.status = Status::InProgress;
+        task.assigned = Some("agent-1".into());
+        save_graph(&graph, &path).unwrap();
+        let to = Utc::now();
+
+        let entries = history::read_all(dir.path()).unwrap();
+        let before = history::graph_from_entries(&entries, from).unwrap();
+        let after = history::graph_from_entries(&entries, to).unwrap();
+        let diffs = diff_graphs(&before, &after, &entries, from, to, None).unwrap();
+
+        assert_eq!(diffs.len(), 1);
+        let diff = &diffs[0];
+        assert_eq!(diff.change, "changed");
+        let fields: Vec<&str> = diff.fields.iter().map(|f| f.field.as_str()).collect();
+        assert_eq!(fields, ["assigned", "status"]);
+        assert_eq!(diff.fields[1].before, "open");
+        assert_eq!(diff.fields[1].after, "in-progress");
+        assert_eq!(diff.edits.len(), 1);
+        assert_eq!(diff.edits[0].change, "updated");
+
+        assert!(run(dir.path(), &from.to_rfc3339(), None, Some("t1"), true).is_ok());
+    }
+}
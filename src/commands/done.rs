@@ -2,16 +2,23 @@ use anyhow::{Context, Result};
 use chrono::Utc;
 use std::path::Path;
 use workgraph::agency::capture_task_output;
-use workgraph::graph::{LogEntry, Status, evaluate_cycle_iteration};
+use workgraph::config::Config;
+use workgraph::graph::{LogEntry, Node, Status, WorkGraph, evaluate_cycle_iteration_in};
 use workgraph::parser::save_graph;
 use workgraph::query;
+use workgraph::worktree::{self, Integration};
 
 #[cfg(test)]
 use super::graph_path;
 #[cfg(test)]
 use workgraph::parser::load_graph;
 
-pub fn run(dir: &Path, id: &str, converged: bool) -> Result<()> {
+/// Mark a task done. If its output contract is not met, completion is
+/// refused with the list of unmet expectations, or with `fail_unmet` the
+/// task is marked failed with that list as the reason.
+pub fn run(dir: &Path, id: &str, converged: bool, fail_unmet: bool) -> Result<()> {
+    // The agent may be running `wg done` from inside the worktree we remove
+    let dir = &dir.canonicalize().unwrap_or_else(|_| dir.to_path_buf());
     let (mut graph, path) = super::load_workgraph_mut(dir)?;
 
     let task = graph.get_task_mut_or_err(id)?;
@@ -21,6 +28,15 @@ pub fn run(dir: &Path, id: &str, converged: bool) -> Result<()> {
         return Ok(());
     }
 
+    if task.gate.is_some() {
+        anyhow::bail!(
+            "Task '{}' is an approval gate. Use 'wg approve {}' or 'wg reject {}'.",
+            id,
+            id,
+            id
+        );
+    }
+
     // Check for unresolved blockers (cycle-aware: skip same-cycle blockers
     // for tasks with cycle_config, since the cycle header is allowed to complete
     // even though its predecessor in the cycle isn't done yet)
@@ -56,6 +72,49 @@ pub fn run(dir: &Path, id: &str, converged: bool) -> Result<()> {
         }
     }
 
+    let report = workgraph::contract::check(dir, graph.get_task_or_err(id)?);
+    if !report.is_met() {
+        if fail_unmet {
+            let reason = format!("Output contract not met: {}", report.problems.join("; "));
+            return super::fail::run(dir, id, Some(&reason));
+        }
+        let problems: Vec<String> = report
+            .problems
+            .iter()
+            .map(|p| format!("  - {}", p))
+            .collect();
+        anyhow::bail!(
+            "Cannot mark '{}' as done: its output contract is not met:\n{}",
+            id,
+            problems.join("\n")
+        );
+    }
+
+    // Capture task output (git diff, artifacts, log) for evaluation, while a
+    // worktree branch can still be diffed on its own.
+    // When auto_evaluate is enabled, the coordinator creates an evaluation task
+    // in the graph that becomes ready once this task is done; the captured output
+    // feeds that evaluator.
+    match capture_task_output(dir, graph.get_task_or_err(id)?) {
+        Ok(output_dir) => {
+            eprintln!("Output captured to {}", output_dir.display());
+        }
+        Err(e) => {
+            eprintln!("Warning: output capture failed: {}", e);
+        }
+    }
+
+    // Merge the task's worktree branch, if it ran in one, before the task
+    // counts as done, so its dependents start from the integrated work
+    let config = Config::load_or_default(dir);
+    let merge_task = match integrate_worktree(dir, &mut graph, id, &config) {
+        Ok(merge_task) => merge_task,
+        Err(e) => {
+            eprintln!("Warning: worktree integration failed: {}", e);
+            None
+        }
+    };
+
     // Re-acquire mutable reference after immutable borrow
     let task = graph
         .get_task_mut(id)
@@ -81,13 +140,24 @@ pub fn run(dir: &Path, id: &str, converged: bool) -> Result<()> {
     // Evaluate structural cycle iteration
     let id_owned = id.to_string();
     let cycle_analysis = graph.compute_cycle_analysis();
-    let cycle_reactivated = evaluate_cycle_iteration(&mut graph, &id_owned, &cycle_analysis);
+    let cycle_decision =
+        evaluate_cycle_iteration_in(&mut graph, &id_owned, &cycle_analysis, Some(dir));
+    let propagation = super::propagate(&mut graph, dir);
 
     save_graph(&graph, &path).context("Failed to save graph")?;
     super::notify_graph_changed(dir);
 
     // Record operation
-    let config = workgraph::config::Config::load_or_default(dir);
+    if let Some((ref merge_id, ref title)) = merge_task {
+        let _ = workgraph::provenance::record(
+            dir,
+            "add_task",
+            Some(merge_id),
+            None,
+            serde_json::json!({ "title": title, "reason": "merge_conflict" }),
+            config.log.rotation_threshold,
+        );
+    }
     let _ = workgraph::provenance::record(
         dir,
         "done",
@@ -96,13 +166,30 @@ pub fn run(dir: &Path, id: &str, converged: bool) -> Result<()> {
         serde_json::Value::Null,
         config.log.rotation_threshold,
     );
+    if let Some(ref decision) = cycle_decision {
+        super::record_cycle_decision(dir, decision, config.log.rotation_threshold);
+    }
 
     println!("Marked '{}' as done", id);
 
-    for task_id in &cycle_reactivated {
-        println!("  Cycle: re-activated '{}'", task_id);
+    // Keep the validated result where `wg context` looks for it
+    if let Some(task) = graph.get_task(id)
+        && let Err(e) = workgraph::contract::store_result(dir, task, &report)
+    {
+        eprintln!("Warning: failed to store result of '{}': {}", id, e);
     }
 
+    if let Some(ref decision) = cycle_decision {
+        if decision.iterated {
+            for task_id in &decision.reactivated {
+                println!("  Cycle: re-activated '{}'", task_id);
+            }
+        } else {
+            println!("  Cycle '{}' stopped: {}", decision.cycle, decision.reason);
+        }
+    }
+    propagation.print();
+
     // Archive agent conversation (prompt + output) for provenance
     if let Some(task) = graph.get_task(id)
         && let Some(ref agent_id) = task.assigned
@@ -117,22 +204,78 @@ pub fn run(dir: &Path, id: &str, converged: bool) -> Result<()> {
         }
     }
 
-    // Capture task output (git diff, artifacts, log) for evaluation.
-    // When auto_evaluate is enabled, the coordinator creates an evaluation task
-    // in the graph that becomes ready once this task is done; the captured output
-    // feeds that evaluator.
-    if let Some(task) = graph.get_task(id) {
-        match capture_task_output(dir, task) {
-            Ok(output_dir) => {
-                eprintln!("Output captured to {}", output_dir.display());
+    Ok(())
+}
+
+/// Integrate an isolated task's branch. A conflict becomes a follow-up task
+/// in `graph` after the finished one, which the finished task's dependents
+/// also wait for; its id and title are returned.
+fn integrate_worktree(
+    dir: &Path,
+    graph: &mut WorkGraph,
+    id: &str,
+    config: &Config,
+) -> Result<Option<(String, String)>> {
+    let settings = &config.worktree;
+    let task = graph.get_task_or_err(id)?.clone();
+    let outcome = worktree::integrate(
+        dir,
+        &task,
+        settings.integration_branch.as_deref(),
+        settings.strategy,
+    )?;
+    match outcome {
+        None => Ok(None),
+        Some(Integration::Merged { branch, into }) => {
+            println!(
+                "  Integrated {} into {} ({})",
+                branch, into, settings.strategy
+            );
+            Ok(None)
+        }
+        Some(Integration::Unchanged { branch }) => {
+            println!("  No changes on {}; worktree removed", branch);
+            Ok(None)
+        }
+        Some(conflict @ Integration::Conflict { .. }) => {
+            let mut follow_up_id = format!("merge-{}", id);
+            let mut n = 2;
+            while graph.get_node(&follow_up_id).is_some() {
+                follow_up_id = format!("merge-{}-{}", id, n);
+                n += 1;
             }
-            Err(e) => {
-                eprintln!("Warning: output capture failed: {}", e);
+            let Some(mut follow_up) =
+                worktree::conflict_task(follow_up_id.clone(), &task, &conflict)
+            else {
+                return Ok(None);
+            };
+            // Dependents need the merged work, not just the finished task
+            let dependents: Vec<String> = graph
+                .tasks()
+                .filter(|t| t.after.iter().any(|a| a == id))
+                .map(|t| t.id.clone())
+                .collect();
+            for dependent in &dependents {
+                if let Some(t) = graph.get_task_mut(dependent) {
+                    t.after.push(follow_up_id.clone());
+                }
             }
+            follow_up.before = dependents;
+            if let Some(t) = graph.get_task_mut(id) {
+                t.before.push(follow_up_id.clone());
+            }
+            let title = follow_up.title.clone();
+            graph.add_node(Node::Task(follow_up));
+
+            if let Integration::Conflict { branch, into, .. } = &conflict {
+                println!(
+                    "  Could not integrate {} into {}; created '{}' to resolve it",
+                    branch, into, follow_up_id
+                );
+            }
+            Ok(Some((follow_up_id, title)))
         }
     }
-
-    Ok(())
 }
 
 #[cfg(test)]
@@ -147,7 +290,7 @@ mod tests {
         let dir_path = dir.path();
         setup_workgraph(dir_path, vec![make_task("t1", "Test task", Status::Open)]);
 
-        let result = run(dir_path, "t1", false);
+        let result = run(dir_path, "t1", false, false);
         assert!(result.is_ok());
 
         let path = graph_path(dir_path);
@@ -165,7 +308,7 @@ mod tests {
             vec![make_task("t1", "Test task", Status::InProgress)],
         );
 
-        let result = run(dir_path, "t1", false);
+        let result = run(dir_path, "t1", false, false);
         assert!(result.is_ok());
 
         let path = graph_path(dir_path);
@@ -181,7 +324,7 @@ mod tests {
         setup_workgraph(dir_path, vec![make_task("t1", "Test task", Status::Done)]);
 
         // Should return Ok (idempotent) rather than error
-        let result = run(dir_path, "t1", false);
+        let result = run(dir_path, "t1", false, false);
         assert!(result.is_ok());
     }
 
@@ -196,7 +339,7 @@ mod tests {
 
         setup_workgraph(dir_path, vec![blocker, blocked]);
 
-        let result = run(dir_path, "blocked", false);
+        let result = run(dir_path, "blocked", false, false);
         assert!(result.is_err());
         let err = result.unwrap_err();
         assert!(err.to_string().contains("blocked by"));
@@ -214,7 +357,7 @@ mod tests {
 
         setup_workgraph(dir_path, vec![blocker, blocked]);
 
-        let result = run(dir_path, "blocked", false);
+        let result = run(dir_path, "blocked", false, false);
         assert!(result.is_ok());
 
         let path = graph_path(dir_path);
@@ -235,7 +378,7 @@ mod tests {
 
         setup_workgraph(dir_path, vec![blocker, blocked]);
 
-        let result = run(dir_path, "blocked", false);
+        let result = run(dir_path, "blocked", false, false);
         assert!(result.is_ok());
 
         let path = graph_path(dir_path);
@@ -244,6 +387,82 @@ mod tests {
         assert_eq!(task.status, Status::Done);
     }
 
+    #[test]
+    fn test_done_abandons_failure_only_dependents() {
+        let dir = tempdir().unwrap();
+        let dir_path = dir.path();
+
+        let build = make_task("build", "Build", Status::InProgress);
+        let mut rollback = make_task("rollback", "Rollback", Status::Open);
+        rollback.after = vec!["build".to_string()];
+        rollback.after_conditions.insert(
+            "build".to_string(),
+            workgraph::graph::EdgeCondition::Failure,
+        );
+
+        setup_workgraph(dir_path, vec![build, rollback]);
+
+        run(dir_path, "build", false, false).unwrap();
+
+        let graph = load_graph(graph_path(dir_path)).unwrap();
+        let rollback = graph.get_task("rollback").unwrap();
+        assert_eq!(rollback.status, Status::Abandoned);
+        assert!(
+            rollback
+                .failure_reason
+                .as_deref()
+                .unwrap()
+                .contains("requires 'build' to fail")
+        );
+    }
+
+    #[test]
+    fn test_done_expands_map_over_output() {
+        let dir = tempdir().unwrap();
+        let dir_path = dir.path();
+
+        let discover = make_task("discover", "Discover", Status::InProgress);
+        let mut port = make_task("port", "Port {{item}}", Status::Open);
+        port.after = vec!["discover".to_string()];
+        port.map = Some(workgraph::graph::MapSpec {
+            source: "discover".to_string(),
+            from: "modules.json".to_string(),
+            template: workgraph::trace_function::TaskTemplate {
+                template_id: "port".to_string(),
+                title: "Port {{item}}".to_string(),
+                description: String::new(),
+                skills: vec![],
+                after: vec![],
+                loops_to: vec![],
+                role_hint: None,
+                deliverables: vec![],
+                verify: None,
+                tags: vec![],
+            },
+            children: vec![],
+            join: None,
+        });
+        setup_workgraph(dir_path, vec![discover, port]);
+
+        let output = dir_path.join("output").join("discover");
+        std::fs::create_dir_all(&output).unwrap();
+        std::fs::write(output.join("modules.json"), r#"["cli", "core"]"#).unwrap();
+
+        run(dir_path, "discover", false, false).unwrap();
+
+        let graph = load_graph(graph_path(dir_path)).unwrap();
+        assert_eq!(graph.get_task("port").unwrap().status, Status::Done);
+        assert_eq!(graph.get_task("port-0").unwrap().title, "Port cli");
+        assert_eq!(graph.get_task("port-1").unwrap().title, "Port core");
+        assert_eq!(graph.get_task("port-join").unwrap().status, Status::Open);
+
+        // Finishing both children completes the join
+        run(dir_path, "port-0", false, false).unwrap();
+        run(dir_path, "port-1", false, false).unwrap();
+        let graph = load_graph(graph_path(dir_path)).unwrap();
+        assert_eq!(graph.get_task("port-join").unwrap().status, Status::Done);
+    }
+
     #[test]
     fn test_done_with_abandoned_blocker_succeeds() {
         // Abandoned blockers are terminal — they should not block dependents
@@ -256,7 +475,7 @@ mod tests {
 
         setup_workgraph(dir_path, vec![blocker, blocked]);
 
-        let result = run(dir_path, "blocked", false);
+        let result = run(dir_path, "blocked", false, false);
         assert!(result.is_ok());
 
         let path = graph_path(dir_path);
@@ -276,7 +495,7 @@ mod tests {
         setup_workgraph(dir_path, vec![task]);
 
         // Verified tasks can now use wg done directly (submit is deprecated)
-        let result = run(dir_path, "t1", false);
+        let result = run(dir_path, "t1", false, false);
         assert!(result.is_ok());
 
         let path = graph_path(dir_path);
@@ -292,7 +511,7 @@ mod tests {
         setup_workgraph(dir_path, vec![make_task("t1", "Test task", Status::Open)]);
 
         let before = Utc::now();
-        let result = run(dir_path, "t1", false);
+        let result = run(dir_path, "t1", false, false);
         assert!(result.is_ok());
 
         let path = graph_path(dir_path);
@@ -315,7 +534,7 @@ mod tests {
         task.assigned = Some("agent-1".to_string());
         setup_workgraph(dir_path, vec![task]);
 
-        let result = run(dir_path, "t1", false);
+        let result = run(dir_path, "t1", false, false);
         assert!(result.is_ok());
 
         let path = graph_path(dir_path);
@@ -334,7 +553,7 @@ mod tests {
         let dir_path = dir.path();
         setup_workgraph(dir_path, vec![]);
 
-        let result = run(dir_path, "nonexistent", false);
+        let result = run(dir_path, "nonexistent", false, false);
         assert!(result.is_err());
         let err = result.unwrap_err();
         assert!(err.to_string().contains("not found"));
@@ -346,7 +565,7 @@ mod tests {
         let dir_path = dir.path();
         // Don't initialize workgraph
 
-        let result = run(dir_path, "t1", false);
+        let result = run(dir_path, "t1", false, false);
         assert!(result.is_err());
         let err = result.unwrap_err();
         assert!(err.to_string().contains("not initialized"));
@@ -358,7 +577,7 @@ mod tests {
         let dir_path = dir.path();
         setup_workgraph(dir_path, vec![make_task("t1", "Test task", Status::Open)]);
 
-        let result = run(dir_path, "t1", false);
+        let result = run(dir_path, "t1", false, false);
         assert!(result.is_ok());
 
         let path = graph_path(dir_path);
@@ -375,7 +594,7 @@ mod tests {
         let dir_path = dir.path();
         setup_workgraph(dir_path, vec![make_task("t1", "Test task", Status::Open)]);
 
-        let result = run(dir_path, "t1", true);
+        let result = run(dir_path, "t1", true, false);
         assert!(result.is_ok());
 
         let path = graph_path(dir_path);
@@ -385,4 +604,104 @@ mod tests {
         let last_log = task.log.last().unwrap();
         assert_eq!(last_log.message, "Task marked as done (converged)");
     }
+
+    #[test]
+    fn test_done_enforces_output_contract() {
+        let dir = tempdir().unwrap();
+        let dir_path = dir.path();
+        let mut task = make_task("t1", "Test task", Status::InProgress);
+        task.contract = Some(workgraph::graph::OutputContract {
+            result_schema: Some(serde_json::json!({"type": "object", "required": ["count"]})),
+            ..Default::default()
+        });
+        setup_workgraph(dir_path, vec![task]);
+
+        let err = run(dir_path, "t1", false, false).unwrap_err();
+        assert!(err.to_string().contains("output contract is not met"));
+        let graph = load_graph(graph_path(dir_path)).unwrap();
+        assert_eq!(graph.get_task("t1").unwrap().status, Status::InProgress);
+
+        let result = workgraph::contract::stored_result_path(dir_path, "t1");
+        std::fs::create_dir_all(result.parent().unwrap()).unwrap();
+        std::fs::write(&result, r#"{"count": 3}"#).unwrap();
+        run(dir_path, "t1", false, false).unwrap();
+        let graph = load_graph(graph_path(dir_path)).unwrap();
+        assert_eq!(graph.get_task("t1").unwrap().status, Status::Done);
+    }
+
+    #[test]
+    fn test_done_fail_unmet_fails_task() {
+        let dir = tempdir().unwrap();
+        let dir_path = dir.path();
+        let mut task = make_task("t1", "Test task", Status::InProgress);
+        task.contract = Some(workgraph::graph::OutputContract {
+            files: vec!["missing-*.txt".to_string()],
+            ..Default::default()
+        });
+        setup_workgraph(dir_path, vec![task]);
+
+        run(dir_path, "t1", false, true).unwrap();
+        let graph = load_graph(graph_path(dir_path)).unwrap();
+        let task = graph.get_task("t1").unwrap();
+        assert_eq!(task.status, Status::Failed);
+        assert!(
+            task.failure_reason
+                .as_deref()
+                .is_some_and(|r| r.contains("nothing matches 'missing-*.txt'"))
+        );
+    }
+
+    #[test]
+    fn test_done_holds_dependents_behind_merge_conflict() {
+        let tmp = tempdir().unwrap();
+        let root = tmp.path();
+        let git = |args: &[&str]| {
+            let status = std::process::Command::new("git")
+                .args(["-c", "user.name=t", "-c", "user.email=t@localhost"])
+                .args(args)
+                .current_dir(root)
+                .output()
+                .unwrap()
+                .status;
+            assert!(status.success(), "git {:?}", args);
+        };
+        git(&["init", "-q", "-b", "main"]);
+        std::fs::write(root.join("shared.txt"), "one\n").unwrap();
+        git(&["add", "-A"]);
+        git(&["commit", "-q", "-m", "init"]);
+
+        let wg_dir = root.join(".workgraph");
+        let mut deploy = make_task("deploy", "Deploy", Status::Open);
+        deploy.after = vec!["b".to_string()];
+        setup_workgraph(
+            &wg_dir,
+            vec![
+                make_task("a", "Task a", Status::InProgress),
+                make_task("b", "Task b", Status::InProgress),
+                deploy,
+            ],
+        );
+        for id in ["a", "b"] {
+            let worktree = worktree::prepare(&wg_dir, id, None).unwrap();
+            std::fs::write(worktree.join("shared.txt"), format!("{}\n", id)).unwrap();
+        }
+
+        run(&wg_dir, "a", false, false).unwrap();
+        assert_eq!(
+            std::fs::read_to_string(root.join("shared.txt")).unwrap(),
+            "a\n"
+        );
+        run(&wg_dir, "b", false, false).unwrap();
+
+        let graph = load_graph(graph_path(&wg_dir)).unwrap();
+        assert_eq!(graph.get_task("b").unwrap().status, Status::Done);
+        let merge = graph.get_task("merge-b").unwrap();
+        assert_eq!(merge.after, vec!["b".to_string()]);
+        assert_eq!(merge.before, vec!["deploy".to_string()]);
+        let deploy = graph.get_task("deploy").unwrap();
+        assert!(deploy.after.contains(&"merge-b".to_string()));
+        assert!(!query::ready_tasks(&graph).iter().any(|t| t.id == "deploy"));
+        let patch = std::fs::read_to_string(wg_dir.join("output/b/changes.patch")).unwrap();
+        assert!(patch.contains("+b"), "{}", patch);
+    }
 }
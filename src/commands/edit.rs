@@ -7,25 +7,57 @@ use workgraph::parser::{load_graph, save_graph};
 
 use super::graph_path;
 
+/// Changes `wg edit` makes to a task. Unset options leave the field alone.
+#[derive(Debug, Default)]
+pub struct EditOptions<'a> {
+    pub title: Option<&'a str>,
+    pub description: Option<&'a str>,
+    pub add_after: &'a [String],
+    pub remove_after: &'a [String],
+    pub add_tag: &'a [String],
+    pub remove_tag: &'a [String],
+    pub model: Option<&'a str>,
+    pub priority: Option<i32>,
+    pub requester: Option<&'a str>,
+    pub add_skill: &'a [String],
+    pub remove_skill: &'a [String],
+    pub add_requires: &'a [(String, Option<f64>)],
+    pub remove_requires: &'a [String],
+    pub max_iterations: Option<u32>,
+    pub cycle_guard: Option<&'a str>,
+    pub cycle_delay: Option<&'a str>,
+    pub timeout: Option<&'a str>,
+    pub schedule: Option<&'a str>,
+    pub schedule_tz: Option<&'a str>,
+    pub catch_up: Option<&'a str>,
+    pub visibility: Option<&'a str>,
+}
+
 /// Edit a task's fields
-#[allow(clippy::too_many_arguments)]
-pub fn run(
-    dir: &Path,
-    task_id: &str,
-    title: Option<&str>,
-    description: Option<&str>,
-    add_after: &[String],
-    remove_after: &[String],
-    add_tag: &[String],
-    remove_tag: &[String],
-    model: Option<&str>,
-    add_skill: &[String],
-    remove_skill: &[String],
-    max_iterations: Option<u32>,
-    cycle_guard: Option<&str>,
-    cycle_delay: Option<&str>,
-    visibility: Option<&str>,
-) -> Result<()> {
+pub fn run(dir: &Path, task_id: &str, options: EditOptions) -> Result<()> {
+    let EditOptions {
+        title,
+        description,
+        add_after,
+        remove_after,
+        add_tag,
+        remove_tag,
+        model,
+        priority,
+        requester,
+        add_skill,
+        remove_skill,
+        add_requires,
+        remove_requires,
+        max_iterations,
+        cycle_guard,
+        cycle_delay,
+        timeout,
+        schedule,
+        schedule_tz,
+        catch_up,
+        visibility,
+    } = options;
     let path = graph_path(dir);
 
     if !path.exists() {
@@ -38,6 +70,12 @@ pub fn run(
     // Validate task exists
     graph.get_task_or_err(task_id)?;
 
+    for (resource_id, _) in add_requires {
+        if graph.get_resource(resource_id).is_none() {
+            anyhow::bail!("Resource '{}' does not exist", resource_id);
+        }
+    }
+
     // Validate self-blocking
     for dep in add_after {
         if dep == task_id {
@@ -143,6 +181,41 @@ pub fn run(
             }
         }
 
+        // Add required resources, or change the amount held on one
+        for (resource_id, amount) in add_requires {
+            let held = task.requires_amounts.get(resource_id).copied();
+            if task.requires.contains(resource_id) && held == *amount {
+                println!("Already requires: {}", resource_id);
+                continue;
+            }
+            if !task.requires.contains(resource_id) {
+                task.requires.push(resource_id.clone());
+            }
+            match amount {
+                Some(amount) => {
+                    task.requires_amounts.insert(resource_id.clone(), *amount);
+                    println!("Added requires: {} ({})", resource_id, amount);
+                }
+                None => {
+                    task.requires_amounts.remove(resource_id);
+                    println!("Added requires: {}", resource_id);
+                }
+            }
+            changed = true;
+        }
+
+        // Remove required resources
+        for resource_id in remove_requires {
+            if let Some(pos) = task.requires.iter().position(|x| x == resource_id) {
+                task.requires.remove(pos);
+                task.requires_amounts.remove(resource_id);
+                println!("Removed requires: {}", resource_id);
+                changed = true;
+            } else {
+                println!("Does not require: {}", resource_id);
+            }
+        }
+
         // Update cycle config
         if let Some(max_iter) = max_iterations {
             let guard = match cycle_guard {
@@ -200,6 +273,84 @@ pub fn run(
             }
         }
 
+        // Update dispatch priority
+        if let Some(p) = priority
+            && task.priority != p
+        {
+            let old = std::mem::replace(&mut task.priority, p);
+            field_changes.push(serde_json::json!({"field": "priority", "old": old, "new": p}));
+            println!("Updated priority: {}", p);
+            changed = true;
+        }
+
+        // Update requester
+        if requester == Some("none") {
+            if let Some(old) = task.requester.take() {
+                field_changes
+                    .push(serde_json::json!({"field": "requester", "old": old, "new": null}));
+                println!("Removed requester");
+                changed = true;
+            }
+        } else if let Some(r) = requester {
+            let old = task.requester.replace(r.to_string());
+            field_changes.push(serde_json::json!({"field": "requester", "old": old, "new": r}));
+            println!("Updated requester: {}", r);
+            changed = true;
+        }
+
+        // Update timeout
+        if timeout == Some("none") {
+            if let Some(old) = task.timeout.take() {
+                field_changes
+                    .push(serde_json::json!({"field": "timeout", "old": old, "new": null}));
+                println!("Removed timeout");
+                changed = true;
+            }
+        } else if let Some(t) = timeout {
+            parse_delay(t).ok_or_else(|| {
+                anyhow::anyhow!("Invalid timeout '{}'. Use format: 30s, 5m, 1h, 24h, 7d", t)
+            })?;
+            let old = task.timeout.replace(t.to_string());
+            field_changes.push(serde_json::json!({"field": "timeout", "old": old, "new": t}));
+            println!("Updated timeout: {}", t);
+            changed = true;
+        }
+
+        // Update schedule. A new expression or timezone restarts the window
+        // count, so no backlog of windows fires under it.
+        if schedule == Some("none") {
+            if task.schedule.take().is_some() {
+                println!("Removed schedule");
+                changed = true;
+            }
+        } else if schedule.is_some() || schedule_tz.is_some() || catch_up.is_some() {
+            let current = task.schedule.as_ref();
+            let Some(cron) = schedule.or(current.map(|s| s.cron.as_str())) else {
+                anyhow::bail!(
+                    "Cannot set --schedule-tz or --catch-up without --schedule: task has no schedule"
+                );
+            };
+            let timezone = schedule_tz.or(current.and_then(|s| s.timezone.as_deref()));
+            let mut updated = crate::commands::add::parse_schedule(cron, timezone, catch_up)?;
+            if catch_up.is_none()
+                && let Some(current) = current
+            {
+                updated.catch_up = current.catch_up;
+            }
+            updated.last_window = match current {
+                Some(c) if c.cron == updated.cron && c.timezone == updated.timezone => {
+                    c.last_window.clone()
+                }
+                _ => Some(chrono::Utc::now().to_rfc3339()),
+            };
+            let old = current.map(|s| s.cron.clone());
+            field_changes
+                .push(serde_json::json!({"field": "schedule", "old": old, "new": updated.cron}));
+            println!("Set schedule: {}", updated.cron);
+            task.schedule = Some(updated);
+            changed = true;
+        }
+
         // Update visibility
         if let Some(vis) = visibility {
             match vis {
@@ -275,23 +426,15 @@ mod tests {
         crate::commands::add::run(
             dir,
             "Test Task",
-            Some("test-task"),
-            Some("Original description"),
-            &["dep1".to_string()],
-            None,
-            None,
-            None,
-            &["tag1".to_string()],
-            &["skill1".to_string()],
-            &[],
-            &[],
-            None,
-            Some("sonnet"),
-            None,
-            None,
-            None,
-            None,
-            "internal",
+            crate::commands::add::AddOptions {
+                id: Some("test-task"),
+                description: Some("Original description"),
+                after: &["dep1".to_string()],
+                tags: &["tag1".to_string()],
+                skills: &["skill1".to_string()],
+                model: Some("sonnet"),
+                ..Default::default()
+            },
         )?;
 
         Ok(())
@@ -306,45 +449,23 @@ mod tests {
         crate::commands::add::run(
             dir,
             "Blocker Task",
-            Some("blocker-task"),
-            None,
-            &[],
-            None,
-            None,
-            None,
-            &[],
-            &[],
-            &[],
-            &[],
-            None,
-            None,
-            None,
-            None,
-            None,
-            None,
-            "internal",
+            crate::commands::add::AddOptions {
+                id: Some("blocker-task"),
+                ..Default::default()
+            },
         )?;
 
         crate::commands::add::run(
             dir,
             "Test Task",
-            Some("test-task"),
-            Some("Original description"),
-            &[],
-            None,
-            None,
-            None,
-            &["tag1".to_string()],
-            &["skill1".to_string()],
-            &[],
-            &[],
-            None,
-            Some("sonnet"),
-            None,
-            None,
-            None,
-            None,
-            "internal",
+            crate::commands::add::AddOptions {
+                id: Some("test-task"),
+                description: Some("Original description"),
+                tags: &["tag1".to_string()],
+                skills: &["skill1".to_string()],
+                model: Some("sonnet"),
+                ..Default::default()
+            },
         )?;
 
         Ok(())
@@ -358,19 +479,10 @@ mod tests {
         let result = run(
             temp_dir.path(),
             "test-task",
-            Some("New Title"),
-            None,
-            &[],
-            &[],
-            &[],
-            &[],
-            None,
-            &[],
-            &[],
-            None,
-            None,
-            None,
-            None,
+            EditOptions {
+                title: Some("New Title"),
+                ..Default::default()
+            },
         );
         assert!(result.is_ok());
 
@@ -388,19 +500,10 @@ mod tests {
         let result = run(
             temp_dir.path(),
             "test-task",
-            None,
-            Some("New description"),
-            &[],
-            &[],
-            &[],
-            &[],
-            None,
-            &[],
-            &[],
-            None,
-            None,
-            None,
-            None,
+            EditOptions {
+                description: Some("New description"),
+                ..Default::default()
+            },
         );
         assert!(result.is_ok());
 
@@ -418,19 +521,10 @@ mod tests {
         let result = run(
             temp_dir.path(),
             "test-task",
-            None,
-            None,
-            &["dep2".to_string()],
-            &[],
-            &[],
-            &[],
-            None,
-            &[],
-            &[],
-            None,
-            None,
-            None,
-            None,
+            EditOptions {
+                add_after: &["dep2".to_string()],
+                ..Default::default()
+            },
         );
         assert!(result.is_ok());
 
@@ -449,19 +543,10 @@ mod tests {
         let result = run(
             temp_dir.path(),
             "test-task",
-            None,
-            None,
-            &[],
-            &["dep1".to_string()],
-            &[],
-            &[],
-            None,
-            &[],
-            &[],
-            None,
-            None,
-            None,
-            None,
+            EditOptions {
+                remove_after: &["dep1".to_string()],
+                ..Default::default()
+            },
         );
         assert!(result.is_ok());
 
@@ -479,19 +564,10 @@ mod tests {
         let result = run(
             temp_dir.path(),
             "test-task",
-            None,
-            None,
-            &[],
-            &[],
-            &["tag2".to_string()],
-            &[],
-            None,
-            &[],
-            &[],
-            None,
-            None,
-            None,
-            None,
+            EditOptions {
+                add_tag: &["tag2".to_string()],
+                ..Default::default()
+            },
         );
         assert!(result.is_ok());
 
@@ -510,19 +586,10 @@ mod tests {
         let result = run(
             temp_dir.path(),
             "test-task",
-            None,
-            None,
-            &[],
-            &[],
-            &[],
-            &["tag1".to_string()],
-            None,
-            &[],
-            &[],
-            None,
-            None,
-            None,
-            None,
+            EditOptions {
+                remove_tag: &["tag1".to_string()],
+                ..Default::default()
+            },
         );
         assert!(result.is_ok());
 
@@ -540,19 +607,10 @@ mod tests {
         let result = run(
             temp_dir.path(),
             "test-task",
-            None,
-            None,
-            &[],
-            &[],
-            &[],
-            &[],
-            Some("opus"),
-            &[],
-            &[],
-            None,
-            None,
-            None,
-            None,
+            EditOptions {
+                model: Some("opus"),
+                ..Default::default()
+            },
         );
         assert!(result.is_ok());
 
@@ -570,19 +628,10 @@ mod tests {
         let result = run(
             temp_dir.path(),
             "test-task",
-            None,
-            None,
-            &[],
-            &[],
-            &[],
-            &[],
-            None,
-            &["skill2".to_string()],
-            &[],
-            None,
-            None,
-            None,
-            None,
+            EditOptions {
+                add_skill: &["skill2".to_string()],
+                ..Default::default()
+            },
         );
         assert!(result.is_ok());
 
@@ -601,26 +650,96 @@ mod tests {
         let result = run(
             temp_dir.path(),
             "test-task",
+            EditOptions {
+                remove_skill: &["skill1".to_string()],
+                ..Default::default()
+            },
+        );
+        assert!(result.is_ok());
+
+        let path = graph_path(temp_dir.path());
+        let graph = load_graph(&path).unwrap();
+        let task = graph.get_task("test-task").unwrap();
+        assert!(!task.skills.contains(&"skill1".to_string()));
+    }
+
+    #[test]
+    fn test_add_and_remove_requires() {
+        let temp_dir = TempDir::new().unwrap();
+        create_test_graph(temp_dir.path()).unwrap();
+        crate::commands::resource::run_add(
+            temp_dir.path(),
+            "gpu",
             None,
             None,
-            &[],
-            &[],
-            &[],
-            &[],
-            None,
-            &[],
-            &["skill1".to_string()],
-            None,
-            None,
+            Some(2.0),
             None,
             None,
-        );
-        assert!(result.is_ok());
+        )
+        .unwrap();
 
+        let edit = |add: &[(String, Option<f64>)], remove: &[String]| {
+            run(
+                temp_dir.path(),
+                "test-task",
+                EditOptions {
+                    add_requires: add,
+                    remove_requires: remove,
+                    ..Default::default()
+                },
+            )
+        };
         let path = graph_path(temp_dir.path());
+
+        edit(&[("gpu".to_string(), Some(1.0))], &[]).unwrap();
         let graph = load_graph(&path).unwrap();
         let task = graph.get_task("test-task").unwrap();
-        assert!(!task.skills.contains(&"skill1".to_string()));
+        assert_eq!(task.requires, vec!["gpu"]);
+        assert_eq!(task.requires_amounts.get("gpu"), Some(&1.0));
+
+        // Re-adding changes the amount
+        edit(&[("gpu".to_string(), None)], &[]).unwrap();
+        let graph = load_graph(&path).unwrap();
+        let task = graph.get_task("test-task").unwrap();
+        assert_eq!(task.requires, vec!["gpu"]);
+        assert!(task.requires_amounts.is_empty());
+
+        edit(&[], &["gpu".to_string()]).unwrap();
+        let graph = load_graph(&path).unwrap();
+        assert!(graph.get_task("test-task").unwrap().requires.is_empty());
+
+        assert!(edit(&[("nope".to_string(), None)], &[]).is_err());
+    }
+
+    #[test]
+    fn test_set_priority_and_requester() {
+        let temp_dir = TempDir::new().unwrap();
+        create_test_graph(temp_dir.path()).unwrap();
+
+        let edit = |priority: Option<i32>, requester: Option<&str>| {
+            run(
+                temp_dir.path(),
+                "test-task",
+                EditOptions {
+                    priority,
+                    requester,
+                    ..Default::default()
+                },
+            )
+        };
+        let path = graph_path(temp_dir.path());
+
+        edit(Some(-2), Some("alice")).unwrap();
+        let graph = load_graph(&path).unwrap();
+        let task = graph.get_task("test-task").unwrap();
+        assert_eq!(task.priority, -2);
+        assert_eq!(task.requester.as_deref(), Some("alice"));
+
+        edit(Some(0), Some("none")).unwrap();
+        let graph = load_graph(&path).unwrap();
+        let task = graph.get_task("test-task").unwrap();
+        assert_eq!(task.priority, 0);
+        assert!(task.requester.is_none());
     }
 
     #[test]
@@ -631,19 +750,10 @@ mod tests {
         let result = run(
             temp_dir.path(),
             "nonexistent-task",
-            Some("New Title"),
-            None,
-            &[],
-            &[],
-            &[],
-            &[],
-            None,
-            &[],
-            &[],
-            None,
-            None,
-            None,
-            None,
+            EditOptions {
+                title: Some("New Title"),
+                ..Default::default()
+            },
         );
         assert!(result.is_err());
         assert!(result.unwrap_err().to_string().contains("not found"));
@@ -654,23 +764,7 @@ mod tests {
         let temp_dir = TempDir::new().unwrap();
         create_test_graph(temp_dir.path()).unwrap();
 
-        let result = run(
-            temp_dir.path(),
-            "test-task",
-            None,
-            None,
-            &[],
-            &[],
-            &[],
-            &[],
-            None,
-            &[],
-            &[],
-            None,
-            None,
-            None,
-            None,
-        );
+        let result = run(temp_dir.path(), "test-task", EditOptions::default());
         assert!(result.is_ok());
     }
 
@@ -682,19 +776,10 @@ mod tests {
         let result = run(
             temp_dir.path(),
             "test-task",
-            None,
-            None,
-            &["test-task".to_string()],
-            &[],
-            &[],
-            &[],
-            None,
-            &[],
-            &[],
-            None,
-            None,
-            None,
-            None,
+            EditOptions {
+                add_after: &["test-task".to_string()],
+                ..Default::default()
+            },
         );
         assert!(result.is_err());
         assert!(
@@ -714,19 +799,10 @@ mod tests {
         let result = run(
             temp_dir.path(),
             "test-task",
-            None,
-            None,
-            &["blocker-task".to_string()],
-            &[],
-            &[],
-            &[],
-            None,
-            &[],
-            &[],
-            None,
-            None,
-            None,
-            None,
+            EditOptions {
+                add_after: &["blocker-task".to_string()],
+                ..Default::default()
+            },
         );
         assert!(result.is_ok());
 
@@ -750,19 +826,10 @@ mod tests {
         run(
             temp_dir.path(),
             "test-task",
-            None,
-            None,
-            &["blocker-task".to_string()],
-            &[],
-            &[],
-            &[],
-            None,
-            &[],
-            &[],
-            None,
-            None,
-            None,
-            None,
+            EditOptions {
+                add_after: &["blocker-task".to_string()],
+                ..Default::default()
+            },
         )
         .unwrap();
 
@@ -770,19 +837,10 @@ mod tests {
         let result = run(
             temp_dir.path(),
             "test-task",
-            None,
-            None,
-            &[],
-            &["blocker-task".to_string()],
-            &[],
-            &[],
-            None,
-            &[],
-            &[],
-            None,
-            None,
-            None,
-            None,
+            EditOptions {
+                remove_after: &["blocker-task".to_string()],
+                ..Default::default()
+            },
         );
         assert!(result.is_ok());
 
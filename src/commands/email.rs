@@ -0,0 +1,514 @@
+//! Email executor: send tasks to human agents and apply their replies
+//!
+//! Usage:
+//!   wg email send <task-id>
+//!   wg email ingest
+//!
+//! The coordinator does both on its own: ready tasks assigned to agents with
+//! `executor = "email"` are mailed to the agent's contact address instead of
+//! spawning a process, and replies in `[email] maildir` are applied every
+//! tick. A reply is only accepted from the contact address of the agent the
+//! task is assigned to, and counts as that agent's work.
+
+use anyhow::{Context, Result};
+use chrono::Utc;
+use serde::Serialize;
+use std::fmt::Write as _;
+use std::path::Path;
+use workgraph::agency::{self, Agent};
+use workgraph::config::Config;
+use workgraph::email::{self, ReplyAction};
+use workgraph::graph::{LogEntry, Status, Task, WorkGraph};
+use workgraph::parser::save_graph;
+
+/// Mail `task_id` to `agent` and claim it on the agent's behalf. A task the
+/// agent already holds is sent again as a reminder. Returns the address the
+/// task went to.
+pub fn dispatch(dir: &Path, task_id: &str, agent: &Agent) -> Result<String> {
+    let contact = agent
+        .contact
+        .clone()
+        .filter(|c| !c.trim().is_empty())
+        .ok_or_else(|| anyhow::anyhow!("Agent '{}' has no contact address", agent.name))?;
+    let config = Config::load_or_default(dir);
+
+    let (graph, _path) = super::load_workgraph(dir)?;
+    let task = graph.get_task_or_err(task_id)?;
+    let resend = task.status == Status::InProgress && task.assigned.as_ref() == Some(&agent.name);
+    if !resend {
+        match task.status {
+            Status::Open | Status::Blocked => super::ensure_capacity(&graph, task_id)?,
+            _ => anyhow::bail!(
+                "Task '{}' is {} and cannot be sent to '{}'",
+                task_id,
+                task.status,
+                agent.name
+            ),
+        }
+    }
+    if task.gate.is_some() {
+        anyhow::bail!(
+            "Task '{}' is an approval gate. Use 'wg approve' or 'wg reject'.",
+            task_id
+        );
+    }
+    // Held through the send, so no other claim takes the capacity meanwhile
+    let claim = if resend {
+        None
+    } else {
+        Some(workgraph::capacity::lock_claim(dir, task)?)
+    };
+    let token = email::reply_token(dir, task_id)?;
+    let body = task_message(&graph, task, agent, &token);
+    email::send(
+        &config.email,
+        &contact,
+        &email::task_subject(task, &token),
+        &body,
+    )?;
+
+    // Claim only once the mail is out; a failed save means a second copy
+    // on the next attempt rather than a task nobody was told about.
+    let (mut graph, path) = super::load_workgraph_mut(dir)?;
+    let task = graph.get_task_mut_or_err(task_id)?;
+    let prev_status = format!("{:?}", task.status);
+    let prev_assigned = task.assigned.clone();
+    if !resend {
+        task.status = Status::InProgress;
+        task.started_at = Some(Utc::now().to_rfc3339());
+        task.assigned = Some(agent.name.clone());
+    }
+    task.log.push(LogEntry {
+        timestamp: Utc::now().to_rfc3339(),
+        actor: None,
+        message: if resend {
+            format!("Task sent again to @{} at {}", agent.name, contact)
+        } else {
+            format!("Task sent to @{} at {}", agent.name, contact)
+        },
+    });
+    save_graph(&graph, &path).context("Failed to save graph")?;
+    drop(claim);
+    super::notify_graph_changed(dir);
+
+    if !resend {
+        let _ = workgraph::provenance::record(
+            dir,
+            "claim",
+            Some(task_id),
+            Some(&agent.name),
+            serde_json::json!({
+                "prev_status": prev_status,
+                "prev_assigned": prev_assigned,
+                "executor": "email",
+                "to": contact,
+            }),
+            config.log.rotation_threshold,
+        );
+    }
+    Ok(contact)
+}
+
+/// The email body sent for a task.
+fn task_message(graph: &WorkGraph, task: &Task, agent: &Agent, token: &str) -> String {
+    let mut body = format!(
+        "Hi {},\n\nYou have a task from workgraph.\n\n  Task:  {}\n  Title: {}\n",
+        agent.name, task.id, task.title
+    );
+    if let Some(ref description) = task.description {
+        let (values, _) = workgraph::bindings::resolve(graph, task);
+        let description = workgraph::bindings::apply(description, &values);
+        let _ = write!(body, "\n{}\n", description.trim_end());
+    }
+    if !task.deliverables.is_empty() {
+        body.push_str("\nDeliverables:\n");
+        for deliverable in &task.deliverables {
+            let _ = writeln!(body, "  - {}", deliverable);
+        }
+    }
+    let _ = write!(
+        body,
+        "\nContext from dependencies:\n{}\n",
+        super::spawn::build_task_context(graph, task)
+    );
+    let _ = write!(
+        body,
+        "\nTo report back, reply to this email and keep [wg:{}#{}] in the subject.\n\
+         Your reply is added to the task log. Add [done] to the subject when the\n\
+         task is finished, or [fail] if it can't be done, saying why in the reply.\n",
+        task.id, token
+    );
+    body
+}
+
+/// The email agent a task is assigned to.
+fn task_agent(dir: &Path, task: &Task) -> Result<Agent> {
+    let hash = task.agent.as_deref().ok_or_else(|| {
+        anyhow::anyhow!(
+            "Task '{}' has no agent; assign one with 'wg assign {} <agent>'",
+            task.id,
+            task.id
+        )
+    })?;
+    let agent = agency::find_agent_by_prefix(&dir.join("agency").join("agents"), hash)
+        .with_context(|| format!("Failed to load agent '{}' of task '{}'", hash, task.id))?;
+    if agent.executor != "email" {
+        anyhow::bail!(
+            "Task '{}' is assigned to '{}', whose executor is '{}', not email",
+            task.id,
+            agent.name,
+            agent.executor
+        );
+    }
+    Ok(agent)
+}
+
+/// `wg email send <task-id>`
+pub fn run_send(dir: &Path, task_id: &str, json: bool) -> Result<()> {
+    let (graph, _path) = super::load_workgraph(dir)?;
+    let agent = task_agent(dir, graph.get_task_or_err(task_id)?)?;
+    let to = dispatch(dir, task_id, &agent)?;
+    if json {
+        println!(
+            "{}",
+            serde_json::to_string_pretty(&serde_json::json!({
+                "task_id": task_id,
+                "agent": agent.name,
+                "to": to,
+            }))?
+        );
+    } else {
+        println!("Sent '{}' to {} at {}", task_id, agent.name, to);
+    }
+    Ok(())
+}
+
+/// What became of one message read from the Maildir.
+#[derive(Debug, Serialize)]
+pub struct Ingested {
+    pub file: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub task_id: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub action: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub from: Option<String>,
+    pub applied: bool,
+    /// Why the message was skipped, if it was
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub reason: Option<String>,
+}
+
+impl std::fmt::Display for Ingested {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match (&self.task_id, &self.action, &self.reason) {
+            (Some(task), Some(action), None) => write!(
+                f,
+                "Applied {} to '{}' from {}",
+                action,
+                task,
+                self.from.as_deref().unwrap_or("?")
+            ),
+            (_, _, reason) => write!(
+                f,
+                "Skipped {}: {}",
+                self.file,
+                reason.as_deref().unwrap_or("not applied")
+            ),
+        }
+    }
+}
+
+/// Apply every new reply in the configured Maildir. Each message is moved to
+/// `cur/` once read, whether or not it could be applied.
+pub fn ingest(dir: &Path) -> Result<Vec<Ingested>> {
+    let config = Config::load_or_default(dir);
+    let maildir = config
+        .email
+        .maildir_path()
+        .ok_or_else(|| anyhow::anyhow!("No reply Maildir; set [email] maildir in config.toml"))?;
+
+    let mut results = Vec::new();
+    for message in email::new_messages(&maildir)? {
+        let raw = std::fs::read(&message)
+            .with_context(|| format!("Failed to read {}", message.display()))?;
+        let file = message
+            .file_name()
+            .map(|n| n.to_string_lossy().into_owned())
+            .unwrap_or_default();
+        let mut result = Ingested {
+            file,
+            task_id: None,
+            action: None,
+            from: None,
+            applied: false,
+            reason: None,
+        };
+        match email::parse_reply(&String::from_utf8_lossy(&raw)) {
+            Ok(reply) => {
+                result.task_id = Some(reply.task_id.clone());
+                result.action = Some(reply.action.to_string());
+                result.from = Some(reply.sender.clone());
+                match apply_reply(dir, &reply, &result.file, &config) {
+                    Ok(()) => result.applied = true,
+                    Err(e) => result.reason = Some(format!("{:#}", e)),
+                }
+            }
+            Err(reason) => result.reason = Some(reason),
+        }
+        email::mark_seen(&message)?;
+        results.push(result);
+    }
+    Ok(results)
+}
+
+/// Apply a reply as the task's agent. Anyone can put the agent's address in
+/// `From:`, so the reply must also carry the task's token, which only went
+/// to that address.
+fn apply_reply(dir: &Path, reply: &email::Reply, file: &str, config: &Config) -> Result<()> {
+    let (graph, _path) = super::load_workgraph(dir)?;
+    let task = graph.get_task_or_err(&reply.task_id)?;
+    if reply.token.as_deref() != Some(email::reply_token(dir, &task.id)?.as_str()) {
+        anyhow::bail!("the subject doesn't carry the reply token of '{}'", task.id);
+    }
+    let agent = task_agent(dir, task)?;
+    let contact = agent.contact.as_deref().map(email::address_of);
+    if !contact.is_some_and(|c| c.eq_ignore_ascii_case(&reply.sender)) {
+        anyhow::bail!(
+            "{} is not the contact address of '{}', the task's agent",
+            reply.sender,
+            agent.name
+        );
+    }
+    if reply.action == ReplyAction::Log && reply.text.is_empty() {
+        anyhow::bail!("the reply is empty");
+    }
+
+    workgraph::history::with_actor(&agent.name, || -> Result<()> {
+        match reply.action {
+            ReplyAction::Log | ReplyAction::Done => {
+                if !reply.text.is_empty() {
+                    super::log::run_add(dir, &reply.task_id, &reply.text, Some(&agent.name))?;
+                }
+                if reply.action == ReplyAction::Done {
+                    super::done::run(dir, &reply.task_id, false, false)?;
+                }
+            }
+            ReplyAction::Fail => {
+                let reason = if reply.text.is_empty() {
+                    "Reported failed by email"
+                } else {
+                    &reply.text
+                };
+                super::fail::run(dir, &reply.task_id, Some(reason))?;
+            }
+        }
+        let _ = workgraph::provenance::record(
+            dir,
+            "email_reply",
+            Some(&reply.task_id),
+            Some(&agent.name),
+            serde_json::json!({
+                "action": reply.action.to_string(),
+                "from": reply.sender,
+                "file": file,
+            }),
+            config.log.rotation_threshold,
+        );
+        Ok(())
+    })
+}
+
+/// `wg email ingest`
+pub fn run_ingest(dir: &Path, json: bool) -> Result<()> {
+    let results = ingest(dir)?;
+    if json {
+        println!("{}", serde_json::to_string_pretty(&results)?);
+    } else if results.is_empty() {
+        println!("No new replies");
+    } else {
+        for result in &results {
+            println!("{}", result);
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use workgraph::agency::{Lineage, PerformanceRecord};
+    use workgraph::graph::Node;
+    use workgraph::parser::load_graph;
+
+    fn setup(dir: &Path) -> Agent {
+        let mut graph = WorkGraph::new();
+        graph.add_node(Node::Task(Task {
+            id: "review".into(),
+            title: "Review the draft".into(),
+            status: Status::InProgress,
+            assigned: Some("ada".into()),
+            agent: Some("ada0001".into()),
+            ..Task::default()
+        }));
+        save_graph(&graph, super::super::graph_path(dir)).unwrap();
+        std::fs::write(
+            dir.join("config.toml"),
+            format!("[email]\nmaildir = \"{}\"\n", dir.join("mail").display()),
+        )
+        .unwrap();
+        std::fs::create_dir_all(dir.join("mail").join("new")).unwrap();
+
+        let agent = Agent {
+            id: "ada0001".into(),
+            role_id: String::new(),
+            motivation_id: String::new(),
+            name: "ada".into(),
+            performance: PerformanceRecord {
+                task_count: 0,
+                avg_score: None,
+                evaluations: vec![],
+            },
+            lineage: Lineage::default(),
+            capabilities: Vec::new(),
+            rate: None,
+            capacity: None,
+            trust_level: Default::default(),
+            contact: Some("Ada <ada@example.org>".into()),
+            executor: "email".into(),
+        };
+        agency::save_agent(&agent, &dir.join("agency").join("agents")).unwrap();
+        agent
+    }
+
+    /// Deliver a message whose subject has `{tag}` replaced by the task's
+    /// tag and token.
+    fn deliver(dir: &Path, name: &str, from: &str, subject: &str, body: &str) {
+        let tag = format!("wg:review#{}", email::reply_token(dir, "review").unwrap());
+        std::fs::write(
+            dir.join("mail").join("new").join(name),
+            format!(
+                "From: {}\nSubject: {}\n\n{}\n",
+                from,
+                subject.replace("{tag}", &tag),
+                body
+            ),
+        )
+        .unwrap();
+    }
+
+    #[test]
+    fn test_ingest_applies_replies_from_the_agent_only() {
+        let dir = tempfile::tempdir().unwrap();
+        setup(dir.path());
+        deliver(
+            dir.path(),
+            "1",
+            "ada@example.org",
+            "Re: [{tag}] Review the draft",
+            "Halfway through.\n\n> quoted",
+        );
+        deliver(
+            dir.path(),
+            "2",
+            "mallory@example.org",
+            "Re: [{tag}] Review the draft [done]",
+            "",
+        );
+        // A forged From: without the token
+        deliver(
+            dir.path(),
+            "3",
+            "ada@example.org",
+            "Re: [wg:review] Review the draft [done]",
+            "",
+        );
+        deliver(
+            dir.path(),
+            "4",
+            "ADA@example.org",
+            "Re: [{tag}] Review the draft [done]",
+            "All good.",
+        );
+
+        let results = ingest(dir.path()).unwrap();
+        let applied: Vec<bool> = results.iter().map(|r| r.applied).collect();
+        assert_eq!(applied, [true, false, false, true]);
+        assert!(results[1].reason.as_deref().unwrap().contains("mallory"));
+        assert!(
+            results[2]
+                .reason
+                .as_deref()
+                .unwrap()
+                .contains("reply token")
+        );
+
+        let graph = load_graph(super::super::graph_path(dir.path())).unwrap();
+        let task = graph.get_task("review").unwrap();
+        assert_eq!(task.status, Status::Done);
+        let messages: Vec<(&str, Option<&str>)> = task
+            .log
+            .iter()
+            .map(|e| (e.message.as_str(), e.actor.as_deref()))
+            .filter(|(m, _)| !m.starts_with("Task marked"))
+            .collect();
+        assert_eq!(messages[0], ("Halfway through.", Some("ada")));
+        assert_eq!(messages[1], ("All good.", Some("ada")));
+
+        assert!(
+            email::new_messages(&dir.path().join("mail"))
+                .unwrap()
+                .is_empty()
+        );
+        assert!(ingest(dir.path()).unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_ingest_fail_reply_records_reason() {
+        let dir = tempfile::tempdir().unwrap();
+        setup(dir.path());
+        deliver(
+            dir.path(),
+            "1",
+            "ada@example.org",
+            "Re: [{tag}] Review the draft [fail]",
+            "No access to the repo.",
+        );
+        let results = ingest(dir.path()).unwrap();
+        assert!(results[0].applied, "{}", results[0]);
+
+        let graph = load_graph(super::super::graph_path(dir.path())).unwrap();
+        let task = graph.get_task("review").unwrap();
+        assert_eq!(task.status, Status::Failed);
+        assert_eq!(
+            task.failure_reason.as_deref(),
+            Some("No access to the repo.")
+        );
+    }
+
+    #[test]
+    fn test_task_message_includes_context_and_instructions() {
+        let dir = tempfile::tempdir().unwrap();
+        let agent = setup(dir.path());
+        let mut graph = WorkGraph::new();
+        graph.add_node(Node::Task(Task {
+            id: "draft".into(),
+            title: "Draft".into(),
+            status: Status::Done,
+            artifacts: vec!["docs/draft.md".into()],
+            ..Task::default()
+        }));
+        let task = Task {
+            id: "review".into(),
+            title: "Review the draft".into(),
+            description: Some("Check the tone.".into()),
+            after: vec!["draft".into()],
+            ..Task::default()
+        };
+        let body = task_message(&graph, &task, &agent, "0123abcd");
+        assert!(body.starts_with("Hi ada,"));
+        assert!(body.contains("Check the tone."));
+        assert!(body.contains("From draft: artifacts: docs/draft.md"));
+        assert!(body.contains("keep [wg:review#0123abcd] in the subject"));
+    }
+}
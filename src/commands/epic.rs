@@ -0,0 +1,219 @@
+use anyhow::Result;
+use serde::Serialize;
+use std::path::Path;
+use workgraph::WorkGraph;
+use workgraph::epic::{self, Rollup};
+use workgraph::graph::{Status, Task};
+
+use super::list::status_marker;
+
+#[derive(Debug, Serialize)]
+struct EpicNode {
+    id: String,
+    title: String,
+    status: Status,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    rollup: Option<Rollup>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    children: Vec<EpicNode>,
+}
+
+/// Show one epic as a tree of its subtasks, or list every epic with its
+/// rollup when no ID is given.
+pub fn run(dir: &Path, id: Option<&str>, json: bool) -> Result<()> {
+    let (graph, _path) = super::load_workgraph(dir)?;
+
+    match id {
+        Some(id) => {
+            let task = graph.get_task_or_err(id)?;
+            let node = build_tree(&graph, task, &mut vec![]);
+            if json {
+                println!("{}", serde_json::to_string_pretty(&node)?);
+            } else {
+                print_epic(&node);
+            }
+        }
+        None => {
+            let epics = list_epics(&graph);
+            if json {
+                let nodes: Vec<&EpicNode> = epics.iter().map(|(node, _)| node).collect();
+                println!("{}", serde_json::to_string_pretty(&nodes)?);
+            } else if epics.is_empty() {
+                println!("No epics. Group tasks with `wg add --parent <epic>`.");
+            } else {
+                for (node, depth) in &epics {
+                    println!("{}{}", "  ".repeat(*depth), epic_line(node));
+                }
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Every epic with its nesting depth, sub-epics directly after their parent.
+fn list_epics(graph: &WorkGraph) -> Vec<(EpicNode, usize)> {
+    let epics = epic::epic_ids(graph);
+    let mut ordered: Vec<&Task> = graph
+        .tasks()
+        .filter(|t| epics.contains(t.id.as_str()))
+        .collect();
+    ordered.sort_by_cached_key(|t| {
+        let mut path: Vec<&str> = epic::ancestors(graph, &t.id)
+            .iter()
+            .map(|a| a.id.as_str())
+            .collect();
+        path.reverse();
+        path.push(&t.id);
+        path.join("\u{0}")
+    });
+    ordered
+        .into_iter()
+        .map(|t| {
+            let node = EpicNode {
+                id: t.id.clone(),
+                title: t.title.clone(),
+                status: t.status,
+                rollup: Some(epic::rollup(graph, &t.id)),
+                children: vec![],
+            };
+            let depth = epic::ancestors(graph, &t.id)
+                .iter()
+                .filter(|a| epics.contains(a.id.as_str()))
+                .count();
+            (node, depth)
+        })
+        .collect()
+}
+
+fn build_tree(graph: &WorkGraph, task: &Task, path: &mut Vec<String>) -> EpicNode {
+    path.push(task.id.clone());
+    let mut children = Vec::new();
+    for child in epic::children(graph, &task.id) {
+        if !path.contains(&child.id) {
+            children.push(build_tree(graph, child, path));
+        }
+    }
+    path.pop();
+    EpicNode {
+        id: task.id.clone(),
+        title: task.title.clone(),
+        status: task.status,
+        rollup: (!children.is_empty()).then(|| epic::rollup(graph, &task.id)),
+        children,
+    }
+}
+
+fn epic_line(node: &EpicNode) -> String {
+    let rollup = node
+        .rollup
+        .as_ref()
+        .map(|r| {
+            let mut parts = vec![r.summary()];
+            if let Some(hours) = r.hours {
+                parts.push(format!("{}h", workgraph::format_hours(hours)));
+            }
+            if let Some(cost) = r.cost {
+                parts.push(format!("${:.2}", cost));
+            }
+            format!(" [{}]", parts.join(", "))
+        })
+        .unwrap_or_default();
+    format!(
+        "{} {} - {}{}",
+        status_marker(node.status),
+        node.id,
+        node.title,
+        rollup
+    )
+}
+
+fn print_epic(node: &EpicNode) {
+    println!("{}", epic_line(node));
+    let Some(ref rollup) = node.rollup else {
+        println!("  (no subtasks)");
+        return;
+    };
+    let counts: Vec<String> = [
+        (rollup.done, "done"),
+        (rollup.in_progress, "in-progress"),
+        (rollup.open, "open"),
+        (rollup.blocked, "blocked"),
+        (rollup.failed, "failed"),
+        (rollup.abandoned, "abandoned"),
+    ]
+    .into_iter()
+    .filter(|(n, _)| *n > 0)
+    .map(|(n, label)| format!("{} {}", n, label))
+    .collect();
+    println!("  {}", counts.join(", "));
+    println!();
+    print_children(&node.children, 1);
+}
+
+fn print_children(children: &[EpicNode], depth: usize) {
+    for child in children {
+        println!("{}{}", "  ".repeat(depth), epic_line(child));
+        print_children(&child.children, depth + 1);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use workgraph::graph::Node;
+
+    fn task(id: &str, parent: Option<&str>, status: Status) -> Task {
+        Task {
+            id: id.to_string(),
+            title: id.to_string(),
+            status,
+            parent: parent.map(String::from),
+            ..Task::default()
+        }
+    }
+
+    fn sample() -> WorkGraph {
+        let mut graph = WorkGraph::new();
+        for t in [
+            task("release", None, Status::Open),
+            task("auth", Some("release"), Status::Open),
+            task("login", Some("auth"), Status::Done),
+            task("logout", Some("auth"), Status::InProgress),
+            task("changelog", Some("release"), Status::Open),
+            task("docs", None, Status::Open),
+            task("guide", Some("docs"), Status::Done),
+        ] {
+            graph.add_node(Node::Task(t));
+        }
+        graph
+    }
+
+    #[test]
+    fn test_list_epics_nests_sub_epics() {
+        let graph = sample();
+        let listed: Vec<(String, usize)> = list_epics(&graph)
+            .into_iter()
+            .map(|(n, depth)| (n.id, depth))
+            .collect();
+        assert_eq!(
+            listed,
+            vec![
+                ("docs".to_string(), 0),
+                ("release".to_string(), 0),
+                ("auth".to_string(), 1),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_build_tree_rolls_up_each_level() {
+        let graph = sample();
+        let tree = build_tree(&graph, graph.get_task("release").unwrap(), &mut vec![]);
+        assert_eq!(tree.children.len(), 2);
+        assert_eq!(tree.rollup.as_ref().unwrap().summary(), "1/3, 33%");
+        let auth = &tree.children[0];
+        assert_eq!(auth.rollup.as_ref().unwrap().summary(), "1/2, 50%");
+        assert!(tree.children[1].rollup.is_none());
+        assert_eq!(epic_line(auth), "[ ] auth - auth [1/2, 50%]");
+    }
+}
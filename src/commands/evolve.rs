@@ -8,7 +8,7 @@ use std::process::Command;
 
 use workgraph::agency::{self, Evaluation, Lineage, Motivation, PerformanceRecord, Role, SkillRef};
 use workgraph::config::Config;
-use workgraph::graph::{Node, Status, Task};
+use workgraph::graph::{Node, Task};
 use workgraph::{load_graph, save_graph};
 
 /// Strategies the evolver can use.
@@ -977,34 +977,11 @@ fn defer_self_mutation(op: &EvolverOperation, dir: &Path, run_id: &str) -> Resul
             op.target_id.as_deref().unwrap_or("?")
         ),
         description: Some(desc),
-        status: Status::Open,
-        assigned: None,
-        estimate: None,
-        before: vec![],
-        after: vec![],
-        requires: vec![],
         tags: vec!["evolution".to_string(), "agency".to_string()],
-        skills: vec![],
-        inputs: vec![],
-        deliverables: vec![],
-        artifacts: vec![],
-        exec: None,
-        not_before: None,
         created_at: Some(Utc::now().to_rfc3339()),
-        started_at: None,
-        completed_at: None,
-        log: vec![],
-        retry_count: 0,
-        max_retries: None,
-        failure_reason: None,
-        model: None,
         verify: Some("Human must approve evolver self-mutation before applying.".to_string()),
-        agent: None,
-        loop_iteration: 0,
-        ready_after: None,
-        paused: false,
         visibility: "internal".to_string(),
-        cycle_config: None,
+        ..Task::default()
     };
 
     graph.add_node(Node::Task(task));
@@ -25,6 +25,25 @@ pub fn run(dir: &Path, task_id: &str, actor: Option<&str>, dry_run: bool) -> Res
         .clone()
         .ok_or_else(|| anyhow::anyhow!("Task '{}' has no exec command defined", task_id))?;
 
+    // Substitute {{deps.<id>.outputs.<key>}} bindings
+    let (values, unresolved) = workgraph::bindings::resolve(&graph, task);
+    let unresolved: Vec<String> = unresolved
+        .iter()
+        .filter(|u| exec_cmd.contains(&u.binding.placeholder))
+        .map(|u| format!("  - {}", u))
+        .collect();
+    if !unresolved.is_empty() {
+        anyhow::bail!(
+            "Cannot execute '{}': unresolved input bindings:\n{}",
+            task_id,
+            unresolved.join("\n")
+        );
+    }
+    let workgraph::bindings::BoundCommand {
+        command: exec_cmd,
+        env: exec_env,
+    } = workgraph::bindings::apply_to_command(&exec_cmd, &values);
+
     // Check task status
     if task.status == Status::Done {
         anyhow::bail!("Task '{}' is already done", task_id);
@@ -63,6 +82,7 @@ pub fn run(dir: &Path, task_id: &str, actor: Option<&str>, dry_run: bool) -> Res
     let output = Command::new("sh")
         .arg("-c")
         .arg(&exec_cmd)
+        .envs(exec_env)
         .output()
         .context("Failed to execute command")?;
 
@@ -187,6 +207,37 @@ mod tests {
         assert_eq!(task.status, Status::Done);
     }
 
+    #[test]
+    fn test_exec_quotes_binding_values() {
+        let temp_dir = TempDir::new().unwrap();
+        let path = temp_dir.path().join("graph.jsonl");
+        let marker = temp_dir.path().join("pwned");
+        let value = format!(
+            "x; touch {} $(touch {})",
+            marker.display(),
+            marker.display()
+        );
+
+        let mut graph = WorkGraph::new();
+        let mut build = make_task("build", "Build");
+        build.status = Status::Done;
+        build
+            .outputs
+            .insert("v".to_string(), serde_json::Value::String(value.clone()));
+        let mut task = make_task("t1", "Use output");
+        task.after = vec!["build".to_string()];
+        // Already quoted by the author: the value must still not run
+        task.exec = Some(format!("test '{{{{deps.build.outputs.v}}}}' = '{}'", value));
+        graph.add_node(Node::Task(build));
+        graph.add_node(Node::Task(task));
+        save_graph(&graph, &path).unwrap();
+
+        run(temp_dir.path(), "t1", None, false).unwrap();
+        assert!(!marker.exists());
+        let graph = load_graph(graph_path(temp_dir.path())).unwrap();
+        assert_eq!(graph.get_task("t1").unwrap().status, Status::Done);
+    }
+
     #[test]
     fn test_exec_failure() {
         let temp_dir = TempDir::new().unwrap();
@@ -52,6 +52,7 @@ pub fn run(dir: &Path, id: &str, reason: Option<&str>) -> Result<()> {
     let retry_count = task.retry_count;
     let max_retries = task.max_retries;
 
+    let propagation = super::propagate(&mut graph, dir);
     save_graph(&graph, &path).context("Failed to save graph")?;
     super::notify_graph_changed(dir);
 
@@ -87,6 +88,7 @@ pub fn run(dir: &Path, id: &str, reason: Option<&str>) -> Result<()> {
             println!("  Retries remaining: {}", max - retry_count);
         }
     }
+    propagation.print();
 
     // Archive agent conversation (prompt + output) for provenance
     if let Some(task) = graph.get_task(id)
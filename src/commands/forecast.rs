@@ -3,6 +3,8 @@ use chrono::{Duration, Utc};
 use serde::Serialize;
 use std::collections::{HashMap, HashSet};
 use std::path::Path;
+use workgraph::accounting::{self, RunUsage};
+use workgraph::capacity::{self, ResourceBound};
 use workgraph::graph::{Status, WorkGraph};
 use workgraph::query::build_reverse_index;
 
@@ -55,6 +57,25 @@ pub struct CriticalPath {
     pub total_hours: f64,
 }
 
+/// Estimated cost against what agent runs actually spent
+#[derive(Debug, Clone, Serialize)]
+pub struct Spend {
+    /// Sum of all task cost estimates
+    pub estimated_total: f64,
+    /// Cost estimates of tasks not finished yet
+    pub estimated_remaining: f64,
+    /// Recorded spend of all agent runs
+    pub actual_spent: f64,
+    pub runs: usize,
+    /// Actual spend of finished tasks that have a cost estimate
+    pub actual_on_estimated: f64,
+    /// Cost estimates of those same tasks
+    pub estimated_on_finished: f64,
+    /// Spent so far plus the remaining estimates, corrected by the
+    /// actual/estimated ratio of finished tasks
+    pub projected_total: Option<f64>,
+}
+
 /// Full forecast output
 #[derive(Debug, Serialize)]
 pub struct ForecastOutput {
@@ -62,14 +83,25 @@ pub struct ForecastOutput {
     pub scenarios: Vec<Scenario>,
     pub blockers: Vec<Blocker>,
     pub critical_path: Option<CriticalPath>,
+    /// Scarce resources that force the tasks needing them to run in turn
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub resource_bounds: Vec<ResourceBound>,
     pub velocity_hours_per_week: f64,
     pub has_velocity_data: bool,
     pub has_estimates: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub spend: Option<Spend>,
 }
 
 pub fn run(dir: &Path, json: bool) -> Result<()> {
     let (graph, _path) = super::load_workgraph(dir)?;
-    let forecast = calculate_forecast(&graph);
+    let mut forecast = calculate_forecast(&graph);
+
+    if let Err(e) = accounting::sync(dir, &graph) {
+        eprintln!("Warning: usage accounting failed: {}", e);
+    }
+    let records = accounting::load(dir)?;
+    forecast.spend = Some(calculate_spend(&graph, &records));
 
     if json {
         println!("{}", serde_json::to_string_pretty(&forecast)?);
@@ -106,12 +138,49 @@ pub fn calculate_forecast(graph: &WorkGraph) -> ForecastOutput {
         scenarios,
         blockers,
         critical_path,
+        resource_bounds: capacity::resource_bounds(graph),
         velocity_hours_per_week: velocity.average_hours_per_week,
         has_velocity_data,
         has_estimates,
+        spend: None,
     }
 }
 
+/// Compare cost estimates with recorded agent spend
+pub fn calculate_spend(graph: &WorkGraph, records: &[RunUsage]) -> Spend {
+    let actual = accounting::cost_by_task(records);
+    let mut spend = Spend {
+        estimated_total: 0.0,
+        estimated_remaining: 0.0,
+        actual_spent: actual.values().sum(),
+        runs: records
+            .iter()
+            .map(|r| r.run.as_str())
+            .collect::<HashSet<_>>()
+            .len(),
+        actual_on_estimated: 0.0,
+        estimated_on_finished: 0.0,
+        projected_total: None,
+    };
+    for task in graph.tasks() {
+        let Some(estimate) = task.estimate.as_ref().and_then(|e| e.cost) else {
+            continue;
+        };
+        spend.estimated_total += estimate;
+        if !task.status.is_terminal() {
+            spend.estimated_remaining += estimate;
+        } else if let Some(cost) = actual.get(&task.id) {
+            spend.actual_on_estimated += cost;
+            spend.estimated_on_finished += estimate;
+        }
+    }
+    if spend.estimated_on_finished > 0.0 {
+        let ratio = spend.actual_on_estimated / spend.estimated_on_finished;
+        spend.projected_total = Some(spend.actual_spent + spend.estimated_remaining * ratio);
+    }
+    spend
+}
+
 /// Calculate remaining work breakdown by status
 fn calculate_remaining_work(graph: &WorkGraph) -> RemainingWork {
     let mut open_tasks = 0;
@@ -426,6 +495,30 @@ fn print_human_output(forecast: &ForecastOutput) {
     );
     println!("  Total: {:.0}h\n", forecast.remaining_work.total_hours);
 
+    if let Some(ref spend) = forecast.spend
+        && (spend.estimated_total > 0.0 || spend.runs > 0)
+    {
+        println!("Spend:");
+        println!(
+            "  Estimated: ${:.2} total, ${:.2} remaining",
+            spend.estimated_total, spend.estimated_remaining
+        );
+        println!(
+            "  Actual: ${:.2} across {} agent run(s)",
+            spend.actual_spent, spend.runs
+        );
+        if spend.estimated_on_finished > 0.0 {
+            println!(
+                "  Finished tasks: ${:.2} actual vs ${:.2} estimated",
+                spend.actual_on_estimated, spend.estimated_on_finished
+            );
+        }
+        if let Some(projected) = spend.projected_total {
+            println!("  Projected total: ${:.2}", projected);
+        }
+        println!();
+    }
+
     // Handle edge cases
     if forecast.remaining_work.total_hours == 0.0 {
         println!("All tasks done or no estimates available.");
@@ -477,6 +570,21 @@ fn print_human_output(forecast: &ForecastOutput) {
         );
     }
 
+    // Resource serialization
+    if !forecast.resource_bounds.is_empty() {
+        println!("Resource constraints:");
+        for bound in &forecast.resource_bounds {
+            println!(
+                "  {}: {} tasks share capacity {}, at least {:.0}h end to end",
+                bound.resource,
+                bound.tasks.len(),
+                bound.capacity,
+                bound.bound_hours
+            );
+        }
+        println!();
+    }
+
     // Blockers
     if !forecast.blockers.is_empty() {
         println!("Blockers that could delay:");
@@ -710,6 +818,31 @@ mod tests {
         assert_eq!(critical.total_hours, 14.0);
     }
 
+    #[test]
+    fn test_resource_bounds_serialize_shared_lock() {
+        let mut graph = WorkGraph::new();
+        graph.add_node(Node::Resource(workgraph::graph::Resource {
+            id: "lock".to_string(),
+            name: None,
+            resource_type: None,
+            available: Some(1.0),
+            unit: None,
+            budget: None,
+        }));
+        for (id, hours) in [("a", 3.0), ("b", 5.0)] {
+            let mut task = make_task_with_hours(id, id, hours);
+            task.requires = vec!["lock".to_string()];
+            graph.add_node(Node::Task(task));
+        }
+
+        let forecast = calculate_forecast(&graph);
+
+        assert_eq!(forecast.resource_bounds.len(), 1);
+        assert_eq!(forecast.resource_bounds[0].tasks, vec!["a", "b"]);
+        // Independent by dependencies, but the lock runs them in turn
+        assert_eq!(forecast.resource_bounds[0].bound_hours, 8.0);
+    }
+
     #[test]
     fn test_critical_path_branching() {
         let mut graph = WorkGraph::new();
@@ -817,4 +950,51 @@ mod tests {
         // Should gracefully return None, not panic or produce garbage date
         assert!(scenario.completion_date.is_none());
     }
+
+    #[test]
+    fn test_spend_projects_from_finished_tasks() {
+        let mut graph = WorkGraph::new();
+        for (id, status, cost) in [
+            ("done", Status::Done, 10.0),
+            ("open", Status::Open, 20.0),
+            ("none", Status::Done, 0.0),
+        ] {
+            let mut task = make_task(id, id);
+            task.status = status;
+            if cost > 0.0 {
+                task.estimate = Some(Estimate {
+                    hours: None,
+                    cost: Some(cost),
+                });
+            }
+            graph.add_node(Node::Task(task));
+        }
+        let record = |run: &str, task: &str, cost: f64| RunUsage {
+            run: run.to_string(),
+            task_id: task.to_string(),
+            agent: None,
+            role: None,
+            model: None,
+            tokens: Default::default(),
+            cost_usd: Some(cost),
+            source: "usage.json".to_string(),
+            recorded_at: String::new(),
+        };
+        let records = vec![
+            record("agent-1", "done", 5.0),
+            record("agent-2", "none", 1.0),
+        ];
+
+        let spend = calculate_spend(&graph, &records);
+        assert_eq!(spend.estimated_total, 30.0);
+        assert_eq!(spend.estimated_remaining, 20.0);
+        assert_eq!(spend.actual_spent, 6.0);
+        assert_eq!(spend.runs, 2);
+        assert_eq!(spend.actual_on_estimated, 5.0);
+        // Finished work cost half its estimate: 6 + 20 * 0.5
+        assert_eq!(spend.projected_total, Some(16.0));
+
+        let no_history = calculate_spend(&graph, &[]);
+        assert_eq!(no_history.projected_total, None);
+    }
 }
@@ -0,0 +1,224 @@
+use anyhow::{Context, Result};
+use serde::Serialize;
+use std::io::{BufRead, Write};
+use std::path::Path;
+use workgraph::gate;
+use workgraph::graph::{GateVote, Task, WorkGraph};
+use workgraph::query;
+
+/// The approver to vote as: `--actor`, or else the login name.
+fn resolve_actor(actor: Option<&str>) -> Result<String> {
+    match actor {
+        Some(a) => Ok(a.to_string()),
+        None => std::env::var("USER")
+            .ok()
+            .filter(|u| !u.is_empty())
+            .context("Cannot tell who is voting; pass --actor"),
+    }
+}
+
+pub fn approve(dir: &Path, id: &str, actor: Option<&str>) -> Result<()> {
+    vote(dir, id, &resolve_actor(actor)?, true, None)
+}
+
+pub fn reject(dir: &Path, id: &str, reason: Option<&str>, actor: Option<&str>) -> Result<()> {
+    vote(dir, id, &resolve_actor(actor)?, false, reason)
+}
+
+fn vote(dir: &Path, id: &str, actor: &str, approved: bool, reason: Option<&str>) -> Result<()> {
+    let (decision, propagation) = gate::decide(dir, id, actor, None, approved, reason, "cli")?;
+
+    println!("{}", decision);
+    if !decision.awaiting.is_empty() {
+        println!("  Awaiting: {}", decision.awaiting.join(", "));
+    }
+    propagation.print();
+    Ok(())
+}
+
+#[derive(Debug, Serialize)]
+struct PendingGate<'a> {
+    id: &'a str,
+    title: &'a str,
+    /// Whether the gate's dependencies are met, so votes count
+    ready: bool,
+    approvers: &'a [String],
+    quorum: u32,
+    approvals: usize,
+    awaiting: Vec<&'a str>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    requested_at: Option<&'a str>,
+    votes: &'a [GateVote],
+}
+
+fn pending(graph: &WorkGraph) -> Vec<(&Task, PendingGate<'_>)> {
+    let mut tasks: Vec<&Task> = graph.tasks().filter(|t| gate::is_open(t)).collect();
+    tasks.sort_by(|a, b| a.id.cmp(&b.id));
+    tasks
+        .into_iter()
+        .filter_map(|task| {
+            let g = task.gate.as_ref()?;
+            let entry = PendingGate {
+                id: &task.id,
+                title: &task.title,
+                ready: query::after(graph, &task.id).is_empty(),
+                approvers: &g.approvers,
+                quorum: g.quorum,
+                approvals: gate::approvals(g),
+                awaiting: gate::awaiting(g),
+                requested_at: g.requested_at.as_deref(),
+                votes: &g.votes,
+            };
+            Some((task, entry))
+        })
+        .collect()
+}
+
+/// List approval gates that are still undecided.
+pub fn list(dir: &Path, json: bool) -> Result<()> {
+    let (graph, _) = super::load_workgraph(dir)?;
+    let gates: Vec<PendingGate> = pending(&graph).into_iter().map(|(_, g)| g).collect();
+
+    if json {
+        println!("{}", serde_json::to_string_pretty(&gates)?);
+        return Ok(());
+    }
+    if gates.is_empty() {
+        println!("No approval gates waiting for a decision");
+        return Ok(());
+    }
+    println!("Approval gates:");
+    for g in &gates {
+        let state = if g.ready {
+            format!("{} of {} approvals", g.approvals, g.quorum)
+        } else {
+            "waiting on dependencies".to_string()
+        };
+        println!("  {} - {} ({})", g.id, g.title, state);
+        if !g.awaiting.is_empty() {
+            println!("    Awaiting: {}", g.awaiting.join(", "));
+        }
+        for v in g.votes {
+            let verb = if v.approved { "approved" } else { "rejected" };
+            match &v.reason {
+                Some(r) => println!("    {} {} at {}: {}", v.actor, verb, v.at, r),
+                None => println!("    {} {} at {}", v.actor, verb, v.at),
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Walk through the ready gates `actor` may vote on and ask for a decision
+/// on each over stdin. This is the fallback for approvers without Matrix.
+pub fn review(dir: &Path, actor: Option<&str>) -> Result<()> {
+    let actor = resolve_actor(actor)?;
+    let (graph, _) = super::load_workgraph(dir)?;
+    let ids: Vec<(String, String)> = pending(&graph)
+        .into_iter()
+        .filter(|(task, g)| {
+            g.ready
+                && task.gate.as_ref().is_some_and(|gate| {
+                    gate::approver_for(gate, &actor, None)
+                        .is_some_and(|name| !gate.votes.iter().any(|v| v.actor == name))
+                })
+        })
+        .map(|(task, _)| (task.id.clone(), task.title.clone()))
+        .collect();
+    if ids.is_empty() {
+        println!("No approval gates waiting for {}", actor);
+        return Ok(());
+    }
+
+    let stdin = std::io::stdin();
+    let mut lines = stdin.lock().lines();
+    let mut prompt = |text: &str| -> Result<Option<String>> {
+        print!("{}", text);
+        std::io::stdout().flush()?;
+        Ok(lines.next().transpose()?.map(|l| l.trim().to_string()))
+    };
+    for (id, title) in ids {
+        let Some(answer) = prompt(&format!(
+            "{} - {}\n  [a]pprove, [r]eject, [s]kip? ",
+            id, title
+        ))?
+        else {
+            break;
+        };
+        match answer.to_lowercase().as_str() {
+            "a" | "approve" => vote(dir, &id, &actor, true, None)?,
+            "r" | "reject" => {
+                let reason = prompt("  Reason: ")?.filter(|r| !r.is_empty());
+                vote(dir, &id, &actor, false, reason.as_deref())?
+            }
+            _ => println!("  Skipped '{}'", id),
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::graph_path;
+    use super::*;
+    use workgraph::graph::{Gate, Node, Status};
+    use workgraph::parser::{load_graph, save_graph};
+    use workgraph::provenance::read_all_operations;
+
+    fn setup(dir: &Path) {
+        let mut graph = WorkGraph::new();
+        graph.add_node(Node::Task(Task {
+            id: "sign-off".to_string(),
+            title: "Sign off".to_string(),
+            gate: Some(Gate {
+                approvers: vec!["alice".to_string(), "bob".to_string()],
+                quorum: 2,
+                votes: vec![],
+                requested_at: None,
+            }),
+            ..Task::default()
+        }));
+        graph.add_node(Node::Task(Task {
+            id: "ship".to_string(),
+            title: "Ship".to_string(),
+            after: vec!["sign-off".to_string()],
+            ..Task::default()
+        }));
+        save_graph(&graph, graph_path(dir)).unwrap();
+    }
+
+    #[test]
+    fn test_approvals_are_recorded_in_provenance() {
+        let tmp = tempfile::tempdir().unwrap();
+        let dir = tmp.path();
+        setup(dir);
+
+        approve(dir, "sign-off", Some("alice")).unwrap();
+        assert!(approve(dir, "sign-off", Some("mallory")).is_err());
+        approve(dir, "sign-off", Some("bob")).unwrap();
+
+        let graph = load_graph(graph_path(dir)).unwrap();
+        assert_eq!(graph.get_task("sign-off").unwrap().status, Status::Done);
+
+        let ops = read_all_operations(dir).unwrap();
+        let approvals: Vec<_> = ops.iter().filter(|e| e.op == "approve").collect();
+        assert_eq!(approvals.len(), 2);
+        assert_eq!(approvals[0].actor.as_deref(), Some("alice"));
+        let passed = ops.iter().find(|e| e.op == "gate_approved").unwrap();
+        assert_eq!(passed.detail["votes"].as_array().unwrap().len(), 2);
+    }
+
+    #[test]
+    fn test_reject_abandons_dependents() {
+        let tmp = tempfile::tempdir().unwrap();
+        let dir = tmp.path();
+        setup(dir);
+
+        reject(dir, "sign-off", Some("not yet"), Some("bob")).unwrap();
+
+        let graph = load_graph(graph_path(dir)).unwrap();
+        assert_eq!(graph.get_task("sign-off").unwrap().status, Status::Failed);
+        assert_eq!(graph.get_task("ship").unwrap().status, Status::Abandoned);
+        assert!(super::super::done::run(dir, "sign-off", false, false).is_err());
+    }
+}
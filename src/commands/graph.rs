@@ -417,6 +417,7 @@ mod tests {
             resource_type: None,
             available: Some(4.0),
             unit: Some("cards".to_string()),
+            budget: None,
         }));
         setup_graph(&dir, &graph);
 
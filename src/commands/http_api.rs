@@ -0,0 +1,1034 @@
+//! HTTP/JSON API for the service daemon.
+//!
+//! A small HTTP/1.1 front end polled from the daemon loop next to the Unix
+//! socket. Each connection is read, authenticated and routed on its own
+//! thread; requests that change coordinator state are translated into the
+//! same [`IpcRequest`]s `wg service` sends and handed to the daemon loop, so
+//! both transports behave alike and a slow client never holds up a tick.
+//! Every connection carries one request (`Connection: close`), except
+//! `GET /api/events`, which streams `wg watch` events as Server-Sent Events.
+
+use anyhow::{Context, Result};
+use serde::Deserialize;
+use serde_json::{Value, json};
+use std::io::{BufRead, BufReader, Read, Write};
+use std::net::{SocketAddr, TcpListener, TcpStream};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::mpsc::{self, Receiver, Sender};
+use std::time::{Duration, Instant};
+
+use workgraph::graph::{Status, Task};
+use workgraph::parser::load_graph;
+
+use super::service::{IpcErrorKind, IpcRequest, IpcResponse};
+
+/// Largest request body accepted
+const MAX_BODY: usize = 1 << 20;
+/// Most headers accepted on one request
+const MAX_HEADERS: usize = 100;
+/// Longest request line or header line accepted
+const MAX_LINE: usize = 8 << 10;
+/// How long a client has to send its whole request
+const REQUEST_DEADLINE: Duration = Duration::from_secs(10);
+/// Most connections whose requests are being read at once
+const MAX_READING: usize = 32;
+/// Most `/api/events` streams open at once
+const MAX_STREAMS: usize = 16;
+/// How often an idle event stream sends a comment line
+const KEEPALIVE: Duration = Duration::from_secs(15);
+
+/// Who sent requests arriving over HTTP, for provenance
+const ORIGIN: &str = "http";
+
+/// A daemon operation parsed by a connection thread, and where to send the
+/// daemon's answer.
+type Job = (IpcRequest, Sender<IpcResponse>);
+
+pub struct HttpServer {
+    listener: TcpListener,
+    token: Option<String>,
+    dir: PathBuf,
+    jobs_tx: Sender<Job>,
+    jobs: Receiver<Job>,
+    reading: Arc<AtomicUsize>,
+    streaming: Arc<AtomicUsize>,
+}
+
+impl HttpServer {
+    /// Listen on `addr`. Binding beyond loopback requires a token, since the
+    /// API can create tasks and kill agents.
+    pub fn bind(dir: &Path, addr: &str, token: Option<String>) -> Result<Self> {
+        let listener = TcpListener::bind(addr)
+            .with_context(|| format!("Failed to bind HTTP API to {}", addr))?;
+        let local = listener.local_addr()?;
+        if token.is_none() && !local.ip().is_loopback() {
+            anyhow::bail!(
+                "Refusing to serve the HTTP API on {} without a token; set WG_HTTP_TOKEN or [http] token",
+                local
+            );
+        }
+        listener.set_nonblocking(true)?;
+        let (jobs_tx, jobs) = mpsc::channel();
+        Ok(Self {
+            listener,
+            token,
+            dir: dir.to_path_buf(),
+            jobs_tx,
+            jobs,
+            reading: Arc::new(AtomicUsize::new(0)),
+            streaming: Arc::new(AtomicUsize::new(0)),
+        })
+    }
+
+    pub fn local_addr(&self) -> Result<SocketAddr> {
+        Ok(self.listener.local_addr()?)
+    }
+
+    /// Hand each waiting connection to a thread of its own, and run the
+    /// daemon operations those threads have parsed through `dispatch`.
+    /// Returns false if there was nothing to do.
+    pub fn poll<F>(&self, mut dispatch: F) -> Result<bool>
+    where
+        F: FnMut(IpcRequest) -> IpcResponse,
+    {
+        let mut busy = false;
+        loop {
+            match self.listener.accept() {
+                Ok((stream, _)) => {
+                    busy = true;
+                    self.accept(stream)?;
+                }
+                Err(ref e) if e.kind() == std::io::ErrorKind::WouldBlock => break,
+                Err(e) => return Err(e).context("HTTP accept error"),
+            }
+        }
+        while let Ok((request, reply)) = self.jobs.try_recv() {
+            busy = true;
+            let _ = reply.send(dispatch(request));
+        }
+        Ok(busy)
+    }
+
+    fn accept(&self, mut stream: TcpStream) -> Result<()> {
+        stream.set_nonblocking(false)?;
+        stream.set_write_timeout(Some(Duration::from_secs(5)))?;
+        let Some(slot) = Slot::take(&self.reading, MAX_READING) else {
+            return respond(
+                &mut stream,
+                error_reply(503, "Too many requests in progress"),
+            );
+        };
+        let connection = Connection {
+            token: self.token.clone(),
+            dir: self.dir.clone(),
+            jobs: self.jobs_tx.clone(),
+            streaming: Arc::clone(&self.streaming),
+        };
+        std::thread::spawn(move || connection.serve(stream, slot));
+        Ok(())
+    }
+}
+
+/// A place among at most `max` connections counted by `count`, given back
+/// on drop.
+struct Slot(Arc<AtomicUsize>);
+
+impl Slot {
+    fn take(count: &Arc<AtomicUsize>, max: usize) -> Option<Self> {
+        count
+            .fetch_update(Ordering::SeqCst, Ordering::SeqCst, |n| {
+                (n < max).then_some(n + 1)
+            })
+            .ok()
+            .map(|_| Slot(Arc::clone(count)))
+    }
+}
+
+impl Drop for Slot {
+    fn drop(&mut self) {
+        self.0.fetch_sub(1, Ordering::SeqCst);
+    }
+}
+
+/// What a connection thread needs to serve its request.
+struct Connection {
+    token: Option<String>,
+    dir: PathBuf,
+    jobs: Sender<Job>,
+    streaming: Arc<AtomicUsize>,
+}
+
+impl Connection {
+    fn serve(self, mut stream: TcpStream, slot: Slot) {
+        let reader = DeadlineReader {
+            stream: &stream,
+            deadline: Instant::now() + REQUEST_DEADLINE,
+        };
+        let request = Request::read(reader);
+        drop(slot);
+        let request = match request {
+            Ok(r) => r,
+            Err(e) => {
+                let _ = respond(&mut stream, error_reply(e.status, &e.message));
+                return;
+            }
+        };
+
+        if !self.authorized(&request) {
+            let _ = respond(
+                &mut stream,
+                error_reply(401, "Missing or invalid bearer token"),
+            );
+            return;
+        }
+        if let Err(message) = self.same_site(&request) {
+            let _ = respond(&mut stream, error_reply(403, message));
+            return;
+        }
+
+        if request.method == "GET" && request.path == "/api/events" {
+            let Some(_slot) = Slot::take(&self.streaming, MAX_STREAMS) else {
+                let _ = respond(&mut stream, error_reply(503, "Too many event streams"));
+                return;
+            };
+            stream_events(&self.dir, stream, &request);
+            return;
+        }
+
+        let reply = route(&self.dir, &request, |ipc| self.call(ipc));
+        let _ = respond(&mut stream, reply);
+    }
+
+    /// Have the daemon loop carry out `request` and wait for its answer.
+    fn call(&self, request: IpcRequest) -> IpcResponse {
+        let (tx, rx) = mpsc::channel();
+        if self.jobs.send((request, tx)).is_err() {
+            return IpcResponse::error_with(IpcErrorKind::Internal, "The service is shutting down");
+        }
+        rx.recv().unwrap_or_else(|_| {
+            IpcResponse::error_with(IpcErrorKind::Internal, "The service is shutting down")
+        })
+    }
+
+    fn authorized(&self, request: &Request) -> bool {
+        let Some(token) = &self.token else {
+            return true;
+        };
+        let presented = request
+            .header("authorization")
+            .and_then(|h| h.strip_prefix("Bearer "))
+            .unwrap_or("");
+        constant_time_eq(presented.trim().as_bytes(), token.as_bytes())
+    }
+
+    /// Without a token, anything that can make the user's browser send a
+    /// request to localhost could otherwise drive the API. Browsers mark
+    /// cross-site requests with `Origin` and, after DNS rebinding, name the
+    /// attacker's host in `Host`, so refuse both; other clients send neither.
+    fn same_site(&self, request: &Request) -> std::result::Result<(), &'static str> {
+        if self.token.is_some() {
+            return Ok(());
+        }
+        if request.header("origin").is_some() {
+            return Err("Requests from web pages need a token");
+        }
+        if request
+            .header("host")
+            .is_some_and(|host| !is_loopback_host(host))
+        {
+            return Err("Requests for a non-local host need a token");
+        }
+        Ok(())
+    }
+}
+
+/// Whether a `Host` header names this machine's loopback interface.
+fn is_loopback_host(host: &str) -> bool {
+    let name = match host.strip_prefix('[') {
+        Some(rest) => rest.split(']').next().unwrap_or(""),
+        None => host.rsplit_once(':').map_or(host, |(name, _)| name),
+    };
+    name.eq_ignore_ascii_case("localhost")
+        || name
+            .parse::<std::net::IpAddr>()
+            .is_ok_and(|ip| ip.is_loopback())
+}
+
+/// Reads a connection, failing once `deadline` passes however slowly the
+/// client trickles its bytes in.
+struct DeadlineReader<'a> {
+    stream: &'a TcpStream,
+    deadline: Instant,
+}
+
+impl Read for DeadlineReader<'_> {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        let left = self.deadline.saturating_duration_since(Instant::now());
+        if left.is_zero() {
+            return Err(std::io::ErrorKind::TimedOut.into());
+        }
+        self.stream.set_read_timeout(Some(left))?;
+        let mut stream = self.stream;
+        stream.read(buf)
+    }
+}
+
+fn constant_time_eq(a: &[u8], b: &[u8]) -> bool {
+    a.len() == b.len() && a.iter().zip(b).fold(0u8, |acc, (x, y)| acc | (x ^ y)) == 0
+}
+
+#[derive(Debug)]
+struct Request {
+    method: String,
+    path: String,
+    query: Vec<(String, String)>,
+    headers: Vec<(String, String)>,
+    body: Vec<u8>,
+}
+
+#[derive(Debug)]
+struct BadRequest {
+    status: u16,
+    message: String,
+}
+
+impl BadRequest {
+    fn new(status: u16, message: impl Into<String>) -> Self {
+        Self {
+            status,
+            message: message.into(),
+        }
+    }
+}
+
+fn read_error(e: std::io::Error) -> BadRequest {
+    match e.kind() {
+        std::io::ErrorKind::TimedOut | std::io::ErrorKind::WouldBlock => {
+            BadRequest::new(408, "Request not received in time")
+        }
+        _ => BadRequest::new(400, format!("Read error: {}", e)),
+    }
+}
+
+impl Request {
+    fn read(source: impl Read) -> std::result::Result<Self, BadRequest> {
+        let mut reader = BufReader::new(source);
+        let mut line = String::new();
+        let mut read_line = |line: &mut String| {
+            line.clear();
+            let read = (&mut reader)
+                .take(MAX_LINE as u64)
+                .read_line(line)
+                .map_err(read_error)?;
+            if read == MAX_LINE && !line.ends_with('\n') {
+                return Err(BadRequest::new(431, "Request line or header too long"));
+            }
+            Ok(())
+        };
+
+        read_line(&mut line)?;
+        let mut parts = line.split_whitespace();
+        let (Some(method), Some(target), Some(_version)) =
+            (parts.next(), parts.next(), parts.next())
+        else {
+            return Err(BadRequest::new(400, "Malformed request line"));
+        };
+        let method = method.to_string();
+        let (path, query) = match target.split_once('?') {
+            Some((path, query)) => (path.to_string(), parse_query(query)),
+            None => (target.to_string(), Vec::new()),
+        };
+
+        let mut headers = Vec::new();
+        loop {
+            read_line(&mut line)?;
+            let header = line.trim_end();
+            if header.is_empty() {
+                break;
+            }
+            if headers.len() >= MAX_HEADERS {
+                return Err(BadRequest::new(431, "Too many headers"));
+            }
+            let Some((name, value)) = header.split_once(':') else {
+                return Err(BadRequest::new(
+                    400,
+                    format!("Malformed header '{}'", header),
+                ));
+            };
+            headers.push((name.trim().to_lowercase(), value.trim().to_string()));
+        }
+
+        let mut request = Self {
+            method,
+            path,
+            query,
+            headers,
+            body: Vec::new(),
+        };
+        let length = match request.header("content-length") {
+            Some(v) => v
+                .parse::<usize>()
+                .map_err(|_| BadRequest::new(400, "Invalid Content-Length"))?,
+            None => 0,
+        };
+        if length > MAX_BODY {
+            return Err(BadRequest::new(413, "Request body too large"));
+        }
+        request.body.resize(length, 0);
+        reader.read_exact(&mut request.body).map_err(read_error)?;
+        Ok(request)
+    }
+
+    fn header(&self, name: &str) -> Option<&str> {
+        self.headers
+            .iter()
+            .find(|(n, _)| n == name)
+            .map(|(_, v)| v.as_str())
+    }
+
+    fn param(&self, name: &str) -> Option<&str> {
+        self.query
+            .iter()
+            .find(|(n, _)| n == name)
+            .map(|(_, v)| v.as_str())
+    }
+
+    /// The JSON body, treating an empty body as `{}`. A body must be sent as
+    /// `application/json`, which an HTML form can't do.
+    fn json<T: serde::de::DeserializeOwned>(&self) -> std::result::Result<T, Reply> {
+        let json_type = self.header("content-type").is_some_and(|t| {
+            t.split(';')
+                .next()
+                .is_some_and(|t| t.trim().eq_ignore_ascii_case("application/json"))
+        });
+        let body: &[u8] = if self.body.is_empty() {
+            b"{}"
+        } else if !json_type {
+            return Err(error_reply(415, "The body must be application/json"));
+        } else {
+            &self.body
+        };
+        serde_json::from_slice(body)
+            .map_err(|e| error_reply(400, &format!("Invalid JSON body: {}", e)))
+    }
+}
+
+fn parse_query(query: &str) -> Vec<(String, String)> {
+    query
+        .split('&')
+        .filter(|pair| !pair.is_empty())
+        .map(|pair| match pair.split_once('=') {
+            Some((k, v)) => (percent_decode(k), percent_decode(v)),
+            None => (percent_decode(pair), String::new()),
+        })
+        .collect()
+}
+
+fn percent_decode(s: &str) -> String {
+    let bytes = s.as_bytes();
+    let mut out = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        let hex = |at: usize| bytes.get(at).and_then(|&b| (b as char).to_digit(16));
+        match (bytes[i], hex(i + 1), hex(i + 2)) {
+            (b'%', Some(hi), Some(lo)) => {
+                out.push((hi * 16 + lo) as u8);
+                i += 2;
+            }
+            (b'+', _, _) => out.push(b' '),
+            (b, _, _) => out.push(b),
+        }
+        i += 1;
+    }
+    String::from_utf8_lossy(&out).into_owned()
+}
+
+/// Status code and JSON body of a response
+type Reply = (u16, Value);
+
+fn error_reply(status: u16, message: &str) -> Reply {
+    (status, json!({ "ok": false, "error": message }))
+}
+
+/// Turn a daemon response into an HTTP reply, with the status code of a
+/// failure given by its kind.
+fn from_ipc(response: IpcResponse, success: u16) -> Reply {
+    if response.ok {
+        return (
+            success,
+            serde_json::to_value(&response).unwrap_or(Value::Null),
+        );
+    }
+    let status = match response.error_kind {
+        Some(IpcErrorKind::NotFound) => 404,
+        Some(IpcErrorKind::Conflict) => 409,
+        Some(IpcErrorKind::Internal) => 500,
+        Some(IpcErrorKind::Invalid) | None => 400,
+    };
+    error_reply(status, &response.error.unwrap_or_default())
+}
+
+fn respond(stream: &mut TcpStream, (status, body): Reply) -> Result<()> {
+    let body = serde_json::to_string(&body)?;
+    let mut head = format!(
+        "HTTP/1.1 {} {}\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n",
+        status,
+        reason(status),
+        body.len()
+    );
+    if status == 401 {
+        head.push_str("WWW-Authenticate: Bearer\r\n");
+    }
+    head.push_str("\r\n");
+    stream.write_all(head.as_bytes())?;
+    stream.write_all(body.as_bytes())?;
+    stream.flush()?;
+    Ok(())
+}
+
+fn reason(status: u16) -> &'static str {
+    match status {
+        200 => "OK",
+        201 => "Created",
+        400 => "Bad Request",
+        401 => "Unauthorized",
+        403 => "Forbidden",
+        404 => "Not Found",
+        408 => "Request Timeout",
+        409 => "Conflict",
+        413 => "Payload Too Large",
+        415 => "Unsupported Media Type",
+        431 => "Request Header Fields Too Large",
+        500 => "Internal Server Error",
+        503 => "Service Unavailable",
+        _ => "Unknown",
+    }
+}
+
+#[derive(Deserialize, Default)]
+#[serde(deny_unknown_fields)]
+struct KillBody {
+    #[serde(default)]
+    force: bool,
+}
+
+#[derive(Deserialize, Default)]
+#[serde(deny_unknown_fields)]
+struct ReconfigureBody {
+    max_agents: Option<usize>,
+    executor: Option<String>,
+    poll_interval: Option<u64>,
+    model: Option<String>,
+}
+
+/// Fields accepted by `POST /api/tasks` and `PATCH /api/tasks/{id}`
+#[derive(Deserialize, Default)]
+#[serde(deny_unknown_fields)]
+struct TaskBody {
+    title: Option<String>,
+    id: Option<String>,
+    description: Option<String>,
+    #[serde(default)]
+    after: Vec<String>,
+    #[serde(default)]
+    tags: Vec<String>,
+    #[serde(default)]
+    skills: Vec<String>,
+    #[serde(default)]
+    deliverables: Vec<String>,
+    model: Option<String>,
+    verify: Option<String>,
+    /// PATCH only: fail with 409 unless the task is still at this revision
+    expected_revision: Option<u64>,
+}
+
+fn route<F>(dir: &Path, request: &Request, mut dispatch: F) -> Reply
+where
+    F: FnMut(IpcRequest) -> IpcResponse,
+{
+    let segments: Vec<&str> = request
+        .path
+        .trim_matches('/')
+        .split('/')
+        .filter(|s| !s.is_empty())
+        .collect();
+    let result = match (request.method.as_str(), segments.as_slice()) {
+        ("GET", ["api", "status"]) => Ok(from_ipc(dispatch(IpcRequest::Status), 200)),
+        ("GET", ["api", "agents"]) => Ok(from_ipc(dispatch(IpcRequest::Agents), 200)),
+        ("POST", ["api", "agents", agent_id, "kill"]) => request.json::<KillBody>().map(|body| {
+            let kill = IpcRequest::Kill {
+                agent_id: percent_decode(agent_id),
+                force: body.force,
+            };
+            from_ipc(dispatch(kill), 200)
+        }),
+        ("POST", ["api", "service", "pause"]) => Ok(from_ipc(dispatch(IpcRequest::Pause), 200)),
+        ("POST", ["api", "service", "resume"]) => Ok(from_ipc(dispatch(IpcRequest::Resume), 200)),
+        ("POST", ["api", "service", "reconfigure"]) => {
+            request.json::<ReconfigureBody>().map(|body| {
+                let reconfigure = IpcRequest::Reconfigure {
+                    max_agents: body.max_agents,
+                    executor: body.executor,
+                    poll_interval: body.poll_interval,
+                    model: body.model,
+                };
+                from_ipc(dispatch(reconfigure), 200)
+            })
+        }
+        ("GET", ["api", "tasks"]) => list_tasks(dir, request.param("status")),
+        ("GET", ["api", "tasks", id]) => load_task(dir, &percent_decode(id))
+            .map(|task| (200, json!({ "ok": true, "task": task }))),
+        ("POST", ["api", "tasks"]) => add_task(request, &mut dispatch),
+        ("PATCH", ["api", "tasks", id]) => edit_task(&percent_decode(id), request, &mut dispatch),
+        _ => Err(error_reply(
+            404,
+            &format!("No route for {} {}", request.method, request.path),
+        )),
+    };
+    result.unwrap_or_else(|reply| reply)
+}
+
+fn list_tasks(dir: &Path, status: Option<&str>) -> std::result::Result<Reply, Reply> {
+    let status = status
+        .map(|s| {
+            serde_json::from_value::<Status>(Value::String(s.to_string()))
+                .map_err(|_| error_reply(400, &format!("Unknown status '{}'", s)))
+        })
+        .transpose()?;
+    let graph = load_graph(super::graph_path(dir))
+        .map_err(|e| error_reply(500, &format!("Failed to load graph: {}", e)))?;
+    let mut tasks: Vec<&Task> = graph
+        .tasks()
+        .filter(|t| status.is_none_or(|s| t.status == s))
+        .collect();
+    tasks.sort_by(|a, b| a.id.cmp(&b.id));
+    Ok((200, json!({ "ok": true, "tasks": tasks })))
+}
+
+fn load_task(dir: &Path, id: &str) -> std::result::Result<Task, Reply> {
+    let graph = load_graph(super::graph_path(dir))
+        .map_err(|e| error_reply(500, &format!("Failed to load graph: {}", e)))?;
+    graph
+        .get_task(id)
+        .cloned()
+        .ok_or_else(|| error_reply(404, &format!("Task '{}' not found", id)))
+}
+
+fn add_task<F>(request: &Request, dispatch: &mut F) -> std::result::Result<Reply, Reply>
+where
+    F: FnMut(IpcRequest) -> IpcResponse,
+{
+    let body: TaskBody = request.json()?;
+    if body.expected_revision.is_some() {
+        return Err(error_reply(
+            400,
+            "expected_revision is only accepted by PATCH /api/tasks/{id}",
+        ));
+    }
+    let title = body
+        .title
+        .filter(|t| !t.trim().is_empty())
+        .ok_or_else(|| error_reply(400, "A task needs a title"))?;
+    let add = IpcRequest::AddTask {
+        title,
+        id: body.id,
+        description: body.description,
+        after: body.after,
+        tags: body.tags,
+        skills: body.skills,
+        deliverables: body.deliverables,
+        model: body.model,
+        verify: body.verify,
+        origin: Some(ORIGIN.to_string()),
+        expected_revision: None,
+    };
+    Ok(from_ipc(dispatch(add), 201))
+}
+
+fn edit_task<F>(id: &str, request: &Request, dispatch: &mut F) -> std::result::Result<Reply, Reply>
+where
+    F: FnMut(IpcRequest) -> IpcResponse,
+{
+    let body: TaskBody = request.json()?;
+    if body.id.as_deref().is_some_and(|b| b != id) {
+        return Err(error_reply(400, "A task's id cannot be changed"));
+    }
+    let update = IpcRequest::UpdateTask {
+        task_id: id.to_string(),
+        expected_revision: body.expected_revision,
+        title: body.title,
+        description: body.description,
+        after: body.after,
+        tags: body.tags,
+        skills: body.skills,
+        deliverables: body.deliverables,
+        model: body.model,
+        verify: body.verify,
+        origin: Some(ORIGIN.to_string()),
+    };
+    Ok(from_ipc(dispatch(update), 200))
+}
+
+/// Stream watch events to `stream` as Server-Sent Events until the client
+/// disconnects. Query parameters mirror `wg watch`: `event` (repeatable or
+/// comma-separated), `task` and `replay`.
+fn stream_events(dir: &Path, mut stream: TcpStream, request: &Request) {
+    let mut event_types: Vec<String> = request
+        .query
+        .iter()
+        .filter(|(n, _)| n == "event")
+        .flat_map(|(_, v)| v.split(','))
+        .map(|v| v.trim().to_string())
+        .filter(|v| !v.is_empty())
+        .collect();
+    if event_types.is_empty() {
+        event_types.push("all".to_string());
+    }
+    let task_filter = request.param("task");
+    let replay = request
+        .param("replay")
+        .and_then(|r| r.parse().ok())
+        .unwrap_or(0);
+
+    let head = "HTTP/1.1 200 OK\r\nContent-Type: text/event-stream\r\nCache-Control: no-cache\r\nConnection: keep-alive\r\n\r\n";
+    if stream.write_all(head.as_bytes()).is_err() {
+        return;
+    }
+    let mut last_write = Instant::now();
+    let _ = super::watch::follow(dir, &event_types, task_filter, replay, |event| {
+        match event {
+            Some(event) => {
+                let data = serde_json::to_string(event)?;
+                write!(stream, "event: {}\ndata: {}\n\n", event.event_type, data)?;
+            }
+            None if last_write.elapsed() >= KEEPALIVE => stream.write_all(b": keepalive\n\n")?,
+            None => return Ok(()),
+        }
+        last_write = Instant::now();
+        stream.flush()
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use workgraph::graph::{Node, WorkGraph};
+    use workgraph::parser::save_graph;
+
+    fn setup(dir: &Path) {
+        let mut graph = WorkGraph::new();
+        for (id, status) in [("build", Status::Done), ("deploy", Status::Open)] {
+            graph.add_node(Node::Task(Task {
+                id: id.to_string(),
+                title: id.to_string(),
+                status,
+                ..Task::default()
+            }));
+        }
+        save_graph(&graph, super::super::graph_path(dir)).unwrap();
+    }
+
+    fn request(method: &str, target: &str, body: &str) -> Request {
+        let (path, query) = match target.split_once('?') {
+            Some((p, q)) => (p.to_string(), parse_query(q)),
+            None => (target.to_string(), Vec::new()),
+        };
+        Request {
+            method: method.to_string(),
+            path,
+            query,
+            headers: vec![("content-type".to_string(), "application/json".to_string())],
+            body: body.as_bytes().to_vec(),
+        }
+    }
+
+    /// Send a raw request to `server` and return the status line and body.
+    fn exchange(server: &HttpServer, raw: &str) -> (String, String) {
+        let addr = server.local_addr().unwrap();
+        let raw = raw.to_string();
+        let client = std::thread::spawn(move || {
+            let mut stream = TcpStream::connect(addr).unwrap();
+            stream.write_all(raw.as_bytes()).unwrap();
+            let mut response = String::new();
+            stream.read_to_string(&mut response).unwrap();
+            response
+        });
+        let deadline = Instant::now() + Duration::from_secs(5);
+        while !client.is_finished() {
+            server
+                .poll(|_| IpcResponse::success(json!({ "status": "running" })))
+                .unwrap();
+            assert!(Instant::now() < deadline, "no response arrived");
+            std::thread::sleep(Duration::from_millis(10));
+        }
+        let response = client.join().unwrap();
+        let (head, body) = response.split_once("\r\n\r\n").unwrap();
+        (head.lines().next().unwrap().to_string(), body.to_string())
+    }
+
+    #[test]
+    fn test_routes_map_to_daemon_requests() {
+        let tmp = tempfile::tempdir().unwrap();
+        setup(tmp.path());
+        let mut seen = Vec::new();
+        let mut dispatch = |req: IpcRequest| {
+            seen.push(serde_json::to_value(&req).unwrap());
+            IpcResponse::success(json!({ "task_id": "x" }))
+        };
+
+        let (status, _) = route(
+            tmp.path(),
+            &request(
+                "POST",
+                "/api/tasks",
+                r#"{"title":"Write docs","tags":["docs"]}"#,
+            ),
+            &mut dispatch,
+        );
+        assert_eq!(status, 201);
+        let (status, _) = route(
+            tmp.path(),
+            &request("POST", "/api/service/reconfigure", r#"{"max_agents":3}"#),
+            &mut dispatch,
+        );
+        assert_eq!(status, 200);
+        let (status, body) = route(
+            tmp.path(),
+            &request("POST", "/api/tasks", "{}"),
+            &mut dispatch,
+        );
+        assert_eq!(status, 400, "{}", body);
+        let (status, _) = route(
+            tmp.path(),
+            &request("DELETE", "/api/tasks/x", ""),
+            &mut dispatch,
+        );
+        assert_eq!(status, 404);
+
+        assert_eq!(seen.len(), 2);
+        assert_eq!(seen[0]["cmd"], "add_task");
+        assert_eq!(seen[0]["origin"], "http");
+        assert_eq!(seen[0]["tags"][0], "docs");
+        assert_eq!(seen[1]["cmd"], "reconfigure");
+        assert_eq!(seen[1]["max_agents"], 3);
+    }
+
+    #[test]
+    fn test_task_reads_and_edits() {
+        let tmp = tempfile::tempdir().unwrap();
+        setup(tmp.path());
+        let mut dispatch = |req: IpcRequest| match req {
+            IpcRequest::UpdateTask {
+                expected_revision: Some(2),
+                ..
+            } => IpcResponse::success(json!({ "revision": 3 })),
+            _ => IpcResponse::error_with(
+                IpcErrorKind::Conflict,
+                "Revision conflict on 'deploy': expected 1, found 2",
+            ),
+        };
+
+        let (status, body) = route(
+            tmp.path(),
+            &request("GET", "/api/tasks?status=open", ""),
+            &mut dispatch,
+        );
+        assert_eq!(status, 200);
+        let ids: Vec<&str> = body["tasks"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|t| t["id"].as_str().unwrap())
+            .collect();
+        assert_eq!(ids, vec!["deploy"]);
+        let (status, _) = route(
+            tmp.path(),
+            &request("GET", "/api/tasks?status=bogus", ""),
+            &mut dispatch,
+        );
+        assert_eq!(status, 400);
+
+        let (status, body) = route(
+            tmp.path(),
+            &request("GET", "/api/tasks/build", ""),
+            &mut dispatch,
+        );
+        assert_eq!(status, 200);
+        assert_eq!(body["task"]["status"], "done");
+        let (status, _) = route(
+            tmp.path(),
+            &request("GET", "/api/tasks/nope", ""),
+            &mut dispatch,
+        );
+        assert_eq!(status, 404);
+
+        let (status, _) = route(
+            tmp.path(),
+            &request("PATCH", "/api/tasks/deploy", r#"{"expected_revision":2}"#),
+            &mut dispatch,
+        );
+        assert_eq!(status, 200);
+        let (status, _) = route(
+            tmp.path(),
+            &request("PATCH", "/api/tasks/deploy", r#"{"expected_revision":1}"#),
+            &mut dispatch,
+        );
+        assert_eq!(status, 409);
+    }
+
+    #[test]
+    fn test_bearer_token_is_required_over_localhost() {
+        let tmp = tempfile::tempdir().unwrap();
+        let server =
+            HttpServer::bind(tmp.path(), "127.0.0.1:0", Some("s3cret".to_string())).unwrap();
+
+        let (status, _) = exchange(&server, "GET /api/status HTTP/1.1\r\nHost: x\r\n\r\n");
+        assert_eq!(status, "HTTP/1.1 401 Unauthorized");
+        let (status, body) = exchange(
+            &server,
+            "GET /api/status HTTP/1.1\r\nAuthorization: Bearer s3cret\r\n\r\n",
+        );
+        assert_eq!(status, "HTTP/1.1 200 OK");
+        assert!(body.contains("\"running\""), "{}", body);
+    }
+
+    #[test]
+    fn test_browser_requests_need_a_token() {
+        let tmp = tempfile::tempdir().unwrap();
+        let server = HttpServer::bind(tmp.path(), "127.0.0.1:0", None).unwrap();
+
+        let (status, _) = exchange(
+            &server,
+            "POST /api/service/pause HTTP/1.1\r\nHost: localhost:8787\r\nOrigin: https://evil.example\r\n\r\n",
+        );
+        assert_eq!(status, "HTTP/1.1 403 Forbidden");
+        let (status, _) = exchange(
+            &server,
+            "GET /api/tasks HTTP/1.1\r\nHost: evil.example:8787\r\n\r\n",
+        );
+        assert_eq!(status, "HTTP/1.1 403 Forbidden");
+        let (status, _) = exchange(
+            &server,
+            "GET /api/status HTTP/1.1\r\nHost: [::1]:8787\r\n\r\n",
+        );
+        assert_eq!(status, "HTTP/1.1 200 OK");
+
+        let mut form = request("POST", "/api/tasks", r#"{"title":"x"}"#);
+        form.headers = vec![("content-type".to_string(), "text/plain".to_string())];
+        let (status, _) = route(tmp.path(), &form, |_| unreachable!());
+        assert_eq!(status, 415);
+    }
+
+    #[test]
+    fn test_event_streams_are_capped() {
+        let tmp = tempfile::tempdir().unwrap();
+        let server = HttpServer::bind(tmp.path(), "127.0.0.1:0", None).unwrap();
+        let addr = server.local_addr().unwrap();
+
+        let mut streams = Vec::new();
+        for _ in 0..MAX_STREAMS {
+            let mut stream = TcpStream::connect(addr).unwrap();
+            stream
+                .write_all(b"GET /api/events HTTP/1.1\r\n\r\n")
+                .unwrap();
+            streams.push(stream);
+        }
+        let opened = std::thread::spawn(move || {
+            for stream in &streams {
+                let mut line = String::new();
+                BufReader::new(stream).read_line(&mut line).unwrap();
+                assert!(line.starts_with("HTTP/1.1 200"), "{}", line);
+            }
+            streams
+        });
+        while !opened.is_finished() {
+            server.poll(|_| IpcResponse::error("unused")).unwrap();
+            std::thread::sleep(Duration::from_millis(10));
+        }
+        let _streams = opened.join().unwrap();
+
+        let (status, _) = exchange(&server, "GET /api/events HTTP/1.1\r\n\r\n");
+        assert_eq!(status, "HTTP/1.1 503 Service Unavailable");
+    }
+
+    #[test]
+    fn test_request_size_is_capped() {
+        let long_header = format!("GET / HTTP/1.1\r\nX-Pad: {}\r\n\r\n", "a".repeat(MAX_LINE));
+        let err = Request::read(long_header.as_bytes()).unwrap_err();
+        assert_eq!(err.status, 431);
+        let huge_body = format!(
+            "POST / HTTP/1.1\r\nContent-Length: {}\r\n\r\n",
+            MAX_BODY + 1
+        );
+        let err = Request::read(huge_body.as_bytes()).unwrap_err();
+        assert_eq!(err.status, 413);
+        let request = Request::read(&b"GET /api/status HTTP/1.1\r\nHost: x\r\n\r\n"[..]).unwrap();
+        assert_eq!(request.header("host"), Some("x"));
+    }
+
+    #[test]
+    fn test_slow_client_does_not_hold_up_others() {
+        let tmp = tempfile::tempdir().unwrap();
+        let server = HttpServer::bind(tmp.path(), "127.0.0.1:0", None).unwrap();
+        let mut slow = TcpStream::connect(server.local_addr().unwrap()).unwrap();
+        slow.write_all(b"GET /api/status HTTP/1.1\r\nX-Slow: ")
+            .unwrap();
+
+        let started = Instant::now();
+        let (status, _) = exchange(&server, "GET /api/status HTTP/1.1\r\n\r\n");
+        assert_eq!(status, "HTTP/1.1 200 OK");
+        assert!(started.elapsed() < REQUEST_DEADLINE);
+    }
+
+    #[test]
+    fn test_public_bind_needs_token() {
+        let tmp = tempfile::tempdir().unwrap();
+        assert!(HttpServer::bind(tmp.path(), "0.0.0.0:0", None).is_err());
+    }
+
+    #[test]
+    fn test_events_stream_as_sse() {
+        let tmp = tempfile::tempdir().unwrap();
+        let dir = tmp.path();
+        workgraph::provenance::record(
+            dir,
+            "add_task",
+            Some("deploy"),
+            None,
+            json!({ "title": "Deploy" }),
+            workgraph::provenance::DEFAULT_ROTATION_THRESHOLD,
+        )
+        .unwrap();
+        let server = HttpServer::bind(dir, "127.0.0.1:0", None).unwrap();
+        let addr = server.local_addr().unwrap();
+
+        let client = std::thread::spawn(move || {
+            let mut stream = TcpStream::connect(addr).unwrap();
+            stream
+                .write_all(b"GET /api/events?replay=5&event=task_state HTTP/1.1\r\n\r\n")
+                .unwrap();
+            let mut reader = BufReader::new(stream);
+            let mut lines = Vec::new();
+            let mut line = String::new();
+            while reader.read_line(&mut line).unwrap() > 0 {
+                let done = line.starts_with("data:");
+                lines.push(std::mem::take(&mut line));
+                if done {
+                    break;
+                }
+            }
+            lines
+        });
+        while !server.poll(|_| IpcResponse::error("unused")).unwrap() {
+            std::thread::sleep(Duration::from_millis(10));
+        }
+
+        let lines = client.join().unwrap();
+        assert!(lines[0].starts_with("HTTP/1.1 200"));
+        assert!(lines.iter().any(|l| l.contains("text/event-stream")));
+        assert!(lines.iter().any(|l| l == "event: task.created\n"));
+        assert!(lines.last().unwrap().contains("\"task_id\":\"deploy\""));
+    }
+}
@@ -1,11 +1,18 @@
 use anyhow::Result;
 use chrono::{DateTime, Utc};
+use std::collections::{HashMap, HashSet};
 use std::path::Path;
-use workgraph::graph::Status;
-
-pub fn run(dir: &Path, status_filter: Option<&str>, paused_only: bool, json: bool) -> Result<()> {
-    let (graph, _path) = super::load_workgraph(dir)?;
-
+use workgraph::epic;
+use workgraph::graph::{Node, Status, Task, WorkGraph};
+
+pub fn run(
+    dir: &Path,
+    status_filter: Option<&str>,
+    paused_only: bool,
+    expand: bool,
+    at: Option<&str>,
+    json: bool,
+) -> Result<()> {
     let status_filter: Option<Status> = match status_filter {
         Some("open") => Some(Status::Open),
         Some("done") => Some(Status::Done),
@@ -20,11 +27,39 @@ pub fn run(dir: &Path, status_filter: Option<&str>, paused_only: bool, json: boo
         None => None,
     };
 
-    let tasks: Vec<_> = graph
-        .tasks()
-        .filter(|t| status_filter.as_ref().is_none_or(|s| &t.status == s))
-        .filter(|t| !paused_only || t.paused)
-        .collect();
+    let (tasks, graph): (Vec<Task>, WorkGraph) = match at {
+        Some(at) => {
+            let (graph, _) = super::load_workgraph_at(dir, at)?;
+            let tasks = graph
+                .tasks()
+                .filter(|t| status_filter.is_none_or(|s| t.status == s))
+                .filter(|t| !paused_only || t.paused)
+                .cloned()
+                .collect();
+            (tasks, graph)
+        }
+        None => {
+            let store = super::open_store(dir)?;
+            let tasks: Vec<_> = store
+                .list_tasks(status_filter)?
+                .into_iter()
+                .filter(|t| !paused_only || t.paused)
+                .collect();
+
+            // Epic rollups need every task, not just the ones listed
+            let mut graph = WorkGraph::new();
+            let all = if status_filter.is_some() || paused_only {
+                store.list_tasks(None)?
+            } else {
+                tasks.clone()
+            };
+            for task in all {
+                graph.add_node(Node::Task(task));
+            }
+            (tasks, graph)
+        }
+    };
+    let epics = epic::epic_ids(&graph);
 
     if json {
         let output: Vec<_> = tasks
@@ -43,6 +78,12 @@ pub fn run(dir: &Path, status_filter: Option<&str>, paused_only: bool, json: boo
                 if t.paused {
                     obj["paused"] = serde_json::json!(true);
                 }
+                if let Some(ref parent) = t.parent {
+                    obj["parent"] = serde_json::json!(parent);
+                }
+                if epics.contains(t.id.as_str()) {
+                    obj["rollup"] = serde_json::json!(epic::rollup(&graph, &t.id));
+                }
                 obj
             })
             .collect();
@@ -50,20 +91,23 @@ pub fn run(dir: &Path, status_filter: Option<&str>, paused_only: bool, json: boo
     } else if tasks.is_empty() {
         println!("No tasks found");
     } else {
-        for task in tasks {
-            let status = match task.status {
-                Status::Open => "[ ]",
-                Status::InProgress => "[~]",
-                Status::Done => "[x]",
-                Status::Blocked => "[!]",
-                Status::Failed => "[F]",
-                Status::Abandoned => "[A]",
-            };
+        for (task, depth) in nest_under_epics(&graph, &tasks, expand) {
             let pause_str = if task.paused { " [PAUSED]" } else { "" };
             let delay_str = format_ready_after_hint(task.ready_after.as_deref());
+            let rollup_str = if epics.contains(task.id.as_str()) {
+                format!(" [{}]", epic::rollup(&graph, &task.id).summary())
+            } else {
+                String::new()
+            };
             println!(
-                "{} {} - {}{}{}",
-                status, task.id, task.title, pause_str, delay_str
+                "{}{} {} - {}{}{}{}",
+                "  ".repeat(depth),
+                status_marker(task.status),
+                task.id,
+                task.title,
+                rollup_str,
+                pause_str,
+                delay_str
             );
         }
     }
@@ -71,6 +115,49 @@ pub fn run(dir: &Path, status_filter: Option<&str>, paused_only: bool, json: boo
     Ok(())
 }
 
+pub fn status_marker(status: Status) -> &'static str {
+    match status {
+        Status::Open => "[ ]",
+        Status::InProgress => "[~]",
+        Status::Done => "[x]",
+        Status::Blocked => "[!]",
+        Status::Failed => "[F]",
+        Status::Abandoned => "[A]",
+    }
+}
+
+/// Order `tasks` so each follows its nearest listed epic, with its nesting
+/// depth. Unless `expand` is set, tasks under a listed epic are folded into
+/// the epic's rollup and left out.
+fn nest_under_epics<'a>(
+    graph: &WorkGraph,
+    tasks: &'a [Task],
+    expand: bool,
+) -> Vec<(&'a Task, usize)> {
+    let listed: HashSet<&str> = tasks.iter().map(|t| t.id.as_str()).collect();
+    let mut roots = Vec::new();
+    let mut children: HashMap<&str, Vec<&Task>> = HashMap::new();
+    for task in tasks {
+        let listed_ancestor = epic::ancestors(graph, &task.id)
+            .into_iter()
+            .find(|a| listed.contains(a.id.as_str()));
+        match listed_ancestor {
+            Some(ancestor) => children.entry(ancestor.id.as_str()).or_default().push(task),
+            None => roots.push(task),
+        }
+    }
+
+    let mut out = Vec::new();
+    let mut stack: Vec<(&Task, usize)> = roots.into_iter().rev().map(|t| (t, 0)).collect();
+    while let Some((task, depth)) = stack.pop() {
+        out.push((task, depth));
+        if expand && let Some(kids) = children.get(task.id.as_str()) {
+            stack.extend(kids.iter().rev().map(|t| (*t, depth + 1)));
+        }
+    }
+    out
+}
+
 /// If ready_after is set and in the future, return a hint string like " [ready in 5m 30s]".
 fn format_ready_after_hint(ready_after: Option<&str>) -> String {
     let Some(ra) = ready_after else {
@@ -182,7 +269,7 @@ mod tests {
     #[test]
     fn test_run_uninitialized() {
         let dir = tempdir().unwrap();
-        let result = run(dir.path(), None, false, false);
+        let result = run(dir.path(), None, false, false, None, false);
         assert!(result.is_err());
         assert!(result.unwrap_err().to_string().contains("not initialized"));
     }
@@ -191,7 +278,7 @@ mod tests {
     fn test_run_no_tasks() {
         let dir = tempdir().unwrap();
         setup_workgraph(dir.path(), vec![]);
-        let result = run(dir.path(), None, false, false);
+        let result = run(dir.path(), None, false, false, None, false);
         assert!(result.is_ok());
     }
 
@@ -206,7 +293,7 @@ mod tests {
                 make_task("t3", "In-progress task", Status::InProgress),
             ],
         );
-        let result = run(dir.path(), Some("open"), false, false);
+        let result = run(dir.path(), Some("open"), false, false, None, false);
         assert!(result.is_ok());
     }
 
@@ -220,7 +307,7 @@ mod tests {
                 make_task("t2", "Done task", Status::Done),
             ],
         );
-        let result = run(dir.path(), Some("done"), false, false);
+        let result = run(dir.path(), Some("done"), false, false, None, false);
         assert!(result.is_ok());
     }
 
@@ -231,7 +318,7 @@ mod tests {
             dir.path(),
             vec![make_task("t1", "IP task", Status::InProgress)],
         );
-        let result = run(dir.path(), Some("in-progress"), false, false);
+        let result = run(dir.path(), Some("in-progress"), false, false, None, false);
         assert!(result.is_ok());
     }
 
@@ -242,7 +329,7 @@ mod tests {
             dir.path(),
             vec![make_task("t1", "Blocked task", Status::Blocked)],
         );
-        let result = run(dir.path(), Some("blocked"), false, false);
+        let result = run(dir.path(), Some("blocked"), false, false, None, false);
         assert!(result.is_ok());
     }
 
@@ -250,7 +337,14 @@ mod tests {
     fn test_run_unknown_status_filter() {
         let dir = tempdir().unwrap();
         setup_workgraph(dir.path(), vec![make_task("t1", "Task", Status::Open)]);
-        let result = run(dir.path(), Some("nonexistent-status"), false, false);
+        let result = run(
+            dir.path(),
+            Some("nonexistent-status"),
+            false,
+            false,
+            None,
+            false,
+        );
         assert!(result.is_err());
         assert!(result.unwrap_err().to_string().contains("Unknown status"));
     }
@@ -303,7 +397,7 @@ mod tests {
         task.ready_after = Some(future.to_rfc3339());
         setup_workgraph(dir.path(), vec![task]);
 
-        let result = run(dir.path(), None, false, false);
+        let result = run(dir.path(), None, false, false, None, false);
         assert!(result.is_ok());
     }
 
@@ -317,7 +411,7 @@ mod tests {
         task.after = vec!["dep-1".to_string()];
         setup_workgraph(dir.path(), vec![task]);
 
-        let result = run(dir.path(), None, false, true);
+        let result = run(dir.path(), None, false, false, None, true);
         assert!(result.is_ok());
     }
 
@@ -379,6 +473,54 @@ mod tests {
         assert!(obj.get("ready_after").is_none());
     }
 
+    #[test]
+    fn test_nest_under_epics() {
+        let child = |id: &str, parent: &str| Task {
+            parent: Some(parent.to_string()),
+            ..make_task(id, id, Status::Open)
+        };
+        let tasks = vec![
+            child("login", "auth"),
+            make_task("auth", "Auth", Status::Open),
+            child("oauth", "auth"),
+            child("google", "oauth"),
+            make_task("docs", "Docs", Status::Open),
+        ];
+        let mut graph = WorkGraph::new();
+        for task in tasks.clone() {
+            graph.add_node(Node::Task(task));
+        }
+
+        let ids = |expand| -> Vec<(String, usize)> {
+            nest_under_epics(&graph, &tasks, expand)
+                .into_iter()
+                .map(|(t, depth)| (t.id.clone(), depth))
+                .collect()
+        };
+        assert_eq!(
+            ids(false),
+            vec![("auth".to_string(), 0), ("docs".to_string(), 0)]
+        );
+        assert_eq!(
+            ids(true),
+            vec![
+                ("auth".to_string(), 0),
+                ("login".to_string(), 1),
+                ("oauth".to_string(), 1),
+                ("google".to_string(), 2),
+                ("docs".to_string(), 0),
+            ]
+        );
+
+        // A child whose epic is filtered out is listed at the top level
+        let open_only = &tasks[2..];
+        let nested: Vec<_> = nest_under_epics(&graph, open_only, false)
+            .into_iter()
+            .map(|(t, depth)| (t.id.as_str(), depth))
+            .collect();
+        assert_eq!(nested, vec![("oauth", 0), ("docs", 0)]);
+    }
+
     #[test]
     fn test_run_status_filter_failed() {
         let dir = tempdir().unwrap();
@@ -389,7 +531,7 @@ mod tests {
                 make_task("t2", "Open task", Status::Open),
             ],
         );
-        let result = run(dir.path(), Some("failed"), false, false);
+        let result = run(dir.path(), Some("failed"), false, false, None, false);
         assert!(result.is_ok());
     }
 
@@ -403,7 +545,7 @@ mod tests {
                 make_task("t2", "Open task", Status::Open),
             ],
         );
-        let result = run(dir.path(), Some("abandoned"), false, false);
+        let result = run(dir.path(), Some("abandoned"), false, false, None, false);
         assert!(result.is_ok());
     }
 
@@ -411,7 +553,7 @@ mod tests {
     fn test_unknown_status_error_lists_valid_values() {
         let dir = tempdir().unwrap();
         setup_workgraph(dir.path(), vec![make_task("t1", "Task", Status::Open)]);
-        let result = run(dir.path(), Some("bogus"), false, false);
+        let result = run(dir.path(), Some("bogus"), false, false, None, false);
         assert!(result.is_err());
         let msg = result.unwrap_err().to_string();
         assert!(msg.contains("Valid values:"));
@@ -455,7 +597,7 @@ mod tests {
                 make_task("t2", "Done", Status::Done),
             ],
         );
-        let result = run(dir.path(), Some("done"), false, true);
+        let result = run(dir.path(), Some("done"), false, false, None, true);
         assert!(result.is_ok());
     }
 
@@ -486,11 +628,11 @@ mod tests {
         assert_eq!(paused_open[0].id, "t-paused");
 
         // run() with paused_only=true should succeed
-        let result = run(dir.path(), None, true, false);
+        let result = run(dir.path(), None, true, false, None, false);
         assert!(result.is_ok());
 
         // run() with paused_only=true and status filter should succeed
-        let result = run(dir.path(), Some("open"), true, false);
+        let result = run(dir.path(), Some("open"), true, false, None, false);
         assert!(result.is_ok());
     }
 }
@@ -0,0 +1,192 @@
+//! Convert the work graph between storage backends (`wg migrate-storage`).
+//!
+//! The graph is copied into the target backend, read back and compared node by
+//! node before `[storage] backend` is switched, so a failed or lossy copy never
+//! becomes the live graph. The old file is kept as a `.bak` next to it.
+
+use anyhow::{Context, Result};
+use std::collections::HashMap;
+use std::fs;
+use std::path::Path;
+use workgraph::config::Config;
+use workgraph::graph::{Node, WorkGraph};
+use workgraph::parser::journal_path;
+use workgraph::store::{self, StorageBackend};
+
+/// Marker left in graph.jsonl after moving to SQLite, so tooling that checks
+/// for an initialized workgraph keeps working.
+const SQLITE_MARKER: &str =
+    "# This workgraph is stored in graph.db (storage.backend = \"sqlite\").\n";
+
+pub fn run(dir: &Path, to: &str, dry_run: bool) -> Result<()> {
+    if !super::graph_path(dir).exists() {
+        anyhow::bail!("Workgraph not initialized. Run 'wg init' first.");
+    }
+
+    let target: StorageBackend = to.parse()?;
+    let current = store::configured_backend(dir)?;
+    if current == target {
+        println!("Graph is already stored in the {} backend.", target);
+        return Ok(());
+    }
+
+    let source = store::open_backend(dir, current)?;
+    let graph = source
+        .load()
+        .context("Failed to load graph from current backend")?;
+
+    if dry_run {
+        println!(
+            "Would migrate {} nodes from {} ({}) to {} ({})",
+            graph.len(),
+            current,
+            current.file_name(),
+            target,
+            target.file_name()
+        );
+        return Ok(());
+    }
+
+    // Write every node rather than treating this as an incremental save.
+    let mut fresh = graph.clone();
+    fresh.untrack_changes();
+
+    // Moving to JSONL overwrites the SQLite marker in graph.jsonl.
+    let dest = store::open_backend(dir, target)?;
+    dest.save(&fresh)
+        .with_context(|| format!("Failed to write {}", dest.location().display()))?;
+
+    verify(&graph, &dest.load()?)?;
+
+    match target {
+        StorageBackend::Sqlite => {
+            let jsonl = dir.join(StorageBackend::Jsonl.file_name());
+            fs::copy(&jsonl, dir.join("graph.jsonl.bak"))
+                .context("Failed to back up graph.jsonl")?;
+            let journal = journal_path(&jsonl);
+            if journal.exists() {
+                fs::rename(&journal, dir.join("graph.jsonl.journal.bak"))?;
+            }
+            fs::write(&jsonl, SQLITE_MARKER).context("Failed to write graph.jsonl marker")?;
+        }
+        StorageBackend::Jsonl => {
+            let db = dir.join(StorageBackend::Sqlite.file_name());
+            fs::rename(&db, dir.join("graph.db.bak")).context("Failed to back up graph.db")?;
+        }
+    }
+
+    let mut config = Config::load(dir)?;
+    config.storage.backend = target.as_str().to_string();
+    config.save(dir)?;
+
+    let config = Config::load_or_default(dir);
+    let _ = workgraph::provenance::record(
+        dir,
+        "migrate-storage",
+        None,
+        None,
+        serde_json::json!({ "from": current.as_str(), "to": target.as_str(), "nodes": graph.len() }),
+        config.log.rotation_threshold,
+    );
+
+    println!(
+        "Migrated {} nodes from {} to {} ({})",
+        graph.len(),
+        current,
+        target,
+        dest.location().display()
+    );
+    Ok(())
+}
+
+/// Fail unless `copy` holds exactly the same nodes as `original`.
+fn verify(original: &WorkGraph, copy: &WorkGraph) -> Result<()> {
+    let copied: HashMap<&str, &Node> = copy.nodes().map(|n| (n.id(), n)).collect();
+    if copied.len() != original.len() {
+        anyhow::bail!(
+            "Migration verification failed: expected {} nodes, found {}",
+            original.len(),
+            copied.len()
+        );
+    }
+    for node in original.nodes() {
+        if copied.get(node.id()) != Some(&node) {
+            anyhow::bail!(
+                "Migration verification failed: node '{}' differs after copy",
+                node.id()
+            );
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+    use workgraph::graph::{LogEntry, Status, Task};
+    use workgraph::parser::{load_graph, save_graph};
+
+    fn setup(dir: &Path) {
+        let mut task = Task {
+            id: "b".to_string(),
+            title: "Task b".to_string(),
+            after: vec!["a".to_string()],
+            ..Task::default()
+        };
+        task.log.push(LogEntry {
+            timestamp: "2026-01-01T00:00:00Z".to_string(),
+            actor: None,
+            message: "created".to_string(),
+        });
+        let mut graph = WorkGraph::new();
+        graph.add_node(Node::Task(Task {
+            id: "a".to_string(),
+            title: "Task a".to_string(),
+            status: Status::Done,
+            ..Task::default()
+        }));
+        graph.add_node(Node::Task(task));
+        save_graph(&graph, super::super::graph_path(dir)).unwrap();
+    }
+
+    #[test]
+    fn test_migrate_roundtrip_is_lossless() {
+        let tmp = tempdir().unwrap();
+        let dir = tmp.path();
+        setup(dir);
+        let before = load_graph(super::super::graph_path(dir)).unwrap();
+
+        run(dir, "sqlite", false).unwrap();
+        assert_eq!(Config::load(dir).unwrap().storage.backend, "sqlite");
+        assert!(dir.join("graph.db").exists());
+        assert!(dir.join("graph.jsonl.bak").exists());
+
+        // load_graph now reads from SQLite
+        let via_sqlite = load_graph(super::super::graph_path(dir)).unwrap();
+        verify(&before, &via_sqlite).unwrap();
+
+        run(dir, "jsonl", false).unwrap();
+        assert_eq!(Config::load(dir).unwrap().storage.backend, "jsonl");
+        assert!(dir.join("graph.db.bak").exists());
+        let back = load_graph(super::super::graph_path(dir)).unwrap();
+        verify(&before, &back).unwrap();
+    }
+
+    #[test]
+    fn test_migrate_dry_run_changes_nothing() {
+        let tmp = tempdir().unwrap();
+        let dir = tmp.path();
+        setup(dir);
+        run(dir, "sqlite", true).unwrap();
+        assert!(!dir.join("graph.db").exists());
+        assert_eq!(Config::load(dir).unwrap().storage.backend, "jsonl");
+    }
+
+    #[test]
+    fn test_migrate_rejects_unknown_backend() {
+        let tmp = tempdir().unwrap();
+        setup(tmp.path());
+        assert!(run(tmp.path(), "postgres", false).is_err());
+    }
+}
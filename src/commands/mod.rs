@@ -26,16 +26,21 @@ pub mod coordinate;
 pub mod cost;
 pub mod critical_path;
 pub mod dead_agents;
+pub mod diff;
 pub mod done;
 pub mod edit;
+pub mod email;
+pub mod epic;
 pub mod evaluate;
 pub mod evolve;
 pub mod exec;
 pub mod fail;
 pub mod forecast;
+pub mod gate;
 pub mod gc;
 pub mod graph;
 pub mod heartbeat;
+pub mod http_api;
 pub mod impact;
 pub mod init;
 pub mod kill;
@@ -44,12 +49,14 @@ pub mod log;
 pub mod loops;
 pub mod match_cmd;
 pub mod migrate_loops;
+pub mod migrate_storage;
 #[cfg(any(feature = "matrix", feature = "matrix-lite"))]
 pub mod matrix;
 pub mod motivation;
 pub mod next;
 #[cfg(any(feature = "matrix", feature = "matrix-lite"))]
 pub mod notify;
+pub mod output;
 pub mod pause;
 pub mod peer;
 pub mod plan;
@@ -64,6 +71,7 @@ pub mod resume;
 pub mod retry;
 pub mod role;
 pub mod runs_cmd;
+pub mod schedule;
 pub mod service;
 pub mod setup;
 pub mod show;
@@ -81,9 +89,11 @@ pub mod trace_import;
 pub mod trace_instantiate;
 pub mod trace_make_adaptive;
 pub mod trajectory;
+pub mod undo;
 pub mod velocity;
 pub mod viz;
 pub mod watch;
+pub mod webhooks;
 pub mod why_blocked;
 pub mod workload;
 
@@ -91,7 +101,13 @@ use std::collections::{HashMap, HashSet};
 use std::path::{Path, PathBuf};
 
 use anyhow::{Context, Result};
+use workgraph::budget::{self, BudgetUsage};
+use workgraph::capacity::{Capacity, ResourceWait};
+use workgraph::config::CoordinatorConfig;
+use workgraph::dispatch::DispatchPolicy;
+use workgraph::graph::{CycleDecision, Task, WorkGraph};
 use workgraph::parser::load_graph;
+pub use workgraph::propagation::propagate;
 
 /// Load the workgraph (immutable) from the given directory.
 /// Returns the graph and the path to the graph file (needed for save_graph).
@@ -104,6 +120,30 @@ pub fn load_workgraph(dir: &Path) -> Result<(workgraph::graph::WorkGraph, PathBu
     Ok((graph, path))
 }
 
+/// Open the configured graph store for read-only queries that do not need
+/// the whole graph (`wg list`, `wg show`, `wg ready`).
+pub fn open_store(dir: &Path) -> Result<Box<dyn workgraph::store::GraphStore>> {
+    if !graph_path(dir).exists() {
+        anyhow::bail!("Workgraph not initialized. Run 'wg init' first.");
+    }
+    workgraph::store::open(dir)
+}
+
+/// Reconstruct the workgraph as it stood at `at` (see
+/// [`workgraph::history::parse_time`]) from the graph history log.
+/// Returns the graph and the parsed time.
+pub fn load_workgraph_at(
+    dir: &Path,
+    at: &str,
+) -> Result<(WorkGraph, chrono::DateTime<chrono::Utc>)> {
+    if !graph_path(dir).exists() {
+        anyhow::bail!("Workgraph not initialized. Run 'wg init' first.");
+    }
+    let at = workgraph::history::parse_time(at)?;
+    let graph = workgraph::history::graph_at(dir, at)?;
+    Ok((graph, at))
+}
+
 /// Load the workgraph (mutable) from the given directory.
 /// Returns the graph and the path to the graph file (needed for save_graph).
 pub fn load_workgraph_mut(dir: &Path) -> Result<(workgraph::graph::WorkGraph, PathBuf)> {
@@ -147,12 +187,113 @@ pub fn collect_transitive_dependents(
     }
 }
 
+/// Fail unless every resource `task_id` requires has capacity left for it.
+/// Claiming the task takes that capacity; save the claim while holding
+/// [`workgraph::capacity::lock_claim`], which checks again.
+pub fn ensure_capacity(graph: &WorkGraph, task_id: &str) -> Result<()> {
+    workgraph::capacity::check(graph, graph.get_task_or_err(task_id)?)?;
+    Ok(())
+}
+
+/// Order `ready` the way the coordinator dispatches it under `config`.
+pub fn dispatch_order<'a>(
+    graph: &WorkGraph,
+    ready: Vec<&'a Task>,
+    config: &CoordinatorConfig,
+) -> Vec<&'a Task> {
+    let path_hours = if config.dispatch == DispatchPolicy::CriticalPathFirst {
+        critical_path::path_hours(graph)
+    } else {
+        HashMap::new()
+    };
+    workgraph::dispatch::order(graph, ready, config, &path_hours)
+}
+
+/// Why the coordinator passes over a ready task.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Hold {
+    /// Approval gates wait for their approvers, not an agent
+    Gate,
+    /// A budget does not admit the task: its ID and how much of it is used
+    Budget { id: String, used: String },
+    /// Resources the task requires are at capacity
+    Capacity(Vec<ResourceWait>),
+}
+
+impl std::fmt::Display for Hold {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Hold::Gate => write!(f, "awaiting approval"),
+            Hold::Budget { id, used } => write!(f, "budget '{}' is used up ({})", id, used),
+            Hold::Capacity(waits) => {
+                let waits: Vec<String> = waits.iter().map(ToString::to_string).collect();
+                write!(f, "waiting for {}", waits.join("; "))
+            }
+        }
+    }
+}
+
+/// The coordinator's ready set in dispatch order, each task paired with
+/// what holds it back, if anything. As in a coordinator tick, every task
+/// not held takes its share of the budgets and resources it requires
+/// before the tasks after it are checked. `wg ready` lists the same queue.
+pub fn dispatch_queue<'a>(
+    graph: &'a WorkGraph,
+    dir: &Path,
+    budgets: &[BudgetUsage],
+    config: &CoordinatorConfig,
+) -> Vec<(&'a Task, Option<Hold>)> {
+    let cycle_analysis = graph.compute_cycle_analysis();
+    let ready = workgraph::query::ready_tasks_with_peers_cycle_aware(graph, dir, &cycle_analysis);
+    let mut budgets = budgets.to_vec();
+    let mut capacity = Capacity::of(graph);
+    dispatch_order(graph, ready, config)
+        .into_iter()
+        .map(|task| {
+            if task.gate.is_some() {
+                return (task, Some(Hold::Gate));
+            }
+            if let Some(usage) = budget::blocking(&budgets, task) {
+                let used = format!(
+                    "{} of {}",
+                    usage.format(usage.used_for(&task.id)),
+                    usage.format(usage.available)
+                );
+                let hold = Hold::Budget {
+                    id: usage.id.clone(),
+                    used,
+                };
+                return (task, Some(hold));
+            }
+            let waits = capacity.waits(task);
+            if !waits.is_empty() {
+                return (task, Some(Hold::Capacity(waits)));
+            }
+            budgets.iter_mut().for_each(|b| b.reserve(task));
+            capacity.acquire(task);
+            (task, None)
+        })
+        .collect()
+}
+
 /// Best-effort notification to the service daemon that the graph has changed.
 /// Silently ignores all errors (daemon not running, socket unavailable, etc.)
 pub fn notify_graph_changed(dir: &Path) {
     let _ = service::send_request(dir, &service::IpcRequest::GraphChanged);
 }
 
+/// Record why a cycle did or did not iterate, for `wg cycles` to explain.
+pub fn record_cycle_decision(dir: &Path, decision: &CycleDecision, threshold: u64) {
+    let _ = workgraph::provenance::record(
+        dir,
+        "cycle_iteration",
+        Some(&decision.cycle),
+        None,
+        serde_json::to_value(decision).unwrap_or_default(),
+        threshold,
+    );
+}
+
 /// Check service status and print a hint for the user/agent.
 /// Returns true if the service is running.
 pub fn print_service_hint(dir: &Path) -> bool {
@@ -206,9 +347,14 @@ mod provenance_coverage_tests {
         let tmp = setup_dir();
         let dir = tmp.path();
         super::add::run(
-            dir, "Test task", Some("prov-add"), None,
-            &[], None, None, None, &[], &[], &[], &[], None, None, None, None, None, None, "internal",
-        ).unwrap();
+            dir,
+            "Test task",
+            super::add::AddOptions {
+                id: Some("prov-add"),
+                ..Default::default()
+            },
+        )
+        .unwrap();
 
         let entries = ops_with_type(dir, "add_task");
         assert_eq!(entries.len(), 1);
@@ -220,14 +366,24 @@ mod provenance_coverage_tests {
         let tmp = setup_dir();
         let dir = tmp.path();
         super::add::run(
-            dir, "Edit target", Some("prov-edit"), None,
-            &[], None, None, None, &[], &[], &[], &[], None, None, None, None, None, None, "internal",
-        ).unwrap();
+            dir,
+            "Edit target",
+            super::add::AddOptions {
+                id: Some("prov-edit"),
+                ..Default::default()
+            },
+        )
+        .unwrap();
 
         super::edit::run(
-            dir, "prov-edit", Some("New Title"), None,
-            &[], &[], &[], &[], None, &[], &[], None, None, None, None,
-        ).unwrap();
+            dir,
+            "prov-edit",
+            super::edit::EditOptions {
+                title: Some("New Title"),
+                ..Default::default()
+            },
+        )
+        .unwrap();
 
         let entries = ops_with_type(dir, "edit");
         assert_eq!(entries.len(), 1);
@@ -242,9 +398,14 @@ mod provenance_coverage_tests {
         let tmp = setup_dir();
         let dir = tmp.path();
         super::add::run(
-            dir, "Claim target", Some("prov-claim"), None,
-            &[], None, None, None, &[], &[], &[], &[], None, None, None, None, None, None, "internal",
-        ).unwrap();
+            dir,
+            "Claim target",
+            super::add::AddOptions {
+                id: Some("prov-claim"),
+                ..Default::default()
+            },
+        )
+        .unwrap();
 
         super::claim::claim(dir, "prov-claim", Some("agent-1")).unwrap();
         let entries = ops_with_type(dir, "claim");
@@ -263,11 +424,16 @@ mod provenance_coverage_tests {
         let tmp = setup_dir();
         let dir = tmp.path();
         super::add::run(
-            dir, "Done target", Some("prov-done"), None,
-            &[], None, None, None, &[], &[], &[], &[], None, None, None, None, None, None, "internal",
-        ).unwrap();
-
-        super::done::run(dir, "prov-done", false).unwrap();
+            dir,
+            "Done target",
+            super::add::AddOptions {
+                id: Some("prov-done"),
+                ..Default::default()
+            },
+        )
+        .unwrap();
+
+        super::done::run(dir, "prov-done", false, false).unwrap();
         let entries = ops_with_type(dir, "done");
         assert_eq!(entries.len(), 1);
         assert_eq!(entries[0].task_id.as_deref(), Some("prov-done"));
@@ -278,9 +444,14 @@ mod provenance_coverage_tests {
         let tmp = setup_dir();
         let dir = tmp.path();
         super::add::run(
-            dir, "Fail target", Some("prov-fail"), None,
-            &[], None, None, None, &[], &[], &[], &[], None, None, None, None, None, None, "internal",
-        ).unwrap();
+            dir,
+            "Fail target",
+            super::add::AddOptions {
+                id: Some("prov-fail"),
+                ..Default::default()
+            },
+        )
+        .unwrap();
 
         super::fail::run(dir, "prov-fail", Some("timeout")).unwrap();
         let entries = ops_with_type(dir, "fail");
@@ -293,9 +464,14 @@ mod provenance_coverage_tests {
         let tmp = setup_dir();
         let dir = tmp.path();
         super::add::run(
-            dir, "Abandon target", Some("prov-abandon"), None,
-            &[], None, None, None, &[], &[], &[], &[], None, None, None, None, None, None, "internal",
-        ).unwrap();
+            dir,
+            "Abandon target",
+            super::add::AddOptions {
+                id: Some("prov-abandon"),
+                ..Default::default()
+            },
+        )
+        .unwrap();
 
         super::abandon::run(dir, "prov-abandon", Some("no longer needed")).unwrap();
         let entries = ops_with_type(dir, "abandon");
@@ -308,9 +484,14 @@ mod provenance_coverage_tests {
         let tmp = setup_dir();
         let dir = tmp.path();
         super::add::run(
-            dir, "Retry target", Some("prov-retry"), None,
-            &[], None, None, None, &[], &[], &[], &[], None, None, None, None, None, None, "internal",
-        ).unwrap();
+            dir,
+            "Retry target",
+            super::add::AddOptions {
+                id: Some("prov-retry"),
+                ..Default::default()
+            },
+        )
+        .unwrap();
 
         super::fail::run(dir, "prov-retry", Some("compile error")).unwrap();
         super::retry::run(dir, "prov-retry").unwrap();
@@ -326,9 +507,14 @@ mod provenance_coverage_tests {
         let tmp = setup_dir();
         let dir = tmp.path();
         super::add::run(
-            dir, "Pause target", Some("prov-pause"), None,
-            &[], None, None, None, &[], &[], &[], &[], None, None, None, None, None, None, "internal",
-        ).unwrap();
+            dir,
+            "Pause target",
+            super::add::AddOptions {
+                id: Some("prov-pause"),
+                ..Default::default()
+            },
+        )
+        .unwrap();
 
         super::pause::run(dir, "prov-pause").unwrap();
         let entries = ops_with_type(dir, "pause");
@@ -346,9 +532,14 @@ mod provenance_coverage_tests {
         let tmp = setup_dir();
         let dir = tmp.path();
         super::add::run(
-            dir, "Artifact target", Some("prov-art"), None,
-            &[], None, None, None, &[], &[], &[], &[], None, None, None, None, None, None, "internal",
-        ).unwrap();
+            dir,
+            "Artifact target",
+            super::add::AddOptions {
+                id: Some("prov-art"),
+                ..Default::default()
+            },
+        )
+        .unwrap();
 
         super::artifact::run_add(dir, "prov-art", "output.txt").unwrap();
         let entries = ops_with_type(dir, "artifact_add");
@@ -366,10 +557,15 @@ mod provenance_coverage_tests {
         let tmp = setup_dir();
         let dir = tmp.path();
         super::add::run(
-            dir, "Archive target", Some("prov-archive"), None,
-            &[], None, None, None, &[], &[], &[], &[], None, None, None, None, None, None, "internal",
-        ).unwrap();
-        super::done::run(dir, "prov-archive", false).unwrap();
+            dir,
+            "Archive target",
+            super::add::AddOptions {
+                id: Some("prov-archive"),
+                ..Default::default()
+            },
+        )
+        .unwrap();
+        super::done::run(dir, "prov-archive", false, false).unwrap();
 
         super::archive::run(dir, false, None, false, false).unwrap();
         let entries = ops_with_type(dir, "archive");
@@ -383,9 +579,14 @@ mod provenance_coverage_tests {
         let tmp = setup_dir();
         let dir = tmp.path();
         super::add::run(
-            dir, "GC target", Some("prov-gc"), None,
-            &[], None, None, None, &[], &[], &[], &[], None, None, None, None, None, None, "internal",
-        ).unwrap();
+            dir,
+            "GC target",
+            super::add::AddOptions {
+                id: Some("prov-gc"),
+                ..Default::default()
+            },
+        )
+        .unwrap();
         super::fail::run(dir, "prov-gc", Some("oops")).unwrap();
         super::abandon::run(dir, "prov-gc", Some("giving up")).unwrap();
 
@@ -403,14 +604,25 @@ mod provenance_coverage_tests {
 
         // add
         super::add::run(
-            dir, "Lifecycle task", Some("lifecycle"), None,
-            &[], None, None, None, &[], &[], &[], &[], None, None, None, None, None, None, "internal",
-        ).unwrap();
+            dir,
+            "Lifecycle task",
+            super::add::AddOptions {
+                id: Some("lifecycle"),
+                ..Default::default()
+            },
+        )
+        .unwrap();
         // edit
         super::edit::run(
-            dir, "lifecycle", Some("Renamed"), None,
-            &[], &[], &["tag1".to_string()], &[], None, &[], &[], None, None, None, None,
-        ).unwrap();
+            dir,
+            "lifecycle",
+            super::edit::EditOptions {
+                title: Some("Renamed"),
+                add_tag: &["tag1".to_string()],
+                ..Default::default()
+            },
+        )
+        .unwrap();
         // pause
         super::pause::run(dir, "lifecycle").unwrap();
         // resume
@@ -426,7 +638,7 @@ mod provenance_coverage_tests {
         // retry
         super::retry::run(dir, "lifecycle").unwrap();
         // done
-        super::done::run(dir, "lifecycle", false).unwrap();
+        super::done::run(dir, "lifecycle", false, false).unwrap();
 
         let all = read_all_operations(dir).unwrap();
         let ops: Vec<&str> = all.iter().map(|e| e.op.as_str()).collect();
@@ -85,6 +85,8 @@ pub fn run_add(
         provider: provider.unwrap_or("openrouter").to_string(),
         cost_per_1m_input: cost_in,
         cost_per_1m_output: cost_out,
+        cost_per_1m_cache_read: None,
+        cost_per_1m_cache_write: None,
         context_window: context_window.unwrap_or(128_000),
         capabilities: capabilities.to_vec(),
         tier,
@@ -16,6 +16,10 @@ struct TaskCandidate {
     missing_skills: Vec<String>,
     hours: Option<f64>,
     inputs_available: bool,
+    /// Effective priority, including any inherited from dependents
+    priority: i32,
+    /// Position in the coordinator's dispatch order (1-based)
+    dispatch_rank: usize,
 }
 
 /// Result of next task query
@@ -39,13 +43,17 @@ pub fn run(dir: &Path, agent_id: &str, json: bool) -> Result<()> {
 
     let agent_skills: HashSet<&String> = agent.capabilities.iter().collect();
 
-    // Get ready tasks
-    let ready = ready_tasks(&graph);
+    // Get ready tasks, in the order the coordinator would dispatch them
+    let coordinator = workgraph::config::Config::load_or_default(dir).coordinator;
+    let ready = super::dispatch_order(&graph, ready_tasks(&graph), &coordinator);
+    let ready: Vec<_> = ready.into_iter().filter(|t| t.gate.is_none()).collect();
+    let priorities = workgraph::dispatch::effective_priorities(&graph);
 
     // Score each task for this agent
     let mut candidates: Vec<TaskCandidate> = ready
         .iter()
-        .map(|task| {
+        .enumerate()
+        .map(|(rank, task)| {
             let task_skills: HashSet<&String> = task.skills.iter().collect();
 
             let matched: Vec<String> = agent_skills
@@ -100,12 +108,18 @@ pub fn run(dir: &Path, agent_id: &str, json: bool) -> Result<()> {
                 missing_skills: missing,
                 hours: task.estimate.as_ref().and_then(|e| e.hours),
                 inputs_available: inputs_available || task.inputs.is_empty(),
+                priority: priorities.get(&task.id).copied().unwrap_or(task.priority),
+                dispatch_rank: rank + 1,
             }
         })
         .collect();
 
-    // Sort by score descending
-    candidates.sort_by(|a, b| b.score.cmp(&a.score));
+    // Sort by score descending; equal scores keep the dispatch order
+    candidates.sort_by(|a, b| {
+        b.score
+            .cmp(&a.score)
+            .then_with(|| a.dispatch_rank.cmp(&b.dispatch_rank))
+    });
 
     // Filter to only tasks with non-negative score (at least partial capability match)
     // But include tasks with no skill requirements
@@ -170,6 +184,12 @@ fn print_candidate(task: &TaskCandidate) {
 
     println!("  {} - {}{}{}", task.id, task.title, hours_str, inputs_str);
     println!("    Score: {}", task.score);
+    let priority = if task.priority != 0 {
+        format!(", priority {}", task.priority)
+    } else {
+        String::new()
+    };
+    println!("    Dispatch order: #{}{}", task.dispatch_rank, priority);
 
     if !task.matched_skills.is_empty() {
         println!("    Matched: {}", task.matched_skills.join(", "));
@@ -131,7 +131,7 @@ pub fn run(
         .context("Failed to create runtime")?;
 
     let result = rt.block_on(async {
-        send_notification(dir, &matrix_config, &target_room, &plain_text, &html).await
+        send_notification(dir, &matrix_config, &target_room, task, &plain_text, &html).await
     });
 
     match result {
@@ -170,6 +170,7 @@ async fn send_notification(
     dir: &Path,
     config: &MatrixConfig,
     room: &str,
+    task: &Task,
     plain_text: &str,
     html: &str,
 ) -> Result<()> {
@@ -182,11 +183,28 @@ async fn send_notification(
         eprintln!("Warning: failed to join room {}: {}", room, e);
     }
 
+    // The lightweight client posts into the task's thread
+    #[cfg(all(feature = "matrix-lite", not(feature = "matrix")))]
+    {
+        let root = client
+            .task_thread(room, task)
+            .await
+            .context("Failed to start the task's thread")?;
+        client
+            .send_html_thread_message(room, Some(&root), plain_text, html)
+            .await
+            .context("Failed to send notification")?;
+    }
+
     // Send the formatted message
-    client
-        .send_html_message(room, plain_text, html)
-        .await
-        .context("Failed to send notification")?;
+    #[cfg(feature = "matrix")]
+    {
+        let _ = task;
+        client
+            .send_html_message(room, plain_text, html)
+            .await
+            .context("Failed to send notification")?;
+    }
 
     Ok(())
 }
@@ -321,32 +339,9 @@ mod tests {
             description: Some("This is a test description".to_string()),
             status: Status::InProgress,
             assigned: Some("agent-1".to_string()),
-            estimate: None,
-            before: vec![],
             after: vec!["blocker-1".to_string()],
-            requires: vec![],
-            tags: vec![],
-            skills: vec![],
-            inputs: vec![],
-            deliverables: vec![],
-            artifacts: vec![],
-            exec: None,
-            not_before: None,
-            created_at: None,
-            started_at: None,
-            completed_at: None,
-            log: vec![],
-            retry_count: 0,
-            max_retries: None,
-            failure_reason: None,
-            model: None,
-            verify: None,
-            agent: None,
-            loop_iteration: 0,
-            ready_after: None,
-            paused: false,
             visibility: "internal".to_string(),
-            cycle_config: None,
+            ..Task::default()
         }
     }
 
@@ -0,0 +1,180 @@
+use anyhow::{Context, Result};
+use serde_json::Value;
+use std::collections::BTreeMap;
+use std::path::Path;
+use workgraph::bindings;
+use workgraph::parser::save_graph;
+
+/// Publish named outputs for a task, from `key=value` pairs and/or a JSON
+/// object file. Pairs override keys from the file.
+pub fn run_set(dir: &Path, task_id: &str, pairs: &[String], file: Option<&str>) -> Result<()> {
+    let mut outputs: BTreeMap<String, Value> = BTreeMap::new();
+    if let Some(file) = file {
+        let content =
+            std::fs::read_to_string(file).with_context(|| format!("Failed to read {}", file))?;
+        let value: Value = serde_json::from_str(&content)
+            .with_context(|| format!("{} is not valid JSON", file))?;
+        let Value::Object(map) = value else {
+            anyhow::bail!("{} must contain a JSON object of outputs", file);
+        };
+        for (key, value) in map {
+            bindings::check_key(&key).map_err(anyhow::Error::msg)?;
+            outputs.insert(key, value);
+        }
+    }
+    for pair in pairs {
+        let (key, value) = bindings::parse_assignment(pair).map_err(anyhow::Error::msg)?;
+        outputs.insert(key, value);
+    }
+    if outputs.is_empty() {
+        anyhow::bail!("Nothing to set: give key=value pairs or --file");
+    }
+
+    let (mut graph, path) = super::load_workgraph_mut(dir)?;
+    let task = graph.get_task_mut_or_err(task_id)?;
+    task.outputs
+        .extend(outputs.iter().map(|(k, v)| (k.clone(), v.clone())));
+
+    save_graph(&graph, &path).context("Failed to save graph")?;
+    super::notify_graph_changed(dir);
+
+    let config = workgraph::config::Config::load_or_default(dir);
+    let _ = workgraph::provenance::record(
+        dir,
+        "output_set",
+        Some(task_id),
+        None,
+        serde_json::json!({ "outputs": outputs }),
+        config.log.rotation_threshold,
+    );
+
+    for (key, value) in &outputs {
+        println!(
+            "Set output '{}' of '{}': {}",
+            key,
+            task_id,
+            bindings::render(value)
+        );
+    }
+    Ok(())
+}
+
+/// Remove published outputs from a task
+pub fn run_unset(dir: &Path, task_id: &str, keys: &[String]) -> Result<()> {
+    let (mut graph, path) = super::load_workgraph_mut(dir)?;
+    let task = graph.get_task_mut_or_err(task_id)?;
+
+    for key in keys {
+        if !task.outputs.contains_key(key) {
+            anyhow::bail!("Task '{}' has no output '{}'", task_id, key);
+        }
+    }
+    for key in keys {
+        task.outputs.remove(key);
+    }
+
+    save_graph(&graph, &path).context("Failed to save graph")?;
+    super::notify_graph_changed(dir);
+
+    let config = workgraph::config::Config::load_or_default(dir);
+    let _ = workgraph::provenance::record(
+        dir,
+        "output_unset",
+        Some(task_id),
+        None,
+        serde_json::json!({ "keys": keys }),
+        config.log.rotation_threshold,
+    );
+
+    println!("Removed {} output(s) from '{}'", keys.len(), task_id);
+    Ok(())
+}
+
+/// Show a task's outputs, or the value of one of them
+pub fn run_get(dir: &Path, task_id: &str, key: Option<&str>, json: bool) -> Result<()> {
+    let (graph, _path) = super::load_workgraph(dir)?;
+    let task = graph.get_task_or_err(task_id)?;
+
+    if let Some(key) = key {
+        let value = task
+            .outputs
+            .get(key)
+            .ok_or_else(|| anyhow::anyhow!("Task '{}' has no output '{}'", task_id, key))?;
+        if json {
+            println!("{}", serde_json::to_string_pretty(value)?);
+        } else {
+            println!("{}", bindings::render(value));
+        }
+        return Ok(());
+    }
+
+    if json {
+        println!("{}", serde_json::to_string_pretty(&task.outputs)?);
+    } else if task.outputs.is_empty() {
+        println!("Task '{}' has no outputs.", task_id);
+    } else {
+        for (key, value) in &task.outputs {
+            println!("{} = {}", key, bindings::render(value));
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::graph_path;
+    use super::*;
+    use tempfile::TempDir;
+    use workgraph::graph::{Node, Task, WorkGraph};
+    use workgraph::parser::load_graph;
+
+    fn setup() -> TempDir {
+        let temp_dir = TempDir::new().unwrap();
+        let mut graph = WorkGraph::new();
+        graph.add_node(Node::Task(Task {
+            id: "build".to_string(),
+            title: "Build".to_string(),
+            ..Task::default()
+        }));
+        save_graph(&graph, graph_path(temp_dir.path())).unwrap();
+        temp_dir
+    }
+
+    #[test]
+    fn test_set_from_pairs_and_file() {
+        let temp_dir = setup();
+        let file = temp_dir.path().join("outputs.json");
+        std::fs::write(&file, r#"{"binary_path": "old", "size": 1024}"#).unwrap();
+
+        run_set(
+            temp_dir.path(),
+            "build",
+            &["binary_path=target/release/wg".to_string()],
+            Some(file.to_str().unwrap()),
+        )
+        .unwrap();
+
+        let graph = load_graph(graph_path(temp_dir.path())).unwrap();
+        let task = graph.get_task("build").unwrap();
+        assert_eq!(task.outputs["binary_path"], "target/release/wg");
+        assert_eq!(task.outputs["size"], 1024);
+
+        run_unset(temp_dir.path(), "build", &["size".to_string()]).unwrap();
+        let graph = load_graph(graph_path(temp_dir.path())).unwrap();
+        assert!(
+            !graph
+                .get_task("build")
+                .unwrap()
+                .outputs
+                .contains_key("size")
+        );
+    }
+
+    #[test]
+    fn test_set_rejects_bad_input() {
+        let temp_dir = setup();
+        assert!(run_set(temp_dir.path(), "build", &[], None).is_err());
+        assert!(run_set(temp_dir.path(), "build", &["no-equals".to_string()], None).is_err());
+        assert!(run_unset(temp_dir.path(), "build", &["missing".to_string()]).is_err());
+    }
+}
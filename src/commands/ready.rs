@@ -1,13 +1,28 @@
 use anyhow::Result;
 use chrono::{DateTime, Utc};
 use std::path::Path;
-use workgraph::graph::Status;
-use workgraph::query::ready_tasks_cycle_aware;
+use workgraph::graph::{Status, Task};
+
+use super::Hold;
 
 pub fn run(dir: &Path, json: bool) -> Result<()> {
-    let (graph, _path) = super::load_workgraph(dir)?;
-    let cycle_analysis = graph.compute_cycle_analysis();
-    let ready = ready_tasks_cycle_aware(&graph, &cycle_analysis);
+    // Budgets count spend of finished tasks too, so this needs the whole graph
+    let graph = super::open_store(dir)?.load()?;
+
+    // List ready tasks in the order the coordinator would dispatch them,
+    // marking those a budget or resource capacity holds back
+    let coordinator = workgraph::config::Config::load_or_default(dir).coordinator;
+    let runs = workgraph::accounting::load(dir).unwrap_or_default();
+    let budgets = workgraph::budget::usage(&graph, &runs, Utc::now());
+    let queue = super::dispatch_queue(&graph, dir, &budgets, &coordinator);
+    // Gates wait for their approvers, not an agent
+    let (gates, ready): (Vec<_>, Vec<_>) = queue
+        .into_iter()
+        .partition(|(_, hold)| *hold == Some(Hold::Gate));
+    let gates: Vec<&Task> = gates.into_iter().map(|(t, _)| t).collect();
+    let priorities = workgraph::dispatch::effective_priorities(&graph);
+    let priority_of =
+        |t: &workgraph::graph::Task| priorities.get(&t.id).copied().unwrap_or(t.priority);
 
     // Find tasks that would be ready except they're waiting on ready_after
     let waiting: Vec<_> = graph
@@ -29,7 +44,7 @@ pub fn run(dir: &Path, json: bool) -> Result<()> {
             task.after.iter().all(|blocker_id| {
                 graph
                     .get_task(blocker_id)
-                    .map(|t| t.status.is_terminal())
+                    .map(|t| task.is_after_satisfied(blocker_id, t.status))
                     .unwrap_or(true)
             })
         })
@@ -38,14 +53,19 @@ pub fn run(dir: &Path, json: bool) -> Result<()> {
     if json {
         let mut output: Vec<_> = ready
             .iter()
-            .map(|t| {
-                serde_json::json!({
+            .map(|(t, hold)| {
+                let mut entry = serde_json::json!({
                     "id": t.id,
                     "title": t.title,
                     "assigned": t.assigned,
                     "estimate": t.estimate,
+                    "priority": priority_of(t),
                     "ready": true,
-                })
+                });
+                if let Some(hold) = hold {
+                    entry["held"] = hold.to_string().into();
+                }
+                entry
             })
             .collect();
         for t in &waiting {
@@ -58,19 +78,44 @@ pub fn run(dir: &Path, json: bool) -> Result<()> {
                 "ready_after": t.ready_after,
             }));
         }
+        for t in &gates {
+            output.push(serde_json::json!({
+                "id": t.id,
+                "title": t.title,
+                "ready": false,
+                "awaiting_approval": true,
+            }));
+        }
         println!("{}", serde_json::to_string_pretty(&output)?);
-    } else if ready.is_empty() && waiting.is_empty() {
+    } else if ready.is_empty() && waiting.is_empty() && gates.is_empty() {
         println!("No tasks ready");
     } else {
         if !ready.is_empty() {
-            println!("Ready tasks:");
-            for task in &ready {
+            println!("Ready tasks (dispatch order: {}):", coordinator.dispatch);
+            for (i, (task, hold)) in ready.iter().enumerate() {
                 let assigned = task
                     .assigned
                     .as_ref()
                     .map(|a| format!(" ({})", a))
                     .unwrap_or_default();
-                println!("  {} - {}{}", task.id, task.title, assigned);
+                let priority = match priority_of(task) {
+                    0 => String::new(),
+                    p if p != task.priority => format!(" [priority {}, inherited]", p),
+                    p => format!(" [priority {}]", p),
+                };
+                let held = hold
+                    .as_ref()
+                    .map(|h| format!(" — held: {}", h))
+                    .unwrap_or_default();
+                println!(
+                    "  {}. {} - {}{}{}{}",
+                    i + 1,
+                    task.id,
+                    task.title,
+                    assigned,
+                    priority,
+                    held
+                );
             }
         }
         if !waiting.is_empty() {
@@ -83,6 +128,15 @@ pub fn run(dir: &Path, json: bool) -> Result<()> {
                 println!("  {} - {} {}", task.id, task.title, countdown);
             }
         }
+        if !gates.is_empty() {
+            if !ready.is_empty() || !waiting.is_empty() {
+                println!();
+            }
+            println!("Awaiting approval (wg approve / wg reject):");
+            for task in &gates {
+                println!("  {} - {}", task.id, task.title);
+            }
+        }
     }
 
     Ok(())
@@ -110,6 +164,7 @@ mod tests {
     use tempfile::tempdir;
     use workgraph::graph::{Node, Task, WorkGraph};
     use workgraph::parser::{load_graph, save_graph};
+    use workgraph::query::ready_tasks_cycle_aware;
 
     fn make_task(id: &str, title: &str, status: Status) -> Task {
         Task {
@@ -361,6 +416,67 @@ mod tests {
         assert!(result.is_ok());
     }
 
+    #[test]
+    fn test_dispatch_queue_marks_held_tasks() {
+        use workgraph::graph::{Budget, Resource};
+        let dir = tempdir().unwrap();
+        let semaphore = |id: &str, budget: Option<Budget>| {
+            Node::Resource(Resource {
+                id: id.to_string(),
+                name: None,
+                resource_type: None,
+                available: Some(if budget.is_some() { 0.0 } else { 1.0 }),
+                unit: None,
+                budget,
+            })
+        };
+        let requiring = |id: &str, resource: &str, priority: i32| Task {
+            requires: vec![resource.to_string()],
+            priority,
+            ..make_task(id, id, Status::Open)
+        };
+        let mut graph = WorkGraph::new();
+        graph.add_node(semaphore("gpu", None));
+        graph.add_node(semaphore(
+            "spend",
+            Some(Budget {
+                tag: Some("paid".to_string()),
+                ..Budget::default()
+            }),
+        ));
+        // The first gpu task takes the only unit; the second waits for it
+        graph.add_node(Node::Task(requiring("train", "gpu", 2)));
+        graph.add_node(Node::Task(requiring("tune", "gpu", 1)));
+        graph.add_node(Node::Task(Task {
+            tags: vec!["paid".to_string()],
+            ..make_task("call-api", "Call API", Status::Open)
+        }));
+        graph.add_node(Node::Task(make_task("docs", "Docs", Status::Open)));
+
+        let coordinator = workgraph::config::Config::default().coordinator;
+        let budgets = workgraph::budget::usage(&graph, &[], Utc::now());
+        let queue = super::super::dispatch_queue(&graph, dir.path(), &budgets, &coordinator);
+        let held: Vec<(&str, Option<String>)> = queue
+            .iter()
+            .map(|(t, hold)| (t.id.as_str(), hold.as_ref().map(ToString::to_string)))
+            .collect();
+        assert_eq!(
+            held,
+            vec![
+                ("train", None),
+                (
+                    "tune",
+                    Some("waiting for gpu: needs 1, 1 of 1 in use by train".into())
+                ),
+                (
+                    "call-api",
+                    Some("budget 'spend' is used up ($0.00 of $0.00)".into())
+                ),
+                ("docs", None),
+            ]
+        );
+    }
+
     #[test]
     fn test_run_json_structure_ready_task() {
         let dir = tempdir().unwrap();
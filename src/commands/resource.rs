@@ -2,7 +2,25 @@ use anyhow::{Context, Result};
 use std::fs::OpenOptions;
 use std::io::Write;
 use std::path::Path;
-use workgraph::graph::{Node, Resource};
+use workgraph::graph::{Budget, BudgetWindow, Meter, Node, Resource};
+
+/// Build a budget from `--budget`, `--window`, `--tag` and `--per-task`.
+pub fn parse_budget(
+    meter: &str,
+    window: Option<&str>,
+    tag: Option<String>,
+    per_task: bool,
+) -> Result<Budget> {
+    Ok(Budget {
+        meter: meter.parse::<Meter>().map_err(anyhow::Error::msg)?,
+        window: window
+            .map(|w| w.parse::<BudgetWindow>())
+            .transpose()
+            .map_err(anyhow::Error::msg)?,
+        tag,
+        per_task,
+    })
+}
 
 pub fn run_add(
     dir: &Path,
@@ -11,6 +29,7 @@ pub fn run_add(
     resource_type: Option<&str>,
     available: Option<f64>,
     unit: Option<&str>,
+    budget: Option<Budget>,
 ) -> Result<()> {
     let (graph, path) = super::load_workgraph(dir)?;
 
@@ -19,12 +38,17 @@ pub fn run_add(
         anyhow::bail!("Node with ID '{}' already exists", id);
     }
 
+    if budget.is_some() && available.is_none() {
+        anyhow::bail!("A budget needs --available as its cap");
+    }
+
     let resource = Resource {
         id: id.to_string(),
         name: name.map(String::from),
         resource_type: resource_type.map(String::from),
         available,
         unit: unit.map(String::from),
+        budget,
     };
 
     // Append to file
@@ -58,6 +82,7 @@ pub fn run_list(dir: &Path, json: bool) -> Result<()> {
                     "type": r.resource_type,
                     "available": r.available,
                     "unit": r.unit,
+                    "budget": r.budget,
                 })
             })
             .collect();
@@ -73,7 +98,18 @@ pub fn run_list(dir: &Path, json: bool) -> Result<()> {
                 (Some(avail), None) => format!("{}", avail),
                 _ => "N/A".to_string(),
             };
-            println!("[{}] {} - {} ({})", type_str, r.id, name_str, avail_str);
+            let budget_str = r
+                .budget
+                .as_ref()
+                .map(|b| {
+                    let window = b.window.map(|w| w.to_string()).unwrap_or("total".into());
+                    format!(" budget: {} {}", window, b.meter)
+                })
+                .unwrap_or_default();
+            println!(
+                "[{}] {} - {} ({}){}",
+                type_str, r.id, name_str, avail_str, budget_str
+            );
         }
     }
 
@@ -99,7 +135,7 @@ mod tests {
     fn test_add_resource_basic() {
         let temp_dir = setup_workgraph();
 
-        let result = run_add(temp_dir.path(), "budget-q1", None, None, None, None);
+        let result = run_add(temp_dir.path(), "budget-q1", None, None, None, None, None);
 
         assert!(result.is_ok());
 
@@ -121,6 +157,7 @@ mod tests {
             Some("money"),
             Some(50000.0),
             Some("usd"),
+            None,
         );
 
         assert!(result.is_ok());
@@ -139,10 +176,10 @@ mod tests {
         let temp_dir = setup_workgraph();
 
         // Add first resource
-        run_add(temp_dir.path(), "budget", None, None, None, None).unwrap();
+        run_add(temp_dir.path(), "budget", None, None, None, None, None).unwrap();
 
         // Try to add duplicate
-        let result = run_add(temp_dir.path(), "budget", None, None, None, None);
+        let result = run_add(temp_dir.path(), "budget", None, None, None, None, None);
 
         assert!(result.is_err());
         assert!(result.unwrap_err().to_string().contains("already exists"));
@@ -152,12 +189,53 @@ mod tests {
     fn test_add_resource_uninitialized_fails() {
         let temp_dir = TempDir::new().unwrap();
 
-        let result = run_add(temp_dir.path(), "budget", None, None, None, None);
+        let result = run_add(temp_dir.path(), "budget", None, None, None, None, None);
 
         assert!(result.is_err());
         assert!(result.unwrap_err().to_string().contains("not initialized"));
     }
 
+    #[test]
+    fn test_add_budget_resource() {
+        let temp_dir = setup_workgraph();
+
+        let budget = parse_budget("usd", Some("daily"), Some("ci".to_string()), false).unwrap();
+        run_add(
+            temp_dir.path(),
+            "ci-daily",
+            None,
+            None,
+            Some(10.0),
+            Some("usd"),
+            Some(budget),
+        )
+        .unwrap();
+
+        let graph = load_graph(graph_path(temp_dir.path())).unwrap();
+        let budget = graph
+            .get_resource("ci-daily")
+            .unwrap()
+            .budget
+            .clone()
+            .unwrap();
+        assert_eq!(budget.meter, Meter::Usd);
+        assert_eq!(budget.window, Some(BudgetWindow::Daily));
+        assert_eq!(budget.tag.as_deref(), Some("ci"));
+
+        assert!(parse_budget("dollars", None, None, false).is_err());
+        let budget = parse_budget("tokens", None, None, true).unwrap();
+        let result = run_add(
+            temp_dir.path(),
+            "no-cap",
+            None,
+            None,
+            None,
+            None,
+            Some(budget),
+        );
+        assert!(result.unwrap_err().to_string().contains("--available"));
+    }
+
     #[test]
     fn test_list_resources_empty() {
         let temp_dir = setup_workgraph();
@@ -178,6 +256,7 @@ mod tests {
             Some("money"),
             Some(50000.0),
             Some("usd"),
+            None,
         )
         .unwrap();
 
@@ -188,6 +267,7 @@ mod tests {
             Some("compute"),
             Some(100.0),
             Some("gpu-hours"),
+            None,
         )
         .unwrap();
 
@@ -206,6 +286,7 @@ mod tests {
             Some("money"),
             Some(1000.0),
             Some("usd"),
+            None,
         )
         .unwrap();
 
@@ -1,6 +1,7 @@
 use anyhow::Result;
 use serde::Serialize;
 use std::path::Path;
+use workgraph::budget::{self, BudgetUsage};
 use workgraph::graph::{Resource, Status, WorkGraph};
 
 /// Resource utilization data
@@ -29,16 +30,20 @@ pub struct ResourceUtilization {
 pub struct ResourcesOutput {
     pub resources: Vec<ResourceUtilization>,
     pub alerts: Vec<ResourceUtilization>,
+    /// Actual spend against budgets, from the usage ledger
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub budgets: Vec<BudgetUsage>,
 }
 
 /// Calculate resource utilization from the graph
 pub fn calculate_utilization(graph: &WorkGraph) -> Vec<ResourceUtilization> {
     let mut utilizations = Vec::new();
 
-    // Get all resources with available capacity defined
+    // Get all resources with available capacity defined. Budgets are
+    // measured against actual spend instead (see `budget_usage`).
     let resources: Vec<&Resource> = graph
         .resources()
-        .filter(|r| r.available.is_some())
+        .filter(|r| r.available.is_some() && r.budget.is_none())
         .collect();
 
     for resource in resources {
@@ -101,15 +106,23 @@ pub fn calculate_utilization(graph: &WorkGraph) -> Vec<ResourceUtilization> {
     utilizations
 }
 
+/// Spend against every budget in the graph, from the usage ledger.
+pub fn budget_usage(dir: &Path, graph: &WorkGraph) -> Result<Vec<BudgetUsage>> {
+    let runs = workgraph::accounting::load(dir)?;
+    Ok(budget::usage(graph, &runs, chrono::Utc::now()))
+}
+
 pub fn run(dir: &Path, json: bool) -> Result<()> {
     let (graph, _path) = super::load_workgraph(dir)?;
     let utilizations = calculate_utilization(&graph);
+    let budgets = budget_usage(dir, &graph)?;
 
-    if utilizations.is_empty() {
+    if utilizations.is_empty() && budgets.is_empty() {
         if json {
             let output = ResourcesOutput {
                 resources: vec![],
                 alerts: vec![],
+                budgets: vec![],
             };
             println!("{}", serde_json::to_string_pretty(&output)?);
         } else {
@@ -128,10 +141,16 @@ pub fn run(dir: &Path, json: bool) -> Result<()> {
         let output = ResourcesOutput {
             resources: utilizations,
             alerts,
+            budgets,
         };
         println!("{}", serde_json::to_string_pretty(&output)?);
     } else {
-        print_human_output(&utilizations);
+        if !utilizations.is_empty() {
+            print_human_output(&utilizations);
+        }
+        if !budgets.is_empty() {
+            print_budgets(&budgets);
+        }
     }
 
     Ok(())
@@ -192,6 +211,55 @@ fn print_human_output(utilizations: &[ResourceUtilization]) {
     }
 }
 
+fn print_budgets(budgets: &[BudgetUsage]) {
+    println!("Budgets:");
+    println!();
+
+    for usage in budgets {
+        let display_name = usage.name.as_deref().unwrap_or(&usage.id);
+        let exhausted = usage.is_exhausted() || !usage.exhausted_tasks().is_empty();
+        println!(
+            "  {}{} ({} cap, {})",
+            if exhausted { "EXHAUSTED: " } else { "" },
+            display_name,
+            usage.format(usage.available),
+            usage.scope()
+        );
+        if usage.budget.per_task {
+            let (task, spent) = usage
+                .by_task
+                .iter()
+                .max_by(|a, b| a.1.total_cmp(b.1))
+                .map(|(t, s)| (t.as_str(), *s))
+                .unwrap_or(("-", 0.0));
+            println!(
+                "    Highest task: {} {} ({:.0}%)",
+                task,
+                usage.format(spent),
+                usage.percent()
+            );
+            let paused = usage.exhausted_tasks();
+            if !paused.is_empty() {
+                println!("    Paused tasks: {}", paused.join(", "));
+            }
+        } else {
+            println!("    Spent: {}", usage.format(usage.spent));
+            if usage.reserved > 0.0 {
+                println!(
+                    "    Reserved (running tasks): {}",
+                    usage.format(usage.reserved)
+                );
+            }
+            println!(
+                "    Remaining: {} ({:.0}% used)",
+                usage.format(usage.available - usage.spent - usage.reserved),
+                usage.percent()
+            );
+        }
+        println!();
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -212,6 +280,7 @@ mod tests {
             resource_type: Some("money".to_string()),
             available: Some(available),
             unit: Some(unit.to_string()),
+            budget: None,
         }
     }
 
@@ -231,6 +300,7 @@ mod tests {
             resource_type: Some("money".to_string()),
             available: None, // No available field
             unit: Some("usd".to_string()),
+            budget: None,
         };
         graph.add_node(Node::Resource(resource));
 
@@ -453,6 +523,7 @@ mod tests {
         let output = ResourcesOutput {
             resources: vec![util.clone()],
             alerts: vec![],
+            budgets: vec![],
         };
 
         let json = serde_json::to_string_pretty(&output).unwrap();
@@ -482,6 +553,7 @@ mod tests {
         let output = ResourcesOutput {
             resources: vec![util.clone()],
             alerts: vec![util],
+            budgets: vec![],
         };
 
         let json = serde_json::to_string_pretty(&output).unwrap();
@@ -45,6 +45,11 @@ pub fn run(dir: &Path, id: &str) -> Result<()> {
     task.assigned = None;
     // Clear converged tag so the loop can fire again if needed
     task.tags.retain(|t| t != "converged");
+    // A retried gate asks its approvers again from scratch
+    if let Some(gate) = task.gate.as_mut() {
+        gate.votes.clear();
+        gate.requested_at = None;
+    }
 
     task.log.push(LogEntry {
         timestamp: Utc::now().to_rfc3339(),
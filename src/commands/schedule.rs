@@ -0,0 +1,158 @@
+use anyhow::Result;
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use std::path::Path;
+use workgraph::graph::{CatchUp, Task, WorkGraph};
+use workgraph::schedule;
+
+#[derive(Debug, Serialize)]
+struct ScheduleInfo {
+    id: String,
+    title: String,
+    cron: String,
+    timezone: String,
+    catch_up: CatchUp,
+    /// "instance" (copies the task) or "cycle" (re-opens its cycle)
+    mode: &'static str,
+    active: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    last_window: Option<String>,
+    next: Vec<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    error: Option<String>,
+}
+
+/// List scheduled tasks with their next fire times.
+pub fn list(dir: &Path, count: usize, json: bool) -> Result<()> {
+    let (graph, _path) = super::load_workgraph(dir)?;
+    let infos = gather(&graph, Utc::now(), count);
+
+    if json {
+        println!("{}", serde_json::to_string_pretty(&infos)?);
+        return Ok(());
+    }
+    if infos.is_empty() {
+        println!("No scheduled tasks. Add one with `wg add --schedule '<cron>'`.");
+        return Ok(());
+    }
+    for info in &infos {
+        let state = if info.active { "" } else { " (paused)" };
+        println!("{} - {}{}", info.id, info.title, state);
+        println!(
+            "  {} ({}, catch-up: {}, {})",
+            info.cron,
+            info.timezone,
+            info.catch_up,
+            if info.mode == "cycle" {
+                "re-opens cycle"
+            } else {
+                "creates a task per run"
+            }
+        );
+        if let Some(ref error) = info.error {
+            println!("  Invalid: {}", error);
+            continue;
+        }
+        if let Some(ref last) = info.last_window {
+            println!("  Last window: {}", last);
+        }
+        if info.next.is_empty() {
+            println!("  Next: never");
+        } else {
+            println!("  Next: {}", info.next.join(", "));
+        }
+    }
+    Ok(())
+}
+
+fn gather(graph: &WorkGraph, now: DateTime<Utc>, count: usize) -> Vec<ScheduleInfo> {
+    let mut tasks: Vec<&Task> = graph.tasks().filter(|t| t.schedule.is_some()).collect();
+    tasks.sort_by(|a, b| a.id.cmp(&b.id));
+    tasks
+        .into_iter()
+        .filter_map(|task| {
+            let sched = task.schedule.as_ref()?;
+            let (next, error) = match schedule::upcoming(sched, now, count) {
+                Ok(times) => (
+                    times
+                        .into_iter()
+                        .map(|t| schedule::format_time(sched, t))
+                        .collect(),
+                    None,
+                ),
+                Err(e) => (vec![], Some(e)),
+            };
+            let last_window = sched
+                .last_window
+                .as_deref()
+                .and_then(|s| s.parse::<DateTime<Utc>>().ok())
+                .map(|t| schedule::format_time(sched, t));
+            Some(ScheduleInfo {
+                id: task.id.clone(),
+                title: task.title.clone(),
+                cron: sched.cron.clone(),
+                timezone: sched.timezone.clone().unwrap_or_else(|| "UTC".to_string()),
+                catch_up: sched.catch_up,
+                mode: if task.cycle_config.is_some() {
+                    "cycle"
+                } else {
+                    "instance"
+                },
+                active: schedule::is_active(task),
+                last_window,
+                next,
+                error,
+            })
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use workgraph::graph::{Node, Schedule};
+
+    #[test]
+    fn test_gather_lists_next_fire_times() {
+        let mut graph = WorkGraph::new();
+        graph.add_node(Node::Task(Task {
+            id: "standup".to_string(),
+            title: "Standup notes".to_string(),
+            schedule: Some(Schedule {
+                cron: "0 9 * * 1-5".to_string(),
+                timezone: Some("Europe/Berlin".to_string()),
+                catch_up: CatchUp::Skip,
+                last_window: None,
+            }),
+            ..Task::default()
+        }));
+        graph.add_node(Node::Task(Task {
+            id: "broken".to_string(),
+            paused: true,
+            schedule: Some(Schedule {
+                cron: "0 25 * * *".to_string(),
+                timezone: None,
+                catch_up: CatchUp::Once,
+                last_window: None,
+            }),
+            ..Task::default()
+        }));
+        graph.add_node(Node::Task(Task {
+            id: "plain".to_string(),
+            ..Task::default()
+        }));
+
+        // Friday 2026-03-06, after 09:00 in Berlin
+        let now: DateTime<Utc> = "2026-03-06T10:00:00Z".parse().unwrap();
+        let infos = gather(&graph, now, 2);
+        assert_eq!(infos.len(), 2);
+        assert_eq!(infos[0].id, "broken");
+        assert!(!infos[0].active);
+        assert!(infos[0].error.as_deref().unwrap().contains("out of range"));
+        assert_eq!(infos[1].mode, "instance");
+        assert_eq!(
+            infos[1].next,
+            vec!["2026-03-09 09:00 CET", "2026-03-10 09:00 CET"]
+        );
+    }
+}
@@ -31,8 +31,9 @@ use std::os::unix::net::{UnixListener, UnixStream};
 use chrono::Utc;
 
 use workgraph::agency;
-use workgraph::config::Config;
-use workgraph::graph::{LogEntry, Node, Status, Task, evaluate_cycle_iteration};
+use workgraph::budget::{self, BudgetUsage};
+use workgraph::config::{Config, CoordinatorConfig};
+use workgraph::graph::{LogEntry, Node, Status, Task, evaluate_cycle_iteration_in};
 use workgraph::parser::{load_graph, save_graph};
 use workgraph::query::ready_tasks_with_peers_cycle_aware;
 use workgraph::service::registry::{AgentEntry, AgentRegistry, AgentStatus};
@@ -340,6 +341,17 @@ fn cleanup_and_count_alive(
     graph_path: &Path,
     max_agents: usize,
 ) -> Result<Result<usize, TickResult>> {
+    // Kill agents that ran past their timeout
+    let grace = Config::load_or_default(dir).coordinator.timeout_grace;
+    let timed_out = enforce_timeouts(dir, graph_path, grace)?;
+    if !timed_out.is_empty() {
+        eprintln!(
+            "[coordinator] Timed out {} agent(s): {:?}",
+            timed_out.len(),
+            timed_out
+        );
+    }
+
     // Clean up dead agents: process exited
     let finished_agents = cleanup_dead_agents(dir, graph_path)?;
     if !finished_agents.is_empty() {
@@ -348,14 +360,16 @@ fn cleanup_and_count_alive(
             finished_agents.len(),
             finished_agents
         );
+        record_usage(dir, graph_path);
     }
 
-    // Now count truly alive agents (process still running)
+    // Now count truly alive agents (process still running), including timed
+    // out agents that have not exited yet
     let registry = AgentRegistry::load(dir)?;
     let alive_count = registry
         .agents
         .values()
-        .filter(|a| a.is_alive() && is_process_alive(a.pid))
+        .filter(|a| (a.is_alive() || a.status == AgentStatus::TimedOut) && is_process_alive(a.pid))
         .count();
 
     if alive_count >= max_agents {
@@ -373,6 +387,160 @@ fn cleanup_and_count_alive(
     Ok(Ok(alive_count))
 }
 
+/// Record the token usage of agent runs that have ended.
+fn record_usage(dir: &Path, graph_path: &Path) {
+    let result = load_graph(graph_path)
+        .context("Failed to load graph")
+        .and_then(|graph| workgraph::accounting::sync(dir, &graph));
+    match result {
+        Ok(records) if !records.is_empty() => {
+            let cost: f64 = records.iter().filter_map(|r| r.cost_usd).sum();
+            eprintln!(
+                "[coordinator] Recorded usage of {} run(s): ${:.2}",
+                records
+                    .iter()
+                    .map(|r| &r.run)
+                    .collect::<std::collections::HashSet<_>>()
+                    .len(),
+                cost
+            );
+        }
+        Ok(_) => {}
+        Err(e) => eprintln!("[coordinator] Warning: usage accounting failed: {}", e),
+    }
+}
+
+/// Work out the spend against every budget. Budgets that became exhausted
+/// (or recovered, e.g. when their window rolled over) since the last tick are
+/// logged, recorded in provenance and announced on Matrix.
+fn check_budgets(
+    dir: &Path,
+    graph: &workgraph::graph::WorkGraph,
+    config: &Config,
+) -> Vec<BudgetUsage> {
+    let runs = workgraph::accounting::load(dir).unwrap_or_else(|e| {
+        eprintln!("[coordinator] Warning: failed to read usage ledger: {}", e);
+        Vec::new()
+    });
+    let usages = budget::usage(graph, &runs, Utc::now());
+
+    let previous = budget::load_exhausted(dir);
+    let current = budget::exhausted_keys(&usages);
+    if previous == current {
+        return usages;
+    }
+
+    let threshold = config.log.rotation_threshold;
+    let mut messages = Vec::new();
+    for key in current.difference(&previous) {
+        let (id, task_id) = match key.split_once('/') {
+            Some((id, task)) => (id, Some(task)),
+            None => (key.as_str(), None),
+        };
+        let Some(usage) = usages.iter().find(|u| u.id == id) else {
+            continue;
+        };
+        let used = task_id.map_or(usage.spent, |t| usage.used_for(t));
+        let held_back = graph
+            .tasks()
+            .filter(|t| matches!(t.status, Status::Open | Status::Blocked))
+            .filter(|t| task_id.is_none_or(|id| t.id == id) && !usage.admits(t))
+            .count();
+        let message = format!(
+            "Budget {} exhausted{}: {} of {} ({}); dispatch paused for {} task(s)",
+            usage.id,
+            task_id.map(|t| format!(" for {}", t)).unwrap_or_default(),
+            usage.format(used),
+            usage.format(usage.available),
+            usage.scope(),
+            held_back
+        );
+        eprintln!("[coordinator] {}", message);
+        let _ = workgraph::provenance::record(
+            dir,
+            "budget_exhausted",
+            task_id,
+            Some("coordinator"),
+            serde_json::json!({
+                "budget": usage.id,
+                "meter": usage.budget.meter,
+                "spent": used,
+                "available": usage.available,
+                "since": usage.since,
+                "held_back": held_back,
+            }),
+            threshold,
+        );
+        messages.push(message);
+    }
+    for key in previous.difference(&current) {
+        let (id, task_id) = match key.split_once('/') {
+            Some((id, task)) => (id, Some(task)),
+            None => (key.as_str(), None),
+        };
+        eprintln!("[coordinator] Budget {} available again", key);
+        let _ = workgraph::provenance::record(
+            dir,
+            "budget_restored",
+            task_id,
+            Some("coordinator"),
+            serde_json::json!({ "budget": id }),
+            threshold,
+        );
+    }
+    if let Err(e) = budget::save_exhausted(dir, &current) {
+        eprintln!("[coordinator] Warning: {}", e);
+    }
+    if !messages.is_empty() {
+        notify_matrix(dir, &messages.join("\n"));
+    }
+    usages
+}
+
+/// Post to the default Matrix room if Matrix is configured. Failures are
+/// logged and otherwise ignored.
+#[cfg(feature = "matrix-lite")]
+fn notify_matrix(dir: &Path, message: &str) {
+    if !workgraph::MatrixConfig::load().is_ok_and(|c| c.is_complete()) {
+        return;
+    }
+    let result = tokio::runtime::Builder::new_current_thread()
+        .enable_all()
+        .build()
+        .context("Failed to create runtime")
+        .and_then(|rt| rt.block_on(workgraph::matrix_lite::send_notification(dir, message)));
+    if let Err(e) = result {
+        eprintln!("[coordinator] Warning: Matrix notification failed: {}", e);
+    }
+}
+
+#[cfg(not(feature = "matrix-lite"))]
+fn notify_matrix(_dir: &Path, _message: &str) {}
+
+/// Post about `task` into its Matrix thread in the default room if Matrix
+/// is configured. Failures are logged and otherwise ignored.
+#[cfg(feature = "matrix-lite")]
+fn notify_matrix_task(dir: &Path, task: &workgraph::graph::Task, message: &str) {
+    if !workgraph::MatrixConfig::load().is_ok_and(|c| c.is_complete()) {
+        return;
+    }
+    let result = tokio::runtime::Builder::new_current_thread()
+        .enable_all()
+        .build()
+        .context("Failed to create runtime")
+        .and_then(|rt| {
+            rt.block_on(workgraph::matrix_lite::send_task_notification(
+                dir, None, task, message, None,
+            ))
+        });
+    if let Err(e) = result {
+        eprintln!("[coordinator] Warning: Matrix notification failed: {}", e);
+    }
+}
+
+#[cfg(not(feature = "matrix-lite"))]
+fn notify_matrix_task(_dir: &Path, _task: &workgraph::graph::Task, _message: &str) {}
+
 /// Check whether any tasks are ready. Returns `None` with an early `TickResult`
 /// if no ready tasks exist.
 fn check_ready_or_return(
@@ -420,6 +588,7 @@ fn build_auto_assign_tasks(graph: &mut workgraph::graph::WorkGraph, config: &Con
         let ready = ready_tasks_with_peers_cycle_aware(graph, dir, &cycle_analysis);
         ready
             .iter()
+            .filter(|t| t.gate.is_none())
             .map(|t| {
                 (
                     t.id.clone(),
@@ -552,35 +721,13 @@ fn build_auto_assign_tasks(graph: &mut workgraph::graph::WorkGraph, config: &Con
             id: assign_task_id.clone(),
             title: format!("Assign agent for: {}", task_title),
             description: Some(desc),
-            status: Status::Open,
-            assigned: None,
-            estimate: None,
             before: vec![task_id.clone()],
-            after: vec![],
-            requires: vec![],
             tags: vec!["assignment".to_string(), "agency".to_string()],
-            skills: vec![],
-            inputs: vec![],
-            deliverables: vec![],
-            artifacts: vec![],
-            exec: None,
-            not_before: None,
             created_at: Some(Utc::now().to_rfc3339()),
-            started_at: None,
-            completed_at: None,
-            log: vec![],
-            retry_count: 0,
-            max_retries: None,
-            failure_reason: None,
             model: config.agency.assigner_model.clone(),
-            verify: None,
             agent: config.agency.assigner_agent.clone(),
-
-            loop_iteration: 0,
-            ready_after: None,
-            paused: false,
             visibility: "internal".to_string(),
-            cycle_config: None,
+            ..Task::default()
         };
 
         graph.add_node(Node::Task(assign_task));
@@ -650,6 +797,10 @@ fn build_auto_evaluate_tasks(
             {
                 return false;
             }
+            // Gates are decided by people; there is no work to evaluate
+            if t.gate.is_some() {
+                return false;
+            }
             // Skip tasks assigned to human agents
             if let Some(ref agent_id) = t.agent
                 && human_agent_ids.contains(agent_id.as_str())
@@ -683,35 +834,14 @@ fn build_auto_evaluate_tasks(
             id: eval_task_id.clone(),
             title: format!("Evaluate: {}", task_title),
             description: Some(desc),
-            status: Status::Open,
-            assigned: None,
-            estimate: None,
-            before: vec![],
             after: vec![task_id.clone()],
-            requires: vec![],
             tags: vec!["evaluation".to_string(), "agency".to_string()],
-            skills: vec![],
-            inputs: vec![],
-            deliverables: vec![],
-            artifacts: vec![],
             exec: Some(format!("wg evaluate {}", task_id)),
-            not_before: None,
             created_at: Some(Utc::now().to_rfc3339()),
-            started_at: None,
-            completed_at: None,
-            log: vec![],
-            retry_count: 0,
-            max_retries: None,
-            failure_reason: None,
             model: config.agency.evaluator_model.clone(),
-            verify: None,
             agent: config.agency.evaluator_agent.clone(),
-
-            loop_iteration: 0,
-            ready_after: None,
-            paused: false,
             visibility: "internal".to_string(),
-            cycle_config: None,
+            ..Task::default()
         };
 
         graph.add_node(Node::Task(eval_task));
@@ -899,22 +1029,29 @@ exit $EXIT_CODE"#,
     Ok((agent_id, pid))
 }
 
-/// Spawn agents on ready tasks, up to `slots_available`. Returns the number of
-/// agents successfully spawned.
+/// Spawn agents on ready tasks, in the order of the configured dispatch
+/// policy, up to `slots_available`. Returns the number of agents
+/// successfully spawned.
 fn spawn_agents_for_ready_tasks(
     dir: &Path,
     graph: &workgraph::graph::WorkGraph,
     executor: &str,
     model: Option<&str>,
     slots_available: usize,
+    budgets: &[BudgetUsage],
+    coordinator: &CoordinatorConfig,
 ) -> usize {
-    let cycle_analysis = graph.compute_cycle_analysis();
-    let final_ready = ready_tasks_with_peers_cycle_aware(graph, dir, &cycle_analysis);
     let agents_dir = dir.join("agency").join("agents");
     let mut spawned = 0;
 
-    let to_spawn = final_ready.iter().take(slots_available);
-    for task in to_spawn {
+    // Held tasks wait; each task let through has already taken its share
+    // of budgets and capacity, so later tasks in the same tick see it.
+    let dispatchable = super::dispatch_queue(graph, dir, budgets, coordinator)
+        .into_iter()
+        .filter(|(_, hold)| hold.is_none())
+        .map(|(task, _)| task)
+        .take(slots_available);
+    for task in dispatchable {
         // Skip if already claimed
         if task.assigned.is_some() {
             continue;
@@ -944,16 +1081,33 @@ fn spawn_agents_for_ready_tasks(
 
         // Resolve executor: tasks with exec commands use shell executor directly,
         // otherwise: agent.executor > config.coordinator.executor
+        let agent = task
+            .agent
+            .as_ref()
+            .and_then(|agent_hash| agency::find_agent_by_prefix(&agents_dir, agent_hash).ok());
         let effective_executor = if task.exec.is_some() {
             "shell".to_string()
         } else {
-            task.agent
+            agent
                 .as_ref()
-                .and_then(|agent_hash| agency::find_agent_by_prefix(&agents_dir, agent_hash).ok())
-                .map(|agent| agent.executor)
+                .map(|agent| agent.executor.clone())
                 .unwrap_or_else(|| executor.to_string())
         };
 
+        // Email agents are people: mail them the task instead of spawning
+        if effective_executor == "email"
+            && let Some(ref agent) = agent
+        {
+            match super::email::dispatch(dir, &task.id, agent) {
+                Ok(to) => eprintln!(
+                    "[coordinator] Emailed {} - {} to {} at {}",
+                    task.id, task.title, agent.name, to
+                ),
+                Err(e) => eprintln!("[coordinator] Failed to email {}: {}", task.id, e),
+            }
+            continue;
+        }
+
         // Pass coordinator model to spawn; spawn resolves the full hierarchy:
         // task.model > executor.model > coordinator.model > 'default'
         eprintln!(
@@ -986,6 +1140,18 @@ pub fn coordinator_tick(
     // Load config for agency settings
     let config = Config::load_or_default(dir);
 
+    // Apply replies from email agents even when no agent can be spawned
+    if config.email.maildir_path().is_some() {
+        match super::email::ingest(dir) {
+            Ok(results) => {
+                for result in results {
+                    eprintln!("[coordinator] Email: {}", result);
+                }
+            }
+            Err(e) => eprintln!("[coordinator] Failed to read email replies: {}", e),
+        }
+    }
+
     // Phase 1: Clean up dead agents and count alive ones
     let alive_count = match cleanup_and_count_alive(dir, &graph_path, max_agents)? {
         Ok(count) => count,
@@ -1001,6 +1167,33 @@ pub fn coordinator_tick(
     // NOTE: These must run BEFORE the early-return check, because they may
     // create new ready tasks (e.g. evaluate-* tasks) that weren't there before.
     let mut graph_modified = false;
+
+    // Re-open failed tasks whose failure looks transient. This comes first:
+    // until a failure is classified, propagation treats it as not final.
+    graph_modified |= retry_failed_tasks(dir, &mut graph, &config);
+
+    // Expand map tasks, complete joins and abandon tasks whose conditional
+    // edges can no longer be met. Catches status changes made outside of
+    // `wg done`/`wg fail` (IPC, triage), failures just judged permanent, and
+    // edges added later.
+    let propagation = super::propagate(&mut graph, dir);
+    for line in propagation.lines() {
+        eprintln!("[coordinator] {}", line);
+    }
+    graph_modified |= !propagation.is_empty();
+
+    // Fire recurring schedules. Under the `skip` catch-up policy, a window
+    // more than two poll intervals old counts as missed.
+    let grace = chrono::Duration::seconds(2 * config.coordinator.poll_interval as i64);
+    let firings = workgraph::schedule::fire_schedules(&mut graph, Utc::now(), grace);
+    for firing in &firings {
+        eprintln!("[coordinator] {}", firing);
+    }
+    graph_modified |= !firings.is_empty();
+
+    // Ask approvers to decide on gates that just became ready
+    graph_modified |= request_approvals(dir, &mut graph, &config);
+
     if config.agency.auto_assign {
         graph_modified |= build_auto_assign_tasks(&mut graph, &config, dir);
     }
@@ -1028,7 +1221,16 @@ pub fn coordinator_tick(
     let final_ready = ready_tasks_with_peers_cycle_aware(&graph, dir, &cycle_analysis);
     let ready_count = final_ready.len();
     drop(final_ready);
-    let spawned = spawn_agents_for_ready_tasks(dir, &graph, executor, model, slots_available);
+    let budgets = check_budgets(dir, &graph, &config);
+    let spawned = spawn_agents_for_ready_tasks(
+        dir,
+        &graph,
+        executor,
+        model,
+        slots_available,
+        &budgets,
+        &config.coordinator,
+    );
 
     Ok(TickResult {
         agents_alive: alive_count + spawned,
@@ -1037,6 +1239,280 @@ pub fn coordinator_tick(
     })
 }
 
+/// Fail the tasks of agents that ran past their timeout and kill them.
+///
+/// The task is failed with reason "timeout" before the agent's process group
+/// gets SIGTERM, so the wrapper script leaves the task alone when the agent
+/// exits. Agents still running `grace_secs` after their deadline get SIGKILL.
+/// Returns the IDs of agents that timed out in this call.
+fn enforce_timeouts(dir: &Path, graph_path: &Path, grace_secs: u64) -> Result<Vec<String>> {
+    let mut locked_registry = AgentRegistry::load_locked(dir)?;
+
+    let mut timed_out = Vec::new();
+    for agent in locked_registry.agents.values_mut() {
+        let (Some(limit), Some(uptime)) = (agent.timeout_secs, agent.uptime_secs()) else {
+            continue;
+        };
+        if uptime < limit as i64 {
+            continue;
+        }
+        if agent.is_alive() && is_process_alive(agent.pid) {
+            agent.status = AgentStatus::TimedOut;
+            timed_out.push((agent.id.clone(), agent.task_id.clone(), agent.pid, limit));
+        } else if agent.status == AgentStatus::TimedOut
+            && uptime >= limit.saturating_add(grace_secs) as i64
+            && is_agent_group_alive(agent.pid)
+        {
+            eprintln!(
+                "[coordinator] Agent '{}' ignored SIGTERM for {}s, sending SIGKILL",
+                agent.id, grace_secs
+            );
+            signal_agent_group(agent.pid, true);
+        }
+    }
+
+    if timed_out.is_empty() {
+        return Ok(vec![]);
+    }
+    locked_registry.save_ref()?;
+    drop(locked_registry);
+
+    let mut graph = load_graph(graph_path).context("Failed to load graph")?;
+    let mut failed = Vec::new();
+    for (agent_id, task_id, _pid, limit) in &timed_out {
+        let Some(task) = graph.get_task_mut(task_id) else {
+            continue;
+        };
+        if task.status != Status::InProgress || task.assigned.as_deref() != Some(agent_id) {
+            continue;
+        }
+        task.status = Status::Failed;
+        task.retry_count += 1;
+        task.failure_reason = Some("timeout".to_string());
+        task.log.push(LogEntry {
+            timestamp: Utc::now().to_rfc3339(),
+            actor: Some("coordinator".to_string()),
+            message: format!(
+                "Agent '{}' exceeded its timeout of {}, killed",
+                agent_id,
+                workgraph::format_duration(*limit as i64, false)
+            ),
+        });
+        failed.push((agent_id, task_id, *limit));
+    }
+    if !failed.is_empty() {
+        save_graph(&graph, graph_path).context("Failed to save graph")?;
+    }
+
+    for (_agent_id, _task_id, pid, _limit) in &timed_out {
+        signal_agent_group(*pid, false);
+    }
+
+    let threshold = Config::load_or_default(dir).log.rotation_threshold;
+    for (agent_id, task_id, limit) in failed {
+        let _ = workgraph::provenance::record(
+            dir,
+            "fail",
+            Some(task_id),
+            Some("coordinator"),
+            serde_json::json!({ "reason": "timeout", "agent_id": agent_id, "timeout_secs": limit }),
+            threshold,
+        );
+    }
+
+    Ok(timed_out.into_iter().map(|(id, _, _, _)| id).collect())
+}
+
+/// Send SIGTERM (or SIGKILL if `force`) to an agent's process group. Agents
+/// are spawned as session leaders, so this reaches the executor as well as
+/// its wrapper script.
+#[cfg(unix)]
+fn signal_agent_group(pid: u32, force: bool) {
+    let signal = if force { libc::SIGKILL } else { libc::SIGTERM };
+    // SAFETY: signalling a process group we spawned; errors (e.g. ESRCH) are ignored
+    unsafe {
+        libc::kill(-(pid as i32), signal);
+    }
+}
+
+#[cfg(not(unix))]
+fn signal_agent_group(_pid: u32, _force: bool) {}
+
+/// Whether any process in an agent's process group is still running
+#[cfg(unix)]
+fn is_agent_group_alive(pid: u32) -> bool {
+    unsafe { libc::kill(-(pid as i32), 0) == 0 }
+}
+
+#[cfg(not(unix))]
+fn is_agent_group_alive(_pid: u32) -> bool {
+    false
+}
+
+/// Classify new failures of tasks with retries left and re-open the
+/// transient ones after a backoff. Epics are skipped: they fail because a
+/// subtask did. Returns true if any task was changed.
+fn retry_failed_tasks(
+    dir: &Path,
+    graph: &mut workgraph::graph::WorkGraph,
+    config: &Config,
+) -> bool {
+    use workgraph::retry::{self, Classification};
+
+    if !config.retry.auto {
+        return false;
+    }
+    let mut ids: Vec<String> = retry::pending(graph, &config.retry).into_iter().collect();
+    if ids.is_empty() {
+        return false;
+    }
+    ids.sort();
+
+    let threshold = config.log.rotation_threshold;
+    for id in ids {
+        let Some(task) = graph.get_task(&id) else {
+            continue;
+        };
+        let classification = match retry::classify(&config.retry, task.failure_reason.as_deref()) {
+            Ok(Some(c)) => c,
+            Ok(None) if config.retry.triage => triage_failure(dir, config, task)
+                .unwrap_or_else(|| Classification::new(config.retry.default, "default")),
+            Ok(None) => Classification::new(config.retry.default, "default"),
+            Err(e) => {
+                eprintln!("[coordinator] Cannot classify failure of '{}': {}", id, e);
+                Classification::new(retry::FailureClass::Permanent, e)
+            }
+        };
+        let Some(task) = graph.get_task_mut(&id) else {
+            continue;
+        };
+        let prev_failure_reason = task.failure_reason.clone();
+        let record = retry::apply(
+            task,
+            &classification,
+            &config.retry,
+            Utc::now(),
+            retry::jitter_sample(&id),
+        );
+        match record.next_attempt {
+            Some(ref at) => {
+                eprintln!(
+                    "[coordinator] Retrying '{}' at {} ({} failure, {})",
+                    id, at, classification.class, classification.rule
+                );
+                let _ = workgraph::provenance::record(
+                    dir,
+                    "retry",
+                    Some(&id),
+                    Some("coordinator"),
+                    serde_json::json!({
+                        "attempt": record.attempt + 1,
+                        "prev_failure_reason": prev_failure_reason,
+                        "automatic": true,
+                        "rule": record.rule,
+                        "ready_after": at,
+                    }),
+                    threshold,
+                );
+            }
+            None => eprintln!(
+                "[coordinator] Not retrying '{}': {} failure ({})",
+                id, classification.class, classification.rule
+            ),
+        }
+    }
+    true
+}
+
+/// Notify the approvers of ready gates that have not been asked yet, over
+/// Matrix if it is configured. The request is always written to the service
+/// log, where `wg gates` users without Matrix can see it.
+///
+/// Returns `true` if the graph was modified.
+fn request_approvals(dir: &Path, graph: &mut workgraph::graph::WorkGraph, config: &Config) -> bool {
+    let mut ids: Vec<String> = {
+        let cycle_analysis = graph.compute_cycle_analysis();
+        ready_tasks_with_peers_cycle_aware(graph, dir, &cycle_analysis)
+            .into_iter()
+            .filter(|t| t.gate.as_ref().is_some_and(|g| g.requested_at.is_none()))
+            .map(|t| t.id.clone())
+            .collect()
+    };
+    if ids.is_empty() {
+        return false;
+    }
+    ids.sort();
+
+    let now = Utc::now().to_rfc3339();
+    for id in ids {
+        let Some(task) = graph.get_task_mut(&id) else {
+            continue;
+        };
+        let message = workgraph::gate::request_message(task);
+        eprintln!("[coordinator] {}", message);
+        notify_matrix_task(dir, task, &message);
+        let approvers = match task.gate.as_mut() {
+            Some(gate) => {
+                gate.requested_at = Some(now.clone());
+                gate.approvers.clone()
+            }
+            None => continue,
+        };
+        task.log.push(LogEntry {
+            timestamp: now.clone(),
+            actor: Some("coordinator".to_string()),
+            message: "Approval requested".to_string(),
+        });
+        let _ = workgraph::provenance::record(
+            dir,
+            "gate_requested",
+            Some(&id),
+            Some("coordinator"),
+            serde_json::json!({ "approvers": approvers }),
+            config.log.rotation_threshold,
+        );
+    }
+    true
+}
+
+/// Ask the triage model whether a failed task's last run is worth retrying.
+/// "restart" and "continue" verdicts count as transient, "done" as permanent.
+fn triage_failure(
+    dir: &Path,
+    config: &Config,
+    task: &Task,
+) -> Option<workgraph::retry::Classification> {
+    use workgraph::retry::{Classification, FailureClass};
+
+    let registry = AgentRegistry::load(dir).ok()?;
+    let output_file = registry
+        .agents
+        .values()
+        .filter(|a| a.task_id == task.id)
+        .max_by(|a, b| a.started_at.cmp(&b.started_at))?
+        .output_file
+        .clone();
+    match run_triage(config, task, &output_file) {
+        Ok(verdict) => {
+            let class = match verdict.verdict.as_str() {
+                "restart" | "continue" => FailureClass::Transient,
+                _ => FailureClass::Permanent,
+            };
+            Some(Classification::new(
+                class,
+                format!("triage verdict '{}': {}", verdict.verdict, verdict.reason),
+            ))
+        }
+        Err(e) => {
+            eprintln!(
+                "[coordinator] Triage failed for '{}': {}, using default class",
+                task.id, e
+            );
+            None
+        }
+    }
+}
+
 /// Reason an agent was detected as dead
 enum DeadReason {
     /// Process is no longer running
@@ -1165,16 +1641,30 @@ fn cleanup_dead_agents(dir: &Path, graph_path: &Path) -> Result<Vec<String>> {
     }
 
     // Evaluate structural cycle iterations for tasks triaged as done
+    let mut cycle_decisions = Vec::new();
     if !tasks_completed_by_triage.is_empty() {
         let cycle_analysis = graph.compute_cycle_analysis();
         for task_id in &tasks_completed_by_triage {
-            evaluate_cycle_iteration(&mut graph, task_id, &cycle_analysis);
+            cycle_decisions.extend(evaluate_cycle_iteration_in(
+                &mut graph,
+                task_id,
+                &cycle_analysis,
+                Some(dir),
+            ));
         }
     }
 
     if tasks_modified {
         save_graph(&graph, graph_path).context("Failed to save graph")?;
     }
+    if !cycle_decisions.is_empty() {
+        let threshold = workgraph::config::Config::load_or_default(dir)
+            .log
+            .rotation_threshold;
+        for decision in &cycle_decisions {
+            super::record_cycle_decision(dir, decision, threshold);
+        }
+    }
 
     // Capture output for completed/failed tasks whose agents just died.
     // done.rs already captures output, but fail.rs does not,
@@ -1689,11 +2179,57 @@ pub enum IpcRequest {
         /// Who requested this (for provenance)
         #[serde(default)]
         origin: Option<String>,
+        /// Conditional write: `0` creates the task only if `id` is unused;
+        /// any other value updates the existing task only if its `revision`
+        /// still matches.
+        #[serde(default)]
+        expected_revision: Option<u64>,
     },
-    /// Query a task's status (cross-repo query)
+    /// Query a task's status and revision (cross-repo query)
     QueryTask {
         task_id: String,
     },
+    /// Edit an existing task. Missing fields and empty lists leave the task
+    /// unchanged; `expected_revision`, if given, makes the write conditional.
+    UpdateTask {
+        task_id: String,
+        #[serde(default)]
+        expected_revision: Option<u64>,
+        #[serde(default)]
+        title: Option<String>,
+        #[serde(default)]
+        description: Option<String>,
+        #[serde(default)]
+        after: Vec<String>,
+        #[serde(default)]
+        tags: Vec<String>,
+        #[serde(default)]
+        skills: Vec<String>,
+        #[serde(default)]
+        deliverables: Vec<String>,
+        #[serde(default)]
+        model: Option<String>,
+        #[serde(default)]
+        verify: Option<String>,
+        /// Who requested this (for provenance)
+        #[serde(default)]
+        origin: Option<String>,
+    },
+}
+
+/// What kind of failure an error response reports, so a transport such as
+/// the HTTP API can map it without reading the message.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum IpcErrorKind {
+    /// The request is malformed or not possible in the current state
+    Invalid,
+    /// A task or agent it names doesn't exist
+    NotFound,
+    /// It conflicts with the graph: an ID already taken, a stale revision
+    Conflict,
+    /// The daemon failed to carry it out
+    Internal,
 }
 
 /// IPC Response types
@@ -1702,6 +2238,8 @@ pub struct IpcResponse {
     pub ok: bool,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub error: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error_kind: Option<IpcErrorKind>,
     #[serde(flatten)]
     pub data: Option<serde_json::Value>,
 }
@@ -1711,14 +2249,20 @@ impl IpcResponse {
         Self {
             ok: true,
             error: None,
+            error_kind: None,
             data: Some(data),
         }
     }
 
     pub fn error(msg: &str) -> Self {
+        Self::error_with(IpcErrorKind::Invalid, msg)
+    }
+
+    pub fn error_with(kind: IpcErrorKind, msg: &str) -> Self {
         Self {
             ok: false,
             error: Some(msg.to_string()),
+            error_kind: Some(kind),
             data: None,
         }
     }
@@ -1793,7 +2337,7 @@ pub fn find_orphan_daemon_pids(_dir: &Path, _exclude_pid: Option<u32>) -> Vec<u3
 pub fn run_start(
     dir: &Path,
     socket_path: Option<&str>,
-    _port: Option<u16>,
+    port: Option<u16>,
     max_agents: Option<usize>,
     executor: Option<&str>,
     interval: Option<u64>,
@@ -1929,6 +2473,11 @@ pub fn run_start(
         args.push("--model".to_string());
         args.push(m.to_string());
     }
+    let http_addr = port.map(|p| format!("127.0.0.1:{}", p));
+    if let Some(addr) = &http_addr {
+        args.push("--http".to_string());
+        args.push(addr.clone());
+    }
     // Redirect daemon stderr to the log file so early startup crashes and
     // unexpected panics that bypass the DaemonLogger are captured.
     let log_path = log_file_path(dir);
@@ -1981,6 +2530,8 @@ pub fn run_start(
         .map(std::string::ToString::to_string)
         .or_else(|| config.coordinator.model.clone());
 
+    let eff_http = http_addr.or_else(|| config.http.bind.clone());
+
     let log_path_str = log_path.to_string_lossy().to_string();
 
     // Warn if auto_assign is enabled but no agency agents are defined
@@ -2003,6 +2554,9 @@ pub fn run_start(
                 "model": eff_model,
             }
         });
+        if let Some(addr) = &eff_http {
+            output["http"] = serde_json::json!(addr);
+        }
         if warn_no_agents {
             output["warning"] = serde_json::json!(
                 "auto_assign is enabled but no agents are defined. Run 'wg agency init' or 'wg agent create' to create agents."
@@ -2013,6 +2567,9 @@ pub fn run_start(
         println!("Service started (PID {})", pid);
         println!("Socket: {}", socket_str);
         println!("Log: {}", log_path_str);
+        if let Some(addr) = &eff_http {
+            println!("HTTP API: http://{}/api", addr);
+        }
         let model_str = eff_model.as_deref().unwrap_or("default");
         println!(
             "Coordinator: max_agents={}, poll_interval={}s, executor={}, model={}",
@@ -2070,6 +2627,7 @@ struct DaemonConfig {
 
 /// Run the actual daemon loop (called by forked process)
 #[cfg(unix)]
+#[allow(clippy::too_many_arguments)]
 pub fn run_daemon(
     dir: &Path,
     socket_path: &str,
@@ -2077,8 +2635,11 @@ pub fn run_daemon(
     cli_executor: Option<&str>,
     cli_interval: Option<u64>,
     cli_model: Option<&str>,
+    cli_http: Option<&str>,
 ) -> Result<()> {
     let socket = PathBuf::from(socket_path);
+    // Graph changes made by the daemon are the coordinator's, not the user's.
+    workgraph::history::set_actor("coordinator");
 
     // --- Persistent logging setup ---
     let logger = DaemonLogger::open(dir).context("Failed to initialise daemon logger")?;
@@ -2138,6 +2699,23 @@ pub fn run_daemon(
         paused: false,
     };
 
+    // Optional HTTP API; CLI --http overrides [http] bind
+    let http = match cli_http
+        .map(std::string::ToString::to_string)
+        .or_else(|| config.http.bind.clone())
+    {
+        Some(addr) => {
+            let server =
+                super::http_api::HttpServer::bind(&dir, &addr, config.http.effective_token())
+                    .inspect_err(|e| logger.error(&format!("HTTP API: {:#}", e)))?;
+            logger.info(&format!("HTTP API listening on {}", server.local_addr()?));
+            Some(server)
+        }
+        None => None,
+    };
+
+    super::webhooks::spawn_worker(dir.clone(), logger.clone());
+
     logger.info(&format!(
         "Coordinator config: poll_interval={}s, max_agents={}, executor={}, model={}",
         daemon_cfg.poll_interval.as_secs(),
@@ -2187,9 +2765,11 @@ pub fn run_daemon(
         // zombies and is_process_alive(pid) keeps returning true.
         reap_zombies();
 
+        let mut idle = true;
+        let mut wake_coordinator = false;
         match listener.accept() {
             Ok((stream, _)) => {
-                let mut wake_coordinator = false;
+                idle = false;
                 if let Err(e) = handle_connection(
                     &dir,
                     stream,
@@ -2200,21 +2780,40 @@ pub fn run_daemon(
                 ) {
                     logger.error(&format!("Error handling connection: {}", e));
                 }
-                if wake_coordinator {
-                    logger.info("GraphChanged received, scheduling immediate coordinator tick");
-                    // Force an immediate coordinator tick
-                    last_coordinator_tick = Instant::now() - daemon_cfg.poll_interval;
-                }
-            }
-            Err(ref e) if e.kind() == std::io::ErrorKind::WouldBlock => {
-                // No connection, sleep briefly
-                std::thread::sleep(Duration::from_millis(100));
             }
+            Err(ref e) if e.kind() == std::io::ErrorKind::WouldBlock => {}
             Err(e) => {
                 logger.error(&format!("Accept error: {}", e));
             }
         }
 
+        if let Some(http) = &http {
+            let served = http.poll(|request| {
+                handle_request(
+                    &dir,
+                    request,
+                    &mut running,
+                    &mut wake_coordinator,
+                    &mut daemon_cfg,
+                    &logger,
+                )
+            });
+            match served {
+                Ok(served) => idle &= !served,
+                Err(e) => logger.error(&format!("Error handling HTTP request: {}", e)),
+            }
+        }
+
+        if wake_coordinator {
+            logger.info("GraphChanged received, scheduling immediate coordinator tick");
+            // Force an immediate coordinator tick
+            last_coordinator_tick = Instant::now() - daemon_cfg.poll_interval;
+        }
+        if idle {
+            // No connection, sleep briefly
+            std::thread::sleep(Duration::from_millis(100));
+        }
+
         // Background safety-net tick: runs on poll_interval even without IPC events.
         // The fast-path is GraphChanged IPC which resets last_coordinator_tick.
         if !daemon_cfg.paused && last_coordinator_tick.elapsed() >= daemon_cfg.poll_interval {
@@ -2281,6 +2880,7 @@ pub fn run_daemon(
 }
 
 #[cfg(not(unix))]
+#[allow(clippy::too_many_arguments)]
 pub fn run_daemon(
     _dir: &Path,
     _socket_path: &str,
@@ -2288,6 +2888,7 @@ pub fn run_daemon(
     _executor: Option<&str>,
     _interval: Option<u64>,
     _model: Option<&str>,
+    _http: Option<&str>,
 ) -> Result<()> {
     anyhow::bail!("Daemon is only supported on Unix systems")
 }
@@ -2467,11 +3068,11 @@ fn handle_request(
             model,
             verify,
             origin,
+            expected_revision,
         } => {
             logger.info(&format!(
-                "IPC AddTask: title='{}', origin={:?}",
-                title,
-                origin
+                "IPC AddTask: title='{}', origin={:?}, expected_revision={:?}",
+                title, origin, expected_revision
             ));
             let resp = handle_add_task(
                 dir,
@@ -2485,6 +3086,7 @@ fn handle_request(
                 model.as_deref(),
                 verify.as_deref(),
                 origin.as_deref(),
+                expected_revision,
             );
             if resp.ok {
                 *wake_coordinator = true;
@@ -2495,6 +3097,59 @@ fn handle_request(
             logger.info(&format!("IPC QueryTask: task_id={}", task_id));
             handle_query_task(dir, &task_id)
         }
+        IpcRequest::UpdateTask {
+            task_id,
+            expected_revision,
+            title,
+            description,
+            after,
+            tags,
+            skills,
+            deliverables,
+            model,
+            verify,
+            origin,
+        } => {
+            logger.info(&format!(
+                "IPC UpdateTask: task_id={}, origin={:?}, expected_revision={:?}",
+                task_id, origin, expected_revision
+            ));
+            let graph = match workgraph::parser::load_graph(super::graph_path(dir)) {
+                Ok(g) => g,
+                Err(e) => {
+                    return IpcResponse::error_with(
+                        IpcErrorKind::Internal,
+                        &format!("Failed to load graph: {}", e),
+                    );
+                }
+            };
+            let Some(task) = graph.get_task(&task_id) else {
+                return IpcResponse::error_with(
+                    IpcErrorKind::NotFound,
+                    &format!("Task '{}' not found", task_id),
+                );
+            };
+            let title = title.unwrap_or_else(|| task.title.clone());
+            let resp = handle_update_task(
+                dir,
+                graph,
+                &task_id,
+                expected_revision,
+                &title,
+                description.as_deref(),
+                &after,
+                &tags,
+                &skills,
+                &deliverables,
+                model.as_deref(),
+                verify.as_deref(),
+                origin.as_deref(),
+            );
+            if resp.ok {
+                *wake_coordinator = true;
+            }
+            resp
+        }
     }
 }
 
@@ -2547,6 +3202,15 @@ fn handle_agents(dir: &Path) -> IpcResponse {
 
 /// Handle kill request
 fn handle_kill(dir: &Path, agent_id: &str, force: bool) -> IpcResponse {
+    if AgentRegistry::load_or_warn(dir)
+        .get_agent(agent_id)
+        .is_none()
+    {
+        return IpcResponse::error_with(
+            IpcErrorKind::NotFound,
+            &format!("Agent '{}' not found", agent_id),
+        );
+    }
     match crate::commands::kill::run(dir, agent_id, force, true) {
         Ok(()) => IpcResponse::success(serde_json::json!({
             "killed": agent_id,
@@ -2569,7 +3233,10 @@ fn handle_heartbeat(dir: &Path, agent_id: &str) -> IpcResponse {
                     "heartbeat": "recorded",
                 }))
             } else {
-                IpcResponse::error(&format!("Agent '{}' not found", agent_id))
+                IpcResponse::error_with(
+                    IpcErrorKind::NotFound,
+                    &format!("Agent '{}' not found", agent_id),
+                )
             }
         }
         Err(e) => IpcResponse::error(&e.to_string()),
@@ -2580,7 +3247,9 @@ fn handle_heartbeat(dir: &Path, agent_id: &str) -> IpcResponse {
 fn handle_status(dir: &Path) -> IpcResponse {
     let state = match ServiceState::load(dir) {
         Ok(Some(s)) => s,
-        Ok(None) => return IpcResponse::error("No service state found"),
+        Ok(None) => {
+            return IpcResponse::error_with(IpcErrorKind::Internal, "No service state found");
+        }
         Err(e) => return IpcResponse::error(&e.to_string()),
     };
 
@@ -2672,7 +3341,10 @@ fn handle_reconfigure(
             }
             Err(e) => {
                 logger.error(&format!("Failed to reload config.toml: {}", e));
-                return IpcResponse::error(&format!("Failed to reload config.toml: {}", e));
+                return IpcResponse::error_with(
+                    IpcErrorKind::Internal,
+                    &format!("Failed to reload config.toml: {}", e),
+                );
             }
         }
     }
@@ -2725,97 +3397,81 @@ fn handle_add_task(
     model: Option<&str>,
     verify: Option<&str>,
     origin: Option<&str>,
+    expected_revision: Option<u64>,
 ) -> IpcResponse {
-    use workgraph::graph::{Node, Status, Task};
+    use workgraph::graph::{Node, Task};
     use workgraph::parser::{load_graph, save_graph};
 
     let graph_path = super::graph_path(dir);
     let mut graph = match load_graph(&graph_path) {
         Ok(g) => g,
-        Err(e) => return IpcResponse::error(&format!("Failed to load graph: {}", e)),
+        Err(e) => {
+            return IpcResponse::error_with(
+                IpcErrorKind::Internal,
+                &format!("Failed to load graph: {}", e),
+            );
+        }
     };
 
+    if let Some(expected) = expected_revision
+        && expected > 0
+    {
+        let Some(id) = id else {
+            return IpcResponse::error("expected_revision requires an id");
+        };
+        return handle_update_task(
+            dir,
+            graph,
+            id,
+            Some(expected),
+            title,
+            description,
+            after,
+            tags,
+            skills,
+            deliverables,
+            model,
+            verify,
+            origin,
+        );
+    }
+
     // Generate or validate task ID
     let task_id = match id {
         Some(id) => {
+            if let Some(existing) = graph.get_task(id) {
+                return IpcResponse::error_with(
+                    IpcErrorKind::Conflict,
+                    &format!(
+                        "Task with ID '{}' already exists (revision {})",
+                        id, existing.revision
+                    ),
+                );
+            }
             if graph.get_node(id).is_some() {
-                return IpcResponse::error(&format!("Task with ID '{}' already exists", id));
+                return IpcResponse::error_with(
+                    IpcErrorKind::Conflict,
+                    &format!("Task with ID '{}' already exists", id),
+                );
             }
             id.to_string()
         }
-        None => {
-            // Reuse the same slug generation logic as add.rs
-            let slug: String = title
-                .to_lowercase()
-                .chars()
-                .map(|c| if c.is_alphanumeric() { c } else { '-' })
-                .collect::<String>()
-                .split('-')
-                .filter(|s| !s.is_empty())
-                .take(3)
-                .collect::<Vec<_>>()
-                .join("-");
-            let base_id = if slug.is_empty() {
-                "task".to_string()
-            } else {
-                slug
-            };
-            if graph.get_node(&base_id).is_none() {
-                base_id
-            } else {
-                let mut found = None;
-                for i in 2..1000 {
-                    let candidate = format!("{}-{}", base_id, i);
-                    if graph.get_node(&candidate).is_none() {
-                        found = Some(candidate);
-                        break;
-                    }
-                }
-                found.unwrap_or_else(|| {
-                    format!(
-                        "task-{}",
-                        std::time::SystemTime::now()
-                            .duration_since(std::time::UNIX_EPOCH)
-                            .map(|d| d.as_secs())
-                            .unwrap_or(0)
-                    )
-                })
-            }
-        }
+        None => workgraph::graph::generate_id(title, &graph),
     };
 
     let task = Task {
         id: task_id.clone(),
         title: title.to_string(),
         description: description.map(String::from),
-        status: Status::Open,
-        assigned: None,
-        estimate: None,
-        before: vec![],
         after: after.to_vec(),
-        requires: vec![],
         tags: tags.to_vec(),
         skills: skills.to_vec(),
-        inputs: vec![],
         deliverables: deliverables.to_vec(),
-        artifacts: vec![],
-        exec: None,
-        not_before: None,
         created_at: Some(chrono::Utc::now().to_rfc3339()),
-        started_at: None,
-        completed_at: None,
-        log: vec![],
-        retry_count: 0,
-        max_retries: None,
-        failure_reason: None,
         model: model.map(String::from),
         verify: verify.map(String::from),
-        agent: None,
-        loop_iteration: 0,
-        ready_after: None,
-        paused: false,
         visibility: "internal".to_string(),
-        cycle_config: None,
+        ..Task::default()
     };
 
     graph.add_node(Node::Task(task));
@@ -2830,7 +3486,10 @@ fn handle_add_task(
     }
 
     if let Err(e) = save_graph(&graph, &graph_path) {
-        return IpcResponse::error(&format!("Failed to save graph: {}", e));
+        return IpcResponse::error_with(
+            IpcErrorKind::Internal,
+            &format!("Failed to save graph: {}", e),
+        );
     }
 
     // Record provenance
@@ -2848,9 +3507,127 @@ fn handle_add_task(
     IpcResponse::success(serde_json::json!({
         "task_id": task_id,
         "title": title,
+        "revision": stored_revision(dir, &task_id),
     }))
 }
 
+/// Update for AddTask and UpdateTask: apply the request's fields to an
+/// existing task, only if its revision is still `expected` when given.
+#[allow(clippy::too_many_arguments)]
+fn handle_update_task(
+    dir: &Path,
+    mut graph: workgraph::graph::WorkGraph,
+    id: &str,
+    expected: Option<u64>,
+    title: &str,
+    description: Option<&str>,
+    after: &[String],
+    tags: &[String],
+    skills: &[String],
+    deliverables: &[String],
+    model: Option<&str>,
+    verify: Option<&str>,
+    origin: Option<&str>,
+) -> IpcResponse {
+    let Some(task) = graph.get_task_mut(id) else {
+        return IpcResponse::error_with(
+            IpcErrorKind::NotFound,
+            &format!("Task '{}' not found", id),
+        );
+    };
+    if let Some(expected) = expected
+        && task.revision != expected
+    {
+        return IpcResponse::error_with(
+            IpcErrorKind::Conflict,
+            &format!(
+                "Revision conflict on '{}': expected {}, found {}",
+                id, expected, task.revision
+            ),
+        );
+    }
+
+    // Empty lists and missing options leave the existing value unchanged
+    task.title = title.to_string();
+    if let Some(description) = description {
+        task.description = Some(description.to_string());
+    }
+    if !tags.is_empty() {
+        task.tags = tags.to_vec();
+    }
+    if !skills.is_empty() {
+        task.skills = skills.to_vec();
+    }
+    if !deliverables.is_empty() {
+        task.deliverables = deliverables.to_vec();
+    }
+    if let Some(model) = model {
+        task.model = Some(model.to_string());
+    }
+    if let Some(verify) = verify {
+        task.verify = Some(verify.to_string());
+    }
+    let mut new_deps = Vec::new();
+    for dep in after {
+        if !task.after.contains(dep) {
+            task.after.push(dep.clone());
+            new_deps.push(dep.clone());
+        }
+    }
+    for dep in &new_deps {
+        if let Some(blocker) = graph.get_task_mut(dep)
+            && !blocker.before.iter().any(|b| b == id)
+        {
+            blocker.before.push(id.to_string());
+        }
+    }
+
+    // A write that lands between the check above and the save would merge
+    // with ours if it changed other fields, so check again under the lock.
+    if let Some(expected) = expected {
+        graph.expect_revision(id, expected);
+    }
+    match workgraph::parser::save_graph(&graph, super::graph_path(dir)) {
+        Ok(()) => {}
+        Err(workgraph::parser::ParseError::Conflict(conflict)) => {
+            return IpcResponse::error_with(IpcErrorKind::Conflict, &conflict.to_string());
+        }
+        Err(e) => {
+            return IpcResponse::error_with(
+                IpcErrorKind::Internal,
+                &format!("Failed to save graph: {}", e),
+            );
+        }
+    }
+
+    let config = workgraph::config::Config::load_or_default(dir);
+    let _ = workgraph::provenance::record(
+        dir,
+        "edit",
+        Some(id),
+        None,
+        serde_json::json!({
+            "title": title,
+            "origin": origin.unwrap_or("unknown"),
+            "remote": true,
+            "expected_revision": expected,
+        }),
+        config.log.rotation_threshold,
+    );
+
+    IpcResponse::success(serde_json::json!({
+        "task_id": id,
+        "title": title,
+        "revision": stored_revision(dir, id),
+    }))
+}
+
+/// A task's revision as currently stored, after a save.
+fn stored_revision(dir: &Path, task_id: &str) -> Option<u64> {
+    let store = workgraph::store::open(dir).ok()?;
+    store.get_task(task_id).ok()?.map(|t| t.revision)
+}
+
 /// Handle QueryTask IPC request — return a task's status for cross-repo dependency checking.
 fn handle_query_task(dir: &Path, task_id: &str) -> IpcResponse {
     use workgraph::parser::load_graph;
@@ -2858,7 +3635,12 @@ fn handle_query_task(dir: &Path, task_id: &str) -> IpcResponse {
     let graph_path = super::graph_path(dir);
     let graph = match load_graph(&graph_path) {
         Ok(g) => g,
-        Err(e) => return IpcResponse::error(&format!("Failed to load graph: {}", e)),
+        Err(e) => {
+            return IpcResponse::error_with(
+                IpcErrorKind::Internal,
+                &format!("Failed to load graph: {}", e),
+            );
+        }
     };
 
     match graph.get_task(task_id) {
@@ -2870,8 +3652,12 @@ fn handle_query_task(dir: &Path, task_id: &str) -> IpcResponse {
             "started_at": task.started_at,
             "completed_at": task.completed_at,
             "failure_reason": task.failure_reason,
+            "revision": task.revision,
         })),
-        None => IpcResponse::error(&format!("Task '{}' not found", task_id)),
+        None => IpcResponse::error_with(
+            IpcErrorKind::NotFound,
+            &format!("Task '{}' not found", task_id),
+        ),
     }
 }
 
@@ -3486,6 +4272,84 @@ mod tests {
         assert!(resp.data.is_none());
     }
 
+    #[test]
+    fn test_ipc_add_task_conditional_update() {
+        let temp_dir = TempDir::new().unwrap();
+        let dir = temp_dir.path();
+        workgraph::parser::save_graph(
+            &workgraph::graph::WorkGraph::new(),
+            super::super::graph_path(dir),
+        )
+        .unwrap();
+        let add = |title: &str, expected: Option<u64>| {
+            handle_add_task(
+                dir,
+                title,
+                Some("remote-task"),
+                None,
+                &[],
+                &[],
+                &[],
+                &[],
+                None,
+                None,
+                Some("peer"),
+                expected,
+            )
+        };
+        let field = |resp: &IpcResponse, key: &str| resp.data.as_ref().unwrap()[key].clone();
+
+        let created = add("Created", Some(0));
+        assert!(created.ok, "{:?}", created.error);
+        assert_eq!(field(&created, "revision"), 1);
+
+        // Creating again with "must not exist" fails and reports the revision
+        let again = add("Created", Some(0));
+        assert!(!again.ok);
+        assert!(again.error.unwrap().contains("revision 1"));
+
+        // Stale revision is rejected, current one applies
+        let stale = add("Stale", Some(7));
+        assert!(!stale.ok);
+        assert!(stale.error.unwrap().contains("expected 7, found 1"));
+        let updated = add("Updated", Some(1));
+        assert!(updated.ok, "{:?}", updated.error);
+        assert_eq!(field(&updated, "revision"), 2);
+
+        let queried = handle_query_task(dir, "remote-task");
+        assert_eq!(field(&queried, "title"), "Updated");
+        assert_eq!(field(&queried, "revision"), 2);
+
+        // A write landing after the revision check still makes it fail,
+        // even one that touches other fields
+        let path = super::super::graph_path(dir);
+        let loaded = workgraph::parser::load_graph(&path).unwrap();
+        let mut other = workgraph::parser::load_graph(&path).unwrap();
+        other.get_task_mut("remote-task").unwrap().tags = vec!["raced".to_string()];
+        workgraph::parser::save_graph(&other, &path).unwrap();
+        let raced = handle_update_task(
+            dir,
+            loaded,
+            "remote-task",
+            Some(2),
+            "Raced",
+            None,
+            &[],
+            &[],
+            &[],
+            &[],
+            None,
+            None,
+            Some("peer"),
+        );
+        assert!(!raced.ok);
+        assert_eq!(raced.error_kind, Some(IpcErrorKind::Conflict));
+        assert_eq!(
+            field(&handle_query_task(dir, "remote-task"), "title"),
+            "Updated"
+        );
+    }
+
     #[test]
     fn test_is_process_running() {
         // Current process should be running
@@ -3745,33 +4609,8 @@ poll_interval = 120
             description: Some("There is a bug in foo.rs".to_string()),
             status: Status::InProgress,
             assigned: Some("agent-1".to_string()),
-            estimate: None,
-            before: vec![],
-            after: vec![],
-            requires: vec![],
-            tags: vec![],
-            skills: vec![],
-            inputs: vec![],
-            deliverables: vec![],
-            artifacts: vec![],
-            exec: None,
-            not_before: None,
-            created_at: None,
-            started_at: None,
-            completed_at: None,
-            log: vec![],
-            retry_count: 0,
-            max_retries: None,
-            failure_reason: None,
-            model: None,
-            verify: None,
-            agent: None,
-
-            loop_iteration: 0,
-            ready_after: None,
-            paused: false,
             visibility: "internal".to_string(),
-            cycle_config: None,
+            ..Task::default()
         };
         let prompt = build_triage_prompt(&task, "some log output");
         assert!(prompt.contains("test-task"));
@@ -3916,36 +4755,10 @@ poll_interval = 120
         let mut task = Task {
             id: "t1".to_string(),
             title: "Test".to_string(),
-            description: None,
             status: Status::InProgress,
             assigned: Some("agent-1".to_string()),
-            estimate: None,
-            before: vec![],
-            after: vec![],
-            requires: vec![],
-            tags: vec![],
-            skills: vec![],
-            inputs: vec![],
-            deliverables: vec![],
-            artifacts: vec![],
-            exec: None,
-            not_before: None,
-            created_at: None,
-            started_at: None,
-            completed_at: None,
-            log: vec![],
-            retry_count: 0,
-            max_retries: None,
-            failure_reason: None,
-            model: None,
-            verify: None,
-            agent: None,
-
-            loop_iteration: 0,
-            ready_after: None,
-            paused: false,
             visibility: "internal".to_string(),
-        cycle_config: None,
+            ..Task::default()
         };
         let verdict = TriageVerdict {
             verdict: "done".to_string(),
@@ -3963,36 +4776,11 @@ poll_interval = 120
         let mut task = Task {
             id: "t1".to_string(),
             title: "Test".to_string(),
-            description: None,
             status: Status::InProgress,
             assigned: Some("agent-1".to_string()),
-            estimate: None,
-            before: vec![],
-            after: vec![],
-            requires: vec![],
-            tags: vec![],
-            skills: vec![],
-            inputs: vec![],
-            deliverables: vec![],
-            artifacts: vec![],
-            exec: None,
-            not_before: None,
-            created_at: None,
-            started_at: None,
-            completed_at: None,
-            log: vec![],
-            retry_count: 0,
-            max_retries: None,
-            failure_reason: None,
-            model: None,
             verify: Some("Check tests pass".to_string()),
-            agent: None,
-
-            loop_iteration: 0,
-            ready_after: None,
-            paused: false,
             visibility: "internal".to_string(),
-        cycle_config: None,
+            ..Task::default()
         };
         let verdict = TriageVerdict {
             verdict: "done".to_string(),
@@ -4011,33 +4799,8 @@ poll_interval = 120
             description: Some("Original description".to_string()),
             status: Status::InProgress,
             assigned: Some("agent-1".to_string()),
-            estimate: None,
-            before: vec![],
-            after: vec![],
-            requires: vec![],
-            tags: vec![],
-            skills: vec![],
-            inputs: vec![],
-            deliverables: vec![],
-            artifacts: vec![],
-            exec: None,
-            not_before: None,
-            created_at: None,
-            started_at: None,
-            completed_at: None,
-            log: vec![],
-            retry_count: 0,
-            max_retries: None,
-            failure_reason: None,
-            model: None,
-            verify: None,
-            agent: None,
-
-            loop_iteration: 0,
-            ready_after: None,
-            paused: false,
             visibility: "internal".to_string(),
-        cycle_config: None,
+            ..Task::default()
         };
         let verdict = TriageVerdict {
             verdict: "continue".to_string(),
@@ -4067,36 +4830,10 @@ poll_interval = 120
         let mut task = Task {
             id: "t1".to_string(),
             title: "Test".to_string(),
-            description: None,
             status: Status::InProgress,
             assigned: Some("agent-1".to_string()),
-            estimate: None,
-            before: vec![],
-            after: vec![],
-            requires: vec![],
-            tags: vec![],
-            skills: vec![],
-            inputs: vec![],
-            deliverables: vec![],
-            artifacts: vec![],
-            exec: None,
-            not_before: None,
-            created_at: None,
-            started_at: None,
-            completed_at: None,
-            log: vec![],
-            retry_count: 0,
-            max_retries: None,
-            failure_reason: None,
-            model: None,
-            verify: None,
-            agent: None,
-
-            loop_iteration: 0,
-            ready_after: None,
-            paused: false,
             visibility: "internal".to_string(),
-        cycle_config: None,
+            ..Task::default()
         };
         let verdict = TriageVerdict {
             verdict: "restart".to_string(),
@@ -4119,33 +4856,10 @@ poll_interval = 120
             description: Some("Original".to_string()),
             status: Status::InProgress,
             assigned: Some("agent-1".to_string()),
-            estimate: None,
-            before: vec![],
-            after: vec![],
-            requires: vec![],
-            tags: vec![],
-            skills: vec![],
-            inputs: vec![],
-            deliverables: vec![],
-            artifacts: vec![],
-            exec: None,
-            not_before: None,
-            created_at: None,
-            started_at: None,
-            completed_at: None,
-            log: vec![],
             retry_count: 3,
             max_retries: Some(3),
-            failure_reason: None,
-            model: None,
-            verify: None,
-            agent: None,
-
-            loop_iteration: 0,
-            ready_after: None,
-            paused: false,
             visibility: "internal".to_string(),
-        cycle_config: None,
+            ..Task::default()
         };
         let verdict = TriageVerdict {
             verdict: "continue".to_string(),
@@ -4169,36 +4883,12 @@ poll_interval = 120
         let mut task = Task {
             id: "t1".to_string(),
             title: "Test".to_string(),
-            description: None,
             status: Status::InProgress,
             assigned: Some("agent-1".to_string()),
-            estimate: None,
-            before: vec![],
-            after: vec![],
-            requires: vec![],
-            tags: vec![],
-            skills: vec![],
-            inputs: vec![],
-            deliverables: vec![],
-            artifacts: vec![],
-            exec: None,
-            not_before: None,
-            created_at: None,
-            started_at: None,
-            completed_at: None,
-            log: vec![],
             retry_count: 2,
             max_retries: Some(2),
-            failure_reason: None,
-            model: None,
-            verify: None,
-            agent: None,
-
-            loop_iteration: 0,
-            ready_after: None,
-            paused: false,
             visibility: "internal".to_string(),
-        cycle_config: None,
+            ..Task::default()
         };
         let verdict = TriageVerdict {
             verdict: "restart".to_string(),
@@ -4372,4 +5062,155 @@ poll_interval = 120
             no_exec.tags.iter().any(|t| t == "evaluation") && no_exec.exec.is_some();
         assert!(!is_inline_eval3);
     }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_enforce_timeouts_fails_task_and_kills_agent() {
+        use std::os::unix::process::CommandExt;
+        use workgraph::graph::WorkGraph;
+
+        let temp_dir = TempDir::new().unwrap();
+        let dir = temp_dir.path();
+        let mut graph = WorkGraph::new();
+        graph.add_node(Node::Task(Task {
+            id: "slow".to_string(),
+            title: "Slow".to_string(),
+            status: Status::InProgress,
+            assigned: Some("agent-1".to_string()),
+            ..Task::default()
+        }));
+        let path = graph_path(dir);
+        save_graph(&graph, &path).unwrap();
+
+        let mut cmd = std::process::Command::new("sleep");
+        cmd.arg("60");
+        unsafe {
+            cmd.pre_exec(|| {
+                libc::setsid();
+                Ok(())
+            });
+        }
+        let mut child = cmd.spawn().unwrap();
+
+        let mut registry = AgentRegistry::new();
+        let agent_id = registry.register_agent(child.id(), "slow", "shell", "/dev/null");
+        let agent = registry.get_agent_mut(&agent_id).unwrap();
+        agent.timeout_secs = Some(60);
+        agent.started_at = (Utc::now() - chrono::Duration::seconds(61)).to_rfc3339();
+        registry.save(dir).unwrap();
+
+        let timed_out = enforce_timeouts(dir, &path, 30).unwrap();
+        assert_eq!(timed_out, vec![agent_id.clone()]);
+        // SIGTERM ends `sleep`
+        child.wait().unwrap();
+
+        let graph = load_graph(&path).unwrap();
+        let task = graph.get_task("slow").unwrap();
+        assert_eq!(task.status, Status::Failed);
+        assert_eq!(task.failure_reason.as_deref(), Some("timeout"));
+        assert_eq!(task.retry_count, 1);
+        let registry = AgentRegistry::load(dir).unwrap();
+        assert_eq!(
+            registry.get_agent(&agent_id).unwrap().status,
+            AgentStatus::TimedOut
+        );
+
+        // Already handled agents are left alone
+        assert!(enforce_timeouts(dir, &path, 30).unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_retry_failed_tasks_reopens_transient_failures() {
+        use workgraph::graph::WorkGraph;
+
+        let temp_dir = TempDir::new().unwrap();
+        let failed = |id: &str, reason: &str| Task {
+            id: id.to_string(),
+            title: id.to_string(),
+            status: Status::Failed,
+            failure_reason: Some(reason.to_string()),
+            retry_count: 1,
+            max_retries: Some(3),
+            ..Task::default()
+        };
+        let mut graph = WorkGraph::new();
+        graph.add_node(Node::Task(failed(
+            "flaky",
+            "API error: rate limit exceeded",
+        )));
+        graph.add_node(Node::Task(failed("broken", "Agent exited with code 1")));
+        graph.add_node(Node::Task(failed("epic", "1 of 1 subtasks failed")));
+        graph.add_node(Node::Task(Task {
+            parent: Some("epic".to_string()),
+            ..failed("child", "tests failed")
+        }));
+
+        let config = Config::default();
+        assert!(retry_failed_tasks(temp_dir.path(), &mut graph, &config));
+
+        let flaky = graph.get_task("flaky").unwrap();
+        assert_eq!(flaky.status, Status::Open);
+        assert!(flaky.ready_after.is_some());
+        assert!(flaky.retry_history[0].transient);
+        let broken = graph.get_task("broken").unwrap();
+        assert_eq!(broken.status, Status::Failed);
+        assert_eq!(broken.retry_history[0].rule, "default");
+        assert!(graph.get_task("epic").unwrap().retry_history.is_empty());
+
+        // Each failure is classified once
+        assert!(!retry_failed_tasks(temp_dir.path(), &mut graph, &config));
+    }
+
+    #[test]
+    fn test_retried_failure_does_not_settle_dependents() {
+        use workgraph::graph::{EdgeCondition, WorkGraph};
+
+        let temp_dir = TempDir::new().unwrap();
+        let task = |id: &str| Task {
+            id: id.to_string(),
+            title: id.to_string(),
+            ..Task::default()
+        };
+        let after = |id: &str, dep: &str, condition: EdgeCondition| Task {
+            after: vec![dep.to_string()],
+            after_conditions: [(dep.to_string(), condition)].into(),
+            ..task(id)
+        };
+        let mut graph = WorkGraph::new();
+        graph.add_node(Node::Task(Task {
+            status: Status::Failed,
+            failure_reason: Some("API error: rate limit exceeded".to_string()),
+            retry_count: 1,
+            max_retries: Some(3),
+            ..task("flaky")
+        }));
+        graph.add_node(Node::Task(after("deploy", "flaky", EdgeCondition::Success)));
+        graph.add_node(Node::Task(after(
+            "rollback",
+            "flaky",
+            EdgeCondition::Failure,
+        )));
+
+        // Before the coordinator decides, the failure is not final
+        assert!(super::super::propagate(&mut graph, temp_dir.path()).is_empty());
+        assert_eq!(graph.get_task("deploy").unwrap().status, Status::Open);
+
+        // The tick decides on retries before anything is dispatched, so the
+        // compensation task never becomes ready
+        let config = Config::default();
+        assert!(retry_failed_tasks(temp_dir.path(), &mut graph, &config));
+        assert_eq!(graph.get_task("flaky").unwrap().status, Status::Open);
+        let ready = workgraph::query::ready_tasks(&graph);
+        assert!(ready.iter().all(|t| t.id != "rollback"));
+
+        // This time it succeeds
+        graph.get_task_mut("flaky").unwrap().status = Status::Done;
+        super::super::propagate(&mut graph, temp_dir.path());
+
+        assert_eq!(graph.get_task("deploy").unwrap().status, Status::Open);
+        assert_eq!(
+            graph.get_task("rollback").unwrap().status,
+            Status::Abandoned
+        );
+    }
 }
@@ -1,8 +1,12 @@
 use anyhow::Result;
 use chrono::{DateTime, Utc};
 use serde::Serialize;
+use std::collections::BTreeMap;
 use std::path::Path;
-use workgraph::graph::{CycleConfig, LogEntry, LoopGuard, Status};
+use workgraph::epic::{self, Rollup};
+use workgraph::graph::{
+    CycleConfig, Gate, LogEntry, LoopGuard, MapSpec, OutputContract, RetryRecord, Schedule, Status,
+};
 use workgraph::query::build_reverse_index;
 
 /// Blocker info with status
@@ -16,9 +20,16 @@ fn is_zero(val: &u32) -> bool {
     *val == 0
 }
 
+fn is_zero_i32(val: &i32) -> bool {
+    *val == 0
+}
+
 /// JSON output structure for show command
 #[derive(Debug, Serialize)]
 struct TaskDetails {
+    /// Time the task is shown as of, for `--at`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    as_of: Option<String>,
     id: String,
     title: String,
     #[serde(skip_serializing_if = "Option::is_none")]
@@ -26,6 +37,13 @@ struct TaskDetails {
     status: Status,
     #[serde(skip_serializing_if = "Option::is_none")]
     assigned: Option<String>,
+    #[serde(skip_serializing_if = "is_zero_i32")]
+    priority: i32,
+    /// Priority inherited from dependents, when above the task's own
+    #[serde(skip_serializing_if = "Option::is_none")]
+    effective_priority: Option<i32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    requester: Option<String>,
     #[serde(skip_serializing_if = "Option::is_none")]
     hours: Option<f64>,
     #[serde(skip_serializing_if = "Option::is_none")]
@@ -40,6 +58,8 @@ struct TaskDetails {
     deliverables: Vec<String>,
     #[serde(skip_serializing_if = "Vec::is_empty")]
     artifacts: Vec<String>,
+    #[serde(skip_serializing_if = "BTreeMap::is_empty")]
+    outputs: BTreeMap<String, serde_json::Value>,
     #[serde(skip_serializing_if = "Option::is_none")]
     exec: Option<String>,
     after: Vec<BlockerInfo>,
@@ -58,6 +78,10 @@ struct TaskDetails {
     retry_count: u32,
     #[serde(skip_serializing_if = "Option::is_none")]
     max_retries: Option<u32>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    retry_history: Vec<RetryRecord>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    timeout: Option<String>,
     #[serde(skip_serializing_if = "Option::is_none")]
     failure_reason: Option<String>,
     #[serde(skip_serializing_if = "Option::is_none")]
@@ -71,6 +95,22 @@ struct TaskDetails {
     #[serde(skip_serializing_if = "Option::is_none")]
     cycle_config: Option<CycleConfig>,
     #[serde(skip_serializing_if = "Option::is_none")]
+    map: Option<MapSpec>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    schedule: Option<Schedule>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    gate: Option<Gate>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    contract: Option<OutputContract>,
+    /// Epic (or map task) this task belongs to
+    #[serde(skip_serializing_if = "Option::is_none")]
+    parent: Option<String>,
+    /// Direct children, if this task is an epic
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    children: Vec<BlockerInfo>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    rollup: Option<Rollup>,
+    #[serde(skip_serializing_if = "Option::is_none")]
     ready_after: Option<String>,
     #[serde(default, skip_serializing_if = "is_not_paused")]
     paused: bool,
@@ -86,8 +126,14 @@ fn is_not_paused(val: &bool) -> bool {
     !*val
 }
 
-pub fn run(dir: &Path, id: &str, json: bool) -> Result<()> {
-    let (graph, _path) = super::load_workgraph(dir)?;
+pub fn run(dir: &Path, id: &str, at: Option<&str>, json: bool) -> Result<()> {
+    let (graph, as_of) = match at {
+        Some(at) => {
+            let (graph, at) = super::load_workgraph_at(dir, at)?;
+            (graph, Some(at))
+        }
+        None => (super::open_store(dir)?.load_neighborhood(id)?, None),
+    };
 
     let task = graph.get_task_or_err(id)?;
 
@@ -151,12 +197,31 @@ pub fn run(dir: &Path, id: &str, json: bool) -> Result<()> {
         })
         .unwrap_or_default();
 
+    // Subtasks of an epic, including the children and join of a map task
+    let children: Vec<BlockerInfo> = epic::children(&graph, id)
+        .into_iter()
+        .map(|child| BlockerInfo {
+            id: child.id.clone(),
+            status: child.status,
+        })
+        .collect();
+    let rollup = (!children.is_empty()).then(|| epic::rollup(&graph, id));
+
+    let effective_priority = workgraph::dispatch::effective_priorities(&graph)
+        .get(id)
+        .copied()
+        .filter(|p| *p != task.priority);
+
     let details = TaskDetails {
+        as_of: as_of.map(|t| t.to_rfc3339()),
         id: task.id.clone(),
         title: task.title.clone(),
         description: task.description.clone(),
         status: task.status,
         assigned: task.assigned.clone(),
+        priority: task.priority,
+        effective_priority,
+        requester: task.requester.clone(),
         hours: task.estimate.as_ref().and_then(|e| e.hours),
         cost: task.estimate.as_ref().and_then(|e| e.cost),
         tags: task.tags.clone(),
@@ -164,6 +229,7 @@ pub fn run(dir: &Path, id: &str, json: bool) -> Result<()> {
         inputs: task.inputs.clone(),
         deliverables: task.deliverables.clone(),
         artifacts: task.artifacts.clone(),
+        outputs: task.outputs.clone(),
         exec: task.exec.clone(),
         after: after_info,
         before: before_info,
@@ -174,12 +240,21 @@ pub fn run(dir: &Path, id: &str, json: bool) -> Result<()> {
         log: task.log.clone(),
         retry_count: task.retry_count,
         max_retries: task.max_retries,
+        retry_history: task.retry_history.clone(),
+        timeout: task.timeout.clone(),
         failure_reason: task.failure_reason.clone(),
         model: task.model.clone(),
         verify: task.verify.clone(),
         agent: task.agent.clone(),
         loop_iteration: task.loop_iteration,
         cycle_config: task.cycle_config.clone(),
+        map: task.map.clone(),
+        schedule: task.schedule.clone(),
+        gate: task.gate.clone(),
+        contract: task.contract.clone(),
+        parent: task.parent.clone(),
+        children,
+        rollup,
         ready_after: task.ready_after.clone(),
         paused: task.paused,
         visibility: task.visibility.clone(),
@@ -195,6 +270,14 @@ pub fn run(dir: &Path, id: &str, json: bool) -> Result<()> {
 }
 
 fn print_human_readable(details: &TaskDetails) {
+    if let Some(ref as_of) = details.as_of
+        && let Ok(t) = DateTime::parse_from_rfc3339(as_of)
+    {
+        println!(
+            "As of: {}",
+            t.with_timezone(&chrono::Local).format("%Y-%m-%d %H:%M:%S")
+        );
+    }
     println!("Task: {}", details.id);
     println!("Title: {}", details.title);
     if details.paused {
@@ -210,9 +293,23 @@ fn print_human_readable(details: &TaskDetails) {
     if let Some(ref assigned) = details.assigned {
         println!("Assigned: {}", assigned);
     }
+    if let Some(effective) = details.effective_priority {
+        println!(
+            "Priority: {} (inherited from dependents; own {})",
+            effective, details.priority
+        );
+    } else if details.priority != 0 {
+        println!("Priority: {}", details.priority);
+    }
+    if let Some(ref requester) = details.requester {
+        println!("Requester: {}", requester);
+    }
     if let Some(ref agent) = details.agent {
         println!("Agent: {}", agent);
     }
+    if let Some(ref parent) = details.parent {
+        println!("Parent: {}", parent);
+    }
 
     // Failure info
     if (details.status == Status::Failed || details.status == Status::Abandoned)
@@ -229,6 +326,34 @@ fn print_human_readable(details: &TaskDetails) {
     } else if let Some(max) = details.max_retries {
         println!("Max retries: {}", max);
     }
+    if let Some(ref timeout) = details.timeout {
+        println!("Timeout: {}", timeout);
+    }
+    if !details.retry_history.is_empty() {
+        println!("Retry history:");
+        for record in &details.retry_history {
+            let outcome = if record.transient {
+                "retried"
+            } else {
+                "not retried"
+            };
+            println!(
+                "  #{} {} {} ({}): {}",
+                record.attempt,
+                record.decided_at,
+                outcome,
+                record.rule,
+                record.failure_reason.as_deref().unwrap_or("no reason")
+            );
+        }
+        if let Some(last) = details.retry_history.last()
+            && let Some(ref next) = last.next_attempt
+            && details.status == Status::Open
+            && last.attempt == details.retry_count
+        {
+            println!("Next attempt: {}{}", next, format_countdown(next));
+        }
+    }
 
     // Description
     if let Some(ref description) = details.description {
@@ -298,6 +423,34 @@ fn print_human_readable(details: &TaskDetails) {
         }
     }
 
+    // Map fan-out
+    if let Some(ref map) = details.map {
+        println!();
+        println!("Map over: {} (from {})", map.source, map.from);
+        match map.join {
+            Some(ref join) => println!("  Join: {}", join),
+            None => println!("  Not expanded yet (template: {})", map.template.title),
+        }
+    }
+
+    if let Some(ref rollup) = details.rollup {
+        println!();
+        println!("Subtasks ({}):", rollup.summary());
+        for child in &details.children {
+            println!("  - {} ({})", child.id, child.status);
+        }
+        let mut estimate = Vec::new();
+        if let Some(hours) = rollup.hours {
+            estimate.push(format!("{}h", workgraph::format_hours(hours)));
+        }
+        if let Some(cost) = rollup.cost {
+            estimate.push(format!("${:.2}", cost));
+        }
+        if !estimate.is_empty() {
+            println!("  Total estimate: {}", estimate.join(", "));
+        }
+    }
+
     // Cycle config
     if let Some(ref cc) = details.cycle_config {
         println!();
@@ -310,6 +463,7 @@ fn print_human_readable(details: &TaskDetails) {
                 }
                 LoopGuard::IterationLessThan(n) => format!("iteration<{}", n),
                 LoopGuard::Always => "always".to_string(),
+                LoopGuard::Expr(expr) => expr.clone(),
             };
             println!("  Guard: {}", guard_str);
         }
@@ -321,6 +475,88 @@ fn print_human_readable(details: &TaskDetails) {
         }
     }
 
+    // Schedule
+    if let Some(ref schedule) = details.schedule {
+        println!();
+        println!("Schedule:");
+        println!(
+            "  {} ({}, catch-up: {})",
+            schedule.cron,
+            schedule.timezone.as_deref().unwrap_or("UTC"),
+            schedule.catch_up
+        );
+        if let Ok(next) = workgraph::schedule::upcoming(schedule, chrono::Utc::now(), 1)
+            && let Some(next) = next.first()
+        {
+            println!(
+                "  Next run: {}",
+                workgraph::schedule::format_time(schedule, *next)
+            );
+        }
+    }
+
+    // Approval gate
+    if let Some(ref gate) = details.gate {
+        println!();
+        println!("Approval gate:");
+        if gate.approvers.is_empty() {
+            println!("  Approvers: anyone ({} needed)", gate.quorum);
+        } else {
+            println!(
+                "  Approvers: {} ({} of {} needed)",
+                gate.approvers.join(", "),
+                gate.quorum,
+                gate.approvers.len()
+            );
+        }
+        if let Some(ref at) = gate.requested_at {
+            println!("  Requested: {}", at);
+        }
+        for vote in &gate.votes {
+            let verb = if vote.approved {
+                "Approved"
+            } else {
+                "Rejected"
+            };
+            match vote.reason {
+                Some(ref r) => println!("  {} by {} at {}: {}", verb, vote.actor, vote.at, r),
+                None => println!("  {} by {} at {}", verb, vote.actor, vote.at),
+            }
+        }
+    }
+
+    // Output contract
+    if let Some(ref contract) = details.contract {
+        println!();
+        println!("Output contract:");
+        for pattern in &contract.files {
+            println!("  File: {}", pattern);
+        }
+        if contract.expects_result() {
+            let path = contract
+                .result_path
+                .clone()
+                .unwrap_or_else(|| format!(".workgraph/output/{}/result.json", details.id));
+            if contract.result_schema.is_some() {
+                println!("  Result: {} (must match its schema)", path);
+            } else {
+                println!("  Result: {} (JSON)", path);
+            }
+        }
+        if let Some(ref check) = contract.check {
+            println!("  Check: `{}` exits {}", check, contract.check_status);
+        }
+    }
+
+    // Published outputs
+    if !details.outputs.is_empty() {
+        println!();
+        println!("Outputs:");
+        for (key, value) in &details.outputs {
+            println!("  {} = {}", key, workgraph::bindings::render(value));
+        }
+    }
+
     println!();
 
     // Timestamps
@@ -429,11 +665,15 @@ mod tests {
     #[test]
     fn test_task_details_serialization() {
         let details = TaskDetails {
+            as_of: None,
             id: "t1".to_string(),
             title: "Test Task".to_string(),
             description: Some("Test description".to_string()),
             status: Status::InProgress,
             assigned: Some("agent-1".to_string()),
+            priority: 0,
+            effective_priority: None,
+            requester: None,
             hours: Some(2.0),
             cost: Some(200.0),
             tags: vec!["test".to_string()],
@@ -441,6 +681,7 @@ mod tests {
             inputs: vec![],
             deliverables: vec![],
             artifacts: vec![],
+            outputs: BTreeMap::new(),
             exec: None,
             after: vec![],
             before: vec![BlockerInfo {
@@ -454,6 +695,8 @@ mod tests {
             log: vec![],
             retry_count: 0,
             max_retries: None,
+            retry_history: vec![],
+            timeout: None,
             failure_reason: None,
             model: None,
             verify: None,
@@ -462,7 +705,14 @@ mod tests {
             ready_after: None,
             paused: false,
             visibility: "internal".to_string(),
-        cycle_config: None,
+            cycle_config: None,
+            map: None,
+            schedule: None,
+            parent: None,
+            children: vec![],
+            rollup: None,
+            gate: None,
+            contract: None,
         };
 
         let json = serde_json::to_string(&details).unwrap();
@@ -502,7 +752,7 @@ mod tests {
         let graph = WorkGraph::new();
         workgraph::parser::save_graph(&graph, &path).unwrap();
 
-        let result = run(temp_dir.path(), "no-such-task", false);
+        let result = run(temp_dir.path(), "no-such-task", None, false);
         assert!(result.is_err());
     }
 
@@ -514,7 +764,7 @@ mod tests {
         graph.add_node(Node::Task(make_task("t1", "Test task")));
         workgraph::parser::save_graph(&graph, &path).unwrap();
 
-        let result = run(temp_dir.path(), "t1", false);
+        let result = run(temp_dir.path(), "t1", None, false);
         assert!(result.is_ok());
     }
 
@@ -526,7 +776,7 @@ mod tests {
         graph.add_node(Node::Task(make_task("t1", "Test task")));
         workgraph::parser::save_graph(&graph, &path).unwrap();
 
-        let result = run(temp_dir.path(), "t1", true);
+        let result = run(temp_dir.path(), "t1", None, true);
         assert!(result.is_ok());
     }
 
@@ -542,7 +792,7 @@ mod tests {
         workgraph::parser::save_graph(&graph, &path).unwrap();
 
         // Should succeed (not crash), blocker defaults to Status::Open with a warning
-        let result = run(temp_dir.path(), "t1", false);
+        let result = run(temp_dir.path(), "t1", None, false);
         assert!(result.is_ok());
     }
 
@@ -556,14 +806,14 @@ mod tests {
         graph.add_node(Node::Task(task));
         workgraph::parser::save_graph(&graph, &path).unwrap();
 
-        let result = run(temp_dir.path(), "t1", true);
+        let result = run(temp_dir.path(), "t1", None, true);
         assert!(result.is_ok());
     }
 
     #[test]
     fn test_run_no_graph_file() {
         let temp_dir = tempfile::TempDir::new().unwrap();
-        let result = run(temp_dir.path(), "t1", false);
+        let result = run(temp_dir.path(), "t1", None, false);
         assert!(result.is_err());
     }
 }
@@ -17,10 +17,11 @@ use serde::Serialize;
 use std::fs;
 use std::path::{Path, PathBuf};
 use std::process::{Command, Stdio};
-use workgraph::graph::{LogEntry, Status};
+use workgraph::graph::{LogEntry, Status, parse_delay};
 use workgraph::parser::{load_graph, save_graph};
 use workgraph::service::executor::{ExecutorRegistry, TemplateVars};
 use workgraph::service::registry::AgentRegistry;
+use workgraph::worktree;
 
 use super::graph_path;
 
@@ -50,7 +51,6 @@ pub struct SpawnResult {
 }
 
 /// Parse a timeout duration string like "30m", "1h", "90s"
-#[cfg(test)]
 fn parse_timeout(timeout_str: &str) -> Result<std::time::Duration> {
     let timeout_str = timeout_str.trim();
     if timeout_str.is_empty() {
@@ -86,7 +86,10 @@ fn agent_output_dir(workgraph_dir: &Path, agent_id: &str) -> PathBuf {
 }
 
 /// Build context string from dependency artifacts and logs
-fn build_task_context(graph: &workgraph::WorkGraph, task: &workgraph::graph::Task) -> String {
+pub(super) fn build_task_context(
+    graph: &workgraph::WorkGraph,
+    task: &workgraph::graph::Task,
+) -> String {
     let mut context_parts = Vec::new();
 
     for dep_id in &task.after {
@@ -186,17 +189,45 @@ fn spawn_agent_inner(
             anyhow::bail!("Cannot spawn on task '{}': task is Abandoned", task_id);
         }
     }
+    if task.gate.is_some() {
+        anyhow::bail!(
+            "Cannot spawn on task '{}': it is an approval gate. Use 'wg approve' or 'wg reject'.",
+            task_id
+        );
+    }
+    super::ensure_capacity(&graph, task_id)?;
 
     // Build context from dependencies
     let task_context = build_task_context(&graph, task);
 
     // Create template variables
     let mut vars = TemplateVars::from_task(task, Some(&task_context), Some(dir));
+    let unresolved = vars.resolve_bindings(&graph, task);
+    if !unresolved.is_empty() {
+        let lines: Vec<String> = unresolved.iter().map(|u| format!("  - {}", u)).collect();
+        anyhow::bail!(
+            "Cannot spawn on task '{}': unresolved input bindings:\n{}",
+            task_id,
+            lines.join("\n")
+        );
+    }
+
+    // Isolate the agent in its own git worktree; {{working_dir}} points there
+    let worktree_config = workgraph::config::Config::load_or_default(dir).worktree;
+    let worktree = if worktree_config.enabled {
+        let path = worktree::prepare(dir, task_id, worktree_config.integration_branch.as_deref())
+            .with_context(|| format!("Failed to prepare worktree for '{}'", task_id))?;
+        vars.working_dir = path.to_string_lossy().to_string();
+        Some(path)
+    } else {
+        None
+    };
 
     // Get task exec command for shell executor
     let task_exec = task.exec.clone();
     // Get task model preference
     let task_model = task.model.clone();
+    let task_timeout = task.timeout.clone();
     // Load executor config using the registry
     let executor_registry = ExecutorRegistry::new(dir);
     let executor_config = executor_registry.load_config(executor_name)?;
@@ -212,6 +243,15 @@ fn spawn_agent_inner(
         .or_else(|| executor_config.executor.model.clone())
         .or_else(|| model.map(std::string::ToString::to_string));
 
+    // Timeout resolution: --timeout > task.timeout > executor timeout
+    let timeout_secs = match timeout {
+        Some(t) => Some(parse_timeout(t)?.as_secs()),
+        None => task_timeout
+            .as_deref()
+            .and_then(parse_delay)
+            .or(executor_config.executor.timeout),
+    };
+
     // Override model in template vars with effective model
     if let Some(ref m) = effective_model {
         vars.model = m.clone();
@@ -237,6 +277,8 @@ fn spawn_agent_inner(
     let settings = executor_config.apply_templates(&vars);
 
     // Build the inner command string first
+    // Values of input bindings referenced by a shell executor's command
+    let mut binding_env = Vec::new();
     let inner_command = match settings.executor_type.as_str() {
         "claude" => {
             // Write prompt to file and pipe to claude - avoids all quoting issues
@@ -294,12 +336,16 @@ fn spawn_agent_inner(
             }
         }
         "shell" => {
+            let bound = vars.apply_bindings_to_command(
+                task_exec
+                    .as_ref()
+                    .ok_or_else(|| anyhow::anyhow!("shell executor requires task exec command"))?,
+            );
+            binding_env = bound.env;
             format!(
                 "{} -c {}",
                 shell_escape(&settings.command),
-                shell_escape(task_exec.as_ref().ok_or_else(|| {
-                    anyhow::anyhow!("shell executor requires task exec command")
-                })?)
+                shell_escape(&bound.command)
             )
         }
         _ => {
@@ -313,7 +359,7 @@ fn spawn_agent_inner(
 
     // Create a wrapper script that runs the command and handles completion
     // This ensures tasks get marked done/failed even if the agent doesn't do it
-    let complete_cmd = "wg done \"$TASK_ID\" 2>> \"$OUTPUT_FILE\" || echo \"[wrapper] WARNING: 'wg done' failed with exit code $?\" >> \"$OUTPUT_FILE\"".to_string();
+    let complete_cmd = "wg done \"$TASK_ID\" --fail-unmet 2>> \"$OUTPUT_FILE\" || echo \"[wrapper] WARNING: 'wg done' failed with exit code $?\" >> \"$OUTPUT_FILE\"".to_string();
     let complete_msg = "[wrapper] Agent exited successfully, marking task done";
 
     let wrapper_script = format!(
@@ -373,10 +419,17 @@ exit $EXIT_CODE
     for (key, value) in &settings.env {
         cmd.env(key, value);
     }
+    cmd.envs(binding_env);
 
     // Add task ID and agent ID to environment
     cmd.env("WG_TASK_ID", task_id);
     cmd.env("WG_AGENT_ID", &temp_agent_id);
+    // Executors may report token usage here (see workgraph::accounting)
+    let abs_dir = dir.canonicalize().unwrap_or_else(|_| dir.to_path_buf());
+    cmd.env(
+        "WG_USAGE_FILE",
+        workgraph::accounting::sidecar_path(&abs_dir, &temp_agent_id),
+    );
     cmd.env("WG_EXECUTOR_TYPE", &settings.executor_type);
     if let Some(ref m) = effective_model {
         cmd.env("WG_MODEL", m);
@@ -385,6 +438,8 @@ exit $EXIT_CODE
     // Set working directory if specified
     if let Some(ref wd) = settings.working_dir {
         cmd.current_dir(wd);
+    } else if let Some(ref wt) = worktree {
+        cmd.current_dir(wt);
     }
 
     // Wrapper script handles output redirect internally
@@ -425,8 +480,21 @@ exit $EXIT_CODE
                 .unwrap_or_default()
         ),
     });
-
+    if let Some(ref wt) = worktree {
+        task.log.push(LogEntry {
+            timestamp: Utc::now().to_rfc3339(),
+            actor: Some(temp_agent_id.clone()),
+            message: format!(
+                "Working in worktree {} on branch {}",
+                wt.display(),
+                worktree::branch_name(task_id)
+            ),
+        });
+    }
+
+    let claim = workgraph::capacity::lock_claim(dir, graph.get_task_or_err(task_id)?)?;
     save_graph(&graph, &graph_path).context("Failed to save graph")?;
+    drop(claim);
 
     // Spawn the process (don't wait). If spawn fails, unclaim the task.
     let child = match cmd.spawn() {
@@ -478,6 +546,9 @@ exit $EXIT_CODE
         &output_file_str,
         effective_model.as_deref(),
     );
+    if let Some(agent) = agent_registry.get_agent_mut(&agent_id) {
+        agent.timeout_secs = timeout_secs;
+    }
     if let Err(save_err) = agent_registry.save(dir) {
         // Registry save failed — kill the orphaned process to prevent invisible agents
         eprintln!(
@@ -503,7 +574,7 @@ exit $EXIT_CODE
         "executor": executor_name,
         "model": &effective_model,
         "started_at": Utc::now().to_rfc3339(),
-        "timeout": timeout,
+        "timeout_secs": timeout_secs,
     });
     fs::write(&metadata_path, serde_json::to_string_pretty(&metadata)?)?;
 
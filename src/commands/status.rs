@@ -5,15 +5,19 @@
 //! - Coordinator config (max_agents, executor, model, poll_interval)
 //! - Agent summary (alive/dead counts, active agents with tasks)
 //! - Task summary (in-progress, ready, blocked, done counts)
+//! - Open epics with their rollup (subtasks too with `--expand`)
+//! - Budget utilization
 //! - Recent activity (last 5 task completions)
 //!
 //! Usage:
-//!   wg status         # Human-readable output
-//!   wg status --json  # Machine-readable JSON output
+//!   wg status           # Human-readable output
+//!   wg status --expand  # Also list the subtasks of each epic
+//!   wg status --json    # Machine-readable JSON output
 
 use anyhow::{Context, Result};
 use chrono::{DateTime, Utc};
 use std::path::Path;
+use workgraph::epic::{self, Rollup};
 use workgraph::graph::Status;
 use workgraph::parser::load_graph;
 use workgraph::query::ready_tasks;
@@ -69,6 +73,39 @@ struct TaskSummaryInfo {
     done_total: usize,
 }
 
+/// Open epic with its rollup
+#[derive(Debug, Clone, serde::Serialize)]
+struct EpicInfo {
+    id: String,
+    title: String,
+    rollup: Rollup,
+    /// Direct subtasks, only with `--expand`
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    children: Vec<EpicChildInfo>,
+}
+
+#[derive(Debug, Clone, serde::Serialize)]
+struct EpicChildInfo {
+    id: String,
+    title: String,
+    status: Status,
+}
+
+/// Spend against a budget
+#[derive(Debug, Clone, serde::Serialize)]
+struct BudgetInfo {
+    id: String,
+    scope: String,
+    /// Spend plus reservations, or the largest task's spend for per-task budgets
+    used: String,
+    available: String,
+    percent: f64,
+    exhausted: bool,
+    /// Tasks that used up a per-task budget
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    capped_tasks: Vec<String>,
+}
+
 /// Recent activity entry
 #[derive(Debug, Clone, serde::Serialize)]
 struct RecentActivityEntry {
@@ -84,11 +121,15 @@ struct StatusOutput {
     coordinator: CoordinatorInfo,
     agents: AgentSummaryInfo,
     tasks: TaskSummaryInfo,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    epics: Vec<EpicInfo>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    budgets: Vec<BudgetInfo>,
     recent: Vec<RecentActivityEntry>,
 }
 
-pub fn run(dir: &Path, json: bool) -> Result<()> {
-    let status = gather_status(dir)?;
+pub fn run(dir: &Path, expand: bool, json: bool) -> Result<()> {
+    let status = gather_status(dir, expand)?;
 
     if json {
         println!("{}", serde_json::to_string_pretty(&status)?);
@@ -99,7 +140,7 @@ pub fn run(dir: &Path, json: bool) -> Result<()> {
     Ok(())
 }
 
-fn gather_status(dir: &Path) -> Result<StatusOutput> {
+fn gather_status(dir: &Path, expand: bool) -> Result<StatusOutput> {
     // 1. Service status
     let service = gather_service_status(dir)?;
 
@@ -112,7 +153,13 @@ fn gather_status(dir: &Path) -> Result<StatusOutput> {
     // 4. Task summary
     let tasks = gather_task_summary(dir)?;
 
-    // 5. Recent activity
+    // 5. Open epics
+    let epics = gather_epics(dir, expand)?;
+
+    // 6. Budgets
+    let budgets = gather_budgets(dir)?;
+
+    // 7. Recent activity
     let recent = gather_recent_activity(dir)?;
 
     Ok(StatusOutput {
@@ -120,10 +167,44 @@ fn gather_status(dir: &Path) -> Result<StatusOutput> {
         coordinator,
         agents,
         tasks,
+        epics,
+        budgets,
         recent,
     })
 }
 
+fn gather_budgets(dir: &Path) -> Result<Vec<BudgetInfo>> {
+    let path = graph_path(dir);
+    if !path.exists() {
+        return Ok(vec![]);
+    }
+    let graph = load_graph(&path).context("Failed to load graph")?;
+    let usages = super::resources::budget_usage(dir, &graph)?;
+    Ok(usages
+        .iter()
+        .map(|usage| {
+            let used = if usage.budget.per_task {
+                usage.by_task.values().copied().fold(0.0, f64::max)
+            } else {
+                usage.spent + usage.reserved
+            };
+            BudgetInfo {
+                id: usage.id.clone(),
+                scope: usage.scope(),
+                used: usage.format(used),
+                available: usage.format(usage.available),
+                percent: usage.percent(),
+                exhausted: usage.is_exhausted(),
+                capped_tasks: usage
+                    .exhausted_tasks()
+                    .into_iter()
+                    .map(String::from)
+                    .collect(),
+            }
+        })
+        .collect())
+}
+
 fn gather_service_status(dir: &Path) -> Result<ServiceStatusInfo> {
     let state = ServiceState::load(dir)?;
 
@@ -253,7 +334,7 @@ fn gather_task_summary(dir: &Path) -> Result<TaskSummaryInfo> {
                     let all_blockers_done = task.after.iter().all(|bid| {
                         graph
                             .get_task(bid)
-                            .map(|t| t.status.is_terminal())
+                            .map(|t| task.is_after_satisfied(bid, t.status))
                             .unwrap_or(true)
                     });
                     if has_future_ready_after && all_blockers_done {
@@ -303,6 +384,39 @@ fn gather_task_summary(dir: &Path) -> Result<TaskSummaryInfo> {
     })
 }
 
+fn gather_epics(dir: &Path, expand: bool) -> Result<Vec<EpicInfo>> {
+    let path = graph_path(dir);
+    if !path.exists() {
+        return Ok(vec![]);
+    }
+
+    let graph = load_graph(&path).context("Failed to load graph")?;
+    let epic_ids = epic::epic_ids(&graph);
+    let mut epics: Vec<EpicInfo> = graph
+        .tasks()
+        .filter(|t| epic_ids.contains(t.id.as_str()) && !t.status.is_terminal())
+        .map(|t| EpicInfo {
+            id: t.id.clone(),
+            title: t.title.clone(),
+            rollup: epic::rollup(&graph, &t.id),
+            children: if expand {
+                epic::children(&graph, &t.id)
+                    .into_iter()
+                    .map(|c| EpicChildInfo {
+                        id: c.id.clone(),
+                        title: c.title.clone(),
+                        status: c.status,
+                    })
+                    .collect()
+            } else {
+                vec![]
+            },
+        })
+        .collect();
+    epics.sort_by(|a, b| a.id.cmp(&b.id));
+    Ok(epics)
+}
+
 fn gather_recent_activity(dir: &Path) -> Result<Vec<RecentActivityEntry>> {
     let path = graph_path(dir);
     if !path.exists() {
@@ -413,6 +527,46 @@ fn print_status(status: &StatusOutput) {
         status.tasks.done_today
     );
 
+    // Open epics
+    if !status.epics.is_empty() {
+        println!();
+        println!("Epics:");
+        for epic in &status.epics {
+            println!("  {}  {} [{}]", epic.id, epic.title, epic.rollup.summary());
+            for child in &epic.children {
+                println!(
+                    "    {} {} - {}",
+                    super::list::status_marker(child.status),
+                    child.id,
+                    child.title
+                );
+            }
+        }
+    }
+
+    // Budgets
+    if !status.budgets.is_empty() {
+        println!();
+        println!("Budgets:");
+        for budget in &status.budgets {
+            println!(
+                "  {}  {} / {} ({:.0}%, {}){}",
+                budget.id,
+                budget.used,
+                budget.available,
+                budget.percent,
+                budget.scope,
+                if budget.exhausted {
+                    " - EXHAUSTED, dispatch paused".to_string()
+                } else if !budget.capped_tasks.is_empty() {
+                    format!(" - reached by {}", budget.capped_tasks.join(", "))
+                } else {
+                    String::new()
+                }
+            );
+        }
+    }
+
     // Recent activity
     if !status.recent.is_empty() {
         println!();
@@ -449,7 +603,7 @@ mod tests {
     #[test]
     fn test_gather_status_empty() {
         let temp_dir = TempDir::new().unwrap();
-        let result = gather_status(temp_dir.path());
+        let result = gather_status(temp_dir.path(), false);
         assert!(result.is_ok());
         let status = result.unwrap();
         assert!(!status.service.running);
@@ -457,6 +611,33 @@ mod tests {
         assert_eq!(status.tasks.ready, 0);
     }
 
+    #[test]
+    fn test_gather_epics() {
+        let temp_dir = TempDir::new().unwrap();
+        let path = temp_dir.path().join("graph.jsonl");
+
+        let mut graph = WorkGraph::new();
+        graph.add_node(Node::Task(make_task("epic", "Epic")));
+        let mut child = make_task("child", "Child");
+        child.parent = Some("epic".to_string());
+        child.status = Status::Done;
+        graph.add_node(Node::Task(child));
+        let mut other = make_task("other", "Other");
+        other.parent = Some("epic".to_string());
+        graph.add_node(Node::Task(other));
+        save_graph(&graph, &path).unwrap();
+
+        let epics = gather_epics(temp_dir.path(), false).unwrap();
+        assert_eq!(epics.len(), 1);
+        assert_eq!(epics[0].id, "epic");
+        assert_eq!(epics[0].rollup.summary(), "1/2, 50%");
+        assert!(epics[0].children.is_empty());
+
+        let epics = gather_epics(temp_dir.path(), true).unwrap();
+        let children: Vec<&str> = epics[0].children.iter().map(|c| c.id.as_str()).collect();
+        assert_eq!(children, vec!["child", "other"]);
+    }
+
     #[test]
     fn test_gather_task_summary() {
         let temp_dir = TempDir::new().unwrap();
@@ -1242,10 +1242,10 @@ pub fn reconstruct_temporal(
                 current_statuses.insert(task_id.clone(), Status::Open);
                 current_assignments.remove(&task_id);
             }
-            "done" => {
+            "done" | "gate_approved" => {
                 current_statuses.insert(task_id.clone(), Status::Done);
             }
-            "fail" => {
+            "fail" | "gate_rejected" => {
                 current_statuses.insert(task_id.clone(), Status::Failed);
             }
             "retry" => {
@@ -358,6 +358,7 @@ fn format_loop_guard(guard: &LoopGuard) -> String {
         LoopGuard::Always => "always".to_string(),
         LoopGuard::IterationLessThan(n) => format!("iteration < {}", n),
         LoopGuard::TaskStatus { task, status } => format!("task_status:{}:{}", task, status),
+        LoopGuard::Expr(expr) => expr.clone(),
     }
 }
 
@@ -2,7 +2,7 @@ use anyhow::{Context, Result};
 use chrono::Utc;
 use std::collections::HashMap;
 use std::path::{Path, PathBuf};
-use workgraph::graph::{Node, Status, Task};
+use workgraph::graph::{Node, Task};
 use workgraph::parser::{load_graph, save_graph};
 use workgraph::trace_function::{
     self, FunctionInput, InputType, PlanningConfig, TaskTemplate, TraceFunction,
@@ -235,34 +235,15 @@ pub fn run(
                 id: task_id.clone(),
                 title: rendered.title.clone(),
                 description: Some(rendered.description.clone()),
-                status: Status::Open,
-                assigned: None,
-                estimate: None,
-                before: vec![],
                 after: real_after.clone(),
-                requires: vec![],
                 tags,
                 skills: rendered.skills.clone(),
-                inputs: vec![],
                 deliverables: rendered.deliverables.clone(),
-                artifacts: vec![],
-                exec: None,
-                not_before: None,
                 created_at: Some(Utc::now().to_rfc3339()),
-                started_at: None,
-                completed_at: None,
-                log: vec![],
-                retry_count: 0,
-                max_retries: None,
-                failure_reason: None,
                 model: task_model,
                 verify: rendered.verify.clone(),
-                agent: None,
-                loop_iteration: 0,
-                ready_after: None,
-                paused: false,
                 visibility: "internal".to_string(),
-                cycle_config: None,
+                ..Task::default()
             };
 
             graph.add_node(Node::Task(task));
@@ -0,0 +1,282 @@
+use anyhow::{Context, Result};
+use chrono::{DateTime, Local};
+use std::collections::HashSet;
+use std::path::Path;
+use workgraph::history::{self, Revert};
+use workgraph::parser::save_graph;
+use workgraph::undo::{self, Conflict, Save};
+
+/// How many saves `wg undo --list` shows by default.
+const DEFAULT_LIST_COUNT: usize = 10;
+
+/// Undo the current actor's last `count` graph changes, or with `list`,
+/// preview the changes `wg undo N` would revert.
+pub fn run(dir: &Path, count: Option<usize>, list: bool, force: bool, json: bool) -> Result<()> {
+    let entries = read_history(dir)?;
+    let saves = undo::saves(&entries);
+    let actor = history::current_actor();
+
+    if list {
+        let plan = undo::plan_undo(
+            &saves,
+            actor.as_deref(),
+            count.unwrap_or(DEFAULT_LIST_COUNT),
+        );
+        return print_list(&saves, &plan, actor.as_deref(), json);
+    }
+
+    let plan = undo::plan_undo(&saves, actor.as_deref(), count.unwrap_or(1));
+    if plan.is_empty() {
+        anyhow::bail!("Nothing to undo for {}", actor_name(actor.as_deref()));
+    }
+    let batch: HashSet<&str> = plan.iter().map(|s| s.id).collect();
+    let conflicts: Vec<Conflict> = plan
+        .iter()
+        .flat_map(|save| undo::conflicts(&saves, save, &batch))
+        .collect();
+    check_conflicts(&conflicts, force, "undo")?;
+
+    let (mut graph, path) = super::load_workgraph_mut(dir)?;
+    for save in &plan {
+        undo::revert(&mut graph, save)
+            .with_context(|| format!("Cannot undo '{}'", save.command.unwrap_or(save.id)))?;
+        history::with_revert(Revert::Undo(save.id.to_string()), || {
+            save_graph(&graph, &path)
+        })
+        .context("Failed to save graph")?;
+    }
+    super::notify_graph_changed(dir);
+
+    record(dir, "undo", actor.as_deref(), &plan);
+    print_result("Undid", &plan, &conflicts, json)
+}
+
+/// Redo the current actor's most recent undo.
+pub fn run_redo(dir: &Path, force: bool, json: bool) -> Result<()> {
+    let entries = read_history(dir)?;
+    let saves = undo::saves(&entries);
+    let actor = history::current_actor();
+
+    let Some(undo_save) = undo::plan_redo(&saves, actor.as_deref()) else {
+        anyhow::bail!("Nothing to redo for {}", actor_name(actor.as_deref()));
+    };
+    let conflicts = undo::conflicts(&saves, undo_save, &HashSet::new());
+    check_conflicts(&conflicts, force, "redo")?;
+
+    let (mut graph, path) = super::load_workgraph_mut(dir)?;
+    undo::revert(&mut graph, undo_save).context("Cannot redo")?;
+    history::with_revert(Revert::Redo(undo_save.id.to_string()), || {
+        save_graph(&graph, &path)
+    })
+    .context("Failed to save graph")?;
+    super::notify_graph_changed(dir);
+
+    // Report the save that was redone rather than the undo of it.
+    let original: Vec<&Save> = undo_save
+        .undo_of()
+        .and_then(|id| saves.iter().find(|s| s.id == id))
+        .into_iter()
+        .collect();
+    record(dir, "redo", actor.as_deref(), &original);
+    print_result("Redid", &original, &conflicts, json)
+}
+
+fn read_history(dir: &Path) -> Result<Vec<history::HistoryEntry>> {
+    if !super::graph_path(dir).exists() {
+        anyhow::bail!("Workgraph not initialized. Run 'wg init' first.");
+    }
+    let config = workgraph::config::Config::load_or_default(dir);
+    if !config.log.history {
+        anyhow::bail!("Undo needs the graph history, which is disabled ([log] history = false)");
+    }
+    history::read_all(dir)
+}
+
+fn check_conflicts(conflicts: &[Conflict], force: bool, verb: &str) -> Result<()> {
+    if conflicts.is_empty() {
+        return Ok(());
+    }
+    if force {
+        for conflict in conflicts {
+            eprintln!("Warning: {}", conflict);
+        }
+        return Ok(());
+    }
+    let details: Vec<String> = conflicts.iter().map(|c| format!("  {}", c)).collect();
+    anyhow::bail!(
+        "Cannot {} without discarding later changes:\n{}\nRe-run with --force to {} anyway",
+        verb,
+        details.join("\n"),
+        verb
+    )
+}
+
+fn record(dir: &Path, op: &str, actor: Option<&str>, saves: &[&Save]) {
+    let config = workgraph::config::Config::load_or_default(dir);
+    let reverted: Vec<_> = saves
+        .iter()
+        .map(|s| {
+            serde_json::json!({
+                "save": s.id,
+                "command": s.command,
+                "tasks": s.node_ids(),
+            })
+        })
+        .collect();
+    let _ = workgraph::provenance::record(
+        dir,
+        op,
+        None,
+        actor,
+        serde_json::json!({ "reverted": reverted }),
+        config.log.rotation_threshold,
+    );
+}
+
+fn save_json(save: &Save) -> serde_json::Value {
+    serde_json::json!({
+        "save": save.id,
+        "command": save.command,
+        "actor": save.actor,
+        "tasks": save.node_ids(),
+    })
+}
+
+fn conflicts_json(conflicts: &[Conflict]) -> Vec<serde_json::Value> {
+    conflicts
+        .iter()
+        .map(|c| {
+            serde_json::json!({
+                "task": c.id,
+                "save": c.save,
+                "actor": c.actor,
+                "command": c.command,
+            })
+        })
+        .collect()
+}
+
+fn print_list(saves: &[Save], plan: &[&Save], actor: Option<&str>, json: bool) -> Result<()> {
+    // Conflicts as `wg undo N` would see them for each entry.
+    let mut batch: HashSet<&str> = HashSet::new();
+    let mut rows = Vec::new();
+    for save in plan {
+        batch.insert(save.id);
+        rows.push((*save, undo::conflicts(saves, save, &batch)));
+    }
+
+    if json {
+        let output: Vec<_> = rows
+            .iter()
+            .map(|(s, c)| {
+                let mut obj = save_json(s);
+                obj["conflicts"] = serde_json::json!(conflicts_json(c));
+                obj
+            })
+            .collect();
+        println!("{}", serde_json::to_string_pretty(&output)?);
+        return Ok(());
+    }
+    if rows.is_empty() {
+        println!("Nothing to undo for {}", actor_name(actor));
+        return Ok(());
+    }
+    println!(
+        "Changes by {} that `wg undo N` would revert, newest first:",
+        actor_name(actor)
+    );
+    for (i, (save, conflicts)) in rows.iter().enumerate() {
+        let tasks: Vec<&str> = save.node_ids().into_iter().collect();
+        println!(
+            "  {:>2}. {}  {}  [{}]",
+            i + 1,
+            format_time(save.id),
+            save.command.unwrap_or("(unknown command)"),
+            tasks.join(", ")
+        );
+        for conflict in conflicts {
+            println!("      conflict: {}", conflict);
+        }
+    }
+    Ok(())
+}
+
+fn print_result(verb: &str, saves: &[&Save], conflicts: &[Conflict], json: bool) -> Result<()> {
+    if json {
+        let reverted: Vec<_> = saves.iter().map(|s| save_json(s)).collect();
+        println!(
+            "{}",
+            serde_json::to_string_pretty(&serde_json::json!({
+                "reverted": reverted,
+                "conflicts": conflicts_json(conflicts),
+            }))?
+        );
+        return Ok(());
+    }
+    for save in saves {
+        let tasks: Vec<&str> = save.node_ids().into_iter().collect();
+        println!(
+            "{} {} ({}) [{}]",
+            verb,
+            save.command.unwrap_or("(unknown command)"),
+            format_time(save.id),
+            tasks.join(", ")
+        );
+    }
+    Ok(())
+}
+
+fn actor_name(actor: Option<&str>) -> String {
+    match actor {
+        Some(actor) => format!("'{}'", actor),
+        None => "this user".to_string(),
+    }
+}
+
+fn format_time(ts: &str) -> String {
+    DateTime::parse_from_rfc3339(ts)
+        .map(|t| {
+            t.with_timezone(&Local)
+                .format("%Y-%m-%d %H:%M:%S")
+                .to_string()
+        })
+        .unwrap_or_else(|_| ts.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+    use workgraph::graph::{Node, Status, Task, WorkGraph};
+    use workgraph::parser::load_graph;
+
+    #[test]
+    fn test_undo_and_redo_edit() {
+        let dir = tempdir().unwrap();
+        let path = super::super::graph_path(dir.path());
+        let mut graph = WorkGraph::new();
+        graph.add_node(Node::Task(Task {
+            id: "t1".into(),
+            title: "Original".into(),
+            ..Task::default()
+        }));
+        save_graph(&graph, &path).unwrap();
+
+        let mut graph = load_graph(&path).unwrap();
+        let task = graph.get_task_mut("t1").unwrap();
+        task.title = "Typo".into();
+        task.status = Status::Abandoned;
+        save_graph(&graph, &path).unwrap();
+
+        run(dir.path(), None, false, false, false).unwrap();
+        let task = load_graph(&path).unwrap().get_task("t1").unwrap().clone();
+        assert_eq!(task.title, "Original");
+        assert_eq!(task.status, Status::Open);
+
+        run_redo(dir.path(), false, false).unwrap();
+        let task = load_graph(&path).unwrap().get_task("t1").unwrap().clone();
+        assert_eq!(task.title, "Typo");
+        assert_eq!(task.status, Status::Abandoned);
+        assert!(run_redo(dir.path(), false, false).is_err());
+    }
+}
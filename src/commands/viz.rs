@@ -1,10 +1,11 @@
 use anyhow::{Context, Result};
-use std::collections::{HashMap, HashSet};
+use std::collections::{BTreeMap, HashMap, HashSet};
 use std::io::IsTerminal;
 use std::path::Path;
 use std::process::{Command, Stdio};
+use workgraph::epic;
 use workgraph::format_hours;
-use workgraph::graph::{Status, Task, WorkGraph};
+use workgraph::graph::{EdgeCondition, Status, Task, WorkGraph};
 
 /// Output format for visualization
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
@@ -41,6 +42,8 @@ pub struct VizOptions {
     pub output: Option<String>,
     /// Show internal tasks (assign-*, evaluate-*) that are normally hidden
     pub show_internal: bool,
+    /// Draw each epic as one node instead of a cluster of its subtasks
+    pub collapse_epics: bool,
 }
 
 impl Default for VizOptions {
@@ -52,6 +55,7 @@ impl Default for VizOptions {
             format: OutputFormat::Ascii,
             output: None,
             show_internal: false,
+            collapse_epics: false,
         }
     }
 }
@@ -158,12 +162,21 @@ pub fn run(dir: &Path, options: &VizOptions) -> Result<()> {
 
     // Filter out internal tasks (assign-*, evaluate-*) unless --show-internal
     let empty_annotations = HashMap::new();
-    let (tasks_to_show, annotations) = if options.show_internal {
+    let (tasks_to_show, mut annotations) = if options.show_internal {
         (tasks_to_show, empty_annotations)
     } else {
         filter_internal_tasks(&graph, tasks_to_show, &empty_annotations)
     };
 
+    // Fold the subtasks of shown epics into the epic node
+    let collapsed: Vec<Task>;
+    let tasks_to_show: Vec<&Task> = if options.collapse_epics {
+        collapsed = collapse_epics(&graph, &tasks_to_show, &mut annotations);
+        collapsed.iter().collect()
+    } else {
+        tasks_to_show
+    };
+
     let task_ids: HashSet<&str> = tasks_to_show.iter().map(|t| t.id.as_str()).collect();
 
     // Calculate critical path if requested
@@ -288,6 +301,13 @@ fn generate_dot(
         }
     }
 
+    // Group the subtasks of each epic (and the fan-out of each map task) in
+    // a cluster, nesting sub-epics inside their parent's cluster
+    let groups = epic_groups(tasks);
+    for root in group_roots(&groups, task_ids) {
+        push_dot_cluster(graph, &groups, root, 1, &mut lines);
+    }
+
     lines.push(String::new());
 
     // Print edges
@@ -295,23 +315,29 @@ fn generate_dot(
         for after in &task.after {
             // Only show edge if the blocker is also in our task set
             if task_ids.contains(after.as_str()) {
+                // Conditional edges get their own label and style
+                let (label, condition_style) = match task.after_condition(after) {
+                    EdgeCondition::Any => ("blocks", ""),
+                    EdgeCondition::Success => ("on success", "color=darkgreen"),
+                    EdgeCondition::Failure => ("on failure", "color=darkorange, style=dashed"),
+                };
                 // Check if this edge is on critical path
                 let edge_style =
                     if critical_path.contains(&task.id) && critical_path.contains(after) {
                         "color=red, penwidth=2"
                     } else {
-                        ""
+                        condition_style
                     };
 
                 if edge_style.is_empty() {
                     lines.push(format!(
-                        "  \"{}\" -> \"{}\" [label=\"blocks\"];",
-                        after, task.id
+                        "  \"{}\" -> \"{}\" [label=\"{}\"];",
+                        after, task.id, label
                     ));
                 } else {
                     lines.push(format!(
-                        "  \"{}\" -> \"{}\" [label=\"blocks\", {}];",
-                        after, task.id, edge_style
+                        "  \"{}\" -> \"{}\" [label=\"{}\", {}];",
+                        after, task.id, label, edge_style
                     ));
                 }
             }
@@ -341,7 +367,7 @@ fn generate_dot(
 }
 
 fn generate_mermaid(
-    _graph: &WorkGraph,
+    graph: &WorkGraph,
     tasks: &[&workgraph::graph::Task],
     task_ids: &HashSet<&str>,
     critical_path: &HashSet<String>,
@@ -383,6 +409,12 @@ fn generate_mermaid(
         lines.push(node);
     }
 
+    // Group the subtasks of each epic in a (nested) subgraph
+    let groups = epic_groups(tasks);
+    for root in group_roots(&groups, task_ids) {
+        push_mermaid_subgraph(graph, &groups, root, 1, &mut lines);
+    }
+
     lines.push(String::new());
 
     // Print edges
@@ -390,14 +422,23 @@ fn generate_mermaid(
         for after in &task.after {
             if task_ids.contains(after.as_str()) {
                 // Check if this edge is on critical path
-                let arrow =
-                    if critical_path.contains(&task.id) && critical_path.contains(after) {
-                        "==>" // thick arrow for critical path
-                    } else {
-                        "-->"
-                    };
+                let arrow = if critical_path.contains(&task.id) && critical_path.contains(after) {
+                    "==>" // thick arrow for critical path
+                } else if task.after_condition(after) == EdgeCondition::Failure {
+                    "-.->" // dotted arrow for failure-only edges
+                } else {
+                    "-->"
+                };
 
-                lines.push(format!("  {} {} {}", after, arrow, task.id));
+                match task.after_condition(after) {
+                    EdgeCondition::Any => {
+                        lines.push(format!("  {} {} {}", after, arrow, task.id));
+                    }
+                    condition => lines.push(format!(
+                        "  {} {}|on {}| {}",
+                        after, arrow, condition, task.id
+                    )),
+                }
             }
         }
     }
@@ -434,6 +475,162 @@ fn generate_mermaid(
     lines.join("\n")
 }
 
+/// Shown tasks grouped by their parent, members sorted by ID.
+fn epic_groups<'a>(tasks: &[&'a Task]) -> BTreeMap<&'a str, Vec<&'a str>> {
+    let mut groups: BTreeMap<&str, Vec<&str>> = BTreeMap::new();
+    for task in tasks {
+        if let Some(ref parent) = task.parent {
+            groups
+                .entry(parent.as_str())
+                .or_default()
+                .push(task.id.as_str());
+        }
+    }
+    for members in groups.values_mut() {
+        members.sort_unstable();
+    }
+    groups
+}
+
+/// Groups that are not drawn inside another group: their epic is hidden, or
+/// is not itself a member of a group.
+fn group_roots<'a>(
+    groups: &BTreeMap<&'a str, Vec<&'a str>>,
+    task_ids: &HashSet<&str>,
+) -> Vec<&'a str> {
+    let nested: HashSet<&str> = groups
+        .values()
+        .flatten()
+        .copied()
+        .filter(|id| groups.contains_key(id))
+        .collect();
+    groups
+        .keys()
+        .copied()
+        .filter(|id| !nested.contains(id) || !task_ids.contains(id))
+        .collect()
+}
+
+/// Cluster label: `map: id` for map tasks, `epic: id (3/5, 60%)` otherwise.
+fn group_label(graph: &WorkGraph, id: &str) -> String {
+    if graph.get_task(id).is_some_and(|t| t.map.is_some()) {
+        format!("map: {}", id)
+    } else {
+        format!("epic: {} ({})", id, epic::rollup(graph, id).summary())
+    }
+}
+
+fn push_dot_cluster(
+    graph: &WorkGraph,
+    groups: &BTreeMap<&str, Vec<&str>>,
+    id: &str,
+    depth: usize,
+    lines: &mut Vec<String>,
+) {
+    let indent = "  ".repeat(depth);
+    lines.push(format!("{}subgraph \"cluster_{}\" {{", indent, id));
+    lines.push(format!("{}  label=\"{}\";", indent, group_label(graph, id)));
+    lines.push(format!("{}  style=dashed;", indent));
+    for member in &groups[id] {
+        lines.push(format!("{}  \"{}\";", indent, member));
+    }
+    for member in &groups[id] {
+        if groups.contains_key(member) && *member != id {
+            push_dot_cluster(graph, groups, member, depth + 1, lines);
+        }
+    }
+    lines.push(format!("{}}}", indent));
+}
+
+fn push_mermaid_subgraph(
+    graph: &WorkGraph,
+    groups: &BTreeMap<&str, Vec<&str>>,
+    id: &str,
+    depth: usize,
+    lines: &mut Vec<String>,
+) {
+    let indent = "  ".repeat(depth);
+    let prefix = if graph.get_task(id).is_some_and(|t| t.map.is_some()) {
+        "map"
+    } else {
+        "epic"
+    };
+    lines.push(format!(
+        "{}subgraph {}_{}[\"{}\"]",
+        indent,
+        prefix,
+        id,
+        group_label(graph, id)
+    ));
+    for member in &groups[id] {
+        lines.push(format!("{}  {}", indent, member));
+    }
+    for member in &groups[id] {
+        if groups.contains_key(member) && *member != id {
+            push_mermaid_subgraph(graph, groups, member, depth + 1, lines);
+        }
+    }
+    lines.push(format!("{}end", indent));
+}
+
+/// Replace every shown task that sits under a shown epic by its outermost
+/// shown epic. Edges into and out of the folded tasks are redirected to the
+/// epic, and the epic is annotated with its rollup.
+fn collapse_epics(
+    graph: &WorkGraph,
+    tasks: &[&Task],
+    annotations: &mut HashMap<String, String>,
+) -> Vec<Task> {
+    let shown: HashSet<&str> = tasks.iter().map(|t| t.id.as_str()).collect();
+    let epics = epic::epic_ids(graph);
+    let representative: HashMap<&str, &str> = tasks
+        .iter()
+        .filter_map(|t| {
+            let outermost = epic::ancestors(graph, &t.id)
+                .into_iter()
+                .rev()
+                .find(|a| shown.contains(a.id.as_str()))?;
+            Some((t.id.as_str(), outermost.id.as_str()))
+        })
+        .collect();
+    let resolve =
+        |id: &str| -> String { representative.get(id).copied().unwrap_or(id).to_string() };
+
+    let mut kept: Vec<Task> = tasks
+        .iter()
+        .filter(|t| !representative.contains_key(t.id.as_str()))
+        .map(|t| (*t).clone())
+        .collect();
+    let index: HashMap<&str, usize> = tasks
+        .iter()
+        .filter(|t| !representative.contains_key(t.id.as_str()))
+        .enumerate()
+        .map(|(i, t)| (t.id.as_str(), i))
+        .collect();
+    for task in tasks {
+        if let Some(owner) = representative.get(task.id.as_str()) {
+            kept[index[*owner]].after.extend(task.after.iter().cloned());
+        }
+    }
+    for task in &mut kept {
+        let resolved: Vec<String> = task.after.iter().map(|a| resolve(a)).collect();
+        task.after.clear();
+        for after in resolved {
+            if after != task.id && !task.after.contains(&after) {
+                task.after.push(after);
+            }
+        }
+        if epics.contains(task.id.as_str()) {
+            let summary = format!("[{}]", epic::rollup(graph, &task.id).summary());
+            annotations
+                .entry(task.id.clone())
+                .and_modify(|a| *a = format!("{} {}", a, summary))
+                .or_insert(summary);
+        }
+    }
+    kept
+}
+
 /// Calculate the critical path (longest dependency chain by hours)
 fn calculate_critical_path(graph: &WorkGraph, active_ids: &HashSet<&str>) -> HashSet<String> {
     // Build forward index: task_id -> tasks that it blocks
@@ -1430,38 +1627,12 @@ mod tests {
         Task {
             id: id.to_string(),
             title: title.to_string(),
-            description: None,
-            status: Status::Open,
-            assigned: None,
             estimate: Some(Estimate {
                 hours: Some(hours),
                 cost: None,
             }),
-            before: vec![],
-            after: vec![],
-            requires: vec![],
-            tags: vec![],
-            skills: vec![],
-            inputs: vec![],
-            deliverables: vec![],
-            artifacts: vec![],
-            exec: None,
-            not_before: None,
-            created_at: None,
-            started_at: None,
-            completed_at: None,
-            log: vec![],
-            retry_count: 0,
-            max_retries: None,
-            failure_reason: None,
-            model: None,
-            verify: None,
-            agent: None,
-            loop_iteration: 0,
-            ready_after: None,
-            paused: false,
             visibility: "internal".to_string(),
-        cycle_config: None,
+            ..Task::default()
         }
     }
 
@@ -1530,6 +1701,114 @@ mod tests {
         assert!(dot.contains("penwidth"));
     }
 
+    #[test]
+    fn test_map_children_are_grouped() {
+        let mut graph = WorkGraph::new();
+        let mut map = make_task("port", "Port {{item}}");
+        map.status = Status::Done;
+        map.map = Some(workgraph::graph::MapSpec {
+            source: "discover".to_string(),
+            from: "modules.json".to_string(),
+            template: workgraph::trace_function::TaskTemplate {
+                template_id: "port".to_string(),
+                title: "Port {{item}}".to_string(),
+                description: String::new(),
+                skills: vec![],
+                after: vec![],
+                loops_to: vec![],
+                role_hint: None,
+                deliverables: vec![],
+                verify: None,
+                tags: vec![],
+            },
+            children: vec!["port-0".to_string()],
+            join: Some("port-join".to_string()),
+        });
+        let mut child = make_task("port-0", "Port cli");
+        child.after = vec!["port".to_string()];
+        child.parent = Some("port".to_string());
+        let mut join = make_task("port-join", "Join: Port {{item}}");
+        join.after = vec!["port".to_string(), "port-0".to_string()];
+        join.parent = Some("port".to_string());
+        graph.add_node(Node::Task(map));
+        graph.add_node(Node::Task(child));
+        graph.add_node(Node::Task(join));
+
+        let tasks: Vec<_> = graph.tasks().collect();
+        let task_ids: HashSet<&str> = tasks.iter().map(|t| t.id.as_str()).collect();
+        let no_annots = HashMap::new();
+
+        let dot = generate_dot(&graph, &tasks, &task_ids, &HashSet::new(), &no_annots);
+        assert!(dot.contains("subgraph \"cluster_port\" {"));
+        assert!(dot.contains("    \"port-0\";\n    \"port-join\";"));
+
+        let mermaid = generate_mermaid(&graph, &tasks, &task_ids, &HashSet::new(), &no_annots);
+        assert!(
+            mermaid.contains("subgraph map_port[\"map: port\"]\n    port-0\n    port-join\n  end")
+        );
+    }
+
+    fn epic_graph() -> WorkGraph {
+        let mut graph = WorkGraph::new();
+        let child = |id: &str, parent: &str| {
+            let mut t = make_task(id, id);
+            t.parent = Some(parent.to_string());
+            t
+        };
+        graph.add_node(Node::Task(make_task("release", "Release")));
+        graph.add_node(Node::Task(child("auth", "release")));
+        let mut login = child("login", "auth");
+        login.status = Status::Done;
+        graph.add_node(Node::Task(login));
+        graph.add_node(Node::Task(child("logout", "auth")));
+        let mut notes = child("notes", "release");
+        notes.after = vec!["logout".to_string()];
+        graph.add_node(Node::Task(notes));
+        let mut announce = make_task("announce", "Announce");
+        announce.after = vec!["notes".to_string()];
+        graph.add_node(Node::Task(announce));
+        graph
+    }
+
+    #[test]
+    fn test_epics_are_nested_clusters() {
+        let graph = epic_graph();
+        let tasks: Vec<_> = graph.tasks().collect();
+        let task_ids: HashSet<&str> = tasks.iter().map(|t| t.id.as_str()).collect();
+        let no_annots = HashMap::new();
+
+        let dot = generate_dot(&graph, &tasks, &task_ids, &HashSet::new(), &no_annots);
+        assert!(dot.contains(
+            "  subgraph \"cluster_release\" {\n    label=\"epic: release (1/3, 33%)\";\n    \
+             style=dashed;\n    \"auth\";\n    \"notes\";\n    subgraph \"cluster_auth\" {"
+        ));
+        assert!(dot.contains("      \"login\";\n      \"logout\";\n    }\n  }"));
+
+        let mermaid = generate_mermaid(&graph, &tasks, &task_ids, &HashSet::new(), &no_annots);
+        assert!(mermaid.contains(
+            "  subgraph epic_release[\"epic: release (1/3, 33%)\"]\n    auth\n    notes\n    \
+             subgraph epic_auth[\"epic: auth (1/2, 50%)\"]\n      login\n      logout\n    end\n  end"
+        ));
+    }
+
+    #[test]
+    fn test_collapse_epics_redirects_edges() {
+        let graph = epic_graph();
+        let tasks: Vec<_> = graph.tasks().collect();
+        let mut annotations = HashMap::new();
+        let collapsed = collapse_epics(&graph, &tasks, &mut annotations);
+
+        let mut ids: Vec<&str> = collapsed.iter().map(|t| t.id.as_str()).collect();
+        ids.sort_unstable();
+        assert_eq!(ids, vec!["announce", "release"]);
+        let announce = collapsed.iter().find(|t| t.id == "announce").unwrap();
+        assert_eq!(announce.after, vec!["release"]);
+        // Edges between subtasks of the same epic disappear
+        let release = collapsed.iter().find(|t| t.id == "release").unwrap();
+        assert!(release.after.is_empty());
+        assert_eq!(annotations["release"], "[1/3, 33%]");
+    }
+
     #[test]
     fn test_generate_mermaid_basic() {
         let mut graph = WorkGraph::new();
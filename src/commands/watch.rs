@@ -1,7 +1,7 @@
 use anyhow::Result;
 use serde::Serialize;
 use std::collections::HashSet;
-use std::io::{BufRead, BufReader, Seek, SeekFrom, Write};
+use std::io::Write;
 use std::path::Path;
 
 use workgraph::provenance;
@@ -44,7 +44,7 @@ fn event_category(event_type: &str) -> &str {
     }
 }
 
-fn should_include_event(
+pub(super) fn should_include_event(
     event_type: &str,
     event_filters: &HashSet<String>,
     task_filter: Option<&str>,
@@ -89,9 +89,41 @@ pub fn run(
     task_filter: Option<&str>,
     replay: usize,
 ) -> Result<()> {
-    let ops_path = provenance::operations_path(dir);
+    let stdout = std::io::stdout();
+    follow(dir, event_types, task_filter, replay, |event| {
+        let Some(event) = event else {
+            return Ok(());
+        };
+        let json_line = serde_json::to_string(event)?;
+        let mut out = stdout.lock();
+        writeln!(out, "{}", json_line)?;
+        out.flush()
+    })
+}
 
+/// Feed matching events to `emit`: up to `replay` historical events, then
+/// new ones as they are appended to the operations log. `emit` also gets
+/// `None` after every idle poll so streaming callers can send keepalives.
+/// Returns once `emit` fails (e.g. the reader hung up).
+pub fn follow<F>(
+    dir: &Path,
+    event_types: &[String],
+    task_filter: Option<&str>,
+    replay: usize,
+    mut emit: F,
+) -> Result<()>
+where
+    F: FnMut(Option<&WatchEvent>) -> std::io::Result<()>,
+{
     let event_filters: HashSet<String> = event_types.iter().cloned().collect();
+    let matches = |event: &WatchEvent| {
+        should_include_event(
+            &event.event_type,
+            &event_filters,
+            task_filter,
+            event.task_id.as_deref(),
+        )
+    };
 
     // Historical replay
     if replay > 0 {
@@ -99,15 +131,11 @@ pub fn run(
         let start = all_ops.len().saturating_sub(replay);
         for op in &all_ops[start..] {
             if let Some(event) = op_to_watch_event(op)
-                && should_include_event(
-                    &event.event_type,
-                    &event_filters,
-                    task_filter,
-                    event.task_id.as_deref(),
-                ) {
-                    let line = serde_json::to_string(&event)?;
-                    println!("{}", line);
-                }
+                && matches(&event)
+                && emit(Some(&event)).is_err()
+            {
+                return Ok(());
+            }
         }
     }
 
@@ -117,81 +145,34 @@ pub fn run(
         .and_then(|v| v.parse().ok())
         .unwrap_or(500);
 
-    // Seek to end of current file
-    let mut last_pos = if ops_path.exists() {
-        std::fs::metadata(&ops_path)
-            .map(|m| m.len())
-            .unwrap_or(0)
-    } else {
-        0
-    };
-
-    let stdout = std::io::stdout();
+    // Start from the end of the log
+    let mut position = provenance::operations_end(dir)?;
 
     loop {
         std::thread::sleep(std::time::Duration::from_millis(poll_ms));
 
-        if !ops_path.exists() {
-            continue;
+        let (events, new_position) =
+            read_events_since(dir, &position).unwrap_or_else(|_| (Vec::new(), position.clone()));
+        if new_position == position && emit(None).is_err() {
+            // Broken pipe - exit cleanly
+            return Ok(());
         }
-
-        let current_size = match std::fs::metadata(&ops_path) {
-            Ok(m) => m.len(),
-            Err(_) => continue,
-        };
-
-        // Detect file rotation (size reset)
-        if current_size < last_pos {
-            last_pos = 0;
-        }
-
-        if current_size == last_pos {
-            continue;
-        }
-
-        // Read new lines
-        let file = match std::fs::File::open(&ops_path) {
-            Ok(f) => f,
-            Err(_) => continue,
-        };
-
-        let mut reader = BufReader::new(file);
-        if reader.seek(SeekFrom::Start(last_pos)).is_err() {
-            continue;
-        }
-
-        let mut new_pos = last_pos;
-        let mut line = String::new();
-        loop {
-            line.clear();
-            match reader.read_line(&mut line) {
-                Ok(0) => break, // EOF
-                Ok(n) => {
-                    new_pos += n as u64;
-                    let trimmed = line.trim();
-                    if trimmed.is_empty() {
-                        continue;
-                    }
-                    if let Ok(op) = serde_json::from_str::<provenance::OperationEntry>(trimmed)
-                        && let Some(event) = op_to_watch_event(&op)
-                            && should_include_event(
-                                &event.event_type,
-                                &event_filters,
-                                task_filter,
-                                event.task_id.as_deref(),
-                            ) {
-                                let json_line = serde_json::to_string(&event)?;
-                                let mut out = stdout.lock();
-                                if writeln!(out, "{}", json_line).is_err() {
-                                    // Broken pipe - exit cleanly
-                                    return Ok(());
-                                }
-                                let _ = out.flush();
-                            }
-                }
-                Err(_) => break,
+        position = new_position;
+        for event in events.iter().filter(|e| matches(e)) {
+            if emit(Some(event)).is_err() {
+                return Ok(());
             }
         }
-        last_pos = new_pos;
     }
 }
+
+/// Read the events recorded after `from`, including any the log rotated
+/// away since, with the position to resume from.
+pub(super) fn read_events_since(
+    dir: &Path,
+    from: &provenance::LogPosition,
+) -> Result<(Vec<WatchEvent>, provenance::LogPosition)> {
+    let (ops, position) = provenance::read_operations_since(dir, from)?;
+    let events = ops.iter().filter_map(op_to_watch_event).collect();
+    Ok((events, position))
+}
@@ -0,0 +1,664 @@
+//! Outbound webhooks.
+//!
+//! The service daemon runs a worker that tails the operations log from a
+//! cursor kept in `service/webhooks.json`, queues one delivery per matching
+//! `[[webhooks]]` entry in `service/webhook-outbox.jsonl`, and POSTs them,
+//! retrying failures with backoff. Both files outlive the daemon, so events
+//! recorded while it is down go out once it is back. Deliveries that run
+//! out of attempts move to `service/webhook-failed.jsonl`.
+
+use anyhow::{Context, Result};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use serde_json::{Value, json};
+use sha2::{Digest, Sha256};
+use std::collections::{HashMap, HashSet};
+use std::fs;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use workgraph::config::{Config, RetryConfig, WebhookConfig};
+use workgraph::provenance;
+
+use super::service::DaemonLogger;
+use super::watch;
+
+/// How often the daemon's worker looks for new events and due deliveries
+const WORKER_INTERVAL: Duration = Duration::from_secs(1);
+/// How long one delivery may take
+const TIMEOUT: Duration = Duration::from_secs(10);
+
+fn cursor_path(dir: &Path) -> PathBuf {
+    dir.join("service").join("webhooks.json")
+}
+
+fn outbox_path(dir: &Path) -> PathBuf {
+    dir.join("service").join("webhook-outbox.jsonl")
+}
+
+fn failed_path(dir: &Path) -> PathBuf {
+    dir.join("service").join("webhook-failed.jsonl")
+}
+
+/// How far into the operations log events have been queued
+type Cursor = provenance::LogPosition;
+
+/// One event waiting to be delivered to one webhook
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct Delivery {
+    /// Stable across retries, so receivers can drop duplicates
+    id: String,
+    url: String,
+    /// The `wg watch` event being sent
+    event: Value,
+    #[serde(default)]
+    attempts: u32,
+    next_attempt_at: DateTime<Utc>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    last_error: Option<String>,
+}
+
+/// Backoff between attempts: 10s doubling up to an hour, ±20%
+fn backoff(attempts: u32, id: &str) -> chrono::Duration {
+    let config = RetryConfig {
+        base: 10,
+        ..RetryConfig::default()
+    };
+    workgraph::retry::backoff(&config, attempts, workgraph::retry::jitter_sample(id))
+}
+
+fn load_cursor(dir: &Path) -> Option<Cursor> {
+    let content = fs::read_to_string(cursor_path(dir)).ok()?;
+    serde_json::from_str(&content).ok()
+}
+
+fn read_jsonl(path: &Path) -> Result<Vec<Delivery>> {
+    if !path.exists() {
+        return Ok(Vec::new());
+    }
+    let content = fs::read_to_string(path).with_context(|| format!("Failed to read {:?}", path))?;
+    content
+        .lines()
+        .filter(|l| !l.trim().is_empty())
+        .map(|l| serde_json::from_str(l).with_context(|| format!("Corrupt entry in {:?}", path)))
+        .collect()
+}
+
+/// Write `contents` to `path` via a temp file and rename, so a crash mid-write
+/// leaves the previous contents in place.
+fn write_atomic(path: &Path, contents: &str) -> Result<()> {
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    let tmp = path.with_extension("tmp");
+    fs::write(&tmp, contents).with_context(|| format!("Failed to write {:?}", tmp))?;
+    fs::rename(&tmp, path).with_context(|| format!("Failed to replace {:?}", path))?;
+    Ok(())
+}
+
+fn save_outbox(dir: &Path, outbox: &[Delivery]) -> Result<()> {
+    let mut contents = String::new();
+    for delivery in outbox {
+        contents.push_str(&serde_json::to_string(delivery)?);
+        contents.push('\n');
+    }
+    write_atomic(&outbox_path(dir), &contents)
+}
+
+fn delivery_id(url: &str, event: &Value) -> String {
+    let mut hasher = Sha256::new();
+    hasher.update(url.as_bytes());
+    hasher.update(event.to_string().as_bytes());
+    hex(&hasher.finalize()[..8])
+}
+
+fn hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+/// HMAC-SHA256 (RFC 2104)
+fn hmac_sha256(key: &[u8], message: &[u8]) -> [u8; 32] {
+    const BLOCK: usize = 64;
+    let mut block = [0u8; BLOCK];
+    if key.len() > BLOCK {
+        block[..32].copy_from_slice(&Sha256::digest(key));
+    } else {
+        block[..key.len()].copy_from_slice(key);
+    }
+    let pad = |byte: u8| block.map(|k| k ^ byte);
+
+    let inner = Sha256::new()
+        .chain_update(pad(0x36))
+        .chain_update(message)
+        .finalize();
+    Sha256::new()
+        .chain_update(pad(0x5c))
+        .chain_update(inner)
+        .finalize()
+        .into()
+}
+
+/// The `X-Workgraph-Signature` header value for `body`
+fn signature(secret: &str, body: &str) -> String {
+    format!(
+        "sha256={}",
+        hex(&hmac_sha256(secret.as_bytes(), body.as_bytes()))
+    )
+}
+
+/// Queue deliveries for events appended to the operations log since the
+/// last call. The first call only records where the log ends, so enabling
+/// webhooks doesn't replay history. Returns the number queued.
+fn collect(dir: &Path, webhooks: &[WebhookConfig]) -> Result<usize> {
+    let Some(cursor) = load_cursor(dir) else {
+        let end = provenance::operations_end(dir)?;
+        write_atomic(&cursor_path(dir), &serde_json::to_string(&end)?)?;
+        return Ok(0);
+    };
+
+    let (events, position) = watch::read_events_since(dir, &cursor)?;
+    if position == cursor {
+        return Ok(0);
+    }
+
+    let mut queued = Vec::new();
+    let now = Utc::now();
+    for event in &events {
+        let event = serde_json::to_value(event)?;
+        let event_type = event["type"].as_str().unwrap_or_default();
+        for hook in webhooks {
+            let filters: HashSet<String> = hook.events.iter().cloned().collect();
+            if watch::should_include_event(event_type, &filters, None, None) {
+                queued.push(Delivery {
+                    id: delivery_id(&hook.url, &event),
+                    url: hook.url.clone(),
+                    event: event.clone(),
+                    attempts: 0,
+                    next_attempt_at: now,
+                    last_error: None,
+                });
+            }
+        }
+    }
+
+    // Queue before moving the cursor: a crash in between sends an event
+    // twice (with the same id) rather than never.
+    let count = queued.len();
+    if count > 0 {
+        let mut outbox = read_jsonl(&outbox_path(dir))?;
+        outbox.extend(queued);
+        save_outbox(dir, &outbox)?;
+    }
+    write_atomic(&cursor_path(dir), &serde_json::to_string(&position)?)?;
+    Ok(count)
+}
+
+/// POST a signed event. Returns the response status, or why it failed.
+fn send(
+    hook: &WebhookConfig,
+    delivery_id: &str,
+    event: &Value,
+) -> std::result::Result<u16, String> {
+    let mut payload = event.clone();
+    payload["id"] = json!(delivery_id);
+    let body = payload.to_string();
+
+    let mut headers = vec![
+        (
+            "X-Workgraph-Event",
+            event["type"].as_str().unwrap_or_default().to_string(),
+        ),
+        ("X-Workgraph-Delivery", delivery_id.to_string()),
+    ];
+    if let Some(secret) = hook.effective_secret() {
+        headers.push(("X-Workgraph-Signature", signature(&secret, &body)));
+    }
+    post(&hook.url, body, &headers)
+}
+
+#[cfg(feature = "matrix-lite")]
+fn post(url: &str, body: String, headers: &[(&str, String)]) -> std::result::Result<u16, String> {
+    let client = reqwest::blocking::Client::builder()
+        .timeout(TIMEOUT)
+        .user_agent(concat!("workgraph/", env!("CARGO_PKG_VERSION")))
+        .build()
+        .map_err(|e| e.to_string())?;
+    let mut request = client
+        .post(url)
+        .header("Content-Type", "application/json")
+        .body(body);
+    for (name, value) in headers {
+        request = request.header(*name, value);
+    }
+    let response = request.send().map_err(|e| e.to_string())?;
+    let status = response.status();
+    if status.is_success() {
+        Ok(status.as_u16())
+    } else {
+        Err(format!("HTTP {}", status))
+    }
+}
+
+#[cfg(not(feature = "matrix-lite"))]
+fn post(url: &str, _body: String, _headers: &[(&str, String)]) -> std::result::Result<u16, String> {
+    let _ = TIMEOUT;
+    Err(format!(
+        "Cannot POST to {} (built without HTTP support; enable matrix-lite feature)",
+        url
+    ))
+}
+
+/// What one pass over the outbox did
+#[derive(Debug, Default, PartialEq)]
+struct Report {
+    sent: usize,
+    retrying: usize,
+    given_up: usize,
+}
+
+/// Attempt every due delivery. Deliveries to one URL go out in order: once
+/// one is waiting or fails, the rest for that URL wait for the next pass.
+fn deliver_due(
+    dir: &Path,
+    webhooks: &[WebhookConfig],
+    now: DateTime<Utc>,
+    logger: Option<&DaemonLogger>,
+) -> Result<Report> {
+    let outbox = read_jsonl(&outbox_path(dir))?;
+    if outbox.is_empty() {
+        return Ok(Report::default());
+    }
+    let hooks: HashMap<&str, &WebhookConfig> =
+        webhooks.iter().map(|h| (h.url.as_str(), h)).collect();
+
+    let mut report = Report::default();
+    let mut blocked: HashSet<String> = HashSet::new();
+    let mut remaining = Vec::new();
+    let mut failed = Vec::new();
+    for mut delivery in outbox {
+        let Some(hook) = hooks.get(delivery.url.as_str()) else {
+            if let Some(logger) = logger {
+                logger.warn(&format!(
+                    "Webhook {} is no longer configured; dropping delivery {}",
+                    delivery.url, delivery.id
+                ));
+            }
+            continue;
+        };
+        if blocked.contains(&delivery.url) || delivery.next_attempt_at > now {
+            blocked.insert(delivery.url.clone());
+            remaining.push(delivery);
+            continue;
+        }
+
+        delivery.attempts += 1;
+        match send(hook, &delivery.id, &delivery.event) {
+            Ok(_) => report.sent += 1,
+            Err(e) => {
+                blocked.insert(delivery.url.clone());
+                delivery.last_error = Some(e.clone());
+                if delivery.attempts >= hook.max_attempts {
+                    if let Some(logger) = logger {
+                        logger.error(&format!(
+                            "Webhook {} delivery {} failed {} times, giving up: {}",
+                            delivery.url, delivery.id, delivery.attempts, e
+                        ));
+                    }
+                    report.given_up += 1;
+                    failed.push(delivery);
+                } else {
+                    delivery.next_attempt_at = now + backoff(delivery.attempts, &delivery.id);
+                    if let Some(logger) = logger {
+                        logger.warn(&format!(
+                            "Webhook {} delivery {} failed (attempt {}), retrying at {}: {}",
+                            delivery.url,
+                            delivery.id,
+                            delivery.attempts,
+                            delivery.next_attempt_at.to_rfc3339(),
+                            e
+                        ));
+                    }
+                    report.retrying += 1;
+                    remaining.push(delivery);
+                }
+            }
+        }
+    }
+
+    if !failed.is_empty() {
+        let mut file = fs::OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(failed_path(dir))?;
+        for delivery in &failed {
+            writeln!(file, "{}", serde_json::to_string(delivery)?)?;
+        }
+    }
+    save_outbox(dir, &remaining)?;
+    Ok(report)
+}
+
+/// Start the daemon's webhook worker. It re-reads `[[webhooks]]` on every
+/// pass, so `config.toml` edits apply without a restart.
+pub fn spawn_worker(dir: PathBuf, logger: DaemonLogger) {
+    std::thread::spawn(move || {
+        loop {
+            let webhooks = Config::load_or_default(&dir).webhooks;
+            let pass = collect(&dir, &webhooks)
+                .and_then(|_| deliver_due(&dir, &webhooks, Utc::now(), Some(&logger)));
+            match pass {
+                Ok(report) if report.sent > 0 => {
+                    logger.info(&format!("Delivered {} webhook event(s)", report.sent));
+                }
+                Ok(_) => {}
+                Err(e) => logger.error(&format!("Webhook worker error: {:#}", e)),
+            }
+            std::thread::sleep(WORKER_INTERVAL);
+        }
+    });
+}
+
+/// Send a sample event to each configured webhook (or just `url`) right
+/// away, bypassing the outbox.
+pub fn run_test(dir: &Path, url: Option<&str>, event_type: &str, json: bool) -> Result<()> {
+    let config = Config::load_or_default(dir);
+    let hooks: Vec<&WebhookConfig> = config
+        .webhooks
+        .iter()
+        .filter(|h| url.is_none_or(|u| h.url == u))
+        .collect();
+    if hooks.is_empty() {
+        match url {
+            Some(u) => anyhow::bail!("No webhook with url '{}' in config.toml", u),
+            None => {
+                anyhow::bail!("No webhooks configured; add a [[webhooks]] entry to config.toml")
+            }
+        }
+    }
+
+    let event = json!({
+        "type": event_type,
+        "timestamp": Utc::now().to_rfc3339(),
+        "task_id": "sample-task",
+        "data": { "title": "Sample task", "test": true },
+    });
+    let mut results = Vec::new();
+    for hook in hooks {
+        let id = format!("test-{}", delivery_id(&hook.url, &event));
+        let result = send(hook, &id, &event);
+        results.push(json!({
+            "url": hook.url,
+            "ok": result.is_ok(),
+            "status": result.as_ref().ok(),
+            "error": result.as_ref().err(),
+            "signed": hook.effective_secret().is_some(),
+        }));
+    }
+
+    let all_ok = results.iter().all(|r| r["ok"] == json!(true));
+    if json {
+        println!("{}", serde_json::to_string_pretty(&results)?);
+    } else {
+        for r in &results {
+            match r["error"].as_str() {
+                None => println!(
+                    "{}: delivered (HTTP {})",
+                    r["url"].as_str().unwrap_or(""),
+                    r["status"]
+                ),
+                Some(e) => println!("{}: failed: {}", r["url"].as_str().unwrap_or(""), e),
+            }
+        }
+    }
+    if !all_ok {
+        anyhow::bail!("Some test deliveries failed");
+    }
+    Ok(())
+}
+
+/// List configured webhooks with their queued and failed deliveries.
+pub fn run_list(dir: &Path, json: bool) -> Result<()> {
+    let config = Config::load_or_default(dir);
+    let outbox = read_jsonl(&outbox_path(dir))?;
+    let failed = read_jsonl(&failed_path(dir))?;
+
+    let rows: Vec<Value> = config
+        .webhooks
+        .iter()
+        .map(|hook| {
+            let queued: Vec<&Delivery> = outbox.iter().filter(|d| d.url == hook.url).collect();
+            json!({
+                "url": hook.url,
+                "events": hook.events,
+                "signed": hook.effective_secret().is_some(),
+                "max_attempts": hook.max_attempts,
+                "queued": queued.len(),
+                "last_error": queued.iter().find_map(|d| d.last_error.as_deref()),
+                "failed": failed.iter().filter(|d| d.url == hook.url).count(),
+            })
+        })
+        .collect();
+
+    if json {
+        println!("{}", serde_json::to_string_pretty(&rows)?);
+        return Ok(());
+    }
+    if rows.is_empty() {
+        println!("No webhooks configured");
+        return Ok(());
+    }
+    for row in &rows {
+        let events: Vec<&str> = row["events"]
+            .as_array()
+            .into_iter()
+            .flatten()
+            .filter_map(|e| e.as_str())
+            .collect();
+        println!(
+            "{} [{}]{}",
+            row["url"].as_str().unwrap_or(""),
+            events.join(", "),
+            if row["signed"] == json!(true) {
+                " (signed)"
+            } else {
+                ""
+            }
+        );
+        println!("  Queued: {}, failed: {}", row["queued"], row["failed"]);
+        if let Some(e) = row["last_error"].as_str() {
+            println!("  Last error: {}", e);
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::{BufRead, BufReader, Read};
+    use std::net::TcpListener;
+
+    fn hook(url: &str, events: &[&str]) -> WebhookConfig {
+        WebhookConfig {
+            url: url.to_string(),
+            events: events.iter().map(|e| e.to_string()).collect(),
+            secret: Some("s3cret".to_string()),
+            secret_env: None,
+            max_attempts: 2,
+        }
+    }
+
+    fn record(dir: &Path, op: &str, task_id: &str) {
+        provenance::record(
+            dir,
+            op,
+            Some(task_id),
+            None,
+            json!({}),
+            provenance::DEFAULT_ROTATION_THRESHOLD,
+        )
+        .unwrap();
+    }
+
+    #[test]
+    fn test_hmac_sha256_matches_rfc4231() {
+        assert_eq!(
+            hex(&hmac_sha256(b"Jefe", b"what do ya want for nothing?")),
+            "5bdcc146bf60754e6a042426089575c75a003f089d2739839dec58b964ec3843"
+        );
+    }
+
+    #[test]
+    fn test_collect_queues_matching_events_once() {
+        let tmp = tempfile::tempdir().unwrap();
+        let dir = tmp.path();
+        let hooks = [
+            hook("http://a.invalid", &["all"]),
+            hook("http://b.invalid", &["task.completed"]),
+        ];
+        record(dir, "add_task", "old");
+        // The first pass only marks where history ends
+        assert_eq!(collect(dir, &hooks).unwrap(), 0);
+
+        record(dir, "add_task", "t1");
+        record(dir, "done", "t1");
+        record(dir, "pause", "t1");
+        assert_eq!(collect(dir, &hooks).unwrap(), 3);
+        assert_eq!(collect(dir, &hooks).unwrap(), 0);
+
+        let outbox = read_jsonl(&outbox_path(dir)).unwrap();
+        let to_b: Vec<_> = outbox
+            .iter()
+            .filter(|d| d.url == "http://b.invalid")
+            .collect();
+        assert_eq!(to_b.len(), 1);
+        assert_eq!(to_b[0].event["type"], "task.completed");
+        assert_eq!(to_b[0].event["task_id"], "t1");
+    }
+
+    #[test]
+    fn test_collect_follows_the_log_across_rotation() {
+        let tmp = tempfile::tempdir().unwrap();
+        let dir = tmp.path();
+        let hooks = [hook("http://a.invalid", &["task.created"])];
+        record(dir, "add_task", "old");
+        assert_eq!(collect(dir, &hooks).unwrap(), 0);
+
+        // Rotate the log several times, archiving events not yet queued
+        for i in 0..10 {
+            provenance::record(
+                dir,
+                "add_task",
+                Some(&format!("t{}", i)),
+                None,
+                json!({}),
+                150,
+            )
+            .unwrap();
+        }
+        assert_eq!(collect(dir, &hooks).unwrap(), 10);
+        record(dir, "add_task", "t10");
+        assert_eq!(collect(dir, &hooks).unwrap(), 1);
+
+        let outbox = read_jsonl(&outbox_path(dir)).unwrap();
+        let ids: Vec<&str> = outbox
+            .iter()
+            .map(|d| d.event["task_id"].as_str().unwrap())
+            .collect();
+        let expected: Vec<String> = (0..=10).map(|i| format!("t{}", i)).collect();
+        assert_eq!(ids, expected);
+    }
+
+    #[test]
+    fn test_failed_deliveries_back_off_then_give_up() {
+        let tmp = tempfile::tempdir().unwrap();
+        let dir = tmp.path();
+        // Nothing listens on a port we bound and released
+        let port = TcpListener::bind("127.0.0.1:0")
+            .unwrap()
+            .local_addr()
+            .unwrap()
+            .port();
+        let hooks = [hook(&format!("http://127.0.0.1:{}/hook", port), &["all"])];
+        collect(dir, &hooks).unwrap();
+        record(dir, "done", "t1");
+        record(dir, "fail", "t2");
+        collect(dir, &hooks).unwrap();
+
+        let now = Utc::now();
+        let report = deliver_due(dir, &hooks, now, None).unwrap();
+        // The second delivery waits behind the first
+        assert_eq!(
+            report,
+            Report {
+                sent: 0,
+                retrying: 1,
+                given_up: 0
+            }
+        );
+        let outbox = read_jsonl(&outbox_path(dir)).unwrap();
+        assert_eq!(outbox.len(), 2);
+        assert!(outbox[0].next_attempt_at > now);
+        assert!(outbox[0].last_error.is_some());
+
+        // Not due yet: nothing is attempted
+        let report = deliver_due(dir, &hooks, now, None).unwrap();
+        assert_eq!(report, Report::default());
+
+        let later = now + chrono::Duration::hours(2);
+        let report = deliver_due(dir, &hooks, later, None).unwrap();
+        assert_eq!(report.given_up, 1);
+        assert_eq!(read_jsonl(&failed_path(dir)).unwrap().len(), 1);
+        assert_eq!(read_jsonl(&outbox_path(dir)).unwrap().len(), 1);
+    }
+
+    #[cfg(feature = "matrix-lite")]
+    #[test]
+    fn test_delivery_is_signed() {
+        let tmp = tempfile::tempdir().unwrap();
+        let dir = tmp.path();
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let hooks = [hook(
+            &format!("http://{}/hook", listener.local_addr().unwrap()),
+            &["task_state"],
+        )];
+
+        let server = std::thread::spawn(move || {
+            let (mut stream, _) = listener.accept().unwrap();
+            let mut reader = BufReader::new(stream.try_clone().unwrap());
+            let mut headers = Vec::new();
+            let mut line = String::new();
+            while reader.read_line(&mut line).unwrap() > 2 {
+                headers.push(line.trim().to_lowercase());
+                line.clear();
+            }
+            let length: usize = headers
+                .iter()
+                .find_map(|h| h.strip_prefix("content-length: "))
+                .unwrap()
+                .parse()
+                .unwrap();
+            let mut body = vec![0; length];
+            reader.read_exact(&mut body).unwrap();
+            stream
+                .write_all(b"HTTP/1.1 204 No Content\r\nContent-Length: 0\r\n\r\n")
+                .unwrap();
+            (headers, String::from_utf8(body).unwrap())
+        });
+
+        collect(dir, &hooks).unwrap();
+        record(dir, "claim", "t1");
+        collect(dir, &hooks).unwrap();
+        let report = deliver_due(dir, &hooks, Utc::now(), None).unwrap();
+        assert_eq!(report.sent, 1);
+        assert!(read_jsonl(&outbox_path(dir)).unwrap().is_empty());
+
+        let (headers, body) = server.join().unwrap();
+        let payload: Value = serde_json::from_str(&body).unwrap();
+        assert_eq!(payload["type"], "task.started");
+        assert!(headers.contains(&"x-workgraph-event: task.started".to_string()));
+        let expected = format!("x-workgraph-signature: {}", signature("s3cret", &body));
+        assert!(headers.contains(&expected), "{:?}", headers);
+    }
+}
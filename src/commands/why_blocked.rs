@@ -2,13 +2,17 @@ use anyhow::Result;
 use std::collections::HashSet;
 use std::path::Path;
 use workgraph::WorkGraph;
-use workgraph::graph::{Status, Task};
+use workgraph::bindings::Unresolved;
+use workgraph::capacity::{Capacity, ResourceWait};
+use workgraph::graph::{EdgeCondition, Status, Task};
 
 /// Information about a blocking chain node
 #[derive(Debug, Clone)]
 struct BlockingNode {
     id: String,
     status: Status,
+    /// Condition on the edge from the dependent task to this one
+    condition: EdgeCondition,
     children: Vec<BlockingNode>,
 }
 
@@ -32,26 +36,54 @@ pub fn run(dir: &Path, id: &str, json: bool) -> Result<()> {
     let mut root_blocker_ids = HashSet::new();
     collect_root_blockers(&graph, &blocking_tree, &mut root_blocker_ids);
 
-    let root_blockers: Vec<RootBlocker> = root_blocker_ids
+    // Resources the task needs that other tasks are holding
+    let capacity = Capacity::of(&graph);
+    let resource_waits = match task.status {
+        Status::Open | Status::Blocked => capacity.waits(task),
+        _ => Vec::new(),
+    };
+
+    // Input bindings that would be left unsubstituted if it ran now
+    let unresolved_bindings = match task.status {
+        Status::Open | Status::Blocked => workgraph::bindings::resolve(&graph, task).1,
+        _ => Vec::new(),
+    };
+
+    let mut root_blockers: Vec<RootBlocker> = root_blocker_ids
         .iter()
         .filter_map(|rid| {
             // For remote refs, we can't get a &Task, but the blocking tree already
             // has the status. Root blockers from remote peers are only shown in the
             // tree; they won't appear here (since graph.get_task won't find them).
             graph.get_task(rid).map(|t| {
-                let is_ready = is_task_ready(&graph, t, dir);
+                let is_ready = is_task_ready(&graph, t, dir) && capacity.admits(t);
                 RootBlocker { task: t, is_ready }
             })
         })
         .collect();
+    root_blockers.sort_by(|a, b| a.task.id.cmp(&b.task.id));
 
     // Count total blocking tasks
     let total_blockers = count_blockers(&blocking_tree);
 
     if json {
-        print_json(task, &blocking_tree, &root_blockers, total_blockers)?;
+        print_json(
+            task,
+            &blocking_tree,
+            &root_blockers,
+            &resource_waits,
+            &unresolved_bindings,
+            total_blockers,
+        )?;
     } else {
-        print_human(task, &blocking_tree, &root_blockers, total_blockers);
+        print_human(
+            task,
+            &blocking_tree,
+            &root_blockers,
+            &resource_waits,
+            &unresolved_bindings,
+            total_blockers,
+        );
     }
 
     Ok(())
@@ -69,6 +101,7 @@ fn build_blocking_tree(
     let mut node = BlockingNode {
         id: task_id.to_string(),
         status,
+        condition: EdgeCondition::Any,
         children: vec![],
     };
 
@@ -83,6 +116,7 @@ fn build_blocking_tree(
             if visited.contains(blocker_id) {
                 continue;
             }
+            let condition = task.after_condition(blocker_id);
 
             if let Some((_peer_name, _remote_task_id)) =
                 workgraph::federation::parse_remote_ref(blocker_id)
@@ -93,18 +127,20 @@ fn build_blocking_tree(
                     _remote_task_id,
                     dir,
                 );
-                if !remote.status.is_terminal() {
+                if !condition.is_satisfied_by(remote.status) {
                     let child = BlockingNode {
                         id: blocker_id.clone(),
                         status: remote.status,
+                        condition,
                         children: vec![], // Don't recurse into remote graphs
                     };
                     node.children.push(child);
                 }
             } else if let Some(blocker) = graph.get_task(blocker_id) {
                 // Local dependency — only include if still actively blocking
-                if !blocker.status.is_terminal() {
-                    let child = build_blocking_tree(graph, blocker_id, visited, dir);
+                if !condition.is_satisfied_by(blocker.status) {
+                    let mut child = build_blocking_tree(graph, blocker_id, visited, dir);
+                    child.condition = condition;
                     node.children.push(child);
                 }
             }
@@ -135,7 +171,12 @@ fn is_task_ready(graph: &WorkGraph, task: &Task, dir: &Path) -> bool {
         return false;
     }
     task.after.iter().all(|blocker_id| {
-        workgraph::query::is_blocker_satisfied(blocker_id, graph, Some(dir))
+        workgraph::query::is_blocker_satisfied(
+            blocker_id,
+            task.after_condition(blocker_id),
+            graph,
+            Some(dir),
+        )
     })
 }
 
@@ -156,13 +197,29 @@ fn count_blockers_recursive(node: &BlockingNode, count: &mut usize, visited: &mu
     }
 }
 
-fn print_human(task: &Task, tree: &BlockingNode, root_blockers: &[RootBlocker], total: usize) {
+fn print_human(
+    task: &Task,
+    tree: &BlockingNode,
+    root_blockers: &[RootBlocker],
+    resource_waits: &[ResourceWait],
+    bindings: &[Unresolved],
+    total: usize,
+) {
     println!("Task: {}", task.id);
 
     if tree.children.is_empty() {
-        println!("Status: {:?}", task.status);
-        println!();
-        println!("{} has no blockers.", task.id);
+        if resource_waits.is_empty() && bindings.is_empty() {
+            println!("Status: {:?}", task.status);
+            println!();
+            println!("{} has no blockers.", task.id);
+        } else if resource_waits.is_empty() {
+            println!("Status: waiting for input bindings");
+            print_unresolved_bindings(bindings);
+        } else {
+            println!("Status: waiting for resources");
+            print_resource_waits(resource_waits);
+            print_unresolved_bindings(bindings);
+        }
         return;
     }
 
@@ -216,6 +273,38 @@ fn print_human(task: &Task, tree: &BlockingNode, root_blockers: &[RootBlocker],
             ids.join(" or ")
         );
     }
+
+    if !resource_waits.is_empty() {
+        print_resource_waits(resource_waits);
+    }
+    print_unresolved_bindings(bindings);
+}
+
+fn print_unresolved_bindings(bindings: &[Unresolved]) {
+    if bindings.is_empty() {
+        return;
+    }
+    println!();
+    println!("Input bindings without a value:");
+    for binding in bindings {
+        if binding.is_pending() {
+            println!("  - {}", binding);
+        } else {
+            println!("  - {} <-- ACTION NEEDED", binding);
+        }
+    }
+}
+
+fn print_resource_waits(waits: &[ResourceWait]) {
+    println!();
+    println!("Waiting for resources:");
+    for wait in waits {
+        if wait.is_impossible() {
+            println!("  - {} <-- CAN NEVER BE MET", wait);
+        } else {
+            println!("  - {}", wait);
+        }
+    }
 }
 
 fn print_tree(node: &BlockingNode, prefix: &str, depth: usize) {
@@ -224,8 +313,13 @@ fn print_tree(node: &BlockingNode, prefix: &str, depth: usize) {
         println!("{}", node.id);
     } else {
         // Child node - print with tree connector and status
-        let status_str = format!("(status: {:?})", node.status);
-        let root_marker = if node.children.is_empty() && !node.status.is_terminal() {
+        let status_str = match node.condition {
+            EdgeCondition::Any => format!("(status: {:?})", node.status),
+            condition => format!("(status: {:?}, needs {})", node.status, condition),
+        };
+        let root_marker = if node.condition.is_unsatisfiable_by(node.status) {
+            " <-- CONDITION CAN NEVER BE MET"
+        } else if node.children.is_empty() && !node.status.is_terminal() {
             " <-- ROOT CAUSE"
         } else {
             ""
@@ -252,6 +346,8 @@ fn print_json(
     task: &Task,
     tree: &BlockingNode,
     root_blockers: &[RootBlocker],
+    resource_waits: &[ResourceWait],
+    bindings: &[Unresolved],
     total: usize,
 ) -> Result<()> {
     let output = serde_json::json!({
@@ -260,7 +356,9 @@ fn print_json(
             "title": task.title,
             "status": task.status,
         },
-        "is_blocked": !tree.children.is_empty(),
+        "is_blocked": !tree.children.is_empty()
+            || !resource_waits.is_empty()
+            || !bindings.is_empty(),
         "blocking_chain": tree_to_json(tree),
         "root_blockers": root_blockers.iter().map(|rb| {
             serde_json::json!({
@@ -271,6 +369,16 @@ fn print_json(
                 "is_ready": rb.is_ready,
             })
         }).collect::<Vec<_>>(),
+        "resource_waits": resource_waits,
+        "unresolved_bindings": bindings.iter().map(|b| {
+            serde_json::json!({
+                "placeholder": b.binding.placeholder,
+                "task": b.binding.task_id,
+                "key": b.binding.key,
+                "pending": b.is_pending(),
+                "problem": b.to_string(),
+            })
+        }).collect::<Vec<_>>(),
         "total_blockers": total,
     });
     println!("{}", serde_json::to_string_pretty(&output)?);
@@ -281,6 +389,7 @@ fn tree_to_json(node: &BlockingNode) -> serde_json::Value {
     serde_json::json!({
         "id": node.id,
         "status": format!("{:?}", node.status),
+        "condition": node.condition,
         "after": node.children.iter().map(tree_to_json).collect::<Vec<_>>(),
     })
 }
@@ -41,6 +41,34 @@ pub struct Config {
     /// Replay configuration
     #[serde(default)]
     pub replay: ReplayConfig,
+
+    /// Graph storage configuration
+    #[serde(default)]
+    pub storage: StorageConfig,
+
+    /// Automatic retry configuration
+    #[serde(default)]
+    pub retry: RetryConfig,
+
+    /// Git worktree isolation for spawned agents
+    #[serde(default)]
+    pub worktree: WorktreeConfig,
+
+    /// HTTP/JSON API served by the service daemon
+    #[serde(default)]
+    pub http: HttpConfig,
+
+    /// Outbound webhooks the service daemon POSTs events to
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub webhooks: Vec<WebhookConfig>,
+
+    /// Who may run which chat commands from Matrix
+    #[serde(default)]
+    pub matrix: MatrixAclConfig,
+
+    /// Email delivery for human agents with `executor = "email"`
+    #[serde(default)]
+    pub email: EmailConfig,
 }
 
 /// Help display configuration
@@ -69,16 +97,24 @@ pub struct LogConfig {
     /// Rotation threshold in bytes (default: 10 MB)
     #[serde(default = "default_rotation_threshold")]
     pub rotation_threshold: u64,
+    /// Record field-level graph history in log/history.jsonl (default: true)
+    #[serde(default = "default_history")]
+    pub history: bool,
 }
 
 fn default_rotation_threshold() -> u64 {
     10 * 1024 * 1024 // 10 MB
 }
 
+fn default_history() -> bool {
+    true
+}
+
 impl Default for LogConfig {
     fn default() -> Self {
         Self {
             rotation_threshold: default_rotation_threshold(),
+            history: true,
         }
     }
 }
@@ -108,6 +144,369 @@ impl Default for ReplayConfig {
     }
 }
 
+/// Graph storage configuration
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StorageConfig {
+    /// Storage backend: "jsonl" (default, graph.jsonl) or "sqlite" (graph.db).
+    /// Switch with `wg migrate-storage` so existing data is carried over.
+    #[serde(default = "default_storage_backend")]
+    pub backend: String,
+
+    /// Append mutations to a write-ahead journal next to graph.jsonl instead
+    /// of rewriting the whole file on every save (jsonl backend only)
+    #[serde(default)]
+    pub journal: bool,
+
+    /// Number of journal entries after which the journal is compacted into
+    /// the graph.jsonl snapshot (default: 500)
+    #[serde(default = "default_compact_threshold")]
+    pub compact_threshold: usize,
+}
+
+fn default_storage_backend() -> String {
+    "jsonl".to_string()
+}
+
+fn default_compact_threshold() -> usize {
+    500
+}
+
+impl Default for StorageConfig {
+    fn default() -> Self {
+        Self {
+            backend: default_storage_backend(),
+            journal: false,
+            compact_threshold: default_compact_threshold(),
+        }
+    }
+}
+
+/// Automatic retry configuration.
+///
+/// The coordinator retries failed tasks that have `max_retries` set when the
+/// failure is classified as transient (see [`crate::retry`]).
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RetryConfig {
+    /// Retry failed tasks automatically (default: true)
+    #[serde(default = "default_retry_auto")]
+    pub auto: bool,
+
+    /// Delay before the first retry, in seconds (default: 30)
+    #[serde(default = "default_retry_base")]
+    pub base: u64,
+
+    /// Factor applied to the delay for each further attempt (default: 2.0)
+    #[serde(default = "default_retry_multiplier")]
+    pub multiplier: f64,
+
+    /// Random spread of each delay as a fraction, e.g. 0.2 for ±20% (default: 0.2)
+    #[serde(default = "default_retry_jitter")]
+    pub jitter: f64,
+
+    /// Upper bound on the delay, in seconds (default: 3600)
+    #[serde(default = "default_retry_cap")]
+    pub cap: u64,
+
+    /// Agent exit codes that mark a failure transient
+    #[serde(default)]
+    pub transient_exit_codes: Vec<i32>,
+
+    /// Agent exit codes that mark a failure permanent
+    #[serde(default)]
+    pub permanent_exit_codes: Vec<i32>,
+
+    /// Regexes on `failure_reason` that mark a failure transient
+    #[serde(default = "default_transient_patterns")]
+    pub transient_patterns: Vec<String>,
+
+    /// Regexes on `failure_reason` that mark a failure permanent
+    #[serde(default)]
+    pub permanent_patterns: Vec<String>,
+
+    /// Ask the triage model about failures no rule matches: "restart" and
+    /// "continue" verdicts count as transient (default: false)
+    #[serde(default)]
+    pub triage: bool,
+
+    /// Class of failures nothing else classifies: "transient" or "permanent"
+    /// (default: permanent)
+    #[serde(default)]
+    pub default: crate::retry::FailureClass,
+}
+
+fn default_retry_auto() -> bool {
+    true
+}
+
+fn default_retry_base() -> u64 {
+    30
+}
+
+fn default_retry_multiplier() -> f64 {
+    2.0
+}
+
+fn default_retry_jitter() -> f64 {
+    0.2
+}
+
+fn default_retry_cap() -> u64 {
+    3600
+}
+
+fn default_transient_patterns() -> Vec<String> {
+    [
+        r"(?i)rate.?limit",
+        r"(?i)too many requests|\b429\b",
+        r"(?i)overloaded|\b50[234]\b",
+        r"(?i)timed? ?out",
+        r"(?i)connection (reset|refused|closed)|network",
+        r"(?i)temporar(y|ily)",
+    ]
+    .into_iter()
+    .map(String::from)
+    .collect()
+}
+
+impl Default for RetryConfig {
+    fn default() -> Self {
+        Self {
+            auto: default_retry_auto(),
+            base: default_retry_base(),
+            multiplier: default_retry_multiplier(),
+            jitter: default_retry_jitter(),
+            cap: default_retry_cap(),
+            transient_exit_codes: vec![],
+            permanent_exit_codes: vec![],
+            transient_patterns: default_transient_patterns(),
+            permanent_patterns: vec![],
+            triage: false,
+            default: crate::retry::FailureClass::default(),
+        }
+    }
+}
+
+/// Git worktree isolation for spawned agents.
+///
+/// When enabled, each spawned agent works in its own worktree on a
+/// `wg/<task-id>` branch, merged into the integration branch on `wg done`
+/// (see [`crate::worktree`]).
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+pub struct WorktreeConfig {
+    /// Give each spawned agent its own worktree (default: false)
+    #[serde(default)]
+    pub enabled: bool,
+
+    /// Branch finished task branches are integrated into (default: the
+    /// branch checked out in the project root)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub integration_branch: Option<String>,
+
+    /// How task branches are integrated: "merge" or "rebase" (default: merge)
+    #[serde(default)]
+    pub strategy: crate::worktree::Strategy,
+}
+
+/// HTTP/JSON API served by the service daemon alongside its Unix socket.
+///
+/// Disabled unless `bind` is set (or `wg service start --port` is given).
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+pub struct HttpConfig {
+    /// Address to listen on, e.g. "127.0.0.1:8787"
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub bind: Option<String>,
+
+    /// Bearer token clients must send. Prefer the `WG_HTTP_TOKEN`
+    /// environment variable over committing a token to config.toml.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub token: Option<String>,
+}
+
+impl HttpConfig {
+    /// The bearer token in effect: `WG_HTTP_TOKEN`, then `token`.
+    pub fn effective_token(&self) -> Option<String> {
+        std::env::var("WG_HTTP_TOKEN")
+            .ok()
+            .filter(|t| !t.is_empty())
+            .or_else(|| self.token.clone().filter(|t| !t.is_empty()))
+    }
+}
+
+/// An outbound webhook: the service daemon POSTs each matching `wg watch`
+/// event to `url` as JSON, retrying failed deliveries with backoff.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WebhookConfig {
+    /// Endpoint to POST events to
+    pub url: String,
+
+    /// Event types or categories to send, as for `wg watch --event`
+    /// (default: all)
+    #[serde(default = "default_webhook_events")]
+    pub events: Vec<String>,
+
+    /// Secret for the `X-Workgraph-Signature` HMAC. Prefer `secret_env`
+    /// over committing a secret to config.toml.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub secret: Option<String>,
+
+    /// Environment variable holding the signing secret
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub secret_env: Option<String>,
+
+    /// Delivery attempts before an event is given up on (default: 10)
+    #[serde(default = "default_webhook_max_attempts")]
+    pub max_attempts: u32,
+}
+
+fn default_webhook_events() -> Vec<String> {
+    vec!["all".to_string()]
+}
+
+fn default_webhook_max_attempts() -> u32 {
+    10
+}
+
+impl WebhookConfig {
+    /// The signing secret in effect: `secret_env`'s variable, then `secret`.
+    pub fn effective_secret(&self) -> Option<String> {
+        self.secret_env
+            .as_deref()
+            .and_then(|var| std::env::var(var).ok())
+            .filter(|s| !s.is_empty())
+            .or_else(|| self.secret.clone().filter(|s| !s.is_empty()))
+    }
+}
+
+/// Access control for commands sent from Matrix rooms.
+///
+/// With no `users` and no `default`, every sender keeps full access, as
+/// before the ACL existed. Once any user is listed, unlisted senders fall
+/// back to `default` (read-only unless set).
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+pub struct MatrixAclConfig {
+    /// Permission for senders not listed in `users`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub default: Option<MatrixPermission>,
+
+    /// Per-user mappings to workgraph actors and permissions
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub users: Vec<MatrixUserAcl>,
+}
+
+impl MatrixAclConfig {
+    /// Permission for a sender with no entry in `users`.
+    pub fn default_permission(&self) -> MatrixPermission {
+        self.default.unwrap_or(if self.users.is_empty() {
+            MatrixPermission::Admin
+        } else {
+            MatrixPermission::ReadOnly
+        })
+    }
+
+    /// The entry for a Matrix user ID, compared case-insensitively.
+    pub fn user(&self, user_id: &str) -> Option<&MatrixUserAcl> {
+        self.users
+            .iter()
+            .find(|u| u.user.eq_ignore_ascii_case(user_id))
+    }
+}
+
+/// One Matrix user's entry in the `[matrix]` ACL.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MatrixUserAcl {
+    /// Full Matrix user ID, e.g. "@erik:example.org"
+    pub user: String,
+
+    /// Workgraph actor the user acts as (default: the ID's localpart)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub actor: Option<String>,
+
+    /// What the user may do
+    pub permission: MatrixPermission,
+
+    /// Only allow changes to tasks carrying one of these tags (default: all)
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub tags: Vec<String>,
+}
+
+/// Permission levels for Matrix commands, each including the ones before it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum MatrixPermission {
+    /// `status`, `ready` and `help` only
+    ReadOnly,
+    /// Claim tasks for oneself; unclaim, log, finish or fail one's own tasks
+    ClaimOwn,
+    /// Also log, finish or fail any task
+    Complete,
+    /// Also claim for other actors and release others' claims
+    Admin,
+}
+
+impl std::fmt::Display for MatrixPermission {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            MatrixPermission::ReadOnly => "read-only",
+            MatrixPermission::ClaimOwn => "claim-own",
+            MatrixPermission::Complete => "complete",
+            MatrixPermission::Admin => "admin",
+        })
+    }
+}
+
+/// Email for human agents: tasks go out over SMTP and replies are read
+/// back from a Maildir. Nothing is sent until `smtp_host` and `from` are set.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EmailConfig {
+    /// SMTP server to send through, e.g. a local relay
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub smtp_host: Option<String>,
+
+    /// SMTP port (default: 25)
+    #[serde(default = "default_smtp_port")]
+    pub smtp_port: u16,
+
+    /// Sender address; replies to it should be delivered to `maildir`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub from: Option<String>,
+
+    /// Maildir that replies arrive in ("~/" is expanded)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub maildir: Option<String>,
+}
+
+fn default_smtp_port() -> u16 {
+    25
+}
+
+impl Default for EmailConfig {
+    fn default() -> Self {
+        Self {
+            smtp_host: None,
+            smtp_port: default_smtp_port(),
+            from: None,
+            maildir: None,
+        }
+    }
+}
+
+impl EmailConfig {
+    /// True once there is a server to send through and an address to send from.
+    pub fn can_send(&self) -> bool {
+        self.smtp_host.as_deref().is_some_and(|h| !h.is_empty())
+            && self.from.as_deref().is_some_and(|f| !f.is_empty())
+    }
+
+    /// The reply Maildir, with a leading "~/" expanded.
+    pub fn maildir_path(&self) -> Option<PathBuf> {
+        let dir = self.maildir.as_deref().filter(|d| !d.is_empty())?;
+        match dir.strip_prefix("~/").zip(dirs::home_dir()) {
+            Some((rest, home)) => Some(home.join(rest)),
+            None => Some(PathBuf::from(dir)),
+        }
+    }
+}
+
 /// Agency (evolutionary identity system) configuration
 #[derive(Debug, Clone, Serialize, Deserialize, Default)]
 pub struct AgencyConfig {
@@ -223,6 +622,26 @@ pub struct CoordinatorConfig {
     /// Overrides agent.model when set. Can be further overridden by CLI --model.
     #[serde(default)]
     pub model: Option<String>,
+
+    /// Seconds between SIGTERM and SIGKILL when an agent exceeds its timeout
+    #[serde(default = "default_timeout_grace")]
+    pub timeout_grace: u64,
+
+    /// Order ready tasks are dispatched in: "fifo", "priority",
+    /// "critical-path-first", "shortest-estimate" or "weighted-fair"
+    /// (default: priority)
+    #[serde(default)]
+    pub dispatch: crate::dispatch::DispatchPolicy,
+
+    /// What weighted-fair dispatch shares agents across: "tag" or
+    /// "requester" (default: tag)
+    #[serde(default)]
+    pub fair_share_by: crate::dispatch::FairShareKey,
+
+    /// Relative share of agents per tag or requester under weighted-fair
+    /// dispatch. Groups not listed weigh 1.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub fair_share_weights: BTreeMap<String, f64>,
 }
 
 fn default_max_agents() -> usize {
@@ -237,6 +656,10 @@ fn default_poll_interval() -> u64 {
     60
 }
 
+fn default_timeout_grace() -> u64 {
+    30
+}
+
 impl Default for CoordinatorConfig {
     fn default() -> Self {
         Self {
@@ -245,6 +668,10 @@ impl Default for CoordinatorConfig {
             poll_interval: default_poll_interval(),
             executor: default_executor(),
             model: None,
+            timeout_grace: default_timeout_grace(),
+            dispatch: crate::dispatch::DispatchPolicy::default(),
+            fair_share_by: crate::dispatch::FairShareKey::default(),
+            fair_share_weights: BTreeMap::new(),
         }
     }
 }
@@ -0,0 +1,612 @@
+//! Output contracts: typed checks on what a task produced
+//!
+//! A task's [`OutputContract`] names files that must exist (as glob
+//! patterns), a JSON result document and the schema it must satisfy, and a
+//! command that must exit with a given status. [`check`] runs all of them
+//! and reports every unmet expectation, so whoever finishes the task sees
+//! the whole list at once rather than one problem per attempt.
+//!
+//! Paths are relative to the task's working directory: its worktree if it
+//! has one, otherwise the project root. Once a task is done, its validated
+//! result is kept at `.workgraph/output/<task-id>/result.json`, where
+//! `wg context` picks it up for the tasks after it.
+//!
+//! Schemas use a subset of JSON Schema: `type`, `enum`, `const`,
+//! `properties`, `required`, `additionalProperties`, `items`, `minItems`,
+//! `maxItems`, `minLength`, `maxLength`, `pattern`, `minimum`, `maximum`,
+//! `exclusiveMinimum`, `exclusiveMaximum`, `allOf`, `anyOf`, `oneOf` and
+//! `not`. Other keywords are ignored.
+
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+use regex::Regex;
+use serde_json::Value;
+
+use crate::graph::{OutputContract, Task};
+use crate::worktree;
+
+/// Outcome of checking a task against its contract.
+#[derive(Debug, Default)]
+pub struct Report {
+    /// Every unmet expectation, one line each
+    pub problems: Vec<String>,
+    /// The result document, if the contract asks for one and it parsed
+    pub result: Option<Value>,
+}
+
+impl Report {
+    pub fn is_met(&self) -> bool {
+        self.problems.is_empty()
+    }
+}
+
+/// Where a finished task's validated result is kept.
+pub fn stored_result_path(workgraph_dir: &Path, task_id: &str) -> PathBuf {
+    workgraph_dir
+        .join("output")
+        .join(task_id)
+        .join("result.json")
+}
+
+/// A finished task's validated result, if it has one.
+pub fn stored_result(workgraph_dir: &Path, task_id: &str) -> Option<Value> {
+    let content = std::fs::read_to_string(stored_result_path(workgraph_dir, task_id)).ok()?;
+    serde_json::from_str(&content).ok()
+}
+
+/// Keep a finished task's validated result where `wg context` looks for it,
+/// if its contract had it written somewhere else.
+pub fn store_result(workgraph_dir: &Path, task: &Task, report: &Report) -> std::io::Result<()> {
+    let (Some(result), Some(contract)) = (&report.result, &task.contract) else {
+        return Ok(());
+    };
+    let stored = stored_result_path(workgraph_dir, &task.id);
+    if result_path(workgraph_dir, task, contract) == stored {
+        return Ok(());
+    }
+    std::fs::create_dir_all(stored.parent().unwrap_or(workgraph_dir))?;
+    std::fs::write(&stored, result.to_string())
+}
+
+/// The directory a task's contract paths are relative to.
+pub fn working_dir(workgraph_dir: &Path, task_id: &str) -> PathBuf {
+    worktree::existing(workgraph_dir, task_id).unwrap_or_else(|| {
+        workgraph_dir
+            .parent()
+            .unwrap_or(workgraph_dir)
+            .to_path_buf()
+    })
+}
+
+/// Where the task is expected to write its result document.
+pub fn result_path(workgraph_dir: &Path, task: &Task, contract: &OutputContract) -> PathBuf {
+    match contract.result_path {
+        Some(ref path) => working_dir(workgraph_dir, &task.id).join(path),
+        None => stored_result_path(workgraph_dir, &task.id),
+    }
+}
+
+/// Check `task` against its contract. A task without one always passes.
+pub fn check(workgraph_dir: &Path, task: &Task) -> Report {
+    let mut report = Report::default();
+    let Some(ref contract) = task.contract else {
+        return report;
+    };
+    let root = working_dir(workgraph_dir, &task.id);
+
+    for pattern in &contract.files {
+        match glob(&root, pattern) {
+            Ok(matches) if matches.is_empty() => report
+                .problems
+                .push(format!("files: nothing matches '{}'", pattern)),
+            Ok(_) => {}
+            Err(e) => report.problems.push(format!("files: {}", e)),
+        }
+    }
+
+    if contract.expects_result() {
+        let path = result_path(workgraph_dir, task, contract);
+        let shown = path.strip_prefix(&root).unwrap_or(&path).display();
+        match std::fs::read_to_string(&path) {
+            Err(_) => report
+                .problems
+                .push(format!("result: {} is missing", shown)),
+            Ok(content) => match serde_json::from_str::<Value>(&content) {
+                Err(e) => report
+                    .problems
+                    .push(format!("result: {} is not valid JSON: {}", shown, e)),
+                Ok(value) => {
+                    if let Some(ref schema) = contract.result_schema {
+                        for error in validate(&value, schema) {
+                            report.problems.push(format!("result: {}", error));
+                        }
+                    }
+                    report.result = Some(value);
+                }
+            },
+        }
+    }
+
+    if let Some(ref command) = contract.check {
+        match Command::new("sh")
+            .arg("-c")
+            .arg(command)
+            .current_dir(&root)
+            .env("WG_TASK_ID", &task.id)
+            .output()
+        {
+            Err(e) => report
+                .problems
+                .push(format!("check: failed to run `{}`: {}", command, e)),
+            Ok(output) => {
+                let code = output.status.code();
+                if code != Some(contract.check_status) {
+                    let status = code.map_or("a signal".to_string(), |c| c.to_string());
+                    let mut problem = format!(
+                        "check: `{}` exited with {}, expected {}",
+                        command, status, contract.check_status
+                    );
+                    let stderr = String::from_utf8_lossy(&output.stderr);
+                    let stdout = String::from_utf8_lossy(&output.stdout);
+                    let last_line = |text: &str| {
+                        text.lines()
+                            .rfind(|l| !l.trim().is_empty())
+                            .map(|l| l.trim().to_string())
+                    };
+                    let last = last_line(&stderr).or_else(|| last_line(&stdout));
+                    if let Some(line) = last {
+                        problem.push_str(&format!(" ({})", line));
+                    }
+                    report.problems.push(problem);
+                }
+            }
+        }
+    }
+
+    report
+}
+
+/// Files under `root` matching a glob pattern: `*` and `?` stay within a
+/// path component, `**` spans any number of them.
+pub fn glob(root: &Path, pattern: &str) -> Result<Vec<PathBuf>, String> {
+    let pattern = pattern.trim_start_matches("./");
+    let regex = glob_regex(pattern)?;
+    // Only walk below the part of the pattern without wildcards
+    let base: PathBuf = pattern
+        .split('/')
+        .take_while(|part| !part.contains(['*', '?', '[']))
+        .collect();
+    let start = root.join(&base);
+    if !start.exists() {
+        return Ok(vec![]);
+    }
+    let mut matches: Vec<PathBuf> = walkdir::WalkDir::new(&start)
+        .into_iter()
+        .filter_entry(|e| e.file_name() != ".git")
+        .filter_map(Result::ok)
+        .filter(|e| e.file_type().is_file())
+        .filter_map(|e| {
+            let relative = e.path().strip_prefix(root).ok()?;
+            let relative = relative.to_string_lossy().replace('\\', "/");
+            regex.is_match(&relative).then(|| e.into_path())
+        })
+        .collect();
+    matches.sort();
+    Ok(matches)
+}
+
+fn glob_regex(pattern: &str) -> Result<Regex, String> {
+    let mut regex = String::from("^");
+    let mut chars = pattern.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '*' if chars.peek() == Some(&'*') => {
+                chars.next();
+                if chars.peek() == Some(&'/') {
+                    chars.next();
+                    regex.push_str("(?:.*/)?");
+                } else {
+                    regex.push_str(".*");
+                }
+            }
+            '*' => regex.push_str("[^/]*"),
+            '?' => regex.push_str("[^/]"),
+            '[' => {
+                let class: String = chars.by_ref().take_while(|&c| c != ']').collect();
+                let class = match class.strip_prefix('!') {
+                    Some(negated) => format!("^{}", negated),
+                    None => class,
+                };
+                regex.push('[');
+                regex.push_str(&class.replace('\\', "\\\\"));
+                regex.push(']');
+            }
+            c => regex.push_str(&regex::escape(&c.to_string())),
+        }
+    }
+    regex.push('$');
+    Regex::new(&regex).map_err(|e| format!("invalid pattern '{}': {}", pattern, e))
+}
+
+/// Reject a schema this module cannot apply: anything but an object or a
+/// boolean, unknown type names, and patterns that don't compile.
+pub fn check_schema(schema: &Value) -> Result<(), String> {
+    let Value::Object(map) = schema else {
+        return match schema {
+            Value::Bool(_) => Ok(()),
+            _ => Err("a schema must be a JSON object or boolean".to_string()),
+        };
+    };
+    if let Some(types) = map.get("type") {
+        let names: Vec<&Value> = match types {
+            Value::Array(list) => list.iter().collect(),
+            other => vec![other],
+        };
+        for name in names {
+            match name.as_str() {
+                Some("null" | "boolean" | "object" | "array" | "number" | "integer" | "string") => {
+                }
+                _ => return Err(format!("unknown type {}", name)),
+            }
+        }
+    }
+    if let Some(pattern) = map.get("pattern").and_then(Value::as_str) {
+        Regex::new(pattern).map_err(|e| format!("invalid pattern '{}': {}", pattern, e))?;
+    }
+    for key in ["items", "additionalProperties", "not"] {
+        if let Some(sub) = map.get(key) {
+            check_schema(sub)?;
+        }
+    }
+    if let Some(Value::Object(properties)) = map.get("properties") {
+        for sub in properties.values() {
+            check_schema(sub)?;
+        }
+    }
+    for key in ["allOf", "anyOf", "oneOf"] {
+        if let Some(Value::Array(subs)) = map.get(key) {
+            for sub in subs {
+                check_schema(sub)?;
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Validate `value` against `schema`, returning one message per violation,
+/// each starting with the JSON path of the offending value.
+pub fn validate(value: &Value, schema: &Value) -> Vec<String> {
+    let mut errors = Vec::new();
+    validate_at(value, schema, "$", &mut errors);
+    errors
+}
+
+fn validate_at(value: &Value, schema: &Value, path: &str, errors: &mut Vec<String>) {
+    let map = match schema {
+        Value::Bool(true) => return,
+        Value::Bool(false) => {
+            errors.push(format!("{}: not allowed", path));
+            return;
+        }
+        Value::Object(map) => map,
+        _ => return,
+    };
+
+    if let Some(types) = map.get("type") {
+        let allowed: Vec<&str> = match types {
+            Value::Array(list) => list.iter().filter_map(Value::as_str).collect(),
+            other => other.as_str().into_iter().collect(),
+        };
+        if !allowed.iter().any(|t| has_type(value, t)) {
+            errors.push(format!(
+                "{}: expected {}, found {}",
+                path,
+                allowed.join(" or "),
+                type_name(value)
+            ));
+            // Keywords below assume the right type
+            return;
+        }
+    }
+    if let Some(Value::Array(options)) = map.get("enum")
+        && !options.contains(value)
+    {
+        let options: Vec<String> = options.iter().map(Value::to_string).collect();
+        errors.push(format!(
+            "{}: {} is not one of {}",
+            path,
+            value,
+            options.join(", ")
+        ));
+    }
+    if let Some(expected) = map.get("const")
+        && expected != value
+    {
+        errors.push(format!("{}: expected {}, found {}", path, expected, value));
+    }
+
+    match value {
+        Value::Object(object) => {
+            if let Some(Value::Array(required)) = map.get("required") {
+                for name in required.iter().filter_map(Value::as_str) {
+                    if !object.contains_key(name) {
+                        errors.push(format!("{}: missing required property '{}'", path, name));
+                    }
+                }
+            }
+            let properties = map.get("properties").and_then(Value::as_object);
+            for (name, item) in object {
+                let item_path = format!("{}.{}", path, name);
+                match properties.and_then(|p| p.get(name)) {
+                    Some(sub) => validate_at(item, sub, &item_path, errors),
+                    None => match map.get("additionalProperties") {
+                        Some(Value::Bool(false)) => {
+                            errors.push(format!("{}: unexpected property", item_path))
+                        }
+                        Some(sub) => validate_at(item, sub, &item_path, errors),
+                        None => {}
+                    },
+                }
+            }
+        }
+        Value::Array(items) => {
+            check_bound(map, "minItems", items.len(), path, "item", errors);
+            check_bound(map, "maxItems", items.len(), path, "item", errors);
+            if let Some(sub) = map.get("items") {
+                for (i, item) in items.iter().enumerate() {
+                    validate_at(item, sub, &format!("{}[{}]", path, i), errors);
+                }
+            }
+        }
+        Value::String(s) => {
+            let len = s.chars().count();
+            check_bound(map, "minLength", len, path, "character", errors);
+            check_bound(map, "maxLength", len, path, "character", errors);
+            if let Some(pattern) = map.get("pattern").and_then(Value::as_str)
+                && let Ok(regex) = Regex::new(pattern)
+                && !regex.is_match(s)
+            {
+                errors.push(format!("{}: \"{}\" does not match /{}/", path, s, pattern));
+            }
+        }
+        Value::Number(n) => {
+            let n = n.as_f64().unwrap_or(f64::NAN);
+            let limit = |key: &str| map.get(key).and_then(Value::as_f64);
+            if let Some(min) = limit("minimum")
+                && n < min
+            {
+                errors.push(format!("{}: {} is less than the minimum {}", path, n, min));
+            }
+            if let Some(max) = limit("maximum")
+                && n > max
+            {
+                errors.push(format!("{}: {} is more than the maximum {}", path, n, max));
+            }
+            if let Some(min) = limit("exclusiveMinimum")
+                && n <= min
+            {
+                errors.push(format!("{}: {} is not more than {}", path, n, min));
+            }
+            if let Some(max) = limit("exclusiveMaximum")
+                && n >= max
+            {
+                errors.push(format!("{}: {} is not less than {}", path, n, max));
+            }
+        }
+        _ => {}
+    }
+
+    if let Some(Value::Array(subs)) = map.get("allOf") {
+        for sub in subs {
+            validate_at(value, sub, path, errors);
+        }
+    }
+    let passing = |key: &str| {
+        map.get(key).and_then(Value::as_array).map(|subs| {
+            subs.iter()
+                .filter(|sub| validate(value, sub).is_empty())
+                .count()
+        })
+    };
+    if passing("anyOf") == Some(0) {
+        errors.push(format!("{}: matches none of the anyOf schemas", path));
+    }
+    if let Some(count) = passing("oneOf")
+        && count != 1
+    {
+        errors.push(format!(
+            "{}: matches {} of the oneOf schemas, expected exactly 1",
+            path, count
+        ));
+    }
+    if let Some(sub) = map.get("not")
+        && validate(value, sub).is_empty()
+    {
+        errors.push(format!("{}: matches a schema it must not", path));
+    }
+}
+
+fn check_bound(
+    map: &serde_json::Map<String, Value>,
+    key: &str,
+    len: usize,
+    path: &str,
+    unit: &str,
+    errors: &mut Vec<String>,
+) {
+    let Some(bound) = map.get(key).and_then(Value::as_u64) else {
+        return;
+    };
+    let bound = bound as usize;
+    let (fails, relation) = if key.starts_with("min") {
+        (len < bound, "at least")
+    } else {
+        (len > bound, "at most")
+    };
+    if fails {
+        errors.push(format!(
+            "{}: has {} {}{}, expected {} {}",
+            path,
+            len,
+            unit,
+            if len == 1 { "" } else { "s" },
+            relation,
+            bound
+        ));
+    }
+}
+
+fn has_type(value: &Value, name: &str) -> bool {
+    match name {
+        "null" => value.is_null(),
+        "boolean" => value.is_boolean(),
+        "object" => value.is_object(),
+        "array" => value.is_array(),
+        "string" => value.is_string(),
+        "number" => value.is_number(),
+        "integer" => {
+            value.is_i64() || value.is_u64() || value.as_f64().is_some_and(|n| n.fract() == 0.0)
+        }
+        _ => false,
+    }
+}
+
+fn type_name(value: &Value) -> &'static str {
+    match value {
+        Value::Null => "null",
+        Value::Bool(_) => "boolean",
+        Value::Object(_) => "object",
+        Value::Array(_) => "array",
+        Value::String(_) => "string",
+        Value::Number(n) if n.is_i64() || n.is_u64() => "integer",
+        Value::Number(_) => "number",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_validate_reports_paths() {
+        let schema = json!({
+            "type": "object",
+            "required": ["version", "files"],
+            "additionalProperties": false,
+            "properties": {
+                "version": {"type": "string", "pattern": "^\\d+\\.\\d+$"},
+                "files": {"type": "array", "minItems": 1, "items": {"type": "string"}},
+                "score": {"type": "number", "minimum": 0, "maximum": 1},
+            },
+        });
+        let mut errors = validate(
+            &json!({"version": "1.x", "files": [1], "score": 2, "extra": true}),
+            &schema,
+        );
+        errors.sort();
+        assert_eq!(
+            errors,
+            [
+                "$.extra: unexpected property",
+                "$.files[0]: expected string, found integer",
+                "$.score: 2 is more than the maximum 1",
+                "$.version: \"1.x\" does not match /^\\d+\\.\\d+$/",
+            ]
+        );
+        assert_eq!(
+            validate(&json!({"files": []}), &schema),
+            [
+                "$: missing required property 'version'",
+                "$.files: has 0 items, expected at least 1",
+            ]
+        );
+        assert!(validate(&json!({"version": "1.2", "files": ["a"]}), &schema).is_empty());
+    }
+
+    #[test]
+    fn test_validate_combinators_and_enum() {
+        let schema = json!({"anyOf": [{"type": "integer"}, {"enum": ["auto"]}]});
+        assert!(validate(&json!(3), &schema).is_empty());
+        assert!(validate(&json!("auto"), &schema).is_empty());
+        assert_eq!(
+            validate(&json!("manual"), &schema),
+            ["$: matches none of the anyOf schemas"]
+        );
+        assert_eq!(
+            validate(&json!(1.0), &json!({"type": "integer"})),
+            Vec::<String>::new()
+        );
+    }
+
+    #[test]
+    fn test_check_schema_rejects_bad_schemas() {
+        assert!(check_schema(&json!({"type": "object"})).is_ok());
+        assert!(check_schema(&json!([1])).is_err());
+        assert!(check_schema(&json!({"type": "text"})).is_err());
+        assert!(check_schema(&json!({"properties": {"a": {"pattern": "("}}})).is_err());
+    }
+
+    #[test]
+    fn test_glob() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path();
+        std::fs::create_dir_all(root.join("dist/nested")).unwrap();
+        std::fs::write(root.join("dist/a.whl"), "").unwrap();
+        std::fs::write(root.join("dist/nested/b.whl"), "").unwrap();
+        std::fs::write(root.join("README.md"), "").unwrap();
+
+        let names = |pattern: &str| -> Vec<String> {
+            glob(root, pattern)
+                .unwrap()
+                .iter()
+                .map(|p| p.strip_prefix(root).unwrap().display().to_string())
+                .collect()
+        };
+        assert_eq!(names("dist/*.whl"), ["dist/a.whl"]);
+        assert_eq!(names("dist/**/*.whl"), ["dist/a.whl", "dist/nested/b.whl"]);
+        assert_eq!(names("./README.[mM]d"), ["README.md"]);
+        assert!(names("build/*").is_empty());
+    }
+
+    #[test]
+    fn test_check_collects_every_problem() {
+        let dir = tempfile::tempdir().unwrap();
+        let wg_dir = dir.path().join(".workgraph");
+        std::fs::create_dir_all(&wg_dir).unwrap();
+        let mut task = Task {
+            id: "build".into(),
+            contract: Some(OutputContract {
+                files: vec!["dist/*.whl".into()],
+                result_schema: Some(json!({"type": "object", "required": ["sha"]})),
+                result_path: None,
+                check: Some("echo 'tests failed' >&2; exit 3".into()),
+                check_status: 0,
+            }),
+            ..Task::default()
+        };
+        let report = check(&wg_dir, &task);
+        assert_eq!(
+            report.problems,
+            [
+                "files: nothing matches 'dist/*.whl'",
+                "result: .workgraph/output/build/result.json is missing",
+                "check: `echo 'tests failed' >&2; exit 3` exited with 3, expected 0 (tests failed)",
+            ]
+        );
+
+        std::fs::create_dir_all(dir.path().join("dist")).unwrap();
+        std::fs::write(dir.path().join("dist/pkg.whl"), "").unwrap();
+        let result = stored_result_path(&wg_dir, "build");
+        std::fs::create_dir_all(result.parent().unwrap()).unwrap();
+        std::fs::write(&result, r#"{"sha": "abc"}"#).unwrap();
+        if let Some(ref mut contract) = task.contract {
+            contract.check = Some("test -f dist/pkg.whl && exit 3".into());
+            contract.check_status = 3;
+        }
+        let report = check(&wg_dir, &task);
+        assert!(report.is_met(), "{:?}", report.problems);
+        assert_eq!(report.result, Some(json!({"sha": "abc"})));
+    }
+}
@@ -0,0 +1,354 @@
+//! Order in which the coordinator dispatches ready tasks.
+//!
+//! Every policy starts from a task's effective priority: its own `priority`,
+//! raised to that of any unfinished task waiting on it, so the blockers of
+//! urgent work are urgent too. Ties fall back to creation order.
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::cmp::Ordering;
+use std::collections::{BTreeMap, HashMap, VecDeque};
+
+use crate::config::CoordinatorConfig;
+use crate::graph::{Status, Task, WorkGraph};
+
+/// How the coordinator picks among ready tasks when it has fewer free
+/// agent slots than ready tasks.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum DispatchPolicy {
+    /// Oldest task first
+    Fifo,
+    /// Highest effective priority first
+    #[default]
+    Priority,
+    /// Task starting the longest remaining dependency chain first
+    #[serde(alias = "critical-path")]
+    CriticalPathFirst,
+    /// Smallest hour estimate first; tasks without one go last
+    #[serde(alias = "shortest-estimate-first")]
+    ShortestEstimate,
+    /// Agents shared across tags or requesters in proportion to their weights
+    WeightedFair,
+}
+
+impl std::fmt::Display for DispatchPolicy {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            DispatchPolicy::Fifo => write!(f, "fifo"),
+            DispatchPolicy::Priority => write!(f, "priority"),
+            DispatchPolicy::CriticalPathFirst => write!(f, "critical-path-first"),
+            DispatchPolicy::ShortestEstimate => write!(f, "shortest-estimate"),
+            DispatchPolicy::WeightedFair => write!(f, "weighted-fair"),
+        }
+    }
+}
+
+impl std::str::FromStr for DispatchPolicy {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().replace('_', "-").as_str() {
+            "fifo" => Ok(DispatchPolicy::Fifo),
+            "priority" => Ok(DispatchPolicy::Priority),
+            "critical-path-first" | "critical-path" => Ok(DispatchPolicy::CriticalPathFirst),
+            "shortest-estimate" | "shortest-estimate-first" => Ok(DispatchPolicy::ShortestEstimate),
+            "weighted-fair" => Ok(DispatchPolicy::WeightedFair),
+            _ => Err(format!(
+                "Invalid dispatch policy '{}'. Valid values: fifo, priority, \
+                 critical-path-first, shortest-estimate, weighted-fair",
+                s
+            )),
+        }
+    }
+}
+
+/// What weighted-fair dispatch divides agents between.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum FairShareKey {
+    /// The first of a task's tags that has a weight, else its first tag
+    #[default]
+    Tag,
+    /// The task's `requester`
+    Requester,
+}
+
+/// Effective priority of every task in the graph.
+pub fn effective_priorities(graph: &WorkGraph) -> HashMap<String, i32> {
+    let mut effective: HashMap<String, i32> =
+        graph.tasks().map(|t| (t.id.clone(), t.priority)).collect();
+    // Raise blockers to their dependents' priority until nothing changes.
+    // Values only grow and are bounded by the highest priority, so cycles
+    // settle too.
+    let mut changed = true;
+    while changed {
+        changed = false;
+        for task in graph.tasks().filter(|t| !t.status.is_terminal()) {
+            let priority = effective[&task.id];
+            for blocker in &task.after {
+                if let Some(p) = effective.get_mut(blocker)
+                    && *p < priority
+                {
+                    *p = priority;
+                    changed = true;
+                }
+            }
+        }
+    }
+    effective
+}
+
+/// Group a task falls in for weighted-fair dispatch. Tasks without a tag or
+/// requester share the empty group.
+pub fn fair_share_group(task: &Task, config: &CoordinatorConfig) -> String {
+    match config.fair_share_by {
+        FairShareKey::Tag => task
+            .tags
+            .iter()
+            .find(|t| config.fair_share_weights.contains_key(*t))
+            .or(task.tags.first())
+            .cloned()
+            .unwrap_or_default(),
+        FairShareKey::Requester => task.requester.clone().unwrap_or_default(),
+    }
+}
+
+/// Sort `ready` into the order the coordinator dispatches it under
+/// `config.dispatch`. `path_hours` gives the hours of the longest chain of
+/// unfinished tasks each task starts; only critical-path-first reads it.
+pub fn order<'a>(
+    graph: &WorkGraph,
+    mut ready: Vec<&'a Task>,
+    config: &CoordinatorConfig,
+    path_hours: &HashMap<String, f64>,
+) -> Vec<&'a Task> {
+    let priorities = effective_priorities(graph);
+    let priority = |t: &Task| priorities.get(&t.id).copied().unwrap_or(t.priority);
+    let by_priority = |a: &Task, b: &Task| priority(b).cmp(&priority(a)).then_with(|| fifo(a, b));
+
+    match config.dispatch {
+        DispatchPolicy::Fifo => ready.sort_by(|a, b| fifo(a, b)),
+        DispatchPolicy::Priority | DispatchPolicy::WeightedFair => {
+            ready.sort_by(|a, b| by_priority(a, b))
+        }
+        DispatchPolicy::CriticalPathFirst => ready.sort_by(|a, b| {
+            let hours = |t: &Task| path_hours.get(&t.id).copied().unwrap_or(0.0);
+            hours(b)
+                .total_cmp(&hours(a))
+                .then_with(|| by_priority(a, b))
+        }),
+        DispatchPolicy::ShortestEstimate => ready.sort_by(|a, b| {
+            let hours = |t: &Task| t.estimate.as_ref().and_then(|e| e.hours);
+            match (hours(a), hours(b)) {
+                (Some(x), Some(y)) => x.total_cmp(&y),
+                (Some(_), None) => Ordering::Less,
+                (None, Some(_)) => Ordering::Greater,
+                (None, None) => Ordering::Equal,
+            }
+            .then_with(|| by_priority(a, b))
+        }),
+    }
+
+    if config.dispatch == DispatchPolicy::WeightedFair {
+        ready = fair_share(graph, ready, config);
+    }
+    ready
+}
+
+/// Creation order, tasks without a timestamp last.
+fn fifo(a: &Task, b: &Task) -> Ordering {
+    let created = |t: &Task| {
+        t.created_at
+            .as_deref()
+            .and_then(|c| c.parse::<DateTime<Utc>>().ok())
+    };
+    match (created(a), created(b)) {
+        (Some(x), Some(y)) => x.cmp(&y),
+        (Some(_), None) => Ordering::Less,
+        (None, Some(_)) => Ordering::Greater,
+        (None, None) => Ordering::Equal,
+    }
+    .then_with(|| a.id.cmp(&b.id))
+}
+
+/// Interleave `ready` (already in priority order) across fair-share groups.
+/// Each pick goes to the group running the fewest tasks relative to its
+/// weight, counting tasks in progress and those picked before it.
+fn fair_share<'a>(
+    graph: &WorkGraph,
+    ready: Vec<&'a Task>,
+    config: &CoordinatorConfig,
+) -> Vec<&'a Task> {
+    let mut running: BTreeMap<String, f64> = BTreeMap::new();
+    for task in graph.tasks().filter(|t| t.status == Status::InProgress) {
+        *running.entry(fair_share_group(task, config)).or_default() += 1.0;
+    }
+    let total = ready.len();
+    let mut queues: BTreeMap<String, VecDeque<(usize, &'a Task)>> = BTreeMap::new();
+    for (rank, task) in ready.into_iter().enumerate() {
+        queues
+            .entry(fair_share_group(task, config))
+            .or_default()
+            .push_back((rank, task));
+    }
+
+    let share = |group: &str, running: &BTreeMap<String, f64>| {
+        let weight = config.fair_share_weights.get(group).copied().unwrap_or(1.0);
+        let held = running.get(group).copied().unwrap_or(0.0);
+        if weight > 0.0 {
+            held / weight
+        } else {
+            f64::INFINITY
+        }
+    };
+
+    let mut ordered = Vec::with_capacity(total);
+    while let Some(group) = queues
+        .iter()
+        .filter_map(|(g, q)| q.front().map(|(rank, _)| (g, *rank)))
+        .min_by(|(a, ra), (b, rb)| {
+            share(a, &running)
+                .total_cmp(&share(b, &running))
+                .then_with(|| ra.cmp(rb))
+        })
+        .map(|(g, _)| g.clone())
+    {
+        if let Some((_, task)) = queues.get_mut(&group).and_then(|q| q.pop_front()) {
+            ordered.push(task);
+        }
+        *running.entry(group).or_default() += 1.0;
+    }
+    ordered
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::graph::{Estimate, Node};
+
+    fn task(id: &str, priority: i32, created: &str) -> Task {
+        Task {
+            id: id.to_string(),
+            title: id.to_string(),
+            priority,
+            created_at: Some(format!("2026-01-01T00:00:{}Z", created)),
+            ..Task::default()
+        }
+    }
+
+    fn ids(tasks: &[&Task]) -> Vec<String> {
+        tasks.iter().map(|t| t.id.clone()).collect()
+    }
+
+    fn config(dispatch: DispatchPolicy) -> CoordinatorConfig {
+        CoordinatorConfig {
+            dispatch,
+            ..CoordinatorConfig::default()
+        }
+    }
+
+    #[test]
+    fn test_priority_is_inherited_by_blockers() {
+        let mut graph = WorkGraph::new();
+        graph.add_node(Node::Task(task("old", 0, "01")));
+        graph.add_node(Node::Task(task("blocker", 0, "02")));
+        let mut urgent = task("urgent", 5, "03");
+        urgent.after = vec!["blocker".to_string()];
+        graph.add_node(Node::Task(urgent));
+        let mut done = task("done", 9, "04");
+        done.status = Status::Done;
+        done.after = vec!["old".to_string()];
+        graph.add_node(Node::Task(done));
+
+        let priorities = effective_priorities(&graph);
+        assert_eq!(priorities["blocker"], 5);
+        // Finished work no longer boosts what it waited on
+        assert_eq!(priorities["old"], 0);
+
+        let old = graph.get_task("old").unwrap();
+        let blocker = graph.get_task("blocker").unwrap();
+        let ordered = order(
+            &graph,
+            vec![old, blocker],
+            &config(DispatchPolicy::Priority),
+            &HashMap::new(),
+        );
+        assert_eq!(ids(&ordered), vec!["blocker", "old"]);
+        let ordered = order(
+            &graph,
+            vec![blocker, old],
+            &config(DispatchPolicy::Fifo),
+            &HashMap::new(),
+        );
+        assert_eq!(ids(&ordered), vec!["old", "blocker"]);
+    }
+
+    #[test]
+    fn test_critical_path_and_shortest_estimate() {
+        let mut graph = WorkGraph::new();
+        for (id, hours) in [("a", Some(4.0)), ("b", Some(1.0)), ("c", None)] {
+            let mut t = task(id, 0, "01");
+            t.estimate = hours.map(|h| Estimate {
+                hours: Some(h),
+                cost: None,
+            });
+            graph.add_node(Node::Task(t));
+        }
+        let ready: Vec<&Task> = ["a", "b", "c"]
+            .iter()
+            .map(|id| graph.get_task(id).unwrap())
+            .collect();
+
+        let ordered = order(
+            &graph,
+            ready.clone(),
+            &config(DispatchPolicy::ShortestEstimate),
+            &HashMap::new(),
+        );
+        assert_eq!(ids(&ordered), vec!["b", "a", "c"]);
+
+        let path_hours: HashMap<String, f64> = [("a", 4.0), ("b", 9.0), ("c", 1.0)]
+            .into_iter()
+            .map(|(id, h)| (id.to_string(), h))
+            .collect();
+        let ordered = order(
+            &graph,
+            ready,
+            &config(DispatchPolicy::CriticalPathFirst),
+            &path_hours,
+        );
+        assert_eq!(ids(&ordered), vec!["b", "a", "c"]);
+    }
+
+    #[test]
+    fn test_weighted_fair_interleaves_groups() {
+        let mut graph = WorkGraph::new();
+        let mut running = task("running", 0, "00");
+        running.status = Status::InProgress;
+        running.tags = vec!["infra".to_string()];
+        graph.add_node(Node::Task(running));
+        for (id, tag, created) in [
+            ("i1", "infra", "01"),
+            ("i2", "infra", "02"),
+            ("i3", "infra", "03"),
+            ("w1", "web", "04"),
+            ("w2", "web", "05"),
+            ("w3", "web", "06"),
+        ] {
+            let mut t = task(id, 0, created);
+            t.tags = vec![tag.to_string()];
+            graph.add_node(Node::Task(t));
+        }
+        let ready: Vec<&Task> = graph.tasks().filter(|t| t.status == Status::Open).collect();
+
+        let mut config = config(DispatchPolicy::WeightedFair);
+        let ordered = order(&graph, ready.clone(), &config, &HashMap::new());
+        // infra already runs one task, so web goes first
+        assert_eq!(ids(&ordered), vec!["w1", "i1", "w2", "i2", "w3", "i3"]);
+
+        config.fair_share_weights.insert("infra".to_string(), 3.0);
+        let ordered = order(&graph, ready, &config, &HashMap::new());
+        assert_eq!(ids(&ordered), vec!["w1", "i1", "i2", "i3", "w2", "w3"]);
+    }
+}
@@ -0,0 +1,819 @@
+//! Email for human agents
+//!
+//! Agents whose executor is `email` are people who work through their
+//! inbox. When the coordinator dispatches a task to one, it mails the task
+//! to the agent's `contact` address with the subject tagged
+//! `[wg:<task-id>#<token>]`. Replies that keep the tag are read from a local
+//! Maildir: the reply text is added to the task log, and `[done]` or `[fail]`
+//! in the subject also finishes or fails the task.
+//!
+//! A `From:` header is trivial to forge, so it alone doesn't show a reply
+//! came from the agent. The token is derived from the task id and a secret
+//! kept in `.workgraph/email/secret`, and is only ever sent to the agent's
+//! address; a reply is applied only if it carries the task's token and comes
+//! from that address.
+//!
+//! Sending is plain SMTP with no TLS or authentication, meant for a relay
+//! on the same host or network (postfix, msmtpd, or a test sink) that
+//! handles onward delivery.
+
+use std::io::{BufRead, BufReader, Write};
+use std::net::{TcpStream, ToSocketAddrs};
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use anyhow::{Context, Result, anyhow, bail};
+use chrono::{DateTime, Utc};
+use sha2::{Digest, Sha256};
+
+use crate::config::EmailConfig;
+use crate::graph::Task;
+
+/// How long to wait for the SMTP server at each step
+const SMTP_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// Subject line of the message sending `task` to its agent, with the
+/// task's [`reply_token`].
+pub fn task_subject(task: &Task, token: &str) -> String {
+    format!("[wg:{}#{}] {}", task.id, token, task.title)
+}
+
+/// The token a reply about `task_id` must carry in its subject tag.
+pub fn reply_token(workgraph_dir: &Path, task_id: &str) -> Result<String> {
+    let mut hasher = Sha256::new();
+    hasher.update(secret(workgraph_dir)?.as_bytes());
+    hasher.update(b"\0");
+    hasher.update(task_id.as_bytes());
+    let digest = format!("{:x}", hasher.finalize());
+    Ok(digest[..16].to_string())
+}
+
+/// The secret reply tokens are derived from, created on first use.
+fn secret(workgraph_dir: &Path) -> Result<String> {
+    let path = workgraph_dir.join("email").join("secret");
+    if let Ok(secret) = std::fs::read_to_string(&path)
+        && !secret.trim().is_empty()
+    {
+        return Ok(secret.trim().to_string());
+    }
+    std::fs::create_dir_all(path.parent().unwrap_or(workgraph_dir))?;
+    let secret = format!("{:x}", Sha256::digest(random_bytes()?));
+    let mut options = std::fs::OpenOptions::new();
+    options.write(true).create_new(true);
+    #[cfg(unix)]
+    std::os::unix::fs::OpenOptionsExt::mode(&mut options, 0o600);
+    match options.open(&path) {
+        Ok(mut file) => {
+            file.write_all(secret.as_bytes())?;
+            Ok(secret)
+        }
+        // Another process created it first
+        Err(e) if e.kind() == std::io::ErrorKind::AlreadyExists => {
+            Ok(std::fs::read_to_string(&path)?.trim().to_string())
+        }
+        Err(e) => Err(e).with_context(|| format!("Failed to create {}", path.display())),
+    }
+}
+
+#[cfg(unix)]
+fn random_bytes() -> Result<[u8; 32]> {
+    use std::io::Read;
+    let mut bytes = [0u8; 32];
+    std::fs::File::open("/dev/urandom")
+        .and_then(|mut f| f.read_exact(&mut bytes))
+        .context("Failed to read /dev/urandom")?;
+    Ok(bytes)
+}
+
+#[cfg(not(unix))]
+fn random_bytes() -> Result<[u8; 32]> {
+    use std::hash::{BuildHasher, Hasher};
+    let mut bytes = [0u8; 32];
+    for chunk in bytes.chunks_mut(8) {
+        // Each RandomState is keyed from the OS's randomness
+        let hasher = std::collections::hash_map::RandomState::new().build_hasher();
+        chunk.copy_from_slice(&hasher.finish().to_le_bytes());
+    }
+    Ok(bytes)
+}
+
+/// Send a plain-text message through the configured SMTP server.
+pub fn send(config: &EmailConfig, to: &str, subject: &str, body: &str) -> Result<()> {
+    let (Some(host), Some(from)) = (config.smtp_host.as_deref(), config.from.as_deref()) else {
+        bail!("Email is not configured; set [email] smtp_host and from in config.toml");
+    };
+    // A line break would end the SMTP command or header it is written into
+    for address in [from, to] {
+        if address.contains(['\r', '\n']) {
+            bail!("Invalid email address {:?}: contains a line break", address);
+        }
+    }
+    let message = format_message(from, to, subject, body, Utc::now());
+
+    let addr = (host, config.smtp_port)
+        .to_socket_addrs()
+        .with_context(|| format!("Failed to resolve SMTP server {}", host))?
+        .next()
+        .ok_or_else(|| anyhow!("SMTP server {} has no address", host))?;
+    let stream = TcpStream::connect_timeout(&addr, SMTP_TIMEOUT).with_context(|| {
+        format!(
+            "Failed to connect to SMTP server {}:{}",
+            host, config.smtp_port
+        )
+    })?;
+    stream.set_read_timeout(Some(SMTP_TIMEOUT))?;
+    stream.set_write_timeout(Some(SMTP_TIMEOUT))?;
+
+    let mut smtp = Smtp {
+        reader: BufReader::new(stream.try_clone()?),
+        writer: stream,
+    };
+    smtp.expect(&[220])?;
+    smtp.command(&format!("EHLO {}", domain_of(from)), &[250])?;
+    smtp.command(&format!("MAIL FROM:<{}>", address_of(from)), &[250])?;
+    smtp.command(&format!("RCPT TO:<{}>", address_of(to)), &[250, 251])?;
+    smtp.command("DATA", &[354])?;
+    smtp.data(&message)?;
+    // The message is accepted at this point; a failed QUIT changes nothing
+    let _ = smtp.command("QUIT", &[221]);
+    Ok(())
+}
+
+/// A minimal SMTP client session.
+struct Smtp {
+    reader: BufReader<TcpStream>,
+    writer: TcpStream,
+}
+
+impl Smtp {
+    fn command(&mut self, line: &str, codes: &[u16]) -> Result<String> {
+        write!(self.writer, "{}\r\n", line)?;
+        self.writer.flush()?;
+        let verb = line.split([' ', ':']).next().unwrap_or(line);
+        self.expect(codes)
+            .with_context(|| format!("SMTP {} rejected", verb))
+    }
+
+    /// Send the message body, dot-stuffed, and its terminating line.
+    fn data(&mut self, message: &str) -> Result<String> {
+        for line in message.split("\r\n") {
+            if line.starts_with('.') {
+                self.writer.write_all(b".")?;
+            }
+            self.writer.write_all(line.as_bytes())?;
+            self.writer.write_all(b"\r\n")?;
+        }
+        self.writer.write_all(b".\r\n")?;
+        self.writer.flush()?;
+        self.expect(&[250])
+            .context("SMTP server refused the message")
+    }
+
+    /// Read a (possibly multi-line) reply and check its code.
+    fn expect(&mut self, codes: &[u16]) -> Result<String> {
+        let mut text = String::new();
+        loop {
+            let mut line = String::new();
+            if self.reader.read_line(&mut line)? == 0 {
+                bail!("SMTP server closed the connection");
+            }
+            let line = line.trim_end();
+            text.push_str(line.get(4..).unwrap_or(""));
+            if line.as_bytes().get(3) != Some(&b'-') {
+                let code: u16 = line.get(..3).and_then(|c| c.parse().ok()).unwrap_or(0);
+                if !codes.contains(&code) {
+                    bail!("{}", line);
+                }
+                return Ok(text);
+            }
+            text.push('\n');
+        }
+    }
+}
+
+/// Render a complete RFC 5322 message with CRLF line endings.
+pub fn format_message(
+    from: &str,
+    to: &str,
+    subject: &str,
+    body: &str,
+    date: DateTime<Utc>,
+) -> String {
+    let body = body.replace("\r\n", "\n");
+    let plain = body.is_ascii() && body.lines().all(|l| l.len() <= 998);
+    let mut headers = vec![
+        format!("From: {}", single_line(from)),
+        format!("To: {}", single_line(to)),
+        format!("Subject: {}", encode_header(&single_line(subject))),
+        format!("Date: {}", date.to_rfc2822()),
+        format!(
+            "Message-ID: <{}.{}@{}>",
+            date.timestamp_millis(),
+            std::process::id(),
+            domain_of(from)
+        ),
+        "MIME-Version: 1.0".to_string(),
+        "Content-Type: text/plain; charset=utf-8".to_string(),
+    ];
+    let encoded = if plain {
+        headers.push("Content-Transfer-Encoding: 7bit".to_string());
+        body.replace('\n', "\r\n")
+    } else {
+        headers.push("Content-Transfer-Encoding: base64".to_string());
+        base64_encode(body.as_bytes())
+            .as_bytes()
+            .chunks(76)
+            .map(|chunk| String::from_utf8_lossy(chunk).into_owned())
+            .collect::<Vec<_>>()
+            .join("\r\n")
+    };
+    format!("{}\r\n\r\n{}", headers.join("\r\n"), encoded)
+}
+
+/// A header value with line breaks turned into spaces, so it can't end its
+/// header and start another.
+fn single_line(value: &str) -> String {
+    value
+        .split(['\r', '\n'])
+        .filter(|part| !part.is_empty())
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+/// Encode a header value as RFC 2047 words if it isn't plain ASCII.
+fn encode_header(value: &str) -> String {
+    if value.is_ascii() {
+        return value.to_string();
+    }
+    // Keep each encoded word within the 75-character limit
+    let mut words = Vec::new();
+    let mut chunk = String::new();
+    for c in value.chars() {
+        if chunk.len() + c.len_utf8() > 45 {
+            words.push(std::mem::take(&mut chunk));
+        }
+        chunk.push(c);
+    }
+    words.push(chunk);
+    words
+        .iter()
+        .map(|w| format!("=?UTF-8?B?{}?=", base64_encode(w.as_bytes())))
+        .collect::<Vec<_>>()
+        .join("\r\n ")
+}
+
+/// The bare address in `Name <user@host>` or `user@host`.
+pub fn address_of(mailbox: &str) -> &str {
+    match (mailbox.rfind('<'), mailbox.rfind('>')) {
+        (Some(start), Some(end)) if start < end => mailbox[start + 1..end].trim(),
+        _ => mailbox.trim(),
+    }
+}
+
+fn domain_of(mailbox: &str) -> &str {
+    address_of(mailbox)
+        .rsplit_once('@')
+        .map_or("localhost", |(_, domain)| domain)
+}
+
+/// What a reply asks for, from the tags in its subject.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ReplyAction {
+    /// Add the reply to the task log
+    Log,
+    /// `[done]`: log the reply, then mark the task done
+    Done,
+    /// `[fail]`: mark the task failed with the reply as the reason
+    Fail,
+}
+
+impl std::fmt::Display for ReplyAction {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            ReplyAction::Log => "log",
+            ReplyAction::Done => "done",
+            ReplyAction::Fail => "fail",
+        })
+    }
+}
+
+/// A reply to a task email.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Reply {
+    pub task_id: String,
+    /// The token in the subject tag, if any; see [`reply_token`]
+    pub token: Option<String>,
+    pub action: ReplyAction,
+    /// Bare address of the sender
+    pub sender: String,
+    /// What the person wrote, without quoted text or signature
+    pub text: String,
+}
+
+/// Parse a raw message as a reply to a task email.
+pub fn parse_reply(raw: &str) -> Result<Reply, String> {
+    let (headers, body) = split_message(raw);
+    let subject = decode_words(&header(&headers, "subject").unwrap_or_default());
+    let sender = header(&headers, "from")
+        .map(|from| address_of(&from).to_string())
+        .filter(|from| !from.is_empty())
+        .ok_or("no From header")?;
+    let (tag, action) =
+        parse_subject(&subject).ok_or_else(|| format!("no [wg:<task>] tag in '{}'", subject))?;
+    let (task_id, token) = match tag.rsplit_once('#') {
+        Some((id, token)) => (id.to_string(), Some(token.to_string())),
+        None => (tag, None),
+    };
+    let text = reply_text(&plain_text(&headers, body));
+    Ok(Reply {
+        task_id,
+        token,
+        action,
+        sender,
+        text,
+    })
+}
+
+/// The task tag (`<task-id>#<token>`) and action tag in a subject line.
+fn parse_subject(subject: &str) -> Option<(String, ReplyAction)> {
+    let mut task_id = None;
+    let mut action = ReplyAction::Log;
+    let mut rest = subject;
+    while let Some(start) = rest.find('[') {
+        let Some(len) = rest[start..].find(']') else {
+            break;
+        };
+        let tag = rest[start + 1..start + len].trim();
+        if let Some(id) = tag.strip_prefix("wg:") {
+            task_id.get_or_insert_with(|| id.trim().to_string());
+        } else if tag.eq_ignore_ascii_case("done") {
+            action = ReplyAction::Done;
+        } else if tag.eq_ignore_ascii_case("fail") || tag.eq_ignore_ascii_case("failed") {
+            action = ReplyAction::Fail;
+        }
+        rest = &rest[start + len + 1..];
+    }
+    task_id.filter(|id| !id.is_empty()).map(|id| (id, action))
+}
+
+/// What the sender wrote, dropping quoted lines, the "On ... wrote:" line
+/// that introduces them, and anything after a signature separator.
+fn reply_text(body: &str) -> String {
+    let mut lines: Vec<&str> = Vec::new();
+    for line in body.lines() {
+        let trimmed = line.trim_end();
+        if trimmed == "--" {
+            break;
+        }
+        if trimmed.starts_with('>') {
+            if lines
+                .last()
+                .is_some_and(|l| l.trim_end().ends_with("wrote:"))
+            {
+                lines.pop();
+            }
+            continue;
+        }
+        lines.push(trimmed);
+    }
+    lines.join("\n").trim().to_string()
+}
+
+/// Split a raw message into unfolded headers and the body.
+fn split_message(raw: &str) -> (Vec<(String, String)>, &str) {
+    let (head, body) = raw
+        .split_once("\r\n\r\n")
+        .or_else(|| raw.split_once("\n\n"))
+        .unwrap_or((raw, ""));
+    let mut headers: Vec<(String, String)> = Vec::new();
+    for line in head.lines() {
+        if line.starts_with([' ', '\t']) {
+            if let Some((_, value)) = headers.last_mut() {
+                value.push(' ');
+                value.push_str(line.trim());
+            }
+        } else if let Some((name, value)) = line.split_once(':') {
+            headers.push((name.trim().to_ascii_lowercase(), value.trim().to_string()));
+        }
+    }
+    (headers, body)
+}
+
+fn header(headers: &[(String, String)], name: &str) -> Option<String> {
+    headers
+        .iter()
+        .find(|(n, _)| n == name)
+        .map(|(_, v)| v.clone())
+}
+
+/// The `key=value` parameter of a header like Content-Type.
+fn header_param(value: &str, key: &str) -> Option<String> {
+    value.split(';').skip(1).find_map(|param| {
+        let (k, v) = param.split_once('=')?;
+        k.trim()
+            .eq_ignore_ascii_case(key)
+            .then(|| v.trim().trim_matches('"').to_string())
+    })
+}
+
+/// The text/plain content of a message or MIME part, decoded.
+fn plain_text(headers: &[(String, String)], body: &str) -> String {
+    let content_type = header(headers, "content-type").unwrap_or_default();
+    if content_type.to_ascii_lowercase().starts_with("multipart/") {
+        let Some(boundary) = header_param(&content_type, "boundary") else {
+            return String::new();
+        };
+        let delimiter = format!("--{}", boundary);
+        // The first part is the preamble, the last the epilogue
+        for part in body.split(delimiter.as_str()).skip(1) {
+            if part.starts_with("--") {
+                break;
+            }
+            let part = part.trim_start_matches(['\r', '\n']);
+            let (part_headers, part_body) = split_message(part);
+            let part_type = header(&part_headers, "content-type").unwrap_or_default();
+            let part_type = part_type.to_ascii_lowercase();
+            if part_type.is_empty()
+                || part_type.starts_with("text/plain")
+                || part_type.starts_with("multipart/")
+            {
+                let text = plain_text(&part_headers, part_body);
+                if !text.trim().is_empty() {
+                    return text;
+                }
+            }
+        }
+        return String::new();
+    }
+    let encoding = header(headers, "content-transfer-encoding")
+        .unwrap_or_default()
+        .to_ascii_lowercase();
+    match encoding.as_str() {
+        "base64" => String::from_utf8_lossy(&base64_decode(body)).into_owned(),
+        "quoted-printable" => {
+            String::from_utf8_lossy(&quoted_printable_decode(body, false)).into_owned()
+        }
+        _ => body.to_string(),
+    }
+}
+
+/// Decode RFC 2047 encoded words (`=?charset?B|Q?...?=`) in a header value.
+fn decode_words(value: &str) -> String {
+    let mut out = String::new();
+    let mut rest = value;
+    let mut after_word = false;
+    while let Some(start) = rest.find("=?") {
+        let decoded = rest[start + 2..].splitn(3, '?').collect::<Vec<_>>();
+        let word = match decoded.as_slice() {
+            [charset, encoding, tail] => tail.find("?=").map(|end| {
+                let text = &tail[..end];
+                let bytes = if encoding.eq_ignore_ascii_case("b") {
+                    base64_decode(text)
+                } else {
+                    quoted_printable_decode(text, true)
+                };
+                // "=?" charset "?" encoding "?" text "?="
+                let consumed = start + 2 + charset.len() + encoding.len() + 2 + end + 2;
+                (String::from_utf8_lossy(&bytes).into_owned(), consumed)
+            }),
+            _ => None,
+        };
+        let Some((text, consumed)) = word else {
+            break;
+        };
+        // Whitespace between adjacent encoded words is not part of the text
+        let between = &rest[..start];
+        if !(after_word && between.trim().is_empty()) {
+            out.push_str(between);
+        }
+        out.push_str(&text);
+        rest = &rest[consumed..];
+        after_word = true;
+    }
+    out.push_str(rest);
+    out
+}
+
+fn quoted_printable_decode(text: &str, header: bool) -> Vec<u8> {
+    let bytes = text.as_bytes();
+    let mut out = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        match bytes[i] {
+            b'=' if bytes[i + 1..].starts_with(b"\r\n") => i += 3,
+            b'=' if bytes[i + 1..].starts_with(b"\n") => i += 2,
+            b'=' => {
+                let hex = text
+                    .get(i + 1..i + 3)
+                    .and_then(|h| u8::from_str_radix(h, 16).ok());
+                match hex {
+                    Some(byte) => {
+                        out.push(byte);
+                        i += 3;
+                    }
+                    None => {
+                        out.push(b'=');
+                        i += 1;
+                    }
+                }
+            }
+            b'_' if header => {
+                out.push(b' ');
+                i += 1;
+            }
+            byte => {
+                out.push(byte);
+                i += 1;
+            }
+        }
+    }
+    out
+}
+
+const BASE64_ALPHABET: &[u8; 64] =
+    b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+
+fn base64_encode(bytes: &[u8]) -> String {
+    let mut out = String::with_capacity(bytes.len().div_ceil(3) * 4);
+    for chunk in bytes.chunks(3) {
+        let n = chunk
+            .iter()
+            .enumerate()
+            .fold(0u32, |n, (i, &b)| n | (u32::from(b) << (16 - 8 * i)));
+        for i in 0..4 {
+            if i <= chunk.len() {
+                out.push(BASE64_ALPHABET[(n >> (18 - 6 * i)) as usize & 0x3f] as char);
+            } else {
+                out.push('=');
+            }
+        }
+    }
+    out
+}
+
+/// Decode base64, skipping line breaks and anything else outside the alphabet.
+fn base64_decode(text: &str) -> Vec<u8> {
+    let mut out = Vec::with_capacity(text.len() / 4 * 3);
+    let mut n = 0u32;
+    let mut bits = 0;
+    for c in text.bytes() {
+        let Some(value) = BASE64_ALPHABET.iter().position(|&a| a == c) else {
+            continue;
+        };
+        n = (n << 6) | value as u32;
+        bits += 6;
+        if bits >= 8 {
+            bits -= 8;
+            out.push((n >> bits) as u8);
+        }
+    }
+    out
+}
+
+/// Messages waiting in the Maildir's `new/` directory, oldest name first.
+pub fn new_messages(maildir: &Path) -> Result<Vec<PathBuf>> {
+    let new_dir = maildir.join("new");
+    if !new_dir.is_dir() {
+        bail!("{} is not a Maildir (no new/ directory)", maildir.display());
+    }
+    let mut messages: Vec<PathBuf> = std::fs::read_dir(&new_dir)
+        .with_context(|| format!("Failed to read {}", new_dir.display()))?
+        .filter_map(|entry| entry.ok().map(|e| e.path()))
+        .filter(|path| {
+            path.is_file()
+                && !path
+                    .file_name()
+                    .is_some_and(|n| n.to_string_lossy().starts_with('.'))
+        })
+        .collect();
+    messages.sort();
+    Ok(messages)
+}
+
+/// Move a message from `new/` to `cur/`, flagged as seen, so it is not
+/// read again.
+pub fn mark_seen(message: &Path) -> Result<PathBuf> {
+    let name = message
+        .file_name()
+        .ok_or_else(|| anyhow!("{} is not a message file", message.display()))?
+        .to_string_lossy();
+    let maildir = message
+        .parent()
+        .and_then(Path::parent)
+        .ok_or_else(|| anyhow!("{} is not inside a Maildir", message.display()))?;
+    let cur = maildir.join("cur");
+    std::fs::create_dir_all(&cur)?;
+    let base = name.split(':').next().unwrap_or(&name);
+    let target = cur.join(format!("{}:2,S", base));
+    std::fs::rename(message, &target)
+        .with_context(|| format!("Failed to move {}", message.display()))?;
+    Ok(target)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Read;
+    use std::net::TcpListener;
+
+    /// Accept one SMTP session on a local port and return the DATA it got.
+    fn smtp_sink() -> (u16, std::thread::JoinHandle<(Vec<String>, String)>) {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let port = listener.local_addr().unwrap().port();
+        let handle = std::thread::spawn(move || {
+            let (stream, _) = listener.accept().unwrap();
+            let mut writer = stream.try_clone().unwrap();
+            let mut reader = BufReader::new(stream);
+            let mut commands = Vec::new();
+            let mut data = String::new();
+            writer.write_all(b"220 sink ready\r\n").unwrap();
+            loop {
+                let mut line = String::new();
+                if reader.read_line(&mut line).unwrap() == 0 {
+                    break;
+                }
+                let line = line.trim_end().to_string();
+                commands.push(line.clone());
+                let reply: &[u8] = match line.as_str() {
+                    l if l.starts_with("EHLO") => b"250-sink\r\n250 8BITMIME\r\n",
+                    "DATA" => {
+                        writer.write_all(b"354 go ahead\r\n").unwrap();
+                        loop {
+                            let mut l = String::new();
+                            reader.read_line(&mut l).unwrap();
+                            if l == ".\r\n" {
+                                break;
+                            }
+                            data.push_str(&l);
+                        }
+                        b"250 queued\r\n"
+                    }
+                    "QUIT" => {
+                        writer.write_all(b"221 bye\r\n").unwrap();
+                        break;
+                    }
+                    _ => b"250 ok\r\n",
+                };
+                writer.write_all(reply).unwrap();
+            }
+            let mut rest = Vec::new();
+            let _ = reader.read_to_end(&mut rest);
+            (commands, data)
+        });
+        (port, handle)
+    }
+
+    #[test]
+    fn test_send_delivers_to_smtp_sink() {
+        let (port, sink) = smtp_sink();
+        let config = EmailConfig {
+            smtp_host: Some("127.0.0.1".into()),
+            smtp_port: port,
+            from: Some("Workgraph <wg@example.org>".into()),
+            maildir: None,
+        };
+        send(
+            &config,
+            "Ada <ada@example.org>",
+            "[wg:t1] Review",
+            "Hello\n.leading dot",
+        )
+        .unwrap();
+        let (commands, data) = sink.join().unwrap();
+        assert_eq!(commands[0], "EHLO example.org");
+        assert_eq!(commands[1], "MAIL FROM:<wg@example.org>");
+        assert_eq!(commands[2], "RCPT TO:<ada@example.org>");
+        assert!(data.contains("Subject: [wg:t1] Review\r\n"));
+        assert!(data.ends_with("Hello\r\n..leading dot\r\n"));
+    }
+
+    #[test]
+    fn test_line_breaks_cannot_inject_headers() {
+        let msg = format_message(
+            "wg@example.org",
+            "a@b",
+            "[wg:t1] Title\r\nBcc: victim@example.org",
+            "body",
+            Utc::now(),
+        );
+        let (headers, _) = split_message(&msg);
+        assert!(headers.iter().all(|(name, _)| name != "bcc"));
+        assert_eq!(
+            header(&headers, "subject").unwrap(),
+            "[wg:t1] Title Bcc: victim@example.org"
+        );
+
+        let config = EmailConfig {
+            smtp_host: Some("127.0.0.1".into()),
+            smtp_port: 1,
+            from: Some("wg@example.org".into()),
+            maildir: None,
+        };
+        let err = send(&config, "a@b>\r\nRCPT TO:<c@d", "s", "b").unwrap_err();
+        assert!(err.to_string().contains("line break"), "{}", err);
+    }
+
+    #[test]
+    fn test_reply_tokens() {
+        let dir = tempfile::tempdir().unwrap();
+        let token = reply_token(dir.path(), "t1").unwrap();
+        assert_eq!(token.len(), 16);
+        assert_eq!(reply_token(dir.path(), "t1").unwrap(), token);
+        assert_ne!(reply_token(dir.path(), "t2").unwrap(), token);
+        let other = tempfile::tempdir().unwrap();
+        assert_ne!(reply_token(other.path(), "t1").unwrap(), token);
+
+        let task = Task {
+            id: "t1".into(),
+            title: "Review".into(),
+            ..Task::default()
+        };
+        let subject = format!("Re: {} [done]", task_subject(&task, &token));
+        let reply = parse_reply(&format!("From: a@b\nSubject: {}\n\nok\n", subject)).unwrap();
+        assert_eq!(reply.task_id, "t1");
+        assert_eq!(reply.token.as_deref(), Some(token.as_str()));
+        assert_eq!(reply.action, ReplyAction::Done);
+    }
+
+    #[test]
+    fn test_send_without_config_fails() {
+        let err = send(&EmailConfig::default(), "a@b", "s", "b").unwrap_err();
+        assert!(err.to_string().contains("not configured"));
+    }
+
+    #[test]
+    fn test_format_message_encodes_non_ascii() {
+        let msg = format_message("wg@example.org", "a@b", "Résumé", "café", Utc::now());
+        assert!(msg.contains("Subject: =?UTF-8?B?UsOpc3Vtw6k=?=\r\n"));
+        assert!(msg.contains("Content-Transfer-Encoding: base64\r\n\r\nY2Fmw6k="));
+        let (headers, body) = split_message(&msg);
+        assert_eq!(
+            decode_words(&header(&headers, "subject").unwrap()),
+            "Résumé"
+        );
+        assert_eq!(plain_text(&headers, body), "café");
+    }
+
+    #[test]
+    fn test_parse_reply_tags_and_quoting() {
+        let raw = "From: Ada Lovelace <Ada@Example.org>\r\n\
+                   Subject: Re: [wg:review-docs] Review docs [done]\r\n\
+                   \r\n\
+                   Looks good, merged.\r\n\
+                   \r\n\
+                   On Mon, Workgraph wrote:\r\n\
+                   > Task: review-docs\r\n\
+                   -- \r\n\
+                   Ada\r\n";
+        let reply = parse_reply(raw).unwrap();
+        assert_eq!(reply.task_id, "review-docs");
+        assert_eq!(reply.token, None);
+        assert_eq!(reply.action, ReplyAction::Done);
+        assert_eq!(reply.sender, "Ada@Example.org");
+        assert_eq!(reply.text, "Looks good, merged.");
+
+        let fail = parse_reply("From: a@b\nSubject: [FAIL] Re: [wg:t2] x\n\nBlocked on access\n");
+        assert_eq!(fail.unwrap().action, ReplyAction::Fail);
+        assert!(parse_reply("From: a@b\nSubject: hello\n\nhi\n").is_err());
+    }
+
+    #[test]
+    fn test_parse_reply_multipart_and_encoded_subject() {
+        let raw = "From: a@b\n\
+                   Subject: =?UTF-8?Q?Re:_[wg:t1]_Caf=C3=A9?=\n\
+                   Content-Type: multipart/alternative; boundary=\"XX\"\n\
+                   \n\
+                   preamble\n\
+                   --XX\n\
+                   Content-Type: text/plain; charset=utf-8\n\
+                   Content-Transfer-Encoding: quoted-printable\n\
+                   \n\
+                   D=C3=A9j=C3=A0 fait, a long line that was =\n\
+                   soft-wrapped\n\
+                   --XX\n\
+                   Content-Type: text/html\n\
+                   \n\
+                   <p>ignored</p>\n\
+                   --XX--\n";
+        let reply = parse_reply(raw).unwrap();
+        assert_eq!(reply.task_id, "t1");
+        assert_eq!(reply.action, ReplyAction::Log);
+        assert_eq!(reply.text, "Déjà fait, a long line that was soft-wrapped");
+    }
+
+    #[test]
+    fn test_maildir_new_messages_and_mark_seen() {
+        let dir = tempfile::tempdir().unwrap();
+        assert!(new_messages(dir.path()).is_err());
+        std::fs::create_dir_all(dir.path().join("new")).unwrap();
+        std::fs::write(dir.path().join("new").join("2.host"), "b").unwrap();
+        std::fs::write(dir.path().join("new").join("1.host"), "a").unwrap();
+        let messages = new_messages(dir.path()).unwrap();
+        assert_eq!(messages.len(), 2);
+        assert!(messages[0].ends_with("1.host"));
+
+        let seen = mark_seen(&messages[0]).unwrap();
+        assert_eq!(seen, dir.path().join("cur").join("1.host:2,S"));
+        assert_eq!(new_messages(dir.path()).unwrap().len(), 1);
+    }
+}
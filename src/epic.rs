@@ -0,0 +1,342 @@
+//! Epics: tasks that group other tasks through their `parent` field.
+//!
+//! Any task with children is an epic. Epics are never dispatched to agents;
+//! [`settle_epics`] completes them once every child is terminal. Progress and
+//! estimates roll up from the leaf tasks underneath, so nested epics count
+//! the work below them exactly once.
+
+use chrono::Utc;
+use serde::Serialize;
+use std::collections::{HashMap, HashSet};
+
+use crate::graph::{LogEntry, Status, Task, WorkGraph};
+
+/// Aggregated status of the leaf tasks under an epic.
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct Rollup {
+    pub total: usize,
+    pub done: usize,
+    pub in_progress: usize,
+    pub open: usize,
+    pub blocked: usize,
+    pub failed: usize,
+    pub abandoned: usize,
+    /// Sum of leaf estimates, if any leaf has one
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub hours: Option<f64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub cost: Option<f64>,
+}
+
+impl Rollup {
+    /// Leaves that have finished, successfully or not.
+    pub fn terminal(&self) -> usize {
+        self.done + self.failed + self.abandoned
+    }
+
+    /// Percentage of leaves that are terminal (0 for an empty epic).
+    pub fn progress(&self) -> f64 {
+        if self.total == 0 {
+            0.0
+        } else {
+            self.terminal() as f64 * 100.0 / self.total as f64
+        }
+    }
+
+    /// Short form for list views, e.g. `3/5, 60%`.
+    pub fn summary(&self) -> String {
+        format!(
+            "{}/{}, {:.0}%",
+            self.terminal(),
+            self.total,
+            self.progress()
+        )
+    }
+
+    fn add(&mut self, task: &Task) {
+        self.total += 1;
+        match task.status {
+            Status::Done => self.done += 1,
+            Status::InProgress => self.in_progress += 1,
+            Status::Open => self.open += 1,
+            Status::Blocked => self.blocked += 1,
+            Status::Failed => self.failed += 1,
+            Status::Abandoned => self.abandoned += 1,
+        }
+        if let Some(ref estimate) = task.estimate {
+            if let Some(hours) = estimate.hours {
+                *self.hours.get_or_insert(0.0) += hours;
+            }
+            if let Some(cost) = estimate.cost {
+                *self.cost.get_or_insert(0.0) += cost;
+            }
+        }
+    }
+}
+
+/// Children of every task that has any, sorted by ID.
+pub fn children_index(graph: &WorkGraph) -> HashMap<&str, Vec<&Task>> {
+    let mut index: HashMap<&str, Vec<&Task>> = HashMap::new();
+    for task in graph.tasks() {
+        if let Some(ref parent) = task.parent {
+            index.entry(parent.as_str()).or_default().push(task);
+        }
+    }
+    for children in index.values_mut() {
+        children.sort_by(|a, b| a.id.cmp(&b.id));
+    }
+    index
+}
+
+/// Direct children of `id`, sorted by ID.
+pub fn children<'a>(graph: &'a WorkGraph, id: &str) -> Vec<&'a Task> {
+    let mut children: Vec<&Task> = graph
+        .tasks()
+        .filter(|t| t.parent.as_deref() == Some(id))
+        .collect();
+    children.sort_by(|a, b| a.id.cmp(&b.id));
+    children
+}
+
+/// IDs of all tasks that have at least one child.
+pub fn epic_ids(graph: &WorkGraph) -> HashSet<&str> {
+    graph.tasks().filter_map(|t| t.parent.as_deref()).collect()
+}
+
+/// All tasks below `id`, depth first. Parent cycles are cut at the first
+/// repeated task.
+pub fn descendants<'a>(graph: &'a WorkGraph, id: &str) -> Vec<&'a Task> {
+    let index = children_index(graph);
+    let mut seen: HashSet<&str> = HashSet::from([id]);
+    let mut out = Vec::new();
+    let mut stack: Vec<&Task> = index.get(id).cloned().unwrap_or_default();
+    stack.reverse();
+    while let Some(task) = stack.pop() {
+        if !seen.insert(task.id.as_str()) {
+            continue;
+        }
+        out.push(task);
+        if let Some(kids) = index.get(task.id.as_str()) {
+            stack.extend(kids.iter().rev());
+        }
+    }
+    out
+}
+
+/// Chain of parents above `id`, nearest first. Stops at a missing parent or
+/// a cycle.
+pub fn ancestors<'a>(graph: &'a WorkGraph, id: &str) -> Vec<&'a Task> {
+    let mut seen: HashSet<&str> = HashSet::from([id]);
+    let mut out = Vec::new();
+    let mut current = graph.get_task(id).and_then(|t| t.parent.as_deref());
+    while let Some(parent_id) = current {
+        if !seen.insert(parent_id) {
+            break;
+        }
+        let Some(parent) = graph.get_task(parent_id) else {
+            break;
+        };
+        out.push(parent);
+        current = parent.parent.as_deref();
+    }
+    out
+}
+
+/// Roll up the leaf tasks under `id`.
+pub fn rollup(graph: &WorkGraph, id: &str) -> Rollup {
+    let epics = epic_ids(graph);
+    let mut rollup = Rollup::default();
+    for task in descendants(graph, id) {
+        if !epics.contains(task.id.as_str()) {
+            rollup.add(task);
+        }
+    }
+    rollup
+}
+
+/// Complete open epics whose children have all reached a terminal status.
+///
+/// An epic is done if any child is done and none failed, failed if any child
+/// failed, and abandoned if every child was abandoned. Nested epics settle
+/// bottom-up across repeated calls. Failed children in `retrying` are about
+/// to be retried and count as unsettled. Returns `(epic_id, status)` for
+/// each epic that was completed.
+pub fn settle_epics(graph: &mut WorkGraph, retrying: &HashSet<String>) -> Vec<(String, Status)> {
+    let settled: Vec<(String, Status, String)> = {
+        let index = children_index(graph);
+        graph
+            .tasks()
+            .filter(|t| matches!(t.status, Status::Open | Status::Blocked))
+            .filter_map(|t| {
+                let kids = index.get(t.id.as_str())?;
+                if !kids
+                    .iter()
+                    .all(|c| c.status.is_terminal() && !retrying.contains(&c.id))
+                {
+                    return None;
+                }
+                let failed = kids.iter().filter(|c| c.status == Status::Failed).count();
+                let abandoned = kids
+                    .iter()
+                    .filter(|c| c.status == Status::Abandoned)
+                    .count();
+                let (status, message) = if failed > 0 {
+                    (
+                        Status::Failed,
+                        format!("{} of {} subtasks failed", failed, kids.len()),
+                    )
+                } else if abandoned == kids.len() {
+                    (
+                        Status::Abandoned,
+                        format!("All {} subtasks were abandoned", kids.len()),
+                    )
+                } else {
+                    (
+                        Status::Done,
+                        format!("All {} subtasks finished", kids.len()),
+                    )
+                };
+                Some((t.id.clone(), status, message))
+            })
+            .collect()
+    };
+
+    let mut results = Vec::new();
+    for (id, status, message) in settled {
+        let Some(task) = graph.get_task_mut(&id) else {
+            continue;
+        };
+        let now = Utc::now().to_rfc3339();
+        task.status = status;
+        if status != Status::Done {
+            task.failure_reason = Some(message.clone());
+        }
+        task.completed_at = Some(now.clone());
+        task.log.push(LogEntry {
+            timestamp: now,
+            actor: None,
+            message,
+        });
+        results.push((id, status));
+    }
+    results
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::graph::{Estimate, Node};
+
+    fn task(id: &str, parent: Option<&str>, status: Status) -> Task {
+        Task {
+            id: id.to_string(),
+            title: id.to_string(),
+            status,
+            parent: parent.map(String::from),
+            ..Task::default()
+        }
+    }
+
+    fn graph(tasks: Vec<Task>) -> WorkGraph {
+        let mut graph = WorkGraph::new();
+        for t in tasks {
+            graph.add_node(Node::Task(t));
+        }
+        graph
+    }
+
+    #[test]
+    fn test_rollup_counts_leaves_and_estimates() {
+        let mut a = task("a", Some("epic"), Status::Done);
+        a.estimate = Some(Estimate {
+            hours: Some(2.0),
+            cost: Some(10.0),
+        });
+        let mut b = task("b", Some("sub"), Status::InProgress);
+        b.estimate = Some(Estimate {
+            hours: Some(3.0),
+            cost: None,
+        });
+        let g = graph(vec![
+            task("epic", None, Status::Open),
+            a,
+            task("sub", Some("epic"), Status::Open),
+            b,
+            task("c", Some("sub"), Status::Failed),
+        ]);
+
+        let r = rollup(&g, "epic");
+        assert_eq!(r.total, 3);
+        assert_eq!(r.done, 1);
+        assert_eq!(r.in_progress, 1);
+        assert_eq!(r.failed, 1);
+        assert_eq!(r.hours, Some(5.0));
+        assert_eq!(r.cost, Some(10.0));
+        assert_eq!(r.summary(), "2/3, 67%");
+
+        assert_eq!(rollup(&g, "a"), Rollup::default());
+        let ids: Vec<&str> = ancestors(&g, "c").iter().map(|t| t.id.as_str()).collect();
+        assert_eq!(ids, vec!["sub", "epic"]);
+    }
+
+    #[test]
+    fn test_settle_epics() {
+        let mut g = graph(vec![
+            task("done-epic", None, Status::Open),
+            task("a", Some("done-epic"), Status::Done),
+            task("b", Some("done-epic"), Status::Abandoned),
+            task("failed-epic", None, Status::Open),
+            task("c", Some("failed-epic"), Status::Failed),
+            task("d", Some("failed-epic"), Status::Done),
+            task("dropped-epic", None, Status::Blocked),
+            task("e", Some("dropped-epic"), Status::Abandoned),
+            task("busy-epic", None, Status::Open),
+            task("f", Some("busy-epic"), Status::Done),
+            task("g", Some("busy-epic"), Status::InProgress),
+        ]);
+
+        let mut settled = settle_epics(&mut g, &HashSet::new());
+        settled.sort_by(|a, b| a.0.cmp(&b.0));
+        assert_eq!(
+            settled,
+            vec![
+                ("done-epic".to_string(), Status::Done),
+                ("dropped-epic".to_string(), Status::Abandoned),
+                ("failed-epic".to_string(), Status::Failed),
+            ]
+        );
+        assert_eq!(
+            g.get_task("failed-epic").unwrap().failure_reason.as_deref(),
+            Some("1 of 2 subtasks failed")
+        );
+        assert_eq!(g.get_task("busy-epic").unwrap().status, Status::Open);
+        assert!(settle_epics(&mut g, &HashSet::new()).is_empty());
+    }
+
+    #[test]
+    fn test_nested_epics_settle_bottom_up() {
+        let mut g = graph(vec![
+            task("top", None, Status::Open),
+            task("mid", Some("top"), Status::Open),
+            task("leaf", Some("mid"), Status::Done),
+        ]);
+        assert_eq!(
+            settle_epics(&mut g, &HashSet::new()),
+            vec![("mid".to_string(), Status::Done)]
+        );
+        assert_eq!(
+            settle_epics(&mut g, &HashSet::new()),
+            vec![("top".to_string(), Status::Done)]
+        );
+    }
+
+    #[test]
+    fn test_parent_cycle_terminates() {
+        let g = graph(vec![
+            task("x", Some("y"), Status::Open),
+            task("y", Some("x"), Status::Open),
+        ]);
+        assert_eq!(descendants(&g, "x").len(), 1);
+        assert_eq!(ancestors(&g, "x").len(), 1);
+    }
+}
@@ -0,0 +1,464 @@
+//! Dynamic fan-out for map tasks.
+//!
+//! A map task carries a [`MapSpec`]. Once its source task is done, the list in
+//! the source's artifact or output file is expanded into one child task per
+//! item, plus a join task that waits on all of them. Tasks that were waiting
+//! on the map task are rewired to wait on the join, so downstream work only
+//! starts once every item has been handled.
+//!
+//! Join tasks need no agent: [`settle_joins`] completes them as soon as all
+//! children are terminal (done if every child succeeded, failed otherwise).
+//! Children and the join are parented to the map task, so the map shows up as
+//! an epic over its fan-out.
+
+use chrono::Utc;
+use std::collections::HashSet;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::graph::{LogEntry, MapSpec, Node, Status, Task, WorkGraph};
+use crate::trace_function::TaskTemplate;
+
+/// What happened to a map task during [`expand_map_tasks`].
+#[derive(Debug, Clone, PartialEq)]
+pub enum MapExpansion {
+    /// The map expanded into `children`, joined by `join`.
+    Expanded {
+        map_id: String,
+        children: Vec<String>,
+        join: String,
+    },
+    /// The item list could not be read; the map task was marked failed.
+    Failed { map_id: String, reason: String },
+}
+
+/// Expand every open map task whose source is done.
+///
+/// `workgraph_dir` is the `.workgraph` directory; artifact paths on the source
+/// task are resolved against its parent (the project root).
+pub fn expand_map_tasks(graph: &mut WorkGraph, workgraph_dir: &Path) -> Vec<MapExpansion> {
+    let pending: Vec<(String, MapSpec)> = graph
+        .tasks()
+        .filter(|t| matches!(t.status, Status::Open | Status::Blocked))
+        .filter_map(|t| {
+            let spec = t.map.as_ref().filter(|m| !m.is_expanded())?;
+            let source = graph.get_task(&spec.source)?;
+            (source.status == Status::Done).then(|| (t.id.clone(), spec.clone()))
+        })
+        .collect();
+
+    let mut results = Vec::new();
+    for (map_id, spec) in pending {
+        let outcome = graph
+            .get_task(&spec.source)
+            .ok_or_else(|| format!("source task '{}' not found", spec.source))
+            .and_then(|source| read_items(workgraph_dir, source, &spec.from))
+            .and_then(|items| expand(graph, &map_id, &spec, &items));
+        match outcome {
+            Ok(expansion) => results.push(expansion),
+            Err(reason) => {
+                if let Some(task) = graph.get_task_mut(&map_id) {
+                    let reason = format!("Map expansion failed: {}", reason);
+                    task.status = Status::Failed;
+                    task.failure_reason = Some(reason.clone());
+                    task.log.push(LogEntry {
+                        timestamp: Utc::now().to_rfc3339(),
+                        actor: None,
+                        message: reason.clone(),
+                    });
+                    results.push(MapExpansion::Failed { map_id, reason });
+                }
+            }
+        }
+    }
+    results
+}
+
+/// Complete join tasks whose children have all settled. Failed children in
+/// `retrying` are about to be retried and count as unsettled.
+///
+/// Returns `(join_id, status)` for every join that was completed.
+pub fn settle_joins(graph: &mut WorkGraph, retrying: &HashSet<String>) -> Vec<(String, Status)> {
+    let settled: Vec<(String, usize, usize)> = graph
+        .tasks()
+        .filter_map(|t| {
+            let spec = t.map.as_ref()?;
+            let join = graph.get_task(spec.join.as_deref()?)?;
+            if !matches!(join.status, Status::Open | Status::Blocked)
+                || spec.children.iter().any(|id| retrying.contains(id))
+            {
+                return None;
+            }
+            let statuses: Vec<Status> = spec
+                .children
+                .iter()
+                .filter_map(|id| graph.get_task(id).map(|c| c.status))
+                .collect();
+            if !statuses.iter().all(|s| s.is_terminal()) {
+                return None;
+            }
+            let failed = statuses.iter().filter(|s| **s != Status::Done).count();
+            Some((join.id.clone(), failed, statuses.len()))
+        })
+        .collect();
+
+    let mut results = Vec::new();
+    for (join_id, failed, total) in settled {
+        let Some(join) = graph.get_task_mut(&join_id) else {
+            continue;
+        };
+        let now = Utc::now().to_rfc3339();
+        let message = if failed == 0 {
+            join.status = Status::Done;
+            format!("All {} mapped tasks done", total)
+        } else {
+            join.status = Status::Failed;
+            let reason = format!("{} of {} mapped tasks did not succeed", failed, total);
+            join.failure_reason = Some(reason.clone());
+            reason
+        };
+        join.completed_at = Some(now.clone());
+        join.log.push(LogEntry {
+            timestamp: now,
+            actor: None,
+            message,
+        });
+        results.push((join_id, join.status));
+    }
+    results
+}
+
+/// Render `template` for one list item.
+pub fn render_template(template: &TaskTemplate, item: &str, index: usize) -> TaskTemplate {
+    let index = index.to_string();
+    let sub = |s: &str| s.replace("{{item}}", item).replace("{{index}}", &index);
+    TaskTemplate {
+        template_id: template.template_id.clone(),
+        title: sub(&template.title),
+        description: sub(&template.description),
+        skills: template.skills.iter().map(|s| sub(s)).collect(),
+        after: template.after.clone(),
+        loops_to: template.loops_to.clone(),
+        role_hint: template.role_hint.clone(),
+        deliverables: template.deliverables.iter().map(|d| sub(d)).collect(),
+        verify: template.verify.as_deref().map(sub),
+        tags: template.tags.iter().map(|t| sub(t)).collect(),
+    }
+}
+
+/// Read the JSON list named `from` on `source`.
+///
+/// `from` is matched against the source's artifacts (by full path or file
+/// name) first, then looked up under `.workgraph/output/<source>/`. Strings
+/// are used as-is; other items are substituted as compact JSON.
+pub fn read_items(workgraph_dir: &Path, source: &Task, from: &str) -> Result<Vec<String>, String> {
+    let path = items_path(workgraph_dir, source, from).ok_or_else(|| {
+        format!(
+            "no artifact or output named '{}' on task '{}'",
+            from, source.id
+        )
+    })?;
+    let content = fs::read_to_string(&path)
+        .map_err(|e| format!("failed to read {}: {}", path.display(), e))?;
+    let value: serde_json::Value = serde_json::from_str(&content)
+        .map_err(|e| format!("{} is not valid JSON: {}", path.display(), e))?;
+    let serde_json::Value::Array(items) = value else {
+        return Err(format!("{} does not contain a JSON list", path.display()));
+    };
+    Ok(items
+        .into_iter()
+        .map(|item| match item {
+            serde_json::Value::String(s) => s,
+            other => other.to_string(),
+        })
+        .collect())
+}
+
+fn items_path(workgraph_dir: &Path, source: &Task, from: &str) -> Option<PathBuf> {
+    let project_root = workgraph_dir.parent().unwrap_or(workgraph_dir);
+    let artifact = source
+        .artifacts
+        .iter()
+        .find(|a| a.as_str() == from || Path::new(a).file_name().is_some_and(|n| n == from));
+    if let Some(artifact) = artifact {
+        return Some(project_root.join(artifact));
+    }
+    let output = workgraph_dir.join("output").join(&source.id).join(from);
+    output.exists().then_some(output)
+}
+
+fn expand(
+    graph: &mut WorkGraph,
+    map_id: &str,
+    spec: &MapSpec,
+    items: &[String],
+) -> Result<MapExpansion, String> {
+    let child_ids: Vec<String> = (0..items.len())
+        .map(|i| format!("{}-{}", map_id, i))
+        .collect();
+    let join_id = format!("{}-join", map_id);
+    if let Some(taken) = child_ids
+        .iter()
+        .chain(std::iter::once(&join_id))
+        .find(|id| graph.get_node(id).is_some())
+    {
+        return Err(format!("task ID '{}' already exists", taken));
+    }
+
+    let now = Utc::now().to_rfc3339();
+    let (map_title, dependents) = {
+        let map_task = graph
+            .get_task(map_id)
+            .ok_or_else(|| format!("map task '{}' not found", map_id))?;
+        let dependents: Vec<String> = graph
+            .tasks()
+            .filter(|t| t.after.iter().any(|a| a == map_id))
+            .map(|t| t.id.clone())
+            .collect();
+        (map_task.title.clone(), dependents)
+    };
+
+    for (index, (item, child_id)) in items.iter().zip(&child_ids).enumerate() {
+        let rendered = render_template(&spec.template, item, index);
+        graph.add_node(Node::Task(Task {
+            id: child_id.clone(),
+            title: rendered.title,
+            description: (!rendered.description.is_empty()).then_some(rendered.description),
+            after: vec![map_id.to_string()],
+            before: vec![join_id.clone()],
+            skills: rendered.skills,
+            deliverables: rendered.deliverables,
+            verify: rendered.verify,
+            tags: rendered.tags,
+            created_at: Some(now.clone()),
+            parent: Some(map_id.to_string()),
+            ..Task::default()
+        }));
+    }
+
+    graph.add_node(Node::Task(Task {
+        id: join_id.clone(),
+        title: format!("Join: {}", map_title),
+        description: Some(format!(
+            "Completes automatically once all {} tasks mapped by '{}' have finished.",
+            child_ids.len(),
+            map_id
+        )),
+        after: std::iter::once(map_id.to_string())
+            .chain(child_ids.iter().cloned())
+            .collect(),
+        before: dependents.clone(),
+        tags: vec!["join".to_string()],
+        created_at: Some(now.clone()),
+        parent: Some(map_id.to_string()),
+        ..Task::default()
+    }));
+
+    // Downstream tasks wait on the join, with the same edge condition they
+    // had on the map task.
+    for dependent_id in &dependents {
+        if let Some(dependent) = graph.get_task_mut(dependent_id) {
+            dependent.after.push(join_id.clone());
+            if let Some(condition) = dependent.after_conditions.get(map_id).copied() {
+                dependent
+                    .after_conditions
+                    .insert(join_id.clone(), condition);
+            }
+        }
+    }
+
+    let map_task = graph
+        .get_task_mut(map_id)
+        .ok_or_else(|| format!("map task '{}' not found", map_id))?;
+    for id in child_ids.iter().chain(std::iter::once(&join_id)) {
+        if !map_task.before.contains(id) {
+            map_task.before.push(id.clone());
+        }
+    }
+    if let Some(ref mut map) = map_task.map {
+        map.children = child_ids.clone();
+        map.join = Some(join_id.clone());
+    }
+    map_task.status = Status::Done;
+    map_task.completed_at = Some(now.clone());
+    map_task.log.push(LogEntry {
+        timestamp: now,
+        actor: None,
+        message: format!(
+            "Expanded into {} tasks from '{}' of '{}'",
+            child_ids.len(),
+            spec.from,
+            spec.source
+        ),
+    });
+
+    Ok(MapExpansion::Expanded {
+        map_id: map_id.to_string(),
+        children: child_ids,
+        join: join_id,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::graph::EdgeCondition;
+    use tempfile::tempdir;
+
+    fn task(id: &str, status: Status) -> Task {
+        Task {
+            id: id.to_string(),
+            title: id.to_string(),
+            status,
+            ..Task::default()
+        }
+    }
+
+    fn map_task(id: &str, source: &str, from: &str) -> Task {
+        let mut t = task(id, Status::Open);
+        t.after = vec![source.to_string()];
+        t.after_conditions
+            .insert(source.to_string(), EdgeCondition::Success);
+        t.map = Some(MapSpec {
+            source: source.to_string(),
+            from: from.to_string(),
+            template: TaskTemplate {
+                template_id: id.to_string(),
+                title: "Port {{item}}".to_string(),
+                description: "Item #{{index}}: {{item}}".to_string(),
+                skills: vec![],
+                after: vec![],
+                loops_to: vec![],
+                role_hint: None,
+                deliverables: vec!["src/{{item}}.rs".to_string()],
+                verify: None,
+                tags: vec![],
+            },
+            children: vec![],
+            join: None,
+        });
+        t
+    }
+
+    /// A workgraph dir with `discover` done and `port` mapping over its
+    /// `modules.json` output, and `release` waiting on the map.
+    fn setup(items: &str) -> (tempfile::TempDir, WorkGraph) {
+        let tmp = tempdir().unwrap();
+        let wg_dir = tmp.path().join(".workgraph");
+        let output = wg_dir.join("output").join("discover");
+        fs::create_dir_all(&output).unwrap();
+        fs::write(output.join("modules.json"), items).unwrap();
+
+        let mut graph = WorkGraph::new();
+        graph.add_node(Node::Task(task("discover", Status::Done)));
+        graph.add_node(Node::Task(map_task("port", "discover", "modules.json")));
+        let mut release = task("release", Status::Open);
+        release.after = vec!["port".to_string()];
+        release
+            .after_conditions
+            .insert("port".to_string(), EdgeCondition::Success);
+        graph.add_node(Node::Task(release));
+        (tmp, graph)
+    }
+
+    #[test]
+    fn test_expand_creates_children_and_join() {
+        let (tmp, mut graph) = setup(r#"["parser", "lexer"]"#);
+        let results = expand_map_tasks(&mut graph, &tmp.path().join(".workgraph"));
+        assert_eq!(
+            results,
+            vec![MapExpansion::Expanded {
+                map_id: "port".to_string(),
+                children: vec!["port-0".to_string(), "port-1".to_string()],
+                join: "port-join".to_string(),
+            }]
+        );
+
+        let child = graph.get_task("port-1").unwrap();
+        assert_eq!(child.title, "Port lexer");
+        assert_eq!(child.description.as_deref(), Some("Item #1: lexer"));
+        assert_eq!(child.deliverables, vec!["src/lexer.rs"]);
+        assert_eq!(child.after, vec!["port"]);
+
+        let join = graph.get_task("port-join").unwrap();
+        assert_eq!(join.after, vec!["port", "port-0", "port-1"]);
+
+        let map = graph.get_task("port").unwrap();
+        assert_eq!(map.status, Status::Done);
+        assert!(map.map.as_ref().unwrap().is_expanded());
+        assert_eq!(child.parent.as_deref(), Some("port"));
+        assert_eq!(join.parent.as_deref(), Some("port"));
+
+        // Downstream work now waits on the join, with the same condition
+        let release = graph.get_task("release").unwrap();
+        assert!(release.after.contains(&"port-join".to_string()));
+        assert_eq!(release.after_condition("port-join"), EdgeCondition::Success);
+
+        // Expanding again is a no-op
+        assert!(expand_map_tasks(&mut graph, &tmp.path().join(".workgraph")).is_empty());
+    }
+
+    #[test]
+    fn test_expand_reads_artifact_of_source() {
+        let tmp = tempdir().unwrap();
+        let wg_dir = tmp.path().join(".workgraph");
+        fs::create_dir_all(tmp.path().join("out")).unwrap();
+        fs::write(tmp.path().join("out/tests.json"), r#"[{"file": "a.rs"}]"#).unwrap();
+
+        let mut source = task("find", Status::Done);
+        source.artifacts = vec!["out/tests.json".to_string()];
+        let mut graph = WorkGraph::new();
+        graph.add_node(Node::Task(source));
+        graph.add_node(Node::Task(map_task("fix", "find", "tests.json")));
+
+        expand_map_tasks(&mut graph, &wg_dir);
+        assert_eq!(
+            graph.get_task("fix-0").unwrap().title,
+            r#"Port {"file":"a.rs"}"#
+        );
+    }
+
+    #[test]
+    fn test_expand_fails_map_on_bad_list() {
+        let (tmp, mut graph) = setup(r#"{"not": "a list"}"#);
+        let results = expand_map_tasks(&mut graph, &tmp.path().join(".workgraph"));
+        assert!(matches!(results[0], MapExpansion::Failed { .. }));
+        let map = graph.get_task("port").unwrap();
+        assert_eq!(map.status, Status::Failed);
+        assert!(
+            map.failure_reason
+                .as_deref()
+                .unwrap()
+                .contains("does not contain a JSON list")
+        );
+    }
+
+    #[test]
+    fn test_settle_joins() {
+        let (tmp, mut graph) = setup(r#"["a", "b"]"#);
+        expand_map_tasks(&mut graph, &tmp.path().join(".workgraph"));
+        assert!(settle_joins(&mut graph, &HashSet::new()).is_empty());
+
+        graph.get_task_mut("port-0").unwrap().status = Status::Done;
+        graph.get_task_mut("port-1").unwrap().status = Status::Failed;
+        assert_eq!(
+            settle_joins(&mut graph, &HashSet::new()),
+            vec![("port-join".to_string(), Status::Failed)]
+        );
+        let join = graph.get_task("port-join").unwrap();
+        assert_eq!(
+            join.failure_reason.as_deref(),
+            Some("1 of 2 mapped tasks did not succeed")
+        );
+    }
+
+    #[test]
+    fn test_empty_list_joins_immediately() {
+        let (tmp, mut graph) = setup("[]");
+        expand_map_tasks(&mut graph, &tmp.path().join(".workgraph"));
+        assert_eq!(
+            settle_joins(&mut graph, &HashSet::new()),
+            vec![("port-join".to_string(), Status::Done)]
+        );
+    }
+}
@@ -0,0 +1,543 @@
+//! Human approval gates.
+//!
+//! A gate is a task that waits for people rather than an agent. Once its
+//! dependencies are met the coordinator asks the approvers for a decision,
+//! over Matrix when it is configured and in the service log otherwise.
+//! Approvers answer with `wg approve` / `wg reject`, from `wg gates --review`,
+//! or by sending `approve <task>` / `reject <task> <reason>` to the Matrix
+//! listener.
+//!
+//! Each approver has one vote; voting again replaces it. The gate is done as
+//! soon as `quorum` approvers approve, and its dependents run. It fails as
+//! soon as so many reject that the quorum is out of reach; tasks waiting on
+//! it are then abandoned, except `--after-failure` dependents, which run.
+//! Further downstream, edge conditions decide as after any abandoned task.
+
+use anyhow::{Context, Result};
+use chrono::Utc;
+use serde::Serialize;
+use std::path::Path;
+
+use crate::graph::{EdgeCondition, Gate, GateVote, LogEntry, Status, Task, WorkGraph};
+use crate::parser::{load_graph, save_graph};
+use crate::propagation::{Propagation, propagate};
+
+/// Where a gate stands.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Outcome {
+    Pending,
+    Approved,
+    Rejected,
+}
+
+/// Check that a gate's quorum can be met by its approvers.
+pub fn validate(gate: &Gate) -> Result<(), String> {
+    if gate.quorum == 0 {
+        return Err("Gate quorum must be at least 1".to_string());
+    }
+    if !gate.approvers.is_empty() && gate.quorum as usize > gate.approvers.len() {
+        return Err(format!(
+            "Gate quorum {} exceeds its {} approver(s)",
+            gate.quorum,
+            gate.approvers.len()
+        ));
+    }
+    Ok(())
+}
+
+/// The approver `actor` votes as, or `None` if they may not vote. An
+/// approver matches the actor, or the full Matrix user ID of a vote sent
+/// over Matrix, ignoring case; a localpart alone never matches, so
+/// `@alice:evil.org` can't vote as `@alice:example.org`. Gates without
+/// named approvers accept anyone.
+pub fn approver_for(gate: &Gate, actor: &str, matrix_id: Option<&str>) -> Option<String> {
+    if gate.approvers.is_empty() {
+        return Some(actor.to_string());
+    }
+    gate.approvers
+        .iter()
+        .find(|a| {
+            a.eq_ignore_ascii_case(actor) || matrix_id.is_some_and(|id| a.eq_ignore_ascii_case(id))
+        })
+        .cloned()
+}
+
+pub fn approvals(gate: &Gate) -> usize {
+    gate.votes.iter().filter(|v| v.approved).count()
+}
+
+pub fn rejections(gate: &Gate) -> usize {
+    gate.votes.iter().filter(|v| !v.approved).count()
+}
+
+/// Approvers who have not voted yet.
+pub fn awaiting(gate: &Gate) -> Vec<&str> {
+    gate.approvers
+        .iter()
+        .filter(|a| !gate.votes.iter().any(|v| &v.actor == *a))
+        .map(String::as_str)
+        .collect()
+}
+
+pub fn outcome(gate: &Gate) -> Outcome {
+    if approvals(gate) >= gate.quorum as usize {
+        return Outcome::Approved;
+    }
+    let reachable = if gate.approvers.is_empty() {
+        rejections(gate) == 0
+    } else {
+        gate.approvers.len() - rejections(gate) >= gate.quorum as usize
+    };
+    if reachable {
+        Outcome::Pending
+    } else {
+        Outcome::Rejected
+    }
+}
+
+/// Whether `task` is a gate that still waits for a decision.
+pub fn is_open(task: &Task) -> bool {
+    task.gate.is_some() && matches!(task.status, Status::Open | Status::Blocked)
+}
+
+/// The result of one vote.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Decision {
+    pub task_id: String,
+    /// Approver the vote was counted for
+    pub actor: String,
+    pub approved: bool,
+    pub outcome: Outcome,
+    pub approvals: usize,
+    pub quorum: u32,
+    /// Approvers who have yet to vote, while the gate is pending
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub awaiting: Vec<String>,
+    /// Tasks abandoned because the gate was rejected
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub abandoned: Vec<String>,
+}
+
+impl std::fmt::Display for Decision {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let verb = if self.approved {
+            "approved"
+        } else {
+            "rejected"
+        };
+        write!(f, "{} {} '{}'", self.actor, verb, self.task_id)?;
+        match self.outcome {
+            Outcome::Pending => write!(f, " ({} of {} approvals)", self.approvals, self.quorum),
+            Outcome::Approved => write!(f, "; gate passed"),
+            Outcome::Rejected => {
+                write!(f, "; gate rejected")?;
+                if !self.abandoned.is_empty() {
+                    write!(f, ", abandoned {}", self.abandoned.join(", "))?;
+                }
+                Ok(())
+            }
+        }
+    }
+}
+
+/// Record `actor`'s vote on gate `task_id` and settle the gate if the vote
+/// decides it. `matrix_id` is the sender of a vote made over Matrix.
+pub fn vote(
+    graph: &mut WorkGraph,
+    task_id: &str,
+    actor: &str,
+    matrix_id: Option<&str>,
+    approved: bool,
+    reason: Option<&str>,
+) -> Result<Decision> {
+    let task = graph
+        .get_task(task_id)
+        .with_context(|| format!("Task '{}' not found", task_id))?;
+    let Some(gate) = &task.gate else {
+        anyhow::bail!("Task '{}' is not an approval gate", task_id);
+    };
+    if !is_open(task) {
+        anyhow::bail!("Gate '{}' is already {}", task_id, task.status);
+    }
+    let waiting: Vec<&str> = task
+        .after
+        .iter()
+        .filter(|dep| {
+            graph
+                .get_task(dep)
+                .is_some_and(|d| !task.is_after_satisfied(dep, d.status))
+        })
+        .map(String::as_str)
+        .collect();
+    if !waiting.is_empty() {
+        anyhow::bail!(
+            "Gate '{}' is still waiting on: {}",
+            task_id,
+            waiting.join(", ")
+        );
+    }
+    let Some(approver) = approver_for(gate, actor, matrix_id) else {
+        anyhow::bail!(
+            "'{}' is not an approver of '{}' (approvers: {})",
+            actor,
+            task_id,
+            gate.approvers.join(", ")
+        );
+    };
+
+    let now = Utc::now().to_rfc3339();
+    let task = graph.get_task_mut(task_id).expect("checked above");
+    let gate = task.gate.as_mut().expect("checked above");
+    gate.votes.retain(|v| v.actor != approver);
+    gate.votes.push(GateVote {
+        actor: approver.clone(),
+        approved,
+        at: now.clone(),
+        reason: reason.map(String::from),
+    });
+    let outcome = outcome(gate);
+    let approvals = approvals(gate);
+    let quorum = gate.quorum;
+    let awaiting: Vec<String> = match outcome {
+        Outcome::Pending => awaiting(gate).into_iter().map(String::from).collect(),
+        _ => Vec::new(),
+    };
+    let approvers: Vec<String> = gate
+        .votes
+        .iter()
+        .filter(|v| v.approved)
+        .map(|v| v.actor.clone())
+        .collect();
+
+    let verb = if approved { "Approved" } else { "Rejected" };
+    task.log.push(LogEntry {
+        timestamp: now.clone(),
+        actor: Some(approver.clone()),
+        message: match reason {
+            Some(r) => format!("{} by {}: {}", verb, approver, r),
+            None => format!("{} by {}", verb, approver),
+        },
+    });
+
+    let mut abandoned = Vec::new();
+    match outcome {
+        Outcome::Pending => {}
+        Outcome::Approved => {
+            task.status = Status::Done;
+            task.completed_at = Some(now.clone());
+            task.log.push(LogEntry {
+                timestamp: now,
+                actor: None,
+                message: format!("Gate passed, approved by {}", approvers.join(", ")),
+            });
+        }
+        Outcome::Rejected => {
+            let message = match reason {
+                Some(r) => format!("Rejected by {}: {}", approver, r),
+                None => format!("Rejected by {}", approver),
+            };
+            task.status = Status::Failed;
+            task.failure_reason = Some(message.clone());
+            task.log.push(LogEntry {
+                timestamp: now,
+                actor: None,
+                message: format!("Gate rejected. {}", message),
+            });
+            abandoned = abandon_dependents(graph, task_id);
+        }
+    }
+
+    Ok(Decision {
+        task_id: task_id.to_string(),
+        actor: approver,
+        approved,
+        outcome,
+        approvals,
+        quorum,
+        awaiting,
+        abandoned,
+    })
+}
+
+/// Abandon the unfinished tasks that wait on rejected gate `gate_id`. A
+/// plain edge on a gate waits for its approval, so only edges that wait for
+/// the gate to fail are left alone. Tasks further downstream settle by their
+/// own edge conditions when the caller propagates.
+fn abandon_dependents(graph: &mut WorkGraph, gate_id: &str) -> Vec<String> {
+    let mut dependents: Vec<String> = graph
+        .tasks()
+        .filter(|t| matches!(t.status, Status::Open | Status::Blocked))
+        .filter(|t| t.after.iter().any(|a| a == gate_id))
+        .filter(|t| t.after_condition(gate_id) != EdgeCondition::Failure)
+        .map(|t| t.id.clone())
+        .collect();
+    dependents.sort();
+    let reason = format!("Gate '{}' was rejected", gate_id);
+    for dep in &dependents {
+        if let Some(task) = graph.get_task_mut(dep) {
+            task.status = Status::Abandoned;
+            task.failure_reason = Some(reason.clone());
+            task.log.push(LogEntry {
+                timestamp: Utc::now().to_rfc3339(),
+                actor: None,
+                message: reason.clone(),
+            });
+        }
+    }
+    dependents
+}
+
+/// Load the graph, vote, propagate the outcome, save it, wake the service
+/// and record the vote in provenance. Used by `wg approve` / `wg reject` and
+/// the Matrix listener; `source` says which.
+pub fn decide(
+    dir: &Path,
+    task_id: &str,
+    actor: &str,
+    matrix_id: Option<&str>,
+    approved: bool,
+    reason: Option<&str>,
+    source: &str,
+) -> Result<(Decision, Propagation)> {
+    let path = dir.join("graph.jsonl");
+    if !path.exists() {
+        anyhow::bail!("Workgraph not initialized. Run 'wg init' first.");
+    }
+    let mut graph = load_graph(&path).context("Failed to load graph")?;
+    let decision = vote(&mut graph, task_id, actor, matrix_id, approved, reason)?;
+    let propagation = propagate(&mut graph, dir);
+    save_graph(&graph, &path).context("Failed to save graph")?;
+    crate::service::ipc::notify_graph_changed(dir);
+    record(dir, &graph, &decision, reason, source);
+    Ok((decision, propagation))
+}
+
+/// Record a vote, and the gate's outcome if it decided it.
+pub fn record(
+    dir: &Path,
+    graph: &WorkGraph,
+    decision: &Decision,
+    reason: Option<&str>,
+    source: &str,
+) {
+    let threshold = crate::config::Config::load_or_default(dir)
+        .log
+        .rotation_threshold;
+    let op = if decision.approved {
+        "approve"
+    } else {
+        "reject"
+    };
+    let _ = crate::provenance::record(
+        dir,
+        op,
+        Some(&decision.task_id),
+        Some(&decision.actor),
+        serde_json::json!({
+            "reason": reason,
+            "source": source,
+            "approvals": decision.approvals,
+            "quorum": decision.quorum,
+        }),
+        threshold,
+    );
+    let op = match decision.outcome {
+        Outcome::Pending => return,
+        Outcome::Approved => "gate_approved",
+        Outcome::Rejected => "gate_rejected",
+    };
+    let votes = graph
+        .get_task(&decision.task_id)
+        .and_then(|t| t.gate.as_ref())
+        .map(|g| g.votes.clone())
+        .unwrap_or_default();
+    let _ = crate::provenance::record(
+        dir,
+        op,
+        Some(&decision.task_id),
+        None,
+        serde_json::json!({
+            "votes": votes,
+            "abandoned": decision.abandoned,
+        }),
+        threshold,
+    );
+}
+
+/// The message asking `task`'s approvers for a decision.
+pub fn request_message(task: &Task) -> String {
+    let Some(gate) = &task.gate else {
+        return String::new();
+    };
+    let who = if gate.approvers.is_empty() {
+        format!("{} from anyone", gate.quorum)
+    } else {
+        format!("{} of {}", gate.quorum, gate.approvers.join(", "))
+    };
+    format!(
+        "Approval needed for '{}': {} (needs {}). Reply `approve {}` or `reject {} <reason>`.",
+        task.id, task.title, who, task.id, task.id
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::graph::Node;
+
+    fn gate_task(approvers: &[&str], quorum: u32) -> Task {
+        Task {
+            id: "release".to_string(),
+            title: "Release sign-off".to_string(),
+            gate: Some(Gate {
+                approvers: approvers.iter().map(|a| a.to_string()).collect(),
+                quorum,
+                votes: Vec::new(),
+                requested_at: None,
+            }),
+            ..Task::default()
+        }
+    }
+
+    fn dependent(id: &str, after: &str, condition: Option<EdgeCondition>) -> Task {
+        let mut task = Task {
+            id: id.to_string(),
+            title: id.to_string(),
+            after: vec![after.to_string()],
+            ..Task::default()
+        };
+        if let Some(c) = condition {
+            task.after_conditions.insert(after.to_string(), c);
+        }
+        task
+    }
+
+    fn graph_with(tasks: Vec<Task>) -> WorkGraph {
+        let mut graph = WorkGraph::new();
+        for task in tasks {
+            graph.add_node(Node::Task(task));
+        }
+        graph
+    }
+
+    #[test]
+    fn test_quorum_of_named_approvers() {
+        let mut graph = graph_with(vec![
+            gate_task(&["alice", "@bob:example.org", "carol"], 2),
+            dependent("deploy", "release", None),
+        ]);
+
+        let d = vote(&mut graph, "release", "alice", None, true, None).unwrap();
+        assert_eq!(d.outcome, Outcome::Pending);
+        assert!(vote(&mut graph, "release", "mallory", None, true, None).is_err());
+
+        // A localpart alone doesn't make a Matrix user an approver
+        let err = vote(
+            &mut graph,
+            "release",
+            "@bob:evil.org",
+            Some("@bob:evil.org"),
+            true,
+            None,
+        )
+        .unwrap_err();
+        assert!(err.to_string().contains("not an approver"));
+
+        // Actors match ignoring case; a second vote replaces the first
+        let d = vote(&mut graph, "release", "Carol", None, false, None).unwrap();
+        assert_eq!(d.actor, "carol");
+        assert_eq!(d.outcome, Outcome::Pending);
+        let d = vote(&mut graph, "release", "carol", None, true, None).unwrap();
+        assert_eq!(d.outcome, Outcome::Approved);
+        assert_eq!(d.approvals, 2);
+
+        let gate = graph.get_task("release").unwrap();
+        assert_eq!(gate.status, Status::Done);
+        assert_eq!(gate.gate.as_ref().unwrap().votes.len(), 2);
+        assert_eq!(
+            awaiting(gate.gate.as_ref().unwrap()),
+            vec!["@bob:example.org"]
+        );
+        assert!(
+            vote(
+                &mut graph,
+                "release",
+                "bob",
+                Some("@Bob:example.org"),
+                true,
+                None
+            )
+            .is_err()
+        );
+    }
+
+    #[test]
+    fn test_rejection_abandons_downstream_but_not_failure_handlers() {
+        let mut graph = graph_with(vec![
+            gate_task(&["alice", "bob"], 2),
+            dependent("deploy", "release", None),
+            dependent("announce", "deploy", Some(EdgeCondition::Success)),
+            dependent("rollback-plan", "release", Some(EdgeCondition::Failure)),
+            dependent("notes", "deploy", None),
+            dependent("cleanup", "deploy", Some(EdgeCondition::Failure)),
+        ]);
+
+        let d = vote(
+            &mut graph,
+            "release",
+            "bob",
+            None,
+            false,
+            Some("tests flaky"),
+        )
+        .unwrap();
+        assert_eq!(d.outcome, Outcome::Rejected);
+        assert_eq!(d.abandoned, vec!["deploy"]);
+
+        // Past the gate's own dependents, edges settle as after any
+        // abandoned task
+        let tmp = tempfile::tempdir().unwrap();
+        let propagation = propagate(&mut graph, tmp.path());
+        assert_eq!(propagation.abandoned, vec!["announce", "cleanup"]);
+        assert_eq!(graph.get_task("notes").unwrap().status, Status::Open);
+
+        let gate = graph.get_task("release").unwrap();
+        assert_eq!(gate.status, Status::Failed);
+        assert_eq!(
+            gate.failure_reason.as_deref(),
+            Some("Rejected by bob: tests flaky")
+        );
+        assert_eq!(
+            graph.get_task("rollback-plan").unwrap().status,
+            Status::Open
+        );
+    }
+
+    #[test]
+    fn test_gate_waits_for_dependencies() {
+        let mut gate = gate_task(&[], 1);
+        gate.after = vec!["build".to_string()];
+        let mut graph = graph_with(vec![
+            Task {
+                id: "build".to_string(),
+                title: "build".to_string(),
+                ..Task::default()
+            },
+            gate,
+        ]);
+        let err = vote(&mut graph, "release", "anyone", None, true, None).unwrap_err();
+        assert!(err.to_string().contains("still waiting on: build"));
+
+        graph.get_task_mut("build").unwrap().status = Status::Done;
+        let d = vote(&mut graph, "release", "anyone", None, true, None).unwrap();
+        assert_eq!(d.outcome, Outcome::Approved);
+    }
+
+    #[test]
+    fn test_validate_quorum() {
+        assert!(validate(gate_task(&["a", "b"], 2).gate.as_ref().unwrap()).is_ok());
+        assert!(validate(gate_task(&["a"], 2).gate.as_ref().unwrap()).is_err());
+        assert!(validate(gate_task(&[], 0).gate.as_ref().unwrap()).is_err());
+    }
+}
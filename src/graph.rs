@@ -1,6 +1,9 @@
 use chrono::{Duration, Utc};
 use serde::{Deserialize, Deserializer, Serialize};
-use std::collections::{HashMap, HashSet};
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::path::{Path, PathBuf};
+
+use crate::trace_function::TaskTemplate;
 
 /// Configuration for structural cycle iteration.
 /// Only present on the cycle header task.
@@ -16,6 +19,188 @@ pub struct CycleConfig {
     pub delay: Option<String>,
 }
 
+/// Fan-out specification for a map task.
+///
+/// When `source` is done, the map task expands into one child per item of
+/// the JSON list found in `from`, each rendered from `template` with `{{item}}`
+/// and `{{index}}` substituted, plus a join task that waits on all of them.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct MapSpec {
+    /// Task whose artifact or output holds the list to map over
+    pub source: String,
+    /// Artifact path (or file name) on the source task, or a file under
+    /// `.workgraph/output/<source>/`, containing a JSON list
+    pub from: String,
+    /// Template for each child task
+    pub template: TaskTemplate,
+    /// Child task IDs, filled in when the map expands
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub children: Vec<String>,
+    /// Generated join task, filled in when the map expands
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub join: Option<String>,
+}
+
+impl MapSpec {
+    /// Whether the map has already been expanded into children.
+    pub fn is_expanded(&self) -> bool {
+        self.join.is_some()
+    }
+}
+
+/// Recurring schedule on a task (see [`crate::schedule`]).
+///
+/// A scheduled task is a template: each time the schedule fires, the service
+/// daemon creates a fresh copy of it. If the task owns a cycle config, the
+/// cycle is re-opened instead.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Schedule {
+    /// Cron expression (`minute hour day-of-month month day-of-week`), a macro
+    /// such as `@daily`, or a fixed interval such as `@every 6h`
+    pub cron: String,
+    /// IANA timezone the expression is evaluated in (default UTC)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub timezone: Option<String>,
+    /// What to do about windows missed while the service was not running
+    #[serde(default, skip_serializing_if = "CatchUp::is_default")]
+    pub catch_up: CatchUp,
+    /// Latest window handled, fired or skipped (RFC 3339)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub last_window: Option<String>,
+}
+
+/// Catch-up policy for missed schedule windows.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum CatchUp {
+    /// Drop missed windows; only fire a window that is due right now
+    Skip,
+    /// Fire once for all missed windows
+    #[default]
+    Once,
+    /// Fire once per missed window
+    All,
+}
+
+impl CatchUp {
+    fn is_default(&self) -> bool {
+        *self == CatchUp::default()
+    }
+}
+
+impl std::fmt::Display for CatchUp {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            CatchUp::Skip => write!(f, "skip"),
+            CatchUp::Once => write!(f, "once"),
+            CatchUp::All => write!(f, "all"),
+        }
+    }
+}
+
+impl std::str::FromStr for CatchUp {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "skip" => Ok(CatchUp::Skip),
+            "once" => Ok(CatchUp::Once),
+            "all" => Ok(CatchUp::All),
+            _ => Err(format!(
+                "Invalid catch-up policy '{}'. Valid values: skip, once, all",
+                s
+            )),
+        }
+    }
+}
+
+/// One automatic retry decision on a failed task (see [`crate::retry`]).
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RetryRecord {
+    /// The task's `retry_count` when the decision was made
+    pub attempt: u32,
+    /// When the failure was classified (RFC 3339)
+    pub decided_at: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub failure_reason: Option<String>,
+    /// Whether the failure was judged transient and retried
+    pub transient: bool,
+    /// Classifier rule that decided, e.g. `pattern 'rate.?limit'`
+    pub rule: String,
+    /// Earliest time of the retry, if one was scheduled (RFC 3339)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub next_attempt: Option<String>,
+}
+
+/// Human approval gate on a task (see [`crate::gate`]).
+///
+/// A gate task is never dispatched to an agent. It completes once `quorum`
+/// of its approvers approve, and fails once enough of them reject that the
+/// quorum can no longer be reached.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Gate {
+    /// Who may decide, as workgraph actors or Matrix user IDs. Empty means
+    /// anyone may.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub approvers: Vec<String>,
+    /// Approvals needed to pass
+    #[serde(default = "default_quorum")]
+    pub quorum: u32,
+    /// Latest vote of each approver, in the order they were cast
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub votes: Vec<GateVote>,
+    /// When the approvers were asked for a decision (RFC 3339)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub requested_at: Option<String>,
+}
+
+fn default_quorum() -> u32 {
+    1
+}
+
+/// One approver's decision on a gate.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct GateVote {
+    pub actor: String,
+    pub approved: bool,
+    /// When the vote was cast (RFC 3339)
+    pub at: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub reason: Option<String>,
+}
+
+/// Typed outputs a task must produce before it can be done (see
+/// [`crate::contract`]). Checked by `wg done`, which refuses completion,
+/// or fails the task when run by the spawn wrapper, until all are met.
+#[derive(Debug, Clone, PartialEq, Default, Serialize, Deserialize)]
+pub struct OutputContract {
+    /// Glob patterns, relative to the project root, that must each match
+    /// at least one file
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub files: Vec<String>,
+    /// JSON Schema the result document must satisfy
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub result_schema: Option<serde_json::Value>,
+    /// Where the result document is written, relative to the project root
+    /// (default: `.workgraph/output/<task-id>/result.json`). Setting this
+    /// without a schema only requires the document to be valid JSON.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub result_path: Option<String>,
+    /// Shell command, run from the project root, that must exit with
+    /// `check_status`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub check: Option<String>,
+    #[serde(default, skip_serializing_if = "is_zero_i32")]
+    pub check_status: i32,
+}
+
+impl OutputContract {
+    /// Whether the task must write a result document.
+    pub fn expects_result(&self) -> bool {
+        self.result_schema.is_some() || self.result_path.is_some()
+    }
+}
+
 /// Guard condition for a loop edge
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub enum LoopGuard {
@@ -25,6 +210,8 @@ pub enum LoopGuard {
     IterationLessThan(u32),
     /// Always loop (up to max_iterations)
     Always,
+    /// Loop while a guard expression holds (see [`crate::guard`])
+    Expr(String),
 }
 
 /// Parse a human-readable duration string like "30s", "5m", "1h", "24h" into seconds.
@@ -49,6 +236,49 @@ pub fn parse_delay(s: &str) -> Option<u64> {
     }
 }
 
+/// Generate a task ID from a title: its first three words as a slug,
+/// suffixed with `-2`, `-3`, ... if the ID is taken.
+pub fn generate_id(title: &str, graph: &WorkGraph) -> String {
+    // Generate a slug from the title
+    let slug: String = title
+        .to_lowercase()
+        .chars()
+        .map(|c| if c.is_alphanumeric() { c } else { '-' })
+        .collect::<String>()
+        .split('-')
+        .filter(|s| !s.is_empty())
+        .take(3)
+        .collect::<Vec<_>>()
+        .join("-");
+
+    let base_id = if slug.is_empty() {
+        "task".to_string()
+    } else {
+        slug
+    };
+
+    // Ensure uniqueness
+    if graph.get_node(&base_id).is_none() {
+        return base_id;
+    }
+
+    for i in 2..1000 {
+        let candidate = format!("{}-{}", base_id, i);
+        if graph.get_node(&candidate).is_none() {
+            return candidate;
+        }
+    }
+
+    // Fallback to timestamp
+    format!(
+        "task-{}",
+        std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .map(|d| d.as_secs())
+            .unwrap_or(0)
+    )
+}
+
 /// A log entry for tracking progress/notes on a task
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct LogEntry {
@@ -133,6 +363,47 @@ impl Status {
     }
 }
 
+/// Condition on an `after` edge: which terminal outcome of the predecessor
+/// lets the dependent task run.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum EdgeCondition {
+    /// Any terminal status (done, failed or abandoned) — the plain `after` edge
+    #[default]
+    Any,
+    /// Only if the predecessor is done
+    Success,
+    /// Only if the predecessor failed (compensation / cleanup tasks)
+    Failure,
+}
+
+impl EdgeCondition {
+    /// Whether a predecessor in `status` satisfies this condition.
+    pub fn is_satisfied_by(&self, status: Status) -> bool {
+        match self {
+            EdgeCondition::Any => status.is_terminal(),
+            EdgeCondition::Success => status == Status::Done,
+            EdgeCondition::Failure => status == Status::Failed,
+        }
+    }
+
+    /// Whether a predecessor in `status` has settled without satisfying
+    /// this condition, so the dependent can never run.
+    pub fn is_unsatisfiable_by(&self, status: Status) -> bool {
+        status.is_terminal() && !self.is_satisfied_by(status)
+    }
+}
+
+impl std::fmt::Display for EdgeCondition {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            EdgeCondition::Any => write!(f, "any"),
+            EdgeCondition::Success => write!(f, "success"),
+            EdgeCondition::Failure => write!(f, "failure"),
+        }
+    }
+}
+
 /// A task node.
 ///
 /// A task in the workgraph with dependencies, status, and execution metadata.
@@ -151,14 +422,30 @@ pub struct Task {
     pub status: Status,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub assigned: Option<String>,
+    /// Dispatch priority; higher runs first. Tasks this one waits on inherit
+    /// it (see [`crate::dispatch`]).
+    #[serde(default, skip_serializing_if = "is_zero_i32")]
+    pub priority: i32,
+    /// Who asked for the task, for weighted-fair dispatch
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub requester: Option<String>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub estimate: Option<Estimate>,
     #[serde(default, skip_serializing_if = "Vec::is_empty", alias = "blocks")]
     pub before: Vec<String>,
     #[serde(default, skip_serializing_if = "Vec::is_empty", alias = "blocked_by")]
     pub after: Vec<String>,
+    /// Conditions on individual `after` edges, keyed by predecessor ID.
+    /// Edges without an entry wait for any terminal status.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub after_conditions: BTreeMap<String, EdgeCondition>,
     #[serde(default, skip_serializing_if = "Vec::is_empty")]
     pub requires: Vec<String>,
+    /// Amounts held on individual `requires` edges while the task is in
+    /// progress, keyed by resource ID. Edges without an entry hold one unit
+    /// of a capacity resource and nothing of a budget.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub requires_amounts: BTreeMap<String, f64>,
     #[serde(default, skip_serializing_if = "Vec::is_empty")]
     pub tags: Vec<String>,
     /// Required skills/capabilities for this task
@@ -173,6 +460,10 @@ pub struct Task {
     /// Actual produced artifacts (paths/references)
     #[serde(default, skip_serializing_if = "Vec::is_empty")]
     pub artifacts: Vec<String>,
+    /// Named values published for dependents (`wg output set`), read by
+    /// `{{deps.<id>.outputs.<key>}}` bindings
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub outputs: BTreeMap<String, serde_json::Value>,
     /// Shell command to execute for this task (optional, for wg exec)
     #[serde(skip_serializing_if = "Option::is_none")]
     pub exec: Option<String>,
@@ -197,6 +488,12 @@ pub struct Task {
     /// Maximum number of retries allowed (None = unlimited)
     #[serde(skip_serializing_if = "Option::is_none")]
     pub max_retries: Option<u32>,
+    /// Automatic retry decisions, oldest first
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub retry_history: Vec<RetryRecord>,
+    /// Wall-clock limit for one agent run (e.g., "30m"). Overrides the executor default.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub timeout: Option<String>,
     /// Reason for failure or abandonment
     #[serde(skip_serializing_if = "Option::is_none")]
     pub failure_reason: Option<String>,
@@ -215,6 +512,22 @@ pub struct Task {
     /// Configuration for structural cycle iteration (only on cycle header tasks)
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub cycle_config: Option<CycleConfig>,
+    /// Fan-out specification (only on map tasks)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub map: Option<MapSpec>,
+    /// Epic this task belongs to. Grouping only: unlike `after`, it does not
+    /// order work. An epic completes once all its children are terminal.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub parent: Option<String>,
+    /// Recurring schedule; makes this task a template for scheduled runs
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub schedule: Option<Schedule>,
+    /// Approval gate; makes this task a human decision point
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub gate: Option<Gate>,
+    /// Typed outputs checked when the task is marked done
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub contract: Option<OutputContract>,
     /// Task is not ready until this timestamp (ISO 8601 / RFC 3339).
     /// Set by loop edges with a delay — prevents immediate dispatch after re-activation.
     #[serde(skip_serializing_if = "Option::is_none")]
@@ -228,12 +541,37 @@ pub struct Task {
     /// "peer" (richer view for credentialed peers).
     #[serde(default = "default_visibility", skip_serializing_if = "is_default_visibility")]
     pub visibility: String,
+    /// Incremented on every save that changes this task. Saves compare-and-swap
+    /// against it so concurrent writers cannot silently overwrite each other.
+    #[serde(default, skip_serializing_if = "is_zero_u64")]
+    pub revision: u64,
 }
 
 fn default_visibility() -> String {
     "internal".to_string()
 }
 
+impl Task {
+    /// The condition on this task's `after` edge to `predecessor`.
+    pub fn after_condition(&self, predecessor: &str) -> EdgeCondition {
+        self.after_conditions
+            .get(predecessor)
+            .copied()
+            .unwrap_or_default()
+    }
+
+    /// Whether the `after` edge to `predecessor` is satisfied by its status.
+    pub fn is_after_satisfied(&self, predecessor: &str, status: Status) -> bool {
+        self.after_condition(predecessor).is_satisfied_by(status)
+    }
+
+    /// Whether this task only serves as a template for scheduled runs and is
+    /// never dispatched itself. Scheduled cycle headers run normally.
+    pub fn is_schedule_template(&self) -> bool {
+        self.schedule.is_some() && self.cycle_config.is_none()
+    }
+}
+
 /// Deserialize loops_to accepting both old string format and array format.
 fn deserialize_loops_to<'de, D>(deserializer: D) -> Result<Vec<serde_json::Value>, D::Error>
 where
@@ -302,14 +640,22 @@ struct TaskHelper {
     #[serde(default)]
     assigned: Option<String>,
     #[serde(default)]
+    priority: i32,
+    #[serde(default)]
+    requester: Option<String>,
+    #[serde(default)]
     estimate: Option<Estimate>,
     #[serde(default, alias = "blocks")]
     before: Vec<String>,
     #[serde(default, alias = "blocked_by")]
     after: Vec<String>,
     #[serde(default)]
+    after_conditions: BTreeMap<String, EdgeCondition>,
+    #[serde(default)]
     requires: Vec<String>,
     #[serde(default)]
+    requires_amounts: BTreeMap<String, f64>,
+    #[serde(default)]
     tags: Vec<String>,
     #[serde(default)]
     skills: Vec<String>,
@@ -320,6 +666,8 @@ struct TaskHelper {
     #[serde(default)]
     artifacts: Vec<String>,
     #[serde(default)]
+    outputs: BTreeMap<String, serde_json::Value>,
+    #[serde(default)]
     exec: Option<String>,
     #[serde(default)]
     not_before: Option<String>,
@@ -336,6 +684,10 @@ struct TaskHelper {
     #[serde(default)]
     max_retries: Option<u32>,
     #[serde(default)]
+    retry_history: Vec<RetryRecord>,
+    #[serde(default)]
+    timeout: Option<String>,
+    #[serde(default)]
     failure_reason: Option<String>,
     #[serde(default)]
     model: Option<String>,
@@ -353,11 +705,23 @@ struct TaskHelper {
     #[serde(default)]
     cycle_config: Option<CycleConfig>,
     #[serde(default)]
+    map: Option<MapSpec>,
+    #[serde(default)]
+    parent: Option<String>,
+    #[serde(default)]
+    schedule: Option<Schedule>,
+    #[serde(default)]
+    gate: Option<Gate>,
+    #[serde(default)]
+    contract: Option<OutputContract>,
+    #[serde(default)]
     ready_after: Option<String>,
     #[serde(default)]
     paused: bool,
     #[serde(default = "default_visibility")]
     visibility: String,
+    #[serde(default)]
+    revision: u64,
     /// Old format: inline identity object. Migrated to `agent` hash on read.
     #[serde(default)]
     identity: Option<LegacyIdentity>,
@@ -386,15 +750,20 @@ impl<'de> Deserialize<'de> for Task {
             description: helper.description,
             status: helper.status,
             assigned: helper.assigned,
+            priority: helper.priority,
+            requester: helper.requester,
             estimate: helper.estimate,
             before: helper.before,
             after: helper.after,
+            after_conditions: helper.after_conditions,
             requires: helper.requires,
+            requires_amounts: helper.requires_amounts,
             tags: helper.tags,
             skills: helper.skills,
             inputs: helper.inputs,
             deliverables: helper.deliverables,
             artifacts: helper.artifacts,
+            outputs: helper.outputs,
             exec: helper.exec,
             not_before: helper.not_before,
             created_at: helper.created_at,
@@ -403,15 +772,23 @@ impl<'de> Deserialize<'de> for Task {
             log: helper.log,
             retry_count: helper.retry_count,
             max_retries: helper.max_retries,
+            retry_history: helper.retry_history,
+            timeout: helper.timeout,
             failure_reason: helper.failure_reason,
             model: helper.model,
             verify: helper.verify,
             agent,
             loop_iteration: helper.loop_iteration,
             cycle_config: helper.cycle_config,
+            map: helper.map,
+            parent: helper.parent,
+            schedule: helper.schedule,
+            gate: helper.gate,
+            contract: helper.contract,
             ready_after: helper.ready_after,
             paused: helper.paused,
             visibility: helper.visibility,
+            revision: helper.revision,
         })
     }
 }
@@ -420,6 +797,14 @@ fn is_zero(val: &u32) -> bool {
     *val == 0
 }
 
+fn is_zero_u64(val: &u64) -> bool {
+    *val == 0
+}
+
+fn is_zero_i32(val: &i32) -> bool {
+    *val == 0
+}
+
 fn is_bool_false(val: &bool) -> bool {
     !*val
 }
@@ -449,6 +834,96 @@ pub struct Resource {
     pub available: Option<f64>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub unit: Option<String>,
+    /// Spend limit the coordinator enforces, with `available` as the cap
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub budget: Option<Budget>,
+}
+
+/// Spend limit on a resource (see [`crate::budget`]).
+///
+/// Spend is read from the usage ledger. Without a `tag`, every task counts
+/// against the budget; tasks that list the resource in `requires` always do.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct Budget {
+    /// What is being limited
+    pub meter: Meter,
+    /// Period after which spend resets; none means the cap never resets
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub window: Option<BudgetWindow>,
+    /// Only count tasks carrying this tag
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tag: Option<String>,
+    /// Cap each task's own spend instead of the total
+    #[serde(default, skip_serializing_if = "is_bool_false")]
+    pub per_task: bool,
+}
+
+/// Quantity a budget limits.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Meter {
+    /// Dollar cost of agent runs
+    #[default]
+    Usd,
+    /// Tokens used by agent runs (input, output and cache)
+    Tokens,
+}
+
+impl std::fmt::Display for Meter {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Meter::Usd => write!(f, "usd"),
+            Meter::Tokens => write!(f, "tokens"),
+        }
+    }
+}
+
+impl std::str::FromStr for Meter {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "usd" | "cost" => Ok(Meter::Usd),
+            "tokens" => Ok(Meter::Tokens),
+            _ => Err(format!("Invalid meter '{}'. Valid values: usd, tokens", s)),
+        }
+    }
+}
+
+/// Calendar period a budget resets over, in UTC.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum BudgetWindow {
+    Daily,
+    /// Weeks start on Monday
+    Weekly,
+    Monthly,
+}
+
+impl std::fmt::Display for BudgetWindow {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            BudgetWindow::Daily => write!(f, "daily"),
+            BudgetWindow::Weekly => write!(f, "weekly"),
+            BudgetWindow::Monthly => write!(f, "monthly"),
+        }
+    }
+}
+
+impl std::str::FromStr for BudgetWindow {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "daily" | "day" => Ok(BudgetWindow::Daily),
+            "weekly" | "week" => Ok(BudgetWindow::Weekly),
+            "monthly" | "month" => Ok(BudgetWindow::Monthly),
+            _ => Err(format!(
+                "Invalid budget window '{}'. Valid values: daily, weekly, monthly",
+                s
+            )),
+        }
+    }
 }
 
 /// A node in the work graph (task or resource)
@@ -495,18 +970,30 @@ pub struct CycleAnalysis {
 impl CycleAnalysis {
     /// Compute cycle analysis from a WorkGraph's after edges.
     pub fn from_graph(graph: &WorkGraph) -> Self {
+        let edges = graph.tasks().flat_map(|task| {
+            task.after
+                .iter()
+                .filter(|dep_id| graph.get_task(dep_id).is_some())
+                .map(move |dep_id| (dep_id.as_str(), task.id.as_str()))
+        });
+        Self::from_edges(graph.tasks().map(|t| t.id.as_str()), edges)
+    }
+
+    /// Compute cycle analysis from task IDs and `(predecessor, successor)` edges,
+    /// for callers that have the edge list without the full graph.
+    /// Edges must only reference IDs in `task_ids`.
+    pub fn from_edges<'a>(
+        task_ids: impl IntoIterator<Item = &'a str>,
+        edges: impl IntoIterator<Item = (&'a str, &'a str)>,
+    ) -> Self {
         use crate::cycle::NamedGraph;
 
         let mut named = NamedGraph::new();
-        for task in graph.tasks() {
-            named.add_node(&task.id);
+        for id in task_ids {
+            named.add_node(id);
         }
-        for task in graph.tasks() {
-            for dep_id in &task.after {
-                if graph.get_task(dep_id).is_some() {
-                    named.add_edge(dep_id, &task.id);
-                }
-            }
+        for (from, to) in edges {
+            named.add_edge(from, to);
         }
 
         let metadata = named.analyze_cycles();
@@ -546,6 +1033,257 @@ impl CycleAnalysis {
     }
 }
 
+/// Node-level changes made to a graph since it was loaded from disk.
+///
+/// Journaled storage uses this to append only the nodes that changed instead
+/// of rewriting the whole file. Changes accumulate until the graph is dropped
+/// or re-tracked; re-appending an unchanged node is harmless on replay.
+///
+/// The first time a node is touched its prior contents are kept as the merge
+/// base, so a save can tell our edits apart from concurrent ones on disk (see
+/// [`merge_node`]).
+#[derive(Debug, Default)]
+pub struct ChangeSet {
+    /// Graph file the baseline was loaded from.
+    pub origin: PathBuf,
+    /// Node IDs inserted or mutably borrowed since the baseline.
+    pub touched: HashSet<String>,
+    /// Node IDs removed since the baseline.
+    pub removed: HashSet<String>,
+    /// Contents of each touched or removed node as this graph last knew it
+    /// on disk (`None` if the node did not exist). Updated after each save.
+    base: std::sync::Mutex<HashMap<String, Option<Node>>>,
+    /// Revisions tasks must still have in storage for the next save to
+    /// succeed (see [`WorkGraph::expect_revision`]). Cleared after each save.
+    expected_revisions: std::sync::Mutex<HashMap<String, u64>>,
+    /// How much of its storage this graph has seen, if the backend tracks it
+    storage_mark: std::sync::Mutex<Option<crate::parser::StorageMark>>,
+}
+
+impl Clone for ChangeSet {
+    fn clone(&self) -> Self {
+        Self {
+            origin: self.origin.clone(),
+            touched: self.touched.clone(),
+            removed: self.removed.clone(),
+            base: std::sync::Mutex::new(self.base_map().clone()),
+            expected_revisions: std::sync::Mutex::new(self.expected_map().clone()),
+            storage_mark: std::sync::Mutex::new(self.mark_slot().clone()),
+        }
+    }
+}
+
+impl ChangeSet {
+    fn base_map(&self) -> std::sync::MutexGuard<'_, HashMap<String, Option<Node>>> {
+        self.base.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    fn mark_slot(&self) -> std::sync::MutexGuard<'_, Option<crate::parser::StorageMark>> {
+        self.storage_mark.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    /// Take the storage mark left by the load or last save, for a save to
+    /// replace once it succeeds.
+    pub fn take_storage_mark(&self) -> Option<crate::parser::StorageMark> {
+        self.mark_slot().take()
+    }
+
+    pub fn set_storage_mark(&self, mark: crate::parser::StorageMark) {
+        *self.mark_slot() = Some(mark);
+    }
+
+    fn expected_map(&self) -> std::sync::MutexGuard<'_, HashMap<String, u64>> {
+        self.expected_revisions
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+    }
+
+    /// The merge base for `id`: what the node looked like before this graph
+    /// changed it.
+    pub fn base(&self, id: &str) -> Option<Node> {
+        self.base_map().get(id).cloned().flatten()
+    }
+
+    /// IDs of every node this graph changed or removed.
+    pub fn changed_ids(&self) -> impl Iterator<Item = &String> {
+        self.touched.iter().chain(self.removed.iter())
+    }
+
+    /// Record `node` as what is now on disk for `id`, so a later save of the
+    /// same graph merges against it instead of the original baseline.
+    pub fn rebase(&self, id: &str, node: Option<Node>) {
+        self.base_map().insert(id.to_string(), node);
+    }
+
+    fn record_base(&mut self, id: &str, node: Option<&Node>) {
+        self.base
+            .get_mut()
+            .unwrap_or_else(|e| e.into_inner())
+            .entry(id.to_string())
+            .or_insert_with(|| node.cloned());
+    }
+}
+
+/// A save lost a compare-and-swap race: another writer changed the same
+/// task fields since this graph was loaded.
+#[derive(Debug, Clone, PartialEq)]
+pub struct MergeConflict {
+    pub id: String,
+    pub reason: String,
+}
+
+impl std::fmt::Display for MergeConflict {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "Conflicting update to '{}': {}. Reload and retry.",
+            self.id, self.reason
+        )
+    }
+}
+
+impl std::error::Error for MergeConflict {}
+
+/// Three-way merge of one node for a compare-and-swap save.
+///
+/// `base` is the node as the saving graph loaded it, `disk` is what storage
+/// holds now and `ours` is the saving graph's version (`None` = absent or
+/// removed). If storage still matches the base, ours wins. Otherwise task
+/// fields changed on only one side are combined, and log entries appended on
+/// both sides are kept; a field changed differently on both sides is a
+/// conflict. The returned task's `revision` is one past the newest side.
+pub fn merge_node(
+    base: Option<&Node>,
+    disk: Option<&Node>,
+    ours: Option<&Node>,
+) -> Result<Option<Node>, MergeConflict> {
+    let id = ours
+        .or(disk)
+        .or(base)
+        .map(|n| n.id().to_string())
+        .unwrap_or_default();
+    let conflict = |reason: &str| MergeConflict {
+        id: id.clone(),
+        reason: reason.to_string(),
+    };
+
+    if ours == base {
+        return Ok(disk.cloned());
+    }
+    if disk == base {
+        return Ok(ours.map(|n| with_next_revision(n, base, disk)));
+    }
+    if without_revision(ours) == without_revision(disk) {
+        return Ok(disk.cloned());
+    }
+    match (base, disk, ours) {
+        (Some(Node::Task(b)), Some(Node::Task(d)), Some(Node::Task(o))) => {
+            let merged = merge_task_fields(b, d, o).map_err(|reason| {
+                conflict(&format!(
+                    "{} (revision {} -> {} on disk)",
+                    reason, b.revision, d.revision
+                ))
+            })?;
+            Ok(Some(with_next_revision(&Node::Task(merged), base, disk)))
+        }
+        (None, Some(_), Some(_)) => Err(conflict("it was created concurrently by another writer")),
+        (Some(_), None, Some(_)) => Err(conflict("it was removed by another writer")),
+        (Some(_), Some(_), None) => Err(conflict(
+            "it was changed by another writer before this removal",
+        )),
+        _ => Err(conflict("it was changed by another writer")),
+    }
+}
+
+fn without_revision(node: Option<&Node>) -> Option<Node> {
+    node.map(|n| match n {
+        Node::Task(t) => Node::Task(Task {
+            revision: 0,
+            ..t.clone()
+        }),
+        other => other.clone(),
+    })
+}
+
+fn with_next_revision(node: &Node, base: Option<&Node>, disk: Option<&Node>) -> Node {
+    let revision_of = |n: Option<&Node>| match n {
+        Some(Node::Task(t)) => t.revision,
+        _ => 0,
+    };
+    match node {
+        Node::Task(t) => Node::Task(Task {
+            revision: revision_of(base).max(revision_of(disk)) + 1,
+            ..t.clone()
+        }),
+        other => other.clone(),
+    }
+}
+
+/// Field-by-field merge of two edits of the same task. Fails on the first
+/// field both sides changed differently.
+fn merge_task_fields(base: &Task, disk: &Task, ours: &Task) -> Result<Task, String> {
+    use serde_json::{Map, Value};
+
+    let to_map = |t: &Task| match serde_json::to_value(t) {
+        Ok(Value::Object(map)) => map,
+        _ => Map::new(),
+    };
+    let (b, d, o) = (to_map(base), to_map(disk), to_map(ours));
+
+    let mut keys: Vec<&String> = b.keys().chain(d.keys()).chain(o.keys()).collect();
+    keys.sort();
+    keys.dedup();
+
+    let mut merged = Map::new();
+    for key in keys {
+        if key == "revision" {
+            continue;
+        }
+        let (bv, dv, ov) = (b.get(key), d.get(key), o.get(key));
+        let value = if ov == bv || ov == dv {
+            dv
+        } else if dv == bv {
+            ov
+        } else if let Some(log) = merge_appended(bv, dv, ov).filter(|_| key == "log") {
+            merged.insert(key.clone(), log);
+            continue;
+        } else {
+            return Err(format!(
+                "field '{}' was also changed by another writer",
+                key
+            ));
+        };
+        if let Some(v) = value {
+            merged.insert(key.clone(), v.clone());
+        }
+    }
+    serde_json::from_value(Value::Object(merged))
+        .map_err(|e| format!("merged task is invalid: {}", e))
+}
+
+/// Merge two arrays that both extend `base` by appending.
+fn merge_appended(
+    base: Option<&serde_json::Value>,
+    disk: Option<&serde_json::Value>,
+    ours: Option<&serde_json::Value>,
+) -> Option<serde_json::Value> {
+    let empty = Vec::new();
+    let items = |v: Option<&serde_json::Value>| -> Option<Vec<serde_json::Value>> {
+        match v {
+            None => Some(empty.clone()),
+            Some(serde_json::Value::Array(a)) => Some(a.clone()),
+            Some(_) => None,
+        }
+    };
+    let (b, d, o) = (items(base)?, items(disk)?, items(ours)?);
+    if !d.starts_with(&b) || !o.starts_with(&b) {
+        return None;
+    }
+    let mut merged = d;
+    merged.extend(o.into_iter().skip(b.len()));
+    Some(serde_json::Value::Array(merged))
+}
+
 /// The work graph: a directed task graph with dependency edges and optional loop edges.
 ///
 /// Tasks depend on other tasks via `after`/`blocks` edges. Resources are
@@ -556,6 +1294,9 @@ pub struct WorkGraph {
     nodes: HashMap<String, Node>,
     /// Cached cycle analysis. Lazily computed; invalidated on structural mutations.
     cycle_analysis: Option<CycleAnalysis>,
+    /// Changes since load. `None` for graphs built in memory, which are always
+    /// written out in full.
+    changes: Option<ChangeSet>,
 }
 
 impl WorkGraph {
@@ -563,12 +1304,110 @@ impl WorkGraph {
         Self {
             nodes: HashMap::new(),
             cycle_analysis: None,
+            changes: None,
+        }
+    }
+
+    /// Start tracking changes against the current contents, which are assumed
+    /// to match what is stored at `origin`.
+    pub fn track_changes(&mut self, origin: &Path) {
+        self.changes = Some(ChangeSet {
+            origin: origin.to_path_buf(),
+            ..ChangeSet::default()
+        });
+    }
+
+    /// Stop tracking changes, so the next save writes the whole graph
+    /// (e.g. when copying it to a different location or backend).
+    pub fn untrack_changes(&mut self) {
+        self.changes = None;
+    }
+
+    /// Changes since the graph was loaded, if it was loaded from disk.
+    pub fn changes(&self) -> Option<&ChangeSet> {
+        self.changes.as_ref()
+    }
+
+    /// Merge this graph's changes with what storage holds now.
+    ///
+    /// `current` looks up a node's stored version. Returns the nodes that
+    /// must be written (`None` = delete), or the first conflicting change.
+    /// Graphs without change tracking return nothing.
+    pub fn merge_changes(
+        &self,
+        mut current: impl FnMut(&str) -> Option<Node>,
+    ) -> Result<Vec<(String, Option<Node>)>, MergeConflict> {
+        let Some(changes) = self.changes.as_ref() else {
+            return Ok(Vec::new());
+        };
+        let mut ids: Vec<&String> = changes.changed_ids().collect();
+        ids.sort();
+        ids.dedup();
+
+        let expected = changes.expected_map().clone();
+        let mut writes = Vec::new();
+        for id in ids {
+            let disk = current(id);
+            if let Some(&revision) = expected.get(id.as_str()) {
+                let found = match &disk {
+                    Some(Node::Task(task)) => task.revision,
+                    _ => 0,
+                };
+                if found != revision {
+                    return Err(MergeConflict {
+                        id: id.clone(),
+                        reason: format!("expected revision {}, found {}", revision, found),
+                    });
+                }
+            }
+            let base = changes.base(id);
+            let merged = merge_node(base.as_ref(), disk.as_ref(), self.nodes.get(id.as_str()))?;
+            if merged != disk {
+                writes.push((id.clone(), merged));
+            }
+        }
+        Ok(writes)
+    }
+
+    /// Record that this graph's changes are now in storage, so saving it
+    /// again only merges what changed since.
+    pub fn mark_saved(&self) {
+        if let Some(ref changes) = self.changes {
+            for id in changes.changed_ids() {
+                changes.rebase(id, self.nodes.get(id.as_str()).cloned());
+            }
+            changes.expected_map().clear();
+        }
+    }
+
+    /// Make the next save fail with a [`MergeConflict`] unless task `id` is
+    /// still at `revision` in storage. The check runs inside the save's
+    /// compare-and-swap, so no write can slip in after it. Graphs built in
+    /// memory don't check.
+    pub fn expect_revision(&mut self, id: &str, revision: u64) {
+        self.mark_touched(id);
+        if let Some(ref changes) = self.changes {
+            changes.expected_map().insert(id.to_string(), revision);
+        }
+    }
+
+    fn mark_touched(&mut self, id: &str) {
+        if let Some(ref mut changes) = self.changes
+            && !changes.touched.contains(id)
+        {
+            changes.record_base(id, self.nodes.get(id));
+            changes.touched.insert(id.to_string());
         }
     }
 
     /// Insert a node (task or resource) into the graph.
     pub fn add_node(&mut self, node: Node) {
         self.cycle_analysis = None;
+        if let Some(ref mut changes) = self.changes {
+            changes.record_base(node.id(), self.nodes.get(node.id()));
+            changes.removed.remove(node.id());
+            changes.touched.insert(node.id().to_string());
+        }
         self.nodes.insert(node.id().to_string(), node);
     }
 
@@ -588,6 +1427,9 @@ impl WorkGraph {
     /// Look up a task by ID (mutable), returning `None` if the node is a resource.
     pub fn get_task_mut(&mut self, id: &str) -> Option<&mut Task> {
         self.cycle_analysis = None;
+        if self.nodes.contains_key(id) {
+            self.mark_touched(id);
+        }
         match self.nodes.get_mut(id) {
             Some(Node::Task(t)) => Some(t),
             _ => None,
@@ -603,6 +1445,9 @@ impl WorkGraph {
     /// Look up a task by ID (mutable), returning an error with did-you-mean suggestions if not found.
     pub fn get_task_mut_or_err(&mut self, id: &str) -> anyhow::Result<&mut Task> {
         self.cycle_analysis = None;
+        if self.nodes.contains_key(id) {
+            self.mark_touched(id);
+        }
         let err = self.task_not_found_error(id);
         self.nodes
             .get_mut(id)
@@ -664,12 +1509,31 @@ impl WorkGraph {
     pub fn remove_node(&mut self, id: &str) -> Option<Node> {
         self.cycle_analysis = None;
         let removed = self.nodes.remove(id);
-        if removed.is_some() {
-            for node in self.nodes.values_mut() {
-                if let Node::Task(task) = node {
+        if let Some(ref node) = removed {
+            if let Some(ref mut changes) = self.changes {
+                changes.record_base(id, Some(node));
+                changes.touched.remove(id);
+                changes.removed.insert(id.to_string());
+            }
+            let references = |task: &Task| {
+                task.after
+                    .iter()
+                    .chain(&task.before)
+                    .chain(&task.requires)
+                    .any(|dep| dep == id)
+            };
+            let rewired: Vec<String> = self
+                .tasks()
+                .filter(|t| references(t))
+                .map(|t| t.id.clone())
+                .collect();
+            for task_id in rewired {
+                self.mark_touched(&task_id);
+                if let Some(Node::Task(task)) = self.nodes.get_mut(&task_id) {
                     task.after.retain(|dep| dep != id);
                     task.before.retain(|dep| dep != id);
                     task.requires.retain(|dep| dep != id);
+                    task.requires_amounts.remove(id);
                 }
             }
         }
@@ -706,18 +1570,149 @@ impl WorkGraph {
 }
 
 /// Evaluate a guard condition against the current graph state.
-fn evaluate_guard(guard: &Option<LoopGuard>, graph: &WorkGraph) -> bool {
+///
+/// Returns whether the cycle may iterate and a sentence explaining why.
+fn evaluate_guard(
+    guard: &Option<LoopGuard>,
+    graph: &WorkGraph,
+    workgraph_dir: Option<&std::path::Path>,
+    iteration: u32,
+) -> (bool, String) {
     match guard {
-        None | Some(LoopGuard::Always) => true,
-        // IterationLessThan is checked by callers where iteration count is available.
-        Some(LoopGuard::IterationLessThan(_)) => true,
-        Some(LoopGuard::TaskStatus { task, status }) => graph
-            .get_task(task)
-            .map(|t| t.status == *status)
-            .unwrap_or(false),
+        None => (true, "no guard".to_string()),
+        Some(LoopGuard::Always) => (true, "guard is 'always'".to_string()),
+        Some(LoopGuard::IterationLessThan(n)) => (
+            iteration < *n,
+            format!("guard 'iteration < {}' with iteration {}", n, iteration),
+        ),
+        Some(LoopGuard::TaskStatus { task, status }) => match graph.get_task(task) {
+            Some(t) => (
+                t.status == *status,
+                format!("guard wants '{}' {}, it is {}", task, status, t.status),
+            ),
+            None => (false, format!("guard task '{}' not found", task)),
+        },
+        Some(LoopGuard::Expr(source)) => {
+            let ctx = crate::guard::GuardContext {
+                graph,
+                workgraph_dir,
+                iteration,
+            };
+            match crate::guard::evaluate(source, &ctx) {
+                Ok(eval) if eval.observed.is_empty() => {
+                    (eval.value, format!("guard `{}` is {}", source, eval.value))
+                }
+                Ok(eval) => (
+                    eval.value,
+                    format!(
+                        "guard `{}` is {} ({})",
+                        source,
+                        eval.value,
+                        eval.observed.join(", ")
+                    ),
+                ),
+                Err(e) => (
+                    false,
+                    format!("guard `{}` could not be evaluated: {}", source, e),
+                ),
+            }
+        }
+    }
+}
+
+/// Abandon waiting tasks whose `after` conditions can never be met.
+///
+/// A task with `--after-success X` cannot run once X failed or was abandoned,
+/// and `--after-failure X` cannot run once X is done or abandoned. Such tasks
+/// (open or blocked, not yet claimed) move to Abandoned with a reason. This
+/// cascades: an abandoned task can in turn strand its own dependents.
+/// Failed tasks in `retrying` are about to be retried, so they don't strand
+/// anything yet.
+///
+/// Returns the IDs of the abandoned tasks.
+pub fn abandon_unsatisfiable(graph: &mut WorkGraph, retrying: &HashSet<String>) -> Vec<String> {
+    let mut abandoned = Vec::new();
+    loop {
+        let mut stranded: Vec<(String, String)> = graph
+            .tasks()
+            .filter(|t| matches!(t.status, Status::Open | Status::Blocked))
+            .filter_map(|task| {
+                task.after_conditions
+                    .iter()
+                    .find_map(|(dep_id, condition)| {
+                        let dep = graph.get_task(dep_id)?;
+                        if !task.after.contains(dep_id)
+                            || retrying.contains(dep_id)
+                            || !condition.is_unsatisfiable_by(dep.status)
+                        {
+                            return None;
+                        }
+                        let wanted = match condition {
+                            EdgeCondition::Success => "succeed",
+                            EdgeCondition::Failure => "fail",
+                            EdgeCondition::Any => return None,
+                        };
+                        Some((
+                            task.id.clone(),
+                            format!(
+                                "Condition can never be met: requires '{}' to {}, but it is {}",
+                                dep_id, wanted, dep.status
+                            ),
+                        ))
+                    })
+            })
+            .collect();
+        if stranded.is_empty() {
+            return abandoned;
+        }
+        stranded.sort();
+
+        for (id, reason) in stranded {
+            if let Some(task) = graph.get_task_mut(&id) {
+                task.status = Status::Abandoned;
+                task.failure_reason = Some(reason.clone());
+                task.log.push(LogEntry {
+                    timestamp: Utc::now().to_rfc3339(),
+                    actor: None,
+                    message: reason,
+                });
+            }
+            abandoned.push(id);
+        }
     }
 }
 
+/// Why a completed cycle did or did not run another iteration.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CycleDecision {
+    /// Cycle member that carries the `CycleConfig`
+    pub cycle: String,
+    /// Task whose completion finished the iteration
+    pub trigger: String,
+    /// Iteration that just finished
+    pub iteration: u32,
+    pub max_iterations: u32,
+    pub iterated: bool,
+    pub reason: String,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub reactivated: Vec<String>,
+}
+
+/// Evaluate structural cycle iteration after a task transitions to Done.
+///
+/// Returns the list of task IDs that were re-activated. Guard expressions
+/// that read files, scores or commands fail without a workgraph directory;
+/// use [`evaluate_cycle_iteration_in`] to supply one.
+pub fn evaluate_cycle_iteration(
+    graph: &mut WorkGraph,
+    completed_task_id: &str,
+    cycle_analysis: &CycleAnalysis,
+) -> Vec<String> {
+    evaluate_cycle_iteration_in(graph, completed_task_id, cycle_analysis, None)
+        .map(|decision| decision.reactivated)
+        .unwrap_or_default()
+}
+
 /// Evaluate structural cycle iteration after a task transitions to Done.
 ///
 /// Checks if the completed task is part of a structural cycle (detected via
@@ -729,68 +1724,72 @@ fn evaluate_guard(guard: &Option<LoopGuard>, graph: &WorkGraph) -> bool {
 /// 4. If iterating: re-open all cycle members, increment `loop_iteration`,
 ///    optionally set `ready_after` if delay is configured.
 ///
-/// Returns the list of task IDs that were re-activated.
-pub fn evaluate_cycle_iteration(
+/// Returns `None` if the cycle was not ready to decide (not in a configured
+/// cycle, or members still running), otherwise the decision taken.
+pub fn evaluate_cycle_iteration_in(
     graph: &mut WorkGraph,
     completed_task_id: &str,
     cycle_analysis: &CycleAnalysis,
-) -> Vec<String> {
+    workgraph_dir: Option<&std::path::Path>,
+) -> Option<CycleDecision> {
     // 1. Check if the completed task is in a cycle
-    let cycle_idx = match cycle_analysis.task_to_cycle.get(completed_task_id) {
-        Some(&idx) => idx,
-        None => return vec![],
-    };
+    let cycle_idx = *cycle_analysis.task_to_cycle.get(completed_task_id)?;
 
     let cycle = &cycle_analysis.cycles[cycle_idx];
 
     // 2. Find the cycle member with CycleConfig (may differ from SCC header).
     //    The spec requires exactly one member to have it; wg check enforces this.
-    let (config_owner_id, cycle_config) = {
-        let mut found = None;
-        for member_id in &cycle.members {
-            if let Some(task) = graph.get_task(member_id)
-                && let Some(ref config) = task.cycle_config {
-                    found = Some((member_id.clone(), config.clone()));
-                    break;
-                }
-        }
-        match found {
-            Some(pair) => pair,
-            None => return vec![], // No config = no cycle iteration
-        }
-    };
+    //    No config = no cycle iteration.
+    let (config_owner_id, cycle_config) = cycle.members.iter().find_map(|member_id| {
+        let config = graph.get_task(member_id)?.cycle_config.clone()?;
+        Some((member_id.clone(), config))
+    })?;
 
     // 3. Check if ALL cycle members are Done
     for member_id in &cycle.members {
         match graph.get_task(member_id) {
             Some(t) if t.status == Status::Done => {}
-            _ => return vec![], // Not all done yet
+            _ => return None, // Not all done yet
         }
     }
 
-    // 4. Check convergence tag on config owner (or any member)
-    if let Some(owner) = graph.get_task(&config_owner_id)
-        && owner.tags.contains(&"converged".to_string()) {
-            return vec![];
-        }
-
-    // 5. Check max_iterations (using config owner's loop_iteration)
     let current_iter = graph
         .get_task(&config_owner_id)
         .map(|t| t.loop_iteration)
         .unwrap_or(0);
+    let mut decision = CycleDecision {
+        cycle: config_owner_id.clone(),
+        trigger: completed_task_id.to_string(),
+        iteration: current_iter,
+        max_iterations: cycle_config.max_iterations,
+        iterated: false,
+        reason: String::new(),
+        reactivated: vec![],
+    };
+
+    // 4. Check convergence tag on config owner (or any member)
+    if let Some(owner) = graph.get_task(&config_owner_id)
+        && owner.tags.contains(&"converged".to_string())
+    {
+        decision.reason = format!("'{}' is tagged converged", config_owner_id);
+        return Some(decision);
+    }
+
+    // 5. Check max_iterations (using config owner's loop_iteration)
     if current_iter >= cycle_config.max_iterations {
-        return vec![];
+        decision.reason = format!(
+            "reached max iterations ({}/{})",
+            current_iter, cycle_config.max_iterations
+        );
+        return Some(decision);
     }
 
     // 6. Check guard condition
-    if !evaluate_guard(&cycle_config.guard, graph) {
-        return vec![];
+    let (pass, reason) = evaluate_guard(&cycle_config.guard, graph, workgraph_dir, current_iter);
+    decision.reason = reason;
+    if !pass {
+        return Some(decision);
     }
-    if let Some(LoopGuard::IterationLessThan(n)) = &cycle_config.guard
-        && current_iter >= *n {
-            return vec![];
-        }
 
     // 7. All checks passed — re-open all cycle members
     let new_iteration = current_iter + 1;
@@ -804,8 +1803,6 @@ pub fn evaluate_cycle_iteration(
             _ => None,
         });
 
-    let mut reactivated = Vec::new();
-
     for member_id in &cycle.members {
         if let Some(task) = graph.get_task_mut(member_id) {
             task.status = Status::Open;
@@ -826,11 +1823,12 @@ pub fn evaluate_cycle_iteration(
                 ),
             });
 
-            reactivated.push(member_id.clone());
+            decision.reactivated.push(member_id.clone());
         }
     }
 
-    reactivated
+    decision.iterated = true;
+    Some(decision)
 }
 
 /// Compute Levenshtein edit distance between two strings.
@@ -937,6 +1935,177 @@ mod tests {
         assert!(t2.requires.is_empty(), "requires should be cleaned");
     }
 
+    #[test]
+    fn test_change_tracking_records_touched_and_removed() {
+        let mut graph = WorkGraph::new();
+        graph.add_node(Node::Task(make_task("a", "A")));
+        let mut b = make_task("b", "B");
+        b.after = vec!["a".to_string()];
+        graph.add_node(Node::Task(b));
+        graph.add_node(Node::Task(make_task("c", "C")));
+        assert!(graph.changes().is_none());
+
+        graph.track_changes(Path::new("graph.jsonl"));
+        graph.get_task_mut("c").unwrap().title = "C2".to_string();
+        graph.remove_node("a");
+
+        let changes = graph.changes().unwrap();
+        assert_eq!(changes.origin, Path::new("graph.jsonl"));
+        // "b" lost its edge to "a", so it was rewritten too
+        assert!(changes.touched.contains("b"));
+        assert!(changes.touched.contains("c"));
+        assert!(!changes.touched.contains("a"));
+        assert!(changes.removed.contains("a"));
+
+        graph.add_node(Node::Task(make_task("a", "A again")));
+        let changes = graph.changes().unwrap();
+        assert!(changes.touched.contains("a"));
+        assert!(!changes.removed.contains("a"));
+    }
+
+    fn log_entry(message: &str) -> LogEntry {
+        LogEntry {
+            timestamp: "2026-01-01T00:00:00Z".to_string(),
+            actor: None,
+            message: message.to_string(),
+        }
+    }
+
+    #[test]
+    fn test_merge_node_takes_ours_when_disk_unchanged() {
+        let base = Node::Task(make_task("t1", "Original"));
+        let ours = Node::Task(make_task("t1", "Ours"));
+        let merged = merge_node(Some(&base), Some(&base), Some(&ours)).unwrap();
+        match merged {
+            Some(Node::Task(t)) => {
+                assert_eq!(t.title, "Ours");
+                assert_eq!(t.revision, 1);
+            }
+            other => panic!("unexpected merge result: {:?}", other),
+        }
+
+        // Nothing changed on our side: keep disk as is
+        let merged = merge_node(Some(&base), Some(&base), Some(&base)).unwrap();
+        assert_eq!(merged, Some(base));
+    }
+
+    #[test]
+    fn test_merge_node_combines_different_fields() {
+        let mut base_task = make_task("t1", "Original");
+        base_task.revision = 3;
+        let mut disk = base_task.clone();
+        disk.artifacts.push("out.txt".to_string());
+        disk.log.push(log_entry("from disk"));
+        disk.revision = 4;
+        let mut ours = base_task.clone();
+        ours.log.push(log_entry("from ours"));
+
+        let merged = merge_node(
+            Some(&Node::Task(base_task)),
+            Some(&Node::Task(disk)),
+            Some(&Node::Task(ours)),
+        )
+        .unwrap();
+        let Some(Node::Task(t)) = merged else {
+            panic!("expected a task");
+        };
+        assert_eq!(t.artifacts, vec!["out.txt"]);
+        let messages: Vec<&str> = t.log.iter().map(|e| e.message.as_str()).collect();
+        assert_eq!(messages, vec!["from disk", "from ours"]);
+        assert_eq!(t.revision, 5);
+    }
+
+    #[test]
+    fn test_merge_node_conflicts_on_same_field() {
+        let base = make_task("t1", "Original");
+        let mut disk = base.clone();
+        disk.title = "Theirs".to_string();
+        disk.revision = 1;
+        let mut ours = base.clone();
+        ours.title = "Ours".to_string();
+
+        let err = merge_node(
+            Some(&Node::Task(base.clone())),
+            Some(&Node::Task(disk.clone())),
+            Some(&Node::Task(ours)),
+        )
+        .unwrap_err();
+        assert_eq!(err.id, "t1");
+        assert!(err.reason.contains("title"));
+
+        // Removing a task someone else just changed is a conflict too
+        assert!(merge_node(Some(&Node::Task(base)), Some(&Node::Task(disk)), None).is_err());
+    }
+
+    #[test]
+    fn test_edge_condition_satisfaction() {
+        assert!(EdgeCondition::Any.is_satisfied_by(Status::Failed));
+        assert!(!EdgeCondition::Any.is_unsatisfiable_by(Status::Abandoned));
+        assert!(EdgeCondition::Success.is_satisfied_by(Status::Done));
+        assert!(EdgeCondition::Success.is_unsatisfiable_by(Status::Failed));
+        assert!(EdgeCondition::Failure.is_satisfied_by(Status::Failed));
+        assert!(EdgeCondition::Failure.is_unsatisfiable_by(Status::Done));
+        assert!(!EdgeCondition::Failure.is_unsatisfiable_by(Status::InProgress));
+    }
+
+    #[test]
+    fn test_after_conditions_roundtrip() {
+        let mut task = make_task("t2", "Cleanup");
+        task.after = vec!["t1".to_string()];
+        task.after_conditions
+            .insert("t1".to_string(), EdgeCondition::Failure);
+        let json = serde_json::to_string(&Node::Task(task)).unwrap();
+        assert!(json.contains(r#""after_conditions":{"t1":"failure"}"#));
+
+        let Node::Task(parsed) = serde_json::from_str(&json).unwrap() else {
+            panic!("expected a task");
+        };
+        assert_eq!(parsed.after_condition("t1"), EdgeCondition::Failure);
+        assert_eq!(parsed.after_condition("other"), EdgeCondition::Any);
+    }
+
+    #[test]
+    fn test_abandon_unsatisfiable_cascades() {
+        let mut graph = WorkGraph::new();
+        let mut build = make_task("build", "Build");
+        build.status = Status::Failed;
+        let mut deploy = make_task("deploy", "Deploy");
+        deploy.after = vec!["build".to_string()];
+        deploy
+            .after_conditions
+            .insert("build".to_string(), EdgeCondition::Success);
+        let mut announce = make_task("announce", "Announce");
+        announce.after = vec!["deploy".to_string()];
+        announce
+            .after_conditions
+            .insert("deploy".to_string(), EdgeCondition::Success);
+        let mut rollback = make_task("rollback", "Rollback");
+        rollback.after = vec!["build".to_string()];
+        rollback
+            .after_conditions
+            .insert("build".to_string(), EdgeCondition::Failure);
+        for task in [build, deploy, announce, rollback] {
+            graph.add_node(Node::Task(task));
+        }
+
+        let abandoned = abandon_unsatisfiable(&mut graph, &HashSet::new());
+        assert_eq!(abandoned, vec!["deploy", "announce"]);
+
+        let deploy = graph.get_task("deploy").unwrap();
+        assert_eq!(deploy.status, Status::Abandoned);
+        assert!(
+            deploy
+                .failure_reason
+                .as_deref()
+                .unwrap()
+                .contains("requires 'build' to succeed")
+        );
+        assert_eq!(graph.get_task("rollback").unwrap().status, Status::Open);
+
+        // Nothing left to do on a second pass
+        assert!(abandon_unsatisfiable(&mut graph, &HashSet::new()).is_empty());
+    }
+
     #[test]
     fn test_tasks_iterator() {
         let mut graph = WorkGraph::new();
@@ -0,0 +1,740 @@
+//! Guard expressions for cycles.
+//!
+//! A cycle's `CycleConfig` can carry an expression that must hold for the
+//! cycle to run another iteration, e.g.
+//!
+//! ```text
+//! score(review) < 0.8 && iteration < 5
+//! contains("reports/test.txt", "FAIL") || exec("cargo test --quiet") != 0
+//! status(lint) == "failed" and not exists("LGTM")
+//! ```
+//!
+//! Grammar (lowest precedence first):
+//!
+//! ```text
+//! expr    := and (("||" | "or") and)*
+//! and     := unary (("&&" | "and") unary)*
+//! unary   := ("!" | "not") unary | primary
+//! primary := "(" expr ")" | "true" | "false" | "always"
+//!          | "exists" "(" string ")" | "contains" "(" string "," string ")"
+//!          | value cmp value
+//! value   := number | string | "iteration"
+//!          | "score" "(" task ")" | "status" "(" task ")" | "exec" "(" string ")"
+//! cmp     := "<" | "<=" | ">" | ">=" | "==" | "!="
+//! ```
+//!
+//! `score` is the latest evaluation score of a task, `status` its status,
+//! `exec` the exit code of a shell command run in the project root (one
+//! still running after a minute is killed, and the guard fails), and
+//! `iteration` the iteration that just finished. Paths are relative to the
+//! project root. Expressions are type-checked when parsed, so mistakes surface
+//! at `wg add`/`wg edit` time rather than when the cycle completes.
+
+use std::fmt;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+use std::time::{Duration, Instant};
+
+use crate::graph::{Status, WorkGraph};
+
+/// How long an `exec` guard command may run before the guard fails.
+const EXEC_TIMEOUT: Duration = Duration::from_secs(60);
+
+/// A parsed guard expression.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Expr {
+    Bool(bool),
+    Not(Box<Expr>),
+    And(Box<Expr>, Box<Expr>),
+    Or(Box<Expr>, Box<Expr>),
+    Compare(Value, CmpOp, Value),
+    /// File exists
+    Exists(String),
+    /// File contains text
+    Contains(String, String),
+}
+
+/// An operand of a comparison.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Value {
+    Number(f64),
+    Str(String),
+    Iteration,
+    Score(String),
+    Status(String),
+    Exec(String),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CmpOp {
+    Lt,
+    Le,
+    Gt,
+    Ge,
+    Eq,
+    Ne,
+}
+
+impl fmt::Display for CmpOp {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let s = match self {
+            CmpOp::Lt => "<",
+            CmpOp::Le => "<=",
+            CmpOp::Gt => ">",
+            CmpOp::Ge => ">=",
+            CmpOp::Eq => "==",
+            CmpOp::Ne => "!=",
+        };
+        f.write_str(s)
+    }
+}
+
+impl fmt::Display for Value {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Value::Number(n) => write!(f, "{}", n),
+            Value::Str(s) => write!(f, "{:?}", s),
+            Value::Iteration => f.write_str("iteration"),
+            Value::Score(task) => write!(f, "score({})", task),
+            Value::Status(task) => write!(f, "status({})", task),
+            Value::Exec(cmd) => write!(f, "exec({:?})", cmd),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Kind {
+    Number,
+    Text,
+}
+
+impl Value {
+    fn kind(&self) -> Kind {
+        match self {
+            Value::Number(_) | Value::Iteration | Value::Score(_) | Value::Exec(_) => Kind::Number,
+            Value::Str(_) | Value::Status(_) => Kind::Text,
+        }
+    }
+}
+
+/// What a guard can see while it is evaluated.
+pub struct GuardContext<'a> {
+    pub graph: &'a WorkGraph,
+    /// The `.workgraph` directory; needed by `score`, `exec`, `exists` and
+    /// `contains`, which fail without it
+    pub workgraph_dir: Option<&'a Path>,
+    /// Iteration of the cycle that just finished
+    pub iteration: u32,
+}
+
+/// Result of evaluating a guard, with the observed values that decided it.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Evaluation {
+    pub value: bool,
+    /// One entry per observed value, e.g. `score(review) = 0.62`
+    pub observed: Vec<String>,
+}
+
+/// Parse and type-check a guard expression.
+pub fn parse(source: &str) -> Result<Expr, String> {
+    let tokens = tokenize(source)?;
+    if tokens.is_empty() {
+        return Err("empty guard expression".to_string());
+    }
+    let mut parser = Parser { tokens, pos: 0 };
+    let expr = parser.expr()?;
+    if let Some(token) = parser.peek() {
+        return Err(format!("unexpected {} after expression", token));
+    }
+    Ok(expr)
+}
+
+/// Parse and evaluate `source`.
+pub fn evaluate(source: &str, ctx: &GuardContext) -> Result<Evaluation, String> {
+    let expr = parse(source)?;
+    let mut observed = Vec::new();
+    let value = expr.eval(ctx, &mut observed)?;
+    Ok(Evaluation { value, observed })
+}
+
+impl Expr {
+    fn eval(&self, ctx: &GuardContext, observed: &mut Vec<String>) -> Result<bool, String> {
+        match self {
+            Expr::Bool(b) => Ok(*b),
+            Expr::Not(inner) => Ok(!inner.eval(ctx, observed)?),
+            Expr::And(a, b) => Ok(a.eval(ctx, observed)? && b.eval(ctx, observed)?),
+            Expr::Or(a, b) => Ok(a.eval(ctx, observed)? || b.eval(ctx, observed)?),
+            Expr::Exists(path) => {
+                let exists = resolve_path(ctx, path)?.exists();
+                observed.push(format!("exists({:?}) = {}", path, exists));
+                Ok(exists)
+            }
+            Expr::Contains(path, needle) => {
+                let full = resolve_path(ctx, path)?;
+                let content = std::fs::read_to_string(&full)
+                    .map_err(|e| format!("cannot read {}: {}", full.display(), e))?;
+                let found = content.contains(needle.as_str());
+                observed.push(format!("contains({:?}, {:?}) = {}", path, needle, found));
+                Ok(found)
+            }
+            Expr::Compare(left, op, right) => {
+                let l = left.resolve(ctx, observed)?;
+                let r = right.resolve(ctx, observed)?;
+                Ok(match (l, r) {
+                    (Resolved::Number(a), Resolved::Number(b)) => match op {
+                        CmpOp::Lt => a < b,
+                        CmpOp::Le => a <= b,
+                        CmpOp::Gt => a > b,
+                        CmpOp::Ge => a >= b,
+                        CmpOp::Eq => a == b,
+                        CmpOp::Ne => a != b,
+                    },
+                    (Resolved::Text(a), Resolved::Text(b)) => match op {
+                        CmpOp::Eq => a == b,
+                        CmpOp::Ne => a != b,
+                        _ => return Err(format!("cannot order text with {}", op)),
+                    },
+                    _ => return Err(format!("cannot compare {} with {}", left, right)),
+                })
+            }
+        }
+    }
+}
+
+enum Resolved {
+    Number(f64),
+    Text(String),
+}
+
+impl Value {
+    fn resolve(&self, ctx: &GuardContext, observed: &mut Vec<String>) -> Result<Resolved, String> {
+        let resolved = match self {
+            Value::Number(n) => return Ok(Resolved::Number(*n)),
+            Value::Str(s) => return Ok(Resolved::Text(s.clone())),
+            Value::Iteration => Resolved::Number(ctx.iteration as f64),
+            Value::Status(task) => {
+                let task = ctx
+                    .graph
+                    .get_task(task)
+                    .ok_or_else(|| format!("task '{}' not found", task))?;
+                Resolved::Text(task.status.to_string())
+            }
+            Value::Score(task) => Resolved::Number(latest_score(ctx, task)?),
+            Value::Exec(cmd) => Resolved::Number(run_command(ctx, cmd)? as f64),
+        };
+        let shown = match &resolved {
+            Resolved::Number(n) => n.to_string(),
+            Resolved::Text(s) => s.clone(),
+        };
+        observed.push(format!("{} = {}", self, shown));
+        Ok(resolved)
+    }
+}
+
+fn workgraph_dir<'a>(ctx: &GuardContext<'a>) -> Result<&'a Path, String> {
+    ctx.workgraph_dir
+        .ok_or_else(|| "no workgraph directory to evaluate against".to_string())
+}
+
+fn resolve_path(ctx: &GuardContext, path: &str) -> Result<PathBuf, String> {
+    let dir = workgraph_dir(ctx)?;
+    let project_root = dir.parent().unwrap_or(dir);
+    Ok(project_root.join(path))
+}
+
+fn latest_score(ctx: &GuardContext, task_id: &str) -> Result<f64, String> {
+    let evals_dir = workgraph_dir(ctx)?.join("agency").join("evaluations");
+    let evaluations = crate::agency::load_all_evaluations(&evals_dir)
+        .map_err(|e| format!("failed to load evaluations: {}", e))?;
+    evaluations
+        .into_iter()
+        .filter(|e| e.task_id == task_id)
+        .max_by(|a, b| a.timestamp.cmp(&b.timestamp))
+        .map(|e| e.score)
+        .ok_or_else(|| format!("task '{}' has no evaluation", task_id))
+}
+
+fn run_command(ctx: &GuardContext, cmd: &str) -> Result<i32, String> {
+    let dir = workgraph_dir(ctx)?;
+    run_with_timeout(dir.parent().unwrap_or(dir), cmd, EXEC_TIMEOUT)
+}
+
+/// Run `cmd` in `root`, killing it and everything it started once `timeout`
+/// passes. Guards run inside `wg done` and coordinator ticks, so a hung
+/// command must not hold them up; a timeout fails the guard.
+fn run_with_timeout(root: &Path, cmd: &str, timeout: Duration) -> Result<i32, String> {
+    let mut command = Command::new("sh");
+    command
+        .arg("-c")
+        .arg(cmd)
+        .current_dir(root)
+        .stdin(Stdio::null())
+        .stdout(Stdio::null())
+        .stderr(Stdio::null());
+    #[cfg(unix)]
+    std::os::unix::process::CommandExt::process_group(&mut command, 0);
+    let mut child = command
+        .spawn()
+        .map_err(|e| format!("failed to run {:?}: {}", cmd, e))?;
+
+    let deadline = Instant::now() + timeout;
+    loop {
+        match child.try_wait() {
+            // Killed by a signal: no exit code, but certainly not success
+            Ok(Some(status)) => return Ok(status.code().unwrap_or(-1)),
+            Ok(None) if Instant::now() < deadline => std::thread::sleep(Duration::from_millis(20)),
+            Ok(None) => break,
+            Err(e) => return Err(format!("failed to wait for {:?}: {}", cmd, e)),
+        }
+    }
+    #[cfg(unix)]
+    unsafe {
+        libc::kill(-(child.id() as i32), libc::SIGKILL);
+    }
+    let _ = child.kill();
+    let _ = child.wait();
+    Err(format!(
+        "{:?} timed out after {}s",
+        cmd,
+        timeout.as_secs_f64()
+    ))
+}
+
+// -- Parsing -----------------------------------------------------------------
+
+#[derive(Debug, Clone, PartialEq)]
+enum Token {
+    LParen,
+    RParen,
+    Comma,
+    Not,
+    And,
+    Or,
+    Cmp(CmpOp),
+    Number(f64),
+    Str(String),
+    Ident(String),
+}
+
+impl fmt::Display for Token {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Token::LParen => f.write_str("'('"),
+            Token::RParen => f.write_str("')'"),
+            Token::Comma => f.write_str("','"),
+            Token::Not => f.write_str("'!'"),
+            Token::And => f.write_str("'&&'"),
+            Token::Or => f.write_str("'||'"),
+            Token::Cmp(op) => write!(f, "'{}'", op),
+            Token::Number(n) => write!(f, "number {}", n),
+            Token::Str(s) => write!(f, "string {:?}", s),
+            Token::Ident(s) => write!(f, "'{}'", s),
+        }
+    }
+}
+
+fn is_word_char(c: char) -> bool {
+    c.is_alphanumeric() || matches!(c, '_' | '-' | '.' | '/' | ':')
+}
+
+fn tokenize(source: &str) -> Result<Vec<Token>, String> {
+    let mut tokens = Vec::new();
+    let mut chars = source.chars().peekable();
+    while let Some(&c) = chars.peek() {
+        match c {
+            c if c.is_whitespace() => {
+                chars.next();
+            }
+            '(' | ')' | ',' => {
+                chars.next();
+                tokens.push(match c {
+                    '(' => Token::LParen,
+                    ')' => Token::RParen,
+                    _ => Token::Comma,
+                });
+            }
+            '&' | '|' => {
+                chars.next();
+                if chars.next() != Some(c) {
+                    return Err(format!("expected '{0}{0}'", c));
+                }
+                tokens.push(if c == '&' { Token::And } else { Token::Or });
+            }
+            '!' | '=' | '<' | '>' => {
+                chars.next();
+                let eq = chars.next_if_eq(&'=').is_some();
+                tokens.push(match (c, eq) {
+                    ('!', false) => Token::Not,
+                    ('!', true) => Token::Cmp(CmpOp::Ne),
+                    ('=', true) => Token::Cmp(CmpOp::Eq),
+                    ('=', false) => return Err("expected '==', found '='".to_string()),
+                    ('<', false) => Token::Cmp(CmpOp::Lt),
+                    ('<', true) => Token::Cmp(CmpOp::Le),
+                    ('>', false) => Token::Cmp(CmpOp::Gt),
+                    _ => Token::Cmp(CmpOp::Ge),
+                });
+            }
+            '"' | '\'' => {
+                chars.next();
+                let mut s = String::new();
+                loop {
+                    match chars.next() {
+                        None => return Err("unterminated string".to_string()),
+                        Some('\\') => match chars.next() {
+                            Some(escaped) => s.push(escaped),
+                            None => return Err("unterminated string".to_string()),
+                        },
+                        Some(ch) if ch == c => break,
+                        Some(ch) => s.push(ch),
+                    }
+                }
+                tokens.push(Token::Str(s));
+            }
+            c if is_word_char(c) => {
+                let mut word = String::new();
+                while let Some(ch) = chars.next_if(|ch| is_word_char(*ch)) {
+                    word.push(ch);
+                }
+                match word.parse::<f64>() {
+                    Ok(n) if n.is_finite() => tokens.push(Token::Number(n)),
+                    _ => tokens.push(Token::Ident(word)),
+                }
+            }
+            other => return Err(format!("unexpected character '{}'", other)),
+        }
+    }
+    Ok(tokens)
+}
+
+struct Parser {
+    tokens: Vec<Token>,
+    pos: usize,
+}
+
+impl Parser {
+    fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.pos)
+    }
+
+    fn next(&mut self) -> Option<Token> {
+        let token = self.tokens.get(self.pos).cloned();
+        self.pos += 1;
+        token
+    }
+
+    fn is_keyword(&self, keyword: &str) -> bool {
+        matches!(self.peek(), Some(Token::Ident(word)) if word.eq_ignore_ascii_case(keyword))
+    }
+
+    fn expect(&mut self, expected: Token) -> Result<(), String> {
+        match self.next() {
+            Some(ref token) if *token == expected => Ok(()),
+            Some(token) => Err(format!("expected {}, found {}", expected, token)),
+            None => Err(format!("expected {}, found end of expression", expected)),
+        }
+    }
+
+    fn expr(&mut self) -> Result<Expr, String> {
+        let mut left = self.and()?;
+        while self.peek() == Some(&Token::Or) || self.is_keyword("or") {
+            self.pos += 1;
+            left = Expr::Or(Box::new(left), Box::new(self.and()?));
+        }
+        Ok(left)
+    }
+
+    fn and(&mut self) -> Result<Expr, String> {
+        let mut left = self.unary()?;
+        while self.peek() == Some(&Token::And) || self.is_keyword("and") {
+            self.pos += 1;
+            left = Expr::And(Box::new(left), Box::new(self.unary()?));
+        }
+        Ok(left)
+    }
+
+    fn unary(&mut self) -> Result<Expr, String> {
+        if self.peek() == Some(&Token::Not) || self.is_keyword("not") {
+            self.pos += 1;
+            return Ok(Expr::Not(Box::new(self.unary()?)));
+        }
+        self.primary()
+    }
+
+    fn primary(&mut self) -> Result<Expr, String> {
+        match self.peek() {
+            Some(Token::LParen) => {
+                self.pos += 1;
+                let inner = self.expr()?;
+                self.expect(Token::RParen)?;
+                return Ok(inner);
+            }
+            Some(Token::Ident(word)) => match word.to_ascii_lowercase().as_str() {
+                "true" | "always" => {
+                    self.pos += 1;
+                    return Ok(Expr::Bool(true));
+                }
+                "false" => {
+                    self.pos += 1;
+                    return Ok(Expr::Bool(false));
+                }
+                "exists" => {
+                    self.pos += 1;
+                    self.expect(Token::LParen)?;
+                    let path = self.string()?;
+                    self.expect(Token::RParen)?;
+                    return Ok(Expr::Exists(path));
+                }
+                "contains" => {
+                    self.pos += 1;
+                    self.expect(Token::LParen)?;
+                    let path = self.string()?;
+                    self.expect(Token::Comma)?;
+                    let needle = self.string()?;
+                    self.expect(Token::RParen)?;
+                    return Ok(Expr::Contains(path, needle));
+                }
+                _ => {}
+            },
+            _ => {}
+        }
+
+        let left = self.value()?;
+        let op = match self.next() {
+            Some(Token::Cmp(op)) => op,
+            Some(token) => {
+                return Err(format!(
+                    "expected a comparison after {}, found {}",
+                    left, token
+                ));
+            }
+            None => return Err(format!("expected a comparison after {}", left)),
+        };
+        let right = self.value()?;
+        check_comparison(&left, op, &right)?;
+        Ok(Expr::Compare(left, op, right))
+    }
+
+    fn value(&mut self) -> Result<Value, String> {
+        match self.next() {
+            Some(Token::Number(n)) => Ok(Value::Number(n)),
+            Some(Token::Str(s)) => Ok(Value::Str(s)),
+            Some(Token::Ident(word)) => match word.to_ascii_lowercase().as_str() {
+                "iteration" => Ok(Value::Iteration),
+                "score" => Ok(Value::Score(self.task_arg()?)),
+                "status" => Ok(Value::Status(self.task_arg()?)),
+                "exec" => {
+                    self.expect(Token::LParen)?;
+                    let cmd = self.string()?;
+                    self.expect(Token::RParen)?;
+                    Ok(Value::Exec(cmd))
+                }
+                _ => Err(format!(
+                    "unknown name '{}' (expected iteration, score, status, exec, exists or contains)",
+                    word
+                )),
+            },
+            Some(token) => Err(format!("expected a value, found {}", token)),
+            None => Err("expected a value, found end of expression".to_string()),
+        }
+    }
+
+    /// `(task-id)` or `("task-id")`
+    fn task_arg(&mut self) -> Result<String, String> {
+        self.expect(Token::LParen)?;
+        let id = match self.next() {
+            Some(Token::Ident(id)) | Some(Token::Str(id)) => id,
+            Some(Token::Number(n)) => n.to_string(),
+            Some(token) => return Err(format!("expected a task ID, found {}", token)),
+            None => return Err("expected a task ID".to_string()),
+        };
+        self.expect(Token::RParen)?;
+        Ok(id)
+    }
+
+    fn string(&mut self) -> Result<String, String> {
+        match self.next() {
+            Some(Token::Str(s)) => Ok(s),
+            Some(token) => Err(format!("expected a quoted string, found {}", token)),
+            None => Err("expected a quoted string".to_string()),
+        }
+    }
+}
+
+fn check_comparison(left: &Value, op: CmpOp, right: &Value) -> Result<(), String> {
+    if left.kind() != right.kind() {
+        return Err(format!("cannot compare {} with {}", left, right));
+    }
+    if left.kind() == Kind::Text && !matches!(op, CmpOp::Eq | CmpOp::Ne) {
+        return Err(format!(
+            "text can only be compared with == or !=, not {}",
+            op
+        ));
+    }
+    // A status compared against a literal must name a real status
+    for (value, other) in [(left, right), (right, left)] {
+        if let (Value::Status(_), Value::Str(s)) = (value, other)
+            && !STATUSES.iter().any(|status| status.to_string() == *s)
+        {
+            let names: Vec<String> = STATUSES.iter().map(|s| s.to_string()).collect();
+            return Err(format!(
+                "unknown status {:?} (expected one of: {})",
+                s,
+                names.join(", ")
+            ));
+        }
+    }
+    Ok(())
+}
+
+const STATUSES: [Status; 6] = [
+    Status::Open,
+    Status::InProgress,
+    Status::Done,
+    Status::Blocked,
+    Status::Failed,
+    Status::Abandoned,
+];
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::agency::{Evaluation as AgencyEvaluation, save_evaluation};
+    use crate::graph::{Node, Task};
+    use std::collections::HashMap;
+
+    fn graph() -> WorkGraph {
+        let mut graph = WorkGraph::new();
+        graph.add_node(Node::Task(Task {
+            id: "lint".to_string(),
+            title: "Lint".to_string(),
+            status: Status::Failed,
+            ..Task::default()
+        }));
+        graph
+    }
+
+    fn eval(source: &str, dir: Option<&Path>) -> Result<bool, String> {
+        let graph = graph();
+        let ctx = GuardContext {
+            graph: &graph,
+            workgraph_dir: dir,
+            iteration: 2,
+        };
+        evaluate(source, &ctx).map(|e| e.value)
+    }
+
+    #[test]
+    fn test_parse_precedence() {
+        assert_eq!(
+            parse("true || false && !true").unwrap(),
+            Expr::Or(
+                Box::new(Expr::Bool(true)),
+                Box::new(Expr::And(
+                    Box::new(Expr::Bool(false)),
+                    Box::new(Expr::Not(Box::new(Expr::Bool(true))))
+                ))
+            )
+        );
+        assert_eq!(
+            parse("score(review-2) < 0.8").unwrap(),
+            Expr::Compare(
+                Value::Score("review-2".to_string()),
+                CmpOp::Lt,
+                Value::Number(0.8)
+            )
+        );
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        assert!(parse("").is_err());
+        assert!(parse("score(x) <").is_err());
+        assert!(parse("iteration = 3").is_err());
+        assert!(parse("(true").is_err());
+        assert!(parse("bogus(x) < 1").unwrap_err().contains("unknown name"));
+        assert!(parse("status(x) < \"done\"").is_err());
+        assert!(parse("score(x) == \"high\"").is_err());
+        assert!(
+            parse("status(x) == \"finished\"")
+                .unwrap_err()
+                .contains("unknown status")
+        );
+        assert!(parse("contains(\"log\")").is_err());
+        assert!(parse("true true").is_err());
+    }
+
+    #[test]
+    fn test_evaluate_graph_values() {
+        assert_eq!(eval("iteration < 3", None), Ok(true));
+        assert_eq!(eval("iteration >= 3", None), Ok(false));
+        assert_eq!(
+            eval("status(lint) == 'failed' and not false", None),
+            Ok(true)
+        );
+        assert_eq!(
+            eval("status(lint) != \"failed\" or iteration == 2", None),
+            Ok(true)
+        );
+        assert!(eval("status(missing) == \"done\"", None).is_err());
+        // Filesystem functions need a workgraph directory
+        assert!(eval("exists(\"x\")", None).is_err());
+    }
+
+    #[test]
+    fn test_evaluate_files_exec_and_scores() {
+        let tmp = tempfile::tempdir().unwrap();
+        let wg = tmp.path().join(".workgraph");
+        std::fs::create_dir_all(&wg).unwrap();
+        std::fs::write(tmp.path().join("report.txt"), "3 passed, 1 FAIL").unwrap();
+
+        let dir = Some(wg.as_path());
+        assert_eq!(eval("contains(\"report.txt\", \"FAIL\")", dir), Ok(true));
+        assert_eq!(eval("exists(\"missing.txt\")", dir), Ok(false));
+        assert_eq!(eval("exec(\"exit 3\") == 3", dir), Ok(true));
+        assert_eq!(eval("exec(\"test -f report.txt\") != 0", dir), Ok(false));
+        let started = Instant::now();
+        assert!(
+            run_with_timeout(tmp.path(), "sleep 5 & sleep 5", Duration::from_millis(200))
+                .unwrap_err()
+                .contains("timed out")
+        );
+        assert!(started.elapsed() < Duration::from_secs(2));
+
+        assert!(
+            eval("score(lint) < 0.8", dir)
+                .unwrap_err()
+                .contains("no evaluation")
+        );
+        let evals = wg.join("agency").join("evaluations");
+        for (id, score, timestamp) in [
+            ("e1", 0.9, "2026-01-01T00:00:00Z"),
+            ("e2", 0.5, "2026-01-02T00:00:00Z"),
+        ] {
+            let evaluation = AgencyEvaluation {
+                id: id.to_string(),
+                task_id: "lint".to_string(),
+                agent_id: String::new(),
+                role_id: String::new(),
+                motivation_id: String::new(),
+                score,
+                dimensions: HashMap::new(),
+                notes: String::new(),
+                evaluator: "test".to_string(),
+                timestamp: timestamp.to_string(),
+                model: None,
+                source: "manual".to_string(),
+            };
+            save_evaluation(&evaluation, &evals).unwrap();
+        }
+        // The latest evaluation wins
+        let graph = graph();
+        let ctx = GuardContext {
+            graph: &graph,
+            workgraph_dir: dir,
+            iteration: 1,
+        };
+        let result = evaluate("score(lint) < 0.8", &ctx).unwrap();
+        assert!(result.value);
+        assert_eq!(result.observed, vec!["score(lint) = 0.5"]);
+    }
+}
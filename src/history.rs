@@ -0,0 +1,650 @@
+//! Field-level graph history for point-in-time queries.
+//!
+//! Every save of a workgraph's `graph.jsonl` (or its SQLite replacement)
+//! appends the nodes it created, removed or changed to `log/history.jsonl`,
+//! with the before/after value of each changed field. The first entry is a
+//! snapshot of the graph as it stood when history started, so replaying the
+//! log up to a timestamp reconstructs the graph at that moment. If history
+//! is turned off for a while, it resumes with a fresh snapshot. The log
+//! rotates into zstd files under `log/history/` like the operation log.
+
+use anyhow::{Context, Result, bail};
+use chrono::{DateTime, Local, NaiveDate, NaiveDateTime, TimeZone, Utc};
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+use std::cell::RefCell;
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+use crate::config::Config;
+use crate::graph::{Node, WorkGraph, parse_delay};
+use crate::provenance;
+
+/// Longest command line kept on a history entry.
+const MAX_COMMAND_LEN: usize = 200;
+
+/// One recorded change to the graph.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HistoryEntry {
+    /// RFC 3339 time of the save that made the change
+    pub timestamp: String,
+    #[serde(flatten)]
+    pub change: Change,
+    /// Command line of the process that saved the graph
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub command: Option<String>,
+    /// Who made the change (see [`current_actor`])
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub actor: Option<String>,
+    /// Timestamp of the save this change undid (`wg undo`)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub undo_of: Option<String>,
+    /// Timestamp of the undo this change reverted (`wg redo`)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub redo_of: Option<String>,
+}
+
+/// A save that reverts an earlier one, for [`with_revert`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Revert {
+    /// Undo of the save with this timestamp
+    Undo(String),
+    /// Redo reverting the undo save with this timestamp
+    Redo(String),
+}
+
+/// Actor set with [`set_actor`] for every save this process makes.
+static ACTOR: Mutex<Option<String>> = Mutex::new(None);
+
+thread_local! {
+    /// Revert marker set by [`with_revert`] for saves on this thread.
+    static REVERT: RefCell<Option<Revert>> = const { RefCell::new(None) };
+
+    /// Actor set by [`with_actor`] for saves on this thread.
+    static ACTING: RefCell<Option<String>> = const { RefCell::new(None) };
+}
+
+/// Record this process's saves as made by `actor` (e.g. the coordinator),
+/// instead of the agent or login name.
+pub fn set_actor(actor: &str) {
+    *ACTOR.lock().unwrap_or_else(|e| e.into_inner()) = Some(actor.to_string());
+}
+
+/// Who saves made by this process are attributed to: the actor set with
+/// [`with_actor`] or [`set_actor`], else the agent (`WG_AGENT_ID`), else the
+/// login name.
+pub fn current_actor() -> Option<String> {
+    if let Some(actor) = ACTING.with(|a| a.borrow().clone()) {
+        return Some(actor);
+    }
+    if let Some(actor) = ACTOR.lock().unwrap_or_else(|e| e.into_inner()).clone() {
+        return Some(actor);
+    }
+    ["WG_AGENT_ID", "USER"]
+        .iter()
+        .find_map(|var| std::env::var(var).ok().filter(|v| !v.is_empty()))
+}
+
+/// Run `f`, attributing the saves it makes to `actor`. Used where one
+/// process acts for several people, like the Matrix listener.
+pub fn with_actor<T>(actor: &str, f: impl FnOnce() -> T) -> T {
+    let previous = ACTING.with(|a| a.borrow_mut().replace(actor.to_string()));
+    let result = f();
+    ACTING.with(|a| *a.borrow_mut() = previous);
+    result
+}
+
+/// Run `f`, marking the saves it makes as reverting another save.
+pub fn with_revert<T>(revert: Revert, f: impl FnOnce() -> T) -> T {
+    REVERT.with(|r| *r.borrow_mut() = Some(revert));
+    let result = f();
+    REVERT.with(|r| *r.borrow_mut() = None);
+    result
+}
+
+/// What a history entry did to the graph. Nodes are kept as their JSON form
+/// so old entries still replay after the node schema changes.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "change", rename_all = "snake_case")]
+pub enum Change {
+    /// The whole graph, recorded when history starts
+    Snapshot { nodes: Vec<Value> },
+    /// A node was added
+    Created { id: String, node: Value },
+    /// Fields of a node changed. `before`/`after` hold the old and new value
+    /// of each changed field (`null` = absent); arrays that only grew are
+    /// recorded as their new items in `appended` instead.
+    Updated {
+        id: String,
+        #[serde(default, skip_serializing_if = "Map::is_empty")]
+        before: Map<String, Value>,
+        #[serde(default, skip_serializing_if = "Map::is_empty")]
+        after: Map<String, Value>,
+        #[serde(default, skip_serializing_if = "Map::is_empty")]
+        appended: Map<String, Value>,
+    },
+    /// A node was removed
+    Removed { id: String, node: Value },
+}
+
+impl Change {
+    /// ID of the node this change touched (`None` for snapshots).
+    pub fn node_id(&self) -> Option<&str> {
+        match self {
+            Change::Snapshot { .. } => None,
+            Change::Created { id, .. }
+            | Change::Updated { id, .. }
+            | Change::Removed { id, .. } => Some(id),
+        }
+    }
+}
+
+/// Path to the current history log.
+pub fn history_path(workgraph_dir: &Path) -> PathBuf {
+    workgraph_dir.join("log").join("history.jsonl")
+}
+
+/// Directory rotated history logs are compressed into.
+fn rotated_dir(workgraph_dir: &Path) -> PathBuf {
+    workgraph_dir.join("log").join("history")
+}
+
+/// Marker left while `[log] history` is off, so the save that resumes
+/// history starts it over from a fresh snapshot.
+fn paused_path(workgraph_dir: &Path) -> PathBuf {
+    workgraph_dir.join("log").join("history.paused")
+}
+
+/// Whether any history has been recorded for this workgraph.
+fn has_history(workgraph_dir: &Path) -> bool {
+    history_path(workgraph_dir).exists() || rotated_dir(workgraph_dir).exists()
+}
+
+/// Whether saves to this workgraph are recorded (`[log] history`). While
+/// they aren't, notes that the history has a gap.
+pub fn recording(workgraph_dir: &Path) -> bool {
+    history_enabled(
+        workgraph_dir,
+        &Config::load_merged(workgraph_dir).unwrap_or_default(),
+    )
+}
+
+fn history_enabled(workgraph_dir: &Path, config: &Config) -> bool {
+    if !config.log.history && has_history(workgraph_dir) {
+        let paused = paused_path(workgraph_dir);
+        if !paused.exists()
+            && let Err(e) = std::fs::write(&paused, "")
+        {
+            eprintln!("Warning: failed to mark graph history as paused: {}", e);
+        }
+    }
+    config.log.history
+}
+
+/// Record node writes made by one save of the graph.
+///
+/// Each write is `(before, after)`, with `None` for a node that did not
+/// exist. `stored` returns the graph as it was before these writes; it is
+/// only called when this is the first recorded save, or the first since
+/// history was turned back on, to write a baseline snapshot. Does nothing
+/// when `[log] history = false`.
+pub fn record(
+    workgraph_dir: &Path,
+    writes: &[(Option<&Node>, Option<&Node>)],
+    stored: impl FnOnce() -> Vec<Node>,
+) -> Result<()> {
+    let config = Config::load_merged(workgraph_dir).unwrap_or_default();
+    if !history_enabled(workgraph_dir, &config) {
+        return Ok(());
+    }
+    let threshold = config.log.rotation_threshold;
+    let timestamp = Utc::now().to_rfc3339();
+    let command = current_command();
+    let actor = current_actor();
+    let revert = REVERT.with(|r| r.borrow().clone());
+    let (undo_of, redo_of) = match revert {
+        Some(Revert::Undo(ts)) => (Some(ts), None),
+        Some(Revert::Redo(ts)) => (None, Some(ts)),
+        None => (None, None),
+    };
+
+    let paused = paused_path(workgraph_dir);
+    let resumed = paused.exists();
+    let mut changes = Vec::new();
+    if resumed || !has_history(workgraph_dir) {
+        let mut nodes = stored().iter().map(to_value).collect::<Result<Vec<_>>>()?;
+        nodes.sort_by(|a, b| node_key(a).cmp(node_key(b)));
+        changes.push(Change::Snapshot { nodes });
+    }
+    for (before, after) in writes {
+        let change = match (before, after) {
+            (None, Some(after)) => Change::Created {
+                id: after.id().to_string(),
+                node: to_value(after)?,
+            },
+            (Some(before), None) => Change::Removed {
+                id: before.id().to_string(),
+                node: to_value(before)?,
+            },
+            (Some(before), Some(after)) => {
+                let (before_fields, after_fields, appended) =
+                    diff_fields(&to_value(before)?, &to_value(after)?);
+                if before_fields.is_empty() && appended.is_empty() {
+                    continue;
+                }
+                Change::Updated {
+                    id: after.id().to_string(),
+                    before: before_fields,
+                    after: after_fields,
+                    appended,
+                }
+            }
+            (None, None) => continue,
+        };
+        changes.push(change);
+    }
+
+    let path = history_path(workgraph_dir);
+    let rotated = rotated_dir(workgraph_dir);
+    for change in changes {
+        let entry = HistoryEntry {
+            timestamp: timestamp.clone(),
+            change,
+            command: command.clone(),
+            actor: actor.clone(),
+            undo_of: undo_of.clone(),
+            redo_of: redo_of.clone(),
+        };
+        provenance::append_rotating(&path, &rotated, &entry, threshold)?;
+    }
+    if resumed {
+        std::fs::remove_file(&paused).context("Failed to clear history pause marker")?;
+    }
+    Ok(())
+}
+
+/// The writes that turn `old` into `new`, for graphs replaced wholesale.
+pub fn graph_writes<'a>(
+    old: &'a WorkGraph,
+    new: &'a WorkGraph,
+) -> Vec<(Option<&'a Node>, Option<&'a Node>)> {
+    let mut writes: Vec<_> = new
+        .nodes()
+        .filter_map(|node| match old.get_node(node.id()) {
+            Some(prev) if prev == node => None,
+            prev => Some((prev, Some(node))),
+        })
+        .collect();
+    writes.extend(
+        old.nodes()
+            .filter(|node| new.get_node(node.id()).is_none())
+            .map(|node| (Some(node), None)),
+    );
+    writes.sort_by_key(|&(before, after)| after.or(before).map(Node::id));
+    writes
+}
+
+/// Read every history entry, oldest first.
+pub fn read_all(workgraph_dir: &Path) -> Result<Vec<HistoryEntry>> {
+    provenance::read_rotating(&history_path(workgraph_dir), &rotated_dir(workgraph_dir))
+}
+
+/// Reconstruct the graph as it stood at `at` by replaying the history log.
+pub fn graph_at(workgraph_dir: &Path, at: DateTime<Utc>) -> Result<WorkGraph> {
+    graph_from_entries(&read_all(workgraph_dir)?, at)
+}
+
+/// Replay `entries` up to and including `at`.
+pub fn graph_from_entries(entries: &[HistoryEntry], at: DateTime<Utc>) -> Result<WorkGraph> {
+    let Some(first) = entries.first() else {
+        bail!("No graph history recorded yet (it starts with the next change to the graph)");
+    };
+    let start = entry_time(first)?;
+    if at < start {
+        bail!(
+            "No graph history before {} (history starts there)",
+            start.with_timezone(&Local).format("%Y-%m-%d %H:%M:%S")
+        );
+    }
+
+    let mut nodes: BTreeMap<String, Value> = BTreeMap::new();
+    for entry in entries {
+        if entry_time(entry)? > at {
+            break;
+        }
+        apply(&mut nodes, &entry.change);
+    }
+
+    let mut graph = WorkGraph::new();
+    for (id, value) in nodes {
+        let node: Node = serde_json::from_value(value)
+            .with_context(|| format!("Failed to rebuild '{}' from history", id))?;
+        graph.add_node(node);
+    }
+    Ok(graph)
+}
+
+/// Apply one change to a graph held as JSON nodes keyed by ID.
+fn apply(nodes: &mut BTreeMap<String, Value>, change: &Change) {
+    match change {
+        Change::Snapshot { nodes: snapshot } => {
+            nodes.clear();
+            for node in snapshot {
+                nodes.insert(node_key(node).to_string(), node.clone());
+            }
+        }
+        Change::Created { id, node } => {
+            nodes.insert(id.clone(), node.clone());
+        }
+        Change::Removed { id, .. } => {
+            nodes.remove(id);
+        }
+        Change::Updated {
+            id,
+            after,
+            appended,
+            ..
+        } => {
+            let Some(Value::Object(fields)) = nodes.get_mut(id) else {
+                return;
+            };
+            for (key, value) in after {
+                if value.is_null() {
+                    fields.remove(key);
+                } else {
+                    fields.insert(key.clone(), value.clone());
+                }
+            }
+            for (key, items) in appended {
+                let Value::Array(items) = items else {
+                    continue;
+                };
+                match fields.get_mut(key) {
+                    Some(Value::Array(existing)) => existing.extend(items.iter().cloned()),
+                    _ => {
+                        fields.insert(key.clone(), Value::Array(items.clone()));
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// Compare two nodes field by field.
+///
+/// Returns the old and new values of each changed top-level field (`null`
+/// for an absent one), plus the new items of array fields that only grew.
+pub fn diff_fields(
+    before: &Value,
+    after: &Value,
+) -> (Map<String, Value>, Map<String, Value>, Map<String, Value>) {
+    let empty = Map::new();
+    let before = before.as_object().unwrap_or(&empty);
+    let after = after.as_object().unwrap_or(&empty);
+
+    let mut old = Map::new();
+    let mut new = Map::new();
+    let mut appended = Map::new();
+    for key in before
+        .keys()
+        .chain(after.keys().filter(|k| !before.contains_key(*k)))
+    {
+        let b = before.get(key).unwrap_or(&Value::Null);
+        let a = after.get(key).unwrap_or(&Value::Null);
+        if b == a {
+            continue;
+        }
+        // Empty arrays are usually omitted from the JSON, so growing from
+        // nothing counts as an append too.
+        let b_items: &[Value] = match b {
+            Value::Array(items) => items,
+            _ => &[],
+        };
+        if let Value::Array(a_items) = a
+            && (b.is_array() || b.is_null())
+            && a_items.len() > b_items.len()
+            && a_items.starts_with(b_items)
+        {
+            appended.insert(key.clone(), Value::Array(a_items[b_items.len()..].to_vec()));
+            continue;
+        }
+        old.insert(key.clone(), b.clone());
+        new.insert(key.clone(), a.clone());
+    }
+    (old, new, appended)
+}
+
+/// Parse a `--at` style time: RFC 3339, `YYYY-MM-DD[THH:MM[:SS]]` in local
+/// time (a space may replace the `T`), `now`, or a duration such as
+/// `2h ago`.
+pub fn parse_time(s: &str) -> Result<DateTime<Utc>> {
+    let s = s.trim();
+    if s.eq_ignore_ascii_case("now") {
+        return Ok(Utc::now());
+    }
+    if let Some(ago) = s.strip_suffix("ago")
+        && let Some(secs) = parse_delay(ago.trim())
+    {
+        return Ok(Utc::now() - chrono::Duration::seconds(secs as i64));
+    }
+    if let Ok(t) = DateTime::parse_from_rfc3339(s) {
+        return Ok(t.with_timezone(&Utc));
+    }
+    let naive = [
+        "%Y-%m-%dT%H:%M:%S",
+        "%Y-%m-%dT%H:%M",
+        "%Y-%m-%d %H:%M:%S",
+        "%Y-%m-%d %H:%M",
+    ]
+    .iter()
+    .find_map(|fmt| NaiveDateTime::parse_from_str(s, fmt).ok())
+    .or_else(|| {
+        NaiveDate::parse_from_str(s, "%Y-%m-%d")
+            .ok()
+            .and_then(|d| d.and_hms_opt(0, 0, 0))
+    });
+    match naive.and_then(|n| Local.from_local_datetime(&n).earliest()) {
+        Some(t) => Ok(t.with_timezone(&Utc)),
+        None => bail!(
+            "Invalid time '{}': expected e.g. 2026-09-01T12:00, 2026-09-01, an RFC 3339 timestamp, 'now' or '2h ago'",
+            s
+        ),
+    }
+}
+
+/// Parse an entry's timestamp.
+pub fn entry_time(entry: &HistoryEntry) -> Result<DateTime<Utc>> {
+    DateTime::parse_from_rfc3339(&entry.timestamp)
+        .map(|t| t.with_timezone(&Utc))
+        .with_context(|| format!("Invalid history timestamp '{}'", entry.timestamp))
+}
+
+fn to_value(node: &Node) -> Result<Value> {
+    serde_json::to_value(node).context("Failed to serialize node for history")
+}
+
+fn node_key(node: &Value) -> &str {
+    node.get("id").and_then(Value::as_str).unwrap_or("")
+}
+
+/// The running command line, shortened for the log.
+fn current_command() -> Option<String> {
+    let mut args = std::env::args();
+    let program = args.next()?;
+    let program = Path::new(&program)
+        .file_name()
+        .map(|n| n.to_string_lossy().into_owned())
+        .unwrap_or(program);
+    let mut command = std::iter::once(program)
+        .chain(args)
+        .collect::<Vec<_>>()
+        .join(" ");
+    if command.len() > MAX_COMMAND_LEN {
+        let mut end = MAX_COMMAND_LEN;
+        while !command.is_char_boundary(end) {
+            end -= 1;
+        }
+        command.truncate(end);
+        command.push('…');
+    }
+    Some(command)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::graph::{LogEntry, Status, Task};
+    use tempfile::TempDir;
+
+    fn task(id: &str, title: &str) -> Node {
+        Node::Task(Task {
+            id: id.to_string(),
+            title: title.to_string(),
+            ..Task::default()
+        })
+    }
+
+    fn at(entries: &[HistoryEntry], i: usize) -> DateTime<Utc> {
+        entry_time(&entries[i]).unwrap()
+    }
+
+    #[test]
+    fn test_record_starts_with_snapshot_then_diffs() {
+        let dir = TempDir::new().unwrap();
+        let old = task("t1", "Old");
+        record(dir.path(), &[(None, Some(&task("t2", "New")))], || {
+            vec![old.clone()]
+        })
+        .unwrap();
+
+        let mut renamed = old.clone();
+        if let Node::Task(t) = &mut renamed {
+            t.title = "Renamed".into();
+            t.status = Status::Done;
+        }
+        record(dir.path(), &[(Some(&old), Some(&renamed))], || {
+            panic!("snapshot taken twice")
+        })
+        .unwrap();
+
+        let entries = read_all(dir.path()).unwrap();
+        assert_eq!(entries.len(), 3);
+        assert!(matches!(&entries[0].change, Change::Snapshot { nodes } if nodes.len() == 1));
+        assert!(matches!(&entries[1].change, Change::Created { id, .. } if id == "t2"));
+        match &entries[2].change {
+            Change::Updated {
+                id, before, after, ..
+            } => {
+                assert_eq!(id, "t1");
+                assert_eq!(before["title"], "Old");
+                assert_eq!(after["title"], "Renamed");
+                assert_eq!(after["status"], "done");
+                assert!(!after.contains_key("id"));
+            }
+            other => panic!("unexpected change {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_replay_reconstructs_past_states() {
+        let dir = TempDir::new().unwrap();
+        let t1 = task("t1", "First");
+        record(dir.path(), &[(None, Some(&t1))], Vec::new).unwrap();
+        std::thread::sleep(std::time::Duration::from_millis(5));
+
+        let mut logged = t1.clone();
+        if let Node::Task(t) = &mut logged {
+            t.assigned = Some("agent-1".into());
+            t.log.push(LogEntry {
+                timestamp: "2026-01-01T00:00:00Z".into(),
+                actor: None,
+                message: "started".into(),
+            });
+        }
+        record(dir.path(), &[(Some(&t1), Some(&logged))], Vec::new).unwrap();
+        std::thread::sleep(std::time::Duration::from_millis(5));
+        record(dir.path(), &[(Some(&logged), None)], Vec::new).unwrap();
+
+        let entries = read_all(dir.path()).unwrap();
+        assert!(matches!(
+            &entries[2].change,
+            Change::Updated { appended, .. } if appended.contains_key("log")
+        ));
+
+        let g = graph_from_entries(&entries, at(&entries, 1)).unwrap();
+        assert_eq!(g.get_task("t1").unwrap().assigned, None);
+
+        let g = graph_from_entries(&entries, at(&entries, 2)).unwrap();
+        let t = g.get_task("t1").unwrap();
+        assert_eq!(t.assigned.as_deref(), Some("agent-1"));
+        assert_eq!(t.log.len(), 1);
+
+        let g = graph_from_entries(&entries, at(&entries, 3)).unwrap();
+        assert!(g.get_task("t1").is_none());
+
+        let before = at(&entries, 0) - chrono::Duration::seconds(1);
+        assert!(graph_from_entries(&entries, before).is_err());
+    }
+
+    #[test]
+    fn test_history_disabled() {
+        let dir = TempDir::new().unwrap();
+        std::fs::write(dir.path().join("config.toml"), "[log]\nhistory = false\n").unwrap();
+        record(dir.path(), &[(None, Some(&task("t1", "T")))], Vec::new).unwrap();
+        assert!(!history_path(dir.path()).exists());
+    }
+
+    #[test]
+    fn test_history_resumes_with_a_snapshot() {
+        let dir = TempDir::new().unwrap();
+        let config = dir.path().join("config.toml");
+        record(dir.path(), &[(None, Some(&task("t1", "T")))], Vec::new).unwrap();
+
+        // Saves made while history is off are not recorded...
+        std::fs::write(&config, "[log]\nhistory = false\n").unwrap();
+        assert!(!recording(dir.path()));
+        record(dir.path(), &[(None, Some(&task("t2", "Off")))], Vec::new).unwrap();
+
+        // ...so the first save after it's back on starts from the graph as
+        // it stands then
+        std::fs::write(&config, "[log]\nhistory = true\n").unwrap();
+        record(dir.path(), &[(None, Some(&task("t3", "On")))], || {
+            vec![task("t1", "T"), task("t2", "Off")]
+        })
+        .unwrap();
+        record(dir.path(), &[(None, Some(&task("t4", "Later")))], Vec::new).unwrap();
+
+        let entries = read_all(dir.path()).unwrap();
+        let snapshots: Vec<usize> = entries
+            .iter()
+            .enumerate()
+            .filter(|(_, e)| matches!(e.change, Change::Snapshot { .. }))
+            .map(|(i, _)| i)
+            .collect();
+        assert_eq!(snapshots, [0, 2]);
+        let g = graph_from_entries(&entries, at(&entries, entries.len() - 1)).unwrap();
+        let mut ids: Vec<&str> = g.nodes().map(|n| n.id()).collect();
+        ids.sort();
+        assert_eq!(ids, ["t1", "t2", "t3", "t4"]);
+    }
+
+    #[test]
+    fn test_parse_time() {
+        let t = parse_time("2026-09-01T12:00:00Z").unwrap();
+        assert_eq!(t.to_rfc3339(), "2026-09-01T12:00:00+00:00");
+        let local = parse_time("2026-09-01T12:00").unwrap();
+        assert_eq!(
+            local
+                .with_timezone(&Local)
+                .format("%Y-%m-%d %H:%M")
+                .to_string(),
+            "2026-09-01 12:00"
+        );
+        assert_eq!(parse_time("2026-09-01 12:00").unwrap(), local);
+        assert!(parse_time("2026-09-01").is_ok());
+        let ago = parse_time("2h ago").unwrap();
+        assert!((Utc::now() - ago).num_minutes() >= 119);
+        assert!(parse_time("yesterday-ish").is_err());
+    }
+}
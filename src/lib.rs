@@ -1,23 +1,43 @@
+pub mod accounting;
 pub mod agency;
+pub mod bindings;
+pub mod budget;
+pub mod capacity;
 pub mod check;
 pub mod config;
+pub mod contract;
 pub mod cycle;
+pub mod dispatch;
+pub mod email;
+pub mod epic;
+pub mod fanout;
 pub mod federation;
+pub mod gate;
 pub mod graph;
+pub mod guard;
+pub mod history;
 #[cfg(feature = "matrix")]
 pub mod matrix;
 pub mod matrix_commands;
 #[cfg(feature = "matrix-lite")]
 pub mod matrix_lite;
+pub mod matrix_threads;
+pub mod models;
 pub mod parser;
 pub mod plan_validator;
+pub mod propagation;
 pub mod provenance;
 pub mod query;
+pub mod retry;
 pub mod runs;
+pub mod schedule;
 pub mod service;
+pub mod store;
 pub mod trace_function;
 pub mod trace_memory;
+pub mod undo;
 pub mod usage;
+pub mod worktree;
 
 pub use config::MatrixConfig;
 pub use graph::WorkGraph;
@@ -38,6 +38,8 @@ struct Cli {
     command: Option<Commands>,
 }
 
+// Parsed once per invocation, so variant size does not matter
+#[allow(clippy::large_enum_variant)]
 #[derive(Subcommand)]
 enum Commands {
     /// Initialize a new workgraph in the current directory
@@ -64,10 +66,36 @@ enum Commands {
         #[arg(long = "after", alias = "blocked-by", value_delimiter = ',', num_args = 1..)]
         after: Vec<String>,
 
+        /// Run only if this task completes successfully (abandoned if it fails)
+        #[arg(long = "after-success", value_delimiter = ',', num_args = 1..)]
+        after_success: Vec<String>,
+
+        /// Run only if this task fails (abandoned if it succeeds)
+        #[arg(long = "after-failure", value_delimiter = ',', num_args = 1..)]
+        after_failure: Vec<String>,
+
+        /// Run once this task finishes, whether it succeeds or fails
+        #[arg(long = "after-any", value_delimiter = ',', num_args = 1..)]
+        after_any: Vec<String>,
+
+        /// Resource this task needs, as ID or ID:AMOUNT. The amount is held
+        /// against the resource while the task runs (can specify multiple)
+        #[arg(long, value_name = "RESOURCE[:AMOUNT]", value_delimiter = ',', num_args = 1..)]
+        requires: Vec<String>,
+
         /// Assign to an actor
         #[arg(long)]
         assign: Option<String>,
 
+        /// Dispatch priority; higher runs first, and tasks this one waits on
+        /// inherit it (default 0)
+        #[arg(long, allow_negative_numbers = true)]
+        priority: Option<i32>,
+
+        /// Who asked for this task, for weighted-fair dispatch
+        #[arg(long)]
+        requester: Option<String>,
+
         /// Estimated hours
         #[arg(long)]
         hours: Option<f64>,
@@ -96,6 +124,10 @@ enum Commands {
         #[arg(long)]
         max_retries: Option<u32>,
 
+        /// Kill the agent and fail the task if a run takes longer (e.g., 30m, 2h)
+        #[arg(long)]
+        timeout: Option<String>,
+
         /// Preferred model for this task (haiku, sonnet, opus)
         #[arg(long)]
         model: Option<String>,
@@ -108,7 +140,8 @@ enum Commands {
         #[arg(long = "max-iterations")]
         max_iterations: Option<u32>,
 
-        /// Guard condition for cycle iteration: 'task:<id>=<status>' or 'always'
+        /// Guard condition for cycle iteration: 'task:<id>=<status>', 'always', or an
+        /// expression such as 'score(review) < 0.8 && iteration < 5'
         #[arg(long = "cycle-guard")]
         cycle_guard: Option<String>,
 
@@ -116,6 +149,73 @@ enum Commands {
         #[arg(long = "cycle-delay")]
         cycle_delay: Option<String>,
 
+        /// Make this a map task: expand into one task per item once SOURCE is done.
+        /// Title, description, skills, tags, deliverables and verify are the
+        /// template; `{{item}}` and `{{index}}` are substituted
+        #[arg(long = "map-over", value_name = "SOURCE", requires = "map_from")]
+        map_over: Option<String>,
+
+        /// Artifact or output file of the source task holding the JSON list to map over
+        #[arg(long = "map-from", value_name = "NAME", requires = "map_over")]
+        map_from: Option<String>,
+
+        /// Epic this task belongs to; the epic completes once all its children are done
+        #[arg(long)]
+        parent: Option<String>,
+
+        /// Run this task on a schedule: a cron expression ('0 9 * * 1-5'), '@daily',
+        /// or '@every 6h'. The task becomes a template the service copies each time
+        #[arg(long)]
+        schedule: Option<String>,
+
+        /// IANA timezone for --schedule (default UTC)
+        #[arg(long = "schedule-tz", value_name = "TZ", requires = "schedule")]
+        schedule_tz: Option<String>,
+
+        /// What to do about windows missed while the service was down: skip, once, all
+        #[arg(long = "catch-up", value_name = "POLICY", requires = "schedule")]
+        catch_up: Option<String>,
+
+        /// Make this an approval gate: no agent runs it; approvers decide with
+        /// 'wg approve' / 'wg reject' or over Matrix
+        #[arg(long)]
+        gate: bool,
+
+        /// Who may approve the gate (comma-separated actors or Matrix user IDs; default: anyone)
+        #[arg(long, value_delimiter = ',', num_args = 1.., requires = "gate")]
+        approvers: Vec<String>,
+
+        /// Approvals needed to pass the gate (default: all approvers)
+        #[arg(long, requires = "gate")]
+        quorum: Option<u32>,
+
+        /// Output contract: a file matching this glob must exist before the
+        /// task can be done (can specify multiple)
+        #[arg(long = "expect-file", value_name = "GLOB")]
+        expect_file: Vec<String>,
+
+        /// Output contract: JSON Schema (inline or a file) the task's result
+        /// document must satisfy
+        #[arg(long = "result-schema", value_name = "SCHEMA")]
+        result_schema: Option<String>,
+
+        /// Output contract: where the result document is written
+        /// (default: .workgraph/output/<id>/result.json)
+        #[arg(long = "result-path", value_name = "PATH")]
+        result_path: Option<String>,
+
+        /// Output contract: command that must succeed before the task can be done
+        #[arg(long, value_name = "COMMAND")]
+        check: Option<String>,
+
+        /// Exit status --check must return (default: 0)
+        #[arg(
+            long = "check-status",
+            requires = "check",
+            allow_negative_numbers = true
+        )]
+        check_status: Option<i32>,
+
         /// Task visibility zone for trace exports (internal, public, peer)
         #[arg(long, default_value = "internal")]
         visibility: String,
@@ -154,6 +254,14 @@ enum Commands {
         #[arg(long)]
         model: Option<String>,
 
+        /// Set the dispatch priority; higher runs first
+        #[arg(long, allow_negative_numbers = true)]
+        priority: Option<i32>,
+
+        /// Set who asked for the task, or 'none' to clear it
+        #[arg(long)]
+        requester: Option<String>,
+
         /// Add a required skill
         #[arg(long = "add-skill")]
         add_skill: Vec<String>,
@@ -162,11 +270,20 @@ enum Commands {
         #[arg(long = "remove-skill")]
         remove_skill: Vec<String>,
 
+        /// Require a resource (ID or ID:AMOUNT); re-adding one changes the amount held
+        #[arg(long = "add-requires", value_name = "RESOURCE[:AMOUNT]")]
+        add_requires: Vec<String>,
+
+        /// Stop requiring a resource
+        #[arg(long = "remove-requires", value_name = "RESOURCE")]
+        remove_requires: Vec<String>,
+
         /// Set maximum iterations for structural cycle (sets cycle_config)
         #[arg(long = "max-iterations")]
         max_iterations: Option<u32>,
 
-        /// Set guard condition for cycle iteration: 'task:<id>=<status>' or 'always'
+        /// Set guard condition for cycle iteration: 'task:<id>=<status>', 'always', or an
+        /// expression such as 'score(review) < 0.8 && iteration < 5'
         #[arg(long = "cycle-guard")]
         cycle_guard: Option<String>,
 
@@ -174,6 +291,22 @@ enum Commands {
         #[arg(long = "cycle-delay")]
         cycle_delay: Option<String>,
 
+        /// Set the per-run timeout (e.g., 30m, 2h), or 'none' to remove it
+        #[arg(long)]
+        timeout: Option<String>,
+
+        /// Set the recurring schedule (cron expression, '@daily', '@every 6h'), or 'none' to remove it
+        #[arg(long)]
+        schedule: Option<String>,
+
+        /// Set the IANA timezone of the schedule
+        #[arg(long = "schedule-tz", value_name = "TZ")]
+        schedule_tz: Option<String>,
+
+        /// Set the catch-up policy of the schedule: skip, once, all
+        #[arg(long = "catch-up", value_name = "POLICY")]
+        catch_up: Option<String>,
+
         /// Set task visibility zone (internal, public, peer)
         #[arg(long)]
         visibility: Option<String>,
@@ -187,6 +320,11 @@ enum Commands {
         /// Signal that the task's iterative loop has converged (stops loop edges from firing)
         #[arg(long)]
         converged: bool,
+
+        /// Mark the task failed, instead of refusing, if its output contract
+        /// is not met (used by the spawn wrapper)
+        #[arg(long)]
+        fail_unmet: bool,
     },
 
     /// Mark a task as failed (can be retried)
@@ -231,6 +369,41 @@ enum Commands {
         id: String,
     },
 
+    /// Approve an approval gate
+    Approve {
+        /// Gate task ID
+        id: String,
+
+        /// Approver to vote as (default: $USER)
+        #[arg(long)]
+        actor: Option<String>,
+    },
+
+    /// Reject an approval gate; tasks waiting on it are abandoned
+    Reject {
+        /// Gate task ID
+        id: String,
+
+        /// Why the gate is rejected
+        #[arg(long)]
+        reason: Option<String>,
+
+        /// Approver to vote as (default: $USER)
+        #[arg(long)]
+        actor: Option<String>,
+    },
+
+    /// List undecided approval gates
+    Gates {
+        /// Prompt for a decision on each ready gate you may vote on
+        #[arg(long)]
+        review: bool,
+
+        /// Approver to review as (default: $USER)
+        #[arg(long)]
+        actor: Option<String>,
+    },
+
     /// Pause a task (coordinator will skip it until resumed)
     Pause {
         /// Task ID to pause
@@ -272,6 +445,12 @@ enum Commands {
         id: String,
     },
 
+    /// Show an epic's subtasks and rollup, or list all epics
+    Epic {
+        /// Epic task ID (omit to list every epic)
+        id: Option<String>,
+    },
+
     /// Check the graph for issues (cycles, orphan references)
     Check,
 
@@ -287,6 +466,15 @@ enum Commands {
         /// Only show paused tasks
         #[arg(long)]
         paused: bool,
+
+        /// Show the tasks inside epics instead of folding them into a rollup
+        #[arg(long)]
+        expand: bool,
+
+        /// List tasks as they were at this time, from the graph history
+        /// (e.g. 2026-09-01T12:00, 2026-09-01, "2h ago")
+        #[arg(long)]
+        at: Option<String>,
     },
 
     /// Visualize the dependency graph (ASCII tree by default)
@@ -322,6 +510,10 @@ enum Commands {
         /// Show internal tasks (assign-*, evaluate-*) normally hidden
         #[arg(long)]
         show_internal: bool,
+
+        /// Draw each epic as a single node carrying its rollup
+        #[arg(long)]
+        collapse_epics: bool,
     },
 
     /// Output the full graph data (DOT format with archive support)
@@ -340,10 +532,15 @@ enum Commands {
         until: Option<String>,
     },
 
-    /// Calculate cost of a task including dependencies
+    /// Compare estimated and actual cost of a task including dependencies,
+    /// or of the whole project
     Cost {
-        /// Task ID
-        id: String,
+        /// Task ID (default: whole project)
+        id: Option<String>,
+
+        /// Break actual spend down by task, role, agent or model
+        #[arg(long)]
+        by: Option<String>,
     },
 
     /// Show coordination status: ready tasks, in-progress tasks, and opportunities
@@ -396,6 +593,17 @@ enum Commands {
         dry_run: bool,
     },
 
+    /// Convert the graph between storage backends (jsonl, sqlite)
+    MigrateStorage {
+        /// Target backend: jsonl or sqlite
+        #[arg(long)]
+        to: String,
+
+        /// Show what would be migrated without making changes
+        #[arg(long)]
+        dry_run: bool,
+    },
+
     /// Analyze graph structure: entry points (no dependencies), dead ends
     /// (nothing depends on them), fan-out (tasks blocking many others),
     /// and high-impact root tasks.
@@ -464,6 +672,45 @@ enum Commands {
     Show {
         /// Task ID
         id: String,
+
+        /// Show the task as it was at this time, from the graph history
+        /// (e.g. 2026-09-01T12:00, 2026-09-01, "2h ago")
+        #[arg(long)]
+        at: Option<String>,
+    },
+
+    /// Show how the graph changed between two times, from the graph history
+    Diff {
+        /// Start time (e.g. 2026-09-01T12:00, 2026-09-01, "2h ago")
+        from: String,
+
+        /// End time (default: now)
+        to: Option<String>,
+
+        /// Only show changes to this task
+        #[arg(long)]
+        task: Option<String>,
+    },
+
+    /// Revert your last graph changes (from the graph history)
+    Undo {
+        /// Number of changes to revert (default: 1; with --list, how many to show)
+        count: Option<usize>,
+
+        /// Preview the changes `wg undo N` would revert instead of reverting
+        #[arg(long)]
+        list: bool,
+
+        /// Revert even if later changes touched the same tasks
+        #[arg(long)]
+        force: bool,
+    },
+
+    /// Re-apply the changes reverted by your last `wg undo`
+    Redo {
+        /// Re-apply even if later changes touched the same tasks
+        #[arg(long)]
+        force: bool,
     },
 
     /// Trace commands: execution history and trace functions
@@ -534,6 +781,12 @@ enum Commands {
         operations: bool,
     },
 
+    /// Recurring tasks run by the service daemon
+    Schedule {
+        #[command(subcommand)]
+        command: ScheduleCommands,
+    },
+
     /// Manage resources
     Resource {
         #[command(subcommand)]
@@ -617,6 +870,13 @@ enum Commands {
         remove: bool,
     },
 
+    /// Publish named outputs that later tasks bind with
+    /// {{deps.<id>.outputs.<key>}}
+    Output {
+        #[command(subcommand)]
+        command: OutputCommands,
+    },
+
     /// Show available context for a task from its dependencies
     Context {
         /// Task ID
@@ -765,6 +1025,11 @@ enum Commands {
         #[arg(long)]
         coordinator_executor: Option<String>,
 
+        /// Set the order ready tasks are dispatched in: fifo, priority,
+        /// critical-path-first, shortest-estimate, weighted-fair
+        #[arg(long, value_name = "POLICY")]
+        dispatch: Option<workgraph::dispatch::DispatchPolicy>,
+
         /// Matrix configuration subcommand
         #[arg(long)]
         matrix: bool,
@@ -922,7 +1187,11 @@ enum Commands {
     Quickstart,
 
     /// Quick one-screen status overview
-    Status,
+    Status {
+        /// List the subtasks under each open epic
+        #[arg(long)]
+        expand: bool,
+    },
 
     /// Send task notification to Matrix room
     #[cfg(any(feature = "matrix", feature = "matrix-lite"))]
@@ -952,6 +1221,18 @@ enum Commands {
         replay: usize,
     },
 
+    /// Outbound webhooks configured in config.toml
+    Webhooks {
+        #[command(subcommand)]
+        command: WebhookCommands,
+    },
+
+    /// Send tasks to email agents and apply their replies
+    Email {
+        #[command(subcommand)]
+        command: EmailCommands,
+    },
+
     /// Matrix integration commands
     #[cfg(any(feature = "matrix", feature = "matrix-lite"))]
     Matrix {
@@ -1208,6 +1489,82 @@ enum RunsCommands {
     },
 }
 
+#[derive(Subcommand)]
+enum ScheduleCommands {
+    /// List scheduled tasks and their next fire times
+    List {
+        /// Number of upcoming fire times to show per schedule
+        #[arg(long, default_value = "3")]
+        count: usize,
+    },
+}
+
+#[derive(Subcommand)]
+enum WebhookCommands {
+    /// List configured webhooks with queued and failed deliveries
+    List,
+
+    /// Send a sample event to each configured webhook now
+    Test {
+        /// Only send to the webhook with this URL
+        #[arg(long)]
+        url: Option<String>,
+
+        /// Event type of the sample (default: task.completed)
+        #[arg(long, default_value = "task.completed")]
+        event: String,
+    },
+}
+
+#[derive(Subcommand)]
+enum EmailCommands {
+    /// Mail a task to its email agent and claim it for them (again, as a
+    /// reminder, if they already hold it)
+    Send {
+        /// Task ID
+        task: String,
+    },
+
+    /// Apply replies waiting in the configured Maildir
+    Ingest,
+}
+
+#[derive(Subcommand)]
+enum OutputCommands {
+    /// Set outputs from key=value pairs (values that parse as JSON are
+    /// stored as JSON) and/or a JSON object file
+    Set {
+        /// Task ID
+        task: String,
+
+        /// Outputs to set, as key=value
+        pairs: Vec<String>,
+
+        /// JSON file holding an object of outputs
+        #[arg(long)]
+        file: Option<String>,
+    },
+
+    /// Show a task's outputs, or the value of one
+    Get {
+        /// Task ID
+        task: String,
+
+        /// Output key (omit to list all)
+        key: Option<String>,
+    },
+
+    /// Remove outputs
+    Unset {
+        /// Task ID
+        task: String,
+
+        /// Output keys to remove
+        #[arg(required = true)]
+        keys: Vec<String>,
+    },
+}
+
 #[derive(Subcommand)]
 enum ResourceCommands {
     /// Add a new resource
@@ -1230,6 +1587,23 @@ enum ResourceCommands {
         /// Unit (usd, hours, gpu-hours, etc.)
         #[arg(long)]
         unit: Option<String>,
+
+        /// Make this a budget the coordinator enforces on agent spend, with
+        /// --available as the cap: usd or tokens
+        #[arg(long, value_name = "METER")]
+        budget: Option<String>,
+
+        /// Reset the budget every day, week or month (UTC)
+        #[arg(long, requires = "budget")]
+        window: Option<String>,
+
+        /// Only count tasks with this tag against the budget
+        #[arg(long, requires = "budget")]
+        tag: Option<String>,
+
+        /// Apply the cap to each task's own spend
+        #[arg(long, requires = "budget")]
+        per_task: bool,
     },
 
     /// List all resources
@@ -1628,7 +2002,7 @@ enum AgentCommands {
 enum ServiceCommands {
     /// Start the agent service daemon
     Start {
-        /// Port to listen on (optional, for HTTP API)
+        /// Serve the HTTP API on 127.0.0.1:PORT (overrides config.toml http.bind)
         #[arg(long)]
         port: Option<u16>,
 
@@ -1739,6 +2113,10 @@ enum ServiceCommands {
         /// Model to use for spawned agents (overrides config.toml coordinator.model)
         #[arg(long)]
         model: Option<String>,
+
+        /// Serve the HTTP API on this address (overrides config.toml http.bind)
+        #[arg(long)]
+        http: Option<String>,
     },
 }
 
@@ -1965,12 +2343,16 @@ fn command_name(cmd: &Commands) -> &'static str {
         Commands::Retry { .. } => "retry",
         Commands::Claim { .. } => "claim",
         Commands::Unclaim { .. } => "unclaim",
+        Commands::Approve { .. } => "approve",
+        Commands::Reject { .. } => "reject",
+        Commands::Gates { .. } => "gates",
         Commands::Pause { .. } => "pause",
         Commands::Resume { .. } => "resume",
         Commands::Reclaim { .. } => "reclaim",
         Commands::Ready => "ready",
         Commands::Blocked { .. } => "blocked",
         Commands::WhyBlocked { .. } => "why-blocked",
+        Commands::Epic { .. } => "epic",
         Commands::Check => "check",
         Commands::Cycles => "cycles",
         Commands::List { .. } => "list",
@@ -1983,6 +2365,7 @@ fn command_name(cmd: &Commands) -> &'static str {
         Commands::Impact { .. } => "impact",
         Commands::Loops => "loops",
         Commands::MigrateLoops { .. } => "migrate-loops",
+        Commands::MigrateStorage { .. } => "migrate-storage",
         Commands::Structure => "structure",
         Commands::Bottlenecks => "bottlenecks",
         Commands::Velocity { .. } => "velocity",
@@ -1995,10 +2378,14 @@ fn command_name(cmd: &Commands) -> &'static str {
         Commands::Archive { .. } => "archive",
         Commands::Gc { .. } => "gc",
         Commands::Show { .. } => "show",
+        Commands::Diff { .. } => "diff",
+        Commands::Undo { .. } => "undo",
+        Commands::Redo { .. } => "redo",
         Commands::Trace { .. } => "trace",
         Commands::Replay { .. } => "replay",
         Commands::Runs { .. } => "runs",
         Commands::Log { .. } => "log",
+        Commands::Schedule { .. } => "schedule",
         Commands::Resource { .. } => "resource",
         Commands::Skill { .. } => "skill",
         Commands::Agency { .. } => "agency",
@@ -2009,6 +2396,7 @@ fn command_name(cmd: &Commands) -> &'static str {
         Commands::Match { .. } => "match",
         Commands::Heartbeat { .. } => "heartbeat",
         Commands::Artifact { .. } => "artifact",
+        Commands::Output { .. } => "output",
         Commands::Context { .. } => "context",
         Commands::Next { .. } => "next",
         Commands::Trajectory { .. } => "trajectory",
@@ -2017,6 +2405,8 @@ fn command_name(cmd: &Commands) -> &'static str {
         Commands::Spawn { .. } => "spawn",
         Commands::Evaluate { .. } => "evaluate",
         Commands::Watch { .. } => "watch",
+        Commands::Webhooks { .. } => "webhooks",
+        Commands::Email { .. } => "email",
         Commands::Evolve { .. } => "evolve",
         Commands::Config { .. } => "config",
         Commands::DeadAgents { .. } => "dead-agents",
@@ -2026,7 +2416,7 @@ fn command_name(cmd: &Commands) -> &'static str {
         Commands::Tui { .. } => "tui",
         Commands::Setup => "setup",
         Commands::Quickstart => "quickstart",
-        Commands::Status => "status",
+        Commands::Status { .. } => "status",
         #[cfg(any(feature = "matrix", feature = "matrix-lite"))]
         Commands::Notify { .. } => "notify",
         #[cfg(any(feature = "matrix", feature = "matrix-lite"))]
@@ -2041,6 +2431,7 @@ fn supports_json(cmd: &Commands) -> bool {
         Commands::Ready
             | Commands::Blocked { .. }
             | Commands::WhyBlocked { .. }
+            | Commands::Epic { .. }
             | Commands::List { .. }
             | Commands::Coordinate { .. }
             | Commands::Plan { .. }
@@ -2058,10 +2449,15 @@ fn supports_json(cmd: &Commands) -> bool {
             | Commands::Archive { .. }
             | Commands::Gc { .. }
             | Commands::Show { .. }
+            | Commands::Diff { .. }
+            | Commands::Undo { .. }
+            | Commands::Redo { .. }
             | Commands::Trace { .. }
             | Commands::Replay { .. }
             | Commands::Runs { .. }
             | Commands::Log { .. }
+            | Commands::Schedule { .. }
+            | Commands::Gates { .. }
             | Commands::Resource { .. }
             | Commands::Skill { .. }
             | Commands::Agency { .. }
@@ -2071,12 +2467,15 @@ fn supports_json(cmd: &Commands) -> bool {
             | Commands::Match { .. }
             | Commands::Heartbeat { .. }
             | Commands::Artifact { .. }
+            | Commands::Output { .. }
             | Commands::Context { .. }
             | Commands::Next { .. }
             | Commands::Trajectory { .. }
             | Commands::Agent { .. }
             | Commands::Evaluate { .. }
             | Commands::Watch { .. }
+            | Commands::Webhooks { .. }
+            | Commands::Email { .. }
             | Commands::Evolve { .. }
             | Commands::Config { .. }
             | Commands::DeadAgents { .. }
@@ -2087,7 +2486,7 @@ fn supports_json(cmd: &Commands) -> bool {
             | Commands::Check
             | Commands::Cycles
             | Commands::Quickstart
-            | Commands::Status
+            | Commands::Status { .. }
     ) || {
         #[cfg(any(feature = "matrix", feature = "matrix-lite"))]
         {
@@ -2183,7 +2582,13 @@ fn main() -> Result<()> {
             description,
             repo,
             after,
+            after_success,
+            after_failure,
+            after_any,
+            requires,
             assign,
+            priority,
+            requester,
             hours,
             cost,
             tag,
@@ -2191,14 +2596,74 @@ fn main() -> Result<()> {
             input,
             deliverable,
             max_retries,
+            timeout,
             model,
             verify,
             max_iterations,
             cycle_guard,
             cycle_delay,
+            map_over,
+            map_from,
+            parent,
+            schedule,
+            schedule_tz,
+            catch_up,
+            gate,
+            approvers,
+            quorum,
+            expect_file,
+            result_schema,
+            result_path,
+            check,
+            check_status,
             visibility,
         } => {
+            use workgraph::graph::EdgeCondition;
+            let conditional_after: Vec<(String, EdgeCondition)> = [
+                (after_success, EdgeCondition::Success),
+                (after_failure, EdgeCondition::Failure),
+                (after_any, EdgeCondition::Any),
+            ]
+            .into_iter()
+            .flat_map(|(ids, condition)| ids.into_iter().map(move |id| (id, condition)))
+            .collect();
+            let requires = requires
+                .iter()
+                .map(|spec| commands::add::parse_requirement(spec))
+                .collect::<Result<Vec<_>>>()?;
             if let Some(ref peer_ref) = repo {
+                if !requires.is_empty() {
+                    anyhow::bail!("--requires is not supported with --repo");
+                }
+                if !conditional_after.is_empty() {
+                    anyhow::bail!(
+                        "--after-success/--after-failure/--after-any are not supported with --repo"
+                    );
+                }
+                if map_over.is_some() {
+                    anyhow::bail!("--map-over is not supported with --repo");
+                }
+                if parent.is_some() {
+                    anyhow::bail!("--parent is not supported with --repo");
+                }
+                if schedule.is_some() {
+                    anyhow::bail!("--schedule is not supported with --repo");
+                }
+                if priority.is_some() || requester.is_some() {
+                    anyhow::bail!("--priority and --requester are not supported with --repo");
+                }
+                if gate {
+                    anyhow::bail!("--gate is not supported with --repo");
+                }
+                if !expect_file.is_empty()
+                    || result_schema.is_some()
+                    || result_path.is_some()
+                    || check.is_some()
+                {
+                    anyhow::bail!(
+                        "--expect-file, --result-schema, --result-path and --check are not supported with --repo"
+                    );
+                }
                 commands::add::run_remote(
                     &workgraph_dir,
                     peer_ref,
@@ -2213,26 +2678,58 @@ fn main() -> Result<()> {
                     verify.as_deref(),
                 )
             } else {
+                let schedule = schedule
+                    .map(|cron| {
+                        commands::add::parse_schedule(
+                            &cron,
+                            schedule_tz.as_deref(),
+                            catch_up.as_deref(),
+                        )
+                    })
+                    .transpose()?;
+                let gate = gate
+                    .then(|| commands::add::parse_gate(&approvers, quorum))
+                    .transpose()?;
+                let contract = commands::add::parse_contract(
+                    &expect_file,
+                    result_schema.as_deref(),
+                    result_path.as_deref(),
+                    check.as_deref(),
+                    check_status,
+                )?;
                 commands::add::run(
                     &workgraph_dir,
                     &title,
-                    id.as_deref(),
-                    description.as_deref(),
-                    &after,
-                    assign.as_deref(),
-                    hours,
-                    cost,
-                    &tag,
-                    &skill,
-                    &input,
-                    &deliverable,
-                    max_retries,
-                    model.as_deref(),
-                    verify.as_deref(),
-                    max_iterations,
-                    cycle_guard.as_deref(),
-                    cycle_delay.as_deref(),
-                    &visibility,
+                    commands::add::AddOptions {
+                        id: id.as_deref(),
+                        description: description.as_deref(),
+                        after: &after,
+                        conditional_after: &conditional_after,
+                        requires: &requires,
+                        assign: assign.as_deref(),
+                        priority,
+                        requester: requester.as_deref(),
+                        hours,
+                        cost,
+                        tags: &tag,
+                        skills: &skill,
+                        inputs: &input,
+                        deliverables: &deliverable,
+                        max_retries,
+                        timeout: timeout.as_deref(),
+                        model: model.as_deref(),
+                        verify: verify.as_deref(),
+                        max_iterations,
+                        cycle_guard: cycle_guard.as_deref(),
+                        cycle_delay: cycle_delay.as_deref(),
+                        map_over: map_over.as_deref(),
+                        map_from: map_from.as_deref(),
+                        parent: parent.as_deref(),
+                        schedule,
+                        gate,
+                        contract,
+                        visibility: Some(visibility.as_str()),
+                    },
                 )
             }
         }
@@ -2245,30 +2742,58 @@ fn main() -> Result<()> {
             add_tag,
             remove_tag,
             model,
+            priority,
+            requester,
             add_skill,
             remove_skill,
+            add_requires,
+            remove_requires,
             max_iterations,
             cycle_guard,
             cycle_delay,
+            timeout,
+            schedule,
+            schedule_tz,
+            catch_up,
             visibility,
-        } => commands::edit::run(
-            &workgraph_dir,
-            &id,
-            title.as_deref(),
-            description.as_deref(),
-            &add_after,
-            &remove_after,
-            &add_tag,
-            &remove_tag,
-            model.as_deref(),
-            &add_skill,
-            &remove_skill,
-            max_iterations,
-            cycle_guard.as_deref(),
-            cycle_delay.as_deref(),
-            visibility.as_deref(),
-        ),
-        Commands::Done { id, converged } => commands::done::run(&workgraph_dir, &id, converged),
+        } => {
+            let add_requires = add_requires
+                .iter()
+                .map(|spec| commands::add::parse_requirement(spec))
+                .collect::<Result<Vec<_>>>()?;
+            commands::edit::run(
+                &workgraph_dir,
+                &id,
+                commands::edit::EditOptions {
+                    title: title.as_deref(),
+                    description: description.as_deref(),
+                    add_after: &add_after,
+                    remove_after: &remove_after,
+                    add_tag: &add_tag,
+                    remove_tag: &remove_tag,
+                    model: model.as_deref(),
+                    priority,
+                    requester: requester.as_deref(),
+                    add_skill: &add_skill,
+                    remove_skill: &remove_skill,
+                    add_requires: &add_requires,
+                    remove_requires: &remove_requires,
+                    max_iterations,
+                    cycle_guard: cycle_guard.as_deref(),
+                    cycle_delay: cycle_delay.as_deref(),
+                    timeout: timeout.as_deref(),
+                    schedule: schedule.as_deref(),
+                    schedule_tz: schedule_tz.as_deref(),
+                    catch_up: catch_up.as_deref(),
+                    visibility: visibility.as_deref(),
+                },
+            )
+        }
+        Commands::Done {
+            id,
+            converged,
+            fail_unmet,
+        } => commands::done::run(&workgraph_dir, &id, converged, fail_unmet),
         Commands::Fail { id, reason } => {
             commands::fail::run(&workgraph_dir, &id, reason.as_deref())
         }
@@ -2280,6 +2805,19 @@ fn main() -> Result<()> {
             commands::claim::claim(&workgraph_dir, &id, actor.as_deref())
         }
         Commands::Unclaim { id } => commands::claim::unclaim(&workgraph_dir, &id),
+        Commands::Approve { id, actor } => {
+            commands::gate::approve(&workgraph_dir, &id, actor.as_deref())
+        }
+        Commands::Reject { id, reason, actor } => {
+            commands::gate::reject(&workgraph_dir, &id, reason.as_deref(), actor.as_deref())
+        }
+        Commands::Gates { review, actor } => {
+            if review {
+                commands::gate::review(&workgraph_dir, actor.as_deref())
+            } else {
+                commands::gate::list(&workgraph_dir, cli.json)
+            }
+        }
         Commands::Pause { id } => commands::pause::run(&workgraph_dir, &id),
         Commands::Resume { id } => commands::resume::run(&workgraph_dir, &id),
         Commands::Reclaim { id, from, to } => {
@@ -2288,11 +2826,22 @@ fn main() -> Result<()> {
         Commands::Ready => commands::ready::run(&workgraph_dir, cli.json),
         Commands::Blocked { id } => commands::blocked::run(&workgraph_dir, &id, cli.json),
         Commands::WhyBlocked { id } => commands::why_blocked::run(&workgraph_dir, &id, cli.json),
+        Commands::Epic { id } => commands::epic::run(&workgraph_dir, id.as_deref(), cli.json),
         Commands::Check => commands::check::run(&workgraph_dir, cli.json),
         Commands::Cycles => commands::cycles::run(&workgraph_dir, cli.json),
-        Commands::List { status, paused } => {
-            commands::list::run(&workgraph_dir, status.as_deref(), paused, cli.json)
-        }
+        Commands::List {
+            status,
+            paused,
+            expand,
+            at,
+        } => commands::list::run(
+            &workgraph_dir,
+            status.as_deref(),
+            paused,
+            expand,
+            at.as_deref(),
+            cli.json,
+        ),
         Commands::Viz {
             all,
             status,
@@ -2302,6 +2851,7 @@ fn main() -> Result<()> {
             graph,
             output,
             show_internal,
+            collapse_epics,
         } => {
             let fmt = if dot {
                 commands::viz::OutputFormat::Dot
@@ -2319,6 +2869,7 @@ fn main() -> Result<()> {
                 format: fmt,
                 output,
                 show_internal,
+                collapse_epics,
             };
             commands::viz::run(&workgraph_dir, &options)
         }
@@ -2327,7 +2878,9 @@ fn main() -> Result<()> {
             since,
             until,
         } => commands::graph::run(&workgraph_dir, archive, since.as_deref(), until.as_deref()),
-        Commands::Cost { id } => commands::cost::run(&workgraph_dir, &id, cli.json),
+        Commands::Cost { id, by } => {
+            commands::cost::run(&workgraph_dir, id.as_deref(), by.as_deref(), cli.json)
+        }
         Commands::Coordinate { max_parallel } => {
             commands::coordinate::run(&workgraph_dir, cli.json, max_parallel)
         }
@@ -2345,6 +2898,9 @@ fn main() -> Result<()> {
         Commands::MigrateLoops { dry_run } => {
             commands::migrate_loops::run(&workgraph_dir, dry_run)
         }
+        Commands::MigrateStorage { to, dry_run } => {
+            commands::migrate_storage::run(&workgraph_dir, &to, dry_run)
+        }
         Commands::Structure => commands::structure::run(&workgraph_dir, cli.json),
         Commands::Bottlenecks => commands::bottlenecks::run(&workgraph_dir, cli.json),
         Commands::Velocity { weeks } => commands::velocity::run(&workgraph_dir, cli.json, weeks),
@@ -2363,7 +2919,20 @@ fn main() -> Result<()> {
             dry_run,
             include_done,
         } => commands::gc::run(&workgraph_dir, dry_run, include_done),
-        Commands::Show { id } => commands::show::run(&workgraph_dir, &id, cli.json),
+        Commands::Diff { from, to, task } => commands::diff::run(
+            &workgraph_dir,
+            &from,
+            to.as_deref(),
+            task.as_deref(),
+            cli.json,
+        ),
+        Commands::Undo { count, list, force } => {
+            commands::undo::run(&workgraph_dir, count, list, force, cli.json)
+        }
+        Commands::Redo { force } => commands::undo::run_redo(&workgraph_dir, force, cli.json),
+        Commands::Show { id, at } => {
+            commands::show::run(&workgraph_dir, &id, at.as_deref(), cli.json)
+        }
         Commands::Trace { command } => match command {
             TraceCommands::Show { id, full, ops_only, recursive, timeline, graph, animate, speed } => {
                 if animate {
@@ -2529,6 +3098,11 @@ fn main() -> Result<()> {
                 }
             }
         }
+        Commands::Schedule { command } => match command {
+            ScheduleCommands::List { count } => {
+                commands::schedule::list(&workgraph_dir, count, cli.json)
+            }
+        },
         Commands::Resource { command } => match command {
             ResourceCommands::Add {
                 id,
@@ -2536,14 +3110,26 @@ fn main() -> Result<()> {
                 resource_type,
                 available,
                 unit,
-            } => commands::resource::run_add(
-                &workgraph_dir,
-                &id,
-                name.as_deref(),
-                resource_type.as_deref(),
-                available,
-                unit.as_deref(),
-            ),
+                budget,
+                window,
+                tag,
+                per_task,
+            } => {
+                let budget = budget
+                    .map(|meter| {
+                        commands::resource::parse_budget(&meter, window.as_deref(), tag, per_task)
+                    })
+                    .transpose()?;
+                commands::resource::run_add(
+                    &workgraph_dir,
+                    &id,
+                    name.as_deref(),
+                    resource_type.as_deref(),
+                    available,
+                    unit.as_deref(),
+                    budget,
+                )
+            }
             ResourceCommands::List => commands::resource::run_list(&workgraph_dir, cli.json),
         },
         Commands::Skill { command } => match command {
@@ -2736,6 +3322,17 @@ fn main() -> Result<()> {
                 commands::artifact::run_list(&workgraph_dir, &task, cli.json)
             }
         }
+        Commands::Output { command } => match command {
+            OutputCommands::Set { task, pairs, file } => {
+                commands::output::run_set(&workgraph_dir, &task, &pairs, file.as_deref())
+            }
+            OutputCommands::Get { task, key } => {
+                commands::output::run_get(&workgraph_dir, &task, key.as_deref(), cli.json)
+            }
+            OutputCommands::Unset { task, keys } => {
+                commands::output::run_unset(&workgraph_dir, &task, &keys)
+            }
+        },
         Commands::Context { task, dependents } => {
             if dependents {
                 commands::context::run_dependents(&workgraph_dir, &task, cli.json)
@@ -2880,6 +3477,18 @@ fn main() -> Result<()> {
             task.as_deref(),
             replay,
         ),
+        Commands::Webhooks { command } => match command {
+            WebhookCommands::List => commands::webhooks::run_list(&workgraph_dir, cli.json),
+            WebhookCommands::Test { url, event } => {
+                commands::webhooks::run_test(&workgraph_dir, url.as_deref(), &event, cli.json)
+            }
+        },
+        Commands::Email { command } => match command {
+            EmailCommands::Send { task } => {
+                commands::email::run_send(&workgraph_dir, &task, cli.json)
+            }
+            EmailCommands::Ingest => commands::email::run_ingest(&workgraph_dir, cli.json),
+        },
         Commands::Evolve {
             dry_run,
             strategy,
@@ -2906,6 +3515,7 @@ fn main() -> Result<()> {
             coordinator_interval,
             poll_interval,
             coordinator_executor,
+            dispatch,
             matrix,
             homeserver,
             username,
@@ -2972,6 +3582,7 @@ fn main() -> Result<()> {
                     && coordinator_interval.is_none()
                     && poll_interval.is_none()
                     && coordinator_executor.is_none()
+                    && dispatch.is_none()
                     && auto_evaluate.is_none()
                     && auto_assign.is_none()
                     && assigner_model.is_none()
@@ -3001,6 +3612,7 @@ fn main() -> Result<()> {
                     coordinator_interval,
                     poll_interval,
                     coordinator_executor.as_deref(),
+                    dispatch,
                     auto_evaluate,
                     auto_assign,
                     assigner_model.as_deref(),
@@ -3122,6 +3734,7 @@ fn main() -> Result<()> {
                 executor,
                 interval,
                 model,
+                http,
             } => commands::service::run_daemon(
                 &workgraph_dir,
                 &socket,
@@ -3129,12 +3742,13 @@ fn main() -> Result<()> {
                 executor.as_deref(),
                 interval,
                 model.as_deref(),
+                http.as_deref(),
             ),
         },
         Commands::Tui { refresh_rate } => tui::run(workgraph_dir, refresh_rate),
         Commands::Setup => commands::setup::run(),
         Commands::Quickstart => commands::quickstart::run(cli.json),
-        Commands::Status => commands::status::run(&workgraph_dir, cli.json),
+        Commands::Status { expand } => commands::status::run(&workgraph_dir, expand, cli.json),
         #[cfg(any(feature = "matrix", feature = "matrix-lite"))]
         Commands::Notify {
             task,
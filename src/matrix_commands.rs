@@ -11,15 +11,33 @@
 //! - `fail <task> [reason]` - Mark a task as failed
 //! - `input <task> <text>` - Add input/log entry to a task
 //! - `unclaim <task>` - Release a claimed task
+//! - `approve <task>` - Approve an approval gate
+//! - `reject <task> [reason]` - Reject an approval gate
+//! - `add "title" [after:a,b] [skill:x] [tag:y]` - Add a task
+//! - `edit <task> ["title"] [after:a] [-after:b] [skill:x] [-tag:y]` - Edit a task
+//! - `show <task>` - Show a task's details
+//! - `why <task>` - Explain why a task isn't ready
+//! - `retry <task>` - Reset a failed task for another attempt
+//! - `pause [task]` / `resume [task]` - Pause or resume the coordinator, or one task
+//! - `agents` - List running agents
+//! - `kill <agent>` - Kill a running agent
 //! - `status` - Show current status
 //! - `ready` - List ready tasks
 //! - `help` - Show help
+//!
+//! Before running a command, the executor checks the sender against the
+//! `[matrix]` ACL in config.toml and attributes any change to the workgraph
+//! actor the sender maps to.
 
-use std::path::Path;
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
 
 use chrono::Utc;
 
-use crate::graph::{LogEntry, Status};
+use crate::config::{Config, MatrixAclConfig, MatrixPermission};
+use crate::graph::{
+    EdgeCondition, LogEntry, Node, Status, Task, WorkGraph, evaluate_cycle_iteration_in,
+};
 use crate::parser::{load_graph, save_graph};
 
 /// A parsed command from a Matrix message
@@ -41,6 +59,36 @@ pub enum MatrixCommand {
     Input { task_id: String, text: String },
     /// Release a claimed task
     Unclaim { task_id: String },
+    /// Approve an approval gate as the sender
+    Approve { task_id: String },
+    /// Reject an approval gate as the sender
+    Reject {
+        task_id: String,
+        reason: Option<String>,
+    },
+    /// Add a task
+    Add {
+        title: String,
+        after: Vec<String>,
+        skills: Vec<String>,
+        tags: Vec<String>,
+    },
+    /// Change a task's title, dependencies, skills or tags
+    Edit { task_id: String, edits: TaskEdits },
+    /// Show a task's details
+    Show { task_id: String },
+    /// Explain why a task isn't ready
+    Why { task_id: String },
+    /// Reset a failed task for another attempt
+    Retry { task_id: String },
+    /// Pause the coordinator, or one task
+    Pause { task_id: Option<String> },
+    /// Resume the coordinator, or one task
+    Resume { task_id: Option<String> },
+    /// List running agents
+    Agents,
+    /// Kill a running agent
+    Kill { agent_id: String },
     /// Show current status summary
     Status,
     /// List ready tasks
@@ -51,6 +99,40 @@ pub enum MatrixCommand {
     Unknown { command: String },
 }
 
+/// Changes made by an `edit` command. Options add to a list; the same
+/// option prefixed with `-` removes from it.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct TaskEdits {
+    pub title: Option<String>,
+    pub add_after: Vec<String>,
+    pub remove_after: Vec<String>,
+    pub add_skills: Vec<String>,
+    pub remove_skills: Vec<String>,
+    pub add_tags: Vec<String>,
+    pub remove_tags: Vec<String>,
+}
+
+impl TaskEdits {
+    fn is_empty(&self) -> bool {
+        *self == TaskEdits::default()
+    }
+
+    /// The tags a task with `tags` has once edited.
+    fn edited_tags(&self, tags: &[String]) -> Vec<String> {
+        let mut edited: Vec<String> = tags
+            .iter()
+            .filter(|t| !self.remove_tags.contains(t))
+            .cloned()
+            .collect();
+        for tag in &self.add_tags {
+            if !edited.contains(tag) {
+                edited.push(tag.clone());
+            }
+        }
+        edited
+    }
+}
+
 impl MatrixCommand {
     /// Parse a command from a message body
     ///
@@ -85,6 +167,25 @@ impl MatrixCommand {
         Some(parse_command(&words))
     }
 
+    /// Parse a message posted in `task_id`'s Matrix thread. Only prefixed
+    /// messages are commands there; anything else is a reply for the task
+    /// log. A bare command like `wg done` applies to the thread's task.
+    pub fn parse_in_thread(message: &str, task_id: &str) -> Option<Self> {
+        let message = message.trim();
+        let stripped = strip_prefix(message);
+        if stripped.len() == message.len() {
+            return None;
+        }
+        let mut words: Vec<&str> = stripped.split_whitespace().collect();
+        if words.len() == 1 && takes_task(&words[0].to_lowercase()) {
+            words.push(task_id);
+        }
+        if words.is_empty() {
+            return None;
+        }
+        Some(parse_command(&words))
+    }
+
     /// Get a human-readable description of what this command does
     pub fn description(&self) -> String {
         match self {
@@ -101,6 +202,26 @@ impl MatrixCommand {
                 format!("Add input to task '{}': {}", task_id, text)
             }
             MatrixCommand::Unclaim { task_id } => format!("Unclaim task '{}'", task_id),
+            MatrixCommand::Approve { task_id } => format!("Approve gate '{}'", task_id),
+            MatrixCommand::Reject { task_id, reason } => match reason {
+                Some(r) => format!("Reject gate '{}': {}", task_id, r),
+                None => format!("Reject gate '{}'", task_id),
+            },
+            MatrixCommand::Add { title, .. } => format!("Add task '{}'", title),
+            MatrixCommand::Edit { task_id, .. } => format!("Edit task '{}'", task_id),
+            MatrixCommand::Show { task_id } => format!("Show task '{}'", task_id),
+            MatrixCommand::Why { task_id } => format!("Explain why '{}' is waiting", task_id),
+            MatrixCommand::Retry { task_id } => format!("Retry task '{}'", task_id),
+            MatrixCommand::Pause { task_id } => match task_id {
+                Some(id) => format!("Pause task '{}'", id),
+                None => "Pause the coordinator".to_string(),
+            },
+            MatrixCommand::Resume { task_id } => match task_id {
+                Some(id) => format!("Resume task '{}'", id),
+                None => "Resume the coordinator".to_string(),
+            },
+            MatrixCommand::Agents => "List running agents".to_string(),
+            MatrixCommand::Kill { agent_id } => format!("Kill agent '{}'", agent_id),
             MatrixCommand::Status => "Show status".to_string(),
             MatrixCommand::Ready => "List ready tasks".to_string(),
             MatrixCommand::Help => "Show help".to_string(),
@@ -139,6 +260,17 @@ fn is_known_command(word: &str) -> bool {
             | "note"
             | "unclaim"
             | "release"
+            | "approve"
+            | "reject"
+            | "add"
+            | "edit"
+            | "show"
+            | "why"
+            | "retry"
+            | "pause"
+            | "resume"
+            | "agents"
+            | "kill"
             | "status"
             | "ready"
             | "list"
@@ -148,6 +280,25 @@ fn is_known_command(word: &str) -> bool {
     )
 }
 
+/// Check if a command takes a task ID as its first argument
+fn takes_task(word: &str) -> bool {
+    matches!(
+        word,
+        "claim"
+            | "done"
+            | "fail"
+            | "unclaim"
+            | "release"
+            | "approve"
+            | "reject"
+            | "show"
+            | "why"
+            | "retry"
+            | "pause"
+            | "resume"
+    )
+}
+
 /// Parse the actual command from words
 fn parse_command(words: &[&str]) -> MatrixCommand {
     if words.is_empty() {
@@ -214,6 +365,65 @@ fn parse_command(words: &[&str]) -> MatrixCommand {
                 task_id: words[1].to_string(),
             }
         }
+        "approve" => {
+            if words.len() < 2 {
+                return MatrixCommand::Unknown {
+                    command: "approve (missing task ID)".to_string(),
+                };
+            }
+            MatrixCommand::Approve {
+                task_id: words[1].to_string(),
+            }
+        }
+        "reject" => {
+            if words.len() < 2 {
+                return MatrixCommand::Unknown {
+                    command: "reject (missing task ID)".to_string(),
+                };
+            }
+            let task_id = words[1].to_string();
+            let reason = if words.len() > 2 {
+                Some(words[2..].join(" "))
+            } else {
+                None
+            };
+            MatrixCommand::Reject { task_id, reason }
+        }
+        "add" => parse_add(&words[1..].join(" ")),
+        "edit" => {
+            if words.len() < 2 {
+                return MatrixCommand::Unknown {
+                    command: "edit (missing task ID)".to_string(),
+                };
+            }
+            parse_edit(words[1], &words[2..].join(" "))
+        }
+        "show" | "why" | "retry" | "kill" => {
+            if words.len() < 2 {
+                let what = if command == "kill" {
+                    "agent ID"
+                } else {
+                    "task ID"
+                };
+                return MatrixCommand::Unknown {
+                    command: format!("{} (missing {})", command, what),
+                };
+            }
+            let id = words[1].to_string();
+            match command.as_str() {
+                "show" => MatrixCommand::Show { task_id: id },
+                "why" => MatrixCommand::Why { task_id: id },
+                "retry" => MatrixCommand::Retry { task_id: id },
+                _ => MatrixCommand::Kill { agent_id: id },
+            }
+        }
+        "pause" => MatrixCommand::Pause {
+            task_id: words.get(1).map(|w| w.to_string()),
+        },
+        "resume" => MatrixCommand::Resume {
+            task_id: words.get(1).map(|w| w.to_string()),
+        },
+        "agents" => MatrixCommand::Agents,
         "status" => MatrixCommand::Status,
         "ready" | "list" | "tasks" => MatrixCommand::Ready,
         "help" | "?" => MatrixCommand::Help,
@@ -223,6 +433,134 @@ fn parse_command(words: &[&str]) -> MatrixCommand {
     }
 }
 
+/// An `after:`, `skill:` or `tag:` option of `add` or `edit`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum ListOption {
+    After,
+    Skills,
+    Tags,
+}
+
+/// Split `add`/`edit` arguments into title words and list options (comma
+/// separated, repeatable). The title may be quoted so it can contain words
+/// like `after:`. With `removals`, options may be prefixed with `-`, and
+/// each comes with whether it was.
+fn parse_options(args: &str, removals: bool) -> (String, Vec<(ListOption, bool, Vec<String>)>) {
+    let mut title = Vec::new();
+    let mut options = Vec::new();
+    for (token, quoted) in split_quoted(args) {
+        let option = if quoted { None } else { token.split_once(':') };
+        let key = option.map(|(key, _)| key.to_lowercase());
+        let mut key = key.as_deref();
+        let removing = removals && key.is_some_and(|k| k.starts_with('-'));
+        if removing {
+            key = key.map(|k| &k[1..]);
+        }
+        let list = match key {
+            Some("after") => ListOption::After,
+            Some("skill" | "skills") => ListOption::Skills,
+            Some("tag" | "tags") => ListOption::Tags,
+            _ => {
+                title.push(token);
+                continue;
+            }
+        };
+        let values = option.map(|(_, v)| v).unwrap_or_default();
+        let values = values
+            .split(',')
+            .filter(|v| !v.is_empty())
+            .map(String::from)
+            .collect();
+        options.push((list, removing, values));
+    }
+    (title.join(" "), options)
+}
+
+/// Parse `add` arguments: `after:`, `skill:` and `tag:` options, with every
+/// other word making up the title.
+fn parse_add(args: &str) -> MatrixCommand {
+    let (title, options) = parse_options(args, false);
+    if title.is_empty() {
+        return MatrixCommand::Unknown {
+            command: "add (missing title)".to_string(),
+        };
+    }
+    let (mut after, mut skills, mut tags) = (Vec::new(), Vec::new(), Vec::new());
+    for (list, _, values) in options {
+        match list {
+            ListOption::After => after.extend(values),
+            ListOption::Skills => skills.extend(values),
+            ListOption::Tags => tags.extend(values),
+        }
+    }
+    MatrixCommand::Add {
+        title,
+        after,
+        skills,
+        tags,
+    }
+}
+
+/// Parse `edit` arguments: options like `add`'s add to the task's lists and
+/// `-after:`, `-skill:` and `-tag:` remove from them; any other words are
+/// the new title.
+fn parse_edit(task_id: &str, args: &str) -> MatrixCommand {
+    let (title, options) = parse_options(args, true);
+    let mut edits = TaskEdits {
+        title: Some(title).filter(|t| !t.is_empty()),
+        ..TaskEdits::default()
+    };
+    for (list, removing, values) in options {
+        let list = match (list, removing) {
+            (ListOption::After, false) => &mut edits.add_after,
+            (ListOption::After, true) => &mut edits.remove_after,
+            (ListOption::Skills, false) => &mut edits.add_skills,
+            (ListOption::Skills, true) => &mut edits.remove_skills,
+            (ListOption::Tags, false) => &mut edits.add_tags,
+            (ListOption::Tags, true) => &mut edits.remove_tags,
+        };
+        list.extend(values);
+    }
+    if edits.is_empty() {
+        return MatrixCommand::Unknown {
+            command: "edit (nothing to change)".to_string(),
+        };
+    }
+    MatrixCommand::Edit {
+        task_id: task_id.to_string(),
+        edits,
+    }
+}
+
+/// Split on whitespace, keeping double-quoted runs (including the curly
+/// quotes phone keyboards insert) together. Returns each token and whether
+/// it was quoted.
+fn split_quoted(s: &str) -> Vec<(String, bool)> {
+    let mut tokens = Vec::new();
+    let mut current = String::new();
+    let mut in_quotes = false;
+    let mut was_quoted = false;
+    for c in s.chars() {
+        match c {
+            '"' | '\u{201c}' | '\u{201d}' => {
+                in_quotes = !in_quotes;
+                was_quoted = true;
+            }
+            c if c.is_whitespace() && !in_quotes => {
+                if !current.is_empty() || was_quoted {
+                    tokens.push((std::mem::take(&mut current), was_quoted));
+                }
+                was_quoted = false;
+            }
+            c => current.push(c),
+        }
+    }
+    if !current.is_empty() || was_quoted {
+        tokens.push((current, was_quoted));
+    }
+    tokens
+}
+
 /// Parse optional actor argument from remaining words
 fn parse_actor_arg(words: &[&str]) -> Option<String> {
     if words.is_empty() {
@@ -257,11 +595,22 @@ pub fn help_text() -> String {
 • `fail <task> [reason]` - Mark a task as failed
 • `input <task> <text>` - Add a log entry to a task
 • `unclaim <task>` - Release a claimed task
+• `approve <task>` - Approve an approval gate
+• `reject <task> [reason]` - Reject an approval gate
+• `add "title" [after:a,b] [skill:rust] [tag:x]` - Add a task
+• `edit <task> ["title"] [after:a] [-after:b] [skill:x] [-tag:y]` - Change a task's title or lists
+• `show <task>` - Show a task's details
+• `why <task>` - Explain why a task isn't ready
+• `retry <task>` - Retry a failed task
+• `pause [task]` / `resume [task]` - Pause or resume the coordinator, or one task
+• `agents` - List running agents
+• `kill <agent>` - Kill a running agent
 • `ready` - List tasks ready to work on
 • `status` - Show project status
 • `help` - Show this help
 
-Prefix commands with `wg` if needed (e.g., `wg claim task-1`)"#
+Prefix commands with `wg` if needed (e.g., `wg claim task-1`).
+Replies in a task's thread are added to its log."#
         .to_string()
 }
 
@@ -274,26 +623,212 @@ pub fn extract_localpart(user_id: &str) -> String {
         .to_string()
 }
 
+// ── Authorization ──────────────────────────────────────────────────────
+
+/// The workgraph actor a Matrix sender acts as, and what they may do.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Principal {
+    pub actor: String,
+    pub permission: MatrixPermission,
+    /// Tags limiting which tasks the sender may change (empty: all)
+    pub tags: Vec<String>,
+}
+
+impl Principal {
+    /// Look `sender` up in the ACL. Listed users act as their configured
+    /// actor; unlisted senders get the default permission and, once the ACL
+    /// lists anyone, act as their full Matrix ID so they can't pass for a
+    /// listed user with the same localpart on another server.
+    pub fn resolve(acl: &MatrixAclConfig, sender: &str) -> Self {
+        match acl.user(sender) {
+            Some(user) => Principal {
+                actor: user
+                    .actor
+                    .clone()
+                    .unwrap_or_else(|| extract_localpart(sender)),
+                permission: user.permission,
+                tags: user.tags.clone(),
+            },
+            None => Principal {
+                actor: if acl.users.is_empty() {
+                    extract_localpart(sender)
+                } else {
+                    sender.to_string()
+                },
+                permission: acl.default_permission(),
+                tags: Vec::new(),
+            },
+        }
+    }
+}
+
+/// Check that `principal` may run `command`, returning a polite refusal if
+/// not. Approve/reject need `claim-own` here and then a place on the gate's
+/// approver list. Like `add`, `edit` needs `complete`, and a sender limited
+/// to some tags can't edit a task out of them.
+pub fn authorize(
+    workgraph_dir: &Path,
+    principal: &Principal,
+    command: &MatrixCommand,
+) -> Result<(), String> {
+    let task_id = match command {
+        MatrixCommand::Claim { task_id, .. }
+        | MatrixCommand::Done { task_id }
+        | MatrixCommand::Fail { task_id, .. }
+        | MatrixCommand::Input { task_id, .. }
+        | MatrixCommand::Unclaim { task_id }
+        | MatrixCommand::Retry { task_id }
+        | MatrixCommand::Approve { task_id }
+        | MatrixCommand::Reject { task_id, .. }
+        | MatrixCommand::Edit { task_id, .. }
+        | MatrixCommand::Pause {
+            task_id: Some(task_id),
+        }
+        | MatrixCommand::Resume {
+            task_id: Some(task_id),
+        } => task_id,
+        MatrixCommand::Add { tags, .. } => {
+            require(principal, command, MatrixPermission::Complete)?;
+            if !principal.tags.is_empty() && !tags.iter().any(|t| principal.tags.contains(t)) {
+                return Err(format!(
+                    "Sorry, {}, tasks you add need one of your tags ({}), e.g. `tag:{}`.",
+                    principal.actor,
+                    principal.tags.join(", "),
+                    principal.tags[0]
+                ));
+            }
+            return Ok(());
+        }
+        MatrixCommand::Pause { task_id: None }
+        | MatrixCommand::Resume { task_id: None }
+        | MatrixCommand::Kill { .. } => {
+            return require(principal, command, MatrixPermission::Admin);
+        }
+        _ => return Ok(()),
+    };
+
+    // A missing graph or task is reported by the command itself.
+    let graph = load_graph(workgraph_dir.join("graph.jsonl")).ok();
+    let task = graph.as_ref().and_then(|g| g.get_task(task_id));
+    let own = task.and_then(|t| t.assigned.as_deref()) == Some(principal.actor.as_str());
+
+    let required = match command {
+        MatrixCommand::Claim {
+            actor: Some(actor), ..
+        } if *actor != principal.actor => MatrixPermission::Admin,
+        MatrixCommand::Claim { .. }
+        | MatrixCommand::Approve { .. }
+        | MatrixCommand::Reject { .. } => MatrixPermission::ClaimOwn,
+        MatrixCommand::Unclaim { .. } if !own => MatrixPermission::Admin,
+        MatrixCommand::Edit { .. } => MatrixPermission::Complete,
+        _ if own => MatrixPermission::ClaimOwn,
+        _ => MatrixPermission::Complete,
+    };
+    require(principal, command, required)?;
+
+    if let Some(task) = task
+        && !principal.tags.is_empty()
+        && !task.tags.iter().any(|t| principal.tags.contains(t))
+    {
+        return Err(format!(
+            "Sorry, {}, task '{}' is outside the tasks you can change here (tags: {}).",
+            principal.actor,
+            task_id,
+            principal.tags.join(", ")
+        ));
+    }
+    if let (Some(task), MatrixCommand::Edit { edits, .. }) = (task, command)
+        && !principal.tags.is_empty()
+        && !edits
+            .edited_tags(&task.tags)
+            .iter()
+            .any(|t| principal.tags.contains(t))
+    {
+        return Err(format!(
+            "Sorry, {}, task '{}' needs to keep one of your tags ({}).",
+            principal.actor,
+            task_id,
+            principal.tags.join(", ")
+        ));
+    }
+    Ok(())
+}
+
+/// Refuse `command` unless `principal` has at least `required` access.
+fn require(
+    principal: &Principal,
+    command: &MatrixCommand,
+    required: MatrixPermission,
+) -> Result<(), String> {
+    if principal.permission >= required {
+        return Ok(());
+    }
+    let action = command.description();
+    let mut action_chars = action.chars();
+    let action = match action_chars.next() {
+        Some(first) => first.to_lowercase().chain(action_chars).collect(),
+        None => action,
+    };
+    Err(format!(
+        "Sorry, {}, you don't have permission to {}. Your access is '{}' and this needs '{}'; ask a project admin if you need more.",
+        principal.actor, action, principal.permission, required
+    ))
+}
+
 // ── Command execution (shared graph-manipulation logic) ────────────────
 
 /// Execute a full command dispatch, returning the response message.
 ///
-/// The `sender` is used as the fallback actor for claim/input commands.
+/// The sender is resolved to a [`Principal`] through the `[matrix]` ACL; its
+/// actor is the fallback assignee for claim, the author of input, and who
+/// the change is attributed to in provenance and graph history. Approve and
+/// reject vote as the actor, or as the full `sender` ID if that is what the
+/// gate lists.
 pub fn execute_command(workgraph_dir: &Path, command: &MatrixCommand, sender: &str) -> String {
-    match command {
+    let acl = Config::load_or_default(workgraph_dir).matrix;
+    let principal = Principal::resolve(&acl, sender);
+    if let Err(refusal) = authorize(workgraph_dir, &principal, command) {
+        return refusal;
+    }
+    let by = principal.actor.as_str();
+
+    crate::history::with_actor(by, || match command {
         MatrixCommand::Claim { task_id, actor } => {
-            let actor_id = actor.clone().unwrap_or_else(|| extract_localpart(sender));
-            execute_claim(workgraph_dir, task_id, Some(&actor_id))
+            let actor_id = actor.as_deref().unwrap_or(by);
+            execute_claim(workgraph_dir, task_id, Some(actor_id), by)
         }
-        MatrixCommand::Done { task_id } => execute_done(workgraph_dir, task_id),
+        MatrixCommand::Done { task_id } => execute_done(workgraph_dir, task_id, by),
         MatrixCommand::Fail { task_id, reason } => {
-            execute_fail(workgraph_dir, task_id, reason.as_deref())
+            execute_fail(workgraph_dir, task_id, reason.as_deref(), by)
+        }
+        MatrixCommand::Input { task_id, text } => execute_input(workgraph_dir, task_id, text, by),
+        MatrixCommand::Unclaim { task_id } => execute_unclaim(workgraph_dir, task_id, by),
+        MatrixCommand::Approve { task_id } => {
+            execute_vote(workgraph_dir, task_id, by, sender, true, None)
         }
-        MatrixCommand::Input { task_id, text } => {
-            let actor = extract_localpart(sender);
-            execute_input(workgraph_dir, task_id, text, &actor)
+        MatrixCommand::Reject { task_id, reason } => {
+            execute_vote(workgraph_dir, task_id, by, sender, false, reason.as_deref())
         }
-        MatrixCommand::Unclaim { task_id } => execute_unclaim(workgraph_dir, task_id),
+        MatrixCommand::Add {
+            title,
+            after,
+            skills,
+            tags,
+        } => execute_add(workgraph_dir, title, after, skills, tags, by),
+        MatrixCommand::Edit { task_id, edits } => execute_edit(workgraph_dir, task_id, edits, by),
+        MatrixCommand::Show { task_id } => execute_show(workgraph_dir, task_id),
+        MatrixCommand::Why { task_id } => execute_why(workgraph_dir, task_id),
+        MatrixCommand::Retry { task_id } => execute_retry(workgraph_dir, task_id, by),
+        MatrixCommand::Pause { task_id } => match task_id {
+            Some(task_id) => execute_pause_task(workgraph_dir, task_id, true, by),
+            None => execute_pause_service(workgraph_dir, true, by),
+        },
+        MatrixCommand::Resume { task_id } => match task_id {
+            Some(task_id) => execute_pause_task(workgraph_dir, task_id, false, by),
+            None => execute_pause_service(workgraph_dir, false, by),
+        },
+        MatrixCommand::Agents => execute_agents(workgraph_dir),
+        MatrixCommand::Kill { agent_id } => execute_kill(workgraph_dir, agent_id, by),
         MatrixCommand::Status => execute_status(workgraph_dir),
         MatrixCommand::Ready => execute_ready(workgraph_dir),
         MatrixCommand::Help => help_text(),
@@ -303,11 +838,36 @@ pub fn execute_command(workgraph_dir: &Path, command: &MatrixCommand, sender: &s
                 command
             )
         }
-    }
+    })
+}
+
+/// Record a Matrix-originated change in the provenance log, attributed to
+/// the sender's actor.
+fn record(
+    workgraph_dir: &Path,
+    op: &str,
+    task_id: Option<&str>,
+    by: &str,
+    detail: serde_json::Value,
+) {
+    let config = Config::load_or_default(workgraph_dir);
+    let mut detail = match detail {
+        serde_json::Value::Object(map) => map,
+        _ => serde_json::Map::new(),
+    };
+    detail.insert("source".to_string(), "matrix".into());
+    let _ = crate::provenance::record(
+        workgraph_dir,
+        op,
+        task_id,
+        Some(by),
+        serde_json::Value::Object(detail),
+        config.log.rotation_threshold,
+    );
 }
 
-/// Execute claim command
-pub fn execute_claim(workgraph_dir: &Path, task_id: &str, actor: Option<&str>) -> String {
+/// Execute claim command. `by` is the actor the change is attributed to.
+pub fn execute_claim(workgraph_dir: &Path, task_id: &str, actor: Option<&str>, by: &str) -> String {
     let graph_path = workgraph_dir.join("graph.jsonl");
 
     if !graph_path.exists() {
@@ -324,6 +884,13 @@ pub fn execute_claim(workgraph_dir: &Path, task_id: &str, actor: Option<&str>) -
         None => return format!("Error: Task '{}' not found", task_id),
     };
 
+    if task.gate.is_some() {
+        return format!(
+            "Task '{}' is an approval gate. Reply `approve {}` or `reject {} <reason>`.",
+            task_id, task_id, task_id
+        );
+    }
+
     match task.status {
         Status::Open | Status::Blocked => {}
         Status::InProgress => {
@@ -348,15 +915,36 @@ pub fn execute_claim(workgraph_dir: &Path, task_id: &str, actor: Option<&str>) -
         }
     }
 
+    let prev_status = task.status;
+    let prev_assigned = task.assigned.clone();
     task.status = Status::InProgress;
     task.started_at = Some(Utc::now().to_rfc3339());
     if let Some(actor_id) = actor {
         task.assigned = Some(actor_id.to_string());
     }
 
+    let Some(task) = graph.get_task(task_id) else {
+        return format!("Error: Task '{}' not found", task_id);
+    };
+    let claim = match crate::capacity::lock_claim(workgraph_dir, task) {
+        Ok(claim) => claim,
+        Err(e) => return format!("Error: {}", e),
+    };
     if let Err(e) = save_graph(&graph, &graph_path) {
         return format!("Error saving graph: {}", e);
     }
+    drop(claim);
+    record(
+        workgraph_dir,
+        "claim",
+        Some(task_id),
+        by,
+        serde_json::json!({
+            "prev_status": prev_status,
+            "prev_assigned": prev_assigned,
+            "assigned": actor,
+        }),
+    );
 
     match actor {
         Some(actor_id) => format!("Claimed '{}' for '{}'", task_id, actor_id),
@@ -364,20 +952,48 @@ pub fn execute_claim(workgraph_dir: &Path, task_id: &str, actor: Option<&str>) -
     }
 }
 
-/// Execute done command
-pub fn execute_done(workgraph_dir: &Path, task_id: &str) -> String {
-    let graph_path = workgraph_dir.join("graph.jsonl");
-
-    if !graph_path.exists() {
+/// Execute approve/reject command. The gate's approvers must list `actor`
+/// or the full Matrix user ID `sender`.
+pub fn execute_vote(
+    workgraph_dir: &Path,
+    task_id: &str,
+    actor: &str,
+    sender: &str,
+    approved: bool,
+    reason: Option<&str>,
+) -> String {
+    if !workgraph_dir.join("graph.jsonl").exists() {
         return "Error: Workgraph not initialized".to_string();
     }
+    match crate::gate::decide(
+        workgraph_dir,
+        task_id,
+        actor,
+        Some(sender),
+        approved,
+        reason,
+        "matrix",
+    ) {
+        Ok((decision, propagation)) => {
+            let mut reply = decision.to_string();
+            for line in propagation.lines() {
+                reply.push_str(&format!("\n{}", line));
+            }
+            reply
+        }
+        Err(e) => format!("Error: {}", e),
+    }
+}
 
-    let mut graph = match load_graph(&graph_path) {
-        Ok(g) => g,
-        Err(e) => return format!("Error loading graph: {}", e),
+/// Execute done command. As with `wg done`, the task's output contract must
+/// be met, and the change propagates to the tasks after it.
+pub fn execute_done(workgraph_dir: &Path, task_id: &str, by: &str) -> String {
+    let (mut graph, graph_path) = match load_for_update(workgraph_dir) {
+        Ok(loaded) => loaded,
+        Err(e) => return e,
     };
 
-    let task = match graph.get_task_mut(task_id) {
+    let task = match graph.get_task(task_id) {
         Some(t) => t,
         None => return format!("Error: Task '{}' not found", task_id),
     };
@@ -386,18 +1002,69 @@ pub fn execute_done(workgraph_dir: &Path, task_id: &str) -> String {
         return format!("Task '{}' is already done", task_id);
     }
 
-    task.status = Status::Done;
-    task.completed_at = Some(Utc::now().to_rfc3339());
+    if task.gate.is_some() {
+        return format!(
+            "Task '{}' is an approval gate. Reply `approve {}` or `reject {} <reason>`.",
+            task_id, task_id, task_id
+        );
+    }
+
+    let report = crate::contract::check(workgraph_dir, task);
+    if !report.is_met() {
+        return format!(
+            "Cannot mark '{}' as done: its output contract is not met: {}",
+            task_id,
+            report.problems.join("; ")
+        );
+    }
+
+    if let Some(task) = graph.get_task_mut(task_id) {
+        task.status = Status::Done;
+        task.completed_at = Some(Utc::now().to_rfc3339());
+        task.log.push(LogEntry {
+            timestamp: Utc::now().to_rfc3339(),
+            actor: Some(by.to_string()),
+            message: "Task marked as done".to_string(),
+        });
+    }
+    let cycle_analysis = graph.compute_cycle_analysis();
+    let cycle_decision =
+        evaluate_cycle_iteration_in(&mut graph, task_id, &cycle_analysis, Some(workgraph_dir));
+    let propagation = crate::propagation::propagate(&mut graph, workgraph_dir);
 
     if let Err(e) = save_graph(&graph, &graph_path) {
         return format!("Error saving graph: {}", e);
     }
+    crate::service::ipc::notify_graph_changed(workgraph_dir);
+    record(
+        workgraph_dir,
+        "done",
+        Some(task_id),
+        by,
+        serde_json::Value::Null,
+    );
+    if let Some(ref decision) = cycle_decision {
+        record(
+            workgraph_dir,
+            "cycle_iteration",
+            Some(&decision.cycle),
+            by,
+            serde_json::to_value(decision).unwrap_or_default(),
+        );
+    }
+    if let Some(task) = graph.get_task(task_id) {
+        let _ = crate::contract::store_result(workgraph_dir, task, &report);
+    }
 
-    format!("Marked '{}' as done", task_id)
+    let mut response = format!("Marked '{}' as done", task_id);
+    for line in propagation.lines() {
+        response.push_str(&format!("\n{}", line));
+    }
+    response
 }
 
 /// Execute fail command
-pub fn execute_fail(workgraph_dir: &Path, task_id: &str, reason: Option<&str>) -> String {
+pub fn execute_fail(workgraph_dir: &Path, task_id: &str, reason: Option<&str>, by: &str) -> String {
     let graph_path = workgraph_dir.join("graph.jsonl");
 
     if !graph_path.exists() {
@@ -434,6 +1101,13 @@ pub fn execute_fail(workgraph_dir: &Path, task_id: &str, reason: Option<&str>) -
     if let Err(e) = save_graph(&graph, &graph_path) {
         return format!("Error saving graph: {}", e);
     }
+    record(
+        workgraph_dir,
+        "fail",
+        Some(task_id),
+        by,
+        serde_json::json!({ "reason": reason }),
+    );
 
     let reason_msg = reason.map(|r| format!(" ({})", r)).unwrap_or_default();
     format!(
@@ -444,6 +1118,80 @@ pub fn execute_fail(workgraph_dir: &Path, task_id: &str, reason: Option<&str>) -
 
 /// Execute input/log command
 pub fn execute_input(workgraph_dir: &Path, task_id: &str, text: &str, actor: &str) -> String {
+    match append_log(workgraph_dir, task_id, text, actor) {
+        Ok(_) => format!("Added log entry to '{}' from {}", task_id, actor),
+        Err(e) => e,
+    }
+}
+
+/// Append a reply written in a task's Matrix thread to the task log, as the
+/// sender's actor and subject to the ACL like `input`. Returns the new log
+/// length, so the listener doesn't post the reply back into the thread.
+pub fn append_thread_reply(
+    workgraph_dir: &Path,
+    task_id: &str,
+    text: &str,
+    sender: &str,
+) -> Result<usize, String> {
+    let acl = Config::load_or_default(workgraph_dir).matrix;
+    let principal = Principal::resolve(&acl, sender);
+    let command = MatrixCommand::Input {
+        task_id: task_id.to_string(),
+        text: text.to_string(),
+    };
+    authorize(workgraph_dir, &principal, &command)?;
+    let by = principal.actor.as_str();
+    crate::history::with_actor(by, || append_log(workgraph_dir, task_id, text, by))
+}
+
+/// Add a log entry to a task and record it, returning the new log length.
+fn append_log(
+    workgraph_dir: &Path,
+    task_id: &str,
+    text: &str,
+    actor: &str,
+) -> Result<usize, String> {
+    let (mut graph, graph_path) = load_for_update(workgraph_dir)?;
+
+    let task = match graph.get_task_mut(task_id) {
+        Some(t) => t,
+        None => return Err(format!("Error: Task '{}' not found", task_id)),
+    };
+
+    task.log.push(LogEntry {
+        timestamp: Utc::now().to_rfc3339(),
+        actor: Some(actor.to_string()),
+        message: text.to_string(),
+    });
+    let len = task.log.len();
+
+    if let Err(e) = save_graph(&graph, &graph_path) {
+        return Err(format!("Error saving graph: {}", e));
+    }
+    record(
+        workgraph_dir,
+        "log",
+        Some(task_id),
+        actor,
+        serde_json::json!({ "message": text }),
+    );
+    Ok(len)
+}
+
+/// Load the graph for a command, as an error reply if that fails.
+fn load_for_update(workgraph_dir: &Path) -> Result<(WorkGraph, PathBuf), String> {
+    let graph_path = workgraph_dir.join("graph.jsonl");
+    if !graph_path.exists() {
+        return Err("Error: Workgraph not initialized".to_string());
+    }
+    match load_graph(&graph_path) {
+        Ok(graph) => Ok((graph, graph_path)),
+        Err(e) => Err(format!("Error loading graph: {}", e)),
+    }
+}
+
+/// Execute unclaim command
+pub fn execute_unclaim(workgraph_dir: &Path, task_id: &str, by: &str) -> String {
     let graph_path = workgraph_dir.join("graph.jsonl");
 
     if !graph_path.exists() {
@@ -460,47 +1208,490 @@ pub fn execute_input(workgraph_dir: &Path, task_id: &str, text: &str, actor: &st
         None => return format!("Error: Task '{}' not found", task_id),
     };
 
-    let entry = LogEntry {
-        timestamp: Utc::now().to_rfc3339(),
-        actor: Some(actor.to_string()),
-        message: text.to_string(),
+    let prev_assigned = task.assigned.take();
+    task.status = Status::Open;
+
+    if let Err(e) = save_graph(&graph, &graph_path) {
+        return format!("Error saving graph: {}", e);
+    }
+    record(
+        workgraph_dir,
+        "unclaim",
+        Some(task_id),
+        by,
+        serde_json::json!({ "prev_assigned": prev_assigned }),
+    );
+
+    format!("Unclaimed '{}'", task_id)
+}
+
+/// Execute add command
+pub fn execute_add(
+    workgraph_dir: &Path,
+    title: &str,
+    after: &[String],
+    skills: &[String],
+    tags: &[String],
+    by: &str,
+) -> String {
+    let (mut graph, graph_path) = match load_for_update(workgraph_dir) {
+        Ok(loaded) => loaded,
+        Err(e) => return e,
     };
 
-    task.log.push(entry);
+    // Unlike `wg add`, refuse unknown dependencies: in chat they are
+    // nearly always typos.
+    let missing: Vec<&str> = after
+        .iter()
+        .filter(|dep| {
+            crate::federation::parse_remote_ref(dep).is_none() && graph.get_node(dep).is_none()
+        })
+        .map(String::as_str)
+        .collect();
+    if !missing.is_empty() {
+        return format!("Error: unknown task(s) in after: {}", missing.join(", "));
+    }
+
+    let task_id = crate::graph::generate_id(title, &graph);
+    graph.add_node(Node::Task(Task {
+        id: task_id.clone(),
+        title: title.to_string(),
+        after: after.to_vec(),
+        skills: skills.to_vec(),
+        tags: tags.to_vec(),
+        requester: Some(by.to_string()),
+        created_at: Some(Utc::now().to_rfc3339()),
+        visibility: "internal".to_string(),
+        ..Task::default()
+    }));
+    for dep in after {
+        if let Some(blocker) = graph.get_task_mut(dep)
+            && !blocker.before.contains(&task_id)
+        {
+            blocker.before.push(task_id.clone());
+        }
+    }
 
     if let Err(e) = save_graph(&graph, &graph_path) {
         return format!("Error saving graph: {}", e);
     }
+    record(
+        workgraph_dir,
+        "add_task",
+        Some(&task_id),
+        by,
+        serde_json::json!({ "title": title }),
+    );
+
+    let mut response = format!("Added task '{}': {}", task_id, title);
+    if !after.is_empty() {
+        response.push_str(&format!(" (after {})", after.join(", ")));
+    }
+    response
+}
+
+/// Execute edit command
+pub fn execute_edit(workgraph_dir: &Path, task_id: &str, edits: &TaskEdits, by: &str) -> String {
+    let (mut graph, graph_path) = match load_for_update(workgraph_dir) {
+        Ok(loaded) => loaded,
+        Err(e) => return e,
+    };
+    if graph.get_task(task_id).is_none() {
+        return format!("Error: Task '{}' not found", task_id);
+    }
+    // As with `add`, unknown dependencies are nearly always typos.
+    let missing: Vec<&str> = edits
+        .add_after
+        .iter()
+        .filter(|dep| {
+            crate::federation::parse_remote_ref(dep).is_none() && graph.get_node(dep).is_none()
+        })
+        .map(String::as_str)
+        .collect();
+    if !missing.is_empty() {
+        return format!("Error: unknown task(s) in after: {}", missing.join(", "));
+    }
+    if edits.add_after.iter().any(|dep| dep == task_id) {
+        return format!("Error: Task '{}' cannot come after itself", task_id);
+    }
+
+    let task = graph.get_task_mut(task_id).expect("task exists");
+    let mut fields = Vec::new();
+    if let Some(title) = &edits.title
+        && *title != task.title
+    {
+        fields.push(serde_json::json!({"field": "title", "old": task.title, "new": title}));
+        task.title = title.clone();
+    }
+    let lists = [
+        (
+            "after",
+            &mut task.after,
+            &edits.add_after,
+            &edits.remove_after,
+        ),
+        (
+            "skills",
+            &mut task.skills,
+            &edits.add_skills,
+            &edits.remove_skills,
+        ),
+        ("tags", &mut task.tags, &edits.add_tags, &edits.remove_tags),
+    ];
+    for (field, list, add, remove) in lists {
+        let old = list.clone();
+        list.retain(|item| !remove.contains(item));
+        for item in add {
+            if !list.contains(item) {
+                list.push(item.clone());
+            }
+        }
+        if *list != old {
+            fields.push(serde_json::json!({"field": field, "old": old, "new": list}));
+        }
+    }
+    if fields.is_empty() {
+        return format!("No changes made to task '{}'", task_id);
+    }
+
+    for dep in &edits.add_after {
+        if let Some(blocker) = graph.get_task_mut(dep)
+            && !blocker.before.iter().any(|b| b == task_id)
+        {
+            blocker.before.push(task_id.to_string());
+        }
+    }
+    for dep in &edits.remove_after {
+        if let Some(blocker) = graph.get_task_mut(dep) {
+            blocker.before.retain(|b| b != task_id);
+        }
+    }
 
-    format!("Added log entry to '{}' from {}", task_id, actor)
+    if let Err(e) = save_graph(&graph, &graph_path) {
+        return format!("Error saving graph: {}", e);
+    }
+    let changed: Vec<&str> = fields.iter().filter_map(|f| f["field"].as_str()).collect();
+    let response = format!("Updated '{}': {}", task_id, changed.join(", "));
+    record(
+        workgraph_dir,
+        "edit",
+        Some(task_id),
+        by,
+        serde_json::json!({ "fields": fields }),
+    );
+    response
 }
 
-/// Execute unclaim command
-pub fn execute_unclaim(workgraph_dir: &Path, task_id: &str) -> String {
-    let graph_path = workgraph_dir.join("graph.jsonl");
+/// Execute show command
+pub fn execute_show(workgraph_dir: &Path, task_id: &str) -> String {
+    let (graph, _) = match load_for_update(workgraph_dir) {
+        Ok(loaded) => loaded,
+        Err(e) => return e,
+    };
+    let task = match graph.get_task(task_id) {
+        Some(t) => t,
+        None => return format!("Error: Task '{}' not found", task_id),
+    };
 
-    if !graph_path.exists() {
-        return "Error: Workgraph not initialized".to_string();
+    let mut response = format!("**{}**: {}\n• Status: {}", task.id, task.title, task.status);
+    if task.paused {
+        response.push_str(" (paused)");
+    }
+    if let Some(assigned) = &task.assigned {
+        response.push_str(&format!("\n• Assigned: {}", assigned));
+    }
+    if !task.after.is_empty() {
+        let deps: Vec<String> = task
+            .after
+            .iter()
+            .map(|dep| match graph.get_task(dep) {
+                Some(d) => format!("{} ({})", dep, d.status),
+                None => dep.clone(),
+            })
+            .collect();
+        response.push_str(&format!("\n• After: {}", deps.join(", ")));
+    }
+    if !task.skills.is_empty() {
+        response.push_str(&format!("\n• Skills: {}", task.skills.join(", ")));
     }
+    if !task.tags.is_empty() {
+        response.push_str(&format!("\n• Tags: {}", task.tags.join(", ")));
+    }
+    if let Some(reason) = &task.failure_reason {
+        response.push_str(&format!("\n• Failure: {}", reason));
+    }
+    if let Some(description) = task.description.as_deref().filter(|d| !d.is_empty()) {
+        response.push_str(&format!("\n\n{}", description));
+    }
+    let recent = task.log.len().saturating_sub(3);
+    if recent < task.log.len() {
+        response.push_str("\n\n**Recent log**");
+        for entry in &task.log[recent..] {
+            response.push_str(&format!(
+                "\n• {}: {}",
+                entry.actor.as_deref().unwrap_or("-"),
+                entry.message
+            ));
+        }
+    }
+    response
+}
 
-    let mut graph = match load_graph(&graph_path) {
-        Ok(g) => g,
-        Err(e) => return format!("Error loading graph: {}", e),
+/// Execute why command: what is keeping a task from being ready
+pub fn execute_why(workgraph_dir: &Path, task_id: &str) -> String {
+    let (graph, _) = match load_for_update(workgraph_dir) {
+        Ok(loaded) => loaded,
+        Err(e) => return e,
     };
+    let task = match graph.get_task(task_id) {
+        Some(t) => t,
+        None => return format!("Error: Task '{}' not found", task_id),
+    };
+    if !matches!(task.status, Status::Open | Status::Blocked) {
+        return format!(
+            "Task '{}' is {}, not waiting to start",
+            task_id, task.status
+        );
+    }
+
+    let mut reasons = Vec::new();
+    if task.paused {
+        reasons.push(format!("it is paused (`resume {}` to unpause)", task_id));
+    }
+    if !crate::query::is_time_ready(task) {
+        let until = task.not_before.as_ref().or(task.ready_after.as_ref());
+        reasons.push(format!(
+            "it is scheduled for later ({})",
+            until.map(String::as_str).unwrap_or("unknown")
+        ));
+    }
+    let waiting = unsatisfied_deps(&graph, task, workgraph_dir);
+    if !waiting.is_empty() {
+        let deps: Vec<String> = waiting.iter().map(|d| describe_dep(&graph, d)).collect();
+        reasons.push(format!("it waits for {}", deps.join(", ")));
+
+        // Root causes: unfinished tasks further up that wait on nothing
+        let mut roots = Vec::new();
+        let mut seen = HashSet::new();
+        let mut stack = waiting;
+        while let Some(id) = stack.pop() {
+            if !seen.insert(id.clone()) {
+                continue;
+            }
+            let Some(dep) = graph.get_task(&id) else {
+                continue;
+            };
+            let upstream = unsatisfied_deps(&graph, dep, workgraph_dir);
+            if upstream.is_empty() {
+                roots.push(id);
+            } else {
+                stack.extend(upstream);
+            }
+        }
+        roots.sort();
+        if roots.iter().any(|r| !task.after.contains(r)) {
+            let roots: Vec<String> = roots.iter().map(|r| describe_dep(&graph, r)).collect();
+            reasons.push(format!("the chain starts at {}", roots.join(", ")));
+        }
+    }
+    for wait in crate::capacity::Capacity::of(&graph).waits(task) {
+        reasons.push(format!("it needs resource {}", wait));
+    }
+
+    if reasons.is_empty() {
+        return format!("Task '{}' is ready to work on", task_id);
+    }
+    let mut response = format!("**Why '{}' isn't ready**", task_id);
+    for reason in reasons {
+        response.push_str(&format!("\n• {}", reason));
+    }
+    response
+}
+
+/// IDs of `task`'s after dependencies that don't yet allow it to start.
+fn unsatisfied_deps(graph: &WorkGraph, task: &Task, workgraph_dir: &Path) -> Vec<String> {
+    task.after
+        .iter()
+        .filter(|dep| {
+            let condition = task
+                .after_conditions
+                .get(*dep)
+                .copied()
+                .unwrap_or(EdgeCondition::Any);
+            !crate::query::is_blocker_satisfied(dep, condition, graph, Some(workgraph_dir))
+        })
+        .cloned()
+        .collect()
+}
 
+fn describe_dep(graph: &WorkGraph, id: &str) -> String {
+    match graph.get_task(id) {
+        Some(dep) => match &dep.assigned {
+            Some(who) => format!("`{}` ({}, {})", id, dep.status, who),
+            None => format!("`{}` ({})", id, dep.status),
+        },
+        None => format!("`{}`", id),
+    }
+}
+
+/// Execute retry command
+pub fn execute_retry(workgraph_dir: &Path, task_id: &str, by: &str) -> String {
+    let (mut graph, graph_path) = match load_for_update(workgraph_dir) {
+        Ok(loaded) => loaded,
+        Err(e) => return e,
+    };
     let task = match graph.get_task_mut(task_id) {
         Some(t) => t,
         None => return format!("Error: Task '{}' not found", task_id),
     };
 
+    if task.status != Status::Failed {
+        return format!(
+            "Task '{}' is {}; only failed tasks can be retried",
+            task_id, task.status
+        );
+    }
+    if let Some(max) = task.max_retries
+        && task.retry_count >= max
+    {
+        return format!(
+            "Task '{}' has reached max retries ({}/{})",
+            task_id, task.retry_count, max
+        );
+    }
+
+    let prev_failure_reason = task.failure_reason.take();
+    let attempt = task.retry_count + 1;
     task.status = Status::Open;
     task.assigned = None;
+    task.tags.retain(|t| t != "converged");
+    if let Some(gate) = task.gate.as_mut() {
+        gate.votes.clear();
+        gate.requested_at = None;
+    }
+    task.log.push(LogEntry {
+        timestamp: Utc::now().to_rfc3339(),
+        actor: Some(by.to_string()),
+        message: format!("Task reset for retry (attempt #{})", attempt),
+    });
 
     if let Err(e) = save_graph(&graph, &graph_path) {
         return format!("Error saving graph: {}", e);
     }
+    record(
+        workgraph_dir,
+        "retry",
+        Some(task_id),
+        by,
+        serde_json::json!({ "attempt": attempt, "prev_failure_reason": prev_failure_reason }),
+    );
+    format!("Reset '{}' for retry (attempt #{})", task_id, attempt)
+}
 
-    format!("Unclaimed '{}'", task_id)
+/// Execute pause/resume for one task
+pub fn execute_pause_task(workgraph_dir: &Path, task_id: &str, pause: bool, by: &str) -> String {
+    let (mut graph, graph_path) = match load_for_update(workgraph_dir) {
+        Ok(loaded) => loaded,
+        Err(e) => return e,
+    };
+    let task = match graph.get_task_mut(task_id) {
+        Some(t) => t,
+        None => return format!("Error: Task '{}' not found", task_id),
+    };
+
+    let (op, done) = if pause {
+        ("pause", "paused")
+    } else {
+        ("resume", "resumed")
+    };
+    if task.paused == pause {
+        return format!(
+            "Task '{}' is {}",
+            task_id,
+            if pause {
+                "already paused"
+            } else {
+                "not paused"
+            }
+        );
+    }
+    task.paused = pause;
+    task.log.push(LogEntry {
+        timestamp: Utc::now().to_rfc3339(),
+        actor: Some(by.to_string()),
+        message: format!("Task {}", done),
+    });
+
+    if let Err(e) = save_graph(&graph, &graph_path) {
+        return format!("Error saving graph: {}", e);
+    }
+    record(workgraph_dir, op, Some(task_id), by, serde_json::json!({}));
+    let verb = if pause { "Paused" } else { "Resumed" };
+    format!("{} '{}'", verb, task_id)
+}
+
+/// Execute pause/resume for the coordinator, via the service daemon
+pub fn execute_pause_service(workgraph_dir: &Path, pause: bool, by: &str) -> String {
+    let cmd = if pause { "pause" } else { "resume" };
+    if let Err(e) = crate::service::ipc::request(workgraph_dir, &serde_json::json!({ "cmd": cmd }))
+    {
+        return format!("Error: {}", e);
+    }
+    record(
+        workgraph_dir,
+        &format!("service_{}", cmd),
+        None,
+        by,
+        serde_json::Value::Null,
+    );
+    if pause {
+        "Coordinator paused (running agents continue, no new spawns)".to_string()
+    } else {
+        "Coordinator resumed".to_string()
+    }
+}
+
+/// Execute agents command
+pub fn execute_agents(workgraph_dir: &Path) -> String {
+    let registry = crate::service::AgentRegistry::load_or_warn(workgraph_dir);
+    let mut agents = registry.list_alive_agents();
+    if agents.is_empty() {
+        return "No agents running".to_string();
+    }
+    agents.sort_by(|a, b| a.id.cmp(&b.id));
+    let mut response = format!("**Running Agents** ({})", agents.len());
+    for agent in agents {
+        response.push_str(&format!(
+            "\n• {} on `{}` ({}, {})",
+            agent.id,
+            agent.task_id,
+            agent.executor,
+            agent.uptime_human()
+        ));
+    }
+    response
+}
+
+/// Execute kill command, via the service daemon
+pub fn execute_kill(workgraph_dir: &Path, agent_id: &str, by: &str) -> String {
+    let task_id = crate::service::AgentRegistry::load_or_warn(workgraph_dir)
+        .get_agent(agent_id)
+        .map(|a| a.task_id.clone());
+    let request = serde_json::json!({ "cmd": "kill", "agent_id": agent_id, "force": false });
+    if let Err(e) = crate::service::ipc::request(workgraph_dir, &request) {
+        return format!("Error: {}", e);
+    }
+    record(
+        workgraph_dir,
+        "kill",
+        task_id.as_deref(),
+        by,
+        serde_json::json!({ "agent": agent_id }),
+    );
+    match task_id {
+        Some(task_id) => format!("Killed {} (task '{}' unclaimed)", agent_id, task_id),
+        None => format!("Killed {}", agent_id),
+    }
 }
 
 /// Execute status command
@@ -687,6 +1878,31 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_parse_approve() {
+        let cmd = MatrixCommand::parse("approve release-gate").unwrap();
+        assert_eq!(
+            cmd,
+            MatrixCommand::Approve {
+                task_id: "release-gate".to_string()
+            }
+        );
+    }
+
+    #[test]
+    fn test_parse_reject_with_reason() {
+        let cmd = MatrixCommand::parse("reject release-gate changelog is missing").unwrap();
+        assert_eq!(
+            cmd,
+            MatrixCommand::Reject {
+                task_id: "release-gate".to_string(),
+                reason: Some("changelog is missing".to_string())
+            }
+        );
+        let cmd = MatrixCommand::parse("reject").unwrap();
+        assert!(matches!(cmd, MatrixCommand::Unknown { .. }));
+    }
+
     #[test]
     fn test_parse_status() {
         let cmd = MatrixCommand::parse("status").unwrap();
@@ -783,4 +1999,474 @@ mod tests {
         assert_eq!(extract_localpart("plainuser"), "plainuser");
         assert_eq!(extract_localpart("@bot:matrix.org"), "bot");
     }
+
+    fn acl_dir() -> tempfile::TempDir {
+        use crate::graph::{Node, Task, WorkGraph};
+        let dir = tempfile::tempdir().unwrap();
+        let mut graph = WorkGraph::new();
+        for (id, tags, assigned) in [
+            ("ui", vec!["frontend"], None),
+            ("api", vec!["backend"], Some("bob")),
+            ("mine", vec!["frontend"], Some("erik")),
+        ] {
+            graph.add_node(Node::Task(Task {
+                id: id.into(),
+                title: id.into(),
+                status: if assigned.is_some() {
+                    Status::InProgress
+                } else {
+                    Status::Open
+                },
+                assigned: assigned.map(String::from),
+                tags: tags.into_iter().map(String::from).collect(),
+                ..Task::default()
+            }));
+        }
+        save_graph(&graph, dir.path().join("graph.jsonl")).unwrap();
+        std::fs::write(
+            dir.path().join("config.toml"),
+            r#"
+[[matrix.users]]
+user = "@erik:example.org"
+permission = "claim-own"
+
+[[matrix.users]]
+user = "@dana:example.org"
+actor = "dana-k"
+permission = "complete"
+tags = ["frontend"]
+"#,
+        )
+        .unwrap();
+        dir
+    }
+
+    #[test]
+    fn test_principal_resolution() {
+        let open = MatrixAclConfig::default();
+        let p = Principal::resolve(&open, "@erik:example.org");
+        assert_eq!(p.actor, "erik");
+        assert_eq!(p.permission, MatrixPermission::Admin);
+
+        let dir = acl_dir();
+        let acl = Config::load(dir.path()).unwrap().matrix;
+        let p = Principal::resolve(&acl, "@Dana:example.org");
+        assert_eq!(p.actor, "dana-k");
+        assert_eq!(p.permission, MatrixPermission::Complete);
+        // An unlisted namesake on another server is not erik.
+        let p = Principal::resolve(&acl, "@erik:evil.example");
+        assert_eq!(p.actor, "@erik:evil.example");
+        assert_eq!(p.permission, MatrixPermission::ReadOnly);
+    }
+
+    #[test]
+    fn test_authorize_permissions_and_scope() {
+        let dir = acl_dir();
+        let acl = Config::load(dir.path()).unwrap().matrix;
+        let erik = Principal::resolve(&acl, "@erik:example.org");
+        let dana = Principal::resolve(&acl, "@dana:example.org");
+        let stranger = Principal::resolve(&acl, "@mallory:example.org");
+        let check = |p: &Principal, msg: &str| {
+            authorize(dir.path(), p, &MatrixCommand::parse(msg).unwrap()).is_ok()
+        };
+
+        assert!(check(&stranger, "status"));
+        assert!(!check(&stranger, "claim ui"));
+        assert!(check(&erik, "claim ui"));
+        assert!(!check(&erik, "claim ui as bob"));
+        assert!(check(&erik, "done mine"));
+        assert!(!check(&erik, "done api"));
+        assert!(!check(&erik, "unclaim api"));
+        assert!(check(&dana, "done ui"));
+        assert!(!check(&dana, "done api"), "api is outside dana's tags");
+        assert!(!check(&dana, "unclaim mine"));
+    }
+
+    #[test]
+    fn test_execute_command_refuses_and_attributes() {
+        let dir = acl_dir();
+        let reply = execute_command(
+            dir.path(),
+            &MatrixCommand::parse("done api").unwrap(),
+            "@erik:example.org",
+        );
+        assert!(reply.starts_with("Sorry, erik"), "{}", reply);
+        let graph = load_graph(dir.path().join("graph.jsonl")).unwrap();
+        assert_eq!(graph.get_task("api").unwrap().status, Status::InProgress);
+
+        let reply = execute_command(
+            dir.path(),
+            &MatrixCommand::parse("done ui").unwrap(),
+            "@dana:example.org",
+        );
+        assert_eq!(reply, "Marked 'ui' as done");
+        let ops = crate::provenance::read_all_operations(dir.path()).unwrap();
+        let op = ops.last().unwrap();
+        assert_eq!(op.op, "done");
+        assert_eq!(op.actor.as_deref(), Some("dana-k"));
+        let history = crate::history::read_all(dir.path()).unwrap();
+        assert_eq!(history.last().unwrap().actor.as_deref(), Some("dana-k"));
+    }
+
+    #[test]
+    fn test_votes_go_through_acl() {
+        let dir = acl_dir();
+        let path = dir.path().join("graph.jsonl");
+        let mut graph = load_graph(&path).unwrap();
+        graph.add_node(Node::Task(Task {
+            id: "release".into(),
+            title: "Release".into(),
+            tags: vec!["frontend".into()],
+            gate: Some(crate::graph::Gate {
+                approvers: vec!["erik".into(), "dana-k".into(), "mallory".into()],
+                quorum: 2,
+                votes: Vec::new(),
+                requested_at: None,
+            }),
+            ..Task::default()
+        }));
+        save_graph(&graph, &path).unwrap();
+        let vote = |msg: &str, sender: &str| {
+            execute_command(dir.path(), &MatrixCommand::parse(msg).unwrap(), sender)
+        };
+
+        // Read-only senders can't vote, even if the gate names them.
+        let reply = vote("approve release", "@mallory:example.org");
+        assert!(reply.starts_with("Sorry"), "{}", reply);
+        let reply = vote("approve release", "@erik:evil.example");
+        assert!(reply.starts_with("Sorry"), "{}", reply);
+
+        vote("approve release", "@erik:example.org");
+        vote("approve release", "@dana:example.org");
+        let graph = load_graph(&path).unwrap();
+        let release = graph.get_task("release").unwrap();
+        let voters: Vec<&str> = release
+            .gate
+            .as_ref()
+            .unwrap()
+            .votes
+            .iter()
+            .map(|v| v.actor.as_str())
+            .collect();
+        assert_eq!(voters, ["erik", "dana-k"]);
+        assert_eq!(release.status, Status::Done);
+    }
+
+    #[test]
+    fn test_parse_add_with_options() {
+        let cmd = MatrixCommand::parse(
+            "wg add \u{201c}Fix login: after redirect\u{201d} after:auth,db skill:rust tag:web",
+        )
+        .unwrap();
+        assert_eq!(
+            cmd,
+            MatrixCommand::Add {
+                title: "Fix login: after redirect".to_string(),
+                after: vec!["auth".to_string(), "db".to_string()],
+                skills: vec!["rust".to_string()],
+                tags: vec!["web".to_string()],
+            }
+        );
+        let cmd = MatrixCommand::parse("add Write docs").unwrap();
+        assert!(matches!(cmd, MatrixCommand::Add { ref title, .. } if title == "Write docs"));
+        let cmd = MatrixCommand::parse("add after:x").unwrap();
+        assert!(matches!(cmd, MatrixCommand::Unknown { .. }));
+    }
+
+    #[test]
+    fn test_parse_edit() {
+        let cmd =
+            MatrixCommand::parse("wg edit ui \"Polish UI\" after:api -after:db -tag:old,stale")
+                .unwrap();
+        assert_eq!(
+            cmd,
+            MatrixCommand::Edit {
+                task_id: "ui".to_string(),
+                edits: TaskEdits {
+                    title: Some("Polish UI".to_string()),
+                    add_after: vec!["api".to_string()],
+                    remove_after: vec!["db".to_string()],
+                    remove_tags: vec!["old".to_string(), "stale".to_string()],
+                    ..TaskEdits::default()
+                },
+            }
+        );
+        let cmd = MatrixCommand::parse("edit ui skill:css").unwrap();
+        assert!(matches!(cmd, MatrixCommand::Edit { ref edits, .. } if edits.title.is_none()));
+        assert!(matches!(
+            MatrixCommand::parse("edit ui").unwrap(),
+            MatrixCommand::Unknown { .. }
+        ));
+    }
+
+    #[test]
+    fn test_parse_service_and_agent_commands() {
+        assert_eq!(
+            MatrixCommand::parse("pause").unwrap(),
+            MatrixCommand::Pause { task_id: None }
+        );
+        assert_eq!(
+            MatrixCommand::parse("resume task-1").unwrap(),
+            MatrixCommand::Resume {
+                task_id: Some("task-1".to_string())
+            }
+        );
+        assert_eq!(
+            MatrixCommand::parse("kill agent-3").unwrap(),
+            MatrixCommand::Kill {
+                agent_id: "agent-3".to_string()
+            }
+        );
+        assert_eq!(
+            MatrixCommand::parse("agents").unwrap(),
+            MatrixCommand::Agents
+        );
+        assert!(matches!(
+            MatrixCommand::parse("why").unwrap(),
+            MatrixCommand::Unknown { .. }
+        ));
+    }
+
+    #[test]
+    fn test_parse_in_thread() {
+        assert_eq!(
+            MatrixCommand::parse_in_thread("done with the tests", "t1"),
+            None
+        );
+        assert_eq!(
+            MatrixCommand::parse_in_thread("wg done", "t1"),
+            Some(MatrixCommand::Done {
+                task_id: "t1".to_string()
+            })
+        );
+        assert_eq!(
+            MatrixCommand::parse_in_thread("!wg show other", "t1"),
+            Some(MatrixCommand::Show {
+                task_id: "other".to_string()
+            })
+        );
+    }
+
+    #[test]
+    fn test_authorize_new_commands() {
+        let dir = acl_dir();
+        let acl = Config::load(dir.path()).unwrap().matrix;
+        let erik = Principal::resolve(&acl, "@erik:example.org");
+        let dana = Principal::resolve(&acl, "@dana:example.org");
+        let check = |p: &Principal, msg: &str| {
+            authorize(dir.path(), p, &MatrixCommand::parse(msg).unwrap()).is_ok()
+        };
+
+        assert!(check(&erik, "show api"));
+        assert!(check(&erik, "pause mine"));
+        assert!(!check(&erik, "pause"));
+        assert!(!check(&dana, "kill agent-1"));
+        assert!(!check(&erik, "add \"New thing\""));
+        assert!(!check(&dana, "add \"New thing\""), "dana must tag it");
+        assert!(check(&dana, "add \"New thing\" tag:frontend"));
+
+        assert!(
+            !check(&erik, "edit mine \"Renamed\""),
+            "edit needs complete"
+        );
+        assert!(check(&dana, "edit ui \"Renamed\" tag:backend"));
+        assert!(
+            !check(&dana, "edit api tag:frontend"),
+            "api is outside dana's tags"
+        );
+        assert!(
+            !check(&dana, "edit ui -tag:frontend"),
+            "ui would leave dana's tags"
+        );
+    }
+
+    #[test]
+    fn test_execute_add_show_why_retry() {
+        let dir = acl_dir();
+        let path = dir.path().join("graph.jsonl");
+
+        let reply = execute_add(
+            dir.path(),
+            "Polish the button",
+            &["ui".to_string()],
+            &["css".to_string()],
+            &["frontend".to_string()],
+            "dana-k",
+        );
+        assert_eq!(
+            reply,
+            "Added task 'polish-the-button': Polish the button (after ui)"
+        );
+        let graph = load_graph(&path).unwrap();
+        assert_eq!(graph.get_task("ui").unwrap().before, ["polish-the-button"]);
+        assert!(
+            execute_add(dir.path(), "X", &["nope".to_string()], &[], &[], "a").starts_with("Error")
+        );
+
+        let shown = execute_show(dir.path(), "polish-the-button");
+        assert!(shown.contains("After: ui (open)"), "{}", shown);
+        assert!(shown.contains("Skills: css"), "{}", shown);
+
+        let why = execute_why(dir.path(), "polish-the-button");
+        assert!(why.contains("it waits for `ui` (open)"), "{}", why);
+        assert_eq!(
+            execute_why(dir.path(), "ui"),
+            "Task 'ui' is ready to work on"
+        );
+
+        assert_eq!(
+            execute_fail(dir.path(), "api", Some("flaky"), "bob"),
+            "Marked 'api' as failed (flaky) (retry #1)"
+        );
+        assert_eq!(
+            execute_retry(dir.path(), "api", "bob"),
+            "Reset 'api' for retry (attempt #2)"
+        );
+        let task = load_graph(&path).unwrap().get_task("api").unwrap().clone();
+        assert_eq!(task.status, Status::Open);
+        assert_eq!(task.failure_reason, None);
+        assert!(execute_retry(dir.path(), "api", "bob").contains("only failed tasks"));
+    }
+
+    #[test]
+    fn test_execute_edit() {
+        let dir = acl_dir();
+        let path = dir.path().join("graph.jsonl");
+        let edit = |msg: &str| {
+            execute_command(
+                dir.path(),
+                &MatrixCommand::parse(msg).unwrap(),
+                "@dana:example.org",
+            )
+        };
+
+        assert_eq!(
+            edit("edit ui \"Polish the UI\" after:mine skill:css"),
+            "Updated 'ui': title, after, skills"
+        );
+        let graph = load_graph(&path).unwrap();
+        let ui = graph.get_task("ui").unwrap();
+        assert_eq!(ui.title, "Polish the UI");
+        assert_eq!(ui.after, ["mine"]);
+        assert_eq!(graph.get_task("mine").unwrap().before, ["ui"]);
+
+        assert_eq!(edit("edit ui -after:mine"), "Updated 'ui': after");
+        let graph = load_graph(&path).unwrap();
+        assert!(graph.get_task("ui").unwrap().after.is_empty());
+        assert!(graph.get_task("mine").unwrap().before.is_empty());
+
+        assert!(edit("edit ui after:nope").starts_with("Error: unknown task"));
+        assert_eq!(edit("edit ui skill:css"), "No changes made to task 'ui'");
+        let ops = crate::provenance::read_all_operations(dir.path()).unwrap();
+        let last = ops.iter().rfind(|op| op.op == "edit").unwrap();
+        assert_eq!(last.actor.as_deref(), Some("dana-k"));
+    }
+
+    #[test]
+    fn test_execute_done_checks_contract_and_propagates() {
+        use crate::graph::{Node, OutputContract, Task, WorkGraph};
+        let dir = tempfile::tempdir().unwrap();
+        let wg_dir = dir.path().join(".workgraph");
+        let mut graph = WorkGraph::new();
+        graph.add_node(Node::Task(Task {
+            id: "report".into(),
+            title: "Report".into(),
+            status: Status::InProgress,
+            contract: Some(OutputContract {
+                files: vec!["report-*.md".to_string()],
+                ..Default::default()
+            }),
+            ..Task::default()
+        }));
+        graph.add_node(Node::Task(Task {
+            id: "rollback".into(),
+            title: "Roll back".into(),
+            after: vec!["report".into()],
+            after_conditions: [("report".to_string(), EdgeCondition::Failure)].into(),
+            ..Task::default()
+        }));
+        std::fs::create_dir_all(&wg_dir).unwrap();
+        save_graph(&graph, wg_dir.join("graph.jsonl")).unwrap();
+
+        let reply = execute_done(&wg_dir, "report", "erik");
+        assert!(reply.contains("contract is not met"), "{}", reply);
+        assert!(reply.contains("report-*.md"), "{}", reply);
+        let graph = load_graph(wg_dir.join("graph.jsonl")).unwrap();
+        assert_eq!(graph.get_task("report").unwrap().status, Status::InProgress);
+
+        std::fs::write(dir.path().join("report-1.md"), "done").unwrap();
+        assert_eq!(
+            execute_done(&wg_dir, "report", "erik"),
+            "Marked 'report' as done\nCondition: abandoned 'rollback'"
+        );
+        let graph = load_graph(wg_dir.join("graph.jsonl")).unwrap();
+        assert_eq!(graph.get_task("report").unwrap().status, Status::Done);
+        assert_eq!(
+            graph.get_task("rollback").unwrap().status,
+            Status::Abandoned
+        );
+    }
+
+    #[test]
+    fn test_execute_vote_propagates_rejection() {
+        use crate::graph::{Gate, Node, Task, WorkGraph};
+        let dir = tempfile::tempdir().unwrap();
+        let mut graph = WorkGraph::new();
+        graph.add_node(Node::Task(Task {
+            id: "release".into(),
+            title: "Release".into(),
+            gate: Some(Gate {
+                approvers: vec!["erik".to_string()],
+                quorum: 1,
+                votes: vec![],
+                requested_at: None,
+            }),
+            ..Task::default()
+        }));
+        graph.add_node(Node::Task(Task {
+            id: "deploy".into(),
+            title: "Deploy".into(),
+            after: vec!["release".into()],
+            ..Task::default()
+        }));
+        graph.add_node(Node::Task(Task {
+            id: "announce".into(),
+            title: "Announce".into(),
+            after: vec!["deploy".into()],
+            after_conditions: [("deploy".to_string(), EdgeCondition::Success)].into(),
+            ..Task::default()
+        }));
+        save_graph(&graph, dir.path().join("graph.jsonl")).unwrap();
+
+        assert_eq!(
+            execute_vote(
+                dir.path(),
+                "release",
+                "erik",
+                "@erik:example.org",
+                false,
+                Some("not yet")
+            ),
+            "erik rejected 'release'; gate rejected, abandoned deploy\n\
+             Condition: abandoned 'announce'"
+        );
+        let graph = load_graph(dir.path().join("graph.jsonl")).unwrap();
+        assert_eq!(
+            graph.get_task("announce").unwrap().status,
+            Status::Abandoned
+        );
+    }
+
+    #[test]
+    fn test_thread_reply_is_logged_as_actor() {
+        let dir = acl_dir();
+        let len =
+            append_thread_reply(dir.path(), "mine", "halfway there", "@erik:example.org").unwrap();
+        let graph = load_graph(dir.path().join("graph.jsonl")).unwrap();
+        let entry = graph.get_task("mine").unwrap().log.last().unwrap().clone();
+        assert_eq!(len, 1);
+        assert_eq!(entry.actor.as_deref(), Some("erik"));
+        assert_eq!(entry.message, "halfway there");
+        assert!(append_thread_reply(dir.path(), "mine", "hi", "@mallory:example.org").is_err());
+    }
 }
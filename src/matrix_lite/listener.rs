@@ -3,6 +3,10 @@
 //! Background listener that processes commands from Matrix rooms using
 //! the lightweight reqwest-based client. Command execution logic is shared
 //! via `matrix_commands`.
+//!
+//! The listener also keeps each task's thread (see `matrix_threads`) up to
+//! date: new task log entries are posted into the thread, and replies in the
+//! thread are added to the task log.
 
 use std::collections::HashSet;
 use std::path::{Path, PathBuf};
@@ -11,6 +15,8 @@ use anyhow::{Context, Result};
 
 use crate::config::MatrixConfig;
 use crate::matrix_commands;
+use crate::matrix_threads::{self, LogCursor, ThreadStore};
+use crate::parser::load_graph;
 
 use super::commands::MatrixCommand;
 use super::{IncomingMessage, MatrixClient};
@@ -32,6 +38,9 @@ pub struct MatrixListener {
     workgraph_dir: PathBuf,
     config: ListenerConfig,
     allowed_rooms: HashSet<String>,
+    /// Room task threads are kept in: the default room, else the first
+    /// configured room
+    thread_room: Option<String>,
 }
 
 impl MatrixListener {
@@ -58,11 +67,17 @@ impl MatrixListener {
             allowed_rooms.insert(default_room.clone());
         }
 
+        let thread_room = matrix_config
+            .default_room
+            .clone()
+            .or_else(|| listener_config.rooms.first().cloned());
+
         Ok(Self {
             client,
             workgraph_dir: workgraph_dir.to_path_buf(),
             config: listener_config,
             allowed_rooms,
+            thread_room,
         })
     }
 
@@ -92,6 +107,11 @@ impl MatrixListener {
         // Join configured rooms
         self.join_rooms().await?;
 
+        // Seed the log cursor so existing history isn't replayed
+        if let Err(e) = self.forward_log_updates().await {
+            eprintln!("Warning: failed to forward task log updates: {}", e);
+        }
+
         println!("Matrix listener started (lite), waiting for messages...");
 
         loop {
@@ -102,6 +122,9 @@ impl MatrixListener {
                         eprintln!("Sync error: {}", e);
                         tokio::time::sleep(std::time::Duration::from_secs(5)).await;
                     }
+                    if let Err(e) = self.forward_log_updates().await {
+                        eprintln!("Warning: failed to forward task log updates: {}", e);
+                    }
                 }
 
                 // Process incoming messages
@@ -133,18 +156,75 @@ impl MatrixListener {
             return Ok(());
         }
 
-        // Parse the command
-        let command = match MatrixCommand::parse(&msg.body) {
-            Some(cmd) => cmd,
-            None => return Ok(()),
+        // In a task's thread, unprefixed messages are replies for its log
+        let thread_task = msg.thread_root.as_deref().and_then(|root| {
+            ThreadStore::load(&self.workgraph_dir)
+                .task_for_root(&msg.room_id, root)
+                .map(String::from)
+        });
+        let command = match &thread_task {
+            Some(task_id) => MatrixCommand::parse_in_thread(&msg.body, task_id),
+            None => MatrixCommand::parse(&msg.body),
+        };
+
+        let response = match (command, thread_task) {
+            // Execute via shared logic
+            (Some(command), _) => {
+                matrix_commands::execute_command(&self.workgraph_dir, &command, &msg.sender)
+            }
+            (None, Some(task_id)) => {
+                match matrix_commands::append_thread_reply(
+                    &self.workgraph_dir,
+                    &task_id,
+                    &msg.body,
+                    &msg.sender,
+                ) {
+                    Ok(len) => {
+                        let mut cursor = LogCursor::load(&self.workgraph_dir);
+                        cursor.skip_reply(&task_id, len);
+                        cursor.save(&self.workgraph_dir)?;
+                        return Ok(());
+                    }
+                    Err(refusal) => refusal,
+                }
+            }
+            (None, None) => return Ok(()),
         };
 
-        // Execute via shared logic
-        let response = matrix_commands::execute_command(&self.workgraph_dir, &command, &msg.sender);
+        // Send response back to room, in the thread it came from
+        self.client
+            .send_thread_message(&msg.room_id, msg.thread_root.as_deref(), &response)
+            .await?;
 
-        // Send response back to room
-        self.client.send_message(&msg.room_id, &response).await?;
+        Ok(())
+    }
 
+    /// Post task log entries added since the last check into each task's
+    /// thread. The cursor is saved before posting, so a failed post drops
+    /// those entries rather than repeating everything on the next attempt.
+    async fn forward_log_updates(&self) -> Result<()> {
+        let Some(room_id) = &self.thread_room else {
+            return Ok(());
+        };
+        let graph_path = self.workgraph_dir.join("graph.jsonl");
+        if !graph_path.exists() {
+            return Ok(());
+        }
+        let graph = load_graph(&graph_path)?;
+        let mut cursor = LogCursor::load(&self.workgraph_dir);
+        let updates = cursor.take_updates(&graph);
+        cursor.save(&self.workgraph_dir)?;
+
+        for (task, entries) in updates {
+            let root = self.client.task_thread(room_id, task).await?;
+            let text: Vec<String> = entries
+                .iter()
+                .map(matrix_threads::format_log_entry)
+                .collect();
+            self.client
+                .send_thread_message(room_id, Some(&root), &text.join("\n"))
+                .await?;
+        }
         Ok(())
     }
 }
@@ -4,6 +4,7 @@
 //! - Send messages (plain text and HTML)
 //! - Receive messages via sync long-polling
 //! - Join rooms
+//! - Post into per-task threads (see `matrix_threads`)
 //!
 //! No E2EE, no SQLite, no heavy dependencies.
 
@@ -19,6 +20,8 @@ use serde::{Deserialize, Serialize};
 use tokio::sync::mpsc;
 
 use crate::config::MatrixConfig;
+use crate::graph::Task;
+use crate::matrix_threads::{self, TaskThread, ThreadStore};
 
 /// State directory name within .workgraph
 const MATRIX_STATE_DIR: &str = "matrix";
@@ -34,6 +37,8 @@ pub struct IncomingMessage {
     pub body: String,
     /// Event ID
     pub event_id: String,
+    /// Root event of the thread the message was posted in, if any
+    pub thread_root: Option<String>,
     /// Whether the message is from the current user
     pub is_own: bool,
 }
@@ -283,15 +288,8 @@ impl MatrixClient {
 
     /// Send a text message to a room
     pub async fn send_message(&self, room_id: &str, message: &str) -> Result<()> {
-        self.send_event(
-            room_id,
-            "m.room.message",
-            serde_json::json!({
-                "msgtype": "m.text",
-                "body": message
-            }),
-        )
-        .await
+        self.send_thread_message(room_id, None, message).await?;
+        Ok(())
     }
 
     /// Send an HTML message to a room
@@ -301,26 +299,77 @@ impl MatrixClient {
         plain_text: &str,
         html: &str,
     ) -> Result<()> {
-        self.send_event(
-            room_id,
-            "m.room.message",
-            serde_json::json!({
-                "msgtype": "m.text",
-                "body": plain_text,
-                "format": "org.matrix.custom.html",
-                "formatted_body": html
-            }),
-        )
-        .await
+        self.send_html_thread_message(room_id, None, plain_text, html)
+            .await?;
+        Ok(())
+    }
+
+    /// Send a text message, in the thread rooted at `thread_root` if given.
+    /// Returns the new event's ID.
+    pub async fn send_thread_message(
+        &self,
+        room_id: &str,
+        thread_root: Option<&str>,
+        message: &str,
+    ) -> Result<String> {
+        let mut content = serde_json::json!({
+            "msgtype": "m.text",
+            "body": message
+        });
+        if let Some(root) = thread_root {
+            content["m.relates_to"] = matrix_threads::thread_relation(root);
+        }
+        self.send_event(room_id, "m.room.message", content).await
+    }
+
+    /// Send an HTML message, in the thread rooted at `thread_root` if given.
+    /// Returns the new event's ID.
+    pub async fn send_html_thread_message(
+        &self,
+        room_id: &str,
+        thread_root: Option<&str>,
+        plain_text: &str,
+        html: &str,
+    ) -> Result<String> {
+        let mut content = serde_json::json!({
+            "msgtype": "m.text",
+            "body": plain_text,
+            "format": "org.matrix.custom.html",
+            "formatted_body": html
+        });
+        if let Some(root) = thread_root {
+            content["m.relates_to"] = matrix_threads::thread_relation(root);
+        }
+        self.send_event(room_id, "m.room.message", content).await
     }
 
-    /// Send an event to a room
+    /// The root event of `task`'s thread in `room_id`, posting the root
+    /// message and recording the thread if it doesn't exist yet.
+    pub async fn task_thread(&self, room_id: &str, task: &Task) -> Result<String> {
+        if let Some(thread) = ThreadStore::load(&self.workgraph_dir).thread_for(&task.id, room_id) {
+            return Ok(thread.root_event_id.clone());
+        }
+        let root_event_id = self
+            .send_thread_message(room_id, None, &matrix_threads::root_message(task))
+            .await?;
+        ThreadStore::record(
+            &self.workgraph_dir,
+            &task.id,
+            TaskThread {
+                room_id: room_id.to_string(),
+                root_event_id: root_event_id.clone(),
+            },
+        )?;
+        Ok(root_event_id)
+    }
+
+    /// Send an event to a room, returning its event ID
     async fn send_event(
         &self,
         room_id: &str,
         event_type: &str,
         content: serde_json::Value,
-    ) -> Result<()> {
+    ) -> Result<String> {
         let txn_id = format!(
             "wg_{}",
             std::time::SystemTime::now()
@@ -351,7 +400,8 @@ impl MatrixClient {
             anyhow::bail!("Failed to send message: {} - {}", status, body);
         }
 
-        Ok(())
+        let sent: SendResponse = resp.json().await.context("Failed to parse send response")?;
+        Ok(sent.event_id)
     }
 
     /// Register a message handler and return a receiver
@@ -453,11 +503,16 @@ impl MatrixClient {
                                 continue;
                             }
 
+                            let thread_root = content
+                                .relates_to
+                                .filter(|r| r.rel_type.as_deref() == Some("m.thread"))
+                                .and_then(|r| r.event_id);
                             let msg = IncomingMessage {
                                 room_id: room_id.clone(),
                                 sender: event.sender,
                                 body,
                                 event_id: event.event_id.unwrap_or_default(),
+                                thread_root,
                                 is_own,
                             };
 
@@ -516,6 +571,19 @@ struct TimelineEvent {
 #[derive(Debug, Deserialize)]
 struct MessageContent {
     body: Option<String>,
+    #[serde(rename = "m.relates_to")]
+    relates_to: Option<Relation>,
+}
+
+#[derive(Debug, Deserialize)]
+struct Relation {
+    rel_type: Option<String>,
+    event_id: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct SendResponse {
+    event_id: String,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -569,6 +637,44 @@ pub async fn send_notification(workgraph_dir: &Path, message: &str) -> Result<()
     Ok(())
 }
 
+/// Send a notification about `task` into its thread (one-shot), starting
+/// the thread if needed. Goes to `room_id`, or the default room.
+pub async fn send_task_notification(
+    workgraph_dir: &Path,
+    room_id: Option<&str>,
+    task: &Task,
+    plain_text: &str,
+    html: Option<&str>,
+) -> Result<()> {
+    let config = MatrixConfig::load()?;
+
+    if !config.has_credentials() {
+        anyhow::bail!(
+            "Matrix not configured. Set homeserver, username, and token in ~/.config/workgraph/matrix.toml"
+        );
+    }
+    let room_id = room_id
+        .or(config.default_room.as_deref())
+        .ok_or_else(|| anyhow::anyhow!("default_room not configured"))?;
+
+    let client = MatrixClient::new(workgraph_dir, &config).await?;
+    let root = client.task_thread(room_id, task).await?;
+    match html {
+        Some(html) => {
+            client
+                .send_html_thread_message(room_id, Some(&root), plain_text, html)
+                .await?
+        }
+        None => {
+            client
+                .send_thread_message(room_id, Some(&root), plain_text)
+                .await?
+        }
+    };
+
+    Ok(())
+}
+
 /// Send a notification to Matrix with a specific room (one-shot)
 pub async fn send_notification_to_room(
     workgraph_dir: &Path,
@@ -600,6 +706,7 @@ mod tests {
             sender: "@user:example.com".to_string(),
             body: "Hello".to_string(),
             event_id: "$event".to_string(),
+            thread_root: None,
             is_own: false,
         };
         let cloned = msg.clone();
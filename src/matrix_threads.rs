@@ -0,0 +1,240 @@
+//! Per-task Matrix threads, shared by the Matrix clients
+//!
+//! Each task that has something to report on Matrix gets a thread: a root
+//! message in the room, with notifications and task log updates posted as
+//! replies to it. Replies people write in the thread are appended to the task
+//! log by the listener.
+//!
+//! Two files under `.workgraph/matrix/` hold the state:
+//! - `threads.json` maps task IDs to their thread's room and root event.
+//!   The listener, the coordinator and `wg notify` all add to it, so it is
+//!   re-read before every change rather than held in memory.
+//! - `log_cursor.json` records how many log entries of each task the
+//!   listener has already forwarded. Only the listener writes it.
+
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+
+use crate::graph::{LogEntry, Task, WorkGraph};
+
+/// A task's thread: the room it lives in and the event it hangs off.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TaskThread {
+    pub room_id: String,
+    pub root_event_id: String,
+}
+
+/// Task ID to thread mapping, stored in `threads.json`.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ThreadStore {
+    #[serde(default)]
+    pub threads: BTreeMap<String, TaskThread>,
+}
+
+impl ThreadStore {
+    pub fn path(workgraph_dir: &Path) -> PathBuf {
+        workgraph_dir.join("matrix").join("threads.json")
+    }
+
+    /// Load the store; a missing or unreadable file is treated as empty.
+    pub fn load(workgraph_dir: &Path) -> Self {
+        load_json(&Self::path(workgraph_dir))
+    }
+
+    /// The thread for `task_id` in `room_id`, if one has been started.
+    pub fn thread_for(&self, task_id: &str, room_id: &str) -> Option<&TaskThread> {
+        self.threads
+            .get(task_id)
+            .filter(|thread| thread.room_id == room_id)
+    }
+
+    /// The task whose thread hangs off `root_event_id` in `room_id`.
+    pub fn task_for_root(&self, room_id: &str, root_event_id: &str) -> Option<&str> {
+        self.threads
+            .iter()
+            .find(|(_, t)| t.room_id == room_id && t.root_event_id == root_event_id)
+            .map(|(id, _)| id.as_str())
+    }
+
+    /// Record a newly started thread, re-reading the file first so threads
+    /// started by other processes are kept.
+    pub fn record(workgraph_dir: &Path, task_id: &str, thread: TaskThread) -> Result<()> {
+        let mut store = Self::load(workgraph_dir);
+        store.threads.insert(task_id.to_string(), thread);
+        save_json(&Self::path(workgraph_dir), &store)
+    }
+}
+
+/// Log entries already forwarded to threads, per task (`log_cursor.json`).
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct LogCursor {
+    /// Set once the cursor has been seeded from an existing graph, so a
+    /// listener's first start doesn't replay every task's history.
+    #[serde(default)]
+    pub seeded: bool,
+    #[serde(default)]
+    pub forwarded: BTreeMap<String, usize>,
+}
+
+impl LogCursor {
+    pub fn path(workgraph_dir: &Path) -> PathBuf {
+        workgraph_dir.join("matrix").join("log_cursor.json")
+    }
+
+    pub fn load(workgraph_dir: &Path) -> Self {
+        load_json(&Self::path(workgraph_dir))
+    }
+
+    pub fn save(&self, workgraph_dir: &Path) -> Result<()> {
+        save_json(&Self::path(workgraph_dir), self)
+    }
+
+    /// Log entries added since the last call, per task, advancing the
+    /// cursor past them. Tasks created after seeding are forwarded from
+    /// their first entry.
+    pub fn take_updates<'a>(&mut self, graph: &'a WorkGraph) -> Vec<(&'a Task, &'a [LogEntry])> {
+        let seeding = !self.seeded;
+        self.seeded = true;
+        let mut updates = Vec::new();
+        for task in graph.tasks() {
+            let seen = self.forwarded.entry(task.id.clone()).or_insert(0);
+            let total = task.log.len();
+            // The log only grows; a shorter one means it was rewritten
+            // (e.g. by `wg undo`), so start over from its current end.
+            if seeding || total < *seen {
+                *seen = total;
+                continue;
+            }
+            if total > *seen {
+                updates.push((task, &task.log[*seen..]));
+                *seen = total;
+            }
+        }
+        self.forwarded.retain(|id, _| graph.get_task(id).is_some());
+        updates.sort_by(|a, b| a.0.id.cmp(&b.0.id));
+        updates
+    }
+
+    /// Skip the entry at `len - 1` of `task_id`'s log, a reply that came
+    /// from the thread itself, if everything before it was forwarded.
+    pub fn skip_reply(&mut self, task_id: &str, len: usize) {
+        if let Some(seen) = self.forwarded.get_mut(task_id)
+            && *seen + 1 == len
+        {
+            *seen = len;
+        }
+    }
+}
+
+/// The message that starts a task's thread.
+pub fn root_message(task: &Task) -> String {
+    format!(
+        "🧵 {}: {} — replies in this thread are added to the task log",
+        task.id, task.title
+    )
+}
+
+/// A task log entry as posted in the task's thread.
+pub fn format_log_entry(entry: &LogEntry) -> String {
+    match &entry.actor {
+        Some(actor) => format!("📝 {}: {}", actor, entry.message),
+        None => format!("📝 {}", entry.message),
+    }
+}
+
+/// `m.relates_to` content placing a message in the thread rooted at
+/// `root_event_id`, with a reply fallback for clients without threads.
+pub fn thread_relation(root_event_id: &str) -> serde_json::Value {
+    serde_json::json!({
+        "rel_type": "m.thread",
+        "event_id": root_event_id,
+        "is_falling_back": true,
+        "m.in_reply_to": { "event_id": root_event_id },
+    })
+}
+
+fn load_json<T: Default + for<'de> Deserialize<'de>>(path: &Path) -> T {
+    match std::fs::read_to_string(path) {
+        Ok(content) => serde_json::from_str(&content).unwrap_or_else(|e| {
+            eprintln!("Warning: ignoring unreadable {}: {}", path.display(), e);
+            T::default()
+        }),
+        Err(_) => T::default(),
+    }
+}
+
+fn save_json<T: Serialize>(path: &Path, value: &T) -> Result<()> {
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+    let tmp = path.with_extension("json.tmp");
+    std::fs::write(&tmp, serde_json::to_string_pretty(value)?)
+        .with_context(|| format!("Failed to write {}", tmp.display()))?;
+    std::fs::rename(&tmp, path).with_context(|| format!("Failed to write {}", path.display()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::graph::Node;
+
+    fn task_with_log(id: &str, entries: usize) -> Node {
+        Node::Task(Task {
+            id: id.into(),
+            title: id.into(),
+            log: (0..entries)
+                .map(|i| LogEntry {
+                    timestamp: String::new(),
+                    actor: None,
+                    message: format!("entry {}", i),
+                })
+                .collect(),
+            ..Task::default()
+        })
+    }
+
+    #[test]
+    fn test_log_cursor_seeds_then_forwards_new_entries() {
+        let mut graph = WorkGraph::new();
+        graph.add_node(task_with_log("old", 3));
+        let mut cursor = LogCursor::default();
+        assert!(cursor.take_updates(&graph).is_empty());
+
+        graph.add_node(task_with_log("new", 1));
+        if let Some(task) = graph.get_task_mut("old") {
+            task.log.push(LogEntry {
+                timestamp: String::new(),
+                actor: Some("agent-1".into()),
+                message: "progress".into(),
+            });
+        }
+        let updates = cursor.take_updates(&graph);
+        let summary: Vec<(&str, usize)> = updates
+            .iter()
+            .map(|(t, entries)| (t.id.as_str(), entries.len()))
+            .collect();
+        assert_eq!(summary, [("new", 1), ("old", 1)]);
+        assert_eq!(format_log_entry(&updates[1].1[0]), "📝 agent-1: progress");
+        assert!(cursor.take_updates(&graph).is_empty());
+    }
+
+    #[test]
+    fn test_thread_store_round_trip() {
+        let dir = tempfile::tempdir().unwrap();
+        let thread = TaskThread {
+            room_id: "!room:example.org".into(),
+            root_event_id: "$root".into(),
+        };
+        ThreadStore::record(dir.path(), "t1", thread.clone()).unwrap();
+        let store = ThreadStore::load(dir.path());
+        assert_eq!(store.thread_for("t1", "!room:example.org"), Some(&thread));
+        assert_eq!(store.thread_for("t1", "!other:example.org"), None);
+        assert_eq!(
+            store.task_for_root("!room:example.org", "$root"),
+            Some("t1")
+        );
+    }
+}
@@ -57,6 +57,14 @@ pub struct ModelEntry {
     /// Cost per 1M output tokens (USD)
     pub cost_per_1m_output: f64,
 
+    /// Cost per 1M cached input tokens read (USD, default: input price)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub cost_per_1m_cache_read: Option<f64>,
+
+    /// Cost per 1M input tokens written to the cache (USD, default: input price)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub cost_per_1m_cache_write: Option<f64>,
+
     /// Context window size in tokens
     #[serde(default)]
     pub context_window: u64,
@@ -69,6 +77,25 @@ pub struct ModelEntry {
     pub tier: ModelTier,
 }
 
+impl ModelEntry {
+    /// Price of the given token usage in USD.
+    pub fn price(&self, tokens: &crate::accounting::TokenCounts) -> f64 {
+        let per_token = |per_1m: f64| per_1m / 1_000_000.0;
+        tokens.input_tokens as f64 * per_token(self.cost_per_1m_input)
+            + tokens.output_tokens as f64 * per_token(self.cost_per_1m_output)
+            + tokens.cache_read_tokens as f64
+                * per_token(
+                    self.cost_per_1m_cache_read
+                        .unwrap_or(self.cost_per_1m_input),
+                )
+            + tokens.cache_write_tokens as f64
+                * per_token(
+                    self.cost_per_1m_cache_write
+                        .unwrap_or(self.cost_per_1m_input),
+                )
+    }
+}
+
 fn default_provider() -> String {
     "openrouter".to_string()
 }
@@ -101,6 +128,8 @@ impl ModelRegistry {
                 provider: "openrouter".into(),
                 cost_per_1m_input: 5.0,
                 cost_per_1m_output: 25.0,
+                cost_per_1m_cache_read: Some(0.50),
+                cost_per_1m_cache_write: Some(6.25),
                 context_window: 1_000_000,
                 capabilities: vec!["coding".into(), "analysis".into(), "creative".into(), "reasoning".into()],
                 tier: ModelTier::Frontier,
@@ -110,6 +139,8 @@ impl ModelRegistry {
                 provider: "openrouter".into(),
                 cost_per_1m_input: 3.0,
                 cost_per_1m_output: 15.0,
+                cost_per_1m_cache_read: Some(0.30),
+                cost_per_1m_cache_write: Some(3.75),
                 context_window: 1_000_000,
                 capabilities: vec!["coding".into(), "analysis".into(), "creative".into()],
                 tier: ModelTier::Mid,
@@ -119,6 +150,8 @@ impl ModelRegistry {
                 provider: "openrouter".into(),
                 cost_per_1m_input: 0.80,
                 cost_per_1m_output: 4.0,
+                cost_per_1m_cache_read: Some(0.08),
+                cost_per_1m_cache_write: Some(1.0),
                 context_window: 200_000,
                 capabilities: vec!["coding".into(), "analysis".into()],
                 tier: ModelTier::Budget,
@@ -128,6 +161,8 @@ impl ModelRegistry {
                 provider: "openrouter".into(),
                 cost_per_1m_input: 2.50,
                 cost_per_1m_output: 10.0,
+                cost_per_1m_cache_read: None,
+                cost_per_1m_cache_write: None,
                 context_window: 128_000,
                 capabilities: vec!["coding".into(), "analysis".into(), "creative".into()],
                 tier: ModelTier::Mid,
@@ -137,6 +172,8 @@ impl ModelRegistry {
                 provider: "openrouter".into(),
                 cost_per_1m_input: 0.15,
                 cost_per_1m_output: 0.60,
+                cost_per_1m_cache_read: None,
+                cost_per_1m_cache_write: None,
                 context_window: 128_000,
                 capabilities: vec!["coding".into(), "analysis".into()],
                 tier: ModelTier::Budget,
@@ -146,6 +183,8 @@ impl ModelRegistry {
                 provider: "openrouter".into(),
                 cost_per_1m_input: 2.0,
                 cost_per_1m_output: 8.0,
+                cost_per_1m_cache_read: None,
+                cost_per_1m_cache_write: None,
                 context_window: 200_000,
                 capabilities: vec!["coding".into(), "analysis".into(), "reasoning".into()],
                 tier: ModelTier::Frontier,
@@ -155,6 +194,8 @@ impl ModelRegistry {
                 provider: "openrouter".into(),
                 cost_per_1m_input: 1.25,
                 cost_per_1m_output: 10.0,
+                cost_per_1m_cache_read: None,
+                cost_per_1m_cache_write: None,
                 context_window: 1_000_000,
                 capabilities: vec!["coding".into(), "analysis".into(), "creative".into(), "reasoning".into()],
                 tier: ModelTier::Mid,
@@ -164,6 +205,8 @@ impl ModelRegistry {
                 provider: "openrouter".into(),
                 cost_per_1m_input: 0.10,
                 cost_per_1m_output: 0.40,
+                cost_per_1m_cache_read: None,
+                cost_per_1m_cache_write: None,
                 context_window: 1_000_000,
                 capabilities: vec!["coding".into(), "analysis".into()],
                 tier: ModelTier::Budget,
@@ -173,6 +216,8 @@ impl ModelRegistry {
                 provider: "openrouter".into(),
                 cost_per_1m_input: 0.30,
                 cost_per_1m_output: 0.88,
+                cost_per_1m_cache_read: None,
+                cost_per_1m_cache_write: None,
                 context_window: 164_000,
                 capabilities: vec!["coding".into(), "analysis".into()],
                 tier: ModelTier::Budget,
@@ -182,6 +227,8 @@ impl ModelRegistry {
                 provider: "openrouter".into(),
                 cost_per_1m_input: 0.55,
                 cost_per_1m_output: 2.19,
+                cost_per_1m_cache_read: None,
+                cost_per_1m_cache_write: None,
                 context_window: 164_000,
                 capabilities: vec!["coding".into(), "analysis".into(), "reasoning".into()],
                 tier: ModelTier::Mid,
@@ -191,6 +238,8 @@ impl ModelRegistry {
                 provider: "openrouter".into(),
                 cost_per_1m_input: 0.20,
                 cost_per_1m_output: 0.60,
+                cost_per_1m_cache_read: None,
+                cost_per_1m_cache_write: None,
                 context_window: 1_000_000,
                 capabilities: vec!["coding".into(), "analysis".into()],
                 tier: ModelTier::Budget,
@@ -200,6 +249,8 @@ impl ModelRegistry {
                 provider: "openrouter".into(),
                 cost_per_1m_input: 0.10,
                 cost_per_1m_output: 0.30,
+                cost_per_1m_cache_read: None,
+                cost_per_1m_cache_write: None,
                 context_window: 512_000,
                 capabilities: vec!["coding".into(), "analysis".into()],
                 tier: ModelTier::Budget,
@@ -209,6 +260,8 @@ impl ModelRegistry {
                 provider: "openrouter".into(),
                 cost_per_1m_input: 0.20,
                 cost_per_1m_output: 0.60,
+                cost_per_1m_cache_read: None,
+                cost_per_1m_cache_write: None,
                 context_window: 131_072,
                 capabilities: vec!["coding".into(), "analysis".into(), "reasoning".into()],
                 tier: ModelTier::Budget,
@@ -260,6 +313,30 @@ impl ModelRegistry {
         self.models.get(id)
     }
 
+    /// Find the entry for a model name as executors report it: a registry
+    /// ID, a bare model name ("claude-opus-4-6"), a dated snapshot
+    /// ("claude-sonnet-4-5-20250929") or a short alias ("opus").
+    pub fn resolve(&self, model: &str) -> Option<&ModelEntry> {
+        if let Some(entry) = self.models.get(model) {
+            return Some(entry);
+        }
+        let bare = |id: &str| id.rsplit('/').next().unwrap_or(id).to_string();
+        let name = bare(model);
+        let mut best: Option<&ModelEntry> = None;
+        for entry in self.models.values() {
+            let entry_name = bare(&entry.id);
+            if entry_name == name {
+                return Some(entry);
+            }
+            let matches = name.starts_with(&format!("{}-", entry_name))
+                || entry_name.contains(&format!("-{}-", name));
+            if matches && best.is_none_or(|b| entry.id.len() > b.id.len()) {
+                best = Some(entry);
+            }
+        }
+        best
+    }
+
     /// Get the default model entry
     pub fn get_default(&self) -> Option<&ModelEntry> {
         self.default_model.as_ref().and_then(|id| self.models.get(id))
@@ -286,7 +363,7 @@ impl ModelRegistry {
     pub fn list(&self, tier: Option<&ModelTier>) -> Vec<&ModelEntry> {
         self.models
             .values()
-            .filter(|m| tier.map_or(true, |t| &m.tier == t))
+            .filter(|m| tier.is_none_or(|t| &m.tier == t))
             .collect()
     }
 }
@@ -369,6 +446,8 @@ mod tests {
             provider: "custom".into(),
             cost_per_1m_input: 1.0,
             cost_per_1m_output: 2.0,
+            cost_per_1m_cache_read: None,
+            cost_per_1m_cache_write: None,
             context_window: 32_000,
             capabilities: vec!["coding".into()],
             tier: ModelTier::Mid,
@@ -378,6 +457,37 @@ mod tests {
         assert!(reg.models.contains_key("custom/my-model"));
     }
 
+    #[test]
+    fn test_resolve_reported_model_names() {
+        let reg = ModelRegistry::with_defaults();
+        let id = |m: &str| reg.resolve(m).map(|e| e.id.as_str());
+        assert_eq!(id("openai/gpt-4o"), Some("openai/gpt-4o"));
+        assert_eq!(id("gpt-4o"), Some("openai/gpt-4o"));
+        assert_eq!(id("gpt-4o-mini"), Some("openai/gpt-4o-mini"));
+        assert_eq!(
+            id("claude-opus-4-6-20260101"),
+            Some("anthropic/claude-opus-4-6")
+        );
+        assert_eq!(id("haiku"), Some("anthropic/claude-haiku-4-5"));
+        assert_eq!(id("unknown-model"), None);
+    }
+
+    #[test]
+    fn test_price_uses_cache_rates() {
+        let reg = ModelRegistry::with_defaults();
+        let tokens = crate::accounting::TokenCounts {
+            input_tokens: 1_000_000,
+            output_tokens: 1_000_000,
+            cache_read_tokens: 1_000_000,
+            cache_write_tokens: 1_000_000,
+        };
+        let opus = reg.get("anthropic/claude-opus-4-6").unwrap();
+        assert!((opus.price(&tokens) - (5.0 + 25.0 + 0.5 + 6.25)).abs() < 1e-9);
+        // Without cache rates, cached tokens cost as much as input
+        let gpt = reg.get("openai/gpt-4o").unwrap();
+        assert!((gpt.price(&tokens) - (2.5 + 10.0 + 2.5 + 2.5)).abs() < 1e-9);
+    }
+
     #[test]
     fn test_list_filter_by_tier() {
         let reg = ModelRegistry::with_defaults();
@@ -1,9 +1,16 @@
-use crate::graph::{Node, WorkGraph};
+use crate::config::{Config, StorageConfig};
+use crate::graph::{ChangeSet, MergeConflict, Node, WorkGraph};
+use crate::store::{GraphStore, StorageBackend};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
 use std::fs::{File, OpenOptions};
-use std::io::{BufRead, BufReader, Write};
+use std::io::{BufRead, BufReader, Read, Seek, SeekFrom, Write};
 use std::path::{Path, PathBuf};
+use std::time::SystemTime;
 use thiserror::Error;
 
+#[cfg(unix)]
+use std::os::unix::fs::MetadataExt;
 #[cfg(unix)]
 use std::os::unix::io::AsRawFd;
 
@@ -16,12 +23,21 @@ pub enum ParseError {
         line: usize,
         source: serde_json::Error,
     },
+    #[error("Journal error on line {line}: {source}")]
+    Journal {
+        line: usize,
+        source: serde_json::Error,
+    },
     #[error("Lock error: {0}")]
     Lock(String),
+    #[error("Storage error: {0}")]
+    Store(String),
+    #[error(transparent)]
+    Conflict(#[from] MergeConflict),
 }
 
 /// RAII guard for file locks - automatically releases lock on drop
-struct FileLock {
+pub(crate) struct FileLock {
     #[cfg(unix)]
     file: File,
 }
@@ -29,7 +45,7 @@ struct FileLock {
 impl FileLock {
     /// Acquire an exclusive lock on a lock file
     #[cfg(unix)]
-    fn acquire<P: AsRef<Path>>(lock_path: P) -> Result<Self, ParseError> {
+    pub(crate) fn acquire<P: AsRef<Path>>(lock_path: P) -> Result<Self, ParseError> {
         use std::os::unix::io::AsRawFd;
 
         // Ensure the .workgraph directory exists
@@ -61,7 +77,7 @@ impl FileLock {
     }
 
     #[cfg(not(unix))]
-    fn acquire<P: AsRef<Path>>(_lock_path: P) -> Result<Self, ParseError> {
+    pub(crate) fn acquire<P: AsRef<Path>>(_lock_path: P) -> Result<Self, ParseError> {
         // On non-Unix systems, we can't use flock - return a no-op lock
         // This is a limitation but workgraph is primarily for Unix systems
         Ok(FileLock {})
@@ -91,13 +107,139 @@ fn get_lock_path<P: AsRef<Path>>(graph_path: P) -> PathBuf {
     }
 }
 
+/// Get the journal path for a given graph file (`graph.jsonl` -> `graph.jsonl.journal`)
+pub fn journal_path<P: AsRef<Path>>(graph_path: P) -> PathBuf {
+    let graph_path = graph_path.as_ref();
+    let mut name = graph_path
+        .file_name()
+        .map(|n| n.to_os_string())
+        .unwrap_or_else(|| "graph.jsonl".into());
+    name.push(".journal");
+    graph_path.with_file_name(name)
+}
+
+/// A single entry in the graph journal.
+///
+/// Journal entries are applied in order on top of the graph.jsonl snapshot.
+/// `Put` carries the full node, so replaying an entry twice is harmless.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "op", rename_all = "lowercase")]
+#[allow(clippy::large_enum_variant)]
+pub enum JournalOp {
+    /// Insert or replace a node
+    Put { node: Node },
+    /// Remove a node and every reference to it
+    Remove { id: String },
+}
+
+/// Load a work graph
+///
+/// Reads `path` as JSONL unless `[storage] backend = "sqlite"` is configured
+/// for a `graph.jsonl` path, in which case the graph comes from the sibling
+/// `graph.db` instead.
+pub fn load_graph<P: AsRef<Path>>(path: P) -> Result<WorkGraph, ParseError> {
+    let path = path.as_ref();
+    match sqlite_store_for(path)? {
+        Some(store) => store
+            .load()
+            .map_err(|e| ParseError::Store(format!("{:#}", e))),
+        None => load_jsonl(path),
+    }
+}
+
+/// Save a work graph to the backend that `load_graph` reads from.
+pub fn save_graph<P: AsRef<Path>>(graph: &WorkGraph, path: P) -> Result<(), ParseError> {
+    let path = path.as_ref();
+    match sqlite_store_for(path)? {
+        Some(store) => store
+            .save(graph)
+            .map_err(|e| match e.downcast::<MergeConflict>() {
+                Ok(conflict) => ParseError::Conflict(conflict),
+                Err(e) => ParseError::Store(format!("{:#}", e)),
+            }),
+        None => save_jsonl(graph, path),
+    }
+}
+
+/// The SQLite store that replaces `path`, if the workgraph is configured for it.
+fn sqlite_store_for(path: &Path) -> Result<Option<Box<dyn GraphStore>>, ParseError> {
+    if !is_workgraph_graph(path) {
+        return Ok(None);
+    }
+    if load_storage_config(path).backend != StorageBackend::Sqlite.as_str() {
+        return Ok(None);
+    }
+    let dir = path.parent().unwrap_or(Path::new("."));
+    crate::store::open_backend(dir, StorageBackend::Sqlite)
+        .map(Some)
+        .map_err(|e| ParseError::Store(format!("{:#}", e)))
+}
+
 /// Load a work graph from a JSONL file
 /// Uses advisory file locking to prevent concurrent access corruption
-pub fn load_graph<P: AsRef<Path>>(path: P) -> Result<WorkGraph, ParseError> {
-    let lock_path = get_lock_path(&path);
+///
+/// If a journal exists next to the file, its entries are replayed on top of
+/// the snapshot. A torn final journal line (from a crash mid-append) is
+/// skipped with a warning.
+pub fn load_jsonl<P: AsRef<Path>>(path: P) -> Result<WorkGraph, ParseError> {
+    let path = path.as_ref();
+    let lock_path = get_lock_path(path);
     let _lock = FileLock::acquire(&lock_path)?;
 
+    let (mut graph, mark) = read_graph_marked(path)?;
+    graph.track_changes(path);
+    if let Some(changes) = graph.changes() {
+        changes.set_storage_mark(mark);
+    }
+    Ok(graph)
+    // Lock is automatically released when _lock goes out of scope
+}
+
+/// How much of its JSONL storage a loaded graph has seen, so a journaled
+/// save can read only the journal entries appended since rather than the
+/// whole graph.
+#[derive(Debug, Clone, Default)]
+pub struct StorageMark {
+    /// The snapshot file seen; compaction or a full save replaces it
+    snapshot: Option<SnapshotId>,
+    /// Length of the complete journal entries seen
+    journal_len: u64,
+    /// Stored version of every node that has changed in storage since the
+    /// graph was loaded (`None` = removed). Any other node is stored as the
+    /// graph loaded it.
+    known: HashMap<String, Option<Node>>,
+}
+
+/// Tells one snapshot file from another written in its place.
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct SnapshotId {
+    len: u64,
+    modified: Option<SystemTime>,
+    #[cfg(unix)]
+    inode: u64,
+}
+
+impl SnapshotId {
+    fn of(meta: &std::fs::Metadata) -> Self {
+        Self {
+            len: meta.len(),
+            modified: meta.modified().ok(),
+            #[cfg(unix)]
+            inode: meta.ino(),
+        }
+    }
+}
+
+/// Read snapshot + journal without taking the lock. Callers must hold it.
+fn read_graph_unlocked(path: &Path) -> Result<WorkGraph, ParseError> {
+    read_graph_marked(path).map(|(graph, _)| graph)
+}
+
+/// Read snapshot + journal, with a mark of what was read. Callers must hold
+/// the lock.
+fn read_graph_marked(path: &Path) -> Result<(WorkGraph, StorageMark), ParseError> {
     let file = File::open(path)?;
+    let snapshot = SnapshotId::of(&file.metadata()?);
     let reader = BufReader::new(file);
     let mut graph = WorkGraph::new();
 
@@ -128,18 +270,400 @@ pub fn load_graph<P: AsRef<Path>>(path: P) -> Result<WorkGraph, ParseError> {
         graph.add_node(node);
     }
 
-    Ok(graph)
-    // Lock is automatically released when _lock goes out of scope
+    let journal_len = replay_journal(&mut graph, &journal_path(path))?;
+
+    let mark = StorageMark {
+        snapshot: Some(snapshot),
+        journal_len,
+        known: HashMap::new(),
+    };
+    Ok((graph, mark))
+}
+
+/// Apply journal entries on top of `graph`. Missing journal is a no-op.
+/// Returns the length of the journal's complete lines.
+fn replay_journal(graph: &mut WorkGraph, journal: &Path) -> Result<u64, ParseError> {
+    let content = match std::fs::read_to_string(journal) {
+        Ok(c) => c,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(0),
+        Err(e) => return Err(e.into()),
+    };
+
+    let lines: Vec<&str> = content.lines().collect();
+    for (idx, line) in lines.iter().enumerate() {
+        let trimmed = line.trim();
+        if trimmed.is_empty() {
+            continue;
+        }
+        let op: JournalOp = match serde_json::from_str(trimmed) {
+            Ok(op) => op,
+            Err(e) if idx + 1 == lines.len() && !content.ends_with('\n') => {
+                eprintln!(
+                    "Warning: ignoring torn journal entry at line {} of {}: {}",
+                    idx + 1,
+                    journal.display(),
+                    e
+                );
+                break;
+            }
+            Err(e) => {
+                return Err(ParseError::Journal {
+                    line: idx + 1,
+                    source: e,
+                });
+            }
+        };
+        match op {
+            JournalOp::Put { node } => graph.add_node(node),
+            JournalOp::Remove { id } => {
+                graph.remove_node(&id);
+            }
+        }
+    }
+
+    Ok(content.rfind('\n').map_or(0, |i| i as u64 + 1))
+}
+
+/// The nodes put by journal entries appended since `mark`, and the length
+/// of the journal's complete lines, or `None` if storage changed in a way
+/// that needs a full read: a new snapshot, a shorter journal, or a removal
+/// (which also rewires the removed node's dependents).
+fn read_journal_tail(
+    path: &Path,
+    mark: &StorageMark,
+) -> Result<Option<(Vec<Node>, u64)>, ParseError> {
+    if mark.snapshot.as_ref() != Some(&SnapshotId::of(&std::fs::metadata(path)?)) {
+        return Ok(None);
+    }
+    let mut file = match File::open(journal_path(path)) {
+        Ok(file) => file,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => {
+            return Ok((mark.journal_len == 0).then(|| (Vec::new(), 0)));
+        }
+        Err(e) => return Err(e.into()),
+    };
+    if file.metadata()?.len() < mark.journal_len {
+        return Ok(None);
+    }
+    file.seek(SeekFrom::Start(mark.journal_len))?;
+    let mut tail = Vec::new();
+    file.read_to_end(&mut tail)?;
+    let complete = tail.iter().rposition(|&b| b == b'\n').map_or(0, |i| i + 1);
+
+    let mut nodes = Vec::new();
+    for line in tail[..complete].split(|&b| b == b'\n') {
+        if line.trim_ascii().is_empty() {
+            continue;
+        }
+        match serde_json::from_slice(line) {
+            Ok(JournalOp::Put { node }) => nodes.push(node),
+            _ => return Ok(None),
+        }
+    }
+    Ok(Some((nodes, mark.journal_len + complete as u64)))
+}
+
+/// What a tracked save knows of the stored graph.
+enum Stored {
+    /// All of it, freshly read
+    Full(Box<WorkGraph>),
+    /// Only what changed since the saving graph was loaded
+    Known(StorageMark),
+}
+
+impl Stored {
+    /// The stored version of `id`, which the saving graph has changed.
+    fn node(&self, id: &str, changes: &ChangeSet) -> Option<Node> {
+        match self {
+            Stored::Full(graph) => graph.get_node(id).cloned(),
+            // Any other node is stored as this graph last saw it, which is
+            // its merge base.
+            Stored::Known(mark) => match mark.known.get(id) {
+                Some(node) => node.clone(),
+                None => changes.base(id),
+            },
+        }
+    }
 }
 
 /// Save a work graph to a JSONL file
 /// Uses advisory file locking and atomic write (temp file + rename) to
 /// prevent data loss on crash.
-pub fn save_graph<P: AsRef<Path>>(graph: &WorkGraph, path: P) -> Result<(), ParseError> {
+///
+/// A graph loaded from this same file is saved by compare-and-swap: its
+/// changes are merged with the file's current contents (see
+/// [`crate::graph::merge_node`]), bumping each changed task's `revision`, and
+/// conflicting edits fail with [`ParseError::Conflict`]. With
+/// `[storage] journal = true` the merge reads only the journal entries
+/// appended since the graph was loaded or last saved (see [`StorageMark`])
+/// and the merged nodes are appended to the journal, which is compacted into
+/// the snapshot once it reaches `compact_threshold` entries; otherwise the
+/// snapshot is rewritten. Graphs built in memory
+/// replace the whole file and discard any journal.
+pub fn save_jsonl<P: AsRef<Path>>(graph: &WorkGraph, path: P) -> Result<(), ParseError> {
+    let path = path.as_ref();
+    let lock_path = get_lock_path(path);
+    let _lock = FileLock::acquire(&lock_path)?;
+
+    let storage = load_storage_config(path);
+    if path.exists()
+        && let Some(changes) = graph.changes()
+        && changes.origin == path
+    {
+        // Compare-and-swap: merge against what is on disk now, not what
+        // was there when the graph was loaded. With a journal and the same
+        // snapshot, only the entries appended since need reading.
+        let mark = changes.take_storage_mark().filter(|_| storage.journal);
+        let tail = match &mark {
+            Some(mark) => read_journal_tail(path, mark)?,
+            None => None,
+        };
+        let mut stored = match (mark, tail) {
+            (Some(mut mark), Some((nodes, journal_len))) => {
+                for node in nodes {
+                    mark.known.insert(node.id().to_string(), Some(node));
+                }
+                mark.journal_len = journal_len;
+                Stored::Known(mark)
+            }
+            _ => Stored::Full(Box::new(read_graph_unlocked(path)?)),
+        };
+
+        let writes = graph.merge_changes(|id| stored.node(id, changes))?;
+        let previous: Vec<Option<Node>> = writes
+            .iter()
+            .map(|(id, _)| stored.node(id, changes))
+            .collect();
+        if let Stored::Full(ref mut on_disk) = stored {
+            apply_writes(on_disk, &writes);
+        }
+
+        if !writes.is_empty() {
+            if storage.journal {
+                let ops: Vec<JournalOp> = writes
+                    .iter()
+                    .map(|(id, node)| match node {
+                        Some(node) => JournalOp::Put { node: node.clone() },
+                        None => JournalOp::Remove { id: id.clone() },
+                    })
+                    .collect();
+                let entries = append_journal(&journal_path(path), &ops)?;
+                if entries >= storage.compact_threshold {
+                    if let Stored::Known(_) = stored {
+                        stored = Stored::Full(Box::new(read_graph_unlocked(path)?));
+                    }
+                    if let Stored::Full(ref on_disk) = stored {
+                        write_snapshot_unlocked(on_disk, path)?;
+                    }
+                    remove_journal(path)?;
+                }
+            } else {
+                let Stored::Full(ref on_disk) = stored else {
+                    unreachable!("saves without a journal read the whole graph");
+                };
+                write_snapshot_unlocked(on_disk, path)?;
+                remove_journal(path)?;
+            }
+            let history: Vec<_> = previous
+                .iter()
+                .zip(&writes)
+                .map(|(before, (_, after))| (before.as_ref(), after.as_ref()))
+                .collect();
+            // The graph before this save: everything untouched plus the
+            // previous versions of the written nodes.
+            record_history(path, &history, || {
+                let read;
+                let on_disk = match &stored {
+                    Stored::Full(on_disk) => on_disk,
+                    Stored::Known(_) => {
+                        read = read_graph_unlocked(path).unwrap_or_default();
+                        &read
+                    }
+                };
+                on_disk
+                    .nodes()
+                    .filter(|node| !writes.iter().any(|(id, _)| id == node.id()))
+                    .chain(previous.iter().flatten())
+                    .cloned()
+                    .collect()
+            });
+        }
+        if storage.journal {
+            changes.set_storage_mark(mark_after_save(graph, stored, writes, path)?);
+        }
+        graph.mark_saved();
+        return Ok(());
+    }
+
+    // Only read what this save replaces if history needs the difference
+    let previous = if !is_workgraph_graph(path)
+        || !crate::history::recording(path.parent().unwrap_or(Path::new(".")))
+    {
+        None
+    } else if path.exists() {
+        read_graph_unlocked(path).ok()
+    } else {
+        Some(WorkGraph::new())
+    };
+    write_snapshot_unlocked(graph, path)?;
+    remove_journal(path)?;
+    if let Some(previous) = previous {
+        let writes = crate::history::graph_writes(&previous, graph);
+        record_history(path, &writes, || previous.nodes().cloned().collect());
+    }
+    // Lock is automatically released when _lock goes out of scope
+    Ok(())
+}
+
+/// Whether `path` is a workgraph's main `graph.jsonl`.
+fn is_workgraph_graph(path: &Path) -> bool {
+    path.file_name().and_then(|n| n.to_str()) == Some("graph.jsonl")
+}
+
+/// Append a save's node writes to the workgraph's history log. Failures are
+/// reported but never fail the save itself.
+fn record_history(
+    path: &Path,
+    writes: &[(Option<&Node>, Option<&Node>)],
+    stored: impl FnOnce() -> Vec<Node>,
+) {
+    if !is_workgraph_graph(path) || writes.is_empty() {
+        return;
+    }
+    let dir = path.parent().unwrap_or(Path::new("."));
+    if let Err(e) = crate::history::record(dir, writes, stored) {
+        eprintln!("Warning: failed to record graph history: {:#}", e);
+    }
+}
+
+/// The storage mark of a graph just saved to `path`, which now holds
+/// `stored`.
+fn mark_after_save(
+    graph: &WorkGraph,
+    stored: Stored,
+    writes: Vec<(String, Option<Node>)>,
+    path: &Path,
+) -> Result<StorageMark, ParseError> {
+    let known = match stored {
+        Stored::Known(mut mark) => {
+            mark.known.extend(writes);
+            mark.known
+        }
+        // Once saved, the graph stands for what it holds, so the nodes
+        // stored differently are all there is to know.
+        Stored::Full(on_disk) => {
+            let mut known: HashMap<String, Option<Node>> = on_disk
+                .nodes()
+                .filter(|node| graph.get_node(node.id()) != Some(node))
+                .map(|node| (node.id().to_string(), Some(node.clone())))
+                .collect();
+            for node in graph.nodes() {
+                if on_disk.get_node(node.id()).is_none() {
+                    known.insert(node.id().to_string(), None);
+                }
+            }
+            known
+        }
+    };
+    let journal_len = match std::fs::metadata(journal_path(path)) {
+        Ok(meta) => meta.len(),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => 0,
+        Err(e) => return Err(e.into()),
+    };
+    Ok(StorageMark {
+        snapshot: Some(SnapshotId::of(&std::fs::metadata(path)?)),
+        journal_len,
+        known,
+    })
+}
+
+/// Apply merged node writes (`None` = delete) to a graph.
+fn apply_writes(graph: &mut WorkGraph, writes: &[(String, Option<Node>)]) {
+    for (id, node) in writes {
+        match node {
+            Some(node) => graph.add_node(node.clone()),
+            None => {
+                graph.remove_node(id);
+            }
+        }
+    }
+}
+
+/// Fold the journal into the graph.jsonl snapshot and remove it.
+/// No-op when there is no journal.
+pub fn compact_graph<P: AsRef<Path>>(path: P) -> Result<(), ParseError> {
     let path = path.as_ref();
     let lock_path = get_lock_path(path);
     let _lock = FileLock::acquire(&lock_path)?;
 
+    if !journal_path(path).exists() {
+        return Ok(());
+    }
+    let graph = read_graph_unlocked(path)?;
+    write_snapshot_unlocked(&graph, path)?;
+    remove_journal(path)
+}
+
+/// Read `[storage]` from the config.toml in the graph's directory.
+/// Falls back to defaults (journal off) when it cannot be read.
+fn load_storage_config(path: &Path) -> StorageConfig {
+    let dir = path.parent().unwrap_or(Path::new("."));
+    Config::load_merged(dir)
+        .map(|c| c.storage)
+        .unwrap_or_default()
+}
+
+/// Append entries to the journal in a single write and fsync it.
+/// Returns the number of entries now in the journal.
+fn append_journal(journal: &Path, ops: &[JournalOp]) -> Result<usize, ParseError> {
+    let mut file = OpenOptions::new()
+        .read(true)
+        .create(true)
+        .append(true)
+        .open(journal)?;
+
+    // Drop a torn tail left by a crash so the new entries start on a fresh line.
+    let existing = std::fs::read(journal)?;
+    if !existing.is_empty() && !existing.ends_with(b"\n") {
+        let keep = existing
+            .iter()
+            .rposition(|&b| b == b'\n')
+            .map(|pos| pos + 1)
+            .unwrap_or(0);
+        file.set_len(keep as u64)?;
+    }
+    let prior = existing.iter().filter(|&&b| b == b'\n').count();
+
+    let mut buf = String::new();
+    for op in ops {
+        let json =
+            serde_json::to_string(op).map_err(|e| ParseError::Json { line: 0, source: e })?;
+        buf.push_str(&json);
+        buf.push('\n');
+    }
+    file.write_all(buf.as_bytes())?;
+    file.flush()?;
+    #[cfg(unix)]
+    {
+        let rc = unsafe { libc::fsync(file.as_raw_fd()) };
+        if rc != 0 {
+            return Err(ParseError::Io(std::io::Error::last_os_error()));
+        }
+    }
+
+    Ok(prior + ops.len())
+}
+
+fn remove_journal(path: &Path) -> Result<(), ParseError> {
+    match std::fs::remove_file(journal_path(path)) {
+        Ok(()) => Ok(()),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(()),
+        Err(e) => Err(e.into()),
+    }
+}
+
+/// Write the full graph atomically (temp file + rename). Callers must hold the lock.
+fn write_snapshot_unlocked(graph: &WorkGraph, path: &Path) -> Result<(), ParseError> {
     // Write to a temporary file in the same directory, then atomically rename.
     // This ensures a crash mid-write leaves the original file intact.
     let parent = path.parent().unwrap_or(Path::new("."));
@@ -161,7 +685,6 @@ pub fn save_graph<P: AsRef<Path>>(graph: &WorkGraph, path: P) -> Result<(), Pars
         file.flush()?;
         #[cfg(unix)]
         {
-            use std::os::unix::io::AsRawFd;
             // fsync to ensure data is on disk before rename
             let rc = unsafe { libc::fsync(file.as_raw_fd()) };
             if rc != 0 {
@@ -180,13 +703,12 @@ pub fn save_graph<P: AsRef<Path>>(graph: &WorkGraph, path: P) -> Result<(), Pars
     }
 
     result
-    // Lock is automatically released when _lock goes out of scope
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::graph::Task;
+    use crate::graph::{Status, Task};
     use std::io::Write;
     use tempfile::NamedTempFile;
 
@@ -853,4 +1375,355 @@ mod tests {
         let task = graph.get_task("dup").unwrap();
         assert_eq!(task.title, "Second version");
     }
+
+    // ---- Journaled storage ----
+
+    fn journal_dir(threshold: usize) -> (tempfile::TempDir, PathBuf) {
+        let dir = tempfile::TempDir::new().unwrap();
+        std::fs::write(
+            dir.path().join("config.toml"),
+            format!(
+                "[storage]\njournal = true\ncompact_threshold = {}\n",
+                threshold
+            ),
+        )
+        .unwrap();
+        let path = dir.path().join("graph.jsonl");
+        let mut graph = WorkGraph::new();
+        graph.add_node(Node::Task(make_task("t1", "Task 1")));
+        graph.add_node(Node::Task(make_task("t2", "Task 2")));
+        save_graph(&graph, &path).unwrap();
+        (dir, path)
+    }
+
+    #[test]
+    fn test_journal_appends_only_changed_nodes() {
+        let (_dir, path) = journal_dir(100);
+        let snapshot = std::fs::read_to_string(&path).unwrap();
+
+        let mut graph = load_graph(&path).unwrap();
+        graph.get_task_mut("t1").unwrap().title = "Renamed".to_string();
+        save_graph(&graph, &path).unwrap();
+
+        // Snapshot untouched; one put in the journal
+        assert_eq!(std::fs::read_to_string(&path).unwrap(), snapshot);
+        let journal = std::fs::read_to_string(journal_path(&path)).unwrap();
+        assert_eq!(journal.lines().count(), 1);
+        assert!(journal.contains("\"op\":\"put\""));
+
+        let reloaded = load_graph(&path).unwrap();
+        assert_eq!(reloaded.get_task("t1").unwrap().title, "Renamed");
+        assert_eq!(reloaded.get_task("t2").unwrap().title, "Task 2");
+    }
+
+    #[test]
+    fn test_journal_records_removals() {
+        let (_dir, path) = journal_dir(100);
+
+        let mut graph = load_graph(&path).unwrap();
+        graph.remove_node("t2");
+        save_graph(&graph, &path).unwrap();
+
+        let reloaded = load_graph(&path).unwrap();
+        assert_eq!(reloaded.len(), 1);
+        assert!(reloaded.get_task("t2").is_none());
+    }
+
+    #[test]
+    fn test_journal_merges_non_overlapping_writers() {
+        let (_dir, path) = journal_dir(100);
+
+        // Two writers load the same state and change different tasks
+        let mut a = load_graph(&path).unwrap();
+        let mut b = load_graph(&path).unwrap();
+        a.get_task_mut("t1").unwrap().title = "From A".to_string();
+        b.get_task_mut("t2").unwrap().title = "From B".to_string();
+        save_graph(&a, &path).unwrap();
+        save_graph(&b, &path).unwrap();
+
+        let reloaded = load_graph(&path).unwrap();
+        assert_eq!(reloaded.get_task("t1").unwrap().title, "From A");
+        assert_eq!(reloaded.get_task("t2").unwrap().title, "From B");
+    }
+
+    #[test]
+    fn test_journal_save_reads_only_the_new_entries() {
+        let (_dir, path) = journal_dir(100);
+
+        let mut a = load_graph(&path).unwrap();
+        let mut b = load_graph(&path).unwrap();
+        a.get_task_mut("t1").unwrap().title = "From A".to_string();
+        save_graph(&a, &path).unwrap();
+
+        // B sees A's entry in the journal tail and merges with it
+        let mark = b.changes().unwrap().take_storage_mark().unwrap();
+        let (tail, journal_len) = read_journal_tail(&path, &mark).unwrap().unwrap();
+        assert_eq!(tail.len(), 1);
+        assert_eq!(
+            journal_len,
+            std::fs::metadata(journal_path(&path)).unwrap().len()
+        );
+        b.changes().unwrap().set_storage_mark(mark);
+        b.get_task_mut("t1").unwrap().status = Status::Done;
+        save_graph(&b, &path).unwrap();
+        let reloaded = load_graph(&path).unwrap();
+        let t1 = reloaded.get_task("t1").unwrap();
+        assert_eq!((t1.title.as_str(), t1.status), ("From A", Status::Done));
+
+        // B's mark now covers both entries, and still catches a conflict
+        a.get_task_mut("t1").unwrap().title = "A again".to_string();
+        save_graph(&a, &path).unwrap();
+        b.get_task_mut("t1").unwrap().title = "From B".to_string();
+        assert!(matches!(
+            save_graph(&b, &path),
+            Err(ParseError::Conflict(_))
+        ));
+
+        // A removal elsewhere needs a full read, which still merges
+        let mut c = load_graph(&path).unwrap();
+        let mut d = load_graph(&path).unwrap();
+        c.remove_node("t2");
+        save_graph(&c, &path).unwrap();
+        let mark = d.changes().unwrap().take_storage_mark().unwrap();
+        assert!(read_journal_tail(&path, &mark).unwrap().is_none());
+        d.changes().unwrap().set_storage_mark(mark);
+        d.get_task_mut("t1").unwrap().title = "From D".to_string();
+        save_graph(&d, &path).unwrap();
+        let reloaded = load_graph(&path).unwrap();
+        assert_eq!(reloaded.get_task("t1").unwrap().title, "From D");
+        assert!(reloaded.get_task("t2").is_none());
+    }
+
+    #[test]
+    fn test_journal_compacts_at_threshold() {
+        let (_dir, path) = journal_dir(2);
+
+        for i in 0..2 {
+            let mut graph = load_graph(&path).unwrap();
+            graph.get_task_mut("t1").unwrap().title = format!("Rev {}", i);
+            save_graph(&graph, &path).unwrap();
+        }
+
+        assert!(!journal_path(&path).exists());
+        let snapshot = std::fs::read_to_string(&path).unwrap();
+        assert!(snapshot.contains("Rev 1"));
+        assert_eq!(
+            load_graph(&path).unwrap().get_task("t1").unwrap().title,
+            "Rev 1"
+        );
+    }
+
+    #[test]
+    fn test_journal_tolerates_torn_last_line() {
+        let (_dir, path) = journal_dir(100);
+
+        let mut graph = load_graph(&path).unwrap();
+        graph.get_task_mut("t1").unwrap().title = "Committed".to_string();
+        save_graph(&graph, &path).unwrap();
+
+        // Simulate a crash mid-append
+        let mut journal = OpenOptions::new()
+            .append(true)
+            .open(journal_path(&path))
+            .unwrap();
+        write!(journal, r#"{{"op":"put","node":{{"id":"t2","kind":"ta"#).unwrap();
+        drop(journal);
+
+        let mut graph = load_graph(&path).unwrap();
+        assert_eq!(graph.get_task("t1").unwrap().title, "Committed");
+        assert_eq!(graph.get_task("t2").unwrap().title, "Task 2");
+
+        // The next append discards the torn tail
+        graph.get_task_mut("t2").unwrap().title = "After crash".to_string();
+        save_graph(&graph, &path).unwrap();
+        let reloaded = load_graph(&path).unwrap();
+        assert_eq!(reloaded.get_task("t2").unwrap().title, "After crash");
+        let journal = std::fs::read_to_string(journal_path(&path)).unwrap();
+        assert_eq!(journal.lines().count(), 2);
+    }
+
+    #[test]
+    fn test_journal_corruption_mid_file_is_error() {
+        let (_dir, path) = journal_dir(100);
+        std::fs::write(
+            journal_path(&path),
+            "garbage\n{\"op\":\"remove\",\"id\":\"t1\"}\n",
+        )
+        .unwrap();
+
+        assert!(matches!(
+            load_graph(&path).unwrap_err(),
+            ParseError::Journal { line: 1, .. }
+        ));
+    }
+
+    #[test]
+    fn test_full_save_discards_journal() {
+        let (_dir, path) = journal_dir(100);
+
+        let mut graph = load_graph(&path).unwrap();
+        graph.get_task_mut("t1").unwrap().title = "Journaled".to_string();
+        save_graph(&graph, &path).unwrap();
+        assert!(journal_path(&path).exists());
+
+        // A graph built in memory is authoritative and rewrites the snapshot
+        let mut fresh = WorkGraph::new();
+        fresh.add_node(Node::Task(make_task("t3", "Task 3")));
+        save_graph(&fresh, &path).unwrap();
+
+        assert!(!journal_path(&path).exists());
+        let reloaded = load_graph(&path).unwrap();
+        assert_eq!(reloaded.len(), 1);
+        assert!(reloaded.get_task("t3").is_some());
+    }
+
+    #[test]
+    fn test_compact_graph_folds_journal() {
+        let (_dir, path) = journal_dir(100);
+
+        let mut graph = load_graph(&path).unwrap();
+        graph.get_task_mut("t1").unwrap().title = "Compacted".to_string();
+        save_graph(&graph, &path).unwrap();
+
+        compact_graph(&path).unwrap();
+        assert!(!journal_path(&path).exists());
+        assert!(
+            std::fs::read_to_string(&path)
+                .unwrap()
+                .contains("Compacted")
+        );
+    }
+
+    fn graph_dir() -> (tempfile::TempDir, PathBuf) {
+        let dir = tempfile::TempDir::new().unwrap();
+        let path = dir.path().join("graph.jsonl");
+        let mut graph = WorkGraph::new();
+        graph.add_node(Node::Task(make_task("t1", "Task 1")));
+        graph.add_node(Node::Task(make_task("t2", "Task 2")));
+        save_graph(&graph, &path).unwrap();
+        (dir, path)
+    }
+
+    #[test]
+    fn test_save_bumps_revision_of_changed_tasks() {
+        let (_dir, path) = graph_dir();
+
+        for expected in 1..=2 {
+            let mut graph = load_graph(&path).unwrap();
+            graph.get_task_mut("t1").unwrap().title = format!("Rev {}", expected);
+            save_graph(&graph, &path).unwrap();
+
+            let reloaded = load_graph(&path).unwrap();
+            assert_eq!(reloaded.get_task("t1").unwrap().revision, expected);
+            assert_eq!(reloaded.get_task("t2").unwrap().revision, 0);
+        }
+    }
+
+    #[test]
+    fn test_save_merges_different_fields_of_same_task() {
+        let (_dir, path) = graph_dir();
+
+        // Like `wg log` and `wg artifact` racing on one task
+        let mut a = load_graph(&path).unwrap();
+        let mut b = load_graph(&path).unwrap();
+        a.get_task_mut("t1")
+            .unwrap()
+            .log
+            .push(crate::graph::LogEntry {
+                timestamp: "2026-01-01T00:00:00Z".to_string(),
+                actor: None,
+                message: "progress".to_string(),
+            });
+        b.get_task_mut("t1")
+            .unwrap()
+            .artifacts
+            .push("out.txt".to_string());
+        save_graph(&a, &path).unwrap();
+        save_graph(&b, &path).unwrap();
+
+        let task = load_graph(&path).unwrap().get_task("t1").unwrap().clone();
+        assert_eq!(task.log.len(), 1);
+        assert_eq!(task.artifacts, vec!["out.txt"]);
+        assert_eq!(task.revision, 2);
+    }
+
+    #[test]
+    fn test_save_rejects_conflicting_write() {
+        let (_dir, path) = graph_dir();
+
+        let mut a = load_graph(&path).unwrap();
+        let mut b = load_graph(&path).unwrap();
+        a.get_task_mut("t1").unwrap().title = "From A".to_string();
+        b.get_task_mut("t1").unwrap().title = "From B".to_string();
+        save_graph(&a, &path).unwrap();
+
+        let err = save_graph(&b, &path).unwrap_err();
+        assert!(matches!(err, ParseError::Conflict(ref c) if c.id == "t1"));
+        assert_eq!(
+            load_graph(&path).unwrap().get_task("t1").unwrap().title,
+            "From A"
+        );
+    }
+
+    #[test]
+    fn test_save_detects_manual_edit_between_load_and_save() {
+        let (_dir, path) = graph_dir();
+
+        let mut graph = load_graph(&path).unwrap();
+        graph.get_task_mut("t1").unwrap().title = "From wg".to_string();
+
+        // Someone edits graph.jsonl by hand without touching `revision`
+        let edited = std::fs::read_to_string(&path)
+            .unwrap()
+            .replace("Task 1", "Hand edited");
+        std::fs::write(&path, edited).unwrap();
+
+        assert!(matches!(
+            save_graph(&graph, &path),
+            Err(ParseError::Conflict(_))
+        ));
+    }
+
+    #[test]
+    fn test_expected_revision_is_checked_at_save() {
+        let (_dir, path) = graph_dir();
+
+        let mut a = load_graph(&path).unwrap();
+        let mut b = load_graph(&path).unwrap();
+        a.get_task_mut("t1").unwrap().title = "From A".to_string();
+        b.expect_revision("t1", 0);
+        b.get_task_mut("t1").unwrap().description = Some("From B".to_string());
+        save_graph(&a, &path).unwrap();
+
+        // A change that would merge cleanly still fails the revision check
+        let err = save_graph(&b, &path).unwrap_err();
+        assert!(matches!(err, ParseError::Conflict(ref c) if c.id == "t1"));
+
+        let mut c = load_graph(&path).unwrap();
+        c.expect_revision("t1", 1);
+        c.get_task_mut("t1").unwrap().description = Some("From C".to_string());
+        save_graph(&c, &path).unwrap();
+        // The expectation is spent once the save succeeds
+        c.get_task_mut("t1").unwrap().title = "Again".to_string();
+        save_graph(&c, &path).unwrap();
+        assert_eq!(
+            load_graph(&path).unwrap().get_task("t1").unwrap().revision,
+            3
+        );
+    }
+
+    #[test]
+    fn test_repeated_saves_of_one_graph_do_not_conflict() {
+        let (_dir, path) = graph_dir();
+
+        let mut graph = load_graph(&path).unwrap();
+        graph.get_task_mut("t1").unwrap().title = "First".to_string();
+        save_graph(&graph, &path).unwrap();
+        graph.get_task_mut("t1").unwrap().title = "Second".to_string();
+        save_graph(&graph, &path).unwrap();
+
+        let task = load_graph(&path).unwrap().get_task("t1").unwrap().clone();
+        assert_eq!(task.title, "Second");
+        assert_eq!(task.revision, 2);
+    }
 }
@@ -0,0 +1,93 @@
+//! Automatic transitions that follow a task changing status.
+//!
+//! Every path that finishes, fails or abandons a task runs [`propagate`]
+//! afterwards, so map expansion, joins, edge conditions and epics settle
+//! the same way whether the change came from the CLI, a gate vote or a
+//! chat command.
+
+use std::path::Path;
+
+use crate::fanout::{self, MapExpansion};
+use crate::graph::{Status, WorkGraph, abandon_unsatisfiable};
+
+/// Automatic transitions that follow a task changing status.
+#[derive(Debug, Default)]
+pub struct Propagation {
+    pub expanded: Vec<MapExpansion>,
+    pub joined: Vec<(String, Status)>,
+    pub abandoned: Vec<String>,
+    pub epics: Vec<(String, Status)>,
+}
+
+impl Propagation {
+    pub fn is_empty(&self) -> bool {
+        self.expanded.is_empty()
+            && self.joined.is_empty()
+            && self.abandoned.is_empty()
+            && self.epics.is_empty()
+    }
+
+    /// One human-readable line per transition.
+    pub fn lines(&self) -> Vec<String> {
+        let mut lines = Vec::new();
+        for expansion in &self.expanded {
+            lines.push(match expansion {
+                MapExpansion::Expanded {
+                    map_id,
+                    children,
+                    join,
+                } => format!(
+                    "Map: expanded '{}' into {} tasks (join: '{}')",
+                    map_id,
+                    children.len(),
+                    join
+                ),
+                MapExpansion::Failed { map_id, reason } => {
+                    format!("Map: '{}' failed — {}", map_id, reason)
+                }
+            });
+        }
+        for (join_id, status) in &self.joined {
+            lines.push(format!("Join: '{}' is {}", join_id, status));
+        }
+        for task_id in &self.abandoned {
+            lines.push(format!("Condition: abandoned '{}'", task_id));
+        }
+        for (epic_id, status) in &self.epics {
+            lines.push(format!("Epic: '{}' is {}", epic_id, status));
+        }
+        lines
+    }
+
+    pub fn print(&self) {
+        for line in self.lines() {
+            println!("  {}", line);
+        }
+    }
+}
+
+/// Expand map tasks whose source is done, complete joins whose children have
+/// settled, abandon tasks whose edge conditions can no longer be met, and
+/// complete epics whose children are all terminal. Each can trigger the
+/// others, so this repeats until nothing changes.
+pub fn propagate(graph: &mut WorkGraph, dir: &Path) -> Propagation {
+    let mut result = Propagation::default();
+    let retry_config = crate::config::Config::load_or_default(dir).retry;
+    loop {
+        // Failures the coordinator may still retry aren't final yet
+        let retrying = crate::retry::pending(graph, &retry_config);
+        let step = Propagation {
+            expanded: fanout::expand_map_tasks(graph, dir),
+            joined: fanout::settle_joins(graph, &retrying),
+            abandoned: abandon_unsatisfiable(graph, &retrying),
+            epics: crate::epic::settle_epics(graph, &retrying),
+        };
+        if step.is_empty() {
+            return result;
+        }
+        result.expanded.extend(step.expanded);
+        result.joined.extend(step.joined);
+        result.abandoned.extend(step.abandoned);
+        result.epics.extend(step.epics);
+    }
+}
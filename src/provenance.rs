@@ -7,6 +7,7 @@
 
 use anyhow::{Context, Result};
 use chrono::Utc;
+use serde::de::DeserializeOwned;
 use serde::{Deserialize, Serialize};
 use std::fs::{self, File, OpenOptions};
 use std::io::{BufRead, BufReader, Read, Write};
@@ -44,33 +45,12 @@ pub fn append_operation(
     entry: &OperationEntry,
     threshold: u64,
 ) -> Result<()> {
-    let dir = log_dir(workgraph_dir);
-    fs::create_dir_all(&dir).context("Failed to create log directory")?;
-
-    let path = operations_path(workgraph_dir);
-
-    // Check if rotation is needed before appending.
-    if path.exists() {
-        let meta = fs::metadata(&path).context("Failed to stat operations.jsonl")?;
-        if meta.len() >= threshold {
-            rotate(&path, &dir)?;
-        }
-    }
-
-    let mut line = serde_json::to_string(entry).context("Failed to serialize operation entry")?;
-    line.push('\n');
-
-    let mut file = OpenOptions::new()
-        .create(true)
-        .append(true)
-        .open(&path)
-        .context("Failed to open operations.jsonl for append")?;
-
-    // Single write_all call ensures atomicity on O_APPEND files for sizes < PIPE_BUF
-    file.write_all(line.as_bytes())
-        .context("Failed to write operation entry")?;
-
-    Ok(())
+    append_rotating(
+        &operations_path(workgraph_dir),
+        &log_dir(workgraph_dir),
+        entry,
+        threshold,
+    )
 }
 
 /// Record an operation using the config's rotation threshold.
@@ -93,22 +73,67 @@ pub fn record(
     append_operation(workgraph_dir, &entry, threshold)
 }
 
-/// Compress the current operations.jsonl to `<UTC-timestamp>.jsonl.zst`
+/// Append `entry` as a JSON line to the log at `path`, first rotating the
+/// log into `rotated_dir` if it has reached `threshold` bytes.
+pub fn append_rotating<T: Serialize>(
+    path: &Path,
+    rotated_dir: &Path,
+    entry: &T,
+    threshold: u64,
+) -> Result<()> {
+    let name = file_name(path);
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent).context("Failed to create log directory")?;
+    }
+
+    // Check if rotation is needed before appending.
+    if path.exists() {
+        let meta = fs::metadata(path).with_context(|| format!("Failed to stat {}", name))?;
+        if meta.len() >= threshold {
+            rotate(path, rotated_dir)?;
+        }
+    }
+
+    let mut line = serde_json::to_string(entry).context("Failed to serialize log entry")?;
+    line.push('\n');
+
+    let mut file = OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(path)
+        .with_context(|| format!("Failed to open {} for append", name))?;
+
+    // Single write_all call ensures atomicity on O_APPEND files for sizes < PIPE_BUF
+    file.write_all(line.as_bytes())
+        .context("Failed to write log entry")?;
+
+    Ok(())
+}
+
+fn file_name(path: &Path) -> String {
+    path.file_name()
+        .map(|n| n.to_string_lossy().into_owned())
+        .unwrap_or_else(|| path.display().to_string())
+}
+
+/// Compress the log at `path` to `<dir>/<UTC-timestamp>.jsonl.zst`
 /// and start a fresh file.
 fn rotate(path: &Path, dir: &Path) -> Result<()> {
+    let name = file_name(path);
+    fs::create_dir_all(dir).context("Failed to create log directory")?;
     let stamp = Utc::now().format("%Y%m%dT%H%M%S%.6fZ");
     let rotated_name = format!("{}.jsonl.zst", stamp);
     let rotated_path = dir.join(&rotated_name);
 
     // Read the current file and compress it.
-    let data = fs::read(path).context("Failed to read operations.jsonl for rotation")?;
+    let data = fs::read(path).with_context(|| format!("Failed to read {} for rotation", name))?;
 
     let compressed = zstd::encode_all(data.as_slice(), 3).context("zstd compression failed")?;
 
     fs::write(&rotated_path, compressed).context("Failed to write rotated compressed file")?;
 
     // Truncate the original to start fresh.
-    File::create(path).context("Failed to truncate operations.jsonl after rotation")?;
+    File::create(path).with_context(|| format!("Failed to truncate {} after rotation", name))?;
 
     Ok(())
 }
@@ -116,58 +141,57 @@ fn rotate(path: &Path, dir: &Path) -> Result<()> {
 /// Read all operations across rotated (compressed) and current files,
 /// returned in chronological order (oldest first).
 pub fn read_all_operations(workgraph_dir: &Path) -> Result<Vec<OperationEntry>> {
-    let dir = log_dir(workgraph_dir);
-    if !dir.exists() {
-        return Ok(Vec::new());
-    }
+    read_rotating(&operations_path(workgraph_dir), &log_dir(workgraph_dir))
+}
 
-    let mut entries = Vec::new();
+/// Where the operations log currently ends.
+pub fn operations_end(workgraph_dir: &Path) -> Result<LogPosition> {
+    end_position(&operations_path(workgraph_dir), &log_dir(workgraph_dir))
+}
 
-    // Collect rotated files, sorted by name (which is chronological).
-    let mut rotated: Vec<PathBuf> = Vec::new();
-    for entry in fs::read_dir(&dir).context("Failed to read log directory")? {
-        let entry = entry?;
-        let name = entry.file_name();
-        let name_str = name.to_string_lossy();
-        if name_str.ends_with(".jsonl.zst") {
-            rotated.push(entry.path());
-        }
-    }
-    rotated.sort();
+/// Read the operations recorded after `from`, including any rotated away
+/// since, with the position to resume from.
+pub fn read_operations_since(
+    workgraph_dir: &Path,
+    from: &LogPosition,
+) -> Result<(Vec<OperationEntry>, LogPosition)> {
+    read_rotating_since(
+        &operations_path(workgraph_dir),
+        &log_dir(workgraph_dir),
+        from,
+    )
+}
 
-    // Read rotated (compressed) files.
-    for rpath in &rotated {
-        let compressed = fs::read(rpath)
-            .with_context(|| format!("Failed to read rotated file {}", rpath.display()))?;
-        let mut decompressed = Vec::new();
-        zstd::stream::read::Decoder::new(compressed.as_slice())
-            .context("Failed to create zstd decoder")?
-            .read_to_end(&mut decompressed)
-            .context("Failed to decompress rotated file")?;
+/// Read every entry of a rotating log: the `*.jsonl.zst` files in
+/// `rotated_dir` (oldest first), then the current file at `path`.
+pub fn read_rotating<T: DeserializeOwned>(path: &Path, rotated_dir: &Path) -> Result<Vec<T>> {
+    let name = file_name(path);
+    let mut entries = Vec::new();
 
+    // Read rotated (compressed) files.
+    for rpath in &rotated_files(rotated_dir)? {
+        let decompressed = decompress(rpath)?;
         for line in decompressed.split(|&b| b == b'\n') {
             if line.is_empty() {
                 continue;
             }
-            let entry: OperationEntry = serde_json::from_slice(line).with_context(|| {
-                format!("Failed to parse operation entry from {}", rpath.display())
-            })?;
+            let entry: T = serde_json::from_slice(line)
+                .with_context(|| format!("Failed to parse log entry from {}", rpath.display()))?;
             entries.push(entry);
         }
     }
 
     // Read current (uncompressed) file.
-    let current = operations_path(workgraph_dir);
-    if current.exists() {
-        let file = File::open(&current).context("Failed to open operations.jsonl")?;
+    if path.exists() {
+        let file = File::open(path).with_context(|| format!("Failed to open {}", name))?;
         let reader = BufReader::new(file);
         for line in reader.lines() {
-            let line = line.context("Failed to read line from operations.jsonl")?;
+            let line = line.with_context(|| format!("Failed to read line from {}", name))?;
             if line.is_empty() {
                 continue;
             }
-            let entry: OperationEntry =
-                serde_json::from_str(&line).context("Failed to parse operation entry")?;
+            let entry: T = serde_json::from_str(&line)
+                .with_context(|| format!("Failed to parse log entry from {}", name))?;
             entries.push(entry);
         }
     }
@@ -175,6 +199,121 @@ pub fn read_all_operations(workgraph_dir: &Path) -> Result<Vec<OperationEntry>>
     Ok(entries)
 }
 
+/// A place in a rotating log that stays valid when it rotates: the newest
+/// rotated file at the time, and a byte offset into the current file.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct LogPosition {
+    /// File name of the newest `*.jsonl.zst` file, if there was one
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub rotated: Option<String>,
+    pub offset: u64,
+}
+
+/// Where a rotating log currently ends.
+pub fn end_position(path: &Path, rotated_dir: &Path) -> Result<LogPosition> {
+    Ok(LogPosition {
+        rotated: newest_name(&rotated_files(rotated_dir)?),
+        offset: fs::metadata(path).map(|m| m.len()).unwrap_or(0),
+    })
+}
+
+/// Read the entries appended to a rotating log after `from`, including any
+/// rotated away since, with the position to resume from. A half-written
+/// last line is left for the next read; lines that don't parse are skipped
+/// so one bad entry can't stall a reader.
+pub fn read_rotating_since<T: DeserializeOwned>(
+    path: &Path,
+    rotated_dir: &Path,
+    from: &LogPosition,
+) -> Result<(Vec<T>, LogPosition)> {
+    loop {
+        let rotated = rotated_files(rotated_dir)?;
+        let mut entries = Vec::new();
+        let mut offset = from.offset;
+        for rpath in rotated.iter().filter(|p| {
+            from.rotated
+                .as_deref()
+                .is_none_or(|r| file_name(p).as_str() > r)
+        }) {
+            let data = decompress(rpath)?;
+            entries.extend(parse_from(&data, offset).0);
+            offset = 0;
+        }
+
+        let data = match fs::read(path) {
+            Ok(data) => data,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Vec::new(),
+            Err(e) => return Err(e).with_context(|| format!("Failed to read {}", file_name(path))),
+        };
+        // A current file shorter than the position was replaced by hand
+        if offset > data.len() as u64 {
+            offset = 0;
+        }
+        let (current, end) = parse_from(&data, offset);
+
+        // The log rotated while it was being read: read it again.
+        let after = rotated_files(rotated_dir)?;
+        if after != rotated {
+            continue;
+        }
+        entries.extend(current);
+        return Ok((
+            entries,
+            LogPosition {
+                rotated: newest_name(&rotated),
+                offset: end,
+            },
+        ));
+    }
+}
+
+/// The complete lines of `data` after byte `offset`, parsed, and the offset
+/// just past the last of them.
+fn parse_from<T: DeserializeOwned>(data: &[u8], offset: u64) -> (Vec<T>, u64) {
+    let start = (offset as usize).min(data.len());
+    let end = data[start..]
+        .iter()
+        .rposition(|&b| b == b'\n')
+        .map_or(start, |i| start + i + 1);
+    let entries = data[start..end]
+        .split(|&b| b == b'\n')
+        .filter(|line| !line.is_empty())
+        .filter_map(|line| serde_json::from_slice(line).ok())
+        .collect();
+    (entries, end as u64)
+}
+
+/// The `*.jsonl.zst` files in `dir`, oldest first.
+fn rotated_files(dir: &Path) -> Result<Vec<PathBuf>> {
+    let mut rotated: Vec<PathBuf> = Vec::new();
+    if dir.exists() {
+        for entry in fs::read_dir(dir).context("Failed to read log directory")? {
+            let entry = entry?;
+            if entry.file_name().to_string_lossy().ends_with(".jsonl.zst") {
+                rotated.push(entry.path());
+            }
+        }
+    }
+    // Names are timestamps, so sorting by name is chronological.
+    rotated.sort();
+    Ok(rotated)
+}
+
+fn newest_name(rotated: &[PathBuf]) -> Option<String> {
+    rotated.last().map(|p| file_name(p))
+}
+
+fn decompress(path: &Path) -> Result<Vec<u8>> {
+    let compressed = fs::read(path)
+        .with_context(|| format!("Failed to read rotated file {}", path.display()))?;
+    let mut decompressed = Vec::new();
+    zstd::stream::read::Decoder::new(compressed.as_slice())
+        .context("Failed to create zstd decoder")?
+        .read_to_end(&mut decompressed)
+        .context("Failed to decompress rotated file")?;
+    Ok(decompressed)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -323,4 +462,39 @@ mod tests {
         let all = read_all_operations(&dir).unwrap();
         assert_eq!(all.len(), 3);
     }
+
+    #[test]
+    fn test_read_since_survives_rotation() {
+        let tmp = TempDir::new().unwrap();
+        let dir = tmp.path().join(".workgraph");
+        let threshold = 200u64;
+        for i in 0..3 {
+            append_operation(
+                &dir,
+                &make_entry("before", Some(&format!("b{}", i))),
+                threshold,
+            )
+            .unwrap();
+        }
+        let from = operations_end(&dir).unwrap();
+
+        // Enough to rotate several times, the first time part-way through
+        for i in 0..20 {
+            append_operation(
+                &dir,
+                &make_entry("after", Some(&format!("a{}", i))),
+                threshold,
+            )
+            .unwrap();
+        }
+        let (ops, position) = read_operations_since(&dir, &from).unwrap();
+        let ids: Vec<String> = ops.into_iter().filter_map(|op| op.task_id).collect();
+        let expected: Vec<String> = (0..20).map(|i| format!("a{}", i)).collect();
+        assert_eq!(ids, expected);
+        assert!(position.rotated.is_some());
+        assert_eq!(position, operations_end(&dir).unwrap());
+
+        let (ops, _) = read_operations_since(&dir, &position).unwrap();
+        assert!(ops.is_empty());
+    }
 }
@@ -1,4 +1,5 @@
-use crate::graph::{CycleAnalysis, Status, Task, WorkGraph};
+use crate::epic::epic_ids;
+use crate::graph::{CycleAnalysis, EdgeCondition, Status, Task, WorkGraph};
 use chrono::{DateTime, Utc};
 use serde::Serialize;
 use std::collections::{HashMap, HashSet};
@@ -195,7 +196,7 @@ where
                 completed_in_plan.contains(blocker_id.as_str())
                     || graph
                         .get_task(blocker_id)
-                        .map(|t| t.status.is_terminal())
+                        .map(|t| task.is_after_satisfied(blocker_id, t.status))
                         .unwrap_or(true)
             });
 
@@ -246,6 +247,7 @@ pub fn build_reverse_index(graph: &WorkGraph) -> HashMap<String, Vec<String>> {
 
 /// Find all tasks that are ready to work on (no open blockers, past not_before)
 pub fn ready_tasks(graph: &WorkGraph) -> Vec<&Task> {
+    let epics = epic_ids(graph);
     graph
         .tasks()
         .filter(|task| {
@@ -257,27 +259,35 @@ pub fn ready_tasks(graph: &WorkGraph) -> Vec<&Task> {
             if task.paused {
                 return false;
             }
+            // Epics complete through their children, never by dispatch;
+            // schedule templates only run through their copies
+            if epics.contains(task.id.as_str()) || task.is_schedule_template() {
+                return false;
+            }
             // Must be past not_before timestamp
             if !is_time_ready(task) {
                 return false;
             }
-            // All blockers must be terminal (done, failed, or abandoned)
+            // All blockers must be terminal (done, failed, or abandoned),
+            // or in the outcome their edge condition requires
             task.after.iter().all(|blocker_id| {
                 graph
                     .get_task(blocker_id)
-                    .map(|t| t.status.is_terminal())
+                    .map(|t| task.is_after_satisfied(blocker_id, t.status))
                     .unwrap_or(true) // If blocker doesn't exist, treat as unblocked
             })
         })
         .collect()
 }
 
-/// Check whether a single after dependency is satisfied (terminal).
+/// Check whether a single after dependency is satisfied: terminal, or in the
+/// outcome `condition` requires.
 ///
 /// Handles both local and remote (`peer:task-id`) references.
 /// For remote refs, resolves via federation config using IPC or direct file access.
 pub fn is_blocker_satisfied(
     blocker_id: &str,
+    condition: EdgeCondition,
     graph: &WorkGraph,
     workgraph_dir: Option<&Path>,
 ) -> bool {
@@ -287,12 +297,12 @@ pub fn is_blocker_satisfied(
             return false; // Can't resolve without workgraph dir; treat as blocked
         };
         let remote = crate::federation::resolve_remote_task_status(peer_name, remote_task_id, wg_dir);
-        remote.status.is_terminal()
+        condition.is_satisfied_by(remote.status)
     } else {
         // Local dependency
         graph
             .get_task(blocker_id)
-            .map(|t| t.status.is_terminal())
+            .map(|t| condition.is_satisfied_by(t.status))
             .unwrap_or(true) // If blocker doesn't exist, treat as unblocked
     }
 }
@@ -303,21 +313,27 @@ pub fn is_blocker_satisfied(
 /// should use this version so that tasks blocked by remote `peer:task-id`
 /// references are correctly resolved.
 pub fn ready_tasks_with_peers<'a>(graph: &'a WorkGraph, workgraph_dir: &Path) -> Vec<&'a Task> {
+    let epics = epic_ids(graph);
     graph
         .tasks()
         .filter(|task| {
             if task.status != Status::Open {
                 return false;
             }
-            if task.paused {
+            if task.paused || epics.contains(task.id.as_str()) || task.is_schedule_template() {
                 return false;
             }
             if !is_time_ready(task) {
                 return false;
             }
-            task.after
-                .iter()
-                .all(|blocker_id| is_blocker_satisfied(blocker_id, graph, Some(workgraph_dir)))
+            task.after.iter().all(|blocker_id| {
+                is_blocker_satisfied(
+                    blocker_id,
+                    task.after_condition(blocker_id),
+                    graph,
+                    Some(workgraph_dir),
+                )
+            })
         })
         .collect()
 }
@@ -332,23 +348,24 @@ pub fn ready_tasks_cycle_aware<'a>(
     graph: &'a WorkGraph,
     cycle_analysis: &CycleAnalysis,
 ) -> Vec<&'a Task> {
+    let epics = epic_ids(graph);
     graph
         .tasks()
         .filter(|task| {
             if task.status != Status::Open {
                 return false;
             }
-            if task.paused {
+            if task.paused || epics.contains(task.id.as_str()) || task.is_schedule_template() {
                 return false;
             }
             if !is_time_ready(task) {
                 return false;
             }
             task.after.iter().all(|blocker_id| {
-                // Normal check: predecessor is terminal
+                // Normal check: predecessor is terminal (or meets the edge condition)
                 if graph
                     .get_task(blocker_id)
-                    .map(|t| t.status.is_terminal())
+                    .map(|t| task.is_after_satisfied(blocker_id, t.status))
                     .unwrap_or(true)
                 {
                     return true;
@@ -374,20 +391,26 @@ pub fn ready_tasks_with_peers_cycle_aware<'a>(
     workgraph_dir: &Path,
     cycle_analysis: &CycleAnalysis,
 ) -> Vec<&'a Task> {
+    let epics = epic_ids(graph);
     graph
         .tasks()
         .filter(|task| {
             if task.status != Status::Open {
                 return false;
             }
-            if task.paused {
+            if task.paused || epics.contains(task.id.as_str()) || task.is_schedule_template() {
                 return false;
             }
             if !is_time_ready(task) {
                 return false;
             }
             task.after.iter().all(|blocker_id| {
-                if is_blocker_satisfied(blocker_id, graph, Some(workgraph_dir)) {
+                if is_blocker_satisfied(
+                    blocker_id,
+                    task.after_condition(blocker_id),
+                    graph,
+                    Some(workgraph_dir),
+                ) {
                     return true;
                 }
                 // Cycle-aware: the task with cycle_config is the user-designated
@@ -412,7 +435,7 @@ pub fn after<'a>(graph: &'a WorkGraph, task_id: &str) -> Vec<&'a Task> {
     task.after
         .iter()
         .filter_map(|id| graph.get_task(id))
-        .filter(|t| !t.status.is_terminal())
+        .filter(|t| !task.is_after_satisfied(&t.id, t.status))
         .collect()
 }
 
@@ -522,6 +545,38 @@ mod tests {
         assert_eq!(ready[0].id, "blocked");
     }
 
+    #[test]
+    fn test_ready_tasks_respects_edge_conditions() {
+        let mut graph = WorkGraph::new();
+
+        let mut build = make_task("build", "Build");
+        build.status = Status::Failed;
+
+        let mut deploy = make_task("deploy", "Deploy");
+        deploy.after = vec!["build".to_string()];
+        deploy
+            .after_conditions
+            .insert("build".to_string(), EdgeCondition::Success);
+
+        let mut rollback = make_task("rollback", "Rollback");
+        rollback.after = vec!["build".to_string()];
+        rollback
+            .after_conditions
+            .insert("build".to_string(), EdgeCondition::Failure);
+
+        let mut report = make_task("report", "Report");
+        report.after = vec!["build".to_string()];
+
+        graph.add_node(Node::Task(build));
+        graph.add_node(Node::Task(deploy));
+        graph.add_node(Node::Task(rollback));
+        graph.add_node(Node::Task(report));
+
+        let mut ready: Vec<&str> = ready_tasks(&graph).iter().map(|t| t.id.as_str()).collect();
+        ready.sort();
+        assert_eq!(ready, vec!["report", "rollback"]);
+    }
+
     #[test]
     fn test_after_returns_blockers() {
         let mut graph = WorkGraph::new();
@@ -1680,7 +1735,12 @@ mod tests {
         t.status = Status::Done;
         graph.add_node(Node::Task(t));
 
-        assert!(is_blocker_satisfied("blocker", &graph, None));
+        assert!(is_blocker_satisfied(
+            "blocker",
+            EdgeCondition::Any,
+            &graph,
+            None
+        ));
     }
 
     #[test]
@@ -1689,19 +1749,34 @@ mod tests {
         let t = make_task("blocker", "Blocker");
         graph.add_node(Node::Task(t));
 
-        assert!(!is_blocker_satisfied("blocker", &graph, None));
+        assert!(!is_blocker_satisfied(
+            "blocker",
+            EdgeCondition::Any,
+            &graph,
+            None
+        ));
     }
 
     #[test]
     fn is_blocker_satisfied_local_missing_treated_as_unblocked() {
         let graph = WorkGraph::new();
-        assert!(is_blocker_satisfied("nonexistent", &graph, None));
+        assert!(is_blocker_satisfied(
+            "nonexistent",
+            EdgeCondition::Any,
+            &graph,
+            None
+        ));
     }
 
     #[test]
     fn is_blocker_satisfied_remote_ref_without_dir() {
         let graph = WorkGraph::new();
         // Remote ref without workgraph_dir → treated as blocked
-        assert!(!is_blocker_satisfied("peer:task-id", &graph, None));
+        assert!(!is_blocker_satisfied(
+            "peer:task-id",
+            EdgeCondition::Any,
+            &graph,
+            None
+        ));
     }
 }
@@ -0,0 +1,308 @@
+//! Automatic retries for failed tasks.
+//!
+//! A failed task with retries left (`retry_count < max_retries`) is classified
+//! once per failure. Transient failures (rate limits, network trouble,
+//! timeouts) are re-opened with an exponential backoff written into
+//! `ready_after`; permanent ones stay failed. Each decision is kept in the
+//! task's `retry_history`.
+
+use chrono::{DateTime, Duration, Utc};
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+use std::collections::hash_map::RandomState;
+use std::hash::{BuildHasher, Hash, Hasher};
+
+use crate::config::RetryConfig;
+use crate::graph::{LogEntry, RetryRecord, Status, Task, WorkGraph};
+
+/// Whether a failure is worth retrying.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum FailureClass {
+    Transient,
+    #[default]
+    Permanent,
+}
+
+impl std::fmt::Display for FailureClass {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            FailureClass::Transient => write!(f, "transient"),
+            FailureClass::Permanent => write!(f, "permanent"),
+        }
+    }
+}
+
+/// A classified failure and the rule that classified it.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Classification {
+    pub class: FailureClass,
+    pub rule: String,
+}
+
+impl Classification {
+    pub fn new(class: FailureClass, rule: impl Into<String>) -> Self {
+        Self {
+            class,
+            rule: rule.into(),
+        }
+    }
+}
+
+/// Exit code from a wrapper failure reason such as "Agent exited with code 2".
+pub fn exit_code(reason: &str) -> Option<i32> {
+    let (_, rest) = reason.split_once("exited with code ")?;
+    let digits: String = rest
+        .chars()
+        .take_while(|c| c.is_ascii_digit() || *c == '-')
+        .collect();
+    digits.parse().ok()
+}
+
+/// Classify a failure by its reason.
+///
+/// Rules are tried in order: permanent exit codes, transient exit codes,
+/// permanent patterns, transient patterns. Returns `Ok(None)` if no rule
+/// matches and an error if a pattern is not a valid regex.
+pub fn classify(
+    config: &RetryConfig,
+    reason: Option<&str>,
+) -> Result<Option<Classification>, String> {
+    let reason = reason.unwrap_or_default();
+    if let Some(code) = exit_code(reason) {
+        if config.permanent_exit_codes.contains(&code) {
+            return Ok(Some(Classification::new(
+                FailureClass::Permanent,
+                format!("exit code {}", code),
+            )));
+        }
+        if config.transient_exit_codes.contains(&code) {
+            return Ok(Some(Classification::new(
+                FailureClass::Transient,
+                format!("exit code {}", code),
+            )));
+        }
+    }
+    for (patterns, class) in [
+        (&config.permanent_patterns, FailureClass::Permanent),
+        (&config.transient_patterns, FailureClass::Transient),
+    ] {
+        for pattern in patterns {
+            let re = Regex::new(pattern)
+                .map_err(|e| format!("invalid retry pattern '{}': {}", pattern, e))?;
+            if re.is_match(reason) {
+                return Ok(Some(Classification::new(
+                    class,
+                    format!("pattern '{}'", pattern),
+                )));
+            }
+        }
+    }
+    Ok(None)
+}
+
+/// Backoff before retry number `attempt` (1-based): `base * multiplier^(attempt-1)`,
+/// spread by `jitter * sample` (`sample` in -1.0..=1.0) and capped at `cap`.
+pub fn backoff(config: &RetryConfig, attempt: u32, sample: f64) -> Duration {
+    let exponent = attempt.saturating_sub(1).min(64) as i32;
+    let cap = config.cap as f64;
+    let delay = (config.base as f64 * config.multiplier.max(1.0).powi(exponent)).min(cap);
+    let jitter = config.jitter.clamp(0.0, 1.0) * sample.clamp(-1.0, 1.0);
+    let secs = (delay * (1.0 + jitter)).clamp(0.0, cap);
+    Duration::milliseconds((secs * 1000.0) as i64)
+}
+
+/// A random number in -1.0..=1.0 for [`backoff`], different per task.
+pub fn jitter_sample(task_id: &str) -> f64 {
+    let mut hasher = RandomState::new().build_hasher();
+    task_id.hash(&mut hasher);
+    Utc::now().timestamp_nanos_opt().hash(&mut hasher);
+    (hasher.finish() as f64 / u64::MAX as f64) * 2.0 - 1.0
+}
+
+/// Whether a failed task has retries left and its latest failure has not
+/// been classified yet. Tasks without `max_retries` are never retried
+/// automatically, and neither are rejected gates.
+pub fn awaiting_decision(task: &Task) -> bool {
+    task.status == Status::Failed
+        && !task.paused
+        && task.gate.is_none()
+        && task.max_retries.is_some_and(|max| task.retry_count < max)
+        && task
+            .retry_history
+            .last()
+            .is_none_or(|r| r.attempt != task.retry_count)
+}
+
+/// Failed tasks the coordinator will classify, and may re-open, on its next
+/// tick. Until then their failure is not final: dependents, epics and joins
+/// must not settle on it. Empty when automatic retries are off. Epics are
+/// never retried.
+pub fn pending(graph: &WorkGraph, config: &RetryConfig) -> HashSet<String> {
+    if !config.auto {
+        return HashSet::new();
+    }
+    let epics = crate::epic::epic_ids(graph);
+    graph
+        .tasks()
+        .filter(|t| awaiting_decision(t) && !epics.contains(t.id.as_str()))
+        .map(|t| t.id.clone())
+        .collect()
+}
+
+/// Record a classified failure on `task`. A transient failure re-opens the
+/// task with a backoff; a permanent one leaves it failed.
+pub fn apply(
+    task: &mut Task,
+    classification: &Classification,
+    config: &RetryConfig,
+    now: DateTime<Utc>,
+    sample: f64,
+) -> RetryRecord {
+    let transient = classification.class == FailureClass::Transient;
+    let next_attempt = transient.then(|| now + backoff(config, task.retry_count, sample));
+    let record = RetryRecord {
+        attempt: task.retry_count,
+        decided_at: now.to_rfc3339(),
+        failure_reason: task.failure_reason.clone(),
+        transient,
+        rule: classification.rule.clone(),
+        next_attempt: next_attempt.map(|t| t.to_rfc3339()),
+    };
+
+    let message = match next_attempt {
+        Some(at) => {
+            task.status = Status::Open;
+            task.assigned = None;
+            task.failure_reason = None;
+            task.ready_after = Some(at.to_rfc3339());
+            task.tags.retain(|t| t != "converged");
+            format!(
+                "Automatic retry #{} after {} ({} failure, {})",
+                task.retry_count + 1,
+                crate::format_duration((at - now).num_seconds(), false),
+                classification.class,
+                classification.rule
+            )
+        }
+        None => format!(
+            "Not retrying: {} failure ({})",
+            classification.class, classification.rule
+        ),
+    };
+    task.log.push(LogEntry {
+        timestamp: now.to_rfc3339(),
+        actor: Some("coordinator".to_string()),
+        message,
+    });
+    task.retry_history.push(record.clone());
+    record
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn failed(reason: &str, retry_count: u32, max_retries: Option<u32>) -> Task {
+        Task {
+            id: "t".to_string(),
+            status: Status::Failed,
+            failure_reason: Some(reason.to_string()),
+            retry_count,
+            max_retries,
+            ..Task::default()
+        }
+    }
+
+    #[test]
+    fn test_classify_rules_in_order() {
+        let mut config = RetryConfig {
+            transient_exit_codes: vec![75],
+            permanent_exit_codes: vec![2],
+            permanent_patterns: vec!["(?i)syntax error".to_string()],
+            ..RetryConfig::default()
+        };
+        let class = |reason: &str, config: &RetryConfig| {
+            classify(config, Some(reason)).unwrap().map(|c| c.class)
+        };
+
+        assert_eq!(exit_code("Agent exited with code 75"), Some(75));
+        assert_eq!(
+            class("Agent exited with code 75", &config),
+            Some(FailureClass::Transient)
+        );
+        // Exit codes win over patterns
+        assert_eq!(
+            class("Agent exited with code 2: rate limited", &config),
+            Some(FailureClass::Permanent)
+        );
+        assert_eq!(
+            class("Syntax error after rate limit", &config),
+            Some(FailureClass::Permanent)
+        );
+        assert_eq!(
+            class("API returned 429 Too Many Requests", &config),
+            Some(FailureClass::Transient)
+        );
+        assert_eq!(class("timeout", &config), Some(FailureClass::Transient));
+        assert_eq!(class("tests failed", &config), None);
+
+        config.permanent_patterns = vec!["(".to_string()];
+        assert!(classify(&config, Some("x")).is_err());
+    }
+
+    #[test]
+    fn test_backoff_grows_and_caps() {
+        let config = RetryConfig {
+            base: 10,
+            multiplier: 3.0,
+            jitter: 0.5,
+            cap: 100,
+            ..RetryConfig::default()
+        };
+        assert_eq!(backoff(&config, 1, 0.0), Duration::seconds(10));
+        assert_eq!(backoff(&config, 2, 0.0), Duration::seconds(30));
+        assert_eq!(backoff(&config, 3, 0.0), Duration::seconds(90));
+        assert_eq!(backoff(&config, 4, 0.0), Duration::seconds(100));
+        assert_eq!(backoff(&config, 2, 1.0), Duration::seconds(45));
+        assert_eq!(backoff(&config, 2, -1.0), Duration::seconds(15));
+        assert_eq!(backoff(&config, 40, 1.0), Duration::seconds(100));
+        let sample = jitter_sample("t");
+        assert!((-1.0..=1.0).contains(&sample));
+    }
+
+    #[test]
+    fn test_apply_reopens_transient_once_per_failure() {
+        let config = RetryConfig::default();
+        let now: DateTime<Utc> = "2026-03-01T12:00:00Z".parse().unwrap();
+        let mut task = failed("rate limit exceeded", 1, Some(3));
+        assert!(awaiting_decision(&task));
+
+        let c = classify(&config, task.failure_reason.as_deref())
+            .unwrap()
+            .unwrap();
+        let record = apply(&mut task, &c, &config, now, 0.0);
+        assert!(record.transient);
+        assert_eq!(task.status, Status::Open);
+        assert_eq!(task.failure_reason, None);
+        assert_eq!(
+            task.ready_after.as_deref(),
+            Some("2026-03-01T12:00:30+00:00")
+        );
+        assert_eq!(task.retry_history.len(), 1);
+
+        // Second failure: decided again, this time permanent
+        task.status = Status::Failed;
+        task.retry_count = 2;
+        task.failure_reason = Some("tests failed".to_string());
+        assert!(awaiting_decision(&task));
+        let c = Classification::new(FailureClass::Permanent, "default");
+        apply(&mut task, &c, &config, now, 0.0);
+        assert_eq!(task.status, Status::Failed);
+        assert!(!awaiting_decision(&task));
+
+        assert!(!awaiting_decision(&failed("x", 3, Some(3))));
+        assert!(!awaiting_decision(&failed("x", 1, None)));
+    }
+}
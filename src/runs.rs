@@ -9,6 +9,8 @@ use serde::{Deserialize, Serialize};
 use std::fs;
 use std::path::{Path, PathBuf};
 
+use crate::store::StorageBackend;
+
 /// Metadata for a single run snapshot.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct RunMeta {
@@ -67,9 +69,20 @@ pub fn snapshot(
     let dest = run_dir(workgraph_dir, run_id);
     fs::create_dir_all(&dest).context("Failed to create run directory")?;
 
-    // Copy graph.jsonl
+    // Copy graph.jsonl (folding any pending journal entries in first).
+    // SQLite-backed graphs are exported to JSONL so snapshots stay portable.
     let graph_src = workgraph_dir.join("graph.jsonl");
-    if graph_src.exists() {
+    if graph_src.exists()
+        && crate::store::configured_backend(workgraph_dir)? == StorageBackend::Sqlite
+    {
+        let mut graph =
+            crate::parser::load_graph(&graph_src).context("Failed to load graph for snapshot")?;
+        graph.untrack_changes();
+        crate::parser::save_jsonl(&graph, dest.join("graph.jsonl"))
+            .context("Failed to write graph.jsonl to snapshot")?;
+    } else if graph_src.exists() {
+        crate::parser::compact_graph(&graph_src)
+            .context("Failed to compact graph journal before snapshot")?;
         fs::copy(&graph_src, dest.join("graph.jsonl"))
             .context("Failed to copy graph.jsonl to snapshot")?;
     }
@@ -129,7 +142,19 @@ pub fn restore_graph(workgraph_dir: &Path, run_id: &str) -> Result<()> {
         anyhow::bail!("Snapshot graph.jsonl not found for run '{}'", run_id);
     }
     let dest = workgraph_dir.join("graph.jsonl");
+    if crate::store::configured_backend(workgraph_dir)? == StorageBackend::Sqlite {
+        let mut graph = crate::parser::load_jsonl(&src).context("Failed to read snapshot graph")?;
+        graph.untrack_changes();
+        crate::parser::save_graph(&graph, &dest)
+            .context("Failed to restore graph from snapshot")?;
+        return Ok(());
+    }
     fs::copy(&src, &dest).context("Failed to restore graph.jsonl from snapshot")?;
+    // Journal entries belong to the state being replaced
+    let journal = crate::parser::journal_path(&dest);
+    if journal.exists() {
+        fs::remove_file(&journal).context("Failed to remove graph journal")?;
+    }
     Ok(())
 }
 
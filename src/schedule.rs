@@ -0,0 +1,928 @@
+//! Recurring tasks.
+//!
+//! A task with a [`Schedule`] is a template. Each coordinator tick calls
+//! [`fire_schedules`], which works out which schedule windows have passed
+//! since the last tick and, per window, creates a fresh copy of the template
+//! (`<template>-<YYYYMMDD-HHMM>`). A scheduled task that owns a cycle config
+//! re-opens its cycle instead, once the previous run has finished.
+//!
+//! Expressions use the classic five cron fields, `minute hour day-of-month
+//! month day-of-week`, with `*`, lists, ranges, steps and month/day names.
+//! As in cron, when both day fields are restricted a day matching either one
+//! fires. The macros `@hourly`, `@daily`, `@weekly`, `@monthly` and `@yearly`
+//! are accepted, as is `@every <duration>` for fixed intervals aligned to the
+//! Unix epoch.
+
+use chrono::{DateTime, Datelike, Duration, NaiveDate, TimeZone, Timelike, Utc};
+use chrono_tz::Tz;
+use std::fmt;
+
+use crate::graph::{CatchUp, LogEntry, Node, Schedule, Status, Task, WorkGraph, parse_delay};
+
+/// Windows fired at most per schedule and tick under [`CatchUp::All`].
+pub const MAX_CATCH_UP: usize = 50;
+
+/// How far ahead to search for a matching time (covers Feb 29 schedules).
+const SEARCH_DAYS: u32 = 366 * 8;
+
+/// Upper bound on windows enumerated for one schedule in one tick.
+const MAX_WINDOWS: usize = 100_000;
+
+/// A parsed schedule expression.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Cron {
+    /// Five-field cron expression; each field is a bitset of allowed values
+    Fields {
+        minutes: u64,
+        hours: u32,
+        days: u32,
+        months: u16,
+        weekdays: u8,
+        /// Day-of-month field was `*`
+        any_day: bool,
+        /// Day-of-week field was `*`
+        any_weekday: bool,
+    },
+    /// Fixed interval in seconds
+    Every(i64),
+}
+
+const MONTHS: [&str; 12] = [
+    "jan", "feb", "mar", "apr", "may", "jun", "jul", "aug", "sep", "oct", "nov", "dec",
+];
+const WEEKDAYS: [&str; 7] = ["sun", "mon", "tue", "wed", "thu", "fri", "sat"];
+
+impl Cron {
+    /// Parse a cron expression, macro, or `@every <duration>`.
+    pub fn parse(expr: &str) -> Result<Cron, String> {
+        let expr = expr.trim();
+        let expanded = match expr.to_ascii_lowercase().as_str() {
+            "@yearly" | "@annually" => "0 0 1 1 *".to_string(),
+            "@monthly" => "0 0 1 * *".to_string(),
+            "@weekly" => "0 0 * * 0".to_string(),
+            "@daily" | "@midnight" => "0 0 * * *".to_string(),
+            "@hourly" => "0 * * * *".to_string(),
+            lower => {
+                if let Some(interval) = lower.strip_prefix("@every") {
+                    let secs = parse_delay(interval.trim())
+                        .ok_or_else(|| format!("invalid interval '{}'", interval.trim()))?;
+                    if secs < 60 {
+                        return Err("intervals must be at least 1m".to_string());
+                    }
+                    let secs =
+                        i64::try_from(secs).map_err(|_| "interval is too long".to_string())?;
+                    return Ok(Cron::Every(secs));
+                }
+                if lower.starts_with('@') {
+                    return Err(format!("unknown macro '{}'", expr));
+                }
+                expr.to_string()
+            }
+        };
+
+        let fields: Vec<&str> = expanded.split_whitespace().collect();
+        let [minute, hour, day, month, weekday] = fields[..] else {
+            return Err(format!(
+                "expected 5 fields (minute hour day-of-month month day-of-week), got {}",
+                fields.len()
+            ));
+        };
+        let minutes = parse_field(minute, 0, 59, &[], "minute")?;
+        let hours = parse_field(hour, 0, 23, &[], "hour")?;
+        let days = parse_field(day, 1, 31, &[], "day-of-month")?;
+        let months = parse_field(month, 1, 12, &MONTHS, "month")?;
+        // 7 is an alias for Sunday
+        let mut weekdays = parse_field(weekday, 0, 7, &WEEKDAYS, "day-of-week")?;
+        if weekdays & (1 << 7) != 0 {
+            weekdays = (weekdays | 1) & !(1 << 7);
+        }
+        Ok(Cron::Fields {
+            minutes,
+            hours: hours as u32,
+            days: days as u32,
+            months: months as u16,
+            weekdays: weekdays as u8,
+            any_day: day == "*",
+            any_weekday: weekday == "*",
+        })
+    }
+
+    /// The first matching time strictly after `after`, evaluated in `tz`.
+    ///
+    /// Local times skipped by a daylight-saving jump never fire; local times
+    /// that occur twice fire on the first occurrence only.
+    pub fn next_after(&self, after: DateTime<Utc>, tz: Tz) -> Option<DateTime<Utc>> {
+        let (minutes, hours, days, months, weekdays, any_day, any_weekday) = match *self {
+            Cron::Every(secs) => {
+                let next = (after.timestamp().div_euclid(secs) + 1) * secs;
+                return DateTime::from_timestamp(next, 0);
+            }
+            Cron::Fields {
+                minutes,
+                hours,
+                days,
+                months,
+                weekdays,
+                any_day,
+                any_weekday,
+            } => (minutes, hours, days, months, weekdays, any_day, any_weekday),
+        };
+
+        let start = after.with_timezone(&tz);
+        let mut date = start.date_naive();
+        for day_offset in 0..SEARCH_DAYS {
+            let first_day = day_offset == 0;
+            if day_matches(date, days, months, weekdays, any_day, any_weekday) {
+                for hour in (0..24).filter(|h| hours & (1 << h) != 0) {
+                    if first_day && hour < start.hour() {
+                        continue;
+                    }
+                    for minute in (0..60).filter(|m| minutes & (1 << m) != 0) {
+                        if first_day && hour == start.hour() && minute < start.minute() {
+                            continue;
+                        }
+                        let Some(local) = date.and_hms_opt(hour, minute, 0) else {
+                            continue;
+                        };
+                        let Some(time) = tz.from_local_datetime(&local).earliest() else {
+                            continue;
+                        };
+                        let time = time.with_timezone(&Utc);
+                        if time > after {
+                            return Some(time);
+                        }
+                    }
+                }
+            }
+            date = date.succ_opt()?;
+        }
+        None
+    }
+}
+
+fn day_matches(
+    date: NaiveDate,
+    days: u32,
+    months: u16,
+    weekdays: u8,
+    any_day: bool,
+    any_weekday: bool,
+) -> bool {
+    if months & (1 << date.month()) == 0 {
+        return false;
+    }
+    let day_ok = days & (1 << date.day()) != 0;
+    let weekday_ok = weekdays & (1 << date.weekday().num_days_from_sunday()) != 0;
+    if any_day || any_weekday {
+        day_ok && weekday_ok
+    } else {
+        day_ok || weekday_ok
+    }
+}
+
+/// Parse one cron field into a bitset with bit `n` set for each allowed value.
+fn parse_field(
+    field: &str,
+    min: u32,
+    max: u32,
+    names: &[&str],
+    label: &str,
+) -> Result<u64, String> {
+    let value = |s: &str| -> Result<u32, String> {
+        let lower = s.to_ascii_lowercase();
+        if let Some(pos) = names.iter().position(|n| *n == lower) {
+            // Month names start at 1, weekday names at 0
+            return Ok(pos as u32 + min);
+        }
+        let n: u32 = s
+            .parse()
+            .map_err(|_| format!("invalid {} value '{}'", label, s))?;
+        if n < min || n > max {
+            return Err(format!(
+                "{} value {} is out of range {}-{}",
+                label, n, min, max
+            ));
+        }
+        Ok(n)
+    };
+
+    let mut bits = 0u64;
+    for part in field.split(',') {
+        let (range, step) = match part.split_once('/') {
+            Some((range, step)) => {
+                let step: u32 = step
+                    .parse()
+                    .ok()
+                    .filter(|s| *s > 0)
+                    .ok_or_else(|| format!("invalid {} step '{}'", label, step))?;
+                (range, step)
+            }
+            None => (part, 1),
+        };
+        let (lo, hi) = if range == "*" {
+            (min, max)
+        } else if let Some((lo, hi)) = range.split_once('-') {
+            (value(lo)?, value(hi)?)
+        } else {
+            let lo = value(range)?;
+            // `5/15` means every 15 starting at 5
+            (lo, if part.contains('/') { max } else { lo })
+        };
+        if lo > hi {
+            return Err(format!("invalid {} range '{}'", label, range));
+        }
+        for n in (lo..=hi).step_by(step as usize) {
+            bits |= 1 << n;
+        }
+    }
+    Ok(bits)
+}
+
+/// Look up an IANA timezone name; `None` means UTC.
+pub fn parse_timezone(name: Option<&str>) -> Result<Tz, String> {
+    match name {
+        None => Ok(Tz::UTC),
+        Some(name) => name
+            .parse::<Tz>()
+            .map_err(|_| format!("unknown timezone '{}'", name)),
+    }
+}
+
+/// Check that a schedule's expression and timezone are valid.
+pub fn validate(schedule: &Schedule) -> Result<(), String> {
+    Cron::parse(&schedule.cron)?;
+    parse_timezone(schedule.timezone.as_deref())?;
+    Ok(())
+}
+
+/// The next `count` fire times strictly after `after`.
+pub fn upcoming(
+    schedule: &Schedule,
+    after: DateTime<Utc>,
+    count: usize,
+) -> Result<Vec<DateTime<Utc>>, String> {
+    let cron = Cron::parse(&schedule.cron)?;
+    let tz = parse_timezone(schedule.timezone.as_deref())?;
+    let mut times = Vec::with_capacity(count);
+    let mut cursor = after;
+    while times.len() < count {
+        let Some(next) = cron.next_after(cursor, tz) else {
+            break;
+        };
+        times.push(next);
+        cursor = next;
+    }
+    Ok(times)
+}
+
+/// Format a fire time in the schedule's timezone, e.g. `2026-03-02 09:00 CET`.
+pub fn format_time(schedule: &Schedule, time: DateTime<Utc>) -> String {
+    let tz = parse_timezone(schedule.timezone.as_deref()).unwrap_or(Tz::UTC);
+    time.with_timezone(&tz)
+        .format("%Y-%m-%d %H:%M %Z")
+        .to_string()
+}
+
+/// Whether a task's schedule fires at all: paused and abandoned templates
+/// keep their schedule but do not run.
+pub fn is_active(task: &Task) -> bool {
+    task.schedule.is_some() && !task.paused && task.status != Status::Abandoned
+}
+
+/// What a schedule did during [`fire_schedules`].
+#[derive(Debug, Clone, PartialEq)]
+pub enum Firing {
+    /// A fresh copy of the template was created for a window.
+    Created {
+        template: String,
+        window: String,
+        task: String,
+    },
+    /// The template's cycle was re-opened for a window.
+    Reopened {
+        template: String,
+        window: String,
+        tasks: Vec<String>,
+    },
+    /// Windows passed without firing.
+    Skipped {
+        template: String,
+        windows: usize,
+        reason: String,
+    },
+    /// The schedule could not be evaluated.
+    Invalid { template: String, reason: String },
+}
+
+impl fmt::Display for Firing {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Firing::Created {
+                template,
+                window,
+                task,
+            } => write!(
+                f,
+                "Schedule '{}': created '{}' for {}",
+                template, task, window
+            ),
+            Firing::Reopened {
+                template,
+                window,
+                tasks,
+            } => write!(
+                f,
+                "Schedule '{}': re-opened cycle ({}) for {}",
+                template,
+                tasks.join(", "),
+                window
+            ),
+            Firing::Skipped {
+                template,
+                windows,
+                reason,
+            } => write!(
+                f,
+                "Schedule '{}': skipped {} window(s): {}",
+                template, windows, reason
+            ),
+            Firing::Invalid { template, reason } => {
+                write!(f, "Schedule '{}' is invalid: {}", template, reason)
+            }
+        }
+    }
+}
+
+/// Fire every active schedule whose windows have passed by `now`.
+///
+/// Windows are counted from the schedule's `last_window`, or from the
+/// template's creation for a schedule that has never run. Under
+/// [`CatchUp::Skip`], a window fires only if it is at most `grace` old, so a
+/// daemon that was down does not replay history. Cycle templates fire at most
+/// once per call whatever the policy, since a cycle cannot run twice at once.
+pub fn fire_schedules(graph: &mut WorkGraph, now: DateTime<Utc>, grace: Duration) -> Vec<Firing> {
+    let mut templates: Vec<(String, Schedule, Option<String>)> = graph
+        .tasks()
+        .filter(|t| is_active(t))
+        .filter_map(|t| {
+            let schedule = t.schedule.clone()?;
+            Some((t.id.clone(), schedule, t.created_at.clone()))
+        })
+        .collect();
+    templates.sort_by(|a, b| a.0.cmp(&b.0));
+
+    let mut results = Vec::new();
+    for (template, schedule, created_at) in templates {
+        let (cron, tz) = match Cron::parse(&schedule.cron)
+            .and_then(|cron| Ok((cron, parse_timezone(schedule.timezone.as_deref())?)))
+        {
+            Ok(parsed) => parsed,
+            Err(reason) => {
+                results.push(Firing::Invalid { template, reason });
+                continue;
+            }
+        };
+
+        // Without a record of when the schedule started, look back one grace
+        // period; the first window found is persisted as `last_window`.
+        let anchor = schedule
+            .last_window
+            .as_deref()
+            .or(created_at.as_deref())
+            .and_then(|s| s.parse::<DateTime<Utc>>().ok())
+            .unwrap_or(now - grace);
+
+        let mut windows = Vec::new();
+        let mut cursor = anchor;
+        while let Some(next) = cron.next_after(cursor, tz) {
+            if next > now || windows.len() >= MAX_WINDOWS {
+                break;
+            }
+            windows.push(next);
+            cursor = next;
+        }
+        let Some(&latest) = windows.last() else {
+            continue;
+        };
+        set_last_window(graph, &template, latest);
+
+        let cycle = graph
+            .get_task(&template)
+            .is_some_and(|t| t.cycle_config.is_some());
+        let to_fire: Vec<DateTime<Utc>> = match schedule.catch_up {
+            CatchUp::Skip if now - latest > grace => vec![],
+            CatchUp::All if !cycle => {
+                windows[windows.len().saturating_sub(MAX_CATCH_UP)..].to_vec()
+            }
+            _ => vec![latest],
+        };
+        if to_fire.len() < windows.len() {
+            let reason = match schedule.catch_up {
+                CatchUp::Skip => "missed while the service was not running".to_string(),
+                CatchUp::Once => "catch-up policy runs missed windows once".to_string(),
+                CatchUp::All if cycle => "a cycle runs once per catch-up".to_string(),
+                CatchUp::All => format!("at most {} windows are caught up", MAX_CATCH_UP),
+            };
+            results.push(Firing::Skipped {
+                template: template.clone(),
+                windows: windows.len() - to_fire.len(),
+                reason,
+            });
+        }
+
+        for window in to_fire {
+            let label = format_time(&schedule, window);
+            let firing = if cycle {
+                reopen_cycle(graph, &template, &label)
+            } else {
+                let task = instantiate(graph, &template, &schedule, window, &label);
+                Firing::Created {
+                    template: template.clone(),
+                    window: label,
+                    task,
+                }
+            };
+            results.push(firing);
+        }
+    }
+    results
+}
+
+fn set_last_window(graph: &mut WorkGraph, template: &str, window: DateTime<Utc>) {
+    if let Some(schedule) = graph
+        .get_task_mut(template)
+        .and_then(|t| t.schedule.as_mut())
+    {
+        schedule.last_window = Some(window.to_rfc3339());
+    }
+}
+
+/// Create a copy of `template_id` for `window`. Returns the new task's ID.
+fn instantiate(
+    graph: &mut WorkGraph,
+    template_id: &str,
+    schedule: &Schedule,
+    window: DateTime<Utc>,
+    label: &str,
+) -> String {
+    let Some(template) = graph.get_task(template_id).cloned() else {
+        return String::new();
+    };
+    let tz = parse_timezone(schedule.timezone.as_deref()).unwrap_or(Tz::UTC);
+    let base = format!(
+        "{}-{}",
+        template_id,
+        window.with_timezone(&tz).format("%Y%m%d-%H%M")
+    );
+    let mut id = base.clone();
+    let mut n = 2;
+    while graph.get_node(&id).is_some() {
+        id = format!("{}-{}", base, n);
+        n += 1;
+    }
+
+    // Everything the template says about the work carries over; only the
+    // state of a run starts fresh.
+    let now = Utc::now().to_rfc3339();
+    let mut task = template;
+    task.id = id.clone();
+    task.status = Status::Open;
+    task.assigned = None;
+    task.before = vec![];
+    task.artifacts = vec![];
+    task.outputs = Default::default();
+    task.created_at = Some(now.clone());
+    task.started_at = None;
+    task.completed_at = None;
+    task.log = vec![LogEntry {
+        timestamp: now,
+        actor: None,
+        message: format!("Created by schedule of '{}' for {}", template_id, label),
+    }];
+    task.retry_count = 0;
+    task.retry_history = vec![];
+    task.failure_reason = None;
+    task.loop_iteration = 0;
+    task.schedule = None;
+    if let Some(gate) = task.gate.as_mut() {
+        gate.votes = vec![];
+        gate.requested_at = None;
+    }
+    task.ready_after = None;
+    task.revision = 0;
+    for dep in &task.after {
+        if let Some(blocker) = graph.get_task_mut(dep)
+            && !blocker.before.contains(&id)
+        {
+            blocker.before.push(id.clone());
+        }
+    }
+    graph.add_node(Node::Task(task));
+    id
+}
+
+/// Re-open the cycle owned by `template_id` if its previous run has finished.
+fn reopen_cycle(graph: &mut WorkGraph, template_id: &str, label: &str) -> Firing {
+    let analysis = graph.compute_cycle_analysis();
+    let members: Vec<String> = match analysis.task_to_cycle.get(template_id) {
+        Some(&idx) => analysis.cycles[idx].members.clone(),
+        None => vec![template_id.to_string()],
+    };
+    if let Some(running) = members
+        .iter()
+        .find(|id| graph.get_task(id).is_some_and(|t| !t.status.is_terminal()))
+    {
+        return Firing::Skipped {
+            template: template_id.to_string(),
+            windows: 1,
+            reason: format!(
+                "previous run is still going ('{}' is not finished)",
+                running
+            ),
+        };
+    }
+
+    let now = Utc::now().to_rfc3339();
+    for id in &members {
+        if let Some(task) = graph.get_task_mut(id) {
+            task.status = Status::Open;
+            task.assigned = None;
+            task.started_at = None;
+            task.completed_at = None;
+            task.failure_reason = None;
+            task.ready_after = None;
+            task.loop_iteration = 0;
+            task.tags.retain(|t| t != "converged");
+            task.log.push(LogEntry {
+                timestamp: now.clone(),
+                actor: None,
+                message: format!("Re-opened by schedule of '{}' for {}", template_id, label),
+            });
+        }
+    }
+    Firing::Reopened {
+        template: template_id.to_string(),
+        window: label.to_string(),
+        tasks: members,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::graph::{
+        CycleConfig, EdgeCondition, Estimate, Gate, GateVote, MapSpec, OutputContract, RetryRecord,
+    };
+    use std::collections::BTreeMap;
+
+    fn utc(s: &str) -> DateTime<Utc> {
+        s.parse().unwrap()
+    }
+
+    fn next(expr: &str, tz: Option<&str>, after: &str) -> String {
+        let cron = Cron::parse(expr).unwrap();
+        let tz = parse_timezone(tz).unwrap();
+        cron.next_after(utc(after), tz).unwrap().to_rfc3339()
+    }
+
+    fn template(id: &str, cron: &str, catch_up: CatchUp, last_window: &str) -> Task {
+        Task {
+            id: id.to_string(),
+            title: format!("Run {}", id),
+            tags: vec!["report".to_string()],
+            schedule: Some(Schedule {
+                cron: cron.to_string(),
+                timezone: None,
+                catch_up,
+                last_window: Some(last_window.to_string()),
+            }),
+            ..Task::default()
+        }
+    }
+
+    #[test]
+    fn test_parse_fields() {
+        assert!(Cron::parse("0 9 * * 1-5").is_ok());
+        assert!(Cron::parse("*/15 0-6,22,23 1 JAN,jul sun").is_ok());
+        assert!(Cron::parse("@daily").is_ok());
+        assert_eq!(Cron::parse("@every 6h").unwrap(), Cron::Every(6 * 3600));
+        assert!(
+            Cron::parse("0 9 * *")
+                .unwrap_err()
+                .contains("expected 5 fields")
+        );
+        assert!(
+            Cron::parse("60 * * * *")
+                .unwrap_err()
+                .contains("out of range")
+        );
+        assert!(Cron::parse("0 9 * * 5-1").is_err());
+        assert!(Cron::parse("*/0 * * * *").is_err());
+        assert!(Cron::parse("@every 10s").is_err());
+        assert!(Cron::parse("@fortnightly").is_err());
+        assert!(parse_timezone(Some("Mars/Olympus")).is_err());
+    }
+
+    #[test]
+    fn test_next_after() {
+        // 2026-03-06 is a Friday: weekdays at 09:00 skip to Monday
+        assert_eq!(
+            next("0 9 * * 1-5", None, "2026-03-06T09:00:00Z"),
+            "2026-03-09T09:00:00+00:00"
+        );
+        assert_eq!(
+            next("*/20 * * * *", None, "2026-03-06T10:41:30Z"),
+            "2026-03-06T11:00:00+00:00"
+        );
+        assert_eq!(
+            next("@every 6h", None, "2026-03-06T13:00:00Z"),
+            "2026-03-06T18:00:00+00:00"
+        );
+        // Day-of-month OR day-of-week when both are restricted
+        assert_eq!(
+            next("0 0 13 * 5", None, "2026-03-06T00:00:00Z"),
+            "2026-03-13T00:00:00+00:00"
+        );
+        assert_eq!(
+            next("0 0 29 2 *", None, "2026-03-01T00:00:00Z"),
+            "2028-02-29T00:00:00+00:00"
+        );
+        // 09:00 in Berlin is 08:00 UTC in winter and 07:00 UTC in summer
+        assert_eq!(
+            next("0 9 * * *", Some("Europe/Berlin"), "2026-03-27T12:00:00Z"),
+            "2026-03-28T08:00:00+00:00"
+        );
+        assert_eq!(
+            next("0 9 * * *", Some("Europe/Berlin"), "2026-03-28T12:00:00Z"),
+            "2026-03-29T07:00:00+00:00"
+        );
+        // 02:30 does not exist in Berlin on 2026-03-29
+        assert_eq!(
+            next("30 2 * * *", Some("Europe/Berlin"), "2026-03-28T12:00:00Z"),
+            "2026-03-30T00:30:00+00:00"
+        );
+    }
+
+    #[test]
+    fn test_fire_schedules_catch_up_policies() {
+        let mut graph = WorkGraph::new();
+        graph.add_node(Node::Task(template(
+            "skip",
+            "0 * * * *",
+            CatchUp::Skip,
+            "2026-03-06T00:00:00Z",
+        )));
+        graph.add_node(Node::Task(template(
+            "once",
+            "0 * * * *",
+            CatchUp::Once,
+            "2026-03-06T00:00:00Z",
+        )));
+        graph.add_node(Node::Task(template(
+            "all",
+            "0 * * * *",
+            CatchUp::All,
+            "2026-03-06T00:00:00Z",
+        )));
+
+        let now = utc("2026-03-06T03:30:00Z");
+        let results = fire_schedules(&mut graph, now, Duration::minutes(2));
+        let created: Vec<String> = results
+            .iter()
+            .filter_map(|r| match r {
+                Firing::Created { task, .. } => Some(task.clone()),
+                _ => None,
+            })
+            .collect();
+        assert_eq!(
+            created,
+            vec![
+                "all-20260306-0100",
+                "all-20260306-0200",
+                "all-20260306-0300",
+                "once-20260306-0300",
+            ]
+        );
+        assert!(results.contains(&Firing::Skipped {
+            template: "skip".to_string(),
+            windows: 3,
+            reason: "missed while the service was not running".to_string(),
+        }));
+
+        let instance = graph.get_task("once-20260306-0300").unwrap();
+        assert_eq!(instance.title, "Run once");
+        assert_eq!(instance.tags, vec!["report"]);
+        assert!(instance.schedule.is_none());
+        assert_eq!(
+            graph
+                .get_task("skip")
+                .unwrap()
+                .schedule
+                .as_ref()
+                .unwrap()
+                .last_window
+                .as_deref(),
+            Some("2026-03-06T03:00:00+00:00")
+        );
+
+        // A window that is due now fires even under skip; nothing fires twice
+        let results = fire_schedules(
+            &mut graph,
+            utc("2026-03-06T04:01:00Z"),
+            Duration::minutes(2),
+        );
+        assert_eq!(results.len(), 3);
+        assert!(graph.get_task("skip-20260306-0400").is_some());
+        assert!(
+            fire_schedules(
+                &mut graph,
+                utc("2026-03-06T04:02:00Z"),
+                Duration::minutes(2)
+            )
+            .is_empty()
+        );
+    }
+
+    #[test]
+    fn test_fire_schedules_reopens_finished_cycle() {
+        let mut graph = WorkGraph::new();
+        let mut write = template("write", "@daily", CatchUp::Once, "2026-03-05T00:00:00Z");
+        write.status = Status::Done;
+        write.after = vec!["review".to_string()];
+        write.loop_iteration = 2;
+        write.tags.push("converged".to_string());
+        write.cycle_config = Some(CycleConfig {
+            max_iterations: 3,
+            guard: None,
+            delay: None,
+        });
+        let review = Task {
+            id: "review".to_string(),
+            status: Status::InProgress,
+            after: vec!["write".to_string()],
+            ..Task::default()
+        };
+        graph.add_node(Node::Task(write));
+        graph.add_node(Node::Task(review));
+
+        let results = fire_schedules(
+            &mut graph,
+            utc("2026-03-06T00:10:00Z"),
+            Duration::minutes(2),
+        );
+        assert!(
+            matches!(&results[..], [Firing::Skipped { reason, .. }] if reason.contains("'review'"))
+        );
+
+        graph.get_task_mut("review").unwrap().status = Status::Done;
+        let results = fire_schedules(
+            &mut graph,
+            utc("2026-03-07T00:10:00Z"),
+            Duration::minutes(2),
+        );
+        assert!(matches!(&results[..], [Firing::Reopened { tasks, .. }] if tasks.len() == 2));
+        let write = graph.get_task("write").unwrap();
+        assert_eq!(write.status, Status::Open);
+        assert_eq!(write.loop_iteration, 0);
+        assert!(!write.tags.contains(&"converged".to_string()));
+        assert_eq!(graph.get_task("review").unwrap().status, Status::Open);
+    }
+
+    #[test]
+    fn test_instances_carry_every_template_field() {
+        // Set every field (no `..Task::default()`), so a new field has to be
+        // added here and is then checked to reach the instance.
+        let schedule = Schedule {
+            cron: "@daily".to_string(),
+            timezone: None,
+            catch_up: CatchUp::Once,
+            last_window: Some("2026-03-05T00:00:00Z".to_string()),
+        };
+        let template = Task {
+            id: "report".to_string(),
+            title: "Daily report".to_string(),
+            description: Some("Summarize the day".to_string()),
+            status: Status::Done,
+            assigned: Some("agent-1".to_string()),
+            priority: 5,
+            requester: Some("ops".to_string()),
+            estimate: Some(Estimate {
+                hours: Some(1.0),
+                cost: Some(2.0),
+            }),
+            before: vec!["publish".to_string()],
+            after: vec!["fetch".to_string()],
+            after_conditions: BTreeMap::from([("fetch".to_string(), EdgeCondition::Success)]),
+            requires: vec!["gpu".to_string()],
+            requires_amounts: BTreeMap::from([("gpu".to_string(), 2.0)]),
+            tags: vec!["report".to_string()],
+            skills: vec!["writing".to_string()],
+            inputs: vec!["data.csv".to_string()],
+            deliverables: vec!["report.md".to_string()],
+            artifacts: vec!["old-report.md".to_string()],
+            outputs: BTreeMap::from([("pages".to_string(), serde_json::json!(3))]),
+            exec: Some("make report".to_string()),
+            not_before: Some("2026-01-01T00:00:00Z".to_string()),
+            created_at: Some("2026-01-01T00:00:00Z".to_string()),
+            started_at: Some("2026-03-05T00:01:00Z".to_string()),
+            completed_at: Some("2026-03-05T00:02:00Z".to_string()),
+            log: vec![LogEntry {
+                timestamp: "2026-03-05T00:02:00Z".to_string(),
+                actor: None,
+                message: "done".to_string(),
+            }],
+            retry_count: 1,
+            max_retries: Some(3),
+            retry_history: vec![RetryRecord {
+                attempt: 1,
+                decided_at: "2026-03-05T00:01:30Z".to_string(),
+                failure_reason: Some("rate limit".to_string()),
+                transient: true,
+                rule: "pattern 'rate.?limit'".to_string(),
+                next_attempt: None,
+            }],
+            timeout: Some("30m".to_string()),
+            failure_reason: Some("rate limit".to_string()),
+            model: Some("opus".to_string()),
+            verify: Some("Numbers add up".to_string()),
+            agent: Some("abc123".to_string()),
+            loop_iteration: 2,
+            cycle_config: Some(CycleConfig {
+                max_iterations: 3,
+                guard: None,
+                delay: None,
+            }),
+            map: Some(MapSpec {
+                source: "fetch".to_string(),
+                from: "items.json".to_string(),
+                template: serde_json::from_value(serde_json::json!({
+                    "template_id": "item",
+                    "title": "Item {{item}}",
+                    "description": "",
+                }))
+                .unwrap(),
+                children: vec![],
+                join: None,
+            }),
+            parent: Some("reporting".to_string()),
+            schedule: Some(schedule.clone()),
+            gate: Some(Gate {
+                approvers: vec!["alice".to_string()],
+                quorum: 1,
+                votes: vec![GateVote {
+                    actor: "alice".to_string(),
+                    approved: true,
+                    at: "2026-03-05T00:01:00Z".to_string(),
+                    reason: None,
+                }],
+                requested_at: Some("2026-03-05T00:00:30Z".to_string()),
+            }),
+            contract: Some(OutputContract {
+                files: vec!["report.md".to_string()],
+                ..OutputContract::default()
+            }),
+            ready_after: Some("2026-03-05T00:03:00Z".to_string()),
+            paused: true,
+            visibility: "public".to_string(),
+            revision: 7,
+        };
+        let mut graph = WorkGraph::new();
+        graph.add_node(Node::Task(template.clone()));
+
+        let id = instantiate(
+            &mut graph,
+            "report",
+            &schedule,
+            utc("2026-03-06T00:00:00Z"),
+            "2026-03-06 00:00 UTC",
+        );
+        let instance = graph.get_task(&id).unwrap();
+        let expected = Task {
+            id: "report-20260306-0000".to_string(),
+            status: Status::Open,
+            assigned: None,
+            before: vec![],
+            artifacts: vec![],
+            outputs: BTreeMap::new(),
+            created_at: instance.created_at.clone(),
+            started_at: None,
+            completed_at: None,
+            log: instance.log.clone(),
+            retry_count: 0,
+            retry_history: vec![],
+            failure_reason: None,
+            loop_iteration: 0,
+            schedule: None,
+            gate: Some(Gate {
+                votes: vec![],
+                requested_at: None,
+                ..template.gate.clone().unwrap()
+            }),
+            ready_after: None,
+            revision: 0,
+            ..template
+        };
+        assert_eq!(instance, &expected);
+        assert_eq!(instance.log.len(), 1);
+    }
+}
@@ -5,12 +5,13 @@
 
 use anyhow::{Context, Result, anyhow};
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
 use std::fs;
 use std::path::{Path, PathBuf};
 
 use crate::agency;
-use crate::graph::Task;
+use crate::bindings;
+use crate::graph::{Task, WorkGraph};
 
 /// Template variables that can be used in executor configurations.
 #[derive(Debug, Clone)]
@@ -24,6 +25,8 @@ pub struct TemplateVars {
     pub skills_preamble: String,
     pub model: String,
     pub task_loop_info: String,
+    /// Values of the task's `{{deps.<id>.outputs.<key>}}` bindings, by placeholder
+    pub bindings: BTreeMap<String, String>,
 }
 
 impl TemplateVars {
@@ -86,9 +89,22 @@ impl TemplateVars {
             skills_preamble,
             model: task.model.clone().unwrap_or_default(),
             task_loop_info,
+            bindings: BTreeMap::new(),
         }
     }
 
+    /// Resolve the task's input bindings against the graph, returning the
+    /// ones that have no value.
+    pub fn resolve_bindings(
+        &mut self,
+        graph: &WorkGraph,
+        task: &Task,
+    ) -> Vec<bindings::Unresolved> {
+        let (values, unresolved) = bindings::resolve(graph, task);
+        self.bindings = values;
+        unresolved
+    }
+
     /// Resolve the identity prompt for a task by looking up its Agent, then the
     /// Agent's role and motivation.
     fn resolve_identity(task: &Task, workgraph_dir: Option<&Path>) -> String {
@@ -202,7 +218,7 @@ impl TemplateVars {
 
     /// Apply template substitution to a string.
     pub fn apply(&self, template: &str) -> String {
-        template
+        let text = template
             .replace("{{task_id}}", &self.task_id)
             .replace("{{task_title}}", &self.task_title)
             .replace("{{task_description}}", &self.task_description)
@@ -211,7 +227,15 @@ impl TemplateVars {
             .replace("{{working_dir}}", &self.working_dir)
             .replace("{{skills_preamble}}", &self.skills_preamble)
             .replace("{{model}}", &self.model)
-            .replace("{{task_loop_info}}", &self.task_loop_info)
+            .replace("{{task_loop_info}}", &self.task_loop_info);
+        bindings::apply(&text, &self.bindings)
+    }
+
+    /// Substitute resolved input bindings only into a shell command, such as
+    /// a task's `exec`, as references to environment variables that must be
+    /// set when running it. The other variables don't apply to it.
+    pub fn apply_bindings_to_command(&self, command: &str) -> bindings::BoundCommand {
+        bindings::apply_to_command(command, &self.bindings)
     }
 }
 
@@ -394,9 +418,11 @@ You MUST use these commands to track your work:
    wg log {{task_id}} "Completed X, now working on Y"
    ```
 
-2. **Record artifacts** if you create/modify files:
+2. **Record artifacts** if you create/modify files, and publish values
+   later tasks need as outputs:
    ```bash
    wg artifact {{task_id}} path/to/file
+   wg output set {{task_id}} key=value
    ```
 
 3. **Complete the task** when done:
@@ -502,9 +528,11 @@ You MUST use these commands to track your work:
    wg log {{task_id}} "Completed X, now working on Y"
    ```
 
-2. **Record artifacts** if you create/modify files:
+2. **Record artifacts** if you create/modify files, and publish values
+   later tasks need as outputs:
    ```bash
    wg artifact {{task_id}} path/to/file
+   wg output set {{task_id}} key=value
    ```
 
 3. **Complete the task** when done:
@@ -534,7 +562,7 @@ You MUST use these commands to track your work:
 Begin working on the task now."#.to_string(),
                     }),
                     working_dir: Some("{{working_dir}}".to_string()),
-                    timeout: Some(600),
+                    timeout: None,
                     model: None,
                 },
             }),
@@ -596,33 +624,8 @@ mod tests {
             title: title.to_string(),
             description: Some("Test description".to_string()),
             status: crate::graph::Status::Open,
-            assigned: None,
-            estimate: None,
-            before: vec![],
-            after: vec![],
-            requires: vec![],
-            tags: vec![],
-            skills: vec![],
-            inputs: vec![],
-            deliverables: vec![],
-            artifacts: vec![],
-            exec: None,
-            not_before: None,
-            created_at: None,
-            started_at: None,
-            completed_at: None,
-            log: vec![],
-            retry_count: 0,
-            max_retries: None,
-            failure_reason: None,
-            model: None,
-            verify: None,
-            agent: None,
-            loop_iteration: 0,
-            ready_after: None,
-            paused: false,
             visibility: "internal".to_string(),
-            cycle_config: None,
+            ..Task::default()
         }
     }
 
@@ -640,6 +643,35 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_template_vars_resolve_bindings() {
+        let mut build = make_test_task("build", "Build");
+        build.status = crate::graph::Status::Done;
+        build
+            .outputs
+            .insert("binary_path".to_string(), "target/release/wg".into());
+        let mut task = make_test_task("deploy", "Deploy");
+        task.after = vec!["build".to_string()];
+        task.description = Some("Ship {{deps.build.outputs.binary_path}}".to_string());
+        task.exec = Some("scp {{deps.build.outputs.binary_path}} host:".to_string());
+        let mut graph = WorkGraph::new();
+        graph.add_node(crate::graph::Node::Task(build));
+        graph.add_node(crate::graph::Node::Task(task.clone()));
+
+        let mut vars = TemplateVars::from_task(&task, None, None);
+        assert!(vars.resolve_bindings(&graph, &task).is_empty());
+        assert_eq!(vars.apply("{{task_description}}"), "Ship target/release/wg");
+        let bound = vars.apply_bindings_to_command(task.exec.as_deref().unwrap());
+        assert_eq!(bound.command, "scp \"${WG_DEP_BUILD_BINARY_PATH}\" host:");
+        assert_eq!(
+            bound.env,
+            vec![(
+                "WG_DEP_BUILD_BINARY_PATH".to_string(),
+                "target/release/wg".to_string()
+            )]
+        );
+    }
+
     #[test]
     fn test_template_vars_from_task() {
         let task = make_test_task("my-task", "My Title");
@@ -0,0 +1,71 @@
+//! Minimal client for the service daemon's Unix socket.
+//!
+//! The daemon and its request types live in the CLI. Library code that needs
+//! to ask the daemon for something (the Matrix listener pausing the
+//! coordinator or killing an agent) sends the request as plain JSON instead.
+
+use anyhow::Result;
+use std::path::Path;
+
+/// Send `request` (e.g. `{"cmd": "pause"}`) to the running service and return
+/// the response data, or the daemon's error.
+#[cfg(unix)]
+pub fn request(workgraph_dir: &Path, request: &serde_json::Value) -> Result<serde_json::Value> {
+    use anyhow::Context;
+    use std::io::{BufRead, BufReader, Write};
+    use std::os::unix::net::UnixStream;
+    use std::time::Duration;
+
+    let state_path = workgraph_dir.join("service").join("state.json");
+    let state: serde_json::Value = match std::fs::read_to_string(&state_path) {
+        Ok(content) => serde_json::from_str(&content)
+            .with_context(|| format!("Failed to parse service state from {:?}", state_path))?,
+        Err(_) => anyhow::bail!("Service not running"),
+    };
+    let socket = state
+        .get("socket_path")
+        .and_then(|s| s.as_str())
+        .context("Service state has no socket_path")?;
+
+    let mut stream =
+        UnixStream::connect(socket).map_err(|_| anyhow::anyhow!("Service not running"))?;
+    stream.set_read_timeout(Some(Duration::from_secs(30)))?;
+    stream.set_write_timeout(Some(Duration::from_secs(5)))?;
+    writeln!(stream, "{}", request)?;
+    stream.flush()?;
+
+    for line in BufReader::new(&stream).lines() {
+        let line = line.context("Failed to read response")?;
+        if line.is_empty() {
+            continue;
+        }
+        let mut response: serde_json::Value =
+            serde_json::from_str(&line).context("Failed to parse response")?;
+        if response.get("ok").and_then(|ok| ok.as_bool()) != Some(true) {
+            let error = response
+                .get("error")
+                .and_then(|e| e.as_str())
+                .unwrap_or("Unknown error");
+            anyhow::bail!("{}", error);
+        }
+        if let Some(map) = response.as_object_mut() {
+            map.remove("ok");
+        }
+        return Ok(response);
+    }
+    anyhow::bail!("No response from service")
+}
+
+/// Best-effort notice to the running service that the graph changed, so the
+/// coordinator ticks now rather than at its next poll.
+pub fn notify_graph_changed(workgraph_dir: &Path) {
+    let _ = request(
+        workgraph_dir,
+        &serde_json::json!({ "cmd": "graph_changed" }),
+    );
+}
+
+#[cfg(not(unix))]
+pub fn request(_workgraph_dir: &Path, _request: &serde_json::Value) -> Result<serde_json::Value> {
+    anyhow::bail!("IPC is only supported on Unix systems")
+}
@@ -5,8 +5,10 @@
 //! This module includes:
 //! - Executor configuration for spawning agents
 //! - Agent registry for tracking running agents
+//! - A minimal IPC client for the service daemon
 
 pub mod executor;
+pub mod ipc;
 pub mod registry;
 
 pub use executor::{
@@ -38,6 +38,8 @@ pub enum AgentStatus {
     Failed,
     /// Agent is dead (no heartbeat)
     Dead,
+    /// Agent was killed for exceeding its task timeout
+    TimedOut,
 }
 
 /// Entry for a single agent in the registry
@@ -62,6 +64,9 @@ pub struct AgentEntry {
     /// Model used for this agent (e.g., "anthropic/claude-opus-4-6")
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub model: Option<String>,
+    /// Wall-clock limit in seconds, enforced by the coordinator
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub timeout_secs: Option<u64>,
 }
 
 impl AgentEntry {
@@ -305,6 +310,7 @@ impl AgentRegistry {
             status: AgentStatus::Working,
             output_file: output_file.to_string(),
             model: model.map(std::string::ToString::to_string),
+            timeout_secs: None,
         };
 
         self.agents.insert(agent_id.clone(), entry);
@@ -0,0 +1,235 @@
+//! Pluggable graph storage.
+//!
+//! Commands read and write the work graph through the [`GraphStore`] trait.
+//! Two backends exist, selected by `[storage] backend` in config.toml:
+//!
+//! - `jsonl` — `.workgraph/graph.jsonl` (plus the optional journal), the default
+//! - `sqlite` — `.workgraph/graph.db`, an embedded SQLite database with
+//!   indexed tasks, edges and log entries (requires the `sqlite` feature)
+//!
+//! Whole-graph loads are always available. The query methods have default
+//! implementations that load the full graph; backends with indexes override
+//! them to avoid deserializing every task.
+
+#[cfg(feature = "sqlite")]
+pub mod sqlite;
+
+use anyhow::{Context, Result};
+use std::path::{Path, PathBuf};
+
+use crate::config::Config;
+use crate::graph::{Status, Task, WorkGraph};
+use crate::parser;
+use crate::query;
+
+/// Available storage backends.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum StorageBackend {
+    Jsonl,
+    Sqlite,
+}
+
+impl StorageBackend {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            StorageBackend::Jsonl => "jsonl",
+            StorageBackend::Sqlite => "sqlite",
+        }
+    }
+
+    /// File (relative to the workgraph directory) that holds the graph.
+    pub fn file_name(&self) -> &'static str {
+        match self {
+            StorageBackend::Jsonl => "graph.jsonl",
+            StorageBackend::Sqlite => "graph.db",
+        }
+    }
+}
+
+impl std::str::FromStr for StorageBackend {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s {
+            "jsonl" => Ok(StorageBackend::Jsonl),
+            "sqlite" => Ok(StorageBackend::Sqlite),
+            other => anyhow::bail!(
+                "Unknown storage backend '{}'. Valid values: jsonl, sqlite",
+                other
+            ),
+        }
+    }
+}
+
+impl std::fmt::Display for StorageBackend {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// Persistent storage for a work graph.
+pub trait GraphStore {
+    /// Which backend this is.
+    fn backend(&self) -> StorageBackend;
+
+    /// Path of the file or database holding the graph.
+    fn location(&self) -> &Path;
+
+    /// Load the complete graph.
+    fn load(&self) -> Result<WorkGraph>;
+
+    /// Persist the graph. Graphs loaded from this store only write the nodes
+    /// that changed; graphs built in memory replace the stored contents.
+    fn save(&self, graph: &WorkGraph) -> Result<()>;
+
+    /// Look up a single task.
+    fn get_task(&self, id: &str) -> Result<Option<Task>> {
+        Ok(self.load()?.get_task(id).cloned())
+    }
+
+    /// All tasks, optionally restricted to one status.
+    fn list_tasks(&self, status: Option<Status>) -> Result<Vec<Task>> {
+        let graph = self.load()?;
+        Ok(graph
+            .tasks()
+            .filter(|t| status.is_none_or(|s| t.status == s))
+            .cloned()
+            .collect())
+    }
+
+    /// A subgraph holding `id`, the tasks it comes after, and the tasks that
+    /// come after it — everything `wg show` needs.
+    fn load_neighborhood(&self, id: &str) -> Result<WorkGraph> {
+        let _ = id;
+        self.load()
+    }
+
+    /// A subgraph holding every open task plus the tasks they come after.
+    /// Sufficient to decide readiness of every open task.
+    fn load_frontier(&self) -> Result<WorkGraph> {
+        self.load()
+    }
+
+    /// Like [`load_frontier`](Self::load_frontier), but for every unfinished
+    /// task rather than only open ones: enough to rank ready tasks the way
+    /// the coordinator does, since only unfinished dependents raise a
+    /// task's priority or lengthen its critical path.
+    fn load_unfinished(&self) -> Result<WorkGraph> {
+        self.load()
+    }
+
+    /// Tasks that are ready to work on, with cycle-aware back-edge exemption.
+    fn ready_tasks(&self) -> Result<Vec<Task>> {
+        let graph = self.load()?;
+        let cycle_analysis = graph.compute_cycle_analysis();
+        Ok(query::ready_tasks_cycle_aware(&graph, &cycle_analysis)
+            .into_iter()
+            .cloned()
+            .collect())
+    }
+}
+
+/// The default store: `graph.jsonl`, read and written through the parser.
+pub struct JsonlStore {
+    path: PathBuf,
+}
+
+impl JsonlStore {
+    pub fn new(path: impl Into<PathBuf>) -> Self {
+        Self { path: path.into() }
+    }
+}
+
+impl GraphStore for JsonlStore {
+    fn backend(&self) -> StorageBackend {
+        StorageBackend::Jsonl
+    }
+
+    fn location(&self) -> &Path {
+        &self.path
+    }
+
+    fn load(&self) -> Result<WorkGraph> {
+        parser::load_jsonl(&self.path)
+            .with_context(|| format!("Failed to load {}", self.path.display()))
+    }
+
+    fn save(&self, graph: &WorkGraph) -> Result<()> {
+        parser::save_jsonl(graph, &self.path)
+            .with_context(|| format!("Failed to save {}", self.path.display()))
+    }
+}
+
+/// The backend configured for `workgraph_dir` (`[storage] backend`).
+pub fn configured_backend(workgraph_dir: &Path) -> Result<StorageBackend> {
+    let config = Config::load_merged(workgraph_dir).unwrap_or_default();
+    config.storage.backend.parse()
+}
+
+/// Open the configured store for a workgraph directory.
+pub fn open(workgraph_dir: &Path) -> Result<Box<dyn GraphStore>> {
+    open_backend(workgraph_dir, configured_backend(workgraph_dir)?)
+}
+
+/// Open a specific backend in a workgraph directory, regardless of config.
+pub fn open_backend(workgraph_dir: &Path, backend: StorageBackend) -> Result<Box<dyn GraphStore>> {
+    let path = workgraph_dir.join(backend.file_name());
+    match backend {
+        StorageBackend::Jsonl => Ok(Box::new(JsonlStore::new(path))),
+        #[cfg(feature = "sqlite")]
+        StorageBackend::Sqlite => Ok(Box::new(sqlite::SqliteStore::open(path)?)),
+        #[cfg(not(feature = "sqlite"))]
+        StorageBackend::Sqlite => anyhow::bail!(
+            "The sqlite storage backend is not available: wg was built without the 'sqlite' feature"
+        ),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::graph::Node;
+    use tempfile::TempDir;
+
+    fn make_task(id: &str, status: Status, after: &[&str]) -> Task {
+        Task {
+            id: id.to_string(),
+            title: format!("Task {}", id),
+            status,
+            after: after.iter().map(|s| s.to_string()).collect(),
+            ..Task::default()
+        }
+    }
+
+    #[test]
+    fn test_backend_parse_roundtrip() {
+        for backend in [StorageBackend::Jsonl, StorageBackend::Sqlite] {
+            assert_eq!(backend.as_str().parse::<StorageBackend>().unwrap(), backend);
+        }
+        assert!("postgres".parse::<StorageBackend>().is_err());
+    }
+
+    #[test]
+    fn test_jsonl_store_default_queries() {
+        let tmp = TempDir::new().unwrap();
+        let store = open(tmp.path()).unwrap();
+        assert_eq!(store.backend(), StorageBackend::Jsonl);
+
+        let mut graph = WorkGraph::new();
+        graph.add_node(Node::Task(make_task("a", Status::Done, &[])));
+        graph.add_node(Node::Task(make_task("b", Status::Open, &["a"])));
+        graph.add_node(Node::Task(make_task("c", Status::Open, &["b"])));
+        store.save(&graph).unwrap();
+
+        assert_eq!(store.get_task("b").unwrap().unwrap().title, "Task b");
+        assert!(store.get_task("zzz").unwrap().is_none());
+        assert_eq!(store.list_tasks(Some(Status::Open)).unwrap().len(), 2);
+        let ready: Vec<String> = store
+            .ready_tasks()
+            .unwrap()
+            .into_iter()
+            .map(|t| t.id)
+            .collect();
+        assert_eq!(ready, vec!["b".to_string()]);
+    }
+}
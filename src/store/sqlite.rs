@@ -0,0 +1,608 @@
+//! SQLite graph storage (`.workgraph/graph.db`).
+//!
+//! Each node is stored as a JSON document in `nodes`, with its status and
+//! kind pulled out into indexed columns. `after` edges are mirrored into
+//! `edges` so readiness and neighborhood queries can run without touching
+//! unrelated tasks, and task logs live in `log_entries` so a node row does not
+//! grow with every `wg log`.
+
+use anyhow::{Context, Result};
+use rusqlite::{Connection, OptionalExtension, TransactionBehavior, params, params_from_iter};
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use super::{GraphStore, StorageBackend};
+use crate::graph::{CycleAnalysis, LogEntry, Node, Status, Task, WorkGraph};
+use crate::query;
+
+const SCHEMA: &str = "
+CREATE TABLE IF NOT EXISTS nodes (
+    id TEXT PRIMARY KEY,
+    kind TEXT NOT NULL,
+    status TEXT,
+    data TEXT NOT NULL
+);
+CREATE INDEX IF NOT EXISTS nodes_status ON nodes(status);
+CREATE TABLE IF NOT EXISTS edges (
+    task_id TEXT NOT NULL,
+    after_id TEXT NOT NULL,
+    PRIMARY KEY (task_id, after_id)
+);
+CREATE INDEX IF NOT EXISTS edges_after ON edges(after_id);
+CREATE TABLE IF NOT EXISTS log_entries (
+    task_id TEXT NOT NULL,
+    seq INTEGER NOT NULL,
+    timestamp TEXT NOT NULL,
+    actor TEXT,
+    message TEXT NOT NULL,
+    PRIMARY KEY (task_id, seq)
+);
+";
+
+/// Graph storage in an embedded SQLite database.
+pub struct SqliteStore {
+    path: PathBuf,
+}
+
+impl SqliteStore {
+    /// Open (creating if needed) the database at `path` and ensure the schema exists.
+    pub fn open(path: impl Into<PathBuf>) -> Result<Self> {
+        let store = Self { path: path.into() };
+        store.connect()?;
+        Ok(store)
+    }
+
+    fn connect(&self) -> Result<Connection> {
+        if let Some(parent) = self.path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        let conn = Connection::open(&self.path)
+            .with_context(|| format!("Failed to open {}", self.path.display()))?;
+        // Concurrent `wg` processes wait for each other instead of failing.
+        conn.busy_timeout(Duration::from_secs(10))?;
+        conn.execute_batch(SCHEMA)
+            .context("Failed to initialize graph database schema")?;
+        Ok(conn)
+    }
+
+    /// Load the nodes matching `filter` (a SQL condition on `nodes`), with
+    /// their log entries attached.
+    fn query_nodes(&self, conn: &Connection, filter: &str, args: &[&str]) -> Result<Vec<Node>> {
+        let mut stmt = conn.prepare(&format!("SELECT data FROM nodes WHERE {}", filter))?;
+        let mut nodes: Vec<Node> = stmt
+            .query_map(params_from_iter(args.iter()), |row| row.get::<_, String>(0))?
+            .map(|data| -> Result<Node> {
+                let data = data?;
+                serde_json::from_str(&data).context("Corrupt node in graph database")
+            })
+            .collect::<Result<_>>()?;
+
+        let mut stmt = conn.prepare(&format!(
+            "SELECT task_id, timestamp, actor, message FROM log_entries \
+             WHERE task_id IN (SELECT id FROM nodes WHERE {}) ORDER BY task_id, seq",
+            filter
+        ))?;
+        let mut logs: HashMap<String, Vec<LogEntry>> = HashMap::new();
+        let rows = stmt.query_map(params_from_iter(args.iter()), |row| {
+            Ok((
+                row.get::<_, String>(0)?,
+                LogEntry {
+                    timestamp: row.get(1)?,
+                    actor: row.get(2)?,
+                    message: row.get(3)?,
+                },
+            ))
+        })?;
+        for row in rows {
+            let (task_id, entry) = row?;
+            logs.entry(task_id).or_default().push(entry);
+        }
+
+        for node in &mut nodes {
+            if let Node::Task(task) = node
+                && let Some(entries) = logs.remove(&task.id)
+            {
+                task.log = entries;
+            }
+        }
+        Ok(nodes)
+    }
+
+    /// Whether saves to this database belong in a workgraph's history log.
+    fn records_history(&self) -> bool {
+        self.path.file_name().and_then(|n| n.to_str()) == Some("graph.db")
+    }
+
+    /// Append a save's node writes to the workgraph's history log. Called
+    /// inside the save's transaction, so its write lock keeps history in the
+    /// same order as the saves. Failures are reported but never fail the save
+    /// itself.
+    fn record_history(&self, conn: &Connection, history: &[(String, Option<Node>, Option<Node>)]) {
+        if history.is_empty() || !self.records_history() {
+            return;
+        }
+        let dir = self.path.parent().unwrap_or(Path::new("."));
+        let writes: Vec<_> = history
+            .iter()
+            .map(|(_, before, after)| (before.as_ref(), after.as_ref()))
+            .collect();
+        // The graph before this save: what the transaction holds now, with
+        // the written nodes swapped back for their previous versions.
+        let stored = || -> Vec<Node> {
+            let nodes = self.query_nodes(conn, "1 = 1", &[]).unwrap_or_default();
+            nodes
+                .into_iter()
+                .filter(|node| !history.iter().any(|(id, _, _)| id == node.id()))
+                .chain(history.iter().filter_map(|(_, before, _)| before.clone()))
+                .collect()
+        };
+        if let Err(e) = crate::history::record(dir, &writes, stored) {
+            eprintln!("Warning: failed to record graph history: {:#}", e);
+        }
+    }
+
+    fn graph_from(nodes: Vec<Node>) -> WorkGraph {
+        let mut graph = WorkGraph::new();
+        for node in nodes {
+            graph.add_node(node);
+        }
+        graph
+    }
+}
+
+/// Insert or replace a node along with its edge and log rows.
+fn write_node(tx: &rusqlite::Transaction<'_>, node: &Node) -> Result<()> {
+    let id = node.id();
+    delete_node(tx, id)?;
+
+    let (kind, status, stored) = match node {
+        Node::Task(task) => {
+            let mut stripped = task.clone();
+            stripped.log.clear();
+            ("task", Some(task.status.to_string()), Node::Task(stripped))
+        }
+        Node::Resource(_) => ("resource", None, node.clone()),
+    };
+    let data = serde_json::to_string(&stored).context("Failed to serialize node")?;
+    tx.execute(
+        "INSERT INTO nodes (id, kind, status, data) VALUES (?1, ?2, ?3, ?4)",
+        params![id, kind, status, data],
+    )?;
+
+    if let Node::Task(task) = node {
+        for after_id in &task.after {
+            tx.execute(
+                "INSERT OR IGNORE INTO edges (task_id, after_id) VALUES (?1, ?2)",
+                params![id, after_id],
+            )?;
+        }
+        for (seq, entry) in task.log.iter().enumerate() {
+            tx.execute(
+                "INSERT INTO log_entries (task_id, seq, timestamp, actor, message) \
+                 VALUES (?1, ?2, ?3, ?4, ?5)",
+                params![id, seq as i64, entry.timestamp, entry.actor, entry.message],
+            )?;
+        }
+    }
+    Ok(())
+}
+
+fn delete_node(tx: &rusqlite::Transaction<'_>, id: &str) -> Result<()> {
+    tx.execute("DELETE FROM nodes WHERE id = ?1", params![id])?;
+    tx.execute("DELETE FROM edges WHERE task_id = ?1", params![id])?;
+    tx.execute("DELETE FROM log_entries WHERE task_id = ?1", params![id])?;
+    Ok(())
+}
+
+impl GraphStore for SqliteStore {
+    fn backend(&self) -> StorageBackend {
+        StorageBackend::Sqlite
+    }
+
+    fn location(&self) -> &Path {
+        &self.path
+    }
+
+    fn load(&self) -> Result<WorkGraph> {
+        let conn = self.connect()?;
+        let mut graph = Self::graph_from(self.query_nodes(&conn, "1 = 1", &[])?);
+        graph.track_changes(&self.path);
+        Ok(graph)
+    }
+
+    fn save(&self, graph: &WorkGraph) -> Result<()> {
+        let mut conn = self.connect()?;
+        // Take the write lock up front so the compare-and-swap below sees
+        // rows no other writer can change before commit.
+        let tx = conn.transaction_with_behavior(TransactionBehavior::Immediate)?;
+
+        // Node writes as (id, before, after), for the history log.
+        let history: Vec<(String, Option<Node>, Option<Node>)> = match graph.changes() {
+            Some(changes) if changes.origin == self.path => {
+                let mut current: HashMap<String, Node> = HashMap::new();
+                for id in changes.changed_ids() {
+                    if let Some(node) = self.query_nodes(&tx, "id = ?1", &[id])?.pop() {
+                        current.insert(id.clone(), node);
+                    }
+                }
+                let writes = graph.merge_changes(|id| current.get(id).cloned())?;
+                for (id, node) in &writes {
+                    match node {
+                        Some(node) => write_node(&tx, node)?,
+                        None => delete_node(&tx, id)?,
+                    }
+                }
+                writes
+                    .into_iter()
+                    .map(|(id, node)| {
+                        let before = current.remove(&id);
+                        (id, before, node)
+                    })
+                    .collect()
+            }
+            _ => {
+                let recording = self.records_history()
+                    && crate::history::recording(self.path.parent().unwrap_or(Path::new(".")));
+                let previous = if recording {
+                    Self::graph_from(self.query_nodes(&tx, "1 = 1", &[])?)
+                } else {
+                    WorkGraph::new()
+                };
+                tx.execute_batch("DELETE FROM nodes; DELETE FROM edges; DELETE FROM log_entries;")?;
+                for node in graph.nodes() {
+                    write_node(&tx, node)?;
+                }
+                crate::history::graph_writes(&previous, graph)
+                    .into_iter()
+                    .map(|(before, after)| {
+                        let id = after.or(before).map(Node::id).unwrap_or_default();
+                        (id.to_string(), before.cloned(), after.cloned())
+                    })
+                    .collect()
+            }
+        };
+
+        self.record_history(&tx, &history);
+        tx.commit().context("Failed to commit graph changes")?;
+        graph.mark_saved();
+        Ok(())
+    }
+
+    fn get_task(&self, id: &str) -> Result<Option<Task>> {
+        let conn = self.connect()?;
+        let node = self
+            .query_nodes(&conn, "id = ?1 AND kind = 'task'", &[id])?
+            .into_iter()
+            .next();
+        Ok(match node {
+            Some(Node::Task(task)) => Some(task),
+            _ => None,
+        })
+    }
+
+    fn list_tasks(&self, status: Option<Status>) -> Result<Vec<Task>> {
+        let conn = self.connect()?;
+        let nodes = match status {
+            Some(status) => {
+                let status = status.to_string();
+                self.query_nodes(&conn, "kind = 'task' AND status = ?1", &[&status])?
+            }
+            None => self.query_nodes(&conn, "kind = 'task'", &[])?,
+        };
+        Ok(nodes
+            .into_iter()
+            .filter_map(|n| match n {
+                Node::Task(t) => Some(t),
+                _ => None,
+            })
+            .collect())
+    }
+
+    fn load_neighborhood(&self, id: &str) -> Result<WorkGraph> {
+        let conn = self.connect()?;
+        let nodes = self.query_nodes(
+            &conn,
+            "id = ?1 \
+             OR id IN (SELECT after_id FROM edges WHERE task_id = ?1) \
+             OR id IN (SELECT task_id FROM edges WHERE after_id = ?1) \
+             OR id = (SELECT json_extract(data, '$.parent') FROM nodes WHERE id = ?1) \
+             OR id IN (WITH RECURSIVE sub(id) AS (SELECT ?1 UNION \
+                 SELECT n.id FROM nodes n JOIN sub ON json_extract(n.data, '$.parent') = sub.id) \
+                 SELECT id FROM sub)",
+            &[id],
+        )?;
+        Ok(Self::graph_from(nodes))
+    }
+
+    fn load_frontier(&self) -> Result<WorkGraph> {
+        let conn = self.connect()?;
+        let open = Status::Open.to_string();
+        let nodes = self.query_nodes(
+            &conn,
+            "status = ?1 OR id IN (SELECT e.after_id FROM edges e \
+             JOIN nodes n ON n.id = e.task_id WHERE n.status = ?1) \
+             OR json_extract(data, '$.parent') IN (SELECT id FROM nodes WHERE status = ?1)",
+            &[&open],
+        )?;
+        Ok(Self::graph_from(nodes))
+    }
+
+    fn load_unfinished(&self) -> Result<WorkGraph> {
+        let conn = self.connect()?;
+        let finished = [Status::Done, Status::Failed, Status::Abandoned].map(|s| s.to_string());
+        let nodes = self.query_nodes(
+            &conn,
+            "status NOT IN (?1, ?2, ?3) OR id IN (SELECT e.after_id FROM edges e \
+             JOIN nodes n ON n.id = e.task_id WHERE n.status NOT IN (?1, ?2, ?3)) \
+             OR json_extract(data, '$.parent') IN \
+             (SELECT id FROM nodes WHERE status NOT IN (?1, ?2, ?3))",
+            &[&finished[0], &finished[1], &finished[2]],
+        )?;
+        Ok(Self::graph_from(nodes))
+    }
+
+    fn ready_tasks(&self) -> Result<Vec<Task>> {
+        let frontier = self.load_frontier()?;
+
+        // Cycle membership depends on edges outside the frontier, so compute
+        // it from the full edge table (IDs only, no task bodies).
+        let conn = self.connect()?;
+        let ids: Vec<String> = conn
+            .prepare("SELECT id FROM nodes WHERE kind = 'task'")?
+            .query_map([], |row| row.get(0))?
+            .collect::<rusqlite::Result<_>>()?;
+        let edges: Vec<(String, String)> = conn
+            .prepare(
+                "SELECT e.after_id, e.task_id FROM edges e \
+                 JOIN nodes n ON n.id = e.after_id WHERE n.kind = 'task'",
+            )?
+            .query_map([], |row| Ok((row.get(0)?, row.get(1)?)))?
+            .collect::<rusqlite::Result<_>>()?;
+        let cycle_analysis = CycleAnalysis::from_edges(
+            ids.iter().map(String::as_str),
+            edges.iter().map(|(a, b)| (a.as_str(), b.as_str())),
+        );
+
+        Ok(query::ready_tasks_cycle_aware(&frontier, &cycle_analysis)
+            .into_iter()
+            .cloned()
+            .collect())
+    }
+}
+
+/// Number of task rows, for quick sanity checks without loading the graph.
+pub fn task_count(store: &SqliteStore) -> Result<usize> {
+    let conn = store.connect()?;
+    let count: Option<i64> = conn
+        .query_row(
+            "SELECT COUNT(*) FROM nodes WHERE kind = 'task'",
+            [],
+            |row| row.get(0),
+        )
+        .optional()?;
+    Ok(count.unwrap_or(0) as usize)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::graph::{CycleConfig, Resource};
+    use tempfile::TempDir;
+
+    fn make_task(id: &str, status: Status, after: &[&str]) -> Task {
+        Task {
+            id: id.to_string(),
+            title: format!("Task {}", id),
+            status,
+            after: after.iter().map(|s| s.to_string()).collect(),
+            ..Task::default()
+        }
+    }
+
+    fn store_with(nodes: Vec<Node>) -> (TempDir, SqliteStore) {
+        let tmp = TempDir::new().unwrap();
+        let store = SqliteStore::open(tmp.path().join("graph.db")).unwrap();
+        let mut graph = WorkGraph::new();
+        for node in nodes {
+            graph.add_node(node);
+        }
+        store.save(&graph).unwrap();
+        (tmp, store)
+    }
+
+    #[test]
+    fn test_roundtrip_preserves_nodes_and_logs() {
+        let mut task = make_task("a", Status::InProgress, &["b", "b"]);
+        task.log.push(LogEntry {
+            timestamp: "2026-01-01T00:00:00Z".to_string(),
+            actor: Some("agent-1".to_string()),
+            message: "first".to_string(),
+        });
+        task.log.push(LogEntry {
+            timestamp: "2026-01-01T00:01:00Z".to_string(),
+            actor: None,
+            message: "second".to_string(),
+        });
+        let resource = Resource {
+            id: "gpu".to_string(),
+            name: Some("GPU".to_string()),
+            resource_type: Some("compute".to_string()),
+            available: Some(2.0),
+            unit: None,
+            budget: None,
+        };
+        let nodes = vec![
+            Node::Task(task.clone()),
+            Node::Task(make_task("b", Status::Done, &[])),
+            Node::Resource(resource.clone()),
+        ];
+        let (_tmp, store) = store_with(nodes);
+
+        let graph = store.load().unwrap();
+        assert_eq!(graph.len(), 3);
+        assert_eq!(graph.get_task("a").unwrap(), &task);
+        assert_eq!(graph.get_resource("gpu").unwrap(), &resource);
+        assert_eq!(task_count(&store).unwrap(), 2);
+    }
+
+    #[test]
+    fn test_incremental_save_writes_only_changes() {
+        let (_tmp, store) = store_with(vec![
+            Node::Task(make_task("a", Status::Open, &[])),
+            Node::Task(make_task("b", Status::Open, &["a"])),
+        ]);
+
+        // Two writers change different tasks; both changes survive
+        let mut first = store.load().unwrap();
+        let mut second = store.load().unwrap();
+        first.get_task_mut("a").unwrap().status = Status::Done;
+        second.get_task_mut("b").unwrap().title = "Renamed".to_string();
+        store.save(&first).unwrap();
+        store.save(&second).unwrap();
+
+        let graph = store.load().unwrap();
+        assert_eq!(graph.get_task("a").unwrap().status, Status::Done);
+        assert_eq!(graph.get_task("b").unwrap().title, "Renamed");
+
+        let mut third = store.load().unwrap();
+        third.remove_node("a");
+        store.save(&third).unwrap();
+        let graph = store.load().unwrap();
+        assert!(graph.get_task("a").is_none());
+        assert!(graph.get_task("b").unwrap().after.is_empty());
+    }
+
+    #[test]
+    fn test_indexed_queries() {
+        let (_tmp, store) = store_with(vec![
+            Node::Task(make_task("done", Status::Done, &[])),
+            Node::Task(make_task("ready", Status::Open, &["done"])),
+            Node::Task(make_task("waiting", Status::Open, &["ready"])),
+            Node::Task(make_task("unrelated", Status::InProgress, &[])),
+        ]);
+
+        assert_eq!(
+            store.get_task("ready").unwrap().unwrap().after,
+            vec!["done".to_string()]
+        );
+        assert!(store.get_task("nope").unwrap().is_none());
+
+        let mut open: Vec<String> = store
+            .list_tasks(Some(Status::Open))
+            .unwrap()
+            .into_iter()
+            .map(|t| t.id)
+            .collect();
+        open.sort();
+        assert_eq!(open, vec!["ready", "waiting"]);
+
+        let hood = store.load_neighborhood("ready").unwrap();
+        assert_eq!(hood.len(), 3);
+        assert!(hood.get_task("unrelated").is_none());
+
+        let unfinished = store.load_unfinished().unwrap();
+        let mut ids: Vec<&str> = unfinished.tasks().map(|t| t.id.as_str()).collect();
+        ids.sort();
+        assert_eq!(ids, ["done", "ready", "unrelated", "waiting"]);
+        assert!(
+            store
+                .load_frontier()
+                .unwrap()
+                .get_task("unrelated")
+                .is_none()
+        );
+
+        let ready: Vec<String> = store
+            .ready_tasks()
+            .unwrap()
+            .into_iter()
+            .map(|t| t.id)
+            .collect();
+        assert_eq!(ready, vec!["ready"]);
+    }
+
+    #[test]
+    fn test_ready_tasks_respects_cycle_header_exemption() {
+        let mut header = make_task("header", Status::Open, &["tail"]);
+        header.cycle_config = Some(CycleConfig {
+            max_iterations: 3,
+            guard: None,
+            delay: None,
+        });
+        let (_tmp, store) = store_with(vec![
+            Node::Task(header),
+            Node::Task(make_task("middle", Status::Open, &["header"])),
+            Node::Task(make_task("tail", Status::Open, &["middle"])),
+        ]);
+
+        let ready: Vec<String> = store
+            .ready_tasks()
+            .unwrap()
+            .into_iter()
+            .map(|t| t.id)
+            .collect();
+        assert_eq!(ready, vec!["header"]);
+    }
+
+    #[test]
+    fn test_epics_are_loaded_with_their_children() {
+        let child = |id: &str, status, parent: &str| Task {
+            parent: Some(parent.to_string()),
+            ..make_task(id, status, &[])
+        };
+        let (_tmp, store) = store_with(vec![
+            Node::Task(make_task("epic", Status::Open, &[])),
+            Node::Task(child("sub", Status::Open, "epic")),
+            Node::Task(child("working", Status::InProgress, "sub")),
+            Node::Task(make_task("unrelated", Status::Done, &[])),
+        ]);
+
+        // Neither epic is ready while work remains below it
+        assert!(store.ready_tasks().unwrap().is_empty());
+
+        let hood = store.load_neighborhood("sub").unwrap();
+        assert_eq!(hood.len(), 3);
+        assert!(hood.get_task("unrelated").is_none());
+        let hood = store.load_neighborhood("epic").unwrap();
+        assert!(hood.get_task("working").is_some());
+    }
+
+    #[test]
+    fn test_history_follows_concurrent_saves() {
+        let (tmp, store) = store_with(vec![Node::Task(make_task("a", Status::Open, &[]))]);
+        let path = store.location().to_path_buf();
+
+        let writers: Vec<_> = (0..4)
+            .map(|writer| {
+                let path = path.clone();
+                std::thread::spawn(move || {
+                    let store = SqliteStore::open(path).unwrap();
+                    for i in 0..10 {
+                        // A conflicting save is retried on a fresh load
+                        loop {
+                            let mut graph = store.load().unwrap();
+                            graph.get_task_mut("a").unwrap().title = format!("{}-{}", writer, i);
+                            if store.save(&graph).is_ok() {
+                                break;
+                            }
+                        }
+                    }
+                })
+            })
+            .collect();
+        for writer in writers {
+            writer.join().unwrap();
+        }
+
+        // Each recorded change starts where the previous one left off
+        let mut title = "Task a".to_string();
+        for entry in crate::history::read_all(tmp.path()).unwrap() {
+            if let crate::history::Change::Updated { before, after, .. } = entry.change {
+                assert_eq!(before["title"], title);
+                title = after["title"].as_str().unwrap().to_string();
+            }
+        }
+        assert_eq!(title, store.load().unwrap().get_task("a").unwrap().title);
+    }
+}
@@ -108,7 +108,7 @@ pub enum InputType {
     Json,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct TaskTemplate {
     pub template_id: String,
     pub title: String,
@@ -129,7 +129,7 @@ pub struct TaskTemplate {
     pub tags: Vec<String>,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct LoopEdgeTemplate {
     pub target: String,
     pub max_iterations: u32,
@@ -4,6 +4,7 @@ use std::io::{BufRead, BufReader, Seek, SeekFrom};
 use std::path::PathBuf;
 use std::time::{Duration, Instant};
 
+use workgraph::epic;
 use workgraph::graph::{Status, Task, WorkGraph};
 use workgraph::parser::load_graph;
 use workgraph::{AgentEntry, AgentRegistry, AgentStatus};
@@ -83,7 +84,10 @@ impl AgentInfo {
     }
 
     pub fn is_dead(&self) -> bool {
-        matches!(self.status, AgentStatus::Dead | AgentStatus::Failed)
+        matches!(
+            self.status,
+            AgentStatus::Dead | AgentStatus::Failed | AgentStatus::TimedOut
+        )
     }
 }
 
@@ -589,7 +593,8 @@ impl GraphExplorer {
 /// Strategy: DFS from root nodes, then render tasks as an indented tree.
 /// Root nodes are tasks with no `after`. Each task is shown indented
 /// under its last blocker. Tasks with multiple blockers get a back-reference
-/// marker under earlier blockers.
+/// marker under earlier blockers. Subtasks of an epic are listed only under
+/// the epic, so collapsing the epic hides them.
 fn build_graph_tree(
     graph: &WorkGraph,
     collapsed: &HashSet<String>,
@@ -603,6 +608,13 @@ fn build_graph_tree(
     let mut roots: Vec<String> = Vec::new();
 
     for task in tasks.values() {
+        if let Some(parent) = task.parent.as_ref().filter(|p| tasks.contains_key(*p)) {
+            children
+                .entry(parent.clone())
+                .or_default()
+                .push(task.id.clone());
+            continue;
+        }
         if task.after.is_empty() {
             roots.push(task.id.clone());
         }
@@ -658,10 +670,25 @@ fn build_graph_tree(
         );
     }
 
+    // Tasks under a collapsed node are hidden, not orphaned
+    let mut hidden: HashSet<&str> = HashSet::new();
+    let mut stack: Vec<&str> = collapsed
+        .iter()
+        .filter(|id| placed.contains(*id))
+        .map(String::as_str)
+        .collect();
+    while let Some(id) = stack.pop() {
+        for kid in children.get(id).into_iter().flatten() {
+            if hidden.insert(kid.as_str()) {
+                stack.push(kid.as_str());
+            }
+        }
+    }
+
     // Handle orphan tasks that weren't reached (e.g., cycles or missing blockers)
     let mut orphans: Vec<String> = tasks
         .keys()
-        .filter(|id| !placed.contains(*id))
+        .filter(|id| !placed.contains(*id) && !hidden.contains(id.as_str()))
         .cloned()
         .collect();
     orphans.sort();
@@ -687,6 +714,15 @@ fn build_graph_tree(
         }
     }
 
+    // Epics carry their rollup in the title
+    let epics = epic::epic_ids(graph);
+    for row in &mut rows {
+        if epics.contains(row.task_id.as_str()) {
+            let summary = epic::rollup(graph, &row.task_id).summary();
+            row.title = format!("{} [{}]", row.title, summary);
+        }
+    }
+
     rows
 }
 
@@ -1181,8 +1217,9 @@ impl App {
                     AgentStatus::Idle => 2,
                     AgentStatus::Stopping => 3,
                     AgentStatus::Dead => 4,
-                    AgentStatus::Failed => 5,
-                    AgentStatus::Done => 6,
+                    AgentStatus::TimedOut => 5,
+                    AgentStatus::Failed => 6,
+                    AgentStatus::Done => 7,
                 }
             };
             order(&a.status)
@@ -2216,4 +2253,40 @@ mod tests {
         assert_eq!(GraphViewMode::Dag, GraphViewMode::Dag);
         assert_ne!(GraphViewMode::Tree, GraphViewMode::Dag);
     }
+
+    // ── Epics in the graph tree ─────────────────────────────────────
+
+    #[test]
+    fn build_graph_tree_nests_and_collapses_epics() {
+        use workgraph::graph::Node;
+        let task = |id: &str, parent: Option<&str>, after: &[&str], status| Task {
+            id: id.to_string(),
+            title: id.to_string(),
+            status,
+            parent: parent.map(String::from),
+            after: after.iter().map(|a| a.to_string()).collect(),
+            ..Task::default()
+        };
+        let mut graph = WorkGraph::new();
+        graph.add_node(Node::Task(task("epic", None, &[], Status::Open)));
+        graph.add_node(Node::Task(task("design", Some("epic"), &[], Status::Done)));
+        graph.add_node(Node::Task(task(
+            "build",
+            Some("epic"),
+            &["design"],
+            Status::Open,
+        )));
+
+        let rows = build_graph_tree(&graph, &HashSet::new(), &HashSet::new(), &HashMap::new());
+        let shape: Vec<(&str, usize)> =
+            rows.iter().map(|r| (r.task_id.as_str(), r.depth)).collect();
+        // Subtasks sit under the epic only, not under each other
+        assert_eq!(shape, vec![("epic", 0), ("build", 1), ("design", 1)]);
+        assert_eq!(rows[0].title, "epic [1/2, 50%]");
+
+        let collapsed = HashSet::from(["epic".to_string()]);
+        let rows = build_graph_tree(&graph, &collapsed, &HashSet::new(), &HashMap::new());
+        assert_eq!(rows.len(), 1);
+        assert!(rows[0].collapsed);
+    }
 }
@@ -1071,6 +1071,7 @@ fn draw_graph_detail_overlay(frame: &mut Frame, explorer: &app::GraphExplorer) {
                     format!(", guard: iter<{}", n)
                 }
                 Some(workgraph::graph::LoopGuard::Always) => ", guard: always".to_string(),
+                Some(workgraph::graph::LoopGuard::Expr(e)) => format!(", guard: {}", e),
                 None => String::new(),
             };
             let delay_str = match &cc.delay {
@@ -1320,6 +1321,7 @@ pub(crate) fn agent_status_color(status: &AgentStatus) -> Color {
         AgentStatus::Idle => Color::Cyan,
         AgentStatus::Stopping => Color::Yellow,
         AgentStatus::Dead => Color::Red,
+        AgentStatus::TimedOut => Color::Red,
         AgentStatus::Failed => Color::Red,
         AgentStatus::Done => Color::DarkGray,
     }
@@ -1333,6 +1335,7 @@ pub(crate) fn agent_status_label(status: &AgentStatus) -> &'static str {
         AgentStatus::Idle => "IDLE",
         AgentStatus::Stopping => "STOPPING",
         AgentStatus::Dead => "DEAD",
+        AgentStatus::TimedOut => "TIMEOUT",
         AgentStatus::Failed => "FAILED",
         AgentStatus::Done => "DONE",
     }
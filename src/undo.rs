@@ -0,0 +1,399 @@
+//! Undo and redo of graph saves.
+//!
+//! Each save of the graph is recorded in the graph history
+//! ([`crate::history`]) with the before/after value of every field it
+//! changed, so it can be inverted later. `wg undo` inverts the newest saves
+//! made by the current actor and records the inversion as a save marked
+//! `undo_of`; `wg redo` inverts that undo save again, marked `redo_of`.
+//!
+//! A save is only undone when no later save touched the same tasks, unless
+//! forced: undoing it would otherwise silently discard the later change.
+
+use anyhow::{Context, Result, bail};
+use serde_json::Value;
+use std::collections::{BTreeSet, HashMap, HashSet};
+
+use crate::graph::{Node, WorkGraph};
+use crate::history::{Change, HistoryEntry};
+
+/// Fields left alone when inverting a save; storage maintains them.
+const UNTOUCHED_FIELDS: &[&str] = &["revision"];
+
+/// All the changes made by one save of the graph.
+#[derive(Debug)]
+pub struct Save<'a> {
+    /// Timestamp of the save, which identifies it
+    pub id: &'a str,
+    pub actor: Option<&'a str>,
+    pub command: Option<&'a str>,
+    pub entries: Vec<&'a HistoryEntry>,
+}
+
+impl Save<'_> {
+    /// The save this one undid, if it is an undo.
+    pub fn undo_of(&self) -> Option<&str> {
+        self.entries.first().and_then(|e| e.undo_of.as_deref())
+    }
+
+    /// The undo this one reverted, if it is a redo.
+    pub fn redo_of(&self) -> Option<&str> {
+        self.entries.first().and_then(|e| e.redo_of.as_deref())
+    }
+
+    /// IDs of the nodes this save touched.
+    pub fn node_ids(&self) -> BTreeSet<&str> {
+        self.entries
+            .iter()
+            .filter_map(|e| e.change.node_id())
+            .collect()
+    }
+}
+
+/// A later save that touched a node the save being reverted also touched.
+#[derive(Debug, Clone)]
+pub struct Conflict {
+    /// Node both saves touched
+    pub id: String,
+    /// Timestamp of the later save
+    pub save: String,
+    pub actor: Option<String>,
+    pub command: Option<String>,
+}
+
+impl std::fmt::Display for Conflict {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let when = chrono::DateTime::parse_from_rfc3339(&self.save)
+            .map(|t| {
+                t.with_timezone(&chrono::Local)
+                    .format("%Y-%m-%d %H:%M:%S")
+                    .to_string()
+            })
+            .unwrap_or_else(|_| self.save.clone());
+        write!(
+            f,
+            "'{}' was changed later by {} at {}",
+            self.id,
+            self.actor.as_deref().unwrap_or("an unknown actor"),
+            when
+        )?;
+        if let Some(ref command) = self.command {
+            write!(f, " ({})", command)?;
+        }
+        Ok(())
+    }
+}
+
+/// Group history entries into saves, oldest first. Baseline snapshots are
+/// not saves and are left out.
+pub fn saves(entries: &[HistoryEntry]) -> Vec<Save<'_>> {
+    let mut saves: Vec<Save> = Vec::new();
+    for entry in entries {
+        if matches!(entry.change, Change::Snapshot { .. }) {
+            continue;
+        }
+        match saves.last_mut() {
+            Some(save) if save.id == entry.timestamp => save.entries.push(entry),
+            _ => saves.push(Save {
+                id: &entry.timestamp,
+                actor: entry.actor.as_deref(),
+                command: entry.command.as_deref(),
+                entries: vec![entry],
+            }),
+        }
+    }
+    saves
+}
+
+/// Which saves are currently undone, and which saves cancel out.
+struct UndoState<'a> {
+    /// Saves whose latest undo has not been redone
+    undone: HashSet<&'a str>,
+    /// Saves with no net effect: undone saves, undo saves and redo saves
+    net_zero: HashSet<&'a str>,
+}
+
+fn undo_state<'a>(saves: &'a [Save<'a>]) -> UndoState<'a> {
+    let redone: HashSet<&str> = saves.iter().filter_map(|s| s.redo_of()).collect();
+    let mut latest_undo: HashMap<&str, &str> = HashMap::new();
+    for save in saves {
+        if let Some(target) = save.undo_of() {
+            latest_undo.insert(target, save.id);
+        }
+    }
+    let undone: HashSet<&str> = latest_undo
+        .into_iter()
+        .filter(|(_, undo)| !redone.contains(undo))
+        .map(|(target, _)| target)
+        .collect();
+    let net_zero = saves
+        .iter()
+        .filter(|s| s.undo_of().is_some() || s.redo_of().is_some())
+        .map(|s| s.id)
+        .chain(undone.iter().copied())
+        .collect();
+    UndoState { undone, net_zero }
+}
+
+/// The newest `n` saves by `actor` that can be undone, newest first.
+pub fn plan_undo<'a>(saves: &'a [Save<'a>], actor: Option<&str>, n: usize) -> Vec<&'a Save<'a>> {
+    let state = undo_state(saves);
+    saves
+        .iter()
+        .rev()
+        .filter(|s| s.actor == actor)
+        .filter(|s| s.undo_of().is_none() && s.redo_of().is_none())
+        .filter(|s| !state.undone.contains(s.id))
+        .take(n)
+        .collect()
+}
+
+/// The undo save by `actor` that `wg redo` would revert: the newest one not
+/// yet redone, unless the actor has made other changes since.
+pub fn plan_redo<'a>(saves: &'a [Save<'a>], actor: Option<&str>) -> Option<&'a Save<'a>> {
+    let redone: HashSet<&str> = saves.iter().filter_map(|s| s.redo_of()).collect();
+    for save in saves.iter().rev().filter(|s| s.actor == actor) {
+        if save.redo_of().is_some() || redone.contains(save.id) {
+            continue;
+        }
+        return save.undo_of().map(|_| save);
+    }
+    None
+}
+
+/// Later saves that touched the nodes `target` touched. Saves in `batch`
+/// (reverted together with `target`) and saves that cancel out are ignored.
+pub fn conflicts(saves: &[Save], target: &Save, batch: &HashSet<&str>) -> Vec<Conflict> {
+    let state = undo_state(saves);
+    let ids = target.node_ids();
+    let Some(pos) = saves.iter().position(|s| s.id == target.id) else {
+        return Vec::new();
+    };
+    let mut conflicts = Vec::new();
+    for later in &saves[pos + 1..] {
+        if batch.contains(later.id) || state.net_zero.contains(later.id) {
+            continue;
+        }
+        for id in later.node_ids() {
+            if ids.contains(id) && !conflicts.iter().any(|c: &Conflict| c.id == id) {
+                conflicts.push(Conflict {
+                    id: id.to_string(),
+                    save: later.id.to_string(),
+                    actor: later.actor.map(String::from),
+                    command: later.command.map(String::from),
+                });
+            }
+        }
+    }
+    conflicts
+}
+
+/// Invert `save` on `graph`: restore the nodes it removed, remove the ones
+/// it created, and put changed fields back to their previous values.
+pub fn revert(graph: &mut WorkGraph, save: &Save) -> Result<()> {
+    for entry in save.entries.iter().rev() {
+        match &entry.change {
+            Change::Snapshot { .. } => {}
+            Change::Created { id, .. } => {
+                graph.remove_node(id);
+            }
+            Change::Removed { id, node } => {
+                let node: Node = serde_json::from_value(node.clone())
+                    .with_context(|| format!("Failed to restore '{}' from history", id))?;
+                graph.add_node(node);
+            }
+            Change::Updated {
+                id,
+                before,
+                appended,
+                ..
+            } => {
+                let Some(node) = graph.get_node(id) else {
+                    bail!("'{}' no longer exists", id);
+                };
+                let mut value = serde_json::to_value(node)?;
+                let Some(fields) = value.as_object_mut() else {
+                    bail!("'{}' is not a JSON object", id);
+                };
+                for (key, old) in before {
+                    if UNTOUCHED_FIELDS.contains(&key.as_str()) {
+                        continue;
+                    }
+                    if old.is_null() {
+                        fields.remove(key);
+                    } else {
+                        fields.insert(key.clone(), old.clone());
+                    }
+                }
+                for (key, items) in appended {
+                    let (Some(Value::Array(existing)), Value::Array(items)) =
+                        (fields.get_mut(key), items)
+                    else {
+                        continue;
+                    };
+                    for item in items.iter().rev() {
+                        if let Some(pos) = existing.iter().rposition(|x| x == item) {
+                            existing.remove(pos);
+                        }
+                    }
+                }
+                let node: Node = serde_json::from_value(value)
+                    .with_context(|| format!("Failed to restore '{}' from history", id))?;
+                graph.add_node(node);
+            }
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::graph::{LogEntry, Status, Task};
+    use serde_json::json;
+
+    fn entry(ts: &str, actor: &str, change: Change) -> HistoryEntry {
+        HistoryEntry {
+            timestamp: ts.to_string(),
+            change,
+            command: Some(format!("wg cmd {}", ts)),
+            actor: Some(actor.to_string()),
+            undo_of: None,
+            redo_of: None,
+        }
+    }
+
+    fn updated(id: &str, before: Value, after: Value) -> Change {
+        Change::Updated {
+            id: id.to_string(),
+            before: before.as_object().unwrap().clone(),
+            after: after.as_object().unwrap().clone(),
+            appended: Default::default(),
+        }
+    }
+
+    #[test]
+    fn test_plan_undo_skips_other_actors_and_undone_saves() {
+        let mut undo = entry("4", "alice", updated("t1", json!({}), json!({})));
+        undo.undo_of = Some("3".into());
+        let entries = vec![
+            entry("1", "alice", updated("t1", json!({}), json!({}))),
+            entry("2", "bob", updated("t2", json!({}), json!({}))),
+            entry("3", "alice", updated("t3", json!({}), json!({}))),
+            undo,
+        ];
+        let saves = saves(&entries);
+        let plan = plan_undo(&saves, Some("alice"), 5);
+        let ids: Vec<&str> = plan.iter().map(|s| s.id).collect();
+        assert_eq!(ids, ["1"]);
+
+        let redo = plan_redo(&saves, Some("alice")).unwrap();
+        assert_eq!(redo.id, "4");
+        assert!(plan_redo(&saves, Some("bob")).is_none());
+    }
+
+    #[test]
+    fn test_redo_is_invalidated_by_new_changes() {
+        let mut undo = entry("2", "alice", updated("t1", json!({}), json!({})));
+        undo.undo_of = Some("1".into());
+        let entries = vec![
+            entry("1", "alice", updated("t1", json!({}), json!({}))),
+            undo,
+            entry("3", "alice", updated("t2", json!({}), json!({}))),
+        ];
+        let saves = saves(&entries);
+        assert!(plan_redo(&saves, Some("alice")).is_none());
+    }
+
+    #[test]
+    fn test_conflicts_with_later_saves() {
+        let entries = vec![
+            entry("1", "alice", updated("t1", json!({}), json!({}))),
+            entry("2", "bob", updated("t2", json!({}), json!({}))),
+            entry("3", "bob", updated("t1", json!({}), json!({}))),
+        ];
+        let saves = saves(&entries);
+        let found = conflicts(&saves, &saves[0], &HashSet::new());
+        assert_eq!(found.len(), 1);
+        assert_eq!(found[0].id, "t1");
+        assert_eq!(found[0].save, "3");
+        assert!(conflicts(&saves, &saves[1], &HashSet::new()).is_empty());
+
+        let batch: HashSet<&str> = ["3"].into_iter().collect();
+        assert!(conflicts(&saves, &saves[0], &batch).is_empty());
+    }
+
+    #[test]
+    fn test_revert_restores_fields_nodes_and_log() {
+        let mut graph = WorkGraph::new();
+        graph.add_node(Node::Task(Task {
+            id: "t1".into(),
+            title: "New".into(),
+            status: Status::Abandoned,
+            log: vec![LogEntry {
+                timestamp: "now".into(),
+                actor: None,
+                message: "Abandoned".into(),
+            }],
+            ..Task::default()
+        }));
+        graph.add_node(Node::Task(Task {
+            id: "t2".into(),
+            ..Task::default()
+        }));
+        let removed = serde_json::to_value(Node::Task(Task {
+            id: "t3".into(),
+            title: "Collected".into(),
+            ..Task::default()
+        }))
+        .unwrap();
+
+        let mut appended = serde_json::Map::new();
+        appended.insert(
+            "log".into(),
+            json!([{"timestamp": "now", "message": "Abandoned"}]),
+        );
+        let entries = vec![
+            entry(
+                "1",
+                "alice",
+                Change::Updated {
+                    id: "t1".into(),
+                    before: json!({"title": "Old", "status": "open"})
+                        .as_object()
+                        .unwrap()
+                        .clone(),
+                    after: json!({"title": "New", "status": "abandoned"})
+                        .as_object()
+                        .unwrap()
+                        .clone(),
+                    appended,
+                },
+            ),
+            entry(
+                "1",
+                "alice",
+                Change::Created {
+                    id: "t2".into(),
+                    node: json!({}),
+                },
+            ),
+            entry(
+                "1",
+                "alice",
+                Change::Removed {
+                    id: "t3".into(),
+                    node: removed,
+                },
+            ),
+        ];
+        let saves = saves(&entries);
+        revert(&mut graph, &saves[0]).unwrap();
+
+        let t1 = graph.get_task("t1").unwrap();
+        assert_eq!(t1.title, "Old");
+        assert_eq!(t1.status, Status::Open);
+        assert!(t1.log.is_empty());
+        assert!(graph.get_task("t2").is_none());
+        assert_eq!(graph.get_task("t3").unwrap().title, "Collected");
+    }
+}
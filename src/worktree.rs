@@ -0,0 +1,482 @@
+//! Git worktree isolation for spawned agents.
+//!
+//! With `[worktree] enabled = true`, `wg spawn` gives each agent its own
+//! worktree at `.workgraph/worktrees/<task-id>` on a `wg/<task-id>` branch
+//! cut from the integration branch, so agents running side by side never
+//! touch each other's files. On `wg done` the agent's changes are committed
+//! and the branch is merged (or rebased and fast-forwarded) into the
+//! integration branch before the task counts as done. A merge that fails
+//! leaves the branch in place and is handed to a follow-up task, which the
+//! task's dependents then wait for. Integrations run one at a time.
+
+use anyhow::{Context, Result, bail};
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+use crate::graph::{LogEntry, Status, Task};
+
+/// How a finished task branch is brought into the integration branch.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Strategy {
+    /// `git merge --no-ff` the task branch
+    #[default]
+    Merge,
+    /// Rebase the task branch onto the integration branch, then fast-forward
+    Rebase,
+}
+
+impl fmt::Display for Strategy {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Strategy::Merge => write!(f, "merge"),
+            Strategy::Rebase => write!(f, "rebase"),
+        }
+    }
+}
+
+/// Outcome of [`integrate`].
+#[derive(Debug, Clone, PartialEq)]
+pub enum Integration {
+    /// The task branch was integrated and its worktree removed
+    Merged { branch: String, into: String },
+    /// The agent made no changes; the worktree was removed
+    Unchanged { branch: String },
+    /// Integration failed; the branch is kept for a follow-up task
+    Conflict {
+        branch: String,
+        into: String,
+        files: Vec<String>,
+        detail: String,
+    },
+}
+
+/// Worktree directory for a task.
+pub fn worktree_path(wg_dir: &Path, task_id: &str) -> PathBuf {
+    wg_dir.join("worktrees").join(task_id)
+}
+
+/// Branch a task's worktree is checked out on.
+pub fn branch_name(task_id: &str) -> String {
+    format!("wg/{}", task_id)
+}
+
+/// The task's worktree, if one has been prepared.
+pub fn existing(wg_dir: &Path, task_id: &str) -> Option<PathBuf> {
+    let path = worktree_path(wg_dir, task_id);
+    path.join(".git").exists().then_some(path)
+}
+
+/// Top level of the git repository the workgraph directory belongs to.
+pub fn repo_root(wg_dir: &Path) -> Option<PathBuf> {
+    let abs = wg_dir.canonicalize().ok()?;
+    let parent = abs.parent()?;
+    git(parent, &["rev-parse", "--show-toplevel"])
+        .ok()
+        .map(PathBuf::from)
+}
+
+/// The configured integration branch, or the branch checked out in `root`.
+pub fn integration_branch(root: &Path, configured: Option<&str>) -> Result<String> {
+    match configured {
+        Some(branch) => Ok(branch.to_string()),
+        None => git(root, &["symbolic-ref", "--short", "HEAD"])
+            .context("Cannot determine the integration branch (detached HEAD?); set worktree.integration_branch"),
+    }
+}
+
+/// Create the task's worktree, or return it if it already exists (e.g. on a
+/// retry). An existing `wg/<task-id>` branch is checked out as is; otherwise
+/// the branch is cut from the integration branch.
+///
+/// The worktree gets a `.workgraph` symlink back to the real workgraph
+/// directory so `wg` commands work from inside it.
+pub fn prepare(wg_dir: &Path, task_id: &str, configured: Option<&str>) -> Result<PathBuf> {
+    let root = repo_root(wg_dir).context("Worktree isolation needs a git repository")?;
+    let path = worktree_path(wg_dir, task_id);
+    if !path.join(".git").exists() {
+        let branch = branch_name(task_id);
+        let path_str = path.to_string_lossy();
+        if branch_exists(&root, &branch) {
+            git(&root, &["worktree", "add", &path_str, &branch])?;
+        } else {
+            let base = integration_branch(&root, configured)?;
+            git(&root, &["worktree", "add", "-b", &branch, &path_str, &base])?;
+        }
+    }
+    link_workgraph(wg_dir, &path)?;
+    Ok(path)
+}
+
+#[cfg(unix)]
+fn link_workgraph(wg_dir: &Path, worktree: &Path) -> Result<()> {
+    let link = worktree.join(".workgraph");
+    if git(worktree, &["check-ignore", "-q", ".workgraph"]).is_err() {
+        // Keep the link out of the agent's commits
+        let common = PathBuf::from(git(worktree, &["rev-parse", "--git-common-dir"])?);
+        let exclude = worktree.join(common).join("info").join("exclude");
+        let mut content = std::fs::read_to_string(&exclude).unwrap_or_default();
+        if !content.is_empty() && !content.ends_with('\n') {
+            content.push('\n');
+        }
+        content.push_str("/.workgraph\n");
+        std::fs::create_dir_all(exclude.parent().unwrap())?;
+        std::fs::write(&exclude, content)?;
+    }
+    if link.symlink_metadata().is_err() {
+        let target = wg_dir.canonicalize()?;
+        std::os::unix::fs::symlink(&target, &link)
+            .with_context(|| format!("Failed to link {}", link.display()))?;
+    }
+    Ok(())
+}
+
+#[cfg(not(unix))]
+fn link_workgraph(_wg_dir: &Path, _worktree: &Path) -> Result<()> {
+    Ok(())
+}
+
+/// Diff of everything the task changed in its worktree relative to where
+/// its branch left the integration branch.
+pub fn diff(wg_dir: &Path, task_id: &str, configured: Option<&str>) -> Result<String> {
+    let Some(path) = existing(wg_dir, task_id) else {
+        bail!("Task '{}' has no worktree", task_id);
+    };
+    let root = repo_root(wg_dir).context("Not a git repository")?;
+    let into = integration_branch(&root, configured)?;
+    let base = git(&path, &["merge-base", "HEAD", &into])?;
+    stage_all(&path)?;
+    git_raw(&path, &["diff", "--cached", &base])
+}
+
+/// Commit the task's pending changes and integrate its branch.
+///
+/// Returns `Ok(None)` if the task has no worktree. On a failed merge or
+/// rebase the operation is aborted, the worktree is removed and the branch
+/// kept, and [`Integration::Conflict`] describes what went wrong. Holds a
+/// lock throughout, so concurrent integrations never see each other's
+/// half-done merge or abort it.
+pub fn integrate(
+    wg_dir: &Path,
+    task: &Task,
+    configured: Option<&str>,
+    strategy: Strategy,
+) -> Result<Option<Integration>> {
+    let _lock = crate::parser::FileLock::acquire(wg_dir.join("worktrees").join(".integrate.lock"))?;
+    let Some(path) = existing(wg_dir, &task.id) else {
+        return Ok(None);
+    };
+    let root = repo_root(wg_dir).context("Not a git repository")?;
+    let into = integration_branch(&root, configured)?;
+    let branch = branch_name(&task.id);
+
+    commit_pending(&root, &path, task)?;
+
+    let ahead = git(
+        &root,
+        &["rev-list", "--count", &format!("{}..{}", into, branch)],
+    )?;
+    if ahead == "0" {
+        remove(&root, &path, &branch)?;
+        return Ok(Some(Integration::Unchanged { branch }));
+    }
+
+    let conflict = |files: Vec<String>, detail: String| Integration::Conflict {
+        branch: branch.clone(),
+        into: into.clone(),
+        files,
+        detail,
+    };
+
+    if strategy == Strategy::Rebase
+        && let Err(e) = git_as(&root, &path, &["rebase", &into])
+    {
+        let files = conflicted_files(&path);
+        let _ = git(&path, &["rebase", "--abort"]);
+        remove_worktree(&root, &path)?;
+        return Ok(Some(conflict(files, e.to_string())));
+    }
+
+    let (target, temporary) = match checkout_of(&root, &into)? {
+        Some(dir) => (dir, false),
+        None => {
+            let dir = wg_dir.join("worktrees").join(".integration");
+            git(&root, &["worktree", "add", &dir.to_string_lossy(), &into])?;
+            (dir, true)
+        }
+    };
+    let message = format!("Merge {} ({})", branch, task.title);
+    let merge_args: &[&str] = match strategy {
+        Strategy::Merge => &["merge", "--no-ff", "-m", &message, &branch],
+        Strategy::Rebase => &["merge", "--ff-only", &branch],
+    };
+    let result = git_as(&root, &target, merge_args);
+    let outcome = match result {
+        Ok(_) => {
+            remove(&root, &path, &branch)?;
+            Integration::Merged {
+                branch,
+                into: into.clone(),
+            }
+        }
+        Err(e) => {
+            let files = conflicted_files(&target);
+            let _ = git(&target, &["merge", "--abort"]);
+            remove_worktree(&root, &path)?;
+            conflict(files, e.to_string())
+        }
+    };
+    if temporary {
+        remove_worktree(&root, &target)?;
+    }
+    Ok(Some(outcome))
+}
+
+/// Follow-up task for a failed integration of `task`, blocked by `task`.
+/// Tasks that depend on `task` should also be made to wait for it.
+pub fn conflict_task(id: String, task: &Task, integration: &Integration) -> Option<Task> {
+    let Integration::Conflict {
+        branch,
+        into,
+        files,
+        detail,
+    } = integration
+    else {
+        return None;
+    };
+    let mut description = format!(
+        "Integrating branch `{}` (task '{}') into `{}` failed:\n\n    {}\n\n",
+        branch,
+        task.id,
+        into,
+        detail.replace('\n', "\n    ")
+    );
+    if !files.is_empty() {
+        description.push_str("Conflicting files:\n");
+        for file in files {
+            description.push_str(&format!("- {}\n", file));
+        }
+        description.push('\n');
+    }
+    description.push_str(&format!(
+        "Run `git merge {}` in your working directory, resolve the conflicts and commit. \
+         Once `{}` contains the work, delete the branch with `git branch -D {}`.",
+        branch, into, branch
+    ));
+    let now = chrono::Utc::now().to_rfc3339();
+    Some(Task {
+        id,
+        title: format!("Resolve merge conflict: {}", task.title),
+        description: Some(description),
+        status: Status::Open,
+        after: vec![task.id.clone()],
+        tags: vec!["merge-conflict".to_string()],
+        visibility: task.visibility.clone(),
+        created_at: Some(now.clone()),
+        log: vec![LogEntry {
+            timestamp: now,
+            actor: None,
+            message: format!(
+                "Created after {} could not be integrated into {}",
+                branch, into
+            ),
+        }],
+        ..Task::default()
+    })
+}
+
+fn stage_all(worktree: &Path) -> Result<()> {
+    git(worktree, &["add", "-A"])?;
+    Ok(())
+}
+
+fn commit_pending(root: &Path, worktree: &Path, task: &Task) -> Result<()> {
+    stage_all(worktree)?;
+    if git(worktree, &["diff", "--cached", "--quiet"]).is_ok() {
+        return Ok(());
+    }
+    let message = format!("{} ({})", task.title, task.id);
+    git_as(root, worktree, &["commit", "-m", &message])?;
+    Ok(())
+}
+
+/// The worktree that has `branch` checked out, if any.
+fn checkout_of(root: &Path, branch: &str) -> Result<Option<PathBuf>> {
+    let list = git(root, &["worktree", "list", "--porcelain"])?;
+    let wanted = format!("branch refs/heads/{}", branch);
+    let mut current = None;
+    for line in list.lines() {
+        if let Some(path) = line.strip_prefix("worktree ") {
+            current = Some(PathBuf::from(path));
+        } else if line == wanted {
+            return Ok(current);
+        }
+    }
+    Ok(None)
+}
+
+fn conflicted_files(dir: &Path) -> Vec<String> {
+    git(dir, &["diff", "--name-only", "--diff-filter=U"])
+        .map(|out| out.lines().map(String::from).collect())
+        .unwrap_or_default()
+}
+
+fn branch_exists(root: &Path, branch: &str) -> bool {
+    let refname = format!("refs/heads/{}", branch);
+    git(root, &["rev-parse", "--verify", "--quiet", &refname]).is_ok()
+}
+
+fn remove(root: &Path, worktree: &Path, branch: &str) -> Result<()> {
+    remove_worktree(root, worktree)?;
+    git(root, &["branch", "-D", branch])?;
+    Ok(())
+}
+
+fn remove_worktree(root: &Path, worktree: &Path) -> Result<()> {
+    git(
+        root,
+        &["worktree", "remove", "--force", &worktree.to_string_lossy()],
+    )?;
+    Ok(())
+}
+
+/// Run a git command that creates commits, falling back to a workgraph
+/// identity when the repository has none configured.
+fn git_as(root: &Path, dir: &Path, args: &[&str]) -> Result<String> {
+    let mut cmd = Command::new("git");
+    cmd.args(args).current_dir(dir);
+    if git(root, &["config", "user.email"]).is_err() {
+        cmd.env("GIT_AUTHOR_NAME", "workgraph")
+            .env("GIT_AUTHOR_EMAIL", "workgraph@localhost")
+            .env("GIT_COMMITTER_NAME", "workgraph")
+            .env("GIT_COMMITTER_EMAIL", "workgraph@localhost");
+    }
+    run(cmd, args).map(|out| out.trim().to_string())
+}
+
+fn git(dir: &Path, args: &[&str]) -> Result<String> {
+    git_raw(dir, args).map(|out| out.trim().to_string())
+}
+
+fn git_raw(dir: &Path, args: &[&str]) -> Result<String> {
+    let mut cmd = Command::new("git");
+    cmd.args(args).current_dir(dir);
+    run(cmd, args)
+}
+
+fn run(mut cmd: Command, args: &[&str]) -> Result<String> {
+    let output = cmd.output().context("Failed to run git")?;
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        let stdout = String::from_utf8_lossy(&output.stdout);
+        let detail = if stderr.trim().is_empty() {
+            stdout.trim()
+        } else {
+            stderr.trim()
+        };
+        bail!("git {} failed: {}", args.join(" "), detail);
+    }
+    Ok(String::from_utf8_lossy(&output.stdout).into_owned())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+    use tempfile::TempDir;
+
+    fn repo() -> (TempDir, PathBuf) {
+        let tmp = TempDir::new().unwrap();
+        let root = tmp.path().to_path_buf();
+        git(&root, &["init", "-q", "-b", "main"]).unwrap();
+        fs::write(root.join("shared.txt"), "one\n").unwrap();
+        git(&root, &["add", "-A"]).unwrap();
+        git_as(&root, &root, &["commit", "-q", "-m", "init"]).unwrap();
+        let wg_dir = root.join(".workgraph");
+        fs::create_dir_all(&wg_dir).unwrap();
+        (tmp, wg_dir)
+    }
+
+    fn task(id: &str) -> Task {
+        Task {
+            id: id.to_string(),
+            title: format!("Task {}", id),
+            ..Task::default()
+        }
+    }
+
+    #[test]
+    fn test_isolated_tasks_merge_into_integration_branch() {
+        let (tmp, wg_dir) = repo();
+        let root = tmp.path();
+        let a = prepare(&wg_dir, "a", None).unwrap();
+        let b = prepare(&wg_dir, "b", None).unwrap();
+        assert!(a.join(".workgraph").exists());
+        assert_eq!(prepare(&wg_dir, "a", None).unwrap(), a);
+
+        fs::write(a.join("a.txt"), "from a\n").unwrap();
+        fs::write(b.join("b.txt"), "from b\n").unwrap();
+        assert!(!root.join("a.txt").exists());
+        let patch = diff(&wg_dir, "a", None).unwrap();
+        assert!(patch.contains("+from a"));
+        assert!(!patch.contains("from b"));
+
+        for (id, strategy) in [("a", Strategy::Merge), ("b", Strategy::Rebase)] {
+            let outcome = integrate(&wg_dir, &task(id), None, strategy)
+                .unwrap()
+                .unwrap();
+            assert_eq!(
+                outcome,
+                Integration::Merged {
+                    branch: branch_name(id),
+                    into: "main".to_string()
+                }
+            );
+            assert!(existing(&wg_dir, id).is_none());
+            assert!(!branch_exists(root, &branch_name(id)));
+        }
+        assert!(root.join("a.txt").exists());
+        assert!(root.join("b.txt").exists());
+
+        prepare(&wg_dir, "c", None).unwrap();
+        let outcome = integrate(&wg_dir, &task("c"), None, Strategy::Merge).unwrap();
+        assert!(matches!(outcome, Some(Integration::Unchanged { .. })));
+        assert_eq!(
+            integrate(&wg_dir, &task("c"), None, Strategy::Merge).unwrap(),
+            None
+        );
+    }
+
+    #[test]
+    fn test_conflict_keeps_branch_and_creates_follow_up() {
+        let (tmp, wg_dir) = repo();
+        let root = tmp.path();
+        let a = prepare(&wg_dir, "a", None).unwrap();
+        let b = prepare(&wg_dir, "b", None).unwrap();
+        fs::write(a.join("shared.txt"), "a\n").unwrap();
+        fs::write(b.join("shared.txt"), "b\n").unwrap();
+
+        integrate(&wg_dir, &task("a"), None, Strategy::Merge).unwrap();
+        let outcome = integrate(&wg_dir, &task("b"), None, Strategy::Merge)
+            .unwrap()
+            .unwrap();
+        let Integration::Conflict { files, .. } = &outcome else {
+            panic!("expected a conflict, got {:?}", outcome);
+        };
+        assert_eq!(files, &vec!["shared.txt".to_string()]);
+        assert_eq!(fs::read_to_string(root.join("shared.txt")).unwrap(), "a\n");
+        assert!(branch_exists(root, "wg/b"));
+        assert!(existing(&wg_dir, "b").is_none());
+        assert!(git(root, &["status", "--porcelain"]).unwrap().is_empty());
+
+        let follow_up = conflict_task("merge-b".to_string(), &task("b"), &outcome).unwrap();
+        assert_eq!(follow_up.after, vec!["b".to_string()]);
+        assert!(follow_up.description.unwrap().contains("- shared.txt"));
+
+        // Retrying re-uses the kept branch
+        let again = prepare(&wg_dir, "b", None).unwrap();
+        assert_eq!(fs::read_to_string(again.join("shared.txt")).unwrap(), "b\n");
+    }
+}
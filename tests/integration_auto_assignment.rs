@@ -214,34 +214,11 @@ fn build_assign_subgraph(dir: &Path) {
             id: assign_task_id.clone(),
             title: format!("Assign agent for: {}", ready_task.title),
             description: Some(desc),
-            status: Status::Open,
-            assigned: None,
-            estimate: None,
             before: vec![ready_task.id.clone()],
-            after: vec![],
-            requires: vec![],
             tags: vec!["assignment".to_string(), "agency".to_string()],
-            skills: vec![],
-            inputs: vec![],
-            deliverables: vec![],
-            artifacts: vec![],
-            exec: None,
-            not_before: None,
             created_at: Some(chrono::Utc::now().to_rfc3339()),
-            started_at: None,
-            completed_at: None,
-            log: vec![],
-            retry_count: 0,
-            max_retries: None,
-            failure_reason: None,
-            model: None,
-            verify: None,
-            agent: None,
-            loop_iteration: 0,
-            ready_after: None,
-            paused: false,
             visibility: "internal".to_string(),
-            cycle_config: None,
+            ..Task::default()
         };
 
         mutable_graph.add_node(Node::Task(assign_task));
@@ -1110,34 +1087,11 @@ Begin working on the task now.
             id: "assign-rust-feature".to_string(),
             title: "Assign agent for: Implement a Rust parser".to_string(),
             description: Some(assign_desc),
-            status: Status::Open,
-            assigned: None,
-            estimate: None,
             before: vec!["rust-feature".to_string()],
-            after: vec![],
-            requires: vec![],
             tags: vec!["assignment".to_string(), "agency".to_string()],
-            skills: vec![],
-            inputs: vec![],
-            deliverables: vec![],
-            artifacts: vec![],
-            exec: None,
-            not_before: None,
             created_at: Some(chrono::Utc::now().to_rfc3339()),
-            started_at: None,
-            completed_at: None,
-            log: vec![],
-            retry_count: 0,
-            max_retries: None,
-            failure_reason: None,
-            model: None,
-            verify: None,
-            agent: None,
-            loop_iteration: 0,
-            ready_after: None,
-            paused: false,
             visibility: "internal".to_string(),
-            cycle_config: None,
+            ..Task::default()
         };
 
         // Wire up: assign-rust-feature blocks rust-feature
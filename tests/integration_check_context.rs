@@ -105,6 +105,7 @@ fn check_valid_resource_reference() {
         resource_type: Some("database".to_string()),
         available: Some(1.0),
         unit: None,
+        budget: None,
     };
     let (_dir, path) = setup_graph_with_nodes(vec![Node::Task(task), Node::Resource(resource)]);
 
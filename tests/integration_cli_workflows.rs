@@ -983,6 +983,37 @@ fn test_ready_json_fields() {
     assert_eq!(item["ready"], true);
 }
 
+#[test]
+fn test_ready_lists_dispatch_order() {
+    let tmp = TempDir::new().unwrap();
+    let mut urgent = make_task("urgent", "Urgent", Status::Open);
+    urgent.priority = 5;
+    urgent.after.push("blocker".to_string());
+    let wg_dir = setup_workgraph(
+        &tmp,
+        vec![
+            make_task("alpha", "Alpha", Status::Open),
+            make_task("blocker", "Blocker", Status::Open),
+            urgent,
+        ],
+    );
+
+    let output = wg_ok(&wg_dir, &["ready", "--json"]);
+    let parsed = parse_json(&output, "ready");
+    let ids: Vec<&str> = parsed
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|item| item["id"].as_str().unwrap())
+        .collect();
+    // blocker inherits urgent's priority and goes first
+    assert_eq!(ids, vec!["blocker", "alpha"]);
+    assert_eq!(parsed[0]["priority"], 5);
+
+    let output = wg_ok(&wg_dir, &["ready"]);
+    assert!(output.contains("1. blocker - Blocker [priority 5, inherited]"));
+}
+
 #[test]
 fn test_ready_json_empty() {
     let tmp = TempDir::new().unwrap();
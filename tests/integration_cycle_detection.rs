@@ -13,8 +13,8 @@ use std::path::{Path, PathBuf};
 use std::process::{Command, Stdio};
 use tempfile::TempDir;
 use workgraph::graph::{
-    CycleAnalysis, CycleConfig, LoopGuard, Node, Status, Task, WorkGraph,
-    evaluate_cycle_iteration,
+    CycleAnalysis, CycleConfig, LoopGuard, Node, Status, Task, WorkGraph, evaluate_cycle_iteration,
+    evaluate_cycle_iteration_in,
 };
 use workgraph::parser::{load_graph, save_graph};
 use workgraph::query::{ready_tasks, ready_tasks_cycle_aware};
@@ -871,6 +871,47 @@ fn test_completion_guard_always_allows() {
     assert!(!reactivated.is_empty(), "Always guard should allow iteration");
 }
 
+#[test]
+fn test_completion_expression_guard_explains_decision() {
+    // Guard: keep iterating while the report still contains FAIL.
+    let tmp = TempDir::new().unwrap();
+    let wg_dir = tmp.path().join(".workgraph");
+    fs::create_dir_all(&wg_dir).unwrap();
+    fs::write(tmp.path().join("report.txt"), "all tests pass").unwrap();
+
+    let mut a = make_task_with_status("a", "A", Status::Done);
+    a.after = vec!["b".to_string()];
+    a.cycle_config = Some(CycleConfig {
+        max_iterations: 5,
+        guard: Some(LoopGuard::Expr(
+            "contains(\"report.txt\", \"FAIL\") && iteration < 3".to_string(),
+        )),
+        delay: None,
+    });
+    let mut b = make_task_with_status("b", "B", Status::Done);
+    b.after = vec!["a".to_string()];
+
+    let mut graph = build_graph(vec![a.clone(), b.clone()]);
+    let analysis = graph.compute_cycle_analysis();
+    let decision = evaluate_cycle_iteration_in(&mut graph, "b", &analysis, Some(&wg_dir)).unwrap();
+    assert!(!decision.iterated);
+    assert_eq!(decision.cycle, "a");
+    assert!(
+        decision
+            .reason
+            .contains("contains(\"report.txt\", \"FAIL\") = false"),
+        "got: {}",
+        decision.reason
+    );
+
+    fs::write(tmp.path().join("report.txt"), "1 FAIL").unwrap();
+    let mut graph = build_graph(vec![a, b]);
+    let decision = evaluate_cycle_iteration_in(&mut graph, "b", &analysis, Some(&wg_dir)).unwrap();
+    assert!(decision.iterated);
+    assert_eq!(decision.reactivated.len(), 2);
+    assert_eq!(graph.get_task("a").unwrap().status, Status::Open);
+}
+
 #[test]
 fn test_completion_delay_applied() {
     // Cycle with delay: header should have ready_after set after re-opening
@@ -4,6 +4,7 @@
 //! 1. Auto-pickup via GraphChanged notification: start daemon, add task, verify pickup
 //! 2. Fallback poll pickup: add task without notification, verify poll picks it up
 //! 3. Dead-agent recovery: kill agent, verify daemon detects and re-spawns
+//! 4. HTTP API: drive the daemon over localhost with `--port`
 //!
 //! These tests run serially because each spawns daemon and agent processes
 //! that are sensitive to CPU/scheduling contention under parallel execution.
@@ -221,6 +222,33 @@ where
     false
 }
 
+/// Helper: send one request to the daemon's HTTP API, returning the status
+/// code and JSON body.
+fn http_request(
+    port: u16,
+    method: &str,
+    path: &str,
+    body: Option<serde_json::Value>,
+) -> (u16, serde_json::Value) {
+    use std::io::Read;
+    let body = body.map(|b| b.to_string()).unwrap_or_default();
+    let mut stream = std::net::TcpStream::connect(("127.0.0.1", port)).unwrap();
+    write!(
+        stream,
+        "{} {} HTTP/1.1\r\nHost: localhost\r\nContent-Type: application/json\r\nContent-Length: {}\r\n\r\n{}",
+        method,
+        path,
+        body.len(),
+        body
+    )
+    .unwrap();
+    let mut response = String::new();
+    stream.read_to_string(&mut response).unwrap();
+    let (head, body) = response.split_once("\r\n\r\n").unwrap();
+    let status = head.split_whitespace().nth(1).unwrap().parse().unwrap();
+    (status, serde_json::from_str(body).unwrap())
+}
+
 /// Helper: get the number of coordinator ticks from the coordinator state file.
 fn coordinator_ticks(wg_dir: &Path) -> u64 {
     let state_path = wg_dir.join("service").join("coordinator-state.json");
@@ -653,3 +681,91 @@ executor = "shell"
 
     stop_service(&wg_dir);
 }
+
+/// Test 4: HTTP API.
+///
+/// Start the daemon with `--port` and exercise the REST endpoints against
+/// localhost: status, pause, creating and editing a task, and reading it back.
+#[test]
+#[serial]
+fn test_http_api() {
+    let tmp = tempfile::tempdir().unwrap();
+    let wg_dir = setup_workgraph(tmp.path());
+    let port = std::net::TcpListener::bind("127.0.0.1:0")
+        .unwrap()
+        .local_addr()
+        .unwrap()
+        .port();
+
+    let socket = socket_path_for(tmp.path());
+    let out = wg_ok(
+        &wg_dir,
+        &[
+            "service",
+            "start",
+            "--socket",
+            &socket,
+            "--executor",
+            "shell",
+            "--interval",
+            "300",
+            "--port",
+            &port.to_string(),
+        ],
+    );
+    assert!(out.contains(&format!("127.0.0.1:{}", port)), "{}", out);
+    assert!(
+        wait_for_service_ready(&wg_dir, Duration::from_secs(5)),
+        "Service daemon socket did not become ready"
+    );
+
+    let (status, body) = http_request(port, "GET", "/api/status", None);
+    assert_eq!(status, 200, "{}", body);
+    assert_eq!(body["status"], "running");
+
+    // Pause so the coordinator leaves the new task alone
+    let (status, _) = http_request(port, "POST", "/api/service/pause", None);
+    assert_eq!(status, 200);
+
+    let (status, body) = http_request(
+        port,
+        "POST",
+        "/api/tasks",
+        Some(serde_json::json!({ "id": "from-http", "title": "From HTTP" })),
+    );
+    assert_eq!(status, 201, "{}", body);
+    let (status, _) = http_request(
+        port,
+        "POST",
+        "/api/tasks",
+        Some(serde_json::json!({ "id": "from-http", "title": "Again" })),
+    );
+    assert_eq!(status, 409);
+
+    let (status, body) = http_request(
+        port,
+        "PATCH",
+        "/api/tasks/from-http",
+        Some(serde_json::json!({ "title": "Edited over HTTP", "tags": ["api"] })),
+    );
+    assert_eq!(status, 200, "{}", body);
+    let (status, body) = http_request(
+        port,
+        "PATCH",
+        "/api/tasks/from-http",
+        Some(serde_json::json!({ "title": "Stale", "expected_revision": 0 })),
+    );
+    assert_eq!(status, 409, "{}", body);
+
+    let (status, body) = http_request(port, "GET", "/api/tasks/from-http", None);
+    assert_eq!(status, 200);
+    assert_eq!(body["task"]["title"], "Edited over HTTP");
+    assert_eq!(body["task"]["tags"][0], "api");
+    assert_eq!(task_status(&wg_dir, "from-http"), "open");
+
+    let (status, body) = http_request(port, "GET", "/api/tasks?status=open", None);
+    assert_eq!(status, 200);
+    assert_eq!(body["tasks"].as_array().unwrap().len(), 1);
+
+    stop_service(&wg_dir);
+}
@@ -62,6 +62,7 @@ fn make_agent_entry(id: &str, pid: u32, task_id: &str, status: AgentStatus) -> A
         status,
         output_file: format!("/tmp/{}.log", id),
         model: None,
+        timeout_secs: None,
     }
 }
 